@@ -395,6 +395,7 @@ impl FromJObjectWithEnv<Fragment> for JObject<'_> {
             deletion_file,
             physical_rows: Some(physical_rows),
             row_id_meta,
+            partition_values: None,
         })
     }
 }
@@ -422,6 +423,7 @@ impl FromJObjectWithEnv<DeletionFile> for JObject<'_> {
             id,
             num_deleted_rows,
             file_type,
+            base_id: None,
         })
     }
 }
@@ -472,6 +474,7 @@ impl FromJObjectWithEnv<DataFile> for JObject<'_> {
             file_major_version,
             file_minor_version,
             file_size_bytes,
+            base_id: None,
         })
     }
 }
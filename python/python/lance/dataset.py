@@ -1668,6 +1668,7 @@ class LanceDataset(pa.dataset.Dataset):
             Literal["INVERTED"],
             Literal["FTS"],
             Literal["NGRAM"],
+            Literal["JSON"],
         ],
         name: Optional[str] = None,
         *,
@@ -1742,7 +1743,7 @@ class LanceDataset(pa.dataset.Dataset):
             or string column.
         index_type : str
             The type of the index.  One of ``"BTREE"``, ``"BITMAP"``,
-            ``"LABEL_LIST"``, ``"NGRAM"``, ``"FTS"`` or ``"INVERTED"``.
+            ``"LABEL_LIST"``, ``"NGRAM"``, ``"FTS"``, ``"INVERTED"`` or ``"JSON"``.
         name : str, optional
             The index name. If not provided, it will be generated from the
             column name.
@@ -1761,6 +1762,7 @@ class LanceDataset(pa.dataset.Dataset):
             * "simple": splits tokens on whitespace and punctuation.
             * "whitespace": splits tokens on whitespace.
             * "raw": no tokenization.
+            * "pattern": each match of `pattern` is a token.
         language: str, default "English"
             This is for the ``INVERTED`` index. The language for stemming
             and stop words. This is only used when `stem` or `remove_stop_words` is true
@@ -1780,6 +1782,26 @@ class LanceDataset(pa.dataset.Dataset):
             This is for the ``INVERTED`` index. If True, the index will convert
             non-ascii characters to ascii characters if possible.
             This would remove accents like "é" -> "e".
+        stop_words: Optional[List[str]], default None
+            This is for the ``INVERTED`` index. The stop words to remove instead of
+            the built-in ones of the language. Only used when `remove_stop_words`
+            is True.
+        synonyms: Optional[List[List[str]]], default None
+            This is for the ``INVERTED`` index. Groups of synonyms, each term of a
+            group is expanded to the other terms of the group.
+        synonym_expansion: str, default "query"
+            This is for the ``INVERTED`` index. Expand the synonyms at ``"query"``
+            time, or at ``"index"`` time.
+        pattern: Optional[str], default None
+            This is for the ``INVERTED`` index. The regular expression of the
+            ``"pattern"`` base tokenizer, each match of it is a token.
+        path: str
+            This is for the ``JSON`` index, and required by it. The path to index,
+            e.g. ``"$.user.id"``. Filters like ``payload->>'$.user.id' = 'x'`` can
+            then use the index.
+        target_index_type: str, default "BTREE"
+            This is for the ``JSON`` index. The index used for the values at the
+            path, either ``"BTREE"`` or ``"BITMAP"``.
 
         Examples
         --------
@@ -1827,10 +1849,17 @@ class LanceDataset(pa.dataset.Dataset):
             raise KeyError(f"{column} not found in schema")
 
         index_type = index_type.upper()
-        if index_type not in ["BTREE", "BITMAP", "NGRAM", "LABEL_LIST", "INVERTED"]:
+        if index_type not in [
+            "BTREE",
+            "BITMAP",
+            "NGRAM",
+            "LABEL_LIST",
+            "INVERTED",
+            "JSON",
+        ]:
             raise NotImplementedError(
                 (
-                    'Only "BTREE", "LABEL_LIST", "INVERTED", "NGRAM", '
+                    'Only "BTREE", "LABEL_LIST", "INVERTED", "NGRAM", "JSON", '
                     'or "BITMAP" are supported for '
                     f"scalar columns.  Received {index_type}",
                 )
@@ -1863,6 +1892,15 @@ class LanceDataset(pa.dataset.Dataset):
                 field_type
             ):
                 raise TypeError(f"NGRAM index column {column} must be a string")
+        elif index_type == "JSON":
+            if (
+                not pa.types.is_large_binary(field_type)
+                and not pa.types.is_string(field_type)
+                and not pa.types.is_large_string(field_type)
+            ):
+                raise TypeError(f"JSON index column {column} must be JSON or a string")
+            if "path" not in kwargs:
+                raise ValueError("A JSON index requires a path, e.g. path='$.user_id'")
         elif index_type in ["INVERTED", "FTS"]:
             value_type = field_type
             if pa.types.is_list(field_type) or pa.types.is_large_list(field_type):
@@ -1986,6 +2024,13 @@ class LanceDataset(pa.dataset.Dataset):
         If ``index_type`` is "IVF_*", then the following parameters are required:
             num_partitions
 
+        Optional parameters for `IVF_*`:
+
+            - truncate_dim
+                Build the index on the first ``truncate_dim`` dimensions of the
+                vectors, e.g. for Matryoshka embeddings. The search results are
+                always re-ranked with the full vectors.
+
         If ``index_type`` is with "PQ", then the following parameters are required:
             num_sub_vectors
 
@@ -4135,6 +4180,17 @@ class DatasetOptimizer:
             This is useful when the data distribution has changed significantly,
             and we want to retrain the index to improve the search quality.
             This would be faster than re-create the index from scratch.
+        split_partition_threshold: float, default None
+            Split the IVF partitions larger than this ratio to the average
+            partition size. Only the vectors of the split partitions are
+            reassigned, the other partitions are copied unless they have new
+            data. The partitions are rebalanced only if `num_indices_to_merge`
+            covers all indices.
+        merge_partition_threshold: float, default None
+            Merge the IVF partitions smaller than this ratio to the average
+            partition size into their nearest partitions. Like
+            `split_partition_threshold`, the partitions are rebalanced only if
+            `num_indices_to_merge` covers all indices.
         """
         self._dataset._ds.optimize_indices(**kwargs)
 
@@ -67,6 +67,9 @@ class FragmentMetadata:
         The deletion file, if any.
     row_id_meta : Optional[RowIdMeta]
         The row id metadata, if any.
+    partition_values : Optional[Dict[int, Optional[str]]]
+        The values of the partition columns, keyed by field id, if this fragment
+        belongs to a single partition of a partitioned dataset.
     """
 
     id: int
@@ -74,6 +77,7 @@ class FragmentMetadata:
     physical_rows: int
     deletion_file: Optional[DeletionFile] = None
     row_id_meta: Optional[RowIdMeta] = None
+    partition_values: Optional[Dict[int, Optional[str]]] = None
 
     @property
     def num_deletions(self) -> int:
@@ -110,6 +114,7 @@ class FragmentMetadata:
             row_id_meta=(
                 self.row_id_meta.asdict() if self.row_id_meta is not None else None
             ),
+            partition_values=self.partition_values,
         )
 
     @staticmethod
@@ -124,12 +129,17 @@ class FragmentMetadata:
         if row_id_meta is not None:
             row_id_meta = RowIdMeta(**row_id_meta)
 
+        partition_values = json_data.get("partition_values")
+        if partition_values is not None:
+            partition_values = {int(k): v for k, v in partition_values.items()}
+
         return FragmentMetadata(
             id=json_data["id"],
             files=[DataFile(**f) for f in json_data["files"]],
             physical_rows=json_data["physical_rows"],
             deletion_file=deletion_file,
             row_id_meta=row_id_meta,
+            partition_values=partition_values,
         )
 
 
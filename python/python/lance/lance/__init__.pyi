@@ -488,6 +488,27 @@ class PyFullTextQuery:
         column: str,
     ) -> PyFullTextQuery: ...
     @staticmethod
+    def prefix_query(
+        prefix: str,
+        column: str,
+        boost: float = 1.0,
+        max_expansions: int = 50,
+    ) -> PyFullTextQuery: ...
+    @staticmethod
+    def wildcard_query(
+        pattern: str,
+        column: str,
+        boost: float = 1.0,
+        max_expansions: int = 50,
+    ) -> PyFullTextQuery: ...
+    @staticmethod
+    def regexp_query(
+        pattern: str,
+        column: str,
+        boost: float = 1.0,
+        max_expansions: int = 50,
+    ) -> PyFullTextQuery: ...
+    @staticmethod
     def boost_query(
         positive: PyFullTextQuery,
         negative: PyFullTextQuery,
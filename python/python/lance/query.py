@@ -12,6 +12,9 @@ from .lance import PyFullTextQuery
 class FullTextQueryType(Enum):
     MATCH = "match"
     MATCH_PHRASE = "match_phrase"
+    PREFIX = "prefix"
+    WILDCARD = "wildcard"
+    REGEXP = "regexp"
     BOOST = "boost"
     MULTI_MATCH = "multi_match"
     BOOLEAN = "boolean"
@@ -162,6 +165,106 @@ class PhraseQuery(FullTextQuery):
         return FullTextQueryType.MATCH_PHRASE
 
 
+class PrefixQuery(FullTextQuery):
+    def __init__(
+        self,
+        prefix: str,
+        column: str,
+        *,
+        boost: float = 1.0,
+        max_expansions: int = 50,
+    ):
+        """
+        Prefix query for full-text search.
+
+        Matches the documents containing any token that starts with the prefix.
+
+        Parameters
+        ----------
+        prefix : str
+            The prefix to match the tokens against, it's not tokenized.
+        column : str
+            The name of the column to match against.
+        boost : float, default 1.0
+            The boost factor for the query.
+        max_expansions : int, default 50
+            The maximum number of tokens the prefix can be expanded to.
+        """
+        self._inner = PyFullTextQuery.prefix_query(
+            prefix, column, boost=boost, max_expansions=max_expansions
+        )
+
+    def query_type(self) -> FullTextQueryType:
+        return FullTextQueryType.PREFIX
+
+
+class WildcardQuery(FullTextQuery):
+    def __init__(
+        self,
+        pattern: str,
+        column: str,
+        *,
+        boost: float = 1.0,
+        max_expansions: int = 50,
+    ):
+        """
+        Wildcard query for full-text search.
+
+        Matches the documents containing any token that matches the pattern,
+        `*` matches any sequence of characters and `?` matches a single character.
+
+        Parameters
+        ----------
+        pattern : str
+            The wildcard pattern to match the tokens against.
+        column : str
+            The name of the column to match against.
+        boost : float, default 1.0
+            The boost factor for the query.
+        max_expansions : int, default 50
+            The maximum number of tokens the pattern can be expanded to.
+        """
+        self._inner = PyFullTextQuery.wildcard_query(
+            pattern, column, boost=boost, max_expansions=max_expansions
+        )
+
+    def query_type(self) -> FullTextQueryType:
+        return FullTextQueryType.WILDCARD
+
+
+class RegexpQuery(FullTextQuery):
+    def __init__(
+        self,
+        pattern: str,
+        column: str,
+        *,
+        boost: float = 1.0,
+        max_expansions: int = 50,
+    ):
+        """
+        Regular expression query for full-text search.
+
+        Matches the documents containing any token fully matched by the pattern.
+
+        Parameters
+        ----------
+        pattern : str
+            The regular expression to match the tokens against.
+        column : str
+            The name of the column to match against.
+        boost : float, default 1.0
+            The boost factor for the query.
+        max_expansions : int, default 50
+            The maximum number of tokens the pattern can be expanded to.
+        """
+        self._inner = PyFullTextQuery.regexp_query(
+            pattern, column, boost=boost, max_expansions=max_expansions
+        )
+
+    def query_type(self) -> FullTextQueryType:
+        return FullTextQueryType.REGEXP
+
+
 class BoostQuery(FullTextQuery):
     def __init__(
         self,
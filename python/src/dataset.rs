@@ -58,13 +58,18 @@ use lance::{dataset::builder::DatasetBuilder, index::vector::IndexFileVersion};
 use lance_arrow::as_fixed_size_list_array;
 use lance_index::scalar::inverted::query::{
     BooleanQuery, BoostQuery, FtsQuery, MatchQuery, MultiMatchQuery, Operator, PhraseQuery,
+    PrefixQuery, RegexpQuery, WildcardQuery,
 };
 use lance_index::{
     infer_system_index_type, metrics::NoOpMetricsCollector, scalar::inverted::query::Occur,
+    scalar::inverted::tokenizer::SynonymExpansion,
 };
 use lance_index::{
     optimize::OptimizeOptions,
-    scalar::{FullTextSearchQuery, InvertedIndexParams, ScalarIndexParams, ScalarIndexType},
+    scalar::{
+        json::JsonIndexParams, FullTextSearchQuery, InvertedIndexParams, ScalarIndexParams,
+        ScalarIndexType,
+    },
     vector::{
         hnsw::builder::HnswBuildParams, ivf::IvfBuildParams, pq::PQBuildParams,
         sq::builder::SQBuildParams,
@@ -1379,6 +1384,12 @@ impl Dataset {
             if let Some(retrain) = kwargs.get_item("retrain")? {
                 options.retrain = retrain.extract()?;
             }
+            if let Some(threshold) = kwargs.get_item("split_partition_threshold")? {
+                options.split_partition_threshold = threshold.extract()?;
+            }
+            if let Some(threshold) = kwargs.get_item("merge_partition_threshold")? {
+                options.merge_partition_threshold = threshold.extract()?;
+            }
         }
         RT.block_on(
             None,
@@ -1409,6 +1420,7 @@ impl Dataset {
             "NGRAM" => IndexType::NGram,
             "LABEL_LIST" => IndexType::LabelList,
             "INVERTED" | "FTS" => IndexType::Inverted,
+            "JSON" => IndexType::Json,
             "IVF_FLAT" | "IVF_PQ" | "IVF_SQ" | "IVF_HNSW_FLAT" | "IVF_HNSW_PQ" | "IVF_HNSW_SQ" => {
                 IndexType::Vector
             }
@@ -1432,6 +1444,34 @@ impl Dataset {
             "LABEL_LIST" => Box::new(ScalarIndexParams {
                 force_index_type: Some(ScalarIndexType::LabelList),
             }),
+            "JSON" => {
+                let path = match kwargs
+                    .map(|kwargs| kwargs.get_item("path"))
+                    .transpose()?
+                    .flatten()
+                {
+                    Some(path) => path.extract::<String>()?,
+                    None => return Err(PyValueError::new_err("A JSON index requires a path")),
+                };
+                let mut params = JsonIndexParams::new(path);
+                if let Some(target) = kwargs
+                    .map(|kwargs| kwargs.get_item("target_index_type"))
+                    .transpose()?
+                    .flatten()
+                {
+                    let target = target.extract::<String>()?;
+                    params = params.with_target_index_type(match target.to_uppercase().as_str() {
+                        "BTREE" => ScalarIndexType::BTree,
+                        "BITMAP" => ScalarIndexType::Bitmap,
+                        _ => {
+                            return Err(PyValueError::new_err(format!(
+                                "A JSON index cannot use a {target} index for its values"
+                            )))
+                        }
+                    });
+                }
+                Box::new(params)
+            }
             "INVERTED" | "FTS" => {
                 let mut params = InvertedIndexParams::default();
                 if let Some(kwargs) = kwargs {
@@ -1475,6 +1515,28 @@ impl Dataset {
                     if let Some(prefix_only) = kwargs.get_item("prefix_only")? {
                         params = params.ngram_prefix_only(prefix_only.extract()?);
                     }
+                    if let Some(stop_words) = kwargs.get_item("stop_words")? {
+                        params = params.stop_words(stop_words.extract()?);
+                    }
+                    if let Some(synonyms) = kwargs.get_item("synonyms")? {
+                        params = params.synonyms(synonyms.extract()?);
+                    }
+                    if let Some(synonym_expansion) = kwargs.get_item("synonym_expansion")? {
+                        let synonym_expansion: String = synonym_expansion.extract()?;
+                        params = params.synonym_expansion(match synonym_expansion.as_str() {
+                            "query" => SynonymExpansion::Query,
+                            "index" => SynonymExpansion::Index,
+                            _ => {
+                                return Err(PyValueError::new_err(format!(
+                                    "synonym_expansion must be 'query' or 'index', got {}",
+                                    synonym_expansion
+                                )))
+                            }
+                        });
+                    }
+                    if let Some(pattern) = kwargs.get_item("pattern")? {
+                        params = params.pattern(pattern.extract()?);
+                    }
                 }
                 Box::new(params)
             }
@@ -2331,6 +2393,10 @@ fn prepare_vector_index_params(
             ivf_params.shuffle_partition_concurrency = n.extract()?
         };
 
+        if let Some(n) = kwargs.get_item("truncate_dim")? {
+            ivf_params.truncate_dim = Some(n.extract()?)
+        };
+
         if let Some(c) = kwargs.get_item("ivf_centroids")? {
             let batch = RecordBatch::from_pyarrow_bound(&c)?;
             if "_ivf_centroids" != batch.schema().field(0).name() {
@@ -2674,6 +2740,57 @@ impl PyFullTextQuery {
         })
     }
 
+    #[staticmethod]
+    #[pyo3(signature = (prefix, column, boost=1.0, max_expansions=50))]
+    fn prefix_query(
+        prefix: String,
+        column: String,
+        boost: f32,
+        max_expansions: usize,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            inner: PrefixQuery::new(prefix)
+                .with_column(Some(column))
+                .with_boost(boost)
+                .with_max_expansions(max_expansions)
+                .into(),
+        })
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (pattern, column, boost=1.0, max_expansions=50))]
+    fn wildcard_query(
+        pattern: String,
+        column: String,
+        boost: f32,
+        max_expansions: usize,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            inner: WildcardQuery::new(pattern)
+                .with_column(Some(column))
+                .with_boost(boost)
+                .with_max_expansions(max_expansions)
+                .into(),
+        })
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (pattern, column, boost=1.0, max_expansions=50))]
+    fn regexp_query(
+        pattern: String,
+        column: String,
+        boost: f32,
+        max_expansions: usize,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            inner: RegexpQuery::new(pattern)
+                .with_column(Some(column))
+                .with_boost(boost)
+                .with_max_expansions(max_expansions)
+                .into(),
+        })
+    }
+
     #[staticmethod]
     #[pyo3(signature = (positive, negative,negative_boost=None))]
     fn boost_query(positive: Self, negative: Self, negative_boost: Option<f32>) -> PyResult<Self> {
@@ -449,6 +449,7 @@ impl PyDeletionFile {
             id,
             file_type,
             num_deleted_rows: Some(num_deleted_rows),
+            base_id: None,
         }))
     }
 
@@ -615,6 +616,7 @@ impl FromPyObject<'_> for PyLance<Fragment> {
             deletion_file,
             physical_rows: ob.getattr("physical_rows")?.extract()?,
             row_id_meta,
+            partition_values: ob.getattr("partition_values")?.extract()?,
         }))
     }
 }
@@ -644,6 +646,7 @@ impl<'py> IntoPyObject<'py> for PyLance<&Fragment> {
             self.0.physical_rows,
             deletion_file,
             row_id_meta,
+            self.0.partition_values.clone(),
         ))
     }
 }
@@ -669,6 +672,7 @@ impl FromPyObject<'_> for PyLance<DataFile> {
             file_major_version: ob.getattr("file_major_version")?.extract()?,
             file_minor_version: ob.getattr("file_minor_version")?.extract()?,
             file_size_bytes,
+            base_id: None,
         }))
     }
 }
@@ -151,6 +151,7 @@ impl FromPyObject<'_> for PyLance<Operation> {
                     index_details: None,
                     index_version,
                     created_at,
+                    base_id: None,
                 }];
 
                 let op = Operation::CreateIndex {
@@ -0,0 +1,661 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! JSON support for Apache Arrow.
+//!
+//! JSON values are stored in a `LargeBinary` column tagged with the
+//! [`JSON_EXT_NAME`] extension name.  Each value is kept in a compact binary
+//! encoding (in the spirit of Postgres' JSONB) where object keys are sorted and
+//! every container records the offsets of its children, so a path can be
+//! looked up without parsing the whole document.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use arrow_array::builder::{LargeBinaryBuilder, LargeStringBuilder, StringBuilder};
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, LargeBinaryArray, LargeStringArray, StringArray};
+use arrow_schema::{ArrowError, DataType, Field as ArrowField};
+use serde_json::{Map, Number, Value};
+
+use crate::bfloat16::ARROW_EXT_NAME_KEY;
+use crate::Result;
+
+pub const JSON_EXT_NAME: &str = "lance.json";
+
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_FLOAT: u8 = 4;
+const TAG_STRING: u8 = 5;
+const TAG_ARRAY: u8 = 6;
+const TAG_OBJECT: u8 = 7;
+
+/// Check whether the given field is a JSON field.
+pub fn is_json_field(field: &ArrowField) -> bool {
+    field.data_type() == &DataType::LargeBinary
+        && field
+            .metadata()
+            .get(ARROW_EXT_NAME_KEY)
+            .map(|name| name == JSON_EXT_NAME)
+            .unwrap_or_default()
+}
+
+/// Create a JSON field.
+pub fn json_field(name: &str, nullable: bool) -> ArrowField {
+    ArrowField::new(name, DataType::LargeBinary, nullable).with_metadata(HashMap::from([(
+        ARROW_EXT_NAME_KEY.to_string(),
+        JSON_EXT_NAME.to_string(),
+    )]))
+}
+
+fn corrupt() -> ArrowError {
+    ArrowError::InvalidArgumentError("Invalid binary JSON value".to_string())
+}
+
+/// Encode a JSON value in the binary JSON format.
+pub fn encode_json(value: &Value) -> Vec<u8> {
+    let mut buf = Vec::new();
+    encode_into(value, &mut buf);
+    buf
+}
+
+/// Parse JSON text and encode it in the binary JSON format.
+pub fn encode_json_str(text: &str) -> Result<Vec<u8>> {
+    let value: Value = serde_json::from_str(text)
+        .map_err(|e| ArrowError::ParseError(format!("Invalid JSON '{}': {}", text, e)))?;
+    Ok(encode_json(&value))
+}
+
+fn push_u32(buf: &mut Vec<u8>, value: usize) {
+    buf.extend_from_slice(&(value as u32).to_le_bytes());
+}
+
+fn encode_into(value: &Value, buf: &mut Vec<u8>) {
+    match value {
+        Value::Null => buf.push(TAG_NULL),
+        Value::Bool(false) => buf.push(TAG_FALSE),
+        Value::Bool(true) => buf.push(TAG_TRUE),
+        Value::Number(num) => {
+            if let Some(int) = num.as_i64() {
+                buf.push(TAG_INT);
+                buf.extend_from_slice(&int.to_le_bytes());
+            } else {
+                buf.push(TAG_FLOAT);
+                buf.extend_from_slice(&num.as_f64().unwrap_or(f64::NAN).to_le_bytes());
+            }
+        }
+        Value::String(s) => {
+            buf.push(TAG_STRING);
+            push_u32(buf, s.len());
+            buf.extend_from_slice(s.as_bytes());
+        }
+        Value::Array(items) => {
+            // [tag][count][end offset of each item][items]
+            let mut data = Vec::new();
+            let mut ends = Vec::with_capacity(items.len());
+            for item in items {
+                encode_into(item, &mut data);
+                ends.push(data.len());
+            }
+            buf.push(TAG_ARRAY);
+            push_u32(buf, items.len());
+            ends.into_iter().for_each(|end| push_u32(buf, end));
+            buf.extend_from_slice(&data);
+        }
+        Value::Object(map) => {
+            // [tag][count][end offset of each key][end offset of each value][keys][values]
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+            let mut keys = Vec::new();
+            let mut values = Vec::new();
+            let mut key_ends = Vec::with_capacity(entries.len());
+            let mut value_ends = Vec::with_capacity(entries.len());
+            for (key, value) in entries {
+                keys.extend_from_slice(key.as_bytes());
+                key_ends.push(keys.len());
+                encode_into(value, &mut values);
+                value_ends.push(values.len());
+            }
+            buf.push(TAG_OBJECT);
+            push_u32(buf, map.len());
+            key_ends.into_iter().for_each(|end| push_u32(buf, end));
+            value_ends.into_iter().for_each(|end| push_u32(buf, end));
+            buf.extend_from_slice(&keys);
+            buf.extend_from_slice(&values);
+        }
+    }
+}
+
+/// A borrowed value in the binary JSON format.
+#[derive(Debug, Clone, Copy)]
+pub struct JsonbRef<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> JsonbRef<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn tag(&self) -> Result<u8> {
+        self.data.first().copied().ok_or_else(corrupt)
+    }
+
+    fn u32_at(&self, pos: usize) -> Result<usize> {
+        let bytes = self.data.get(pos..pos + 4).ok_or_else(corrupt)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+    }
+
+    fn bytes_at(&self, pos: usize, len: usize) -> Result<&'a [u8]> {
+        self.data.get(pos..pos + len).ok_or_else(corrupt)
+    }
+
+    // The slice [start, end) of the i-th child of a container whose end offsets
+    // start at `ends_pos` and whose data starts at `data_pos`
+    fn child_range(&self, ends_pos: usize, data_pos: usize, i: usize) -> Result<(usize, usize)> {
+        let start = if i == 0 {
+            0
+        } else {
+            self.u32_at(ends_pos + 4 * (i - 1))?
+        };
+        let end = self.u32_at(ends_pos + 4 * i)?;
+        if start > end {
+            return Err(corrupt());
+        }
+        Ok((data_pos + start, data_pos + end))
+    }
+
+    fn len(&self) -> Result<usize> {
+        self.u32_at(1)
+    }
+
+    // Returns the number of entries of an object, the positions of its key and
+    // value end offsets and the positions of its key and value data
+    fn object_layout(&self) -> Result<(usize, usize, usize, usize, usize)> {
+        let n = self.len()?;
+        let key_ends = 5;
+        let value_ends = key_ends + 4 * n;
+        let keys_pos = value_ends + 4 * n;
+        let keys_len = if n == 0 {
+            0
+        } else {
+            self.u32_at(value_ends - 4)?
+        };
+        Ok((n, key_ends, value_ends, keys_pos, keys_pos + keys_len))
+    }
+
+    /// Returns true if this is a JSON null.
+    pub fn is_null(&self) -> bool {
+        self.data.first() == Some(&TAG_NULL)
+    }
+
+    /// Look up a key of an object.
+    ///
+    /// Returns None if this is not an object or the key is missing.
+    pub fn get(&self, key: &str) -> Result<Option<Self>> {
+        if self.tag()? != TAG_OBJECT {
+            return Ok(None);
+        }
+        let (n, key_ends, value_ends, keys_pos, values_pos) = self.object_layout()?;
+        let (mut lo, mut hi) = (0, n);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let (start, end) = self.child_range(key_ends, keys_pos, mid)?;
+            match self.bytes_at(start, end - start)?.cmp(key.as_bytes()) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => {
+                    let (start, end) = self.child_range(value_ends, values_pos, mid)?;
+                    return Ok(Some(Self::new(self.bytes_at(start, end - start)?)));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Look up an element of an array.
+    ///
+    /// Returns None if this is not an array or the index is out of bounds.
+    pub fn index(&self, i: usize) -> Result<Option<Self>> {
+        if self.tag()? != TAG_ARRAY {
+            return Ok(None);
+        }
+        let n = self.len()?;
+        if i >= n {
+            return Ok(None);
+        }
+        let (start, end) = self.child_range(5, 5 + 4 * n, i)?;
+        Ok(Some(Self::new(self.bytes_at(start, end - start)?)))
+    }
+
+    /// Follow a path from this value.
+    pub fn get_path(&self, path: &JsonPath) -> Result<Option<Self>> {
+        let mut current = *self;
+        for segment in path.segments() {
+            let next = match segment {
+                JsonPathSegment::Key(key) => current.get(key)?,
+                JsonPathSegment::Index(i) => current.index(*i)?,
+            };
+            match next {
+                Some(next) => current = next,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(current))
+    }
+
+    /// Decode into a [`serde_json::Value`].
+    pub fn to_value(&self) -> Result<Value> {
+        Ok(match self.tag()? {
+            TAG_NULL => Value::Null,
+            TAG_FALSE => Value::Bool(false),
+            TAG_TRUE => Value::Bool(true),
+            TAG_INT => {
+                let bytes = self.bytes_at(1, 8)?;
+                Value::Number(i64::from_le_bytes(bytes.try_into().unwrap()).into())
+            }
+            TAG_FLOAT => {
+                let bytes = self.bytes_at(1, 8)?;
+                Number::from_f64(f64::from_le_bytes(bytes.try_into().unwrap()))
+                    .map(Value::Number)
+                    .unwrap_or(Value::Null)
+            }
+            TAG_STRING => Value::String(self.as_str()?.unwrap().to_string()),
+            TAG_ARRAY => {
+                let n = self.len()?;
+                let items = (0..n)
+                    .map(|i| self.index(i)?.ok_or_else(corrupt)?.to_value())
+                    .collect::<Result<Vec<_>>>()?;
+                Value::Array(items)
+            }
+            TAG_OBJECT => {
+                let (n, key_ends, value_ends, keys_pos, values_pos) = self.object_layout()?;
+                let mut map = Map::new();
+                for i in 0..n {
+                    let (start, end) = self.child_range(key_ends, keys_pos, i)?;
+                    let key = std::str::from_utf8(self.bytes_at(start, end - start)?)
+                        .map_err(|_| corrupt())?;
+                    let (start, end) = self.child_range(value_ends, values_pos, i)?;
+                    let value = Self::new(self.bytes_at(start, end - start)?).to_value()?;
+                    map.insert(key.to_string(), value);
+                }
+                Value::Object(map)
+            }
+            _ => return Err(corrupt()),
+        })
+    }
+
+    /// The value of a JSON string, or None if this is not a string.
+    pub fn as_str(&self) -> Result<Option<&'a str>> {
+        if self.tag()? != TAG_STRING {
+            return Ok(None);
+        }
+        let len = self.u32_at(1)?;
+        let bytes = self.bytes_at(5, len)?;
+        std::str::from_utf8(bytes).map(Some).map_err(|_| corrupt())
+    }
+
+    /// Encode as JSON text.
+    pub fn to_json_string(&self) -> Result<String> {
+        Ok(self.to_value()?.to_string())
+    }
+
+    /// Convert to SQL text: strings are returned without quotes, null is
+    /// returned as None and anything else is returned as JSON text.
+    pub fn to_text(&self) -> Result<Option<String>> {
+        if self.is_null() {
+            Ok(None)
+        } else if let Some(s) = self.as_str()? {
+            Ok(Some(s.to_string()))
+        } else {
+            self.to_json_string().map(Some)
+        }
+    }
+}
+
+fn value_to_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn value_get_path<'a>(value: &'a Value, path: &JsonPath) -> Option<&'a Value> {
+    path.segments()
+        .iter()
+        .try_fold(value, |current, segment| match segment {
+            JsonPathSegment::Key(key) => current.get(key.as_str()),
+            JsonPathSegment::Index(i) => current.get(*i),
+        })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum JsonPathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// A path into a JSON document such as `$.user.tags[0]`.
+///
+/// Keys may be written as `.key` or `['key']`, array elements as `[n]`. The
+/// leading `$` is optional, so `user.id` is the same path as `$.user.id`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct JsonPath {
+    segments: Vec<JsonPathSegment>,
+}
+
+impl JsonPath {
+    pub fn new(segments: Vec<JsonPathSegment>) -> Self {
+        Self { segments }
+    }
+
+    pub fn segments(&self) -> &[JsonPathSegment] {
+        &self.segments
+    }
+
+    pub fn push(&mut self, segment: JsonPathSegment) {
+        self.segments.push(segment);
+    }
+
+    pub fn parse(path: &str) -> Result<Self> {
+        let invalid = |msg: &str| {
+            ArrowError::InvalidArgumentError(format!("Invalid JSON path '{}': {}", path, msg))
+        };
+        let mut rest = path.trim();
+        if let Some(stripped) = rest.strip_prefix('$') {
+            rest = stripped;
+        } else if !rest.is_empty() && !rest.starts_with('.') && !rest.starts_with('[') {
+            // A bare key, e.g. "user.id"
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            let mut parsed = Self::parse(&format!("$.{}", &rest[..end]))?;
+            parsed
+                .segments
+                .extend(Self::parse(&format!("${}", &rest[end..]))?.segments);
+            return Ok(parsed);
+        }
+
+        let mut segments = Vec::new();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('.') {
+                let end = after.find(['.', '[']).unwrap_or(after.len());
+                if end == 0 {
+                    return Err(invalid("empty key"));
+                }
+                segments.push(JsonPathSegment::Key(after[..end].to_string()));
+                rest = &after[end..];
+            } else if let Some(after) = rest.strip_prefix('[') {
+                let quote = after
+                    .chars()
+                    .next()
+                    .ok_or_else(|| invalid("unclosed '['"))?;
+                if quote == '\'' || quote == '"' {
+                    let mut key = String::new();
+                    let mut chars = after[1..].char_indices();
+                    let mut close = None;
+                    while let Some((pos, c)) = chars.next() {
+                        match c {
+                            '\\' => {
+                                let (_, escaped) =
+                                    chars.next().ok_or_else(|| invalid("unclosed quote"))?;
+                                key.push(escaped);
+                            }
+                            c if c == quote => {
+                                close = Some(pos + 1);
+                                break;
+                            }
+                            c => key.push(c),
+                        }
+                    }
+                    let close = close.ok_or_else(|| invalid("unclosed quote"))?;
+                    let after = after[close..]
+                        .strip_prefix(quote)
+                        .and_then(|s| s.strip_prefix(']'))
+                        .ok_or_else(|| invalid("expected ']'"))?;
+                    segments.push(JsonPathSegment::Key(key));
+                    rest = after;
+                } else {
+                    let end = after.find(']').ok_or_else(|| invalid("unclosed '['"))?;
+                    let index = after[..end]
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|_| invalid("array index must be a non-negative integer"))?;
+                    segments.push(JsonPathSegment::Index(index));
+                    rest = &after[end + 1..];
+                }
+            } else {
+                return Err(invalid("expected '.' or '['"));
+            }
+        }
+        Ok(Self { segments })
+    }
+}
+
+impl Display for JsonPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "$")?;
+        for segment in &self.segments {
+            match segment {
+                JsonPathSegment::Key(key)
+                    if !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_') =>
+                {
+                    write!(f, ".{}", key)?
+                }
+                JsonPathSegment::Key(key) => write!(
+                    f,
+                    "[\"{}\"]",
+                    key.replace('\\', "\\\\").replace('"', "\\\"")
+                )?,
+                JsonPathSegment::Index(i) => write!(f, "[{}]", i)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Encode an array of JSON text (Utf8 or LargeUtf8) in the binary JSON format.
+pub fn encode_json_array(array: &dyn Array) -> Result<LargeBinaryArray> {
+    let mut builder = LargeBinaryBuilder::with_capacity(array.len(), 0);
+    let mut append = |text: Option<&str>| -> Result<()> {
+        match text {
+            Some(text) => builder.append_value(encode_json_str(text)?),
+            None => builder.append_null(),
+        }
+        Ok(())
+    };
+    match array.data_type() {
+        DataType::Utf8 => array.as_string::<i32>().iter().try_for_each(&mut append)?,
+        DataType::LargeUtf8 => array.as_string::<i64>().iter().try_for_each(&mut append)?,
+        DataType::LargeBinary => return Ok(array.as_binary::<i64>().clone()),
+        other => {
+            return Err(ArrowError::InvalidArgumentError(format!(
+                "Cannot convert {} to JSON",
+                other
+            )))
+        }
+    }
+    Ok(builder.finish())
+}
+
+/// Decode an array in the binary JSON format to JSON text.
+pub fn decode_json_array(array: &LargeBinaryArray) -> Result<LargeStringArray> {
+    let mut builder = LargeStringBuilder::with_capacity(array.len(), 0);
+    for value in array.iter() {
+        match value {
+            Some(value) => builder.append_value(JsonbRef::new(value).to_json_string()?),
+            None => builder.append_null(),
+        }
+    }
+    Ok(builder.finish())
+}
+
+/// Extract the value at `path` from each JSON value of `array`.
+///
+/// `array` may either be in the binary JSON format (LargeBinary / Binary) or
+/// be JSON text (Utf8 / LargeUtf8).  If `as_text` is true then the extracted
+/// values are converted with [`JsonbRef::to_text`], otherwise they are returned
+/// as JSON text.  Missing values are null.
+pub fn json_extract(array: &dyn Array, path: &JsonPath, as_text: bool) -> Result<StringArray> {
+    let mut builder = StringBuilder::with_capacity(array.len(), 0);
+    let mut append_binary = |value: Option<&[u8]>| -> Result<()> {
+        let extracted = match value {
+            Some(value) => JsonbRef::new(value).get_path(path)?,
+            None => None,
+        };
+        let text = match extracted {
+            Some(v) if as_text => v.to_text()?,
+            Some(v) => Some(v.to_json_string()?),
+            None => None,
+        };
+        builder.append_option(text);
+        Ok(())
+    };
+    match array.data_type() {
+        DataType::LargeBinary => array
+            .as_binary::<i64>()
+            .iter()
+            .try_for_each(&mut append_binary)?,
+        DataType::Binary => array
+            .as_binary::<i32>()
+            .iter()
+            .try_for_each(&mut append_binary)?,
+        DataType::Utf8 | DataType::LargeUtf8 => {
+            let mut append_text = |text: Option<&str>| -> Result<()> {
+                let value = text
+                    .map(serde_json::from_str::<Value>)
+                    .transpose()
+                    .map_err(|e| ArrowError::ParseError(format!("Invalid JSON: {}", e)))?;
+                let extracted = value.as_ref().and_then(|v| value_get_path(v, path));
+                let text = match extracted {
+                    Some(v) if as_text => value_to_text(v),
+                    Some(v) => Some(v.to_string()),
+                    None => None,
+                };
+                builder.append_option(text);
+                Ok(())
+            };
+            if array.data_type() == &DataType::Utf8 {
+                array
+                    .as_string::<i32>()
+                    .iter()
+                    .try_for_each(&mut append_text)?
+            } else {
+                array
+                    .as_string::<i64>()
+                    .iter()
+                    .try_for_each(&mut append_text)?
+            }
+        }
+        DataType::Null => (0..array.len()).for_each(|_| builder.append_null()),
+        other => {
+            return Err(ArrowError::InvalidArgumentError(format!(
+                "Cannot extract JSON path from {}",
+                other
+            )))
+        }
+    }
+    Ok(builder.finish())
+}
+
+/// Convenience wrapper to build a JSON array from JSON text.
+pub fn json_array_from_strs<'a>(
+    values: impl IntoIterator<Item = Option<&'a str>>,
+) -> Result<Arc<LargeBinaryArray>> {
+    let text = values.into_iter().collect::<StringArray>();
+    encode_json_array(&text).map(Arc::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_roundtrip() {
+        let value = json!({
+            "user_id": "u1",
+            "n": 42,
+            "x": -1.5,
+            "ok": true,
+            "none": null,
+            "tags": ["a", {"b": [1, 2]}],
+            "": {},
+            "zz": []
+        });
+        let encoded = encode_json(&value);
+        let jsonb = JsonbRef::new(&encoded);
+        assert_eq!(jsonb.to_value().unwrap(), value);
+
+        let get = |path: &str| {
+            jsonb
+                .get_path(&JsonPath::parse(path).unwrap())
+                .unwrap()
+                .map(|v| v.to_value().unwrap())
+        };
+        assert_eq!(get("$.user_id"), Some(json!("u1")));
+        assert_eq!(get("n"), Some(json!(42)));
+        assert_eq!(get("$.tags[1].b[1]"), Some(json!(2)));
+        assert_eq!(get("$['tags'][0]"), Some(json!("a")));
+        assert_eq!(get("$.none"), Some(Value::Null));
+        assert_eq!(get("$.missing"), None);
+        assert_eq!(get("$.tags[5]"), None);
+        assert_eq!(get("$.n.x"), None);
+        assert_eq!(get("$"), Some(value));
+
+        assert!(JsonbRef::new(&[TAG_OBJECT, 1]).to_value().is_err());
+    }
+
+    #[test]
+    fn test_json_path() {
+        for (input, expected) in [
+            ("$.a.b[2]", "$.a.b[2]"),
+            ("a.b", "$.a.b"),
+            ("a[0]", "$.a[0]"),
+            ("$['a b'][\"c\"]", "$[\"a b\"].c"),
+            ("$", "$"),
+        ] {
+            assert_eq!(JsonPath::parse(input).unwrap().to_string(), expected);
+        }
+        let path = JsonPath::parse("$[\"a\\\"b\"]").unwrap();
+        assert_eq!(path.segments(), &[JsonPathSegment::Key("a\"b".to_string())]);
+        assert_eq!(JsonPath::parse(&path.to_string()).unwrap(), path);
+
+        for invalid in ["$.", "$[", "$[-1]", "$['a'", "$a"] {
+            assert!(JsonPath::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_json_extract() {
+        let text = StringArray::from(vec![
+            Some(r#"{"user": {"id": "x", "age": 3}}"#),
+            Some(r#"{"user": {"id": null}}"#),
+            None,
+            Some(r#"[1, 2]"#),
+        ]);
+        let binary = encode_json_array(&text).unwrap();
+        let path = JsonPath::parse("$.user.id").unwrap();
+        for array in [&text as &dyn Array, &binary as &dyn Array] {
+            let extracted = json_extract(array, &path, true).unwrap();
+            assert_eq!(
+                extracted.iter().collect::<Vec<_>>(),
+                vec![Some("x"), None, None, None]
+            );
+            let extracted = json_extract(array, &path, false).unwrap();
+            assert_eq!(
+                extracted.iter().collect::<Vec<_>>(),
+                vec![Some("\"x\""), Some("null"), None, None]
+            );
+            let user = json_extract(array, &JsonPath::parse("user").unwrap(), true).unwrap();
+            assert_eq!(user.value(0), r#"{"age":3,"id":"x"}"#);
+        }
+
+        let decoded = decode_json_array(&binary).unwrap();
+        assert_eq!(decoded.value(3), "[1,2]");
+        assert!(decoded.is_null(2));
+        assert!(encode_json_array(&StringArray::from(vec!["{"])).is_err());
+    }
+}
@@ -53,6 +53,36 @@ pub static BLOB_DESC_FIELD: LazyLock<ArrowField> = LazyLock::new(|| {
 pub static BLOB_DESC_LANCE_FIELD: LazyLock<Field> =
     LazyLock::new(|| Field::try_from(&*BLOB_DESC_FIELD).unwrap());
 
+/// Marks a struct column of [`EXTERNAL_BLOB_FIELDS`] as references to blobs stored
+/// outside of the dataset
+pub const EXTERNAL_BLOB_META_KEY: &str = "lance-encoding:blob-external";
+
+/// The reference to an external blob, the `uri` of the object,
+/// the optional byte range (`offset` and `length`) of the blob in the object,
+/// and the optional `etag` the object is expected to have
+pub static EXTERNAL_BLOB_FIELDS: LazyLock<Fields> = LazyLock::new(|| {
+    Fields::from(vec![
+        ArrowField::new("uri", DataType::Utf8, false),
+        ArrowField::new("offset", DataType::UInt64, true),
+        ArrowField::new("length", DataType::UInt64, true),
+        ArrowField::new("etag", DataType::Utf8, true),
+    ])
+});
+
+/// Creates a field of references to external blobs
+pub fn external_blob_field(name: &str, nullable: bool) -> ArrowField {
+    ArrowField::new(
+        name,
+        DataType::Struct(EXTERNAL_BLOB_FIELDS.clone()),
+        nullable,
+    )
+    .with_metadata(
+        [(EXTERNAL_BLOB_META_KEY.to_owned(), "true".to_owned())]
+            .into_iter()
+            .collect(),
+    )
+}
+
 /// LogicalType is a string presentation of arrow type.
 /// to be serialized into protobuf.
 #[derive(Debug, Clone, PartialEq, DeepSizeOf)]
@@ -28,7 +28,9 @@ use super::{
     Dictionary, LogicalType, Projection,
 };
 use crate::{
-    datatypes::{BLOB_DESC_LANCE_FIELD, BLOB_META_KEY},
+    datatypes::{
+        BLOB_DESC_LANCE_FIELD, BLOB_META_KEY, EXTERNAL_BLOB_FIELDS, EXTERNAL_BLOB_META_KEY,
+    },
     Error, Result,
 };
 
@@ -503,6 +505,13 @@ impl Field {
         self.metadata.contains_key(BLOB_META_KEY)
     }
 
+    /// Check if the field holds references to blobs stored outside of the dataset,
+    /// see [`crate::datatypes::external_blob_field`]
+    pub fn is_external_blob(&self) -> bool {
+        self.metadata.contains_key(EXTERNAL_BLOB_META_KEY)
+            && self.data_type() == DataType::Struct(EXTERNAL_BLOB_FIELDS.clone())
+    }
+
     /// If the field is a blob, return a new field with the same name and id
     /// but with the data type set to a struct of the blob description fields.
     ///
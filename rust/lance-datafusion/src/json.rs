@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! JSON functions
+//!
+//! * `json_extract(json, path)` returns the value at `path` as text.  Strings
+//!   are returned without quotes and JSON null / missing values are null.  This
+//!   is what the `json->>'key'` operator is planned as.
+//! * `json_query(json, path)` returns the value at `path` as JSON text.  This is
+//!   what the `json->'key'` operator is planned as.
+//!
+//! Both accept JSON columns (see [`lance_arrow::json`]) as well as columns of
+//! JSON text.
+
+use std::sync::{Arc, LazyLock};
+
+use arrow_schema::DataType;
+use datafusion::error::{DataFusionError, Result as DFResult};
+use datafusion::logical_expr::expr::ScalarFunction;
+use datafusion::logical_expr::{
+    ColumnarValue, ScalarFunctionArgs, ScalarUDF, ScalarUDFImpl, Signature, Volatility,
+};
+use datafusion::prelude::Expr;
+use datafusion::scalar::ScalarValue;
+use lance_arrow::json::{json_extract, JsonPath};
+
+pub const JSON_EXTRACT: &str = "json_extract";
+pub const JSON_QUERY: &str = "json_query";
+
+pub static JSON_EXTRACT_UDF: LazyLock<Arc<ScalarUDF>> =
+    LazyLock::new(|| Arc::new(ScalarUDF::new_from_impl(JsonExtractFunc::new(true))));
+pub static JSON_QUERY_UDF: LazyLock<Arc<ScalarUDF>> =
+    LazyLock::new(|| Arc::new(ScalarUDF::new_from_impl(JsonExtractFunc::new(false))));
+
+#[derive(Debug, Clone)]
+struct JsonExtractFunc {
+    as_text: bool,
+    signature: Signature,
+}
+
+impl JsonExtractFunc {
+    fn new(as_text: bool) -> Self {
+        Self {
+            as_text,
+            signature: Signature::any(2, Volatility::Immutable),
+        }
+    }
+}
+
+impl ScalarUDFImpl for JsonExtractFunc {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        if self.as_text {
+            JSON_EXTRACT
+        } else {
+            JSON_QUERY
+        }
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> DFResult<DataType> {
+        match &arg_types[0] {
+            DataType::LargeBinary
+            | DataType::Binary
+            | DataType::Utf8
+            | DataType::LargeUtf8
+            | DataType::Null => Ok(DataType::Utf8),
+            other => Err(DataFusionError::Plan(format!(
+                "{} expects a JSON or string argument, got {}",
+                self.name(),
+                other
+            ))),
+        }
+    }
+
+    fn invoke_with_args(&self, func_args: ScalarFunctionArgs) -> DFResult<ColumnarValue> {
+        let path = match &func_args.args[1] {
+            ColumnarValue::Scalar(
+                ScalarValue::Utf8(Some(path))
+                | ScalarValue::LargeUtf8(Some(path))
+                | ScalarValue::Utf8View(Some(path)),
+            ) => JsonPath::parse(path)?,
+            _ => {
+                return Err(DataFusionError::Execution(format!(
+                    "{} expects the path to be a string literal",
+                    self.name()
+                )))
+            }
+        };
+        match &func_args.args[0] {
+            ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::new(json_extract(
+                array.as_ref(),
+                &path,
+                self.as_text,
+            )?))),
+            ColumnarValue::Scalar(scalar) => {
+                let array = scalar.to_array()?;
+                let extracted = json_extract(array.as_ref(), &path, self.as_text)?;
+                Ok(ColumnarValue::Scalar(ScalarValue::try_from_array(
+                    &extracted, 0,
+                )?))
+            }
+        }
+    }
+}
+
+/// Create the expression `json_extract(expr, path)`
+pub fn json_extract_expr(expr: Expr, path: &JsonPath) -> Expr {
+    Expr::ScalarFunction(ScalarFunction {
+        func: JSON_EXTRACT_UDF.clone(),
+        args: vec![
+            expr,
+            Expr::Literal(ScalarValue::Utf8(Some(path.to_string())), None),
+        ],
+    })
+}
+
+/// If `expr` is `json_extract(column, path)` then return the column and path
+pub fn as_json_extract(expr: &Expr) -> Option<(&str, JsonPath)> {
+    let Expr::ScalarFunction(func) = expr else {
+        return None;
+    };
+    if func.name() != JSON_EXTRACT || func.args.len() != 2 {
+        return None;
+    }
+    match (&func.args[0], &func.args[1]) {
+        (Expr::Column(col), Expr::Literal(ScalarValue::Utf8(Some(path)), _)) => {
+            Some((col.name.as_str(), JsonPath::parse(path).ok()?))
+        }
+        _ => None,
+    }
+}
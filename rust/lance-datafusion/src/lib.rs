@@ -6,6 +6,7 @@ pub mod dataframe;
 pub mod datagen;
 pub mod exec;
 pub mod expr;
+pub mod json;
 pub mod logical_expr;
 pub mod planner;
 pub mod projection;
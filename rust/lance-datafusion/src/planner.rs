@@ -8,6 +8,7 @@ use std::collections::{BTreeSet, VecDeque};
 use std::sync::Arc;
 
 use crate::expr::safe_coerce_scalar;
+use crate::json::{JSON_EXTRACT, JSON_EXTRACT_UDF, JSON_QUERY, JSON_QUERY_UDF};
 use crate::logical_expr::{coerce_filter_type_to_boolean, get_as_string_scalar_opt, resolve_expr};
 use crate::sql::{parse_sql_expr, parse_sql_filter};
 use arrow::compute::CastOptions;
@@ -46,6 +47,7 @@ use datafusion::{
 };
 use datafusion_functions::core::getfield::GetFieldFunc;
 use lance_arrow::cast::cast_with_options;
+use lance_arrow::json::{JsonPath, JsonPathSegment};
 use lance_core::datatypes::Schema;
 use lance_core::error::LanceOptionExt;
 use snafu::location;
@@ -205,6 +207,8 @@ impl ContextProvider for LanceContextProvider {
             // TODO: cast should go thru CAST syntax instead of UDF
             // Going thru UDF makes it hard for the optimizer to find no-ops
             "_cast_list_f16" => Some(Arc::new(ScalarUDF::new_from_impl(CastListF16Udf::new()))),
+            JSON_EXTRACT => Some(JSON_EXTRACT_UDF.clone()),
+            JSON_QUERY => Some(JSON_QUERY_UDF.clone()),
             _ => self.state.scalar_functions().get(f).cloned(),
         }
     }
@@ -315,6 +319,9 @@ impl Planner {
     }
 
     fn binary_expr(&self, left: &SQLExpr, op: &BinaryOperator, right: &SQLExpr) -> Result<Expr> {
+        if matches!(op, BinaryOperator::Arrow | BinaryOperator::LongArrow) {
+            return self.json_access(left, op, right);
+        }
         Ok(Expr::BinaryExpr(BinaryExpr::new(
             Box::new(self.parse_sql_expr(left)?),
             self.binary_op(op)?,
@@ -322,6 +329,80 @@ impl Planner {
         )))
     }
 
+    // `json->'key'` and `json->>'key'`.  Chains such as `json->'a'->>'b'` are
+    // folded into a single path lookup.
+    fn json_access(&self, left: &SQLExpr, op: &BinaryOperator, right: &SQLExpr) -> Result<Expr> {
+        let mut path = JsonPath::default();
+        let base = self.json_path_base(left, &mut path)?;
+        self.push_json_path(right, &mut path)?;
+        let func = if matches!(op, BinaryOperator::LongArrow) {
+            JSON_EXTRACT_UDF.clone()
+        } else {
+            JSON_QUERY_UDF.clone()
+        };
+        Ok(Expr::ScalarFunction(ScalarFunction {
+            func,
+            args: vec![
+                base,
+                Expr::Literal(ScalarValue::Utf8(Some(path.to_string())), None),
+            ],
+        }))
+    }
+
+    fn json_path_base(&self, expr: &SQLExpr, path: &mut JsonPath) -> Result<Expr> {
+        match expr {
+            SQLExpr::BinaryOp {
+                left,
+                op: BinaryOperator::Arrow,
+                right,
+            } => {
+                let base = self.json_path_base(left, path)?;
+                self.push_json_path(right, path)?;
+                Ok(base)
+            }
+            _ => self.parse_sql_expr(expr),
+        }
+    }
+
+    fn push_json_path(&self, expr: &SQLExpr, path: &mut JsonPath) -> Result<()> {
+        match expr {
+            SQLExpr::Value(ValueWithSpan {
+                value: Value::SingleQuotedString(key),
+                ..
+            }) if key.starts_with('$') => {
+                let parsed = JsonPath::parse(key)
+                    .map_err(|e| Error::invalid_input(e.to_string(), location!()))?;
+                parsed
+                    .segments()
+                    .iter()
+                    .for_each(|segment| path.push(segment.clone()));
+            }
+            SQLExpr::Value(ValueWithSpan {
+                value: Value::SingleQuotedString(key),
+                ..
+            }) => path.push(JsonPathSegment::Key(key.clone())),
+            SQLExpr::Value(ValueWithSpan {
+                value: Value::Number(n, _),
+                ..
+            }) => {
+                let index = n.parse::<usize>().map_err(|_| {
+                    Error::invalid_input(
+                        format!("JSON array index must be a non-negative integer, got {n}"),
+                        location!(),
+                    )
+                })?;
+                path.push(JsonPathSegment::Index(index));
+            }
+            _ => {
+                return Err(Error::invalid_input(
+                    format!("JSON key must be a string or integer literal, got {expr}"),
+                    location!(),
+                ))
+            }
+        }
+        Ok(())
+    }
+
     fn unary_expr(&self, op: &UnaryOperator, expr: &SQLExpr) -> Result<Expr> {
         Ok(match op {
             UnaryOperator::Not | UnaryOperator::PGBitwiseNot => {
@@ -727,7 +808,7 @@ impl Planner {
                 data_type: self.parse_type(data_type)?,
             })),
             SQLExpr::JsonAccess { .. } => Err(Error::invalid_input(
-                "JSON access is not supported",
+                "JSON access with ':' is not supported, use ->> or json_extract instead",
                 location!(),
             )),
             SQLExpr::CompoundFieldAccess { root, access_chain } => {
@@ -1652,6 +1733,74 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sql_json_access() {
+        let json = lance_arrow::json::json_array_from_strs([
+            Some(r#"{"user": {"id": "x", "tags": ["a", "b"]}, "n": 1}"#),
+            Some(r#"{"user": {"id": "y"}, "n": 2}"#),
+            None,
+        ])
+        .unwrap();
+        let text = Arc::new(StringArray::from(vec![
+            Some(r#"{"user": {"id": "x"}}"#),
+            Some("{}"),
+            Some("[]"),
+        ]));
+        let schema = Arc::new(Schema::new(vec![
+            lance_arrow::json::json_field("payload", true),
+            Field::new("text", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(schema.clone(), vec![json, text]).unwrap();
+        let planner = Planner::new(schema);
+
+        let expr = planner
+            .parse_filter("payload->'user'->>'id' = 'x'")
+            .unwrap();
+        assert_eq!(
+            expr,
+            crate::json::json_extract_expr(col("payload"), &JsonPath::parse("user.id").unwrap())
+                .eq(lit("x"))
+        );
+
+        let cases = [
+            ("payload->'user'->>'id' = 'x'", vec![true, false, false]),
+            (
+                "json_extract(payload, '$.user.id') = 'y'",
+                vec![false, true, false],
+            ),
+            ("payload->>'$.user.tags[1]' = 'b'", vec![true, false, false]),
+            (
+                "payload->'user'->'tags'->>0 = 'a'",
+                vec![true, false, false],
+            ),
+            ("payload->>'n' = '2'", vec![false, true, false]),
+            (
+                "payload->'user'->'tags' = '[\"a\",\"b\"]'",
+                vec![true, false, false],
+            ),
+            ("text->>'$.user.id' = 'x'", vec![true, false, false]),
+            ("json_query(text, 'user') IS NULL", vec![false, true, true]),
+        ];
+        for (filter, expected) in cases {
+            let expr = planner.parse_filter(filter).unwrap();
+            let expr = planner.optimize_expr(expr).unwrap();
+            let physical_expr = planner.create_physical_expr(&expr).unwrap();
+            let result = physical_expr.evaluate(&batch).unwrap();
+            let result = result.into_array(batch.num_rows()).unwrap();
+            let result = result
+                .as_any()
+                .downcast_ref::<BooleanArray>()
+                .unwrap()
+                .iter()
+                .map(|v| v.unwrap_or(false))
+                .collect::<Vec<_>>();
+            assert_eq!(result, expected, "unexpected result for {}", filter);
+        }
+
+        assert!(planner.parse_filter("payload->>'$.' = 'x'").is_err());
+        assert!(planner.parse_filter("payload->>text = 'x'").is_err());
+    }
+
     #[test]
     fn test_columns_in_expr() {
         let expr = col("s0").gt(lit("value")).and(
@@ -290,7 +290,40 @@ impl BlobFieldEncoder {
         }
     }
 
+    // The values were already written to the file (see `FileWriter::start_blob` in lance-file)
+    // and the array holds their descriptions
+    fn written_descriptions(descriptions: &StructArray) -> ArrayRef {
+        if descriptions.null_count() == 0 {
+            return Arc::new(descriptions.clone());
+        }
+        let positions = descriptions.column(0).as_primitive::<UInt64Type>();
+        let sizes = descriptions.column(1).as_primitive::<UInt64Type>();
+        let (positions, sizes): (Vec<u64>, Vec<u64>) = (0..descriptions.len())
+            .map(|i| {
+                if descriptions.is_null(i) {
+                    // Null values are always (1, 0)
+                    (1, 0)
+                } else {
+                    (positions.value(i), sizes.value(i))
+                }
+            })
+            .unzip();
+        Arc::new(StructArray::new(
+            BLOB_DESC_FIELDS.clone(),
+            vec![
+                Arc::new(UInt64Array::from(positions)),
+                Arc::new(UInt64Array::from(sizes)),
+            ],
+            None,
+        ))
+    }
+
     fn write_bins(array: ArrayRef, external_buffers: &mut OutOfLineBuffers) -> Result<ArrayRef> {
+        if let Some(descriptions) = array.as_struct_opt() {
+            if descriptions.fields() == &*BLOB_DESC_FIELDS {
+                return Ok(Self::written_descriptions(descriptions));
+            }
+        }
         let binarray = array
             .as_binary_opt::<i64>()
             .ok_or_else(|| Error::InvalidInput {
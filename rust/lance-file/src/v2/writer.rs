@@ -3,8 +3,10 @@
 
 use core::panic;
 use std::collections::HashMap;
+use std::pin::Pin;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use std::task::{ready, Context, Poll};
 
 use arrow_array::RecordBatch;
 
@@ -30,7 +32,7 @@ use object_store::path::Path;
 use prost::Message;
 use prost_types::Any;
 use snafu::location;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 use tracing::instrument;
 
 use crate::datatypes::FieldsWithMeta;
@@ -491,6 +493,21 @@ impl FileWriter {
         Ok(self.global_buffers.len() as u32)
     }
 
+    /// Starts writing a value of a blob column directly to the file
+    ///
+    /// The bytes written to the returned [`BlobValueWriter`] are streamed to the file
+    /// so the value never needs to be held in memory.  The description returned by
+    /// [`BlobValueWriter::finish`] can then be written in place of the value, as a
+    /// struct array of [`lance_core::datatypes::BLOB_DESC_FIELDS`], in a later batch.
+    pub async fn start_blob(&mut self) -> Result<BlobValueWriter<'_>> {
+        let position = self.writer.tell().await? as u64;
+        Ok(BlobValueWriter {
+            writer: &mut self.writer,
+            position,
+            size: 0,
+        })
+    }
+
     async fn finish_writers(&mut self) -> Result<()> {
         let mut col_idx = 0;
         for mut writer in std::mem::take(&mut self.column_writers) {
@@ -626,6 +643,48 @@ impl FileWriter {
     }
 }
 
+/// Streams a single blob value into a file, see [`FileWriter::start_blob`]
+pub struct BlobValueWriter<'a> {
+    writer: &'a mut ObjectWriter,
+    position: u64,
+    size: u64,
+}
+
+impl BlobValueWriter<'_> {
+    /// Finishes the value and returns its (position, size) description
+    pub async fn finish(self) -> Result<(u64, u64)> {
+        let pad_bytes = pad_bytes::<PAGE_BUFFER_ALIGNMENT>(self.size as usize);
+        self.writer.write_all(&PAD_BUFFER[..pad_bytes]).await?;
+        if self.size == 0 {
+            // Empty values are always (0,0)
+            Ok((0, 0))
+        } else {
+            Ok((self.position, self.size))
+        }
+    }
+}
+
+impl AsyncWrite for BlobValueWriter<'_> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let written = ready!(Pin::new(&mut *self.writer).poll_write(cx, buf))?;
+        self.size += written as u64;
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.writer).poll_flush(cx)
+    }
+
+    // The file stays open for the rest of the data, shutting down only flushes the value
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.writer).poll_flush(cx)
+    }
+}
+
 /// Utility trait for converting EncodedBatch to Bytes using the
 /// lance file format
 pub trait EncodedBatchWriteExt {
@@ -100,7 +100,7 @@ fn bench_inverted(c: &mut Criterion) {
             black_box(
                 invert_index
                     .bm25_search(
-                        vec![sample_words[word_idx].clone()].into(),
+                        Arc::new(vec![sample_words[word_idx].clone()].into()),
                         params.clone().into(),
                         Operator::Or,
                         no_filter.clone(),
@@ -71,6 +71,10 @@ pub struct FragReuseGroup {
     pub changed_row_addrs: Vec<u8>,
     pub old_frags: Vec<FragDigest>,
     pub new_frags: Vec<FragDigest>,
+    /// The position each changed row address was written to in the new
+    /// fragments, if the rewrite reordered rows. Empty otherwise.
+    #[serde(default)]
+    pub new_row_positions: Vec<u32>,
 }
 
 impl From<&FragReuseGroup> for pb::fragment_reuse_index_details::Group {
@@ -79,6 +83,7 @@ impl From<&FragReuseGroup> for pb::fragment_reuse_index_details::Group {
             changed_row_addrs: group.changed_row_addrs.clone(),
             old_fragments: group.old_frags.iter().map(|f| f.into()).collect(),
             new_fragments: group.new_frags.iter().map(|f| f.into()).collect(),
+            new_row_positions: group.new_row_positions.clone(),
         }
     }
 }
@@ -99,6 +104,7 @@ impl TryFrom<pb::fragment_reuse_index_details::Group> for FragReuseGroup {
                 .into_iter()
                 .map(FragDigest::try_from)
                 .collect::<Result<_>>()?,
+            new_row_positions: group.new_row_positions,
         })
     }
 }
@@ -418,6 +424,7 @@ pub mod tests {
                         num_deleted_rows: 0,
                     },
                 ],
+                new_row_positions: vec![],
             }],
         };
 
@@ -442,6 +449,7 @@ pub mod tests {
                         num_deleted_rows: 0,
                     },
                 ],
+                new_row_positions: vec![1, 0],
             }],
         };
 
@@ -480,6 +488,10 @@ pub mod tests {
                 }
             ]
         );
+        assert_eq!(
+            roundtrip_details.versions[0].groups[0].new_row_positions,
+            vec![1, 0]
+        );
         assert_eq!(
             roundtrip_details.versions[0].groups[0].old_frags,
             vec![FragDigest {
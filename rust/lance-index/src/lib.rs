@@ -96,6 +96,8 @@ pub enum IndexType {
 
     MemWal = 7,
 
+    Json = 8, // Json
+
     // 100+ and up for vector index.
     /// Flat vector index.
     Vector = 100, // Legacy vector index, alias to IvfPq
@@ -105,6 +107,7 @@ pub enum IndexType {
     IvfHnswSq = 104,
     IvfHnswPq = 105,
     IvfHnswFlat = 106,
+    DiskAnn = 107,
 }
 
 impl std::fmt::Display for IndexType {
@@ -117,12 +120,14 @@ impl std::fmt::Display for IndexType {
             Self::NGram => write!(f, "NGram"),
             Self::FragmentReuse => write!(f, "FragmentReuse"),
             Self::MemWal => write!(f, "MemWal"),
+            Self::Json => write!(f, "Json"),
             Self::Vector | Self::IvfPq => write!(f, "IVF_PQ"),
             Self::IvfFlat => write!(f, "IVF_FLAT"),
             Self::IvfSq => write!(f, "IVF_SQ"),
             Self::IvfHnswSq => write!(f, "IVF_HNSW_SQ"),
             Self::IvfHnswPq => write!(f, "IVF_HNSW_PQ"),
             Self::IvfHnswFlat => write!(f, "IVF_HNSW_FLAT"),
+            Self::DiskAnn => write!(f, "DISKANN"),
         }
     }
 }
@@ -140,6 +145,7 @@ impl TryFrom<i32> for IndexType {
             v if v == Self::Inverted as i32 => Ok(Self::Inverted),
             v if v == Self::FragmentReuse as i32 => Ok(Self::FragmentReuse),
             v if v == Self::MemWal as i32 => Ok(Self::MemWal),
+            v if v == Self::Json as i32 => Ok(Self::Json),
             v if v == Self::Vector as i32 => Ok(Self::Vector),
             v if v == Self::IvfFlat as i32 => Ok(Self::IvfFlat),
             v if v == Self::IvfSq as i32 => Ok(Self::IvfSq),
@@ -147,6 +153,7 @@ impl TryFrom<i32> for IndexType {
             v if v == Self::IvfHnswSq as i32 => Ok(Self::IvfHnswSq),
             v if v == Self::IvfHnswPq as i32 => Ok(Self::IvfHnswPq),
             v if v == Self::IvfHnswFlat as i32 => Ok(Self::IvfHnswFlat),
+            v if v == Self::DiskAnn as i32 => Ok(Self::DiskAnn),
             _ => Err(Error::InvalidInput {
                 source: format!("the input value {} is not a valid IndexType", value).into(),
                 location: location!(),
@@ -165,6 +172,7 @@ impl IndexType {
                 | Self::LabelList
                 | Self::Inverted
                 | Self::NGram
+                | Self::Json
         )
     }
 
@@ -178,6 +186,7 @@ impl IndexType {
                 | Self::IvfHnswFlat
                 | Self::IvfFlat
                 | Self::IvfSq
+                | Self::DiskAnn
         )
     }
 
@@ -200,6 +209,7 @@ impl IndexType {
             Self::NGram => 0,
             Self::FragmentReuse => 0,
             Self::MemWal => 0,
+            Self::Json => 0,
 
             // for now all vector indices are built by the same builder,
             // so they share the same version.
@@ -209,7 +219,8 @@ impl IndexType {
             | Self::IvfPq
             | Self::IvfHnswSq
             | Self::IvfHnswPq
-            | Self::IvfHnswFlat => 1,
+            | Self::IvfHnswFlat
+            | Self::DiskAnn => 1,
         }
     }
 }
@@ -1,6 +1,14 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
+/// Default ratio to the average partition size above which an IVF partition is split,
+/// used by [`OptimizeOptions::rebalance`].
+pub const DEFAULT_SPLIT_PARTITION_THRESHOLD: f64 = 4.0;
+
+/// Default ratio to the average partition size below which an IVF partition is merged,
+/// used by [`OptimizeOptions::rebalance`].
+pub const DEFAULT_MERGE_PARTITION_THRESHOLD: f64 = 0.1;
+
 /// Options for optimizing all indices.
 #[derive(Debug)]
 pub struct OptimizeOptions {
@@ -33,6 +41,29 @@ pub struct OptimizeOptions {
     ///
     /// NOTE: this option is only supported for v3 vector indices.
     pub retrain: bool,
+
+    /// Split the IVF partitions larger than this ratio to the average partition size.
+    /// Default: None, which disables splitting.
+    ///
+    /// An oversized partition is split in two with a local k-means over its vectors,
+    /// and only the vectors of the split partitions are reassigned, the other
+    /// partitions are copied unless they have new data.
+    /// Rebalancing changes the IVF model, which the delta indices must share, so the
+    /// partitions are rebalanced only if `num_indices_to_merge` covers all indices.
+    ///
+    /// NOTE: this option is only supported for v3 vector indices.
+    pub split_partition_threshold: Option<f64>,
+
+    /// Merge the IVF partitions smaller than this ratio to the average partition size.
+    /// Default: None, which disables merging.
+    ///
+    /// The centroid of a tiny partition is removed and its vectors are reassigned
+    /// to the nearest remaining partitions.
+    /// Like `split_partition_threshold`, the partitions are rebalanced only if
+    /// `num_indices_to_merge` covers all indices.
+    ///
+    /// NOTE: this option is only supported for v3 vector indices.
+    pub merge_partition_threshold: Option<f64>,
 }
 
 impl Default for OptimizeOptions {
@@ -41,6 +72,8 @@ impl Default for OptimizeOptions {
             num_indices_to_merge: 1,
             index_names: None,
             retrain: false,
+            split_partition_threshold: None,
+            merge_partition_threshold: None,
         }
     }
 }
@@ -51,6 +84,8 @@ impl OptimizeOptions {
             num_indices_to_merge: 1,
             index_names: None,
             retrain: false,
+            split_partition_threshold: None,
+            merge_partition_threshold: None,
         }
     }
 
@@ -59,6 +94,8 @@ impl OptimizeOptions {
             num_indices_to_merge: 0,
             index_names: None,
             retrain: false,
+            split_partition_threshold: None,
+            merge_partition_threshold: None,
         }
     }
 
@@ -67,6 +104,22 @@ impl OptimizeOptions {
             num_indices_to_merge: 0,
             index_names: None,
             retrain: true,
+            split_partition_threshold: None,
+            merge_partition_threshold: None,
+        }
+    }
+
+    /// Rebalance the IVF partitions with the default split and merge thresholds.
+    ///
+    /// The delta updates are merged into the latest index, if there are more delta
+    /// indices, set `num_indices_to_merge` to merge all of them.
+    pub fn rebalance() -> Self {
+        Self {
+            num_indices_to_merge: 1,
+            index_names: None,
+            retrain: false,
+            split_partition_threshold: Some(DEFAULT_SPLIT_PARTITION_THRESHOLD),
+            merge_partition_threshold: Some(DEFAULT_MERGE_PARTITION_THRESHOLD),
         }
     }
 
@@ -79,4 +132,19 @@ impl OptimizeOptions {
         self.index_names = Some(names);
         self
     }
+
+    pub fn split_partition_threshold(mut self, threshold: f64) -> Self {
+        self.split_partition_threshold = Some(threshold);
+        self
+    }
+
+    pub fn merge_partition_threshold(mut self, threshold: f64) -> Self {
+        self.merge_partition_threshold = Some(threshold);
+        self
+    }
+
+    /// Whether the IVF partitions should be split or merged.
+    pub fn rebalances_partitions(&self) -> bool {
+        self.split_partition_threshold.is_some() || self.merge_partition_threshold.is_some()
+    }
 }
@@ -14,15 +14,20 @@ use async_trait::async_trait;
 use datafusion::functions::string::contains::ContainsFunc;
 use datafusion::functions_array::array_has;
 use datafusion::physical_plan::SendableRecordBatchStream;
+use datafusion_common::tree_node::{Transformed, TreeNode};
 use datafusion_common::{scalar::ScalarValue, Column};
 
 use datafusion_expr::expr::ScalarFunction;
 use datafusion_expr::Expr;
 use deepsize::DeepSizeOf;
+use inverted::highlight::HighlightParams;
+use inverted::parser::parse_query_string;
 use inverted::query::{fill_fts_query_column, FtsQuery, FtsQueryNode, FtsSearchParams, MatchQuery};
+use lance_arrow::json::JsonPath;
 use lance_core::cache::LanceCache;
 use lance_core::utils::mask::RowIdTreeMap;
 use lance_core::{Error, Result};
+use lance_datafusion::json::json_extract_expr;
 use snafu::location;
 
 use crate::metrics::MetricsCollector;
@@ -33,6 +38,7 @@ pub mod btree;
 pub mod expression;
 pub mod flat;
 pub mod inverted;
+pub mod json;
 pub mod label_list;
 pub mod lance_format;
 pub mod ngram;
@@ -49,6 +55,7 @@ pub enum ScalarIndexType {
     LabelList,
     NGram,
     Inverted,
+    Json,
 }
 
 impl TryFrom<IndexType> for ScalarIndexType {
@@ -61,6 +68,7 @@ impl TryFrom<IndexType> for ScalarIndexType {
             IndexType::LabelList => Ok(Self::LabelList),
             IndexType::NGram => Ok(Self::NGram),
             IndexType::Inverted => Ok(Self::Inverted),
+            IndexType::Json => Ok(Self::Json),
             _ => Err(Error::InvalidInput {
                 source: format!("Index type {:?} is not a scalar index", value).into(),
                 location: location!(),
@@ -77,6 +85,7 @@ impl From<ScalarIndexType> for IndexType {
             ScalarIndexType::LabelList => Self::LabelList,
             ScalarIndexType::NGram => Self::NGram,
             ScalarIndexType::Inverted => Self::Inverted,
+            ScalarIndexType::Json => Self::Json,
         }
     }
 }
@@ -107,6 +116,7 @@ impl IndexParams for ScalarIndexParams {
             Some(ScalarIndexType::LabelList) => IndexType::LabelList,
             Some(ScalarIndexType::Inverted) => IndexType::Inverted,
             Some(ScalarIndexType::NGram) => IndexType::NGram,
+            Some(ScalarIndexType::Json) => IndexType::Json,
         }
     }
 
@@ -238,6 +248,14 @@ pub struct FullTextSearchQuery {
     /// Increasing this value will reduce the recall and improve the performance
     /// 1.0 is the value that would give the best performance without recall loss
     pub wand_factor: Option<f32>,
+
+    /// If set, the results contain the `_highlights` and `_snippet` columns
+    /// with the matched terms of the query
+    pub highlight: Option<HighlightParams>,
+
+    /// If true, the results contain the `_explain` column
+    /// with the breakdown of the score of each result
+    pub explain: bool,
 }
 
 impl FullTextSearchQuery {
@@ -248,6 +266,8 @@ impl FullTextSearchQuery {
             query,
             limit: None,
             wand_factor: None,
+            highlight: None,
+            explain: false,
         }
     }
 
@@ -258,6 +278,8 @@ impl FullTextSearchQuery {
             query,
             limit: None,
             wand_factor: None,
+            highlight: None,
+            explain: false,
         }
     }
 
@@ -267,9 +289,18 @@ impl FullTextSearchQuery {
             query,
             limit: None,
             wand_factor: None,
+            highlight: None,
+            explain: false,
         }
     }
 
+    /// Create a query from a query string,
+    /// e.g. `title:(rust OR arrow) AND body:"columnar format"~2 -draft^0.5`,
+    /// see [`inverted::parser::QueryStringParser`] for the syntax
+    pub fn from_query_string(query: &str) -> Result<Self> {
+        Ok(Self::new_query(parse_query_string(query)?))
+    }
+
     /// Set the column to search over
     /// This is available for only MatchQuery and PhraseQuery
     pub fn with_column(mut self, column: String) -> Result<Self> {
@@ -296,6 +327,20 @@ impl FullTextSearchQuery {
         self
     }
 
+    /// Highlight the matched terms in the results,
+    /// if None, no highlights are returned
+    pub fn with_highlight(mut self, highlight: Option<HighlightParams>) -> Self {
+        self.highlight = highlight;
+        self
+    }
+
+    /// Explain how the score of each result is computed,
+    /// see [`inverted::explain::Explanation`]
+    pub fn with_explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
     pub fn columns(&self) -> HashSet<String> {
         self.query.columns()
     }
@@ -557,6 +602,51 @@ impl AnyQuery for TextQuery {
     }
 }
 
+/// A query that a JsonIndex can satisfy
+///
+/// The inner query is applied to the text extracted from `path` (the same
+/// text `json_extract` returns)
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonQuery {
+    pub path: JsonPath,
+    pub query: SargableQuery,
+}
+
+impl JsonQuery {
+    pub fn new(path: JsonPath, query: SargableQuery) -> Self {
+        Self { path, query }
+    }
+}
+
+impl AnyQuery for JsonQuery {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn format(&self, col: &str) -> String {
+        self.query
+            .format(&format!("json_extract({}, '{}')", col, self.path))
+    }
+
+    fn to_expr(&self, col: String) -> Expr {
+        self.query
+            .to_expr(col)
+            .transform(|expr| match expr {
+                Expr::Column(_) => Ok(Transformed::yes(json_extract_expr(expr, &self.path))),
+                _ => Ok(Transformed::no(expr)),
+            })
+            .unwrap()
+            .data
+    }
+
+    fn dyn_eq(&self, other: &dyn AnyQuery) -> bool {
+        match other.as_any().downcast_ref::<Self>() {
+            Some(o) => self == o,
+            None => false,
+        }
+    }
+}
+
 /// The result of a search operation against a scalar index
 #[derive(Debug, PartialEq)]
 pub enum SearchResult {
@@ -463,6 +463,42 @@ pub async fn train_bitmap_index(
     do_train_bitmap_index(batches_source, dictionary, index_store).await
 }
 
+/// Merge bitmap indices over disjoint sets of rows into a single index.
+///
+/// This is used to combine the segments of an index built by many workers.
+pub async fn merge_bitmap_indices(
+    indices: &[Arc<BitmapIndex>],
+    index_store: &dyn IndexStore,
+) -> Result<()> {
+    let Some(first) = indices.first() else {
+        return Err(Error::invalid_input(
+            "merging bitmap indices: no index to merge",
+            location!(),
+        ));
+    };
+
+    let mut state: HashMap<ScalarValue, RowIdTreeMap> = HashMap::new();
+    let mut null_map = RowIdTreeMap::default();
+    for index in indices {
+        if index.value_type != first.value_type {
+            return Err(Error::invalid_input(
+                format!(
+                    "merging bitmap indices: value type {} doesn't match {}",
+                    index.value_type, first.value_type
+                ),
+                location!(),
+            ));
+        }
+        for (key, bitmap) in index.index_map.iter() {
+            *state.entry(key.0.clone()).or_default() |= bitmap.clone();
+        }
+        null_map |= index.null_map.clone();
+    }
+    state.insert(ScalarValue::try_from(&first.value_type)?, null_map);
+
+    write_bitmap_index(state, index_store, &first.value_type).await
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::scalar::bitmap::BitmapIndex;
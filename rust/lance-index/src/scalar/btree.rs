@@ -50,7 +50,7 @@ use serde::{Serialize, Serializer};
 use snafu::location;
 use tracing::info;
 
-const BTREE_LOOKUP_NAME: &str = "page_lookup.lance";
+pub const BTREE_LOOKUP_NAME: &str = "page_lookup.lance";
 const BTREE_PAGES_NAME: &str = "page_data.lance";
 pub const DEFAULT_BTREE_BATCH_SIZE: u64 = 4096;
 const BATCH_SIZE_META_KEY: &str = "batch_size";
@@ -776,6 +776,46 @@ impl BTreeIndex {
         }
     }
 
+    /// The smallest and largest non-null values in the index, read from the page lookup
+    ///
+    /// The nulls are sorted first, so the min of the page with both nulls and values is null
+    /// in the lookup, only this page is loaded to find its smallest non-null value.
+    ///
+    /// Returns `None` if the index has no non-null values
+    pub async fn min_max(&self) -> Result<Option<(ScalarValue, ScalarValue)>> {
+        let tree = &self.page_lookup.tree;
+        let Some(max) = tree.values().flatten().map(|page| &page.max).max() else {
+            return Ok(None);
+        };
+        let mut min = tree
+            .iter()
+            .find(|(min, pages)| !min.0.is_null() && !pages.is_empty())
+            .map(|(min, _)| min.clone());
+
+        let index_reader = LazyIndexReader::new(self.store.clone());
+        for (_, pages) in tree.iter().take_while(|(min, _)| min.0.is_null()) {
+            for page in pages {
+                let page = index_reader
+                    .get()
+                    .await?
+                    .read_record_batch(page.page_number as u64, self.batch_size)
+                    .await?;
+                let values = page.column(0);
+                // the page is sorted with nulls first
+                let first_value = values.null_count();
+                if first_value < values.len() {
+                    let value =
+                        OrderableScalarValue(ScalarValue::try_from_array(values, first_value)?);
+                    if min.as_ref().is_none_or(|min| &value < min) {
+                        min = Some(value);
+                    }
+                }
+            }
+        }
+
+        Ok(min.map(|min| (min.0, max.0.clone())))
+    }
+
     async fn lookup_page(
         &self,
         page_number: u32,
@@ -1344,41 +1384,85 @@ impl TrainingSource for BTreeUpdater {
         chunk_size: u32,
     ) -> Result<SendableRecordBatchStream> {
         let data_type = self.new_data.schema().field(0).data_type().clone();
-        // Datafusion currently has bugs with spilling on string columns
-        // See https://github.com/apache/datafusion/issues/10073
-        //
-        // One we upgrade we can remove this
-        let use_spilling = !matches!(data_type, DataType::Utf8 | DataType::LargeUtf8);
-
         let new_input = Arc::new(OneShotExec::new(self.new_data));
         let old_input = Self::into_old_input(self.index);
         debug_assert_eq!(
             old_input.schema().flattened_fields().len(),
             new_input.schema().flattened_fields().len()
         );
-        let sort_expr = PhysicalSortExpr {
-            expr: Arc::new(Column::new("values", 0)),
-            options: SortOptions {
-                descending: false,
-                nulls_first: true,
-            },
-        };
-        // The UnionExec creates multiple partitions but the SortPreservingMergeExec merges
-        // them back into a single partition.
-        let all_data = Arc::new(UnionExec::new(vec![old_input, new_input]));
-        let ordered = Arc::new(SortPreservingMergeExec::new(
-            LexOrdering::new(vec![sort_expr]),
-            all_data,
-        ));
+        merge_ordered_inputs(vec![old_input, new_input], &data_type, chunk_size)
+    }
 
-        let unchunked = execute_plan(
-            ordered,
-            LanceExecutionOptions {
-                use_spilling,
-                ..Default::default()
-            },
-        )?;
-        Ok(chunk_concat_stream(unchunked, chunk_size as usize))
+    async fn scan_unordered_chunks(
+        self: Box<Self>,
+        _chunk_size: u32,
+    ) -> Result<SendableRecordBatchStream> {
+        // BTree indices will never use unordered scans
+        unimplemented!()
+    }
+}
+
+// Merge the inputs, each sorted by the values, into a single sorted stream of page-size batches
+fn merge_ordered_inputs(
+    inputs: Vec<Arc<dyn ExecutionPlan>>,
+    data_type: &DataType,
+    chunk_size: u32,
+) -> Result<SendableRecordBatchStream> {
+    // Datafusion currently has bugs with spilling on string columns
+    // See https://github.com/apache/datafusion/issues/10073
+    //
+    // One we upgrade we can remove this
+    let use_spilling = !matches!(data_type, DataType::Utf8 | DataType::LargeUtf8);
+
+    let sort_expr = PhysicalSortExpr {
+        expr: Arc::new(Column::new("values", 0)),
+        options: SortOptions {
+            descending: false,
+            nulls_first: true,
+        },
+    };
+    // The UnionExec creates multiple partitions but the SortPreservingMergeExec merges
+    // them back into a single partition.
+    let all_data = Arc::new(UnionExec::new(inputs));
+    let ordered = Arc::new(SortPreservingMergeExec::new(
+        LexOrdering::new(vec![sort_expr]),
+        all_data,
+    ));
+
+    let unchunked = execute_plan(
+        ordered,
+        LanceExecutionOptions {
+            use_spilling,
+            ..Default::default()
+        },
+    )?;
+    Ok(chunk_concat_stream(unchunked, chunk_size as usize))
+}
+
+/// A source of training data created by merging the data of btree indices
+/// over disjoint sets of rows
+struct BTreeMerger {
+    indices: Vec<BTreeIndex>,
+}
+
+#[async_trait]
+impl TrainingSource for BTreeMerger {
+    async fn scan_ordered_chunks(
+        self: Box<Self>,
+        chunk_size: u32,
+    ) -> Result<SendableRecordBatchStream> {
+        let data_type = self.indices[0]
+            .sub_index
+            .schema()
+            .field(0)
+            .data_type()
+            .clone();
+        let inputs = self
+            .indices
+            .into_iter()
+            .map(BTreeUpdater::into_old_input)
+            .collect();
+        merge_ordered_inputs(inputs, &data_type, chunk_size)
     }
 
     async fn scan_unordered_chunks(
@@ -1390,6 +1474,33 @@ impl TrainingSource for BTreeUpdater {
     }
 }
 
+/// Merge btree indices over disjoint sets of rows into a single index.
+///
+/// The pages of the indices are read back in order and retrained, this is used to
+/// combine the segments of an index built by many workers.
+pub async fn merge_btree_indices(
+    indices: &[Arc<BTreeIndex>],
+    index_store: &dyn IndexStore,
+) -> Result<()> {
+    let Some(first) = indices.first() else {
+        return Err(Error::invalid_input(
+            "merging btree indices: no index to merge",
+            location!(),
+        ));
+    };
+    let sub_index = first.sub_index.clone();
+    let merger = Box::new(BTreeMerger {
+        indices: indices.iter().map(|index| index.as_ref().clone()).collect(),
+    });
+    train_btree_index(
+        merger,
+        sub_index.as_ref(),
+        index_store,
+        DEFAULT_BTREE_BATCH_SIZE as u32,
+    )
+    .await
+}
+
 /// A stream that reads the original training data back out of the index
 ///
 /// This is used for updating the index
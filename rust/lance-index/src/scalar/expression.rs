@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
-use std::{ops::Bound, sync::Arc};
+use std::{borrow::Cow, ops::Bound, sync::Arc};
 
 use arrow_array::Array;
 use arrow_schema::{DataType, Field};
@@ -14,13 +14,15 @@ use datafusion_expr::{
 };
 
 use futures::join;
+use lance_arrow::json::JsonPath;
 use lance_core::{utils::mask::RowIdMask, Error, Result};
-use lance_datafusion::{expr::safe_coerce_scalar, planner::Planner};
+use lance_datafusion::{expr::safe_coerce_scalar, json::as_json_extract, planner::Planner};
 use snafu::location;
 use tracing::instrument;
 
 use super::{
-    AnyQuery, LabelListQuery, MetricsCollector, SargableQuery, ScalarIndex, SearchResult, TextQuery,
+    AnyQuery, JsonQuery, LabelListQuery, MetricsCollector, SargableQuery, ScalarIndex,
+    SearchResult, TextQuery,
 };
 
 /// An indexed expression consists of a scalar index query with a post-scan filter
@@ -212,26 +214,104 @@ impl ScalarQueryParser for SargableQueryParser {
         value: &ScalarValue,
         op: &Operator,
     ) -> Option<IndexedExpression> {
-        let query = match op {
-            Operator::Lt => SargableQuery::Range(Bound::Unbounded, Bound::Excluded(value.clone())),
-            Operator::LtEq => {
-                SargableQuery::Range(Bound::Unbounded, Bound::Included(value.clone()))
-            }
-            Operator::Gt => SargableQuery::Range(Bound::Excluded(value.clone()), Bound::Unbounded),
-            Operator::GtEq => {
-                SargableQuery::Range(Bound::Included(value.clone()), Bound::Unbounded)
-            }
-            Operator::Eq => SargableQuery::Equals(value.clone()),
-            // This will be negated by the caller
-            Operator::NotEq => SargableQuery::Equals(value.clone()),
-            _ => unreachable!(),
-        };
         Some(IndexedExpression::index_query(
             column.to_string(),
             self.index_name.clone(),
-            Arc::new(query),
+            Arc::new(sargable_comparison(value, op)),
+        ))
+    }
+
+    fn visit_scalar_function(
+        &self,
+        _: &str,
+        _: &DataType,
+        _: &ScalarUDF,
+        _: &[Expr],
+    ) -> Option<IndexedExpression> {
+        None
+    }
+}
+
+fn sargable_comparison(value: &ScalarValue, op: &Operator) -> SargableQuery {
+    match op {
+        Operator::Lt => SargableQuery::Range(Bound::Unbounded, Bound::Excluded(value.clone())),
+        Operator::LtEq => SargableQuery::Range(Bound::Unbounded, Bound::Included(value.clone())),
+        Operator::Gt => SargableQuery::Range(Bound::Excluded(value.clone()), Bound::Unbounded),
+        Operator::GtEq => SargableQuery::Range(Bound::Included(value.clone()), Bound::Unbounded),
+        Operator::Eq => SargableQuery::Equals(value.clone()),
+        // This will be negated by the caller
+        Operator::NotEq => SargableQuery::Equals(value.clone()),
+        _ => unreachable!(),
+    }
+}
+
+/// The name a JSON index is registered under with an [`IndexInformationProvider`]
+///
+/// Filters on `json_extract(column, path)` look up the index with this name
+/// instead of the column name.
+pub fn json_index_key(column: &str, path: &JsonPath) -> String {
+    format!("{}->>'{}'", column, path)
+}
+
+/// A parser for JSON indices
+///
+/// The index is looked up with [`json_index_key`] but the queries it creates
+/// are against the JSON column itself.
+#[derive(Debug)]
+pub struct JsonQueryParser {
+    index_name: String,
+    column: String,
+    path: JsonPath,
+}
+
+impl JsonQueryParser {
+    pub fn new(index_name: String, column: String, path: JsonPath) -> Self {
+        Self {
+            index_name,
+            column,
+            path,
+        }
+    }
+
+    fn query(&self, query: SargableQuery) -> Option<IndexedExpression> {
+        Some(IndexedExpression::index_query(
+            self.column.clone(),
+            self.index_name.clone(),
+            Arc::new(JsonQuery::new(self.path.clone(), query)),
         ))
     }
+}
+
+impl ScalarQueryParser for JsonQueryParser {
+    fn visit_between(
+        &self,
+        _: &str,
+        low: &Bound<ScalarValue>,
+        high: &Bound<ScalarValue>,
+    ) -> Option<IndexedExpression> {
+        self.query(SargableQuery::Range(low.clone(), high.clone()))
+    }
+
+    fn visit_in_list(&self, _: &str, in_list: &[ScalarValue]) -> Option<IndexedExpression> {
+        self.query(SargableQuery::IsIn(in_list.to_vec()))
+    }
+
+    fn visit_is_bool(&self, _: &str, _: bool) -> Option<IndexedExpression> {
+        None
+    }
+
+    fn visit_is_null(&self, _: &str) -> Option<IndexedExpression> {
+        self.query(SargableQuery::IsNull())
+    }
+
+    fn visit_comparison(
+        &self,
+        _: &str,
+        value: &ScalarValue,
+        op: &Operator,
+    ) -> Option<IndexedExpression> {
+        self.query(sargable_comparison(value, op))
+    }
 
     fn visit_scalar_function(
         &self,
@@ -863,10 +943,16 @@ impl ScalarIndexExpr {
 }
 
 // Extract a column from the expression, if it is a column, or None
-fn maybe_column(expr: &Expr) -> Option<&str> {
+//
+// A path extracted from a JSON column (`json_extract(col, path)`) is treated as
+// a column named by [`json_index_key`]
+fn maybe_column(expr: &Expr) -> Option<Cow<'_, str>> {
     match expr {
-        Expr::Column(col) => Some(&col.name),
-        _ => None,
+        Expr::Column(col) => Some(Cow::Borrowed(&col.name)),
+        _ => {
+            let (col, path) = as_json_extract(expr)?;
+            Some(Cow::Owned(json_index_key(col, &path)))
+        }
     }
 }
 
@@ -874,9 +960,9 @@ fn maybe_column(expr: &Expr) -> Option<&str> {
 fn maybe_indexed_column<'a, 'b>(
     expr: &'a Expr,
     index_info: &'b dyn IndexInformationProvider,
-) -> Option<(&'a str, &'b DataType, &'b dyn ScalarQueryParser)> {
+) -> Option<(Cow<'a, str>, &'b DataType, &'b dyn ScalarQueryParser)> {
     let col = maybe_column(expr)?;
-    let data_type = index_info.get_index(col);
+    let data_type = index_info.get_index(&col);
     data_type.map(|(ty, parser)| (col, ty, parser))
 }
 
@@ -953,7 +1039,7 @@ fn visit_between(
     let high = maybe_scalar(&between.high, col_type)?;
 
     let indexed_expr =
-        query_parser.visit_between(column, &Bound::Included(low), &Bound::Included(high))?;
+        query_parser.visit_between(&column, &Bound::Included(low), &Bound::Included(high))?;
 
     if between.negated {
         indexed_expr.maybe_not()
@@ -969,7 +1055,7 @@ fn visit_in_list(
     let (column, col_type, query_parser) = maybe_indexed_column(&in_list.expr, index_info)?;
     let values = maybe_scalar_list(&in_list.list, col_type)?;
 
-    let indexed_expr = query_parser.visit_in_list(column, &values)?;
+    let indexed_expr = query_parser.visit_in_list(&column, &values)?;
 
     if in_list.negated {
         indexed_expr.maybe_not()
@@ -987,7 +1073,7 @@ fn visit_is_bool(
     if *col_type != DataType::Boolean {
         None
     } else {
-        query_parser.visit_is_bool(column, value)
+        query_parser.visit_is_bool(&column, value)
     }
 }
 
@@ -1000,7 +1086,7 @@ fn visit_column(
     if *col_type != DataType::Boolean {
         None
     } else {
-        query_parser.visit_is_bool(column, true)
+        query_parser.visit_is_bool(&column, true)
     }
 }
 
@@ -1010,7 +1096,7 @@ fn visit_is_null(
     negated: bool,
 ) -> Option<IndexedExpression> {
     let (column, _, query_parser) = maybe_indexed_column(expr, index_info)?;
-    let indexed_expr = query_parser.visit_is_null(column)?;
+    let indexed_expr = query_parser.visit_is_null(&column)?;
     if negated {
         indexed_expr.maybe_not()
     } else {
@@ -1030,7 +1116,7 @@ fn visit_comparison(
     let left_col = maybe_indexed_column(&expr.left, index_info);
     if let Some((column, col_type, query_parser)) = left_col {
         let scalar = maybe_scalar(&expr.right, col_type)?;
-        query_parser.visit_comparison(column, &scalar, &expr.op)
+        query_parser.visit_comparison(&column, &scalar, &expr.op)
     } else {
         // Datafusion's query simplifier will canonicalize expressions and so we shouldn't reach this case.  If, for some reason, we
         // do reach this case we can handle it in the future by inverting expr.op and swapping the left and right sides
@@ -1093,7 +1179,7 @@ fn maybe_range(
         _ => return None,
     };
 
-    parser.visit_between(left_col, &low, &high)
+    parser.visit_between(&left_col, &low, &high)
 }
 
 fn visit_and(
@@ -1164,7 +1250,7 @@ fn visit_scalar_fn(
         return None;
     }
     let (col, data_type, query_parser) = maybe_indexed_column(&scalar_fn.args[0], index_info)?;
-    query_parser.visit_scalar_function(col, data_type, &scalar_fn.func, &scalar_fn.args)
+    query_parser.visit_scalar_function(&col, data_type, &scalar_fn.func, &scalar_fn.args)
 }
 
 fn visit_node(expr: &Expr, index_info: &dyn IndexInformationProvider) -> Option<IndexedExpression> {
@@ -1734,4 +1820,77 @@ mod tests {
         // Non-normalized arithmetic (can use expression simplification)
         check_no_index(&index_info, "aisle + 3 < 10")
     }
+
+    #[test]
+    fn test_json_expressions() {
+        let path = JsonPath::parse("$.user.id").unwrap();
+        let index_info = MockIndexInfoProvider::new(vec![(
+            &json_index_key("payload", &path),
+            ColInfo::new(
+                DataType::Utf8,
+                Box::new(JsonQueryParser::new(
+                    "payload_user_idx".to_string(),
+                    "payload".to_string(),
+                    path.clone(),
+                )),
+            ),
+        )]);
+        let extracted = |path: &str| {
+            lance_datafusion::json::json_extract_expr(
+                Expr::Column(Column::new_unqualified("payload")),
+                &JsonPath::parse(path).unwrap(),
+            )
+        };
+        let json_query = |query: SargableQuery| {
+            Some(IndexedExpression::index_query(
+                "payload".to_string(),
+                "payload_user_idx".to_string(),
+                Arc::new(JsonQuery::new(path.clone(), query)),
+            ))
+        };
+        let lit = |value: &str| Expr::Literal(ScalarValue::Utf8(Some(value.to_string())), None);
+
+        let expr = extracted("$.user.id").eq(lit("abc"));
+        assert_eq!(
+            apply_scalar_indices(expr, &index_info),
+            json_query(SargableQuery::Equals(ScalarValue::Utf8(Some(
+                "abc".to_string()
+            ))))
+            .unwrap()
+        );
+        let expr = extracted("$.user.id").is_null();
+        assert_eq!(
+            apply_scalar_indices(expr, &index_info),
+            json_query(SargableQuery::IsNull()).unwrap()
+        );
+        let expr = extracted("$.user.id").gt_eq(lit("a"));
+        assert_eq!(
+            apply_scalar_indices(expr, &index_info),
+            json_query(SargableQuery::Range(
+                Bound::Included(ScalarValue::Utf8(Some("a".to_string()))),
+                Bound::Unbounded
+            ))
+            .unwrap()
+        );
+
+        // Other paths, and the JSON column itself, are not indexed
+        let expr = extracted("$.user.name").eq(lit("abc"));
+        assert!(apply_scalar_indices(expr, &index_info)
+            .scalar_query
+            .is_none());
+        let expr = Expr::Column(Column::new_unqualified("payload")).is_null();
+        assert!(apply_scalar_indices(expr, &index_info)
+            .scalar_query
+            .is_none());
+
+        // The query is converted back to a filter on the JSON column
+        let query = JsonQuery::new(
+            path.clone(),
+            SargableQuery::Equals(ScalarValue::Utf8(Some("abc".to_string()))),
+        );
+        assert_eq!(
+            query.to_expr("payload".to_string()),
+            extracted("$.user.id").eq(lit("abc"))
+        );
+    }
 }
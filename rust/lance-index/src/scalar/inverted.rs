@@ -3,15 +3,20 @@
 
 pub mod builder;
 mod encoding;
+pub mod explain;
+pub mod highlight;
 mod index;
 mod iter;
 mod merger;
+pub mod parser;
+pub mod pattern;
 pub mod query;
 mod scorer;
 pub mod tokenizer;
 mod wand;
 
 pub use builder::InvertedIndexBuilder;
+pub use merger::merge_index_segments;
 pub use index::*;
 use lance_core::Result;
 pub use tokenizer::*;
@@ -230,15 +230,7 @@ impl InvertedIndexBuilder {
     }
 
     async fn write_metadata(&self, dest_store: &dyn IndexStore, partitions: &[u64]) -> Result<()> {
-        let metadata = HashMap::from_iter(vec![
-            ("partitions".to_owned(), serde_json::to_string(&partitions)?),
-            ("params".to_owned(), serde_json::to_string(&self.params)?),
-        ]);
-        let mut writer = dest_store
-            .new_index_file(METADATA_FILE, Arc::new(Schema::empty()))
-            .await?;
-        writer.finish_with_metadata(metadata).await?;
-        Ok(())
+        write_metadata(dest_store, &self.params, partitions).await
     }
 
     async fn write(&self, dest_store: &dyn IndexStore) -> Result<()> {
@@ -270,6 +262,24 @@ impl InvertedIndexBuilder {
     }
 }
 
+// write the metadata file that lists the partitions of the index,
+// the index is complete once this file is written
+pub(crate) async fn write_metadata(
+    dest_store: &dyn IndexStore,
+    params: &InvertedIndexParams,
+    partitions: &[u64],
+) -> Result<()> {
+    let metadata = HashMap::from_iter(vec![
+        ("partitions".to_owned(), serde_json::to_string(&partitions)?),
+        ("params".to_owned(), serde_json::to_string(params)?),
+    ]);
+    let mut writer = dest_store
+        .new_index_file(METADATA_FILE, Arc::new(Schema::empty()))
+        .await?;
+    writer.finish_with_metadata(metadata).await?;
+    Ok(())
+}
+
 impl Default for InvertedIndexBuilder {
     fn default() -> Self {
         let params = InvertedIndexParams::default();
@@ -0,0 +1,390 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, LazyLock};
+
+use arrow::array::StringBuilder;
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field};
+use lance_arrow::iter_str_array;
+use lance_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use snafu::location;
+
+use super::highlight::{tokenize_doc, DocToken, PhraseMatcher, TermMatcher};
+use super::query::*;
+use super::scorer::{idf, BM25Scorer, B, K1};
+use super::InvertedIndex;
+
+pub const EXPLAIN_COL: &str = "_explain";
+/// The JSON serialized [`Explanation`] of the score of each result
+pub static EXPLAIN_FIELD: LazyLock<Field> =
+    LazyLock::new(|| Field::new(EXPLAIN_COL, DataType::Utf8, true));
+
+/// The breakdown of a score, each node is a value with its description,
+/// and the details are the values it's computed from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Explanation {
+    pub value: f32,
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<Explanation>,
+}
+
+impl Explanation {
+    pub fn new(value: f32, description: impl Into<String>) -> Self {
+        Self {
+            value,
+            description: description.into(),
+            details: Vec::new(),
+        }
+    }
+
+    pub fn with_details(mut self, details: Vec<Self>) -> Self {
+        self.details = details;
+        self
+    }
+
+    fn boosted(self, boost: f32) -> Self {
+        if boost == 1.0 {
+            return self;
+        }
+        Self::new(self.value * boost, "product of")
+            .with_details(vec![self, Self::new(boost, "boost")])
+    }
+
+    fn sum(details: Vec<Self>, description: impl Into<String>) -> Self {
+        let value = details.iter().map(|detail| detail.value).sum();
+        Self::new(value, description).with_details(details)
+    }
+}
+
+struct ColumnContext<'a> {
+    index: &'a InvertedIndex,
+    scorer: BM25Scorer<'a>,
+    tokenizer: tantivy::tokenizer::TextAnalyzer,
+    query_tokenizer: tantivy::tokenizer::TextAnalyzer,
+}
+
+/// Explains the BM25 scores of the documents for a full text search query.
+///
+/// The statistics (number of documents, average document length and document frequency
+/// of the terms) come from the index on each column,
+/// the term frequencies and document lengths are computed by tokenizing the documents
+/// in the same way as the index.
+pub struct ScoreExplainer<'a> {
+    columns: HashMap<String, ColumnContext<'a>>,
+}
+
+impl<'a> ScoreExplainer<'a> {
+    /// Creates an explainer with the inverted indices of the columns searched by the query
+    pub fn new(indices: impl IntoIterator<Item = (String, &'a InvertedIndex)>) -> Self {
+        let columns = indices
+            .into_iter()
+            .map(|(column, index)| {
+                let context = ColumnContext {
+                    index,
+                    scorer: BM25Scorer::new(index.partitions.iter().map(|part| part.as_ref())),
+                    tokenizer: index.tokenizer(),
+                    query_tokenizer: index.query_tokenizer(),
+                };
+                (column, context)
+            })
+            .collect();
+        Self { columns }
+    }
+
+    /// Returns the JSON serialized explanations of the rows of the batch,
+    /// the batch must contain all the columns searched by the query,
+    /// it's null if the row doesn't match the query
+    pub fn explain_batch(&mut self, query: &FtsQuery, batch: &RecordBatch) -> Result<ArrayRef> {
+        let mut docs = Vec::with_capacity(self.columns.len());
+        for column in self.columns.keys() {
+            let array = batch.column_by_name(column).ok_or_else(|| {
+                Error::invalid_input(
+                    format!("column {} to explain not found in batch", column),
+                    location!(),
+                )
+            })?;
+            if !matches!(array.data_type(), DataType::Utf8 | DataType::LargeUtf8) {
+                return Err(Error::invalid_input(
+                    format!(
+                        "explaining scores is only supported on string columns, but column {} is {}",
+                        column,
+                        array.data_type()
+                    ),
+                    location!(),
+                ));
+            }
+            docs.push((column.clone(), iter_str_array(array).collect::<Vec<_>>()));
+        }
+
+        let mut builder = StringBuilder::with_capacity(batch.num_rows(), batch.num_rows() * 256);
+        for row in 0..batch.num_rows() {
+            let mut doc_tokens = HashMap::with_capacity(docs.len());
+            for (column, values) in &docs {
+                let context = self.columns.get_mut(column).unwrap();
+                let tokens = values[row]
+                    .map(|doc| tokenize_doc(&mut context.tokenizer, doc))
+                    .unwrap_or_default();
+                doc_tokens.insert(column.as_str(), tokens);
+            }
+            match self.explain(query, &doc_tokens)? {
+                Some(explanation) => builder.append_value(serde_json::to_string(&explanation)?),
+                None => builder.append_null(),
+            }
+        }
+        Ok(Arc::new(builder.finish()))
+    }
+
+    // returns None if the document doesn't match the query
+    fn explain(
+        &mut self,
+        query: &FtsQuery,
+        doc_tokens: &HashMap<&str, Vec<DocToken>>,
+    ) -> Result<Option<Explanation>> {
+        match query {
+            FtsQuery::Match(query) => self.explain_match(query, doc_tokens),
+            FtsQuery::Phrase(query) => {
+                let (context, tokens) = self.context(query.column.as_deref(), doc_tokens)?;
+                let phrase =
+                    PhraseMatcher::new(&mut context.query_tokenizer, &query.terms, query.slop);
+                let mut matched = Vec::new();
+                phrase.find(tokens, &mut matched);
+                if matched.is_empty() {
+                    return Ok(None);
+                }
+                let details = phrase
+                    .tokens()
+                    .map(|term| explain_term(context, tokens, term))
+                    .collect();
+                let explanation = Explanation::sum(
+                    details,
+                    format!("sum of the terms of phrase \"{}\"", query.terms),
+                );
+                Ok(Some(explanation.boosted(query.boost)))
+            }
+            FtsQuery::Prefix(query) => self.explain_pattern(query.clone().into(), doc_tokens),
+            FtsQuery::Wildcard(query) => self.explain_pattern(query.clone().into(), doc_tokens),
+            FtsQuery::Regexp(query) => self.explain_pattern(query.clone().into(), doc_tokens),
+            FtsQuery::Boost(query) => {
+                let Some(positive) = self.explain(&query.positive, doc_tokens)? else {
+                    return Ok(None);
+                };
+                let Some(negative) = self.explain(&query.negative, doc_tokens)? else {
+                    return Ok(Some(positive));
+                };
+                let value = positive.value - query.negative_boost * negative.value;
+                Ok(Some(
+                    Explanation::new(value, "positive score - negative_boost * negative score")
+                        .with_details(vec![
+                            positive,
+                            Explanation::new(query.negative_boost, "negative_boost"),
+                            negative,
+                        ]),
+                ))
+            }
+            FtsQuery::MultiMatch(query) => {
+                let mut details = Vec::with_capacity(query.match_queries.len());
+                for query in &query.match_queries {
+                    if let Some(explanation) = self.explain_match(query, doc_tokens)? {
+                        details.push(explanation);
+                    }
+                }
+                let Some(value) = details
+                    .iter()
+                    .map(|detail| detail.value)
+                    .max_by(|a, b| a.total_cmp(b))
+                else {
+                    return Ok(None);
+                };
+                Ok(Some(
+                    Explanation::new(value, "max of the matched columns").with_details(details),
+                ))
+            }
+            FtsQuery::Boolean(query) => {
+                for query in &query.must_not {
+                    if self.explain(query, doc_tokens)?.is_some() {
+                        return Ok(None);
+                    }
+                }
+                let mut details = Vec::with_capacity(query.must.len() + query.should.len());
+                for query in &query.must {
+                    match self.explain(query, doc_tokens)? {
+                        Some(explanation) => details.push(explanation),
+                        None => return Ok(None),
+                    }
+                }
+                let mut matched_should = false;
+                for query in &query.should {
+                    if let Some(explanation) = self.explain(query, doc_tokens)? {
+                        details.push(explanation);
+                        matched_should = true;
+                    }
+                }
+                if query.must.is_empty() && !matched_should {
+                    return Ok(None);
+                }
+                Ok(Some(Explanation::sum(
+                    details,
+                    "sum of the matched must and should clauses",
+                )))
+            }
+        }
+    }
+
+    fn explain_match(
+        &mut self,
+        query: &MatchQuery,
+        doc_tokens: &HashMap<&str, Vec<DocToken>>,
+    ) -> Result<Option<Explanation>> {
+        let (context, tokens) = self.context(query.column.as_deref(), doc_tokens)?;
+        // the fuzzy queries are tokenized without the filters,
+        // then expanded to the indexed terms
+        let fuzziness = query.fuzziness.unwrap_or_default();
+        let terms = match fuzziness {
+            0 => collect_tokens(&query.terms, &mut context.query_tokenizer, None),
+            _ => collect_tokens(
+                &query.terms,
+                &mut tantivy::tokenizer::TextAnalyzer::from(
+                    tantivy::tokenizer::SimpleTokenizer::default(),
+                ),
+                None,
+            ),
+        };
+
+        let mut details = Vec::new();
+        let mut explained = HashSet::new();
+        let mut queried = HashSet::new();
+        for term in terms.iter().filter(|term| queried.insert(term.as_str())) {
+            let matcher = match fuzziness {
+                0 => TermMatcher::Exact(term.clone()),
+                _ => TermMatcher::fuzzy(term, fuzziness, query.prefix_length)?,
+            };
+            let mut matched = false;
+            for token in tokens.iter().filter(|token| matcher.matches(&token.text)) {
+                matched = true;
+                if explained.insert(token.text.as_str()) {
+                    details.push(explain_term(context, tokens, &token.text));
+                }
+            }
+            if !matched && query.operator == Operator::And {
+                return Ok(None);
+            }
+        }
+        if details.is_empty() {
+            return Ok(None);
+        }
+        let explanation = Explanation::sum(
+            details,
+            format!(
+                "sum of the matched terms of \"{}\" ({})",
+                query.terms,
+                <&str>::from(query.operator)
+            ),
+        );
+        Ok(Some(explanation.boosted(query.boost)))
+    }
+
+    fn explain_pattern(
+        &mut self,
+        query: TermPatternQuery,
+        doc_tokens: &HashMap<&str, Vec<DocToken>>,
+    ) -> Result<Option<Explanation>> {
+        let (context, tokens) = self.context(query.column.as_deref(), doc_tokens)?;
+        let expansions = context
+            .index
+            .expand_pattern(&query.pattern, query.max_expansions)?
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let mut explained = HashSet::new();
+        let details = tokens
+            .iter()
+            .filter(|token| {
+                expansions.contains(&token.text) && explained.insert(token.text.as_str())
+            })
+            .map(|token| explain_term(context, tokens, &token.text))
+            .collect::<Vec<_>>();
+        if details.is_empty() {
+            return Ok(None);
+        }
+        let explanation = Explanation::sum(
+            details,
+            format!("sum of the terms matching {}", query.pattern),
+        );
+        Ok(Some(explanation.boosted(query.boost)))
+    }
+
+    fn context<'b>(
+        &'b mut self,
+        column: Option<&str>,
+        doc_tokens: &'b HashMap<&str, Vec<DocToken>>,
+    ) -> Result<(&'b mut ColumnContext<'a>, &'b [DocToken])> {
+        let column = column.ok_or_else(|| {
+            Error::invalid_input("the column must be specified in the query", location!())
+        })?;
+        let context = self.columns.get_mut(column).ok_or_else(|| {
+            Error::invalid_input(
+                format!(
+                    "no inverted index to explain the scores of column {}",
+                    column
+                ),
+                location!(),
+            )
+        })?;
+        Ok((context, doc_tokens[column].as_slice()))
+    }
+}
+
+fn explain_term(context: &ColumnContext<'_>, doc_tokens: &[DocToken], term: &str) -> Explanation {
+    let freq = doc_tokens.iter().filter(|token| token.text == term).count() as f32;
+    let doc_length = doc_tokens.len() as f32;
+    let num_docs = context.scorer.num_docs();
+    // the terms not indexed yet are treated as they appear in 1 document
+    let nq = context.scorer.nq(term).max(1);
+    let avgdl = context.scorer.avgdl();
+
+    let idf = idf(nq, num_docs);
+    let tf = freq * (K1 + 1.0) / (freq + K1 * (1.0 - B + B * doc_length / avgdl));
+    Explanation::new(idf * tf, format!("weight of term \"{}\", idf * tf", term)).with_details(vec![
+        Explanation::new(idf, "idf, computed as ln(1 + (N - n + 0.5) / (n + 0.5))").with_details(
+            vec![
+                Explanation::new(nq as f32, "n, number of documents containing the term"),
+                Explanation::new(num_docs as f32, "N, total number of documents"),
+            ],
+        ),
+        Explanation::new(
+            tf,
+            "tf, computed as freq * (k1 + 1) / (freq + k1 * (1 - b + b * dl / avgdl))",
+        )
+        .with_details(vec![
+            Explanation::new(freq, "freq, occurrences of the term in the document"),
+            Explanation::new(K1, "k1, term saturation parameter"),
+            Explanation::new(B, "b, length normalization parameter"),
+            Explanation::new(doc_length, "dl, length of the document"),
+            Explanation::new(avgdl, "avgdl, average length of the documents"),
+        ]),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explanation_json() {
+        let explanation = Explanation::new(2.0, "sum of").with_details(vec![
+            Explanation::new(1.5, "term a"),
+            Explanation::new(0.5, "term b"),
+        ]);
+        let json = serde_json::to_string(&explanation.clone().boosted(2.0)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"value":4.0,"description":"product of","details":[{"value":2.0,"description":"sum of","details":[{"value":1.5,"description":"term a"},{"value":0.5,"description":"term b"}]},{"value":2.0,"description":"boost"}]}"#
+        );
+        let parsed: Explanation = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.details[0], explanation);
+        assert_eq!(explanation.clone().boosted(1.0), explanation);
+    }
+}
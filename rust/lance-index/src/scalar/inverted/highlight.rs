@@ -0,0 +1,604 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::ops::Range;
+use std::sync::{Arc, LazyLock};
+
+use arrow::array::{ListBuilder, StringBuilder, StructBuilder, UInt32Builder};
+use arrow_array::{Array, ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Fields};
+use lance_arrow::{iter_str_array, RecordBatchExt};
+use lance_core::{Error, Result};
+use snafu::location;
+
+use super::pattern::{automaton_matches, PatternAutomaton};
+use super::query::*;
+use super::InvertedIndexParams;
+
+pub const HIGHLIGHTS_COL: &str = "_highlights";
+pub const SNIPPET_COL: &str = "_snippet";
+
+static HIGHLIGHT_OFFSET_FIELDS: LazyLock<Fields> = LazyLock::new(|| {
+    Fields::from(vec![
+        Field::new("start", DataType::UInt32, false),
+        Field::new("end", DataType::UInt32, false),
+    ])
+});
+/// The byte offsets of the matched terms in the document
+pub static HIGHLIGHTS_FIELD: LazyLock<Field> = LazyLock::new(|| {
+    Field::new(
+        HIGHLIGHTS_COL,
+        DataType::List(Arc::new(Field::new(
+            "item",
+            DataType::Struct(HIGHLIGHT_OFFSET_FIELDS.clone()),
+            true,
+        ))),
+        true,
+    )
+});
+/// The best fragment of the document with the matched terms tagged
+pub static SNIPPET_FIELD: LazyLock<Field> =
+    LazyLock::new(|| Field::new(SNIPPET_COL, DataType::Utf8, true));
+
+/// Options for highlighting the full text search results
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighlightParams {
+    /// The column to highlight, it can be omitted if the query searches only one column
+    pub column: Option<String>,
+    /// The maximum number of characters in the snippet.
+    /// Default to 100.
+    pub fragment_size: usize,
+    /// The tag inserted before each matched term in the snippet.
+    /// Default to `<em>`.
+    pub pre_tag: String,
+    /// The tag inserted after each matched term in the snippet.
+    /// Default to `</em>`.
+    pub post_tag: String,
+}
+
+impl Default for HighlightParams {
+    fn default() -> Self {
+        Self {
+            column: None,
+            fragment_size: 100,
+            pre_tag: "<em>".to_owned(),
+            post_tag: "</em>".to_owned(),
+        }
+    }
+}
+
+impl HighlightParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_column(mut self, column: Option<String>) -> Self {
+        self.column = column;
+        self
+    }
+
+    pub fn with_fragment_size(mut self, fragment_size: usize) -> Self {
+        self.fragment_size = fragment_size;
+        self
+    }
+
+    pub fn with_tags(mut self, pre_tag: String, post_tag: String) -> Self {
+        self.pre_tag = pre_tag;
+        self.post_tag = post_tag;
+        self
+    }
+}
+
+// matches a single document token
+pub(crate) enum TermMatcher {
+    Exact(String),
+    Fuzzy {
+        automaton: fst::automaton::Levenshtein,
+        prefix: String,
+    },
+    Pattern(PatternAutomaton),
+}
+
+impl TermMatcher {
+    pub(crate) fn fuzzy(token: &str, fuzziness: u32, prefix_length: u32) -> Result<Self> {
+        let automaton =
+            fst::automaton::Levenshtein::new(token, fuzziness).map_err(|e| Error::Index {
+                message: format!("failed to construct the fuzzy query: {}", e),
+                location: location!(),
+            })?;
+        let prefix = token.chars().take(prefix_length as usize).collect();
+        Ok(Self::Fuzzy { automaton, prefix })
+    }
+
+    pub(crate) fn matches(&self, token: &str) -> bool {
+        match self {
+            Self::Exact(term) => term == token,
+            Self::Fuzzy { automaton, prefix } => {
+                token.starts_with(prefix.as_str()) && automaton_matches(automaton, token)
+            }
+            Self::Pattern(automaton) => automaton.matches(token),
+        }
+    }
+}
+
+// the tokens of a phrase with their positions in the query
+pub(crate) struct PhraseMatcher {
+    tokens: Vec<(String, u32)>,
+    slop: u32,
+}
+
+impl PhraseMatcher {
+    pub(crate) fn new(
+        tokenizer: &mut tantivy::tokenizer::TextAnalyzer,
+        phrase: &str,
+        slop: u32,
+    ) -> Self {
+        let mut stream = tokenizer.token_stream(phrase);
+        let mut tokens: Vec<(String, u32)> = Vec::new();
+        while let Some(token) = stream.next() {
+            // skip the expanded synonyms like the phrase search does
+            if tokens
+                .last()
+                .is_some_and(|(_, position)| *position == token.position as u32)
+            {
+                continue;
+            }
+            tokens.push((token.text.clone(), token.position as u32));
+        }
+        Self { tokens, slop }
+    }
+
+    pub(crate) fn tokens(&self) -> impl Iterator<Item = &str> {
+        self.tokens.iter().map(|(token, _)| token.as_str())
+    }
+
+    // finds the phrase occurrences in the document, the distance between
+    // consecutive phrase tokens can differ from the query by at most `slop`,
+    // the offsets of the matched tokens are appended to `dst`
+    pub(crate) fn find(&self, doc_tokens: &[DocToken], dst: &mut Vec<Range<usize>>) {
+        let Some((first, _)) = self.tokens.first() else {
+            return;
+        };
+        for (start, token) in doc_tokens.iter().enumerate() {
+            if &token.text != first {
+                continue;
+            }
+            let mut matched = vec![token.offsets.clone()];
+            let mut last = start;
+            for window in self.tokens.windows(2) {
+                let (next, expected) = (&window[1].0, window[1].1 - window[0].1);
+                let found = doc_tokens[last + 1..].iter().position(|doc_token| {
+                    let distance = doc_token.position - doc_tokens[last].position;
+                    &doc_token.text == next && distance.abs_diff(expected) <= self.slop
+                });
+                match found {
+                    Some(offset) => {
+                        last += offset + 1;
+                        matched.push(doc_tokens[last].offsets.clone());
+                    }
+                    None => break,
+                }
+            }
+            if matched.len() == self.tokens.len() {
+                dst.extend(matched);
+            }
+        }
+    }
+}
+
+pub(crate) struct DocToken {
+    pub(crate) text: String,
+    pub(crate) offsets: Range<usize>,
+    pub(crate) position: u32,
+}
+
+pub(crate) fn tokenize_doc(
+    tokenizer: &mut tantivy::tokenizer::TextAnalyzer,
+    doc: &str,
+) -> Vec<DocToken> {
+    let mut doc_tokens = Vec::new();
+    let mut stream = tokenizer.token_stream(doc);
+    while let Some(token) = stream.next() {
+        doc_tokens.push(DocToken {
+            text: token.text.clone(),
+            offsets: token.offset_from..token.offset_to,
+            position: token.position as u32,
+        });
+    }
+    doc_tokens
+}
+
+/// Finds the matched terms of a full text search query in the documents,
+/// the documents are tokenized in the same way as the index
+pub struct Highlighter {
+    column: String,
+    tokenizer: tantivy::tokenizer::TextAnalyzer,
+    query_tokenizer: tantivy::tokenizer::TextAnalyzer,
+    terms: Vec<TermMatcher>,
+    phrases: Vec<PhraseMatcher>,
+    params: HighlightParams,
+}
+
+impl std::fmt::Debug for Highlighter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Highlighter")
+            .field("column", &self.column)
+            .field("params", &self.params)
+            .finish()
+    }
+}
+
+impl Highlighter {
+    /// Creates a highlighter for the query on the column,
+    /// the `index_params` must be the params of the inverted index on the column.
+    ///
+    /// The terms of `must_not` clauses and negative boost queries are not highlighted.
+    /// The phrases are highlighted only if they appear in the document as phrases
+    /// when the index is built with positions, otherwise each term is highlighted separately.
+    pub fn try_new(
+        query: &FtsQuery,
+        column: String,
+        index_params: &InvertedIndexParams,
+        params: HighlightParams,
+    ) -> Result<Self> {
+        let mut highlighter = Self {
+            column,
+            tokenizer: index_params.build()?,
+            query_tokenizer: index_params.build_for_query()?,
+            terms: Vec::new(),
+            phrases: Vec::new(),
+            params,
+        };
+        highlighter.collect_terms(query, index_params)?;
+        Ok(highlighter)
+    }
+
+    pub fn column(&self) -> &str {
+        &self.column
+    }
+
+    fn collect_terms(
+        &mut self,
+        query: &FtsQuery,
+        index_params: &InvertedIndexParams,
+    ) -> Result<()> {
+        match query {
+            FtsQuery::Match(query) => self.collect_match_terms(query)?,
+            FtsQuery::Phrase(query) => {
+                if query.column.as_deref() != Some(self.column.as_str()) {
+                    return Ok(());
+                }
+                let phrase =
+                    PhraseMatcher::new(&mut self.query_tokenizer, &query.terms, query.slop);
+                if index_params.with_position {
+                    self.phrases.push(phrase);
+                } else {
+                    self.terms.extend(
+                        phrase
+                            .tokens()
+                            .map(|token| TermMatcher::Exact(token.to_owned())),
+                    );
+                }
+            }
+            FtsQuery::Prefix(query) => {
+                self.collect_pattern_terms(query.clone().into(), index_params)?
+            }
+            FtsQuery::Wildcard(query) => {
+                self.collect_pattern_terms(query.clone().into(), index_params)?
+            }
+            FtsQuery::Regexp(query) => {
+                self.collect_pattern_terms(query.clone().into(), index_params)?
+            }
+            FtsQuery::Boost(query) => self.collect_terms(&query.positive, index_params)?,
+            FtsQuery::MultiMatch(query) => {
+                for query in &query.match_queries {
+                    self.collect_match_terms(query)?;
+                }
+            }
+            FtsQuery::Boolean(query) => {
+                for query in query.must.iter().chain(query.should.iter()) {
+                    self.collect_terms(query, index_params)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn collect_pattern_terms(
+        &mut self,
+        query: TermPatternQuery,
+        index_params: &InvertedIndexParams,
+    ) -> Result<()> {
+        if query.column.as_deref() == Some(self.column.as_str()) {
+            let automaton = PatternAutomaton::try_new_for_index(&query.pattern, index_params)?;
+            self.terms.push(TermMatcher::Pattern(automaton));
+        }
+        Ok(())
+    }
+
+    fn collect_match_terms(&mut self, query: &MatchQuery) -> Result<()> {
+        if query.column.as_deref() != Some(self.column.as_str()) {
+            return Ok(());
+        }
+        let tokens = collect_tokens(&query.terms, &mut self.query_tokenizer, None);
+        for token in tokens {
+            let fuzziness = match query.fuzziness {
+                Some(fuzziness) => fuzziness,
+                None => MatchQuery::auto_fuzziness(&token),
+            };
+            if fuzziness == 0 {
+                self.terms.push(TermMatcher::Exact(token));
+                continue;
+            }
+            self.terms
+                .push(TermMatcher::fuzzy(&token, fuzziness, query.prefix_length)?);
+        }
+        Ok(())
+    }
+
+    /// Returns the byte ranges of the matched terms in the document, sorted by offset
+    pub fn highlights(&mut self, doc: &str) -> Vec<Range<usize>> {
+        let doc_tokens = tokenize_doc(&mut self.tokenizer, doc);
+        let mut highlights = doc_tokens
+            .iter()
+            .filter(|token| self.terms.iter().any(|term| term.matches(&token.text)))
+            .map(|token| token.offsets.clone())
+            .collect::<Vec<_>>();
+        for phrase in &self.phrases {
+            phrase.find(&doc_tokens, &mut highlights);
+        }
+
+        highlights.sort_unstable_by_key(|range| (range.start, range.end));
+        highlights.dedup();
+        highlights
+    }
+
+    /// Returns the fragment of the document containing the most matched terms,
+    /// with the matched terms surrounded by the tags
+    pub fn snippet(&self, doc: &str, highlights: &[Range<usize>]) -> String {
+        let fragment_size = self.params.fragment_size;
+        // the byte offset after `n` characters from `start`
+        let advance = |start: usize, n: usize| {
+            doc[start..]
+                .char_indices()
+                .nth(n)
+                .map(|(i, _)| start + i)
+                .unwrap_or(doc.len())
+        };
+
+        // find the window starting at a match that covers the most matches
+        let mut best = (0, 0);
+        for (i, highlight) in highlights.iter().enumerate() {
+            let end = advance(highlight.start, fragment_size);
+            let count = highlights[i..]
+                .iter()
+                .take_while(|range| range.end <= end)
+                .count();
+            if count > best.1 {
+                best = (i, count);
+            }
+        }
+
+        let (start, end) = if best.1 == 0 {
+            (0, advance(0, fragment_size))
+        } else {
+            // center the matches in the fragment
+            let first = highlights[best.0].start;
+            let last = highlights[best.0 + best.1 - 1].end;
+            let span = doc[first..last].chars().count();
+            let before = (fragment_size - span.min(fragment_size)) / 2;
+            let start = doc[..first]
+                .char_indices()
+                .rev()
+                .take(before)
+                .last()
+                .map(|(i, _)| i)
+                .unwrap_or(first);
+            let end = advance(start, fragment_size).max(last);
+            (start, end)
+        };
+
+        let mut snippet = String::with_capacity(end - start + 16);
+        let mut offset = start;
+        for range in highlights
+            .iter()
+            .filter(|range| range.start >= start && range.end <= end)
+        {
+            // the highlights may overlap with n-gram tokenizers
+            if range.start < offset {
+                continue;
+            }
+            snippet.push_str(&doc[offset..range.start]);
+            snippet.push_str(&self.params.pre_tag);
+            snippet.push_str(&doc[range.clone()]);
+            snippet.push_str(&self.params.post_tag);
+            offset = range.end;
+        }
+        snippet.push_str(&doc[offset..end]);
+        snippet
+    }
+
+    /// Appends the highlights and snippet columns to the batch
+    pub fn highlight_batch(&mut self, batch: RecordBatch) -> Result<RecordBatch> {
+        let docs = batch.column_by_name(&self.column).ok_or_else(|| {
+            Error::invalid_input(
+                format!("column {} to highlight not found in batch", self.column),
+                location!(),
+            )
+        })?;
+        if !matches!(docs.data_type(), DataType::Utf8 | DataType::LargeUtf8) {
+            return Err(Error::invalid_input(
+                format!(
+                    "highlighting is only supported on string columns, but column {} is {}",
+                    self.column,
+                    docs.data_type()
+                ),
+                location!(),
+            ));
+        }
+
+        let offset_builder = StructBuilder::from_fields(HIGHLIGHT_OFFSET_FIELDS.clone(), 0);
+        let mut highlights_builder = ListBuilder::new(offset_builder);
+        let mut snippet_builder = StringBuilder::with_capacity(docs.len(), docs.len() * 32);
+        for doc in iter_str_array(docs) {
+            let Some(doc) = doc else {
+                highlights_builder.append_null();
+                snippet_builder.append_null();
+                continue;
+            };
+            let highlights = self.highlights(doc);
+            let offsets = highlights_builder.values();
+            for range in &highlights {
+                offsets
+                    .field_builder::<UInt32Builder>(0)
+                    .unwrap()
+                    .append_value(range.start as u32);
+                offsets
+                    .field_builder::<UInt32Builder>(1)
+                    .unwrap()
+                    .append_value(range.end as u32);
+                offsets.append(true);
+            }
+            highlights_builder.append(true);
+            snippet_builder.append_value(self.snippet(doc, &highlights));
+        }
+
+        let batch = batch.try_with_column(
+            HIGHLIGHTS_FIELD.clone(),
+            Arc::new(highlights_builder.finish()) as ArrayRef,
+        )?;
+        let batch = batch.try_with_column(
+            SNIPPET_FIELD.clone(),
+            Arc::new(snippet_builder.finish()) as ArrayRef,
+        )?;
+        Ok(batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::AsArray;
+    use arrow::datatypes::UInt32Type;
+    use arrow_array::StringArray;
+    use arrow_schema::Schema;
+
+    use super::*;
+
+    fn highlighter(query: FtsQuery, index_params: InvertedIndexParams) -> Highlighter {
+        Highlighter::try_new(
+            &query,
+            "text".to_owned(),
+            &index_params,
+            HighlightParams::new().with_fragment_size(30),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_highlight_terms() {
+        let query = BooleanQuery::new([
+            (
+                Occur::Should,
+                MatchQuery::new("Rust".to_owned())
+                    .with_column(Some("text".to_owned()))
+                    .into(),
+            ),
+            (
+                Occur::Should,
+                PrefixQuery::new("colum".to_owned())
+                    .with_column(Some("text".to_owned()))
+                    .into(),
+            ),
+            (
+                Occur::MustNot,
+                MatchQuery::new("draft".to_owned())
+                    .with_column(Some("text".to_owned()))
+                    .into(),
+            ),
+            // other columns are not highlighted
+            (
+                Occur::Should,
+                MatchQuery::new("format".to_owned())
+                    .with_column(Some("title".to_owned()))
+                    .into(),
+            ),
+        ]);
+        let mut highlighter = highlighter(query.into(), InvertedIndexParams::default());
+
+        let doc = "A draft about rust and the columnar format in Rust";
+        let highlights = highlighter.highlights(doc);
+        let matched = highlights
+            .iter()
+            .map(|range| &doc[range.clone()])
+            .collect::<Vec<_>>();
+        assert_eq!(matched, vec!["rust", "columnar", "Rust"]);
+
+        // the fragment covers the most matches
+        let snippet = highlighter.snippet(doc, &highlights);
+        assert!(snippet.chars().count() <= 30 + "<em></em>".len() * 3);
+        assert!(snippet.contains("<em>columnar</em>"), "{}", snippet);
+        assert!(snippet.contains("<em>rust</em>"), "{}", snippet);
+
+        // no match
+        let highlights = highlighter.highlights("nothing to see here");
+        assert!(highlights.is_empty());
+        assert_eq!(
+            highlighter.snippet("nothing to see here", &highlights),
+            "nothing to see here"
+        );
+    }
+
+    #[test]
+    fn test_highlight_phrase() {
+        let query: FtsQuery = PhraseQuery::new("columnar format".to_owned())
+            .with_column(Some("text".to_owned()))
+            .into();
+        let doc = "format of columnar data, the columnar format";
+
+        // with positions only the phrase is highlighted
+        let mut with_position = highlighter(
+            query.clone(),
+            InvertedIndexParams::default().with_position(true),
+        );
+        let highlights = with_position.highlights(doc);
+        assert_eq!(highlights, vec![29..37, 38..44]);
+
+        // without positions all the terms are highlighted
+        let mut without_position = highlighter(query, InvertedIndexParams::default());
+        let highlights = without_position.highlights(doc);
+        assert_eq!(highlights.len(), 4);
+    }
+
+    #[test]
+    fn test_highlight_batch() {
+        let query: FtsQuery = MatchQuery::new("lance".to_owned())
+            .with_column(Some("text".to_owned()))
+            .into();
+        let mut highlighter = highlighter(query, InvertedIndexParams::default());
+        let docs = StringArray::from(vec![Some("lance is a format"), None, Some("lance lance")]);
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new("text", DataType::Utf8, true)])),
+            vec![Arc::new(docs)],
+        )
+        .unwrap();
+        let batch = highlighter.highlight_batch(batch).unwrap();
+
+        let highlights = batch[HIGHLIGHTS_COL].as_list::<i32>();
+        assert!(highlights.is_null(1));
+        let offsets = highlights.value(2);
+        let offsets = offsets.as_struct();
+        assert_eq!(
+            offsets.column(0).as_primitive::<UInt32Type>().values(),
+            &[0, 6]
+        );
+        assert_eq!(
+            offsets.column(1).as_primitive::<UInt32Type>().values(),
+            &[5, 11]
+        );
+
+        let snippets = batch[SNIPPET_COL].as_string::<i32>();
+        assert_eq!(snippets.value(0), "<em>lance</em> is a format");
+        assert!(snippets.is_null(1));
+        assert_eq!(snippets.value(2), "<em>lance</em> <em>lance</em>");
+    }
+}
@@ -54,6 +54,7 @@ use super::{
         BLOCK_SIZE,
     },
     iter::PlainPostingListIterator,
+    pattern::PatternAutomaton,
     query::*,
     scorer::{idf, BM25Scorer, Scorer, B, K1},
 };
@@ -66,7 +67,7 @@ use super::{
     encoding::compress_positions,
     iter::{PostingListIterator, TokenIterator, TokenSource},
 };
-use super::{wand::*, InvertedIndexBuilder, InvertedIndexParams};
+use super::{wand::*, InvertedIndexBuilder, InvertedIndexParams, TokenizerRegistry};
 use crate::frag_reuse::FragReuseIndex;
 use crate::scalar::{
     AnyQuery, IndexReader, IndexStore, MetricsCollector, SargableQuery, ScalarIndex, SearchResult,
@@ -100,6 +101,7 @@ pub struct InvertedIndex {
     params: InvertedIndexParams,
     store: Arc<dyn IndexStore>,
     tokenizer: tantivy::tokenizer::TextAnalyzer,
+    query_tokenizer: tantivy::tokenizer::TextAnalyzer,
     pub(crate) partitions: Vec<Arc<InvertedPartition>>,
 }
 
@@ -132,14 +134,42 @@ impl InvertedIndex {
         }
     }
 
+    /// The tokenizer for the documents
     pub fn tokenizer(&self) -> tantivy::tokenizer::TextAnalyzer {
         self.tokenizer.clone()
     }
 
+    /// The tokenizer for the queries, it expands the synonyms if configured so
+    pub fn query_tokenizer(&self) -> tantivy::tokenizer::TextAnalyzer {
+        self.query_tokenizer.clone()
+    }
+
     pub fn params(&self) -> &InvertedIndexParams {
         &self.params
     }
 
+    // expand the pattern to the tokens matching it across all partitions,
+    // returns at most `max_expansions` tokens in lexicographic order
+    pub fn expand_pattern(
+        &self,
+        pattern: &TermPattern,
+        max_expansions: usize,
+    ) -> Result<Vec<String>> {
+        let automaton = self.pattern_automaton(pattern)?;
+        let mut tokens = Vec::new();
+        for part in &self.partitions {
+            tokens.extend(part.expand_pattern(&automaton, max_expansions)?);
+        }
+        tokens.sort_unstable();
+        tokens.dedup();
+        tokens.truncate(max_expansions);
+        Ok(tokens)
+    }
+
+    fn pattern_automaton(&self, pattern: &TermPattern) -> Result<PatternAutomaton> {
+        PatternAutomaton::try_new_for_index(pattern, &self.params)
+    }
+
     // search the documents that contain the query
     // return the row ids of the documents sorted by bm25 score
     // ref: https://en.wikipedia.org/wiki/Okapi_BM25
@@ -148,7 +178,7 @@ impl InvertedIndex {
     #[instrument(level = "debug", skip_all)]
     pub async fn bm25_search(
         &self,
-        tokens: Arc<Vec<String>>,
+        tokens: Arc<Tokens>,
         params: Arc<FtsSearchParams>,
         operator: Operator,
         prefilter: Arc<dyn PreFilter>,
@@ -186,7 +216,7 @@ impl InvertedIndex {
         while let Some(res) = parts.try_next().await? {
             for (row_id, freq, length) in res? {
                 let mut score = 0.0;
-                for token in tokens.iter() {
+                for token in tokens.tokens() {
                     score += scorer.score(token, freq, length);
                 }
                 if candidates.len() < limit {
@@ -205,10 +235,76 @@ impl InvertedIndex {
             .unzip())
     }
 
+    /// Load the index, the custom base tokenizers are resolved by the registry
+    pub async fn load_with_tokenizer_registry(
+        store: Arc<dyn IndexStore>,
+        frag_reuse_index: Option<Arc<FragReuseIndex>>,
+        index_cache: LanceCache,
+        tokenizer_registry: Option<Arc<TokenizerRegistry>>,
+    ) -> Result<Arc<Self>> {
+        // for new index format, there is a metadata file and multiple partitions,
+        // each partition is a separate index containing tokens, inverted list and docs.
+        // for old index format, there is no metadata file, and it's just like a single partition
+
+        match store.open_index_file(METADATA_FILE).await {
+            Ok(reader) => {
+                let params = reader.schema().metadata.get("params").ok_or(Error::Index {
+                    message: "params not found in metadata".to_owned(),
+                    location: location!(),
+                })?;
+                let mut params = serde_json::from_str::<InvertedIndexParams>(params)?;
+                if let Some(registry) = tokenizer_registry {
+                    params = params.with_tokenizer_registry(registry);
+                }
+                let partitions =
+                    reader
+                        .schema()
+                        .metadata
+                        .get("partitions")
+                        .ok_or(Error::Index {
+                            message: "partitions not found in metadata".to_owned(),
+                            location: location!(),
+                        })?;
+                let partitions: Vec<u64> = serde_json::from_str(partitions)?;
+
+                let partitions = partitions.into_iter().map(|id| {
+                    let store = store.clone();
+                    let frag_reuse_index_clone = frag_reuse_index.clone();
+                    let index_cache = index_cache.clone();
+                    async move {
+                        Result::Ok(Arc::new(
+                            InvertedPartition::load(store, id, frag_reuse_index_clone, index_cache)
+                                .await?,
+                        ))
+                    }
+                });
+                let partitions = stream::iter(partitions)
+                    .buffer_unordered(store.io_parallelism())
+                    .try_collect::<Vec<_>>()
+                    .await?;
+                let tokenizer = params.build()?;
+                let query_tokenizer = params.build_for_query()?;
+                Ok(Arc::new(Self {
+                    params,
+                    store,
+                    tokenizer,
+                    query_tokenizer,
+                    partitions,
+                }))
+            }
+            Err(_) => {
+                // old index format
+                Self::load_legacy_index(store, frag_reuse_index, index_cache, tokenizer_registry)
+                    .await
+            }
+        }
+    }
+
     async fn load_legacy_index(
         store: Arc<dyn IndexStore>,
         frag_reuse_index: Option<Arc<FragReuseIndex>>,
         index_cache: LanceCache,
+        tokenizer_registry: Option<Arc<TokenizerRegistry>>,
     ) -> Result<Arc<Self>> {
         log::warn!("loading legacy FTS index");
         let tokens_fut = tokio::spawn({
@@ -244,16 +340,21 @@ impl InvertedIndex {
             }
         });
 
-        let (tokenizer_config, tokens) = tokens_fut.await??;
+        let (mut tokenizer_config, tokens) = tokens_fut.await??;
         let inverted_list = invert_list_fut.await??;
         let docs = docs_fut.await??;
 
+        if let Some(registry) = tokenizer_registry {
+            tokenizer_config = tokenizer_config.with_tokenizer_registry(registry);
+        }
         let tokenizer = tokenizer_config.build()?;
+        let query_tokenizer = tokenizer_config.build_for_query()?;
 
         Ok(Arc::new(Self {
             params: tokenizer_config,
             store: store.clone(),
             tokenizer,
+            query_tokenizer,
             partitions: vec![Arc::new(InvertedPartition {
                 id: 0,
                 store,
@@ -348,56 +449,7 @@ impl ScalarIndex for InvertedIndex {
     where
         Self: Sized,
     {
-        // for new index format, there is a metadata file and multiple partitions,
-        // each partition is a separate index containing tokens, inverted list and docs.
-        // for old index format, there is no metadata file, and it's just like a single partition
-
-        match store.open_index_file(METADATA_FILE).await {
-            Ok(reader) => {
-                let params = reader.schema().metadata.get("params").ok_or(Error::Index {
-                    message: "params not found in metadata".to_owned(),
-                    location: location!(),
-                })?;
-                let params = serde_json::from_str::<InvertedIndexParams>(params)?;
-                let partitions =
-                    reader
-                        .schema()
-                        .metadata
-                        .get("partitions")
-                        .ok_or(Error::Index {
-                            message: "partitions not found in metadata".to_owned(),
-                            location: location!(),
-                        })?;
-                let partitions: Vec<u64> = serde_json::from_str(partitions)?;
-
-                let partitions = partitions.into_iter().map(|id| {
-                    let store = store.clone();
-                    let frag_reuse_index_clone = frag_reuse_index.clone();
-                    let index_cache = index_cache.clone();
-                    async move {
-                        Result::Ok(Arc::new(
-                            InvertedPartition::load(store, id, frag_reuse_index_clone, index_cache)
-                                .await?,
-                        ))
-                    }
-                });
-                let partitions = stream::iter(partitions)
-                    .buffer_unordered(store.io_parallelism())
-                    .try_collect::<Vec<_>>()
-                    .await?;
-                let tokenizer = params.build()?;
-                Ok(Arc::new(Self {
-                    params,
-                    store,
-                    tokenizer,
-                    partitions,
-                }))
-            }
-            Err(_) => {
-                // old index format
-                Self::load_legacy_index(store, frag_reuse_index, index_cache).await
-            }
-        }
+        Self::load_with_tokenizer_registry(store, frag_reuse_index, index_cache, None).await
     }
 
     async fn remap(
@@ -468,9 +520,12 @@ impl InvertedPartition {
         self.tokens.get(token)
     }
 
-    pub fn expand_fuzzy(&self, tokens: &[String], params: &FtsSearchParams) -> Result<Vec<String>> {
+    /// Expands each token to the tokens within the fuzziness,
+    /// the expanded tokens share the position of the original token
+    pub fn expand_fuzzy(&self, tokens: &Tokens, params: &FtsSearchParams) -> Result<Tokens> {
         let mut new_tokens = Vec::with_capacity(min(tokens.len(), params.max_expansions));
-        for token in tokens {
+        let mut new_positions = Vec::with_capacity(new_tokens.capacity());
+        for (token, position) in tokens.iter() {
             let fuzziness = match params.fuzziness {
                 Some(fuzziness) => fuzziness,
                 None => MatchQuery::auto_fuzziness(token),
@@ -500,8 +555,26 @@ impl InvertedPartition {
                     location: location!(),
                 });
             }
+            new_positions.resize(new_tokens.len(), position);
+        }
+        Ok(Tokens::new(new_tokens, new_positions))
+    }
+
+    pub fn expand_pattern(
+        &self,
+        automaton: &PatternAutomaton,
+        max_expansions: usize,
+    ) -> Result<Vec<String>> {
+        let mut tokens = Vec::new();
+        if let TokenMap::Fst(ref map) = self.tokens.tokens {
+            take_fst_keys(map.search(automaton), &mut tokens, max_expansions);
+        } else {
+            return Err(Error::Index {
+                message: "tokens is not fst, which is not expected".to_owned(),
+                location: location!(),
+            });
         }
-        Ok(new_tokens)
+        Ok(tokens)
     }
 
     // search the documents that contain the query
@@ -510,7 +583,7 @@ impl InvertedPartition {
     #[instrument(level = "debug", skip_all)]
     pub async fn bm25_search(
         &self,
-        tokens: &[String],
+        tokens: &Tokens,
         params: &FtsSearchParams,
         operator: Operator,
         mask: Arc<RowIdMask>,
@@ -520,13 +593,13 @@ impl InvertedPartition {
         let is_phrase_query = params.phrase_slop.is_some();
         let tokens = match is_fuzzy {
             true => self.expand_fuzzy(tokens, params)?,
-            false => tokens.to_vec(),
+            false => tokens.clone(),
         };
         let mut token_ids = Vec::with_capacity(tokens.len());
-        for token in tokens {
+        for (token, position) in tokens {
             let token_id = self.map(&token);
             if let Some(token_id) = token_id {
-                token_ids.push((token_id, token));
+                token_ids.push((token_id, token, position));
             } else if is_phrase_query {
                 // if the token is not found, we can't do phrase query
                 return Ok(Vec::new());
@@ -537,25 +610,20 @@ impl InvertedPartition {
         }
         if !is_phrase_query {
             // remove duplicates
-            token_ids.sort_unstable_by_key(|(token_id, _)| *token_id);
-            token_ids.dedup_by_key(|(token_id, _)| *token_id);
+            token_ids.sort_unstable_by_key(|(token_id, _, _)| *token_id);
+            token_ids.dedup_by_key(|(token_id, _, _)| *token_id);
         }
 
         let num_docs = self.docs.len();
         let postings = stream::iter(token_ids)
-            .enumerate()
-            .map(|(position, (token_id, token))| async move {
+            .map(|(token_id, token, position)| async move {
                 let posting = self
                     .inverted_list
                     .posting_list(token_id, is_phrase_query, metrics)
                     .await?;
 
                 Result::Ok(PostingIterator::new(
-                    token,
-                    token_id,
-                    position as u32,
-                    posting,
-                    num_docs,
+                    token, token_id, position, posting, num_docs,
                 ))
             })
             .buffered(self.store.io_parallelism())
@@ -2011,6 +2079,103 @@ pub fn flat_bm25_search(
     Ok(batch)
 }
 
+// score the documents by the tokens matching the pattern,
+// the tokens don't exist in the index are treated as they appear in 1 document
+#[allow(clippy::too_many_arguments)]
+pub fn flat_pattern_search(
+    batch: RecordBatch,
+    doc_col: &str,
+    automaton: &PatternAutomaton,
+    nq: &HashMap<String, usize>,
+    tokenizer: &mut tantivy::tokenizer::TextAnalyzer,
+    avgdl: f32,
+    num_docs: usize,
+) -> std::result::Result<RecordBatch, DataFusionError> {
+    let doc_iter = iter_str_array(&batch[doc_col]);
+    let mut scores = Vec::with_capacity(batch.num_rows());
+    for doc in doc_iter {
+        let Some(doc) = doc else {
+            scores.push(0.0);
+            continue;
+        };
+
+        let doc_tokens = collect_tokens(doc, tokenizer, None);
+        let doc_norm = K1 * (1.0 - B + B * doc_tokens.len() as f32 / avgdl);
+        let mut doc_token_count = HashMap::new();
+        for token in doc_tokens {
+            if !automaton.matches(&token) {
+                continue;
+            }
+            doc_token_count
+                .entry(token)
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+        let mut score = 0.0;
+        for (token, freq) in doc_token_count {
+            let freq = freq as f32;
+            let idf = idf(nq.get(&token).copied().unwrap_or(1), num_docs);
+            score += idf * (freq * (K1 + 1.0) / (freq + doc_norm));
+        }
+        scores.push(score);
+    }
+
+    let score_col = Arc::new(Float32Array::from(scores)) as ArrayRef;
+    let batch = batch
+        .try_with_column(SCORE_FIELD.clone(), score_col)?
+        .project_by_schema(&FTS_SCHEMA)?;
+    Ok(batch)
+}
+
+pub fn flat_pattern_search_stream(
+    input: SendableRecordBatchStream,
+    doc_col: String,
+    pattern: TermPattern,
+    max_expansions: usize,
+    index: &InvertedIndex,
+) -> Result<SendableRecordBatchStream> {
+    let mut tokenizer = index.tokenizer.clone();
+    let automaton = index.pattern_automaton(&pattern)?;
+
+    let bm25_scorer = BM25Scorer::new(index.partitions.iter().map(|p| p.as_ref()));
+    let num_docs = bm25_scorer.num_docs();
+    let avgdl = bm25_scorer.avgdl();
+    let tokens = index.expand_pattern(&pattern, max_expansions)?;
+    let mut nq = HashMap::with_capacity(tokens.len());
+    for token in tokens {
+        let token_nq = bm25_scorer.nq(&token).max(1);
+        nq.insert(token, token_nq);
+    }
+    let stream = input.map(move |batch| {
+        let batch = batch?;
+        let batch = flat_pattern_search(
+            batch,
+            &doc_col,
+            &automaton,
+            &nq,
+            &mut tokenizer,
+            avgdl,
+            num_docs,
+        )?;
+
+        // filter out rows with score 0
+        let score_col = batch[SCORE_COL].as_primitive::<Float32Type>();
+        let mask = score_col
+            .iter()
+            .map(|score| score.is_some_and(|score| score > 0.0))
+            .collect::<Vec<_>>();
+        let mask = BooleanArray::from(mask);
+        let batch = arrow::compute::filter_record_batch(&batch, &mask)?;
+        debug_assert!(batch[ROW_ID].null_count() == 0, "flat FTS produces nulls");
+        Ok(batch)
+    });
+
+    Ok(
+        Box::pin(RecordBatchStreamAdapter::new(FTS_SCHEMA.clone(), stream))
+            as SendableRecordBatchStream,
+    )
+}
+
 pub fn flat_bm25_search_stream(
     input: SendableRecordBatchStream,
     doc_col: String,
@@ -2018,7 +2183,8 @@ pub fn flat_bm25_search_stream(
     index: &InvertedIndex,
 ) -> SendableRecordBatchStream {
     let mut tokenizer = index.tokenizer.clone();
-    let tokens = collect_tokens(&query, &mut tokenizer, None)
+    let mut query_tokenizer = index.query_tokenizer.clone();
+    let tokens = collect_tokens(&query, &mut query_tokenizer, None)
         .into_iter()
         .sorted_unstable()
         .collect::<HashSet<_>>();
@@ -2,14 +2,20 @@
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use lance_core::Result;
+use lance_core::cache::LanceCache;
+use lance_core::{Error, Result};
+use snafu::location;
 
 use crate::scalar::IndexStore;
 
 use super::{
-    builder::{doc_file_path, posting_file_path, token_file_path, InnerBuilder, PositionRecorder},
-    InvertedPartition, PostingListBuilder,
+    builder::{
+        doc_file_path, posting_file_path, token_file_path, write_metadata, InnerBuilder,
+        PositionRecorder, LANCE_FTS_TARGET_SIZE,
+    },
+    InvertedIndexParams, InvertedPartition, PostingListBuilder, METADATA_FILE,
 };
 
 pub trait Merger {
@@ -168,3 +174,61 @@ impl Merger for SizeBasedMerger<'_> {
         Ok(self.partitions.clone())
     }
 }
+
+/// Merge the segments of an inverted index built over disjoint sets of rows.
+///
+/// Each segment is a complete index in its own store, built with the same params.
+/// The partitions of all the segments are merged by size into `dest_store`.
+pub async fn merge_index_segments(
+    segments: &[Arc<dyn IndexStore>],
+    dest_store: &dyn IndexStore,
+) -> Result<()> {
+    let mut params: Option<(String, InvertedIndexParams)> = None;
+    let mut partitions = Vec::new();
+    for store in segments {
+        let reader = store.open_index_file(METADATA_FILE).await?;
+        let metadata = &reader.schema().metadata;
+        let get = |key: &str| {
+            metadata.get(key).ok_or(Error::Index {
+                message: format!("{} not found in metadata", key),
+                location: location!(),
+            })
+        };
+        let segment_params = get("params")?;
+        match &params {
+            Some((first_params, _)) if first_params != segment_params => {
+                return Err(Error::invalid_input(
+                    format!(
+                        "merging inverted index segments: params {} don't match {}",
+                        segment_params, first_params
+                    ),
+                    location!(),
+                ));
+            }
+            Some(_) => {}
+            None => {
+                params = Some((
+                    segment_params.clone(),
+                    serde_json::from_str(segment_params)?,
+                ));
+            }
+        }
+
+        let ids: Vec<u64> = serde_json::from_str(get("partitions")?)?;
+        for id in ids {
+            partitions.push(
+                InvertedPartition::load(store.clone(), id, None, LanceCache::no_cache()).await?,
+            );
+        }
+    }
+    let Some((_, params)) = params else {
+        return Err(Error::invalid_input(
+            "merging inverted index segments: no segment to merge",
+            location!(),
+        ));
+    };
+
+    let mut merger = SizeBasedMerger::new(dest_store, partitions, *LANCE_FTS_TARGET_SIZE << 20);
+    let partitions = merger.merge().await?;
+    write_metadata(dest_store, &params, &partitions).await
+}
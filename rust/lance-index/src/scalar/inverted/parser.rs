@@ -0,0 +1,686 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use lance_core::{Error, Result};
+use snafu::location;
+
+use super::query::*;
+
+/// Parses Lucene-like query strings into [`FtsQuery`].
+///
+/// The supported syntax:
+/// - `term`: match the term, the column is determined at query time
+/// - `column:term`: match the term in the column
+/// - `"a phrase"`, `"a phrase"~2`: phrase query with optional slop
+/// - `term~`, `term~1`: fuzzy match with automatic or the given edit distance
+/// - `prefix*`, `te?m*`: prefix and wildcard queries
+/// - `/regexp/`: regular expression query
+/// - `term^2.0`, `(a b)^0.5`: boost the score of a clause
+/// - `AND`, `&&`, `OR`, `||`, `NOT`, `!`, `+term`, `-term`: boolean operators
+/// - `column:(a OR b)`: group clauses, the column applies to all of them
+///
+/// Clauses without an operator are combined with the default operator,
+/// which is `OR` by default.
+#[derive(Debug, Clone, Default)]
+pub struct QueryStringParser {
+    default_operator: Operator,
+}
+
+impl QueryStringParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_default_operator(mut self, operator: Operator) -> Self {
+        self.default_operator = operator;
+        self
+    }
+
+    pub fn parse(&self, query: &str) -> Result<FtsQuery> {
+        let tokens = lex(query)?;
+        if tokens.is_empty() {
+            return Err(Error::invalid_input(
+                "the query string is empty".to_owned(),
+                location!(),
+            ));
+        }
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: query.len(),
+            default_operator: self.default_operator,
+        };
+        let query = parser.parse_query(None)?;
+        if let Some((pos, token)) = parser.peek() {
+            return Err(parse_error(*pos, format!("unexpected {}", token)));
+        }
+        Ok(query)
+    }
+}
+
+/// Parses the query string with the default settings of [`QueryStringParser`]
+pub fn parse_query_string(query: &str) -> Result<FtsQuery> {
+    QueryStringParser::new().parse(query)
+}
+
+fn parse_error(pos: usize, message: impl AsRef<str>) -> Error {
+    Error::invalid_input(
+        format!(
+            "failed to parse query string at position {}: {}",
+            pos,
+            message.as_ref()
+        ),
+        location!(),
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    Colon,
+    Plus,
+    Minus,
+    Not,
+    And,
+    Or,
+    // the unescaped text and the raw text with escapes,
+    // the raw text is used to tell the escaped wildcards from the real ones
+    Term { text: String, raw: String },
+    Phrase(String),
+    Regexp(String),
+    // `~` with optional distance or slop
+    Tilde(Option<u32>),
+    Caret(f32),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::LParen => write!(f, "'('"),
+            Self::RParen => write!(f, "')'"),
+            Self::Colon => write!(f, "':'"),
+            Self::Plus => write!(f, "'+'"),
+            Self::Minus => write!(f, "'-'"),
+            Self::Not => write!(f, "NOT"),
+            Self::And => write!(f, "AND"),
+            Self::Or => write!(f, "OR"),
+            Self::Term { raw, .. } => write!(f, "term '{}'", raw),
+            Self::Phrase(phrase) => write!(f, "phrase \"{}\"", phrase),
+            Self::Regexp(pattern) => write!(f, "regexp /{}/", pattern),
+            Self::Tilde(_) => write!(f, "'~'"),
+            Self::Caret(_) => write!(f, "'^'"),
+        }
+    }
+}
+
+fn is_term_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '(' | ')' | ':' | '"' | '^' | '~')
+}
+
+fn lex(query: &str) -> Result<Vec<(usize, Token)>> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+    while let Some(&(pos, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | ':' => {
+                chars.next();
+                let token = match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    _ => Token::Colon,
+                };
+                tokens.push((pos, token));
+            }
+            '"' | '/' => {
+                chars.next();
+                let mut text = String::new();
+                let mut closed = false;
+                while let Some((_, ch)) = chars.next() {
+                    match ch {
+                        '\\' => match chars.next() {
+                            // keep the escapes except for the delimiter,
+                            // so that the regexp escapes still work
+                            Some((_, escaped)) if escaped == c => text.push(escaped),
+                            Some((_, escaped)) => {
+                                if c == '/' {
+                                    text.push('\\');
+                                }
+                                text.push(escaped);
+                            }
+                            None => break,
+                        },
+                        ch if ch == c => {
+                            closed = true;
+                            break;
+                        }
+                        ch => text.push(ch),
+                    }
+                }
+                if !closed {
+                    let what = if c == '"' { "phrase" } else { "regexp" };
+                    return Err(parse_error(pos, format!("unterminated {}", what)));
+                }
+                let token = match c {
+                    '"' => Token::Phrase(text),
+                    _ => Token::Regexp(text),
+                };
+                tokens.push((pos, token));
+            }
+            '~' | '^' => {
+                chars.next();
+                let mut number = String::new();
+                while let Some(&(_, ch)) = chars.peek() {
+                    if !ch.is_ascii_digit() && ch != '.' {
+                        break;
+                    }
+                    number.push(ch);
+                    chars.next();
+                }
+                let token = if c == '~' {
+                    if number.is_empty() {
+                        Token::Tilde(None)
+                    } else {
+                        let distance = number.parse::<u32>().map_err(|_| {
+                            parse_error(
+                                pos,
+                                format!("expected an integer after '~', found '{}'", number),
+                            )
+                        })?;
+                        Token::Tilde(Some(distance))
+                    }
+                } else {
+                    let boost = number.parse::<f32>().map_err(|_| {
+                        parse_error(
+                            pos,
+                            format!("expected a number after '^', found '{}'", number),
+                        )
+                    })?;
+                    Token::Caret(boost)
+                };
+                tokens.push((pos, token));
+            }
+            '+' | '-' | '!'
+                if chars
+                    .clone()
+                    .nth(1)
+                    .is_some_and(|(_, next)| !next.is_whitespace()) =>
+            {
+                chars.next();
+                let token = match c {
+                    '+' => Token::Plus,
+                    '-' => Token::Minus,
+                    _ => Token::Not,
+                };
+                tokens.push((pos, token));
+            }
+            _ => {
+                let mut text = String::new();
+                let mut raw = String::new();
+                while let Some(&(_, ch)) = chars.peek() {
+                    if ch == '\\' {
+                        chars.next();
+                        if let Some((_, escaped)) = chars.next() {
+                            text.push(escaped);
+                            raw.push('\\');
+                            raw.push(escaped);
+                        }
+                        continue;
+                    }
+                    if !is_term_char(ch) {
+                        break;
+                    }
+                    text.push(ch);
+                    raw.push(ch);
+                    chars.next();
+                }
+                let token = match raw.as_str() {
+                    "AND" | "&&" => Token::And,
+                    "OR" | "||" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => Token::Term { text, raw },
+                };
+                tokens.push((pos, token));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    // the length of the query string, for reporting errors at the end
+    end: usize,
+    default_operator: Operator,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn current_pos(&self) -> usize {
+        self.peek().map(|(pos, _)| *pos).unwrap_or(self.end)
+    }
+
+    // parses the clauses until the end of the query or the closing parenthesis
+    fn parse_query(&mut self, column: Option<&str>) -> Result<FtsQuery> {
+        let start = self.current_pos();
+        let mut clauses: Vec<(Occur, FtsQuery)> = Vec::new();
+        while let Some((pos, token)) = self.peek() {
+            if *token == Token::RParen {
+                break;
+            }
+
+            let pos = *pos;
+            let conjunction = match token {
+                Token::And | Token::Or => {
+                    let conjunction = token.clone();
+                    if clauses.is_empty() {
+                        return Err(parse_error(pos, format!("unexpected {}", conjunction)));
+                    }
+                    self.next();
+                    Some(conjunction)
+                }
+                _ => None,
+            };
+            let modifier = match self.peek() {
+                Some((_, token @ (Token::Plus | Token::Minus | Token::Not))) => {
+                    let modifier = token.clone();
+                    self.next();
+                    Some(modifier)
+                }
+                _ => None,
+            };
+            let query = self.parse_clause(column)?;
+
+            // `a AND b` requires both `a` and `b`,
+            // `a OR b` makes both optional even if the default operator is `AND`
+            if let Some((occur, _)) = clauses.last_mut() {
+                match (&conjunction, self.default_operator) {
+                    (Some(Token::And), _) if matches!(occur, Occur::Should) => *occur = Occur::Must,
+                    (Some(Token::Or), Operator::And) if matches!(occur, Occur::Must) => {
+                        *occur = Occur::Should
+                    }
+                    _ => {}
+                }
+            }
+            let occur = match (modifier, conjunction) {
+                (Some(Token::Minus | Token::Not), _) => Occur::MustNot,
+                (Some(Token::Plus), _) | (None, Some(Token::And)) => Occur::Must,
+                (None, Some(Token::Or)) => Occur::Should,
+                _ => match self.default_operator {
+                    Operator::And => Occur::Must,
+                    Operator::Or => Occur::Should,
+                },
+            };
+            clauses.push((occur, query));
+        }
+
+        match clauses.len() {
+            0 => Err(parse_error(start, "expected a query")),
+            1 if !matches!(clauses[0].0, Occur::MustNot) => Ok(clauses.pop().unwrap().1),
+            _ => {
+                if clauses
+                    .iter()
+                    .all(|(occur, _)| matches!(occur, Occur::MustNot))
+                {
+                    return Err(parse_error(
+                        start,
+                        "the query must contain at least one clause that is not negated",
+                    ));
+                }
+                Ok(BooleanQuery::new(clauses).into())
+            }
+        }
+    }
+
+    fn parse_clause(&mut self, column: Option<&str>) -> Result<FtsQuery> {
+        // `column:` scopes the following term, phrase or group
+        let field = match (self.tokens.get(self.pos), self.tokens.get(self.pos + 1)) {
+            (Some((_, Token::Term { text, .. })), Some((_, Token::Colon))) => {
+                let field = text.clone();
+                self.pos += 2;
+                Some(field)
+            }
+            _ => None,
+        };
+        let column = field.as_deref().or(column);
+
+        let pos = self.current_pos();
+        let query = match self.next() {
+            Some((_, Token::LParen)) => {
+                let query = self.parse_query(column)?;
+                match self.next() {
+                    Some((_, Token::RParen)) => {}
+                    Some((pos, token)) => {
+                        return Err(parse_error(pos, format!("expected ')', found {}", token)))
+                    }
+                    None => return Err(parse_error(self.end, "expected ')'")),
+                }
+                query
+            }
+            Some((_, Token::Term { text, raw })) => {
+                let query = term_query(text, &raw, column);
+                if let Some((pos, Token::Tilde(distance))) = self.peek().cloned() {
+                    self.next();
+                    match query {
+                        FtsQuery::Match(query) => query.with_fuzziness(distance).into(),
+                        _ => {
+                            return Err(parse_error(
+                                pos,
+                                "fuzzy matching is not supported with wildcards",
+                            ))
+                        }
+                    }
+                } else {
+                    query
+                }
+            }
+            Some((_, Token::Phrase(phrase))) => {
+                let query = PhraseQuery::new(phrase).with_column(column.map(String::from));
+                match self.peek().cloned() {
+                    Some((_, Token::Tilde(Some(slop)))) => {
+                        self.next();
+                        query.with_slop(slop).into()
+                    }
+                    Some((pos, Token::Tilde(None))) => {
+                        return Err(parse_error(pos, "expected the slop after '~'"))
+                    }
+                    _ => query.into(),
+                }
+            }
+            Some((_, Token::Regexp(pattern))) => RegexpQuery::new(pattern)
+                .with_column(column.map(String::from))
+                .into(),
+            Some((pos, token)) => {
+                return Err(parse_error(
+                    pos,
+                    format!("expected a term, phrase or group, found {}", token),
+                ))
+            }
+            None => {
+                return Err(parse_error(
+                    pos,
+                    "expected a term, phrase or group at the end of query",
+                ))
+            }
+        };
+
+        match self.peek() {
+            Some((_, Token::Caret(boost))) => {
+                let boost = *boost;
+                self.next();
+                Ok(with_boost(query, boost))
+            }
+            _ => Ok(query),
+        }
+    }
+}
+
+fn term_query(text: String, raw: &str, column: Option<&str>) -> FtsQuery {
+    let column = column.map(String::from);
+    // find the wildcards those are not escaped
+    let mut wildcards = Vec::new();
+    let mut chars = raw.chars().enumerate();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '*' | '?' => wildcards.push((i, c)),
+            _ => {}
+        }
+    }
+    match wildcards.as_slice() {
+        [] => MatchQuery::new(text).with_column(column).into(),
+        [(i, '*')] if *i + 1 == raw.chars().count() => {
+            let mut prefix = text;
+            prefix.pop();
+            PrefixQuery::new(prefix).with_column(column).into()
+        }
+        _ => WildcardQuery::new(raw.to_owned())
+            .with_column(column)
+            .into(),
+    }
+}
+
+// multiplies the boost of all the leaf queries
+fn with_boost(query: FtsQuery, boost: f32) -> FtsQuery {
+    match query {
+        FtsQuery::Match(query) => {
+            let new_boost = query.boost * boost;
+            query.with_boost(new_boost).into()
+        }
+        FtsQuery::Phrase(query) => {
+            let new_boost = query.boost * boost;
+            query.with_boost(new_boost).into()
+        }
+        FtsQuery::Prefix(query) => {
+            let new_boost = query.boost * boost;
+            query.with_boost(new_boost).into()
+        }
+        FtsQuery::Wildcard(query) => {
+            let new_boost = query.boost * boost;
+            query.with_boost(new_boost).into()
+        }
+        FtsQuery::Regexp(query) => {
+            let new_boost = query.boost * boost;
+            query.with_boost(new_boost).into()
+        }
+        FtsQuery::Boost(query) => BoostQuery {
+            positive: Box::new(with_boost(*query.positive, boost)),
+            negative: Box::new(with_boost(*query.negative, boost)),
+            negative_boost: query.negative_boost,
+        }
+        .into(),
+        FtsQuery::MultiMatch(query) => MultiMatchQuery {
+            match_queries: query
+                .match_queries
+                .into_iter()
+                .map(|query| {
+                    let new_boost = query.boost * boost;
+                    query.with_boost(new_boost)
+                })
+                .collect(),
+        }
+        .into(),
+        FtsQuery::Boolean(query) => BooleanQuery {
+            should: query
+                .should
+                .into_iter()
+                .map(|query| with_boost(query, boost))
+                .collect(),
+            must: query
+                .must
+                .into_iter()
+                .map(|query| with_boost(query, boost))
+                .collect(),
+            must_not: query.must_not,
+        }
+        .into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str) -> Option<String> {
+        Some(name.to_owned())
+    }
+
+    #[test]
+    fn test_parse_leaf_queries() {
+        assert_eq!(
+            parse_query_string("rust").unwrap(),
+            MatchQuery::new("rust".to_owned()).into()
+        );
+        assert_eq!(
+            parse_query_string("title:rust^2").unwrap(),
+            MatchQuery::new("rust".to_owned())
+                .with_column(column("title"))
+                .with_boost(2.0)
+                .into()
+        );
+        assert_eq!(
+            parse_query_string("rust~").unwrap(),
+            MatchQuery::new("rust".to_owned())
+                .with_fuzziness(None)
+                .into()
+        );
+        assert_eq!(
+            parse_query_string("rust~1").unwrap(),
+            MatchQuery::new("rust".to_owned())
+                .with_fuzziness(Some(1))
+                .into()
+        );
+        assert_eq!(
+            parse_query_string("body:\"columnar format\"~2").unwrap(),
+            PhraseQuery::new("columnar format".to_owned())
+                .with_column(column("body"))
+                .with_slop(2)
+                .into()
+        );
+        assert_eq!(
+            parse_query_string("inter*").unwrap(),
+            PrefixQuery::new("inter".to_owned()).into()
+        );
+        assert_eq!(
+            parse_query_string("log_?rr*").unwrap(),
+            WildcardQuery::new("log_?rr*".to_owned()).into()
+        );
+        // escaped wildcards are literals
+        assert_eq!(
+            parse_query_string("c\\+\\+").unwrap(),
+            MatchQuery::new("c++".to_owned()).into()
+        );
+        assert_eq!(
+            parse_query_string("a\\*b*").unwrap(),
+            PrefixQuery::new("a*b".to_owned()).into()
+        );
+        assert_eq!(
+            parse_query_string("/lan(ce|d)/").unwrap(),
+            RegexpQuery::new("lan(ce|d)".to_owned()).into()
+        );
+        // `-` inside a term is not an operator
+        assert_eq!(
+            parse_query_string("full-text").unwrap(),
+            MatchQuery::new("full-text".to_owned()).into()
+        );
+    }
+
+    #[test]
+    fn test_parse_boolean_queries() {
+        let query =
+            parse_query_string("title:(rust OR arrow) AND body:\"columnar format\"~2 -draft^0.5")
+                .unwrap();
+        let title = BooleanQuery::new([
+            (
+                Occur::Should,
+                MatchQuery::new("rust".to_owned())
+                    .with_column(column("title"))
+                    .into(),
+            ),
+            (
+                Occur::Should,
+                MatchQuery::new("arrow".to_owned())
+                    .with_column(column("title"))
+                    .into(),
+            ),
+        ]);
+        let expected = BooleanQuery::new([
+            (Occur::Must, title.into()),
+            (
+                Occur::Must,
+                PhraseQuery::new("columnar format".to_owned())
+                    .with_column(column("body"))
+                    .with_slop(2)
+                    .into(),
+            ),
+            (
+                Occur::MustNot,
+                MatchQuery::new("draft".to_owned()).with_boost(0.5).into(),
+            ),
+        ]);
+        assert_eq!(query, expected.into());
+
+        // clauses are optional by default, and required with `AND` as default operator
+        let query = parse_query_string("a +b NOT c").unwrap();
+        let expected = BooleanQuery::new([
+            (Occur::Should, MatchQuery::new("a".to_owned()).into()),
+            (Occur::Must, MatchQuery::new("b".to_owned()).into()),
+            (Occur::MustNot, MatchQuery::new("c".to_owned()).into()),
+        ]);
+        assert_eq!(query, expected.into());
+        let query = QueryStringParser::new()
+            .with_default_operator(Operator::And)
+            .parse("a b || c")
+            .unwrap();
+        let expected = BooleanQuery::new([
+            (Occur::Must, MatchQuery::new("a".to_owned()).into()),
+            (Occur::Should, MatchQuery::new("b".to_owned()).into()),
+            (Occur::Should, MatchQuery::new("c".to_owned()).into()),
+        ]);
+        assert_eq!(query, expected.into());
+
+        // boosting a group boosts all of its clauses
+        let query = parse_query_string("(a \"b c\")^2").unwrap();
+        let expected = BooleanQuery::new([
+            (
+                Occur::Should,
+                MatchQuery::new("a".to_owned()).with_boost(2.0).into(),
+            ),
+            (
+                Occur::Should,
+                PhraseQuery::new("b c".to_owned()).with_boost(2.0).into(),
+            ),
+        ]);
+        assert_eq!(query, expected.into());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("", "empty"),
+            ("   ", "empty"),
+            ("title:(rust OR arrow", "position 20: expected ')'"),
+            ("rust)", "position 4: unexpected ')'"),
+            ("\"columnar format", "position 0: unterminated phrase"),
+            ("/lan(ce", "position 0: unterminated regexp"),
+            ("AND rust", "position 0: unexpected AND"),
+            (
+                "rust AND",
+                "expected a term, phrase or group at the end of query",
+            ),
+            ("rust^", "position 4: expected a number after '^'"),
+            ("inter*~1", "fuzzy matching is not supported with wildcards"),
+            ("\"a b\"~", "expected the slop after '~'"),
+            ("-draft", "at least one clause that is not negated"),
+            (
+                "title:",
+                "expected a term, phrase or group at the end of query",
+            ),
+        ];
+        for (query, message) in cases {
+            let err = parse_query_string(query).unwrap_err().to_string();
+            assert!(
+                err.contains(message),
+                "query: {:?}, expected error containing {:?}, got: {}",
+                query,
+                message,
+                err
+            );
+        }
+    }
+}
@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use lance_core::{Error, Result};
+use regex_automata::dfa::{dense, Automaton, StartKind};
+use regex_automata::util::primitives::StateID;
+use regex_automata::util::start;
+use regex_automata::{Anchored, MatchKind};
+use snafu::location;
+
+use super::query::TermPattern;
+use super::InvertedIndexParams;
+
+// the upper bound of the memory used by a compiled pattern,
+// this prevents a pathological regex from blowing up the DFA construction
+const DFA_SIZE_LIMIT: usize = 16 * 1024 * 1024;
+
+/// An automaton that accepts the tokens fully matched by a [`TermPattern`].
+///
+/// All patterns are compiled into an anchored DFA,
+/// so that it can be used to search the fst token map directly.
+#[derive(Debug, Clone)]
+pub struct PatternAutomaton {
+    dfa: dense::DFA<Vec<u32>>,
+}
+
+impl PatternAutomaton {
+    pub fn try_new(pattern: &TermPattern) -> Result<Self> {
+        let regex = match pattern {
+            TermPattern::Prefix(prefix) => format!("{}.*", escape(prefix)),
+            TermPattern::Wildcard(pattern) => wildcard_to_regex(pattern),
+            TermPattern::Regexp(pattern) => format!("(?:{})", pattern),
+        };
+        let dfa = dense::Builder::new()
+            .configure(
+                dense::Config::new()
+                    .start_kind(StartKind::Anchored)
+                    .match_kind(MatchKind::All)
+                    .dfa_size_limit(Some(DFA_SIZE_LIMIT))
+                    .determinize_size_limit(Some(DFA_SIZE_LIMIT)),
+            )
+            .build(&format!("^{}$", regex))
+            .map_err(|e| {
+                Error::invalid_input(format!("invalid pattern {}: {}", pattern, e), location!())
+            })?;
+        Ok(Self { dfa })
+    }
+
+    /// Compiles the pattern to match the tokens produced with the index params
+    pub fn try_new_for_index(pattern: &TermPattern, params: &InvertedIndexParams) -> Result<Self> {
+        // the tokens are lower cased at indexing if configured,
+        // so do the same for prefix and wildcard patterns,
+        // regular expressions are kept as is because lower casing may change their meaning
+        let pattern = match pattern {
+            TermPattern::Prefix(prefix) if params.lower_case => {
+                TermPattern::Prefix(prefix.to_lowercase())
+            }
+            TermPattern::Wildcard(pattern) if params.lower_case => {
+                TermPattern::Wildcard(pattern.to_lowercase())
+            }
+            pattern => pattern.clone(),
+        };
+        Self::try_new(&pattern)
+    }
+
+    /// Returns true if the whole token matches the pattern
+    pub fn matches(&self, token: &str) -> bool {
+        automaton_matches(self, token)
+    }
+}
+
+/// Runs the automaton over the token, returns true if the whole token is accepted
+pub fn automaton_matches<A: fst::Automaton>(automaton: &A, token: &str) -> bool {
+    let mut state = automaton.start();
+    for &byte in token.as_bytes() {
+        if !automaton.can_match(&state) {
+            return false;
+        }
+        state = automaton.accept(&state, byte);
+    }
+    automaton.is_match(&state)
+}
+
+impl fst::Automaton for PatternAutomaton {
+    // None means the DFA can't be started or has stopped,
+    // no token can be matched from this state
+    type State = Option<StateID>;
+
+    fn start(&self) -> Self::State {
+        let config = start::Config::new().anchored(Anchored::Yes);
+        self.dfa.start_state(&config).ok()
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        match state {
+            // the DFA reports matches with a delay of one byte,
+            // so feed it the end of input before checking
+            Some(state) => self.dfa.is_match_state(self.dfa.next_eoi_state(*state)),
+            None => false,
+        }
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        match state {
+            Some(state) => !self.dfa.is_dead_state(*state) && !self.dfa.is_quit_state(*state),
+            None => false,
+        }
+    }
+
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        state.map(|state| self.dfa.next_state(state, byte))
+    }
+}
+
+// `*` matches any sequence of characters, `?` matches any single character,
+// and `\` escapes the next character
+fn wildcard_to_regex(pattern: &str) -> String {
+    let mut regex = String::with_capacity(pattern.len() * 2);
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    push_escaped(&mut regex, c);
+                }
+            }
+            c => push_escaped(&mut regex, c),
+        }
+    }
+    regex
+}
+
+fn escape(literal: &str) -> String {
+    let mut escaped = String::with_capacity(literal.len());
+    literal.chars().for_each(|c| push_escaped(&mut escaped, c));
+    escaped
+}
+
+fn push_escaped(dst: &mut String, c: char) {
+    if matches!(
+        c,
+        '\\' | '.'
+            | '+'
+            | '*'
+            | '?'
+            | '('
+            | ')'
+            | '|'
+            | '['
+            | ']'
+            | '{'
+            | '}'
+            | '^'
+            | '$'
+            | '#'
+            | '&'
+            | '-'
+            | '~'
+    ) {
+        dst.push('\\');
+    }
+    dst.push(c);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_automaton() {
+        let prefix = PatternAutomaton::try_new(&TermPattern::Prefix("inter".to_owned())).unwrap();
+        assert!(prefix.matches("inter"));
+        assert!(prefix.matches("internet"));
+        assert!(!prefix.matches("inte"));
+        assert!(!prefix.matches("winter"));
+
+        let wildcard =
+            PatternAutomaton::try_new(&TermPattern::Wildcard("log_?rr*".to_owned())).unwrap();
+        assert!(wildcard.matches("log_err"));
+        assert!(wildcard.matches("log_errors"));
+        assert!(!wildcard.matches("log_rr"));
+        assert!(!wildcard.matches("my_log_err"));
+
+        // special characters are literals in prefix and wildcard patterns
+        let wildcard =
+            PatternAutomaton::try_new(&TermPattern::Wildcard("a.b\\*".to_owned())).unwrap();
+        assert!(wildcard.matches("a.b*"));
+        assert!(!wildcard.matches("axb*"));
+        assert!(!wildcard.matches("a.bc"));
+
+        let regexp =
+            PatternAutomaton::try_new(&TermPattern::Regexp("lan(ce|d)[0-9]?".to_owned())).unwrap();
+        assert!(regexp.matches("lance"));
+        assert!(regexp.matches("land7"));
+        assert!(!regexp.matches("lancer"));
+        assert!(!regexp.matches("xland"));
+
+        // alternation must match the whole token, not only the first branch
+        let regexp = PatternAutomaton::try_new(&TermPattern::Regexp("a|ab".to_owned())).unwrap();
+        assert!(regexp.matches("a"));
+        assert!(regexp.matches("ab"));
+
+        assert!(PatternAutomaton::try_new(&TermPattern::Regexp("(".to_owned())).is_err());
+    }
+}
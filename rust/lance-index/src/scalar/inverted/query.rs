@@ -115,6 +115,9 @@ pub enum FtsQuery {
     // leaf queries
     Match(MatchQuery),
     Phrase(PhraseQuery),
+    Prefix(PrefixQuery),
+    Wildcard(WildcardQuery),
+    Regexp(RegexpQuery),
 
     // compound queries
     Boost(BoostQuery),
@@ -127,6 +130,9 @@ impl std::fmt::Display for FtsQuery {
         match self {
             Self::Match(query) => write!(f, "Match({:?})", query),
             Self::Phrase(query) => write!(f, "Phrase({:?})", query),
+            Self::Prefix(query) => write!(f, "Prefix({:?})", query),
+            Self::Wildcard(query) => write!(f, "Wildcard({:?})", query),
+            Self::Regexp(query) => write!(f, "Regexp({:?})", query),
             Self::Boost(query) => write!(
                 f,
                 "Boosting(positive={}, negative={}, negative_boost={})",
@@ -149,6 +155,9 @@ impl FtsQueryNode for FtsQuery {
         match self {
             Self::Match(query) => query.columns(),
             Self::Phrase(query) => query.columns(),
+            Self::Prefix(query) => query.columns(),
+            Self::Wildcard(query) => query.columns(),
+            Self::Regexp(query) => query.columns(),
             Self::Boost(query) => {
                 let mut columns = query.positive.columns();
                 columns.extend(query.negative.columns());
@@ -180,6 +189,9 @@ impl FtsQuery {
         match self {
             Self::Match(query) => query.terms.clone(),
             Self::Phrase(query) => format!("\"{}\"", query.terms), // Phrase queries are quoted
+            Self::Prefix(query) => format!("{}*", query.prefix),
+            Self::Wildcard(query) => query.pattern.clone(),
+            Self::Regexp(query) => format!("/{}/", query.pattern),
             Self::Boost(query) => query.positive.query(),
             Self::MultiMatch(query) => query.match_queries[0].terms.clone(),
             Self::Boolean(_) => {
@@ -193,6 +205,9 @@ impl FtsQuery {
         match self {
             Self::Match(query) => query.column.is_none(),
             Self::Phrase(query) => query.column.is_none(),
+            Self::Prefix(query) => query.column.is_none(),
+            Self::Wildcard(query) => query.column.is_none(),
+            Self::Regexp(query) => query.column.is_none(),
             Self::Boost(query) => {
                 query.positive.is_missing_column() || query.negative.is_missing_column()
             }
@@ -200,6 +215,7 @@ impl FtsQuery {
             Self::Boolean(query) => {
                 query.must.iter().any(|q| q.is_missing_column())
                     || query.should.iter().any(|q| q.is_missing_column())
+                    || query.must_not.iter().any(|q| q.is_missing_column())
             }
         }
     }
@@ -208,6 +224,9 @@ impl FtsQuery {
         match self {
             Self::Match(query) => Self::Match(query.with_column(Some(column))),
             Self::Phrase(query) => Self::Phrase(query.with_column(Some(column))),
+            Self::Prefix(query) => Self::Prefix(query.with_column(Some(column))),
+            Self::Wildcard(query) => Self::Wildcard(query.with_column(Some(column))),
+            Self::Regexp(query) => Self::Regexp(query.with_column(Some(column))),
             Self::Boost(query) => {
                 let positive = query.positive.with_column(column.clone());
                 let negative = query.negative.with_column(column);
@@ -263,6 +282,24 @@ impl From<PhraseQuery> for FtsQuery {
     }
 }
 
+impl From<PrefixQuery> for FtsQuery {
+    fn from(query: PrefixQuery) -> Self {
+        Self::Prefix(query)
+    }
+}
+
+impl From<WildcardQuery> for FtsQuery {
+    fn from(query: WildcardQuery) -> Self {
+        Self::Wildcard(query)
+    }
+}
+
+impl From<RegexpQuery> for FtsQuery {
+    fn from(query: RegexpQuery) -> Self {
+        Self::Regexp(query)
+    }
+}
+
 impl From<BoostQuery> for FtsQuery {
     fn from(query: BoostQuery) -> Self {
         Self::Boost(query)
@@ -396,6 +433,9 @@ pub struct PhraseQuery {
     pub terms: String,
     #[serde(default = "u32::default")]
     pub slop: u32,
+
+    #[serde(default = "MatchQuery::default_boost")]
+    pub boost: f32,
 }
 
 impl PhraseQuery {
@@ -404,6 +444,7 @@ impl PhraseQuery {
             column: None,
             terms,
             slop: 0,
+            boost: 1.0,
         }
     }
 
@@ -416,6 +457,11 @@ impl PhraseQuery {
         self.slop = slop;
         self
     }
+
+    pub fn with_boost(mut self, boost: f32) -> Self {
+        self.boost = boost;
+        self
+    }
 }
 
 impl FtsQueryNode for PhraseQuery {
@@ -428,6 +474,235 @@ impl FtsQueryNode for PhraseQuery {
     }
 }
 
+/// Matches the documents containing any token that starts with the prefix.
+///
+/// The prefix is not tokenized, it's expanded against the token dictionary
+/// of the index, so it should be a single token.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrefixQuery {
+    // The column to search in.
+    // If None, it will be determined at query time.
+    pub column: Option<String>,
+    pub prefix: String,
+
+    #[serde(default = "MatchQuery::default_boost")]
+    pub boost: f32,
+
+    /// The maximum number of tokens the prefix can be expanded to.
+    /// Default to 50.
+    #[serde(default = "MatchQuery::default_max_expansions")]
+    pub max_expansions: usize,
+}
+
+impl PrefixQuery {
+    pub fn new(prefix: String) -> Self {
+        Self {
+            column: None,
+            prefix,
+            boost: 1.0,
+            max_expansions: 50,
+        }
+    }
+
+    pub fn with_column(mut self, column: Option<String>) -> Self {
+        self.column = column;
+        self
+    }
+
+    pub fn with_boost(mut self, boost: f32) -> Self {
+        self.boost = boost;
+        self
+    }
+
+    pub fn with_max_expansions(mut self, max_expansions: usize) -> Self {
+        self.max_expansions = max_expansions;
+        self
+    }
+}
+
+impl FtsQueryNode for PrefixQuery {
+    fn columns(&self) -> HashSet<String> {
+        let mut columns = HashSet::new();
+        if let Some(column) = &self.column {
+            columns.insert(column.clone());
+        }
+        columns
+    }
+}
+
+/// Matches the documents containing any token that matches the wildcard pattern.
+///
+/// `*` matches any sequence of characters (including none),
+/// `?` matches exactly one character, and `\` escapes the next character.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WildcardQuery {
+    // The column to search in.
+    // If None, it will be determined at query time.
+    pub column: Option<String>,
+    pub pattern: String,
+
+    #[serde(default = "MatchQuery::default_boost")]
+    pub boost: f32,
+
+    /// The maximum number of tokens the pattern can be expanded to.
+    /// Default to 50.
+    #[serde(default = "MatchQuery::default_max_expansions")]
+    pub max_expansions: usize,
+}
+
+impl WildcardQuery {
+    pub fn new(pattern: String) -> Self {
+        Self {
+            column: None,
+            pattern,
+            boost: 1.0,
+            max_expansions: 50,
+        }
+    }
+
+    pub fn with_column(mut self, column: Option<String>) -> Self {
+        self.column = column;
+        self
+    }
+
+    pub fn with_boost(mut self, boost: f32) -> Self {
+        self.boost = boost;
+        self
+    }
+
+    pub fn with_max_expansions(mut self, max_expansions: usize) -> Self {
+        self.max_expansions = max_expansions;
+        self
+    }
+}
+
+impl FtsQueryNode for WildcardQuery {
+    fn columns(&self) -> HashSet<String> {
+        let mut columns = HashSet::new();
+        if let Some(column) = &self.column {
+            columns.insert(column.clone());
+        }
+        columns
+    }
+}
+
+/// Matches the documents containing any token that matches the regular expression.
+///
+/// The regular expression must match the whole token, it's not case folded
+/// even if the index lower cases the tokens.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegexpQuery {
+    // The column to search in.
+    // If None, it will be determined at query time.
+    pub column: Option<String>,
+    pub pattern: String,
+
+    #[serde(default = "MatchQuery::default_boost")]
+    pub boost: f32,
+
+    /// The maximum number of tokens the pattern can be expanded to.
+    /// Default to 50.
+    #[serde(default = "MatchQuery::default_max_expansions")]
+    pub max_expansions: usize,
+}
+
+impl RegexpQuery {
+    pub fn new(pattern: String) -> Self {
+        Self {
+            column: None,
+            pattern,
+            boost: 1.0,
+            max_expansions: 50,
+        }
+    }
+
+    pub fn with_column(mut self, column: Option<String>) -> Self {
+        self.column = column;
+        self
+    }
+
+    pub fn with_boost(mut self, boost: f32) -> Self {
+        self.boost = boost;
+        self
+    }
+
+    pub fn with_max_expansions(mut self, max_expansions: usize) -> Self {
+        self.max_expansions = max_expansions;
+        self
+    }
+}
+
+impl FtsQueryNode for RegexpQuery {
+    fn columns(&self) -> HashSet<String> {
+        let mut columns = HashSet::new();
+        if let Some(column) = &self.column {
+            columns.insert(column.clone());
+        }
+        columns
+    }
+}
+
+/// The pattern of a term-level query,
+/// which is expanded against the token dictionary at search time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TermPattern {
+    Prefix(String),
+    Wildcard(String),
+    Regexp(String),
+}
+
+impl std::fmt::Display for TermPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Prefix(prefix) => write!(f, "{}*", prefix),
+            Self::Wildcard(pattern) => write!(f, "{}", pattern),
+            Self::Regexp(pattern) => write!(f, "/{}/", pattern),
+        }
+    }
+}
+
+/// The common form of [`PrefixQuery`], [`WildcardQuery`] and [`RegexpQuery`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermPatternQuery {
+    pub column: Option<String>,
+    pub pattern: TermPattern,
+    pub boost: f32,
+    pub max_expansions: usize,
+}
+
+impl From<PrefixQuery> for TermPatternQuery {
+    fn from(query: PrefixQuery) -> Self {
+        Self {
+            column: query.column,
+            pattern: TermPattern::Prefix(query.prefix),
+            boost: query.boost,
+            max_expansions: query.max_expansions,
+        }
+    }
+}
+
+impl From<WildcardQuery> for TermPatternQuery {
+    fn from(query: WildcardQuery) -> Self {
+        Self {
+            column: query.column,
+            pattern: TermPattern::Wildcard(query.pattern),
+            boost: query.boost,
+            max_expansions: query.max_expansions,
+        }
+    }
+}
+
+impl From<RegexpQuery> for TermPatternQuery {
+    fn from(query: RegexpQuery) -> Self {
+        Self {
+            column: query.column,
+            pattern: TermPattern::Regexp(query.pattern),
+            boost: query.boost,
+            max_expansions: query.max_expansions,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BoostQuery {
     pub positive: Box<FtsQuery>,
@@ -668,6 +943,94 @@ pub fn collect_tokens(
     tokens
 }
 
+/// The tokens of a query with their positions in the query,
+/// the synonyms of a token share the position of the token
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tokens {
+    tokens: Vec<String>,
+    positions: Vec<u32>,
+}
+
+impl Tokens {
+    pub fn new(tokens: Vec<String>, positions: Vec<u32>) -> Self {
+        debug_assert_eq!(tokens.len(), positions.len());
+        Self { tokens, positions }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    pub fn push(&mut self, token: String, position: u32) {
+        self.tokens.push(token);
+        self.positions.push(position);
+    }
+
+    /// Iterates the tokens, without the positions
+    pub fn tokens(&self) -> impl Iterator<Item = &String> {
+        self.tokens.iter()
+    }
+
+    /// Iterates the tokens with their positions
+    pub fn iter(&self) -> impl Iterator<Item = (&String, u32)> {
+        self.tokens.iter().zip(self.positions.iter().copied())
+    }
+}
+
+/// Each token is at its own position
+impl From<Vec<String>> for Tokens {
+    fn from(tokens: Vec<String>) -> Self {
+        let positions = (0..tokens.len() as u32).collect();
+        Self { tokens, positions }
+    }
+}
+
+impl IntoIterator for Tokens {
+    type Item = (String, u32);
+    type IntoIter = std::iter::Zip<std::vec::IntoIter<String>, std::vec::IntoIter<u32>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tokens.into_iter().zip(self.positions)
+    }
+}
+
+/// Collects the tokens of a query with their positions,
+/// so the expanded synonyms can be matched as alternatives of the original token
+pub fn collect_query_tokens(
+    text: &str,
+    tokenizer: &mut tantivy::tokenizer::TextAnalyzer,
+) -> Tokens {
+    let mut stream = tokenizer.token_stream(text);
+    let mut tokens = Tokens::default();
+    while let Some(token) = stream.next() {
+        tokens.push(token.text.to_owned(), token.position as u32);
+    }
+    tokens
+}
+
+/// Collects the tokens of a phrase, the tokens at the same position as the previous one
+/// are skipped, so the expanded synonyms don't break the phrase
+pub fn collect_phrase_tokens(
+    text: &str,
+    tokenizer: &mut tantivy::tokenizer::TextAnalyzer,
+) -> Vec<String> {
+    let mut stream = tokenizer.token_stream(text);
+    let mut tokens = Vec::new();
+    let mut last_position = None;
+    while let Some(token) = stream.next() {
+        if last_position == Some(token.position) {
+            continue;
+        }
+        last_position = Some(token.position);
+        tokens.push(token.text.to_owned());
+    }
+    tokens
+}
+
 pub fn fill_fts_query_column(
     query: &FtsQuery,
     columns: &[String],
@@ -719,6 +1082,18 @@ pub fn fill_fts_query_column(
                 }
             }
         }
+        FtsQuery::Prefix(query) => fill_term_pattern_query_column(
+            columns,
+            |column| FtsQuery::Prefix(query.clone().with_column(Some(column))),
+        ),
+        FtsQuery::Wildcard(query) => fill_term_pattern_query_column(
+            columns,
+            |column| FtsQuery::Wildcard(query.clone().with_column(Some(column))),
+        ),
+        FtsQuery::Regexp(query) => fill_term_pattern_query_column(
+            columns,
+            |column| FtsQuery::Regexp(query.clone().with_column(Some(column))),
+        ),
        FtsQuery::Boost(boost_query) => {
             let positive = fill_fts_query_column(&boost_query.positive, columns, replace)?;
             let negative = fill_fts_query_column(&boost_query.negative, columns, replace)?;
@@ -766,6 +1141,28 @@ pub fn fill_fts_query_column(
     }
 }
 
+// term pattern queries have no multi-column form,
+// so search all the columns with a boolean should query
+fn fill_term_pattern_query_column(
+    columns: &[String],
+    with_column: impl Fn(String) -> FtsQuery,
+) -> Result<FtsQuery> {
+    match columns.len() {
+        0 => {
+            Err(Error::invalid_input(
+                "Cannot perform full text search unless an INVERTED index has been created on at least one column".to_string(),
+                location!(),
+            ))
+        }
+        1 => Ok(with_column(columns[0].clone())),
+        _ => Ok(FtsQuery::Boolean(BooleanQuery::new(
+            columns
+                .iter()
+                .map(|column| (Occur::Should, with_column(column.clone()))),
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -830,4 +1227,55 @@ mod tests {
         let query: PhraseQuery = serde_json::from_value(query).unwrap();
         assert_eq!(query, expected);
     }
+
+    #[test]
+    fn test_term_pattern_query_serde() {
+        use super::*;
+        use serde_json::json;
+
+        let query = json!({
+            "prefix": {
+                "prefix": "inter",
+                "column": "text",
+            }
+        });
+        let expected = FtsQuery::Prefix(
+            PrefixQuery::new("inter".to_string()).with_column(Some("text".to_string())),
+        );
+        let query: FtsQuery = serde_json::from_value(query).unwrap();
+        assert_eq!(query, expected);
+
+        let query = json!({
+            "wildcard": {
+                "pattern": "log_?rr*",
+                "boost": 2.0,
+                "max_expansions": 10,
+            }
+        });
+        let expected = FtsQuery::Wildcard(
+            WildcardQuery::new("log_?rr*".to_string())
+                .with_boost(2.0)
+                .with_max_expansions(10),
+        );
+        let query: FtsQuery = serde_json::from_value(query).unwrap();
+        assert_eq!(query, expected);
+
+        // a pattern query without column is searched on all the columns
+        let query = FtsQuery::Regexp(RegexpQuery::new("lan(ce|d)".to_string()));
+        let columns = vec!["title".to_string(), "content".to_string()];
+        let filled = fill_fts_query_column(&query, &columns, false).unwrap();
+        let FtsQuery::Boolean(bool_query) = filled else {
+            panic!("expected boolean query, got {}", filled);
+        };
+        assert!(bool_query.must.is_empty());
+        assert_eq!(bool_query.should.len(), 2);
+        assert_eq!(
+            bool_query
+                .should
+                .iter()
+                .flat_map(|q| q.columns())
+                .collect::<HashSet<_>>(),
+            columns.into_iter().collect()
+        );
+    }
 }
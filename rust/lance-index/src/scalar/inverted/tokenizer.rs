@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
-use std::{env, path::PathBuf};
+use std::{env, path::PathBuf, sync::Arc};
 
 use lance_core::{Error, Result};
 use serde::{Deserialize, Serialize};
@@ -9,6 +9,8 @@ use snafu::location;
 
 #[cfg(feature = "tokenizer-jieba")]
 mod jieba;
+mod registry;
+mod synonym;
 
 #[cfg(feature = "tokenizer-lindera")]
 mod lindera;
@@ -19,6 +21,21 @@ use jieba::JiebaTokenizerBuilder;
 #[cfg(feature = "tokenizer-lindera")]
 use lindera::LinderaTokenizerBuilder;
 
+pub use registry::TokenizerRegistry;
+pub use synonym::SynonymFilter;
+
+/// When the synonyms are expanded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SynonymExpansion {
+    /// Expand the query terms, the index is not affected so the synonyms can be changed
+    /// without rebuilding the index
+    #[default]
+    Query,
+    /// Index the synonyms of the document terms along with them
+    Index,
+}
+
 /// Tokenizer configs
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InvertedIndexParams {
@@ -26,8 +43,10 @@ pub struct InvertedIndexParams {
     /// - `simple`: splits tokens on whitespace and punctuation
     /// - `whitespace`: splits tokens on whitespace
     /// - `raw`: no tokenization
+    /// - `pattern`: each match of `pattern` is a token
     /// - `lindera/*`: Lindera tokenizer
     /// - `jieba/*`: Jieba tokenizer
+    /// - the name of a tokenizer registered in [`TokenizerRegistry`]
     ///
     /// `simple` is recommended for most cases and the default value
     pub(crate) base_tokenizer: String,
@@ -75,6 +94,28 @@ pub struct InvertedIndexParams {
     /// whether prefix only
     #[serde(default)]
     pub(crate) prefix_only: bool,
+
+    /// custom stop words replacing the built-in ones of the language,
+    /// this is only used when `remove_stop_words` is true
+    #[serde(default)]
+    pub(crate) stop_words: Option<Vec<String>>,
+
+    /// groups of synonyms, each term of a group is expanded to the other terms
+    #[serde(default)]
+    pub(crate) synonyms: Vec<Vec<String>>,
+
+    /// whether to expand the synonyms at query time or at index time
+    #[serde(default)]
+    pub(crate) synonym_expansion: SynonymExpansion,
+
+    /// the regular expression for the `pattern` base tokenizer
+    #[serde(default)]
+    pub(crate) pattern: Option<String>,
+
+    /// the registry to resolve the custom base tokenizers,
+    /// this is set at runtime and not persisted
+    #[serde(skip)]
+    pub(crate) tokenizer_registry: Option<Arc<TokenizerRegistry>>,
 }
 
 fn bool_true() -> bool {
@@ -103,8 +144,10 @@ impl InvertedIndexParams {
     /// - `whitespace`: splits tokens on whitespace
     /// - `raw`: no tokenization
     /// - `ngram`: N-Gram tokenizer
+    /// - `pattern`: each match of the regular expression set by [`Self::pattern`] is a token
     /// - `lindera/*`: Lindera tokenizer
     /// - `jieba/*`: Jieba tokenizer
+    /// - the name of a tokenizer registered in [`TokenizerRegistry`]
     ///
     /// The `language` is used for stemming and removing stop words,
     /// this is not used for `lindera/*` and `jieba/*` tokenizers.
@@ -122,6 +165,11 @@ impl InvertedIndexParams {
             min_ngram_length: default_min_ngram_length(),
             max_ngram_length: default_max_ngram_length(),
             prefix_only: false,
+            stop_words: None,
+            synonyms: Vec::new(),
+            synonym_expansion: SynonymExpansion::default(),
+            pattern: None,
+            tokenizer_registry: None,
         }
     }
 
@@ -195,7 +243,54 @@ impl InvertedIndexParams {
         self
     }
 
+    /// Set the stop words to remove instead of the built-in ones of the language,
+    /// the stop words are matched against the tokens before stemming.
+    /// Only works when `remove_stop_words` is true.
+    pub fn stop_words(mut self, stop_words: Option<Vec<String>>) -> Self {
+        self.stop_words = stop_words;
+        self
+    }
+
+    /// Set the groups of synonyms, each term of a group is expanded to the other terms.
+    /// The terms are matched against the tokens before stemming,
+    /// so each term must be a single token.
+    pub fn synonyms(mut self, synonyms: Vec<Vec<String>>) -> Self {
+        self.synonyms = synonyms;
+        self
+    }
+
+    /// Set whether to expand the synonyms at query time or at index time.
+    /// Default to [`SynonymExpansion::Query`].
+    pub fn synonym_expansion(mut self, synonym_expansion: SynonymExpansion) -> Self {
+        self.synonym_expansion = synonym_expansion;
+        self
+    }
+
+    /// Set the regular expression of the `pattern` base tokenizer,
+    /// each match of it is a token.
+    pub fn pattern(mut self, pattern: Option<String>) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
+    /// Set the registry to resolve the custom base tokenizers
+    pub fn with_tokenizer_registry(mut self, registry: Arc<TokenizerRegistry>) -> Self {
+        self.tokenizer_registry = Some(registry);
+        self
+    }
+
+    /// Build the tokenizer for the documents
     pub fn build(&self) -> Result<tantivy::tokenizer::TextAnalyzer> {
+        self.build_pipeline(self.synonym_expansion == SynonymExpansion::Index)
+    }
+
+    /// Build the tokenizer for the queries,
+    /// it differs from [`Self::build`] only in whether the synonyms are expanded
+    pub fn build_for_query(&self) -> Result<tantivy::tokenizer::TextAnalyzer> {
+        self.build_pipeline(self.synonym_expansion == SynonymExpansion::Query)
+    }
+
+    fn build_pipeline(&self, expand_synonyms: bool) -> Result<tantivy::tokenizer::TextAnalyzer> {
         let mut builder = self.build_base_tokenizer()?;
         if let Some(max_token_length) = self.max_token_length {
             builder = builder.filter_dynamic(tantivy::tokenizer::RemoveLongFilter::limit(
@@ -205,10 +300,21 @@ impl InvertedIndexParams {
         if self.lower_case {
             builder = builder.filter_dynamic(tantivy::tokenizer::LowerCaser);
         }
+        if let (true, Some(stop_words)) = (self.remove_stop_words, &self.stop_words) {
+            let stop_words = stop_words.iter().map(|word| match self.lower_case {
+                true => word.to_lowercase(),
+                false => word.clone(),
+            });
+            builder =
+                builder.filter_dynamic(tantivy::tokenizer::StopWordFilter::remove(stop_words));
+        }
+        if expand_synonyms && !self.synonyms.is_empty() {
+            builder = builder.filter_dynamic(SynonymFilter::new(&self.synonyms, self.lower_case));
+        }
         if self.stem {
             builder = builder.filter_dynamic(tantivy::tokenizer::Stemmer::new(self.language));
         }
-        if self.remove_stop_words {
+        if self.remove_stop_words && self.stop_words.is_none() {
             let stop_word_filter = tantivy::tokenizer::StopWordFilter::new(self.language)
                 .ok_or_else(|| {
                     Error::invalid_input(
@@ -250,6 +356,18 @@ impl InvertedIndexParams {
                 .map_err(|e| Error::invalid_input(e.to_string(), location!()))?,
             )
             .dynamic()),
+            "pattern" => {
+                let pattern = self.pattern.as_deref().ok_or_else(|| {
+                    Error::invalid_input(
+                        "the pattern must be set for the pattern tokenizer",
+                        location!(),
+                    )
+                })?;
+                let tokenizer = tantivy::tokenizer::RegexTokenizer::new(pattern).map_err(|e| {
+                    Error::invalid_input(format!("invalid pattern {}: {}", pattern, e), location!())
+                })?;
+                Ok(tantivy::tokenizer::TextAnalyzer::builder(tokenizer).dynamic())
+            }
             #[cfg(feature = "tokenizer-lindera")]
             s if s.starts_with("lindera/") => {
                 let Some(home) = language_model_home() else {
@@ -271,10 +389,16 @@ impl InvertedIndexParams {
                 };
                 jieba::JiebaBuilder::load(&home.join(s))?.build()
             }
-            _ => Err(Error::invalid_input(
-                format!("unknown base tokenizer {}", self.base_tokenizer),
-                location!(),
-            )),
+            name => self
+                .tokenizer_registry
+                .as_ref()
+                .and_then(|registry| registry.get(name))
+                .ok_or_else(|| {
+                    Error::invalid_input(
+                        format!("unknown base tokenizer {}", self.base_tokenizer),
+                        location!(),
+                    )
+                }),
         }
     }
 }
@@ -289,3 +413,89 @@ pub fn language_model_home() -> Option<PathBuf> {
         Err(_) => dirs::data_local_dir().map(|p| p.join(LANCE_LANGUAGE_MODEL_DEFAULT_DIRECTORY)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scalar::inverted::query::collect_tokens;
+
+    fn tokens(tokenizer: Result<tantivy::tokenizer::TextAnalyzer>, text: &str) -> Vec<String> {
+        collect_tokens(text, &mut tokenizer.unwrap(), None)
+    }
+
+    #[test]
+    fn test_custom_stop_words() {
+        let params = InvertedIndexParams::default()
+            .stem(false)
+            .stop_words(Some(vec!["Lance".to_owned(), "format".to_owned()]));
+        // the built-in stop words are not removed
+        assert_eq!(
+            tokens(params.build(), "The Lance format is fast"),
+            vec!["the", "is", "fast"]
+        );
+    }
+
+    #[test]
+    fn test_synonyms() {
+        let params = InvertedIndexParams::default()
+            .stem(false)
+            .synonyms(vec![vec!["Car".to_owned(), "automobile".to_owned()]]);
+        assert_eq!(tokens(params.build(), "red car"), vec!["red", "car"]);
+        assert_eq!(
+            tokens(params.build_for_query(), "red car"),
+            vec!["red", "car", "automobile"]
+        );
+
+        let params = params.synonym_expansion(SynonymExpansion::Index);
+        assert_eq!(
+            tokens(params.build(), "red automobile"),
+            vec!["red", "automobile", "car"]
+        );
+        assert_eq!(
+            tokens(params.build_for_query(), "red car"),
+            vec!["red", "car"]
+        );
+
+        // the params are persisted with the index
+        let json = serde_json::to_string(&params).unwrap();
+        let params = serde_json::from_str::<InvertedIndexParams>(&json).unwrap();
+        assert_eq!(params.synonym_expansion, SynonymExpansion::Index);
+        assert_eq!(params.synonyms.len(), 1);
+    }
+
+    #[test]
+    fn test_pattern_tokenizer() {
+        let params = InvertedIndexParams::default()
+            .base_tokenizer("pattern".to_owned())
+            .pattern(Some("[A-Z]+-[0-9]+".to_owned()));
+        assert_eq!(
+            tokens(params.build(), "fixed ABC-123 and XY-9 today"),
+            vec!["abc-123", "xy-9"]
+        );
+
+        let params = params.pattern(None);
+        assert!(params.build().is_err());
+    }
+
+    #[test]
+    fn test_tokenizer_registry() {
+        let mut registry = TokenizerRegistry::new();
+        registry
+            .register(
+                "letters",
+                tantivy::tokenizer::WhitespaceTokenizer::default(),
+            )
+            .unwrap();
+        assert!(registry
+            .register("letters", tantivy::tokenizer::RawTokenizer::default())
+            .is_err());
+        assert!(registry
+            .register("simple", tantivy::tokenizer::RawTokenizer::default())
+            .is_err());
+
+        let params = InvertedIndexParams::default().base_tokenizer("letters".to_owned());
+        assert!(params.build().is_err());
+        let params = params.with_tokenizer_registry(Arc::new(registry));
+        assert_eq!(tokens(params.build(), "Lance,format"), vec!["lance,format"]);
+    }
+}
@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use lance_core::{Error, Result};
+use snafu::location;
+use tantivy::tokenizer::{TextAnalyzer, TextAnalyzerBuilder, Tokenizer};
+
+type TokenizerFactory = Arc<dyn Fn() -> TextAnalyzerBuilder + Send + Sync>;
+
+/// The user-defined base tokenizers.
+///
+/// A registered tokenizer can be used as the `base_tokenizer` of
+/// [`super::InvertedIndexParams`] by its name, the filters configured in the params
+/// (lower casing, stemming, stop words, synonyms...) are applied on top of it.
+/// Only the name is persisted with the index, so the same tokenizer must be registered
+/// when the index is built and when it's queried.
+#[derive(Clone, Default)]
+pub struct TokenizerRegistry {
+    tokenizers: HashMap<String, TokenizerFactory>,
+}
+
+impl std::fmt::Debug for TokenizerRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenizerRegistry")
+            .field("tokenizers", &self.tokenizers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl TokenizerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a tokenizer with the name.
+    ///
+    /// A name can only be registered once, and it can't be the name of a built-in tokenizer.
+    pub fn register<T: Tokenizer>(&mut self, name: impl Into<String>, tokenizer: T) -> Result<()> {
+        let name = name.into();
+        if is_builtin_tokenizer(&name) {
+            return Err(Error::invalid_input(
+                format!("{name} is a built-in tokenizer"),
+                location!(),
+            ));
+        }
+        if self.tokenizers.contains_key(&name) {
+            return Err(Error::invalid_input(
+                format!("{name} is already registered"),
+                location!(),
+            ));
+        }
+        self.tokenizers.insert(
+            name,
+            Arc::new(move || TextAnalyzer::builder(tokenizer.clone()).dynamic()),
+        );
+        Ok(())
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<TextAnalyzerBuilder> {
+        self.tokenizers.get(name).map(|factory| factory())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.tokenizers.contains_key(name)
+    }
+}
+
+fn is_builtin_tokenizer(name: &str) -> bool {
+    matches!(
+        name,
+        "simple" | "whitespace" | "raw" | "ngram" | "pattern" | "jieba"
+    ) || name.starts_with("lindera/")
+        || name.starts_with("jieba/")
+}
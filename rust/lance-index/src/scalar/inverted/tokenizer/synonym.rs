@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// Emits the synonyms of each token right after it,
+/// the synonyms share the offsets and position of the original token
+#[derive(Clone)]
+pub struct SynonymFilter {
+    synonyms: Arc<HashMap<String, Vec<String>>>,
+}
+
+impl SynonymFilter {
+    /// Each term of a group is expanded to all the other terms of the group
+    pub fn new(groups: &[Vec<String>], lower_case: bool) -> Self {
+        let mut synonyms: HashMap<String, Vec<String>> = HashMap::new();
+        for group in groups {
+            let group = group
+                .iter()
+                .map(|term| match lower_case {
+                    true => term.to_lowercase(),
+                    false => term.clone(),
+                })
+                .collect::<Vec<_>>();
+            for term in &group {
+                let entry = synonyms.entry(term.clone()).or_default();
+                for synonym in &group {
+                    if synonym != term && !entry.contains(synonym) {
+                        entry.push(synonym.clone());
+                    }
+                }
+            }
+        }
+        Self {
+            synonyms: Arc::new(synonyms),
+        }
+    }
+}
+
+impl TokenFilter for SynonymFilter {
+    type Tokenizer<T: Tokenizer> = SynonymFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> Self::Tokenizer<T> {
+        SynonymFilterWrapper {
+            synonyms: self.synonyms,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SynonymFilterWrapper<T> {
+    synonyms: Arc<HashMap<String, Vec<String>>>,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for SynonymFilterWrapper<T> {
+    type TokenStream<'a> = SynonymTokenStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        SynonymTokenStream {
+            synonyms: &self.synonyms,
+            tail: self.inner.token_stream(text),
+            pending: Vec::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct SynonymTokenStream<'a, T> {
+    synonyms: &'a HashMap<String, Vec<String>>,
+    tail: T,
+    // the synonyms of the current token that are not emitted yet, in reverse order
+    pending: Vec<&'a str>,
+    token: Token,
+}
+
+impl<T: TokenStream> TokenStream for SynonymTokenStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if let Some(synonym) = self.pending.pop() {
+            self.token.text.clear();
+            self.token.text.push_str(synonym);
+            return true;
+        }
+        if !self.tail.advance() {
+            return false;
+        }
+        self.token = self.tail.token().clone();
+        if let Some(synonyms) = self.synonyms.get(&self.token.text) {
+            self.pending
+                .extend(synonyms.iter().rev().map(|synonym| synonym.as_str()));
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
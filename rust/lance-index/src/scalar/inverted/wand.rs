@@ -9,6 +9,7 @@ use arrow::array::AsArray;
 use arrow::datatypes::{Int32Type, UInt32Type};
 use arrow_array::{Array, UInt32Array};
 use arrow_schema::DataType;
+use itertools::Itertools;
 use lance_core::utils::mask::RowIdMask;
 use lance_core::Result;
 use tracing::instrument;
@@ -271,6 +272,9 @@ pub struct Wand<'a, S: Scorer> {
     threshold: f32, // multiple of factor and the minimum score of the top-k documents
     operator: Operator,
     num_terms: usize,
+    // the number of distinct positions of the query terms,
+    // the synonyms of a term share the same position
+    num_positions: usize,
     // we need to sort the posting iterators frequently,
     // so wrap them in `Box` to avoid the cost of copying
     #[allow(clippy::vec_box)]
@@ -292,11 +296,17 @@ impl<'a, S: Scorer> Wand<'a, S> {
     ) -> Self {
         let mut posting_lists = postings.collect::<Vec<_>>();
         posting_lists.sort_unstable();
+        let num_positions = posting_lists
+            .iter()
+            .map(|posting| posting.position)
+            .unique()
+            .count();
 
         Self {
             threshold: 0.0,
             operator,
             num_terms: posting_lists.len(),
+            num_positions,
             postings: posting_lists.into_iter().map(Box::new).collect(),
             docs,
             scorer,
@@ -453,10 +463,29 @@ impl<'a, S: Scorer> Wand<'a, S> {
     #[instrument(level = "debug", skip_all)]
     fn find_pivot_term(&self) -> Option<usize> {
         if self.operator == Operator::And {
-            // for AND query, we always require all terms to be present in the document,
-            // so the pivot is always the last term as long as no posting list is exhausted
-            if self.postings.len() == self.num_terms {
-                return Some(self.num_terms - 1);
+            if self.num_positions == self.num_terms {
+                // for AND query, we always require all terms to be present in the document,
+                // so the pivot is always the last term as long as no posting list is exhausted
+                if self.postings.len() == self.num_terms {
+                    return Some(self.num_terms - 1);
+                }
+                return None;
+            }
+
+            // the synonyms at the same position are alternatives, so we require any term
+            // of each position, the pivot is the first term that all positions are covered
+            // by it and the preceding terms
+            let mut covered = Vec::with_capacity(self.num_positions);
+            for (idx, posting) in self.postings.iter().enumerate() {
+                let Some(doc) = posting.doc() else {
+                    return None;
+                };
+                if !covered.contains(&posting.position) {
+                    covered.push(posting.position);
+                }
+                if covered.len() == self.num_positions {
+                    return Some(self.extend_pivot(idx, doc.doc_id()));
+                }
             }
             return None;
         }
@@ -469,14 +498,21 @@ impl<'a, S: Scorer> Wand<'a, S> {
                 break;
             }
         }
-        let mut pivot = pivot?;
+        let pivot = pivot?;
         let doc_id = self.postings[pivot].doc().unwrap().doc_id();
+        Some(self.extend_pivot(pivot, doc_id))
+    }
+
+    // include the following terms on the same doc into the pivot
+    fn extend_pivot(&self, mut pivot: usize, doc_id: u64) -> usize {
         while pivot + 1 < self.postings.len()
-            && self.postings[pivot + 1].doc().unwrap().doc_id() == doc_id
+            && self.postings[pivot + 1]
+                .doc()
+                .is_some_and(|doc| doc.doc_id() == doc_id)
         {
             pivot += 1;
         }
-        Some(pivot)
+        pivot
     }
 
     // pick the term that has the maximum upper bound and the current doc id is less than the given doc id
@@ -725,4 +761,54 @@ mod tests {
             .unwrap();
         assert_eq!(result.len(), 0); // Should not panic
     }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_wand_and_with_synonyms(#[values(false, true)] is_compressed: bool) {
+        let mut docs = DocSet::default();
+        for i in 0..4 {
+            docs.append(i as u64, 2);
+        }
+
+        // "red car", where "automobile" is a synonym of "car" at the same position
+        let postings = vec![
+            PostingIterator::new(
+                String::from("red"),
+                0,
+                0,
+                generate_posting_list(vec![0, 1, 3], 1.0, is_compressed),
+                docs.len(),
+            ),
+            PostingIterator::new(
+                String::from("car"),
+                1,
+                1,
+                generate_posting_list(vec![0, 2], 1.0, is_compressed),
+                docs.len(),
+            ),
+            PostingIterator::new(
+                String::from("automobile"),
+                2,
+                1,
+                generate_posting_list(vec![1], 1.0, is_compressed),
+                docs.len(),
+            ),
+        ];
+
+        let bm25 = BM25Scorer::new(std::iter::empty());
+        let mut wand = Wand::new(Operator::And, postings.into_iter(), &docs, bm25);
+        let result = wand
+            .search(
+                &FtsSearchParams::default(),
+                Arc::new(RowIdMask::default()),
+                &NoOpMetricsCollector,
+            )
+            .unwrap();
+        let row_ids = result
+            .into_iter()
+            .map(|(row_id, _, _)| row_id)
+            .sorted()
+            .collect::<Vec<_>>();
+        assert_eq!(row_ids, vec![0, 1]);
+    }
 }
@@ -0,0 +1,532 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Index on a path inside a JSON column
+//!
+//! The value at the path is extracted as text (the same text `json_extract`
+//! and the `->>` operator return) and indexed with a btree or bitmap index.
+//! The index answers queries like `payload->>'user_id' = 'abc'`.
+
+use std::{any::Any, collections::HashMap, sync::Arc};
+
+use arrow_array::{ArrayRef, RecordBatch, UInt32Array};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, SortOptions};
+use async_trait::async_trait;
+use datafusion::physical_plan::{
+    sorts::sort::SortExec, stream::RecordBatchStreamAdapter, SendableRecordBatchStream,
+};
+use datafusion_physical_expr::{expressions::Column, LexOrdering, PhysicalSortExpr};
+use deepsize::DeepSizeOf;
+use futures::TryStreamExt;
+use lance_arrow::json::{json_extract, JsonPath};
+use lance_core::cache::LanceCache;
+use lance_core::{Error, Result};
+use lance_datafusion::{
+    chunker::chunk_concat_stream,
+    exec::{execute_plan, LanceExecutionOptions, OneShotExec},
+};
+use roaring::RoaringBitmap;
+use serde::Serialize;
+use snafu::location;
+use tracing::instrument;
+
+use super::{
+    bitmap::{train_bitmap_index, BitmapIndex},
+    btree::{train_btree_index, BTreeIndex, TrainingSource, DEFAULT_BTREE_BATCH_SIZE},
+    flat::FlatIndexMetadata,
+    AnyQuery, IndexStore, JsonQuery, MetricsCollector, ScalarIndex, ScalarIndexType, SearchResult,
+};
+use crate::frag_reuse::FragReuseIndex;
+use crate::{Index, IndexParams, IndexType};
+
+pub const JSON_INDEX_FILE: &str = "json_index.lance";
+const PATH_META_KEY: &str = "path";
+const TARGET_TYPE_META_KEY: &str = "target_index_type";
+
+/// Parameters for a JSON index
+#[derive(Debug, Clone)]
+pub struct JsonIndexParams {
+    /// The path to index, e.g. `$.user.id`
+    pub path: String,
+    /// The index used for the extracted values, either BTree or Bitmap
+    pub target_index_type: ScalarIndexType,
+}
+
+impl JsonIndexParams {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            target_index_type: ScalarIndexType::BTree,
+        }
+    }
+
+    pub fn with_target_index_type(mut self, target_index_type: ScalarIndexType) -> Self {
+        self.target_index_type = target_index_type;
+        self
+    }
+
+    fn validate(&self) -> Result<JsonPath> {
+        if !matches!(
+            self.target_index_type,
+            ScalarIndexType::BTree | ScalarIndexType::Bitmap
+        ) {
+            return Err(Error::invalid_input(
+                format!(
+                    "A JSON index can only use a BTree or Bitmap index for its values, got {:?}",
+                    self.target_index_type
+                ),
+                location!(),
+            ));
+        }
+        Ok(JsonPath::parse(&self.path)?)
+    }
+}
+
+impl IndexParams for JsonIndexParams {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn index_type(&self) -> IndexType {
+        IndexType::Json
+    }
+
+    fn index_name(&self) -> &str {
+        "JSON"
+    }
+}
+
+#[derive(Serialize)]
+struct JsonStatistics {
+    path: String,
+    target_index_type: String,
+    target: serde_json::Value,
+}
+
+/// A scalar index on the values found at one path of a JSON column
+#[derive(Debug)]
+pub struct JsonIndex {
+    path: JsonPath,
+    target_index_type: ScalarIndexType,
+    target: Arc<dyn ScalarIndex>,
+}
+
+impl DeepSizeOf for JsonIndex {
+    fn deep_size_of_children(&self, context: &mut deepsize::Context) -> usize {
+        self.target.deep_size_of_children(context)
+    }
+}
+
+impl JsonIndex {
+    pub fn path(&self) -> &JsonPath {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl Index for JsonIndex {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_index(self: Arc<Self>) -> Arc<dyn Index> {
+        self
+    }
+
+    fn as_vector_index(self: Arc<Self>) -> Result<Arc<dyn crate::vector::VectorIndex>> {
+        Err(Error::NotSupported {
+            source: "JsonIndex is not a vector index".into(),
+            location: location!(),
+        })
+    }
+
+    async fn prewarm(&self) -> Result<()> {
+        self.target.prewarm().await
+    }
+
+    fn index_type(&self) -> IndexType {
+        IndexType::Json
+    }
+
+    fn statistics(&self) -> Result<serde_json::Value> {
+        let stats = JsonStatistics {
+            path: self.path.to_string(),
+            target_index_type: format!("{:?}", self.target_index_type),
+            target: self.target.statistics()?,
+        };
+        serde_json::to_value(stats).map_err(|e| Error::Internal {
+            message: format!("failed to serialize json index statistics: {}", e),
+            location: location!(),
+        })
+    }
+
+    async fn calculate_included_frags(&self) -> Result<RoaringBitmap> {
+        self.target.calculate_included_frags().await
+    }
+}
+
+#[async_trait]
+impl ScalarIndex for JsonIndex {
+    #[instrument(name = "json_search", level = "debug", skip_all)]
+    async fn search(
+        &self,
+        query: &dyn AnyQuery,
+        metrics: &dyn MetricsCollector,
+    ) -> Result<SearchResult> {
+        let query = query.as_any().downcast_ref::<JsonQuery>().ok_or_else(|| {
+            Error::invalid_input(
+                format!("A JSON index cannot answer the query {:?}", query),
+                location!(),
+            )
+        })?;
+        if query.path != self.path {
+            return Err(Error::Internal {
+                message: format!(
+                    "A JSON index on {} was asked to search the path {}",
+                    self.path, query.path
+                ),
+                location: location!(),
+            });
+        }
+        self.target.search(&query.query, metrics).await
+    }
+
+    fn can_answer_exact(&self, _: &dyn AnyQuery) -> bool {
+        true
+    }
+
+    async fn load(
+        store: Arc<dyn IndexStore>,
+        frag_reuse_index: Option<Arc<FragReuseIndex>>,
+        index_cache: LanceCache,
+    ) -> Result<Arc<Self>> {
+        let reader = store.open_index_file(JSON_INDEX_FILE).await?;
+        let metadata = &reader.schema().metadata;
+        let get_meta = |key: &str| {
+            metadata.get(key).ok_or_else(|| Error::Index {
+                message: format!("JSON index file is missing the {} metadata", key),
+                location: location!(),
+            })
+        };
+        let path = JsonPath::parse(get_meta(PATH_META_KEY)?)?;
+        let target_index_type = match get_meta(TARGET_TYPE_META_KEY)?.as_str() {
+            "BTree" => ScalarIndexType::BTree,
+            "Bitmap" => ScalarIndexType::Bitmap,
+            other => {
+                return Err(Error::Index {
+                    message: format!("Unsupported JSON index target type {}", other),
+                    location: location!(),
+                })
+            }
+        };
+        let target = match target_index_type {
+            ScalarIndexType::Bitmap => {
+                BitmapIndex::load(store, frag_reuse_index, index_cache).await?
+                    as Arc<dyn ScalarIndex>
+            }
+            _ => {
+                BTreeIndex::load(store, frag_reuse_index, index_cache).await?
+                    as Arc<dyn ScalarIndex>
+            }
+        };
+        Ok(Arc::new(Self {
+            path,
+            target_index_type,
+            target,
+        }))
+    }
+
+    async fn remap(
+        &self,
+        mapping: &HashMap<u64, Option<u64>>,
+        dest_store: &dyn IndexStore,
+    ) -> Result<()> {
+        self.target.remap(mapping, dest_store).await?;
+        write_json_metadata(dest_store, &self.path, self.target_index_type).await
+    }
+
+    async fn update(
+        &self,
+        new_data: SendableRecordBatchStream,
+        dest_store: &dyn IndexStore,
+    ) -> Result<()> {
+        let extracted = extract_chunks(new_data, self.path.clone());
+        let extracted = match self.target_index_type {
+            // The btree merges the new data with its existing (sorted) pages
+            ScalarIndexType::BTree => sort_chunks(extracted)?,
+            _ => extracted,
+        };
+        self.target.update(extracted, dest_store).await?;
+        write_json_metadata(dest_store, &self.path, self.target_index_type).await
+    }
+}
+
+async fn write_json_metadata(
+    store: &dyn IndexStore,
+    path: &JsonPath,
+    target_index_type: ScalarIndexType,
+) -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![Field::new(
+        "unused",
+        DataType::UInt32,
+        true,
+    )]));
+    let mut writer = store
+        .new_index_file(JSON_INDEX_FILE, schema.clone())
+        .await?;
+    let batch = RecordBatch::try_new(schema, vec![Arc::new(UInt32Array::from(vec![0]))])?;
+    writer.write_record_batch(batch).await?;
+    writer
+        .finish_with_metadata(HashMap::from([
+            (PATH_META_KEY.to_string(), path.to_string()),
+            (
+                TARGET_TYPE_META_KEY.to_string(),
+                format!("{:?}", target_index_type),
+            ),
+        ]))
+        .await
+}
+
+fn extracted_schema(schema: &Schema) -> SchemaRef {
+    let mut fields = vec![Arc::new(Field::new("values", DataType::Utf8, true))];
+    fields.extend(schema.fields().iter().skip(1).cloned());
+    Arc::new(Schema::new(fields))
+}
+
+/// Replace the first column (the JSON values) with the text at `path`
+fn extract_chunks(source: SendableRecordBatchStream, path: JsonPath) -> SendableRecordBatchStream {
+    let schema = extracted_schema(source.schema().as_ref());
+    let schema_copy = schema.clone();
+    let stream = source.and_then(move |batch| {
+        let result = json_extract(batch.column(0).as_ref(), &path, true)
+            .map_err(datafusion::error::DataFusionError::from)
+            .and_then(|values| {
+                let mut columns = vec![Arc::new(values) as ArrayRef];
+                columns.extend(batch.columns().iter().skip(1).cloned());
+                Ok(RecordBatch::try_new(schema.clone(), columns)?)
+            });
+        std::future::ready(result)
+    });
+    Box::pin(RecordBatchStreamAdapter::new(schema_copy, stream))
+}
+
+fn sort_chunks(source: SendableRecordBatchStream) -> Result<SendableRecordBatchStream> {
+    let sort_expr = PhysicalSortExpr {
+        expr: Arc::new(Column::new("values", 0)),
+        options: SortOptions {
+            descending: false,
+            nulls_first: true,
+        },
+    };
+    let input = Arc::new(OneShotExec::new(source));
+    let plan = Arc::new(SortExec::new(LexOrdering::new(vec![sort_expr]), input));
+    // Datafusion currently has bugs with spilling on string columns
+    // See https://github.com/apache/datafusion/issues/10073
+    execute_plan(
+        plan,
+        LanceExecutionOptions {
+            use_spilling: false,
+            ..Default::default()
+        },
+    )
+}
+
+struct JsonTrainingSource {
+    source: Box<dyn TrainingSource + Send>,
+    path: JsonPath,
+}
+
+#[async_trait]
+impl TrainingSource for JsonTrainingSource {
+    async fn scan_ordered_chunks(
+        self: Box<Self>,
+        chunk_size: u32,
+    ) -> Result<SendableRecordBatchStream> {
+        // The source can only order by the JSON value, not the extracted text
+        let source = self.source.scan_unordered_chunks(chunk_size).await?;
+        let sorted = sort_chunks(extract_chunks(source, self.path))?;
+        Ok(chunk_concat_stream(sorted, chunk_size as usize))
+    }
+
+    async fn scan_unordered_chunks(
+        self: Box<Self>,
+        chunk_size: u32,
+    ) -> Result<SendableRecordBatchStream> {
+        let source = self.source.scan_unordered_chunks(chunk_size).await?;
+        Ok(extract_chunks(source, self.path))
+    }
+}
+
+/// Trains a new JSON index
+pub async fn train_json_index(
+    data_source: Box<dyn TrainingSource + Send>,
+    index_store: &dyn IndexStore,
+    params: &JsonIndexParams,
+) -> Result<()> {
+    let path = params.validate()?;
+    let source = Box::new(JsonTrainingSource {
+        source: data_source,
+        path: path.clone(),
+    });
+    match params.target_index_type {
+        ScalarIndexType::Bitmap => train_bitmap_index(source, index_store).await?,
+        _ => {
+            train_btree_index(
+                source,
+                &FlatIndexMetadata::new(DataType::Utf8),
+                index_store,
+                DEFAULT_BTREE_BATCH_SIZE as u32,
+            )
+            .await?
+        }
+    }
+    write_json_metadata(index_store, &path, params.target_index_type).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::{RecordBatch, UInt64Array};
+    use arrow_schema::{DataType, Field, Schema};
+    use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+    use datafusion_common::ScalarValue;
+    use lance_arrow::json::{json_array_from_strs, json_field};
+    use lance_core::cache::LanceCache;
+    use lance_core::utils::mask::RowIdTreeMap;
+    use lance_io::object_store::ObjectStore;
+    use object_store::path::Path;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::metrics::NoOpMetricsCollector;
+    use crate::scalar::lance_format::LanceIndexStore;
+    use crate::scalar::SargableQuery;
+
+    struct BatchSource {
+        batch: RecordBatch,
+    }
+
+    #[async_trait]
+    impl TrainingSource for BatchSource {
+        async fn scan_ordered_chunks(
+            self: Box<Self>,
+            _chunk_size: u32,
+        ) -> Result<SendableRecordBatchStream> {
+            unimplemented!()
+        }
+
+        async fn scan_unordered_chunks(
+            self: Box<Self>,
+            _chunk_size: u32,
+        ) -> Result<SendableRecordBatchStream> {
+            Ok(batch_stream(self.batch))
+        }
+    }
+
+    fn batch_stream(batch: RecordBatch) -> SendableRecordBatchStream {
+        Box::pin(RecordBatchStreamAdapter::new(
+            batch.schema(),
+            futures::stream::iter(vec![Ok(batch)]),
+        ))
+    }
+
+    fn make_batch(docs: &[Option<&str>], first_row_id: u64) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            json_field("payload", true),
+            Field::new("_rowid", DataType::UInt64, false),
+        ]));
+        let row_ids = first_row_id..first_row_id + docs.len() as u64;
+        RecordBatch::try_new(
+            schema,
+            vec![
+                json_array_from_strs(docs.iter().copied()).unwrap(),
+                Arc::new(UInt64Array::from_iter_values(row_ids)),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn equals(path: &JsonPath, value: &str) -> JsonQuery {
+        JsonQuery::new(
+            path.clone(),
+            SargableQuery::Equals(ScalarValue::Utf8(Some(value.to_string()))),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_json_index() {
+        for target_index_type in [ScalarIndexType::BTree, ScalarIndexType::Bitmap] {
+            let tempdir = tempdir().unwrap();
+            let store = Arc::new(LanceIndexStore::new(
+                Arc::new(ObjectStore::local()),
+                Path::from_filesystem_path(tempdir.path()).unwrap(),
+                Arc::new(LanceCache::no_cache()),
+            ));
+            let params =
+                JsonIndexParams::new("$.user.id").with_target_index_type(target_index_type);
+            let batch = make_batch(
+                &[
+                    Some(r#"{"user": {"id": "b"}}"#),
+                    Some(r#"{"user": {"id": "a"}}"#),
+                    Some(r#"{"user": {}}"#),
+                    None,
+                    Some(r#"{"user": {"id": "b"}, "x": 1}"#),
+                ],
+                0,
+            );
+            train_json_index(Box::new(BatchSource { batch }), store.as_ref(), &params)
+                .await
+                .unwrap();
+
+            let index = JsonIndex::load(store.clone(), None, LanceCache::no_cache())
+                .await
+                .unwrap();
+            let path = JsonPath::parse("$.user.id").unwrap();
+            assert_eq!(index.path(), &path);
+            let result = index
+                .search(&equals(&path, "b"), &NoOpMetricsCollector)
+                .await
+                .unwrap();
+            assert_eq!(
+                result,
+                SearchResult::Exact(RowIdTreeMap::from_iter(&[0, 4]))
+            );
+            let result = index
+                .search(
+                    &JsonQuery::new(path.clone(), SargableQuery::IsNull()),
+                    &NoOpMetricsCollector,
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                result,
+                SearchResult::Exact(RowIdTreeMap::from_iter(&[2, 3]))
+            );
+
+            // Updating the index extracts the path from the new rows
+            let update_dir = tempdir().unwrap();
+            let update_store = Arc::new(LanceIndexStore::new(
+                Arc::new(ObjectStore::local()),
+                Path::from_filesystem_path(update_dir.path()).unwrap(),
+                Arc::new(LanceCache::no_cache()),
+            ));
+            let new_batch = make_batch(&[Some(r#"{"user": {"id": "a"}}"#)], 5);
+            index
+                .update(batch_stream(new_batch), update_store.as_ref())
+                .await
+                .unwrap();
+            let updated = JsonIndex::load(update_store, None, LanceCache::no_cache())
+                .await
+                .unwrap();
+            let result = updated
+                .search(&equals(&path, "a"), &NoOpMetricsCollector)
+                .await
+                .unwrap();
+            assert_eq!(
+                result,
+                SearchResult::Exact(RowIdTreeMap::from_iter(&[1, 5]))
+            );
+        }
+    }
+}
@@ -24,6 +24,7 @@ use std::sync::LazyLock;
 use v3::subindex::SubIndexType;
 
 pub mod bq;
+pub mod diskann;
 pub mod flat;
 pub mod graph;
 pub mod hnsw;
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! DiskANN, a graph index that lives on disk.
+//!
+//! The index is a single Vamana graph over all the vectors (Subramanya et al., 2019).
+//! Unlike HNSW, which is loaded into memory partition by partition, only the PQ codes
+//! and the row ids of the nodes are kept in memory.  The adjacency lists and the full
+//! precision vectors are stored in fixed size node records in the graph file, so the
+//! search reads a handful of nodes per hop with range reads, navigates by the PQ
+//! distances and ranks the visited nodes by their exact distances.
+//!
+//! The index directory contains:
+//! - `index.idx`: a Lance file of the row ids and the PQ codes of the nodes, in node
+//!   order, with the graph and the PQ metadata in the schema metadata.
+//! - `diskann.graph`: the node records, see [graph].
+
+use arrow_array::RecordBatch;
+use deepsize::DeepSizeOf;
+use lance_core::Result;
+use lance_file::v2::writer::FileWriter;
+use lance_io::object_store::ObjectStore;
+use lance_linalg::distance::DistanceType;
+use object_store::path::Path;
+use serde::{Deserialize, Serialize};
+
+use crate::vector::pq::storage::ProductQuantizationMetadata;
+use crate::vector::quantizer::QuantizerMetadata;
+use crate::vector::storage::STORAGE_METADATA_KEY;
+use crate::{IndexMetadata, IndexType, INDEX_METADATA_SCHEMA_KEY};
+
+pub mod builder;
+pub mod graph;
+pub mod index;
+mod spill;
+
+pub use builder::{DiskAnnBuildParams, DiskAnnBuilder};
+pub use index::DiskAnnIndex;
+
+pub const DISKANN_METADATA_KEY: &str = "lance:diskann";
+pub const DISKANN_GRAPH_FILE_NAME: &str = "diskann.graph";
+
+/// The column of the vectors in the batches streamed from a [DiskAnnIndex].
+pub const DISKANN_VECTOR_COLUMN: &str = "__vector";
+
+/// The row id of a node whose row has been deleted.
+///
+/// The node is kept in the graph so the paths through it still work, but it's
+/// never returned by a search.
+pub const DELETED_ROW_ID: u64 = u64::MAX;
+
+/// The metadata of the graph, stored in the index file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DeepSizeOf)]
+pub struct DiskAnnMetadata {
+    /// The dimension of the vectors
+    pub dimension: usize,
+
+    /// The max number of neighbors of each node
+    pub max_degree: usize,
+
+    /// The pruning factor the graph is built with
+    pub alpha: f32,
+
+    /// The default size of the candidate list while searching
+    pub search_list_size: usize,
+
+    /// The node the searches start from, the medoid of the vectors
+    pub entry: u32,
+
+    /// The number of nodes in the graph, including the deleted ones
+    pub num_nodes: usize,
+}
+
+/// Write the in-memory part of the index, that is the row ids and the PQ codes
+/// of the nodes in node order.
+pub(crate) async fn write_index_file(
+    object_store: &ObjectStore,
+    path: &Path,
+    batch: &RecordBatch,
+    pq_metadata: &ProductQuantizationMetadata,
+    metadata: &DiskAnnMetadata,
+    distance_type: DistanceType,
+) -> Result<()> {
+    let mut writer = FileWriter::try_new(
+        object_store.create(path).await?,
+        batch.schema_ref().as_ref().try_into()?,
+        Default::default(),
+    )?;
+    if batch.num_rows() > 0 {
+        writer.write_batch(batch).await?;
+    }
+
+    let index_metadata = IndexMetadata {
+        index_type: IndexType::DiskAnn.to_string(),
+        distance_type: distance_type.to_string(),
+    };
+    writer.add_schema_metadata(
+        INDEX_METADATA_SCHEMA_KEY,
+        serde_json::to_string(&index_metadata)?,
+    );
+    writer.add_schema_metadata(DISKANN_METADATA_KEY, serde_json::to_string(metadata)?);
+
+    // the codebook goes to a global buffer, as the IVF indices do
+    let mut pq_metadata = pq_metadata.clone();
+    if let Some(extra_metadata) = pq_metadata.extra_metadata()? {
+        let idx = writer.add_global_buffer(extra_metadata).await?;
+        pq_metadata.set_buffer_index(idx);
+    }
+    writer.add_schema_metadata(STORAGE_METADATA_KEY, serde_json::to_string(&pq_metadata)?);
+    writer.finish().await?;
+    Ok(())
+}
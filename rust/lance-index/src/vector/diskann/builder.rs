@@ -0,0 +1,1075 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Build the Vamana graph of DiskANN.
+//!
+//! The graph starts as a random regular graph, then every node is searched from the
+//! entry (the medoid) and its neighbors are replaced by the pruned search path, with
+//! the reverse edges added to the neighbors.  The first pass prunes with `alpha = 1`
+//! to get a sparse graph, the second with the configured `alpha` to add the long range
+//! edges that keep the searches short.
+//!
+//! The nodes are processed in batches in parallel, with the batch size doubling from 1
+//! up to a small fraction of the nodes, so the early nodes see an updated graph.
+//!
+//! Only the row ids and the PQ codes of the nodes are kept in memory while building.
+//! The vectors are spilled to temporary files, and if there are more than
+//! `partition_size` of them, they are split by k-means into overlapping partitions:
+//! every vector goes to its 2 closest partitions.  The graph of each partition is
+//! built in memory, one partition at a time, and the graphs are merged node by node
+//! into the graph file.  The shared nodes connect the partition graphs.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Arc;
+
+use arrow::compute::{cast, concat_batches};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float32Type, UInt64Type};
+use arrow_array::{Array, FixedSizeListArray, Float32Array, RecordBatch, UInt64Array};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use bytes::Bytes;
+use deepsize::DeepSizeOf;
+use futures::{Stream, TryStreamExt};
+use lance_arrow::FixedSizeListArrayExt;
+use lance_core::utils::tokio::spawn_cpu;
+use lance_core::{Error, Result, ROW_ID, ROW_ID_FIELD};
+use lance_io::object_store::ObjectStore;
+use lance_linalg::distance::{l2, DistanceType};
+use lance_linalg::kernels::normalize_fsl;
+use log::info;
+use object_store::path::Path;
+use rand::rngs::SmallRng;
+use rand::seq::{index::sample, SliceRandom};
+use rand::{thread_rng, Rng, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use snafu::location;
+use tempfile::tempdir;
+
+use super::graph::GraphWriter;
+use super::spill::{get_f32s, get_u32, put_f32s, put_u32, RecordReader, RecordWriter};
+use super::{write_index_file, DiskAnnMetadata, DISKANN_GRAPH_FILE_NAME, DISKANN_VECTOR_COLUMN};
+use crate::vector::graph::{OrderedFloat, OrderedNode, Visited, VisitedGenerator};
+use crate::vector::kmeans::{kmeans_find_partitions, KMeans};
+use crate::vector::pq::storage::ProductQuantizationStorage;
+use crate::vector::pq::transform::PQTransformer;
+use crate::vector::pq::{PQBuildParams, ProductQuantizer};
+use crate::vector::quantizer::{QuantizerBuildParams, QuantizerStorage};
+use crate::vector::transform::Transformer;
+use crate::vector::PQ_CODE_COLUMN;
+use crate::INDEX_FILE_NAME;
+
+// the max size of a batch of nodes updated in parallel, as a fraction of the nodes
+const MAX_BATCH_FRACTION: f32 = 0.02;
+
+// the number of partitions each vector is added to
+const PARTITION_REPLICAS: usize = 2;
+
+// the min number of vectors sampled for training the partitions
+const MIN_SAMPLE_SIZE: usize = 64 * 1024;
+
+const KMEANS_MAX_ITERS: u32 = 50;
+
+// the number of vectors read from the spilled files at a time
+const CHUNK_SIZE: usize = 8192;
+
+// the files spilled to the temporary directory
+const VECTORS_FILE: &str = "vectors";
+
+fn default_partition_size() -> usize {
+    1_000_000
+}
+
+/// Parameters of building DiskANN index
+#[derive(Debug, Clone, Serialize, Deserialize, DeepSizeOf)]
+pub struct DiskAnnBuildParams {
+    /// max number of neighbors of each node, `R` in the paper
+    pub max_degree: usize,
+
+    /// size of the candidate list while building the graph, `L` in the paper,
+    /// it's also the default size of the candidate list while searching
+    pub search_list_size: usize,
+
+    /// pruning factor, larger values keep more long range edges
+    pub alpha: f32,
+
+    /// max number of vectors of a graph built in memory,
+    /// more vectors are split into overlapping partitions
+    #[serde(default = "default_partition_size")]
+    pub partition_size: usize,
+}
+
+impl Default for DiskAnnBuildParams {
+    fn default() -> Self {
+        Self {
+            max_degree: 64,
+            search_list_size: 100,
+            alpha: 1.2,
+            partition_size: default_partition_size(),
+        }
+    }
+}
+
+impl DiskAnnBuildParams {
+    /// The max number of neighbors of each node.
+    /// The default value is `64`.
+    pub fn max_degree(mut self, max_degree: usize) -> Self {
+        self.max_degree = max_degree;
+        self
+    }
+
+    /// The size of the candidate list while building the graph, and the default
+    /// size while searching.
+    ///
+    /// The default value is `100`.
+    pub fn search_list_size(mut self, search_list_size: usize) -> Self {
+        self.search_list_size = search_list_size;
+        self
+    }
+
+    /// The pruning factor, it must be at least `1.0`.
+    /// The default value is `1.2`.
+    pub fn alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// The max number of vectors of a graph built in memory, this bounds the memory
+    /// used by the build.  More vectors are split into partitions of about this size,
+    /// each vector is added to 2 partitions.
+    ///
+    /// The default value is `1_000_000`.
+    pub fn partition_size(mut self, partition_size: usize) -> Self {
+        self.partition_size = partition_size;
+        self
+    }
+}
+
+/// Builds a DiskANN index and writes it to the index directory.
+pub struct DiskAnnBuilder {
+    params: DiskAnnBuildParams,
+    pq_params: PQBuildParams,
+    distance_type: DistanceType,
+    quantizer: Option<ProductQuantizer>,
+}
+
+// the vectors spilled in node order
+struct SpilledVectors {
+    path: Path,
+    dimension: usize,
+    row_ids: Vec<u64>,
+    // a uniform sample of the vectors for training
+    sample: FixedSizeListArray,
+    centroid: Vec<f32>,
+}
+
+impl DiskAnnBuilder {
+    pub fn new(
+        params: DiskAnnBuildParams,
+        pq_params: PQBuildParams,
+        distance_type: DistanceType,
+    ) -> Self {
+        Self {
+            params,
+            pq_params,
+            distance_type,
+            quantizer: None,
+        }
+    }
+
+    /// Use a trained quantizer instead of training a new one,
+    /// the `pq_params` are ignored then.
+    pub fn with_quantizer(mut self, quantizer: ProductQuantizer) -> Self {
+        self.quantizer = Some(quantizer);
+        self
+    }
+
+    /// Build the index of the vectors and write it to `index_dir`.
+    ///
+    /// For cosine distance, the vectors are normalized, and the queries must be
+    /// normalized as well.
+    pub async fn build(
+        self,
+        row_ids: UInt64Array,
+        vectors: &FixedSizeListArray,
+        object_store: &ObjectStore,
+        index_dir: &Path,
+    ) -> Result<()> {
+        if row_ids.len() != vectors.len() {
+            return Err(Error::Index {
+                message: format!(
+                    "DiskANN: can't index {} vectors with {} row ids",
+                    vectors.len(),
+                    row_ids.len()
+                ),
+                location: location!(),
+            });
+        }
+        let schema = Arc::new(Schema::new(vec![
+            ROW_ID_FIELD.clone(),
+            Field::new(DISKANN_VECTOR_COLUMN, vectors.data_type().clone(), true),
+        ]));
+        let batch =
+            RecordBatch::try_new(schema, vec![Arc::new(row_ids), Arc::new(vectors.clone())])?;
+        self.build_stream(
+            futures::stream::iter([Ok(batch)]),
+            DISKANN_VECTOR_COLUMN,
+            object_store,
+            index_dir,
+        )
+        .await
+    }
+
+    /// Build the index of the vectors in `column` of the batches, with the row ids
+    /// in the row id column, and write it to `index_dir`.
+    ///
+    /// The batches are read once, the vectors are spilled to a temporary directory.
+    pub async fn build_stream(
+        self,
+        mut batches: impl Stream<Item = Result<RecordBatch>> + Unpin,
+        column: &str,
+        object_store: &ObjectStore,
+        index_dir: &Path,
+    ) -> Result<()> {
+        if self.distance_type == DistanceType::Hamming {
+            return Err(Error::Index {
+                message: "DiskANN: hamming distance is not supported".to_string(),
+                location: location!(),
+            });
+        }
+        if self.params.max_degree == 0 || self.params.alpha < 1.0 || self.params.partition_size == 0
+        {
+            return Err(Error::Index {
+                message: format!(
+                    "DiskANN: invalid parameters, max_degree and partition_size must be positive and alpha at least 1.0: {:?}",
+                    self.params
+                ),
+                location: location!(),
+            });
+        }
+
+        let tmp_dir = tempdir()?;
+        let tmp_path = Path::from_filesystem_path(tmp_dir.path())?;
+        let tmp_store = ObjectStore::local();
+
+        let vectors = self
+            .spill_vectors(&mut batches, column, &tmp_store, &tmp_path)
+            .await?;
+        let num_nodes = vectors.row_ids.len();
+
+        let quantizer = match self.quantizer.clone() {
+            Some(quantizer) => quantizer,
+            None => {
+                let pq_distance_type = match self.distance_type {
+                    DistanceType::Cosine => DistanceType::L2,
+                    distance_type => distance_type,
+                };
+                info!("DiskANN: training PQ on {} vectors", vectors.sample.len());
+                self.pq_params.build(&vectors.sample, pq_distance_type)?
+            }
+        };
+
+        let num_partitions = if num_nodes > self.params.partition_size {
+            (num_nodes * PARTITION_REPLICAS)
+                .div_ceil(self.params.partition_size)
+                .min(vectors.sample.len())
+        } else {
+            1
+        };
+        let centroids = if num_partitions > 1 {
+            info!(
+                "DiskANN: splitting {} vectors into {} partitions",
+                num_nodes, num_partitions
+            );
+            let sample = vectors.sample.clone();
+            let kmeans =
+                spawn_cpu(move || Ok(KMeans::new(&sample, num_partitions, KMEANS_MAX_ITERS)?))
+                    .await?;
+            Some(Arc::new(
+                kmeans
+                    .centroids
+                    .as_primitive::<Float32Type>()
+                    .values()
+                    .to_vec(),
+            ))
+        } else {
+            None
+        };
+
+        let (codes, entry) = self
+            .partition_vectors(
+                &vectors,
+                &quantizer,
+                centroids,
+                num_partitions,
+                &tmp_store,
+                &tmp_path,
+            )
+            .await?;
+
+        let mut edge_files = Vec::with_capacity(num_partitions);
+        for partition in 0..num_partitions {
+            if let Some(path) = self
+                .build_partition(partition, vectors.dimension, &tmp_store, &tmp_path)
+                .await?
+            {
+                edge_files.push(path);
+            }
+        }
+
+        self.merge_partitions(
+            &vectors,
+            &edge_files,
+            &tmp_store,
+            object_store,
+            &index_dir.child(DISKANN_GRAPH_FILE_NAME),
+        )
+        .await?;
+
+        let storage = ProductQuantizationStorage::new(
+            quantizer.codebook.clone(),
+            codes,
+            quantizer.num_bits,
+            quantizer.num_sub_vectors,
+            quantizer.dimension,
+            quantizer.distance_type,
+            false,
+            None,
+        )?
+        .with_rotation(quantizer.rotation.clone());
+        let metadata = DiskAnnMetadata {
+            dimension: vectors.dimension,
+            max_degree: self.params.max_degree,
+            alpha: self.params.alpha,
+            search_list_size: self.params.search_list_size,
+            entry,
+            num_nodes,
+        };
+        write_index_file(
+            object_store,
+            &index_dir.child(INDEX_FILE_NAME),
+            storage.batch(),
+            storage.metadata(),
+            &metadata,
+            self.distance_type,
+        )
+        .await
+    }
+
+    // write the vectors as f32 in node order, normalized for cosine distance,
+    // and collect the row ids, a sample and the centroid of the vectors
+    async fn spill_vectors(
+        &self,
+        batches: &mut (impl Stream<Item = Result<RecordBatch>> + Unpin),
+        column: &str,
+        tmp_store: &ObjectStore,
+        tmp_path: &Path,
+    ) -> Result<SpilledVectors> {
+        let path = tmp_path.child(VECTORS_FILE);
+        let sample_size = self.pq_params.sample_size().max(MIN_SAMPLE_SIZE);
+        let mut rng = SmallRng::from_entropy();
+
+        let mut dimension = 0;
+        let mut writer = None;
+        let mut row_ids = Vec::new();
+        let mut sample = Vec::new();
+        let mut sum = Vec::new();
+        while let Some(batch) = batches.try_next().await? {
+            if batch.num_rows() == 0 {
+                continue;
+            }
+            let vectors = batch
+                .column_by_name(column)
+                .and_then(|col| col.as_fixed_size_list_opt())
+                .ok_or(Error::Index {
+                    message: format!("DiskANN: column {} of vectors not found", column),
+                    location: location!(),
+                })?;
+            let values = cast(vectors.values(), &DataType::Float32)?;
+            let mut vectors = FixedSizeListArray::try_new_from_values(
+                values.as_primitive::<Float32Type>().clone(),
+                vectors.value_length(),
+            )?;
+            if self.distance_type == DistanceType::Cosine {
+                vectors = normalize_fsl(&vectors)?;
+            }
+
+            if writer.is_none() {
+                dimension = vectors.value_length() as usize;
+                sum = vec![0.0_f64; dimension];
+                writer = Some(RecordWriter::try_new(tmp_store, &path, 4 * dimension).await?);
+            } else if vectors.value_length() as usize != dimension {
+                return Err(Error::Index {
+                    message: format!(
+                        "DiskANN: vectors of dimension {} and {} in the same column",
+                        dimension,
+                        vectors.value_length()
+                    ),
+                    location: location!(),
+                });
+            }
+            let writer = writer.as_mut().expect("created with the first batch");
+
+            let values = vectors.values().as_primitive::<Float32Type>().values();
+            for (i, vector) in values.chunks_exact(dimension).enumerate() {
+                // reservoir sampling, so the sample is uniform over all the vectors
+                let seen = row_ids.len() + i;
+                if seen < sample_size {
+                    sample.extend_from_slice(vector);
+                } else {
+                    let slot = rng.gen_range(0..=seen);
+                    if slot < sample_size {
+                        sample[slot * dimension..(slot + 1) * dimension].copy_from_slice(vector);
+                    }
+                }
+                for (s, v) in sum.iter_mut().zip(vector) {
+                    *s += *v as f64;
+                }
+                writer.write(|buf| put_f32s(buf, vector)).await?;
+            }
+            row_ids.extend_from_slice(batch[ROW_ID].as_primitive::<UInt64Type>().values());
+        }
+
+        let Some(writer) = writer else {
+            return Err(Error::Index {
+                message: "DiskANN: no vectors to index".to_string(),
+                location: location!(),
+            });
+        };
+        writer.finish().await?;
+        if row_ids.len() > u32::MAX as usize {
+            return Err(Error::Index {
+                message: format!("DiskANN: can't index {} vectors", row_ids.len()),
+                location: location!(),
+            });
+        }
+
+        let centroid = sum
+            .iter()
+            .map(|s| (s / row_ids.len() as f64) as f32)
+            .collect();
+        let sample =
+            FixedSizeListArray::try_new_from_values(Float32Array::from(sample), dimension as i32)?;
+        Ok(SpilledVectors {
+            path,
+            dimension,
+            row_ids,
+            sample,
+            centroid,
+        })
+    }
+
+    // PQ encode the vectors, and write each of them with its node id to its closest
+    // partitions, returns the PQ codes and the node closest to the centroid
+    async fn partition_vectors(
+        &self,
+        vectors: &SpilledVectors,
+        quantizer: &ProductQuantizer,
+        centroids: Option<Arc<Vec<f32>>>,
+        num_partitions: usize,
+        tmp_store: &ObjectStore,
+        tmp_path: &Path,
+    ) -> Result<(RecordBatch, u32)> {
+        let dimension = vectors.dimension;
+        let vector_size = 4 * dimension;
+        let mut writers = Vec::with_capacity(num_partitions);
+        for partition in 0..num_partitions {
+            let path = partition_path(tmp_path, partition);
+            writers.push(RecordWriter::try_new(tmp_store, &path, 4 + vector_size).await?);
+        }
+
+        let schema: SchemaRef = Arc::new(Schema::new(vec![
+            ROW_ID_FIELD.clone(),
+            Field::new(
+                DISKANN_VECTOR_COLUMN,
+                DataType::FixedSizeList(
+                    Arc::new(Field::new("item", DataType::Float32, true)),
+                    dimension as i32,
+                ),
+                true,
+            ),
+        ]));
+        let transformer = Arc::new(PQTransformer::new(
+            quantizer.clone(),
+            DISKANN_VECTOR_COLUMN,
+            PQ_CODE_COLUMN,
+        ));
+        let centroid = Arc::new(vectors.centroid.clone());
+
+        let mut codes = Vec::new();
+        let mut entry = (OrderedFloat(f32::MAX), 0);
+        let mut reader = RecordReader::try_new(tmp_store, &vectors.path, vector_size).await?;
+        let mut start = 0;
+        while let Some(chunk) = reader.next_chunk(CHUNK_SIZE).await? {
+            let len = chunk.len() / vector_size;
+            let row_ids = UInt64Array::from(vectors.row_ids[start..start + len].to_vec());
+            let schema = schema.clone();
+            let transformer = transformer.clone();
+            let centroids = centroids.clone();
+            let centroid = centroid.clone();
+            let values = chunk.clone();
+            let (batch, partitions, closest) = spawn_cpu(move || {
+                let values = Float32Array::from_iter_values(get_f32s(&values));
+                let closest = values
+                    .values()
+                    .par_chunks(dimension)
+                    .enumerate()
+                    .map(|(i, vector)| (OrderedFloat(l2(centroid.as_slice(), vector)), i as u32))
+                    .min()
+                    .expect("the chunk is not empty");
+                let partitions = match centroids {
+                    Some(centroids) => values
+                        .values()
+                        .par_chunks(dimension)
+                        .map(|vector| -> Result<Vec<u32>> {
+                            let partitions = kmeans_find_partitions(
+                                centroids.as_slice(),
+                                vector,
+                                PARTITION_REPLICAS,
+                                DistanceType::L2,
+                            )?;
+                            Ok(partitions.values().to_vec())
+                        })
+                        .collect::<Result<Vec<_>>>()?,
+                    None => vec![vec![0]; len],
+                };
+                let vectors = FixedSizeListArray::try_new_from_values(values, dimension as i32)?;
+                let batch =
+                    RecordBatch::try_new(schema, vec![Arc::new(row_ids), Arc::new(vectors)])?;
+                Ok((transformer.transform(&batch)?, partitions, closest))
+            })
+            .await?;
+
+            entry = entry.min((closest.0, start as u32 + closest.1));
+            for (i, (record, partitions)) in
+                chunk.chunks_exact(vector_size).zip(partitions).enumerate()
+            {
+                let node_id = (start + i) as u32;
+                for partition in partitions {
+                    writers[partition as usize]
+                        .write(|buf| {
+                            put_u32(buf, node_id);
+                            buf.extend_from_slice(record);
+                        })
+                        .await?;
+                }
+            }
+            codes.push(batch);
+            start += len;
+        }
+        for writer in writers {
+            writer.finish().await?;
+        }
+
+        let codes = concat_batches(&codes[0].schema(), &codes)?;
+        Ok((codes, entry.1))
+    }
+
+    // build the graph of a partition and write the edges of its nodes in node order,
+    // returns the path of the edges, or `None` if the partition is empty
+    async fn build_partition(
+        &self,
+        partition: usize,
+        dimension: usize,
+        tmp_store: &ObjectStore,
+        tmp_path: &Path,
+    ) -> Result<Option<Path>> {
+        let path = partition_path(tmp_path, partition);
+        let record_size = 4 + 4 * dimension;
+        let mut reader = RecordReader::try_new(tmp_store, &path, record_size).await?;
+        let mut ids = Vec::with_capacity(reader.num_records());
+        let mut values = Vec::with_capacity(reader.num_records() * dimension);
+        while let Some(chunk) = reader.next_chunk(CHUNK_SIZE).await? {
+            for record in chunk.chunks_exact(record_size) {
+                ids.push(get_u32(record, 0));
+                values.extend(get_f32s(&record[4..]));
+            }
+        }
+        tmp_store.delete(&path).await?;
+        if ids.is_empty() {
+            return Ok(None);
+        }
+
+        info!(
+            "DiskANN: building graph of partition {} with {} nodes",
+            partition,
+            ids.len()
+        );
+        let params = self.params.clone();
+        let neighbors = spawn_cpu(move || {
+            let graph = VamanaGraph::build(&values, dimension, &params);
+            Ok(graph
+                .neighbors
+                .iter()
+                .enumerate()
+                .map(|(id, neighbors)| {
+                    neighbors
+                        .iter()
+                        .map(|&neighbor| (neighbor, graph.distance(id as u32, neighbor)))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>())
+        })
+        .await?;
+
+        let path = edges_path(tmp_path, partition);
+        let max_degree = self.params.max_degree;
+        let mut writer =
+            RecordWriter::try_new(tmp_store, &path, edge_record_size(max_degree)).await?;
+        // the ids are increasing, the vectors are partitioned in node order
+        for (&id, neighbors) in ids.iter().zip(neighbors) {
+            let neighbors = neighbors
+                .iter()
+                .map(|&(neighbor, dist)| (ids[neighbor as usize], dist));
+            writer
+                .write(|buf| encode_edges(buf, id, neighbors, max_degree))
+                .await?;
+        }
+        writer.finish().await?;
+        Ok(Some(path))
+    }
+
+    // merge the edges of the partition graphs node by node into the graph file,
+    // a node of several partitions keeps its closest neighbors of all of them
+    async fn merge_partitions(
+        &self,
+        vectors: &SpilledVectors,
+        edge_files: &[Path],
+        tmp_store: &ObjectStore,
+        object_store: &ObjectStore,
+        path: &Path,
+    ) -> Result<()> {
+        let max_degree = self.params.max_degree;
+        let mut cursors = Vec::with_capacity(edge_files.len());
+        let mut heap = BinaryHeap::with_capacity(edge_files.len());
+        for (i, edge_file) in edge_files.iter().enumerate() {
+            let cursor =
+                EdgeCursor::try_new(tmp_store, edge_file, edge_record_size(max_degree)).await?;
+            if let Some(id) = cursor.node_id() {
+                heap.push(Reverse((id, i)));
+            }
+            cursors.push(cursor);
+        }
+
+        let vector_size = 4 * vectors.dimension;
+        let mut writer =
+            GraphWriter::try_new(object_store, path, vectors.dimension, max_degree).await?;
+        let mut reader = RecordReader::try_new(tmp_store, &vectors.path, vector_size).await?;
+        let mut node_id = 0;
+        let mut neighbors = Vec::new();
+        let mut vector = Vec::with_capacity(vectors.dimension);
+        while let Some(chunk) = reader.next_chunk(CHUNK_SIZE).await? {
+            for record in chunk.chunks_exact(vector_size) {
+                neighbors.clear();
+                while let Some(&Reverse((id, i))) = heap.peek() {
+                    if id != node_id {
+                        break;
+                    }
+                    heap.pop();
+                    let cursor = &mut cursors[i];
+                    decode_edges(
+                        cursor.record().expect("the cursor has a record"),
+                        &mut neighbors,
+                    );
+                    cursor.advance().await?;
+                    if let Some(id) = cursor.node_id() {
+                        heap.push(Reverse((id, i)));
+                    }
+                }
+                neighbors.sort_unstable_by_key(|(neighbor, _)| *neighbor);
+                neighbors.dedup_by_key(|(neighbor, _)| *neighbor);
+                neighbors.sort_unstable_by(|a, b| a.1.total_cmp(&b.1));
+                neighbors.truncate(max_degree);
+
+                vector.clear();
+                vector.extend(get_f32s(record));
+                let ids = neighbors
+                    .iter()
+                    .map(|(neighbor, _)| *neighbor)
+                    .collect::<Vec<_>>();
+                writer.write_node(&vector, &ids).await?;
+                node_id += 1;
+            }
+        }
+        writer.finish().await
+    }
+}
+
+fn partition_path(tmp_path: &Path, partition: usize) -> Path {
+    tmp_path.child(format!("partition-{}", partition))
+}
+
+fn edges_path(tmp_path: &Path, partition: usize) -> Path {
+    tmp_path.child(format!("edges-{}", partition))
+}
+
+// the edges of a node in a partition graph, the neighbors are global node ids:
+// | node: u32 | degree: u32 | max_degree x (neighbor: u32, distance: f32) |
+fn edge_record_size(max_degree: usize) -> usize {
+    8 + 8 * max_degree
+}
+
+fn encode_edges(
+    buf: &mut Vec<u8>,
+    id: u32,
+    neighbors: impl ExactSizeIterator<Item = (u32, f32)>,
+    max_degree: usize,
+) {
+    let degree = neighbors.len();
+    debug_assert!(degree <= max_degree);
+    put_u32(buf, id);
+    put_u32(buf, degree as u32);
+    for (neighbor, dist) in neighbors {
+        put_u32(buf, neighbor);
+        put_f32s(buf, &[dist]);
+    }
+    buf.resize(buf.len() + 8 * (max_degree - degree), 0);
+}
+
+fn decode_edges(record: &[u8], neighbors: &mut Vec<(u32, f32)>) {
+    let degree = get_u32(record, 4) as usize;
+    neighbors.extend((0..degree).map(|i| {
+        let offset = 8 + 8 * i;
+        (
+            get_u32(record, offset),
+            f32::from_bits(get_u32(record, offset + 4)),
+        )
+    }));
+}
+
+// reads the edge records of a partition in node order
+struct EdgeCursor {
+    reader: RecordReader,
+    chunk: Bytes,
+    offset: usize,
+    record_size: usize,
+}
+
+impl EdgeCursor {
+    async fn try_new(object_store: &ObjectStore, path: &Path, record_size: usize) -> Result<Self> {
+        let mut reader = RecordReader::try_new(object_store, path, record_size).await?;
+        let chunk = reader.next_chunk(CHUNK_SIZE).await?.unwrap_or_default();
+        Ok(Self {
+            reader,
+            chunk,
+            offset: 0,
+            record_size,
+        })
+    }
+
+    fn record(&self) -> Option<&[u8]> {
+        self.chunk.get(self.offset..self.offset + self.record_size)
+    }
+
+    fn node_id(&self) -> Option<u32> {
+        self.record().map(|record| get_u32(record, 0))
+    }
+
+    async fn advance(&mut self) -> Result<()> {
+        self.offset += self.record_size;
+        if self.offset >= self.chunk.len() {
+            self.chunk = self
+                .reader
+                .next_chunk(CHUNK_SIZE)
+                .await?
+                .unwrap_or_default();
+            self.offset = 0;
+        }
+        Ok(())
+    }
+}
+
+/// The Vamana graph while it's being built.
+///
+/// The graph is built with L2 distance, the vectors are normalized for cosine
+/// distance so that's the same order, and for dot product the searches are
+/// guided by the PQ distances of the query anyway.
+pub(crate) struct VamanaGraph<'a> {
+    vectors: &'a [f32],
+    dimension: usize,
+    pub entry: u32,
+    pub neighbors: Vec<Vec<u32>>,
+}
+
+impl<'a> VamanaGraph<'a> {
+    pub fn build(vectors: &'a [f32], dimension: usize, params: &DiskAnnBuildParams) -> Self {
+        let num_nodes = vectors.len() / dimension;
+        let mut graph = Self {
+            vectors,
+            dimension,
+            entry: 0,
+            neighbors: vec![Vec::new(); num_nodes],
+        };
+        if num_nodes <= 1 {
+            return graph;
+        }
+
+        graph.entry = graph.medoid();
+        graph.init_random(params.max_degree);
+
+        let mut order = (0..num_nodes as u32).collect::<Vec<_>>();
+        let max_batch_size = ((num_nodes as f32 * MAX_BATCH_FRACTION).ceil() as usize).max(1);
+        for alpha in [1.0, params.alpha] {
+            order.shuffle(&mut thread_rng());
+            let mut start = 0;
+            let mut batch_size = 1;
+            while start < num_nodes {
+                let end = (start + batch_size).min(num_nodes);
+                graph.insert_batch(&order[start..end], alpha, params);
+                start = end;
+                batch_size = (batch_size * 2).min(max_batch_size);
+            }
+        }
+        graph
+    }
+
+    fn vector(&self, id: u32) -> &[f32] {
+        let start = id as usize * self.dimension;
+        &self.vectors[start..start + self.dimension]
+    }
+
+    fn distance(&self, a: u32, b: u32) -> f32 {
+        l2(self.vector(a), self.vector(b))
+    }
+
+    // the node closest to the centroid
+    fn medoid(&self) -> u32 {
+        let num_nodes = self.neighbors.len();
+        let mut centroid = vec![0.0_f32; self.dimension];
+        for vector in self.vectors.chunks_exact(self.dimension) {
+            for (c, v) in centroid.iter_mut().zip(vector) {
+                *c += v;
+            }
+        }
+        centroid.iter_mut().for_each(|c| *c /= num_nodes as f32);
+        (0..num_nodes as u32)
+            .into_par_iter()
+            .map(|id| (OrderedFloat(l2(&centroid, self.vector(id))), id))
+            .min()
+            .map(|(_, id)| id)
+            .unwrap_or(0)
+    }
+
+    fn init_random(&mut self, max_degree: usize) {
+        let num_nodes = self.neighbors.len();
+        let degree = max_degree.min(num_nodes - 1);
+        self.neighbors
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(id, neighbors)| {
+                let mut rng = thread_rng();
+                // sample from the other nodes, skipping the node itself
+                *neighbors = sample(&mut rng, num_nodes - 1, degree)
+                    .iter()
+                    .map(|i| if i >= id { i as u32 + 1 } else { i as u32 })
+                    .collect();
+            });
+    }
+
+    fn insert_batch(&mut self, batch: &[u32], alpha: f32, params: &DiskAnnBuildParams) {
+        let num_nodes = self.neighbors.len();
+        let this = &*self;
+        let updates = batch
+            .par_iter()
+            .map_init(
+                || VisitedGenerator::new(num_nodes),
+                |visited_generator, &id| {
+                    let mut visited = visited_generator.generate(num_nodes);
+                    let mut candidates =
+                        this.search(this.vector(id), params.search_list_size, &mut visited);
+                    candidates.extend(this.neighbors[id as usize].iter().map(|&neighbor| {
+                        OrderedNode::new(neighbor, this.distance(id, neighbor).into())
+                    }));
+                    let neighbors = this.robust_prune(id, candidates, alpha, params.max_degree);
+                    (id, neighbors)
+                },
+            )
+            .collect::<Vec<_>>();
+
+        let mut reverse_edges: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (id, neighbors) in updates {
+            for &neighbor in &neighbors {
+                reverse_edges.entry(neighbor).or_default().push(id);
+            }
+            self.neighbors[id as usize] = neighbors;
+        }
+
+        let this = &*self;
+        let updates = reverse_edges
+            .into_par_iter()
+            .map(|(id, new_neighbors)| {
+                let mut neighbors = this.neighbors[id as usize].clone();
+                for neighbor in new_neighbors {
+                    if !neighbors.contains(&neighbor) {
+                        neighbors.push(neighbor);
+                    }
+                }
+                if neighbors.len() > params.max_degree {
+                    let candidates = neighbors
+                        .iter()
+                        .map(|&neighbor| {
+                            OrderedNode::new(neighbor, this.distance(id, neighbor).into())
+                        })
+                        .collect();
+                    neighbors = this.robust_prune(id, candidates, alpha, params.max_degree);
+                }
+                (id, neighbors)
+            })
+            .collect::<Vec<_>>();
+        for (id, neighbors) in updates {
+            self.neighbors[id as usize] = neighbors;
+        }
+    }
+
+    /// Greedy search from the entry, returns the expanded nodes.
+    fn search(&self, query: &[f32], list_size: usize, visited: &mut Visited) -> Vec<OrderedNode> {
+        let mut candidates = CandidateList::new(list_size);
+        visited.insert(self.entry);
+        candidates.insert(OrderedNode::new(
+            self.entry,
+            l2(query, self.vector(self.entry)).into(),
+        ));
+
+        let mut expanded = Vec::new();
+        while let Some(node) = candidates.next_unexpanded() {
+            for &neighbor in &self.neighbors[node.id as usize] {
+                if visited.contains(neighbor) {
+                    continue;
+                }
+                visited.insert(neighbor);
+                candidates.insert(OrderedNode::new(
+                    neighbor,
+                    l2(query, self.vector(neighbor)).into(),
+                ));
+            }
+            expanded.push(node);
+        }
+        expanded
+    }
+
+    /// Select at most `max_degree` neighbors from the candidates, a candidate is
+    /// skipped if it's `alpha` times closer to a selected neighbor than to the node.
+    fn robust_prune(
+        &self,
+        id: u32,
+        mut candidates: Vec<OrderedNode>,
+        alpha: f32,
+        max_degree: usize,
+    ) -> Vec<u32> {
+        candidates.retain(|node| node.id != id);
+        candidates.sort_unstable_by(|a, b| a.dist.cmp(&b.dist).then(a.id.cmp(&b.id)));
+        candidates.dedup_by_key(|node| node.id);
+
+        // the distances are squared L2
+        let alpha = alpha * alpha;
+        let mut pruned = vec![false; candidates.len()];
+        let mut neighbors = Vec::with_capacity(max_degree);
+        for i in 0..candidates.len() {
+            if pruned[i] {
+                continue;
+            }
+            let selected = candidates[i].id;
+            neighbors.push(selected);
+            if neighbors.len() == max_degree {
+                break;
+            }
+            for j in i + 1..candidates.len() {
+                if !pruned[j]
+                    && alpha * self.distance(selected, candidates[j].id) <= candidates[j].dist.0
+                {
+                    pruned[j] = true;
+                }
+            }
+        }
+        neighbors
+    }
+}
+
+/// The sorted list of the best candidates of a search, with whether each of them
+/// has been expanded.
+pub(crate) struct CandidateList {
+    capacity: usize,
+    nodes: Vec<(OrderedNode, bool)>,
+}
+
+impl CandidateList {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            nodes: Vec::with_capacity(capacity + 1),
+        }
+    }
+
+    pub fn insert(&mut self, node: OrderedNode) {
+        if self.nodes.len() == self.capacity
+            && node.dist >= self.nodes.last().expect("capacity is positive").0.dist
+        {
+            return;
+        }
+        let pos = self.nodes.partition_point(|(n, _)| n.dist <= node.dist);
+        self.nodes.insert(pos, (node, false));
+        self.nodes.truncate(self.capacity);
+    }
+
+    /// Mark the closest unexpanded candidate as expanded and return it.
+    pub fn next_unexpanded(&mut self) -> Option<OrderedNode> {
+        self.nodes
+            .iter_mut()
+            .find(|(_, expanded)| !*expanded)
+            .map(|(node, expanded)| {
+                *expanded = true;
+                node.clone()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use lance_testing::datagen::generate_random_array;
+
+    #[test]
+    fn test_candidate_list() {
+        let mut candidates = CandidateList::new(3);
+        for (id, dist) in [(0, 5.0), (1, 1.0), (2, 3.0), (3, 4.0), (4, 0.5)] {
+            candidates.insert(OrderedNode::new(id, OrderedFloat(dist)));
+        }
+        let mut ids = vec![];
+        while let Some(node) = candidates.next_unexpanded() {
+            ids.push(node.id);
+        }
+        assert_eq!(ids, vec![4, 1, 2]);
+    }
+
+    #[test]
+    fn test_vamana_graph() {
+        const DIM: usize = 16;
+        const NUM_NODES: usize = 1000;
+        let vectors = generate_random_array(DIM * NUM_NODES);
+        let params = DiskAnnBuildParams::default()
+            .max_degree(16)
+            .search_list_size(32);
+        let graph = VamanaGraph::build(vectors.values(), DIM, &params);
+
+        assert!(graph
+            .neighbors
+            .iter()
+            .enumerate()
+            .all(|(id, neighbors)| !neighbors.is_empty()
+                && neighbors.len() <= 16
+                && !neighbors.contains(&(id as u32))));
+
+        // the greedy search finds the node itself from the entry
+        let mut visited_generator = VisitedGenerator::new(NUM_NODES);
+        let found = (0..NUM_NODES as u32)
+            .filter(|&id| {
+                let mut visited = visited_generator.generate(NUM_NODES);
+                let expanded = graph.search(graph.vector(id), 32, &mut visited);
+                expanded.iter().any(|node| node.id == id)
+            })
+            .count();
+        assert!(found as f32 / NUM_NODES as f32 >= 0.99, "found {}", found);
+    }
+}
@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! The on-disk layout of the DiskANN graph.
+//!
+//! Every node is a fixed size record, so the record of node `i` starts at
+//! `i * node_size` and a hop of the search is a single range read per node:
+//!
+//! ```text
+//! | vector: dimension x f32 | degree: u32 | neighbors: max_degree x u32 |
+//! ```
+//!
+//! All the values are little endian, the unused neighbor slots are zero.
+
+use std::ops::Range;
+
+use lance_core::{Error, Result};
+use lance_io::object_store::ObjectStore;
+use lance_io::object_writer::ObjectWriter;
+use lance_io::scheduler::FileScheduler;
+use object_store::path::Path;
+use snafu::location;
+use tokio::io::AsyncWriteExt;
+
+// the number of nodes read or written in one request when going through the whole graph
+const READ_CHUNK_SIZE: usize = 4096;
+
+/// The size in bytes of a node record.
+pub fn node_size(dimension: usize, max_degree: usize) -> usize {
+    4 * dimension + 4 + 4 * max_degree
+}
+
+/// A node read from the graph file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskNode {
+    pub vector: Vec<f32>,
+    pub neighbors: Vec<u32>,
+}
+
+fn encode_node(buf: &mut Vec<u8>, vector: &[f32], neighbors: &[u32], max_degree: usize) {
+    debug_assert!(neighbors.len() <= max_degree);
+    for v in vector {
+        buf.extend_from_slice(&v.to_le_bytes());
+    }
+    buf.extend_from_slice(&(neighbors.len() as u32).to_le_bytes());
+    for neighbor in neighbors {
+        buf.extend_from_slice(&neighbor.to_le_bytes());
+    }
+    buf.resize(buf.len() + 4 * (max_degree - neighbors.len()), 0);
+}
+
+fn decode_node(bytes: &[u8], dimension: usize) -> Result<DiskNode> {
+    let read_u32 = |offset: usize| {
+        u32::from_le_bytes([
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ])
+    };
+    let vector = (0..dimension)
+        .map(|i| f32::from_bits(read_u32(4 * i)))
+        .collect();
+    let degree = read_u32(4 * dimension) as usize;
+    let neighbors_offset = 4 * dimension + 4;
+    if neighbors_offset + 4 * degree > bytes.len() {
+        return Err(Error::Index {
+            message: format!(
+                "DiskANN: corrupted node record, degree {} overflows {} bytes",
+                degree,
+                bytes.len()
+            ),
+            location: location!(),
+        });
+    }
+    let neighbors = (0..degree)
+        .map(|i| read_u32(neighbors_offset + 4 * i))
+        .collect();
+    Ok(DiskNode { vector, neighbors })
+}
+
+/// Writes the node records of a graph in node order.
+pub struct GraphWriter {
+    writer: ObjectWriter,
+    buf: Vec<u8>,
+    dimension: usize,
+    max_degree: usize,
+}
+
+impl GraphWriter {
+    pub async fn try_new(
+        object_store: &ObjectStore,
+        path: &Path,
+        dimension: usize,
+        max_degree: usize,
+    ) -> Result<Self> {
+        Ok(Self {
+            writer: object_store.create(path).await?,
+            // buffer a chunk of nodes so we don't issue a write per node
+            buf: Vec::with_capacity(node_size(dimension, max_degree) * READ_CHUNK_SIZE),
+            dimension,
+            max_degree,
+        })
+    }
+
+    /// Append the record of the next node, it has at most `max_degree` neighbors.
+    pub async fn write_node(&mut self, vector: &[f32], neighbors: &[u32]) -> Result<()> {
+        debug_assert_eq!(vector.len(), self.dimension);
+        encode_node(&mut self.buf, vector, neighbors, self.max_degree);
+        if self.buf.len() >= node_size(self.dimension, self.max_degree) * READ_CHUNK_SIZE {
+            self.writer.write_all(&self.buf).await?;
+            self.buf.clear();
+        }
+        Ok(())
+    }
+
+    pub async fn finish(mut self) -> Result<()> {
+        self.writer.write_all(&self.buf).await?;
+        self.writer.shutdown().await?;
+        Ok(())
+    }
+}
+
+/// Reads the node records with the I/O scheduler.
+#[derive(Debug, Clone)]
+pub struct GraphReader {
+    file: FileScheduler,
+    dimension: usize,
+    max_degree: usize,
+}
+
+impl GraphReader {
+    pub fn new(file: FileScheduler, dimension: usize, max_degree: usize) -> Self {
+        Self {
+            file,
+            dimension,
+            max_degree,
+        }
+    }
+
+    fn node_range(&self, id: u32) -> Range<u64> {
+        let node_size = node_size(self.dimension, self.max_degree) as u64;
+        let start = id as u64 * node_size;
+        start..start + node_size
+    }
+
+    /// Read the nodes of the given ids.
+    ///
+    /// The ids are read in one request, sorted so the nearby records are coalesced,
+    /// and the nodes are returned in the order of the sorted ids.
+    pub async fn read_nodes(&self, ids: &[u32]) -> Result<Vec<(u32, DiskNode)>> {
+        let mut ids = ids.to_vec();
+        ids.sort_unstable();
+        ids.dedup();
+        let ranges = ids.iter().map(|&id| self.node_range(id)).collect();
+        let bytes = self.file.submit_request(ranges, 0).await?;
+        ids.into_iter()
+            .zip(bytes)
+            .map(|(id, bytes)| Ok((id, decode_node(&bytes, self.dimension)?)))
+            .collect()
+    }
+
+    /// Read the vectors of the nodes in the range of ids, in node order.
+    pub async fn read_vectors(&self, ids: Range<usize>) -> Result<Vec<f32>> {
+        let node_size = node_size(self.dimension, self.max_degree);
+        let mut vectors = Vec::with_capacity(ids.len() * self.dimension);
+        for start in ids.clone().step_by(READ_CHUNK_SIZE) {
+            let end = (start + READ_CHUNK_SIZE).min(ids.end);
+            let range = (start * node_size) as u64..(end * node_size) as u64;
+            let bytes = self.file.submit_single(range, 0).await?;
+            for record in bytes.chunks_exact(node_size) {
+                vectors.extend(decode_node(record, self.dimension)?.vector);
+            }
+        }
+        Ok(vectors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_node() {
+        let vector = vec![1.0, -2.5, 3.25];
+        let neighbors = vec![7, 0, 42];
+        let mut buf = Vec::new();
+        encode_node(&mut buf, &vector, &neighbors, 5);
+        assert_eq!(buf.len(), node_size(3, 5));
+
+        let node = decode_node(&buf, 3).unwrap();
+        assert_eq!(node.vector, vector);
+        assert_eq!(node.neighbors, neighbors);
+
+        // corrupted degree
+        buf[12..16].copy_from_slice(&10_u32.to_le_bytes());
+        assert!(decode_node(&buf, 3).is_err());
+    }
+}
@@ -0,0 +1,631 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Search the DiskANN graph.
+//!
+//! The search is a beam search: the closest unexpanded candidates by PQ distance are
+//! read from the graph file together, their neighbors are added to the candidates
+//! by PQ distance, and the visited nodes are ranked by the exact distances of the
+//! vectors stored in their records.
+
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use arrow::compute::{cast, concat_batches, filter_record_batch};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float32Type, UInt64Type};
+use arrow_array::{
+    ArrayRef, BooleanArray, FixedSizeListArray, Float32Array, RecordBatch, UInt32Array, UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema};
+use async_trait::async_trait;
+use datafusion::error::DataFusionError;
+use datafusion::execution::SendableRecordBatchStream;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use deepsize::DeepSizeOf;
+use futures::{StreamExt, TryStreamExt};
+use lance_arrow::{FixedSizeListArrayExt, RecordBatchExt};
+use lance_core::cache::LanceCache;
+use lance_core::{Error, Result, ROW_ID, ROW_ID_FIELD};
+use lance_encoding::decoder::{DecoderPlugins, FilterExpression};
+use lance_file::v2::reader::{FileReader, FileReaderOptions};
+use lance_io::object_store::ObjectStore;
+use lance_io::scheduler::{ScanScheduler, SchedulerConfig};
+use lance_io::traits::Reader;
+use lance_io::utils::CachedFileSize;
+use lance_io::ReadBatchParams;
+use lance_linalg::distance::DistanceType;
+use object_store::path::Path;
+use roaring::RoaringBitmap;
+use serde_json::json;
+use snafu::location;
+use tracing::instrument;
+
+use super::builder::CandidateList;
+use super::graph::GraphReader;
+use super::{
+    write_index_file, DiskAnnBuildParams, DiskAnnMetadata, DELETED_ROW_ID, DISKANN_GRAPH_FILE_NAME,
+    DISKANN_METADATA_KEY, DISKANN_VECTOR_COLUMN,
+};
+use crate::frag_reuse::FragReuseIndex;
+use crate::metrics::MetricsCollector;
+use crate::prefilter::PreFilter;
+use crate::vector::graph::OrderedNode;
+use crate::vector::ivf::storage::IvfModel;
+use crate::vector::pq::storage::{ProductQuantizationMetadata, ProductQuantizationStorage};
+use crate::vector::pq::ProductQuantizer;
+use crate::vector::quantizer::{
+    Quantization, QuantizationType, Quantizer, QuantizerMetadata, QuantizerStorage,
+};
+use crate::vector::storage::{DistCalculator, VectorStore, STORAGE_METADATA_KEY};
+use crate::vector::v3::subindex::SubIndexType;
+use crate::vector::{Query, VectorIndex, VECTOR_RESULT_SCHEMA};
+use crate::{Index, IndexMetadata, IndexType, INDEX_FILE_NAME, INDEX_METADATA_SCHEMA_KEY};
+
+/// The number of nodes read from the graph file in one hop.
+const BEAM_WIDTH: usize = 4;
+
+/// The number of nodes of a batch of [DiskAnnIndex::to_batch_stream].
+const STREAM_BATCH_SIZE: usize = 8192;
+
+/// DiskANN index, the PQ codes and the row ids are in memory,
+/// the graph and the vectors are read from the graph file while searching.
+#[derive(Debug)]
+pub struct DiskAnnIndex {
+    metadata: DiskAnnMetadata,
+    distance_type: DistanceType,
+    storage: Arc<ProductQuantizationStorage>,
+    quantizer: Quantizer,
+    graph: GraphReader,
+    index_dir: Path,
+
+    // DiskANN has no IVF, this is an empty model with no partition
+    ivf: IvfModel,
+}
+
+impl DeepSizeOf for DiskAnnIndex {
+    fn deep_size_of_children(&self, context: &mut deepsize::Context) -> usize {
+        self.metadata.deep_size_of_children(context) + self.storage.deep_size_of_children(context)
+    }
+}
+
+impl DiskAnnIndex {
+    /// Open the DiskANN index in `index_dir`.
+    pub async fn try_new(
+        object_store: Arc<ObjectStore>,
+        index_dir: Path,
+        frag_reuse_index: Option<Arc<FragReuseIndex>>,
+        file_metadata_cache: &LanceCache,
+    ) -> Result<Self> {
+        let scheduler_config = SchedulerConfig::max_bandwidth(&object_store);
+        let scheduler = ScanScheduler::new(object_store, scheduler_config);
+
+        let reader = FileReader::try_open(
+            scheduler
+                .open_file(
+                    &index_dir.child(INDEX_FILE_NAME),
+                    &CachedFileSize::unknown(),
+                )
+                .await?,
+            None,
+            Arc::<DecoderPlugins>::default(),
+            file_metadata_cache,
+            FileReaderOptions::default(),
+        )
+        .await?;
+        let schema_metadata = &reader.schema().metadata;
+        let get_metadata = |key: &str| {
+            schema_metadata.get(key).ok_or(Error::Index {
+                message: format!("{} not found", key),
+                location: location!(),
+            })
+        };
+        let index_metadata: IndexMetadata =
+            serde_json::from_str(get_metadata(INDEX_METADATA_SCHEMA_KEY)?)?;
+        let distance_type = DistanceType::try_from(index_metadata.distance_type.as_str())?;
+        let metadata: DiskAnnMetadata = serde_json::from_str(get_metadata(DISKANN_METADATA_KEY)?)?;
+        let mut pq_metadata: ProductQuantizationMetadata =
+            serde_json::from_str(get_metadata(STORAGE_METADATA_KEY)?)?;
+        if let Some(pos) = pq_metadata.buffer_index() {
+            let bytes = reader.read_global_buffer(pos).await?;
+            pq_metadata.parse_buffer(bytes)?;
+        }
+
+        let schema = Arc::new(reader.schema().as_ref().into());
+        let batch = if reader.num_rows() == 0 {
+            RecordBatch::new_empty(schema)
+        } else {
+            let batches = reader
+                .read_stream(
+                    ReadBatchParams::RangeFull,
+                    u32::MAX,
+                    1,
+                    FilterExpression::no_filter(),
+                )?
+                .try_collect::<Vec<_>>()
+                .await?;
+            concat_batches(&schema, batches.iter())?
+        };
+        // the nodes are addressed by their positions, so the rows of the deleted
+        // nodes are marked instead of being removed as the storage would do
+        let batch = match frag_reuse_index {
+            Some(frag_reuse_index) => {
+                let row_ids = batch[ROW_ID]
+                    .as_primitive::<UInt64Type>()
+                    .values()
+                    .iter()
+                    .map(|&row_id| match row_id {
+                        DELETED_ROW_ID => DELETED_ROW_ID,
+                        _ => frag_reuse_index
+                            .remap_row_id(row_id)
+                            .unwrap_or(DELETED_ROW_ID),
+                    })
+                    .collect::<Vec<_>>();
+                batch.replace_column_by_name(ROW_ID, Arc::new(UInt64Array::from(row_ids)))?
+            }
+            None => batch,
+        };
+        let storage =
+            ProductQuantizationStorage::try_from_batch(batch, &pq_metadata, distance_type, None)?;
+        let quantizer = ProductQuantizer::from_metadata(storage.metadata(), distance_type)?;
+
+        let graph = GraphReader::new(
+            scheduler
+                .open_file(
+                    &index_dir.child(DISKANN_GRAPH_FILE_NAME),
+                    &CachedFileSize::unknown(),
+                )
+                .await?,
+            metadata.dimension,
+            metadata.max_degree,
+        );
+
+        Ok(Self {
+            metadata,
+            distance_type,
+            storage: Arc::new(storage),
+            quantizer,
+            graph,
+            index_dir,
+            ivf: IvfModel::empty(),
+        })
+    }
+
+    pub fn metadata(&self) -> &DiskAnnMetadata {
+        &self.metadata
+    }
+
+    /// The parameters the graph was built with.
+    pub fn build_params(&self) -> DiskAnnBuildParams {
+        DiskAnnBuildParams::default()
+            .max_degree(self.metadata.max_degree)
+            .search_list_size(self.metadata.search_list_size)
+            .alpha(self.metadata.alpha)
+    }
+
+    /// Beam search from the entry, returns the visited nodes with their exact
+    /// distances, sorted by the distances.
+    async fn beam_search(
+        &self,
+        query: &[f32],
+        dist_calc: &impl DistCalculator,
+        list_size: usize,
+        metrics: &dyn MetricsCollector,
+    ) -> Result<Vec<OrderedNode>> {
+        let distance = self.distance_type.func::<f32>();
+        let entry = self.metadata.entry;
+
+        let mut candidates = CandidateList::new(list_size);
+        let mut visited = HashSet::new();
+        visited.insert(entry);
+        candidates.insert(OrderedNode::new(entry, dist_calc.distance(entry).into()));
+
+        let mut results = Vec::new();
+        loop {
+            let beam = (0..BEAM_WIDTH)
+                .map_while(|_| candidates.next_unexpanded())
+                .map(|node| node.id)
+                .collect::<Vec<_>>();
+            if beam.is_empty() {
+                break;
+            }
+            for (id, node) in self.graph.read_nodes(&beam).await? {
+                results.push(OrderedNode::new(id, distance(query, &node.vector).into()));
+                for neighbor in node.neighbors {
+                    if visited.insert(neighbor) {
+                        candidates.insert(OrderedNode::new(
+                            neighbor,
+                            dist_calc.distance(neighbor).into(),
+                        ));
+                    }
+                }
+            }
+        }
+        metrics.record_comparisons(visited.len());
+
+        results.sort_unstable();
+        Ok(results)
+    }
+}
+
+#[async_trait]
+impl Index for DiskAnnIndex {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_index(self: Arc<Self>) -> Arc<dyn Index> {
+        self
+    }
+
+    fn as_vector_index(self: Arc<Self>) -> Result<Arc<dyn VectorIndex>> {
+        Ok(self)
+    }
+
+    fn statistics(&self) -> Result<serde_json::Value> {
+        let num_deleted = self
+            .storage
+            .row_ids()
+            .filter(|row_id| **row_id == DELETED_ROW_ID)
+            .count();
+        Ok(json!({
+            "index_type": IndexType::DiskAnn.to_string(),
+            "distance_type": self.distance_type.to_string(),
+            "num_nodes": self.metadata.num_nodes,
+            "num_deleted_nodes": num_deleted,
+            "max_degree": self.metadata.max_degree,
+            "alpha": self.metadata.alpha,
+            "search_list_size": self.metadata.search_list_size,
+        }))
+    }
+
+    async fn prewarm(&self) -> Result<()> {
+        // the PQ codes are loaded when opening the index,
+        // the graph is expected to stay on disk
+        Ok(())
+    }
+
+    fn index_type(&self) -> IndexType {
+        IndexType::DiskAnn
+    }
+
+    async fn calculate_included_frags(&self) -> Result<RoaringBitmap> {
+        Ok(self
+            .storage
+            .row_ids()
+            .filter(|row_id| **row_id != DELETED_ROW_ID)
+            .map(|row_id| (row_id >> 32) as u32)
+            .collect())
+    }
+}
+
+#[async_trait]
+impl VectorIndex for DiskAnnIndex {
+    async fn search(
+        &self,
+        query: &Query,
+        pre_filter: Arc<dyn PreFilter>,
+        metrics: &dyn MetricsCollector,
+    ) -> Result<RecordBatch> {
+        self.search_in_partition(0, query, pre_filter, metrics)
+            .await
+    }
+
+    fn find_partitions(&self, _: &Query) -> Result<UInt32Array> {
+        Ok(UInt32Array::from(vec![0]))
+    }
+
+    fn total_partitions(&self) -> usize {
+        1
+    }
+
+    #[instrument(level = "debug", skip_all, name = "DiskAnnIndex::search")]
+    async fn search_in_partition(
+        &self,
+        _: usize,
+        query: &Query,
+        pre_filter: Arc<dyn PreFilter>,
+        metrics: &dyn MetricsCollector,
+    ) -> Result<RecordBatch> {
+        let k = query.k * query.refine_factor.unwrap_or(1) as usize;
+        let num_nodes = self.metadata.num_nodes;
+        if k == 0 || num_nodes == 0 {
+            return Ok(RecordBatch::new_empty(VECTOR_RESULT_SCHEMA.clone()));
+        }
+
+        let key = cast(&query.key, &DataType::Float32)?;
+        if key.len() != self.metadata.dimension {
+            return Err(Error::invalid_input(
+                format!(
+                    "query dim({}) doesn't match the index dim({})",
+                    key.len(),
+                    self.metadata.dimension
+                ),
+                location!(),
+            ));
+        }
+        let dist_calc = self.storage.dist_calculator(key.clone());
+        let key = key.as_primitive::<Float32Type>().values();
+
+        pre_filter.wait_for_ready().await?;
+        let mask = (!pre_filter.is_empty()).then(|| pre_filter.mask());
+        let lower_bound = query.lower_bound.unwrap_or(f32::MIN);
+        let upper_bound = query.upper_bound.unwrap_or(f32::MAX);
+
+        // grow the candidate list until there are enough results that pass the filters
+        let mut list_size = query
+            .ef
+            .unwrap_or(self.metadata.search_list_size)
+            .max(k)
+            .min(num_nodes);
+        loop {
+            let nodes = self
+                .beam_search(key, &dist_calc, list_size, metrics)
+                .await?;
+            let (distances, row_ids): (Vec<f32>, Vec<u64>) = nodes
+                .into_iter()
+                .filter_map(|node| {
+                    let row_id = self.storage.row_id(node.id);
+                    let dist = node.dist.0;
+                    (row_id != DELETED_ROW_ID
+                        && mask.as_ref().map_or(true, |mask| mask.selected(row_id))
+                        && (lower_bound..upper_bound).contains(&dist))
+                    .then_some((dist, row_id))
+                })
+                .take(k)
+                .unzip();
+
+            if row_ids.len() >= k || list_size >= num_nodes {
+                return Ok(RecordBatch::try_new(
+                    VECTOR_RESULT_SCHEMA.clone(),
+                    vec![
+                        Arc::new(Float32Array::from(distances)),
+                        Arc::new(UInt64Array::from(row_ids)),
+                    ],
+                )?);
+            }
+            list_size = (list_size * 2).min(num_nodes);
+        }
+    }
+
+    fn is_loadable(&self) -> bool {
+        false
+    }
+
+    fn use_residual(&self) -> bool {
+        false
+    }
+
+    async fn load(
+        &self,
+        _reader: Arc<dyn Reader>,
+        _offset: usize,
+        _length: usize,
+    ) -> Result<Box<dyn VectorIndex>> {
+        Err(Error::Index {
+            message: "DiskANN index can't be loaded as a sub-index".to_string(),
+            location: location!(),
+        })
+    }
+
+    /// Stream the row ids and the vectors of the nodes that are not deleted, in node
+    /// order, the vectors are read from the graph file.
+    ///
+    /// The vectors are normalized if the distance type is cosine.
+    async fn to_batch_stream(&self, with_vector: bool) -> Result<SendableRecordBatchStream> {
+        let dimension = self.metadata.dimension;
+        let num_nodes = self.metadata.num_nodes;
+        let mut fields = vec![ROW_ID_FIELD.clone()];
+        if with_vector {
+            fields.push(Field::new(
+                DISKANN_VECTOR_COLUMN,
+                DataType::FixedSizeList(
+                    Arc::new(Field::new("item", DataType::Float32, true)),
+                    dimension as i32,
+                ),
+                true,
+            ));
+        }
+        let schema = Arc::new(Schema::new(fields));
+
+        let row_ids = self.storage.batch()[ROW_ID]
+            .as_primitive::<UInt64Type>()
+            .clone();
+        let graph = self.graph.clone();
+        let batch_schema = schema.clone();
+        let stream = futures::stream::iter((0..num_nodes).step_by(STREAM_BATCH_SIZE))
+            .then(move |start| {
+                let end = (start + STREAM_BATCH_SIZE).min(num_nodes);
+                let row_ids = row_ids.slice(start, end - start);
+                let graph = graph.clone();
+                let schema = batch_schema.clone();
+                async move {
+                    let not_deleted = row_ids
+                        .values()
+                        .iter()
+                        .map(|row_id| Some(*row_id != DELETED_ROW_ID))
+                        .collect::<BooleanArray>();
+                    let mut columns: Vec<ArrayRef> = vec![Arc::new(row_ids)];
+                    if with_vector {
+                        let vectors = graph.read_vectors(start..end).await?;
+                        columns.push(Arc::new(FixedSizeListArray::try_new_from_values(
+                            Float32Array::from(vectors),
+                            dimension as i32,
+                        )?));
+                    }
+                    let batch = RecordBatch::try_new(schema, columns)?;
+                    Ok::<_, Error>(filter_record_batch(&batch, &not_deleted)?)
+                }
+            })
+            .map_err(DataFusionError::from);
+        Ok(Box::pin(RecordBatchStreamAdapter::new(schema, stream)))
+    }
+
+    fn num_rows(&self) -> u64 {
+        self.row_ids().count() as u64
+    }
+
+    fn row_ids(&self) -> Box<dyn Iterator<Item = &'_ u64> + '_> {
+        Box::new(
+            self.storage
+                .row_ids()
+                .filter(|row_id| **row_id != DELETED_ROW_ID),
+        )
+    }
+
+    async fn remap(&mut self, _mapping: &HashMap<u64, Option<u64>>) -> Result<()> {
+        Err(Error::Index {
+            message: "Remapping DiskANN in this way not supported".to_string(),
+            location: location!(),
+        })
+    }
+
+    async fn remap_to(
+        self: Arc<Self>,
+        store: ObjectStore,
+        mapping: &HashMap<u64, Option<u64>>,
+        _column: String,
+        index_dir: Path,
+    ) -> Result<()> {
+        // only the row ids change, the graph is copied as is
+        let row_ids = self
+            .storage
+            .row_ids()
+            .map(|row_id| match mapping.get(row_id) {
+                Some(Some(new_row_id)) => *new_row_id,
+                Some(None) => DELETED_ROW_ID,
+                None => *row_id,
+            })
+            .collect::<Vec<_>>();
+        let batch = self
+            .storage
+            .batch()
+            .replace_column_by_name(ROW_ID, Arc::new(UInt64Array::from(row_ids)))?;
+        write_index_file(
+            &store,
+            &index_dir.child(INDEX_FILE_NAME),
+            &batch,
+            self.storage.metadata(),
+            &self.metadata,
+            self.distance_type,
+        )
+        .await?;
+        store
+            .copy(
+                &self.index_dir.child(DISKANN_GRAPH_FILE_NAME),
+                &index_dir.child(DISKANN_GRAPH_FILE_NAME),
+            )
+            .await
+    }
+
+    fn metric_type(&self) -> DistanceType {
+        self.distance_type
+    }
+
+    fn ivf_model(&self) -> &IvfModel {
+        &self.ivf
+    }
+
+    fn quantizer(&self) -> Quantizer {
+        self.quantizer.clone()
+    }
+
+    fn sub_index_type(&self) -> (SubIndexType, QuantizationType) {
+        (SubIndexType::DiskAnn, QuantizationType::Product)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::{FixedSizeListArray, UInt64Array};
+    use lance_arrow::FixedSizeListArrayExt;
+    use lance_core::cache::LanceCache;
+    use lance_io::object_store::ObjectStore;
+    use lance_linalg::distance::DistanceType;
+    use lance_testing::datagen::generate_random_array;
+    use object_store::path::Path;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::metrics::NoOpMetricsCollector;
+    use crate::prefilter::NoFilter;
+    use crate::vector::diskann::DiskAnnBuilder;
+    use crate::vector::pq::PQBuildParams;
+
+    const DIM: usize = 32;
+    const NUM_ROWS: usize = 2000;
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_diskann_search(
+        #[values(DistanceType::L2, DistanceType::Cosine)] distance_type: DistanceType,
+        // a single graph, or the merged graphs of 8 partitions
+        #[values(NUM_ROWS, NUM_ROWS / 4)] partition_size: usize,
+    ) {
+        let test_dir = tempfile::tempdir().unwrap();
+        let index_dir = Path::from_filesystem_path(test_dir.path()).unwrap();
+        let object_store = Arc::new(ObjectStore::local());
+
+        let values = generate_random_array(NUM_ROWS * DIM);
+        let vectors = FixedSizeListArray::try_new_from_values(values, DIM as i32).unwrap();
+        let row_ids = UInt64Array::from_iter_values(0..NUM_ROWS as u64);
+        DiskAnnBuilder::new(
+            DiskAnnBuildParams::default()
+                .max_degree(32)
+                .partition_size(partition_size),
+            PQBuildParams::new(8, 8),
+            distance_type,
+        )
+        .build(row_ids, &vectors, &object_store, &index_dir)
+        .await
+        .unwrap();
+
+        let index = DiskAnnIndex::try_new(object_store, index_dir, None, &LanceCache::no_cache())
+            .await
+            .unwrap();
+        assert_eq!(index.num_rows(), NUM_ROWS as u64);
+
+        let batches = index
+            .to_batch_stream(true)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let batch = concat_batches(&batches[0].schema(), &batches).unwrap();
+        let row_ids = batch[ROW_ID].as_primitive::<UInt64Type>();
+        let stored = batch[DISKANN_VECTOR_COLUMN].as_fixed_size_list();
+        assert_eq!(row_ids.len(), NUM_ROWS);
+
+        // a stored vector must be the nearest to itself
+        let mut found = 0;
+        for i in (0..NUM_ROWS).step_by(20) {
+            let query = Query {
+                column: String::new(),
+                key: stored.value(i),
+                k: 10,
+                lower_bound: None,
+                upper_bound: None,
+                minimum_nprobes: 1,
+                maximum_nprobes: None,
+                ef: None,
+                refine_factor: None,
+                metric_type: distance_type,
+                use_index: true,
+            };
+            let results = index
+                .search(&query, Arc::new(NoFilter), &NoOpMetricsCollector)
+                .await
+                .unwrap();
+            assert_eq!(results.num_rows(), 10);
+            let result_ids = results[ROW_ID].as_primitive::<UInt64Type>();
+            if result_ids.values().contains(&row_ids.value(i)) {
+                found += 1;
+            }
+        }
+        assert!(found >= 95, "found {} of 100", found);
+    }
+}
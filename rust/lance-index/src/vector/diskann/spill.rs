@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Temporary files of fixed size records.
+//!
+//! The builder spills the vectors, the partitions and the edges of the partition
+//! graphs to these files, so only one partition is in memory at a time.
+
+use bytes::Bytes;
+use lance_core::Result;
+use lance_io::object_store::ObjectStore;
+use lance_io::object_writer::ObjectWriter;
+use lance_io::traits::Reader;
+use object_store::path::Path;
+use tokio::io::AsyncWriteExt;
+
+// the size of the buffer before it's flushed to the file
+const FLUSH_SIZE: usize = 8 * 1024 * 1024;
+
+/// Appends fixed size records to a file.
+pub(crate) struct RecordWriter {
+    writer: ObjectWriter,
+    buf: Vec<u8>,
+    record_size: usize,
+    num_records: usize,
+}
+
+impl RecordWriter {
+    pub async fn try_new(
+        object_store: &ObjectStore,
+        path: &Path,
+        record_size: usize,
+    ) -> Result<Self> {
+        Ok(Self {
+            writer: object_store.create(path).await?,
+            buf: Vec::with_capacity(FLUSH_SIZE + record_size),
+            record_size,
+            num_records: 0,
+        })
+    }
+
+    /// Append a record, `encode` must write exactly `record_size` bytes.
+    pub async fn write(&mut self, encode: impl FnOnce(&mut Vec<u8>)) -> Result<()> {
+        let len = self.buf.len();
+        encode(&mut self.buf);
+        debug_assert_eq!(self.buf.len() - len, self.record_size);
+        self.num_records += 1;
+        if self.buf.len() >= FLUSH_SIZE {
+            self.writer.write_all(&self.buf).await?;
+            self.buf.clear();
+        }
+        Ok(())
+    }
+
+    /// Flush and close the file, returns the number of records.
+    pub async fn finish(mut self) -> Result<usize> {
+        self.writer.write_all(&self.buf).await?;
+        self.writer.shutdown().await?;
+        Ok(self.num_records)
+    }
+}
+
+/// Reads the records of a file sequentially, in chunks.
+pub(crate) struct RecordReader {
+    reader: Box<dyn Reader>,
+    record_size: usize,
+    num_records: usize,
+    next: usize,
+}
+
+impl RecordReader {
+    pub async fn try_new(
+        object_store: &ObjectStore,
+        path: &Path,
+        record_size: usize,
+    ) -> Result<Self> {
+        let reader = object_store.open(path).await?;
+        let num_records = reader.size().await? / record_size;
+        Ok(Self {
+            reader,
+            record_size,
+            num_records,
+            next: 0,
+        })
+    }
+
+    pub fn num_records(&self) -> usize {
+        self.num_records
+    }
+
+    /// Read the next `max_records` records at most, `None` at the end of the file.
+    pub async fn next_chunk(&mut self, max_records: usize) -> Result<Option<Bytes>> {
+        if self.next >= self.num_records {
+            return Ok(None);
+        }
+        let end = (self.next + max_records.max(1)).min(self.num_records);
+        let bytes = self
+            .reader
+            .get_range(self.next * self.record_size..end * self.record_size)
+            .await?;
+        self.next = end;
+        Ok(Some(bytes))
+    }
+}
+
+pub(crate) fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+pub(crate) fn put_f32s(buf: &mut Vec<u8>, values: &[f32]) {
+    for v in values {
+        buf.extend_from_slice(&v.to_le_bytes());
+    }
+}
+
+pub(crate) fn get_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().expect("4 bytes"))
+}
+
+pub(crate) fn get_f32s(bytes: &[u8]) -> impl Iterator<Item = f32> + '_ {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes(b.try_into().expect("4 bytes")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_records_roundtrip() {
+        let test_dir = tempfile::tempdir().unwrap();
+        let path = Path::from_filesystem_path(test_dir.path())
+            .unwrap()
+            .child("records");
+        let object_store = ObjectStore::local();
+
+        let mut writer = RecordWriter::try_new(&object_store, &path, 8)
+            .await
+            .unwrap();
+        for i in 0..10 {
+            writer
+                .write(|buf| {
+                    put_u32(buf, i);
+                    put_f32s(buf, &[i as f32 / 2.0]);
+                })
+                .await
+                .unwrap();
+        }
+        assert_eq!(writer.finish().await.unwrap(), 10);
+
+        let mut reader = RecordReader::try_new(&object_store, &path, 8)
+            .await
+            .unwrap();
+        assert_eq!(reader.num_records(), 10);
+        let mut records = Vec::new();
+        while let Some(chunk) = reader.next_chunk(3).await.unwrap() {
+            assert!(chunk.len() <= 3 * 8);
+            for record in chunk.chunks_exact(8) {
+                records.push((get_u32(record, 0), get_f32s(&record[4..]).next().unwrap()));
+            }
+        }
+        assert_eq!(
+            records,
+            (0..10).map(|i| (i, i as f32 / 2.0)).collect::<Vec<_>>()
+        );
+    }
+}
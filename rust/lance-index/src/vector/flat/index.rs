@@ -161,6 +161,9 @@ impl IvfSubIndex for FlatIndex {
 #[derive(Debug, Clone, Serialize, Deserialize, DeepSizeOf)]
 pub struct FlatMetadata {
     pub dim: usize,
+    /// Whether the vectors are stored as int8 instead of float32.
+    #[serde(default)]
+    pub int8: bool,
 }
 
 #[async_trait::async_trait]
@@ -174,11 +177,29 @@ impl QuantizerMetadata for FlatMetadata {
 pub struct FlatQuantizer {
     dim: usize,
     distance_type: DistanceType,
+    int8: bool,
 }
 
 impl FlatQuantizer {
     pub fn new(dim: usize, distance_type: DistanceType) -> Self {
-        Self { dim, distance_type }
+        Self {
+            dim,
+            distance_type,
+            int8: false,
+        }
+    }
+
+    /// Create a quantizer that stores the int8 vectors as they are.
+    pub fn new_int8(dim: usize, distance_type: DistanceType) -> Self {
+        Self {
+            dim,
+            distance_type,
+            int8: true,
+        }
+    }
+
+    pub fn is_int8(&self) -> bool {
+        self.int8
     }
 }
 
@@ -188,8 +209,12 @@ impl Quantization for FlatQuantizer {
     type Storage = FlatFloatStorage;
 
     fn build(data: &dyn Array, distance_type: DistanceType, _: &Self::BuildParams) -> Result<Self> {
-        let dim = data.as_fixed_size_list().value_length();
-        Ok(Self::new(dim as usize, distance_type))
+        let data = data.as_fixed_size_list();
+        let dim = data.value_length() as usize;
+        match data.value_type() {
+            DataType::Int8 => Ok(Self::new_int8(dim, distance_type)),
+            _ => Ok(Self::new(dim, distance_type)),
+        }
     }
 
     fn retrain(&mut self, _: &dyn Array) -> Result<()> {
@@ -208,11 +233,15 @@ impl Quantization for FlatQuantizer {
         Ok(Quantizer::Flat(Self {
             dim: metadata.dim,
             distance_type,
+            int8: metadata.int8,
         }))
     }
 
     fn metadata(&self, _: Option<crate::vector::quantizer::QuantizationMetadata>) -> FlatMetadata {
-        FlatMetadata { dim: self.dim }
+        FlatMetadata {
+            dim: self.dim,
+            int8: self.int8,
+        }
     }
 
     fn metadata_key() -> &'static str {
@@ -228,10 +257,15 @@ impl Quantization for FlatQuantizer {
     }
 
     fn field(&self) -> Field {
+        let value_type = if self.int8 {
+            DataType::Int8
+        } else {
+            DataType::Float32
+        };
         Field::new(
             FLAT_COLUMN,
             DataType::FixedSizeList(
-                Arc::new(Field::new("item", DataType::Float32, true)),
+                Arc::new(Field::new("item", value_type, true)),
                 self.dim as i32,
             ),
             true,
@@ -301,7 +335,10 @@ impl Quantization for FlatBinQuantizer {
     }
 
     fn metadata(&self, _: Option<crate::vector::quantizer::QuantizationMetadata>) -> FlatMetadata {
-        FlatMetadata { dim: self.dim }
+        FlatMetadata {
+            dim: self.dim,
+            int8: false,
+        }
     }
 
     fn metadata_key() -> &'static str {
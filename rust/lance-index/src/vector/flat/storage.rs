@@ -10,13 +10,13 @@ use crate::vector::storage::{DistCalculator, VectorStore};
 use crate::vector::utils::do_prefetch;
 use arrow::array::AsArray;
 use arrow::compute::concat_batches;
-use arrow::datatypes::UInt8Type;
+use arrow::datatypes::{Int8Type, UInt8Type};
 use arrow_array::ArrowPrimitiveType;
 use arrow_array::{
     types::{Float32Type, UInt64Type},
     Array, ArrayRef, FixedSizeListArray, RecordBatch, UInt64Array,
 };
-use arrow_schema::SchemaRef;
+use arrow_schema::{DataType, SchemaRef};
 use deepsize::DeepSizeOf;
 use lance_core::{Error, Result, ROW_ID};
 use lance_file::reader::FileReader;
@@ -119,6 +119,7 @@ impl FlatFloatStorage {
         Self {
             metadata: FlatMetadata {
                 dim: vectors.value_length() as usize,
+                int8: vectors.value_type() == DataType::Int8,
             },
             batch,
             distance_type,
@@ -133,7 +134,7 @@ impl FlatFloatStorage {
 }
 
 impl VectorStore for FlatFloatStorage {
-    type DistanceCalculator<'a> = FlatDistanceCal<'a, Float32Type>;
+    type DistanceCalculator<'a> = FlatFloatDistanceCal<'a>;
 
     fn to_batches(&self) -> Result<impl Iterator<Item = RecordBatch>> {
         Ok([self.batch.clone()].into_iter())
@@ -277,6 +278,7 @@ impl FlatBinStorage {
         Self {
             metadata: FlatMetadata {
                 dim: vectors.value_length() as usize,
+                int8: false,
             },
             batch,
             distance_type,
@@ -365,6 +367,19 @@ impl<'a> FlatDistanceCal<'a, Float32Type> {
     }
 }
 
+impl<'a> FlatDistanceCal<'a, Int8Type> {
+    fn new(vectors: &'a FixedSizeListArray, query: ArrayRef, distance_type: DistanceType) -> Self {
+        let flat_array = vectors.values().as_primitive::<Int8Type>();
+        let dimension = vectors.value_length() as usize;
+        Self {
+            vectors: flat_array.values(),
+            query: query.as_primitive::<Int8Type>().values().to_vec(),
+            dimension,
+            distance_fn: distance_type.func(),
+        }
+    }
+}
+
 impl<'a> FlatDistanceCal<'a, UInt8Type> {
     fn new(vectors: &'a FixedSizeListArray, query: ArrayRef, _distance_type: DistanceType) -> Self {
         // Gained significant performance improvement by using strong typed primitive slice.
@@ -408,3 +423,51 @@ impl<T: ArrowPrimitiveType> DistCalculator for FlatDistanceCal<'_, T> {
         do_prefetch(vector.as_ptr_range())
     }
 }
+
+/// The distance calculator of [`FlatFloatStorage`], the vectors are either float32 or int8.
+pub enum FlatFloatDistanceCal<'a> {
+    Float32(FlatDistanceCal<'a, Float32Type>),
+    Int8(FlatDistanceCal<'a, Int8Type>),
+}
+
+impl<'a> FlatFloatDistanceCal<'a> {
+    fn new(vectors: &'a FixedSizeListArray, query: ArrayRef, distance_type: DistanceType) -> Self {
+        match vectors.value_type() {
+            DataType::Int8 => Self::Int8(FlatDistanceCal::<Int8Type>::new(
+                vectors,
+                query,
+                distance_type,
+            )),
+            _ => Self::Float32(FlatDistanceCal::<Float32Type>::new(
+                vectors,
+                query,
+                distance_type,
+            )),
+        }
+    }
+}
+
+impl DistCalculator for FlatFloatDistanceCal<'_> {
+    #[inline]
+    fn distance(&self, id: u32) -> f32 {
+        match self {
+            Self::Float32(cal) => cal.distance(id),
+            Self::Int8(cal) => cal.distance(id),
+        }
+    }
+
+    fn distance_all(&self, k_hint: usize) -> Vec<f32> {
+        match self {
+            Self::Float32(cal) => cal.distance_all(k_hint),
+            Self::Int8(cal) => cal.distance_all(k_hint),
+        }
+    }
+
+    #[inline]
+    fn prefetch(&self, id: u32) {
+        match self {
+            Self::Float32(cal) => cal.prefetch(id),
+            Self::Int8(cal) => cal.prefetch(id),
+        }
+    }
+}
@@ -351,6 +351,94 @@ pub fn beam_search(
     results.into_sorted_vec()
 }
 
+/// Beam search over a graph with a filter
+///
+/// Unlike [beam_search], the distances are computed only for the nodes in `bitset`.
+/// Like ACORN-1 (Patel et al., 2024), a neighbor that is filtered out is not
+/// evaluated but expanded, its neighbors are evaluated instead, so the search can
+/// walk through the filtered-out regions of the graph without losing the
+/// connectivity.  The entry point may be filtered out, it's only used to start.
+///
+/// Returns a descending sorted list of ``(dist, node_id)`` pairs.
+///
+/// WARNING: Internal API,  API stability is not guaranteed
+pub fn filtered_beam_search(
+    graph: &dyn Graph,
+    ep: &OrderedNode,
+    params: &HnswQueryParams,
+    dist_calc: &impl DistCalculator,
+    bitset: &Visited,
+    prefetch_distance: Option<usize>,
+    visited: &mut Visited,
+) -> Vec<OrderedNode> {
+    let k = params.ef;
+    let mut candidates = BinaryHeap::with_capacity(k);
+    visited.insert(ep.id);
+    candidates.push(Reverse(ep.clone()));
+
+    let lower_bound: OrderedFloat = params.lower_bound.unwrap_or(f32::MIN).into();
+    let upper_bound: OrderedFloat = params.upper_bound.unwrap_or(f32::MAX).into();
+
+    let mut results = BinaryHeap::with_capacity(k);
+    if bitset.contains(ep.id) && ep.dist >= lower_bound && ep.dist < upper_bound {
+        results.push(ep.clone());
+    }
+
+    let mut allowed_neighbors = Vec::new();
+    while let Some(Reverse(current)) = candidates.pop() {
+        let furthest = results
+            .peek()
+            .map(|node: &OrderedNode| node.dist)
+            .unwrap_or(OrderedFloat(f32::INFINITY));
+        if current.dist > furthest && results.len() == k {
+            break;
+        }
+
+        // collect the unvisited nodes in the filter within 2 hops,
+        // going through the filtered out neighbors only
+        allowed_neighbors.clear();
+        for &neighbor in graph.neighbors(current.id).iter() {
+            if visited.contains(neighbor) {
+                continue;
+            }
+            visited.insert(neighbor);
+            if bitset.contains(neighbor) {
+                allowed_neighbors.push(neighbor);
+                continue;
+            }
+            for &second_hop in graph.neighbors(neighbor).iter() {
+                if !visited.contains(second_hop) && bitset.contains(second_hop) {
+                    visited.insert(second_hop);
+                    allowed_neighbors.push(second_hop);
+                }
+            }
+        }
+
+        let process_neighbor = |neighbor: u32| {
+            let dist: OrderedFloat = dist_calc.distance(neighbor).into();
+            if dist <= furthest || results.len() < k {
+                if dist >= lower_bound && dist < upper_bound {
+                    if results.len() < k {
+                        results.push((dist, neighbor).into());
+                    } else if dist < results.peek().unwrap().dist {
+                        results.pop();
+                        results.push((dist, neighbor).into());
+                    }
+                }
+                candidates.push(Reverse((dist, neighbor).into()));
+            }
+        };
+        process_neighbors_with_look_ahead(
+            &allowed_neighbors,
+            process_neighbor,
+            prefetch_distance,
+            dist_calc,
+        );
+    }
+
+    results.into_sorted_vec()
+}
+
 /// Greedy search over a graph
 ///
 /// This searches for only one result, only used for finding the entry point
@@ -28,7 +28,7 @@ use lance_core::{Error, Result};
 use rand::{thread_rng, Rng};
 use serde::{Deserialize, Serialize};
 
-use super::super::graph::beam_search;
+use super::super::graph::{beam_search, filtered_beam_search};
 use super::{select_neighbors_heuristic, HnswMetadata, HNSW_TYPE, VECTOR_ID_COL, VECTOR_ID_FIELD};
 use crate::metrics::MetricsCollector;
 use crate::prefilter::PreFilter;
@@ -44,6 +44,10 @@ use crate::vector::{Query, DIST_COL, VECTOR_RESULT_SCHEMA};
 
 pub const HNSW_METADATA_KEY: &str = "lance:hnsw";
 
+/// If the prefilter keeps less than this fraction of the nodes,
+/// the search falls back to flat search over the allowed nodes.
+pub const FLAT_SEARCH_SELECTIVITY: f64 = 0.1;
+
 /// Parameters of building HNSW index
 #[derive(Debug, Clone, Serialize, Deserialize, DeepSizeOf)]
 pub struct HnswBuildParams {
@@ -186,18 +190,27 @@ impl HNSW {
 
         let bottom_level = HnswBottomView::new(nodes);
         let mut visited = visited_generator.generate(storage.len());
-        Ok(beam_search(
-            &bottom_level,
-            &ep,
-            params,
-            &dist_calc,
-            bitset.as_ref(),
-            prefetch_distance,
-            &mut visited,
-        )
-        .into_iter()
-        .take(k)
-        .collect())
+        let results = match bitset.as_ref() {
+            Some(bitset) => filtered_beam_search(
+                &bottom_level,
+                &ep,
+                params,
+                &dist_calc,
+                bitset,
+                prefetch_distance,
+                &mut visited,
+            ),
+            None => beam_search(
+                &bottom_level,
+                &ep,
+                params,
+                &dist_calc,
+                None,
+                prefetch_distance,
+                &mut visited,
+            ),
+        };
+        Ok(results.into_iter().take(k).collect())
     }
 
     #[instrument(level = "debug", skip(self, query, bitset, storage))]
@@ -713,12 +726,16 @@ impl IvfSubIndex for HNSW {
             .as_ref()
             .map(|b| b.count_ones())
             .unwrap_or(storage.len());
-        let results = if remained < self.len() * 10 / 100 {
-            let prefilter_bitset =
-                prefilter_bitset.expect("the prefilter bitset must be set for flat search");
-            self.flat_search(storage, query, k, prefilter_bitset, &params)
-        } else {
-            self.search_basic(query, k, &params, prefilter_bitset, storage)?
+        // the graph traversal would have to expand too many filtered out nodes
+        // to find `ef` candidates, so brute force over the allowed nodes is faster
+        let results = match prefilter_bitset {
+            Some(prefilter_bitset)
+                if remained <= params.ef
+                    || (remained as f64) < self.len() as f64 * FLAT_SEARCH_SELECTIVITY =>
+            {
+                self.flat_search(storage, query, k, prefilter_bitset, &params)
+            }
+            prefilter_bitset => self.search_basic(query, k, &params, prefilter_bitset, storage)?,
         };
         // if the queue is full, we just don't push it back, so ignore the error here
         let _ = self.inner.visited_generator_queue.push(prefilter_generator);
@@ -822,11 +839,16 @@ impl IvfSubIndex for HNSW {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
     use std::sync::Arc;
 
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::UInt64Type;
     use arrow_array::FixedSizeListArray;
     use arrow_schema::Schema;
+    use itertools::Itertools;
     use lance_arrow::FixedSizeListArrayExt;
+    use lance_core::ROW_ID;
     use lance_file::{
         reader::FileReader,
         writer::{FileWriter, FileWriterOptions},
@@ -837,12 +859,16 @@ mod tests {
     use lance_table::io::manifest::ManifestDescribing;
     use lance_testing::datagen::generate_random_array;
     use object_store::path::Path;
+    use rstest::rstest;
 
+    use crate::metrics::NoOpMetricsCollector;
+    use crate::prefilter::NoFilter;
     use crate::scalar::IndexWriter;
+    use crate::vector::storage::{DistCalculator, VectorStore};
     use crate::vector::v3::subindex::IvfSubIndex;
     use crate::vector::{
         flat::storage::FlatFloatStorage,
-        graph::{DISTS_FIELD, NEIGHBORS_FIELD},
+        graph::{OrderedNode, VisitedGenerator, DISTS_FIELD, NEIGHBORS_FIELD},
         hnsw::{
             builder::{HnswBuildParams, HnswQueryParams},
             HNSW, VECTOR_ID_FIELD,
@@ -909,4 +935,106 @@ mod tests {
             .unwrap();
         assert_eq!(builder_results, loaded_results);
     }
+
+    #[rstest]
+    #[case(8)]
+    #[case(20)]
+    fn test_filtered_search(#[case] keep_every: u32) {
+        const DIM: usize = 32;
+        const TOTAL: usize = 4096;
+        let data = generate_random_array(TOTAL * DIM);
+        let fsl = FixedSizeListArray::try_new_from_values(data, DIM as i32).unwrap();
+        let store = FlatFloatStorage::new(fsl.clone(), DistanceType::L2);
+        let hnsw = HNSW::index_vectors(
+            &store,
+            HnswBuildParams::default()
+                .num_edges(20)
+                .ef_construction(100),
+        )
+        .unwrap();
+
+        let k = 10;
+        let params = HnswQueryParams {
+            ef: 100,
+            lower_bound: None,
+            upper_bound: None,
+        };
+        let mut generator = VisitedGenerator::new(TOTAL);
+        let mut hits = 0;
+        let mut total = 0;
+        for i in 0..20 {
+            let query = fsl.value(i * 7 + 1);
+            let mut bitset = generator.generate(TOTAL);
+            for id in (0..TOTAL as u32).filter(|id| id % keep_every == 0) {
+                bitset.insert(id);
+            }
+
+            let dist_calc = store.dist_calculator(query.clone());
+            let expected = (0..TOTAL as u32)
+                .filter(|id| id % keep_every == 0)
+                .map(|id| OrderedNode::new(id, dist_calc.distance(id).into()))
+                .sorted()
+                .take(k)
+                .map(|node| node.id)
+                .collect::<HashSet<_>>();
+
+            let results = hnsw
+                .search_basic(query, k, &params, Some(bitset), &store)
+                .unwrap();
+            assert_eq!(results.len(), k);
+            assert!(results.iter().all(|node| node.id % keep_every == 0));
+            hits += results
+                .iter()
+                .filter(|node| expected.contains(&node.id))
+                .count();
+            total += k;
+        }
+        let recall = hits as f32 / total as f32;
+        assert!(recall >= 0.9, "recall {} is too low", recall);
+    }
+
+    #[test]
+    fn test_search_small_partition_without_filter() {
+        const DIM: usize = 32;
+        const TOTAL: usize = 50;
+        let data = generate_random_array(TOTAL * DIM);
+        let fsl = FixedSizeListArray::try_new_from_values(data, DIM as i32).unwrap();
+        let store = FlatFloatStorage::new(fsl.clone(), DistanceType::L2);
+        let hnsw = HNSW::index_vectors(&store, HnswBuildParams::default()).unwrap();
+
+        // the partition has fewer nodes than ef, and there is no prefilter bitset
+        let k = 10;
+        let params = HnswQueryParams {
+            ef: 100,
+            lower_bound: None,
+            upper_bound: None,
+        };
+        let query = fsl.value(3);
+        let results = hnsw
+            .search(
+                query.clone(),
+                k,
+                params,
+                &store,
+                Arc::new(NoFilter),
+                &NoOpMetricsCollector,
+            )
+            .unwrap();
+        assert_eq!(results.num_rows(), k);
+
+        let dist_calc = store.dist_calculator(query);
+        let expected = (0..TOTAL as u32)
+            .map(|id| OrderedNode::new(id, dist_calc.distance(id).into()))
+            .sorted()
+            .take(k)
+            .map(|node| store.row_id(node.id))
+            .collect::<HashSet<_>>();
+        let row_ids = results[ROW_ID]
+            .as_primitive::<UInt64Type>()
+            .values()
+            .iter()
+            .copied()
+            .collect::<HashSet<_>>();
+        assert_eq!(row_ids, expected);
+    }
 }
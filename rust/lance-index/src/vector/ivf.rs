@@ -32,6 +32,9 @@ pub mod shuffler;
 pub mod storage;
 mod transform;
 
+// the float copy of the int8 vectors to assign the partitions with
+const FLOAT_VECTOR_COLUMN: &str = "__float_vector";
+
 /// Create an IVF from the flatten centroids.
 ///
 /// Parameters
@@ -57,6 +60,12 @@ pub fn new_ivf_transformer_with_quantizer(
     range: Option<Range<u32>>,
 ) -> Result<IvfTransformer> {
     match quantizer {
+        Quantizer::Flat(q) if q.is_int8() => Ok(IvfTransformer::new_flat_int8(
+            centroids,
+            metric_type,
+            vector_column,
+            range,
+        )),
         Quantizer::Flat(_) | Quantizer::FlatBin(_) => Ok(IvfTransformer::new_flat(
             centroids,
             metric_type,
@@ -116,8 +125,14 @@ impl IvfTransformer {
         vector_column: &str,
         range: Option<Range<u32>>,
     ) -> Self {
-        let mut transforms: Vec<Arc<dyn Transformer>> =
-            vec![Arc::new(super::transform::Flatten::new(vector_column))];
+        let mut transforms: Vec<Arc<dyn Transformer>> = vec![
+            Arc::new(super::transform::Flatten::new(vector_column)),
+            Arc::new(super::transform::ConvertToFloat::new(vector_column)),
+            Arc::new(super::transform::Truncate::new(
+                vector_column,
+                centroids.value_length() as usize,
+            )),
+        ];
 
         let dt = if distance_type == DistanceType::Cosine {
             transforms.push(Arc::new(super::transform::NormalizeTransformer::new(
@@ -148,6 +163,59 @@ impl IvfTransformer {
         Self::new(centroids, distance_type, transforms)
     }
 
+    /// Create a IVF_FLAT transformer that keeps the int8 vectors as they are.
+    ///
+    /// The partitions are assigned with a float copy of the vectors, which is
+    /// dropped before the vectors are stored.
+    pub fn new_flat_int8(
+        centroids: FixedSizeListArray,
+        distance_type: DistanceType,
+        vector_column: &str,
+        range: Option<Range<u32>>,
+    ) -> Self {
+        let mut transforms: Vec<Arc<dyn Transformer>> = vec![
+            Arc::new(super::transform::Flatten::new(vector_column)),
+            Arc::new(super::transform::Truncate::new(
+                vector_column,
+                centroids.value_length() as usize,
+            )),
+            Arc::new(KeepFiniteVectors::new(vector_column)),
+            Arc::new(super::transform::ConvertToFloat::new_with_output(
+                vector_column,
+                FLOAT_VECTOR_COLUMN,
+            )),
+        ];
+
+        let dt = if distance_type == DistanceType::Cosine {
+            transforms.push(Arc::new(super::transform::NormalizeTransformer::new(
+                FLOAT_VECTOR_COLUMN,
+            )));
+            MetricType::L2
+        } else {
+            distance_type
+        };
+
+        transforms.push(Arc::new(PartitionTransformer::new(
+            centroids.clone(),
+            dt,
+            FLOAT_VECTOR_COLUMN,
+        )));
+        transforms.push(Arc::new(super::transform::DropColumn::new(
+            FLOAT_VECTOR_COLUMN,
+        )));
+
+        if let Some(range) = range {
+            transforms.push(Arc::new(transform::PartitionFilter::new(
+                PART_ID_COLUMN,
+                range,
+            )));
+        }
+
+        transforms.push(Arc::new(FlatTransformer::new(vector_column)));
+
+        Self::new(centroids, distance_type, transforms)
+    }
+
     /// Create a IVF_PQ struct.
     pub fn with_pq(
         centroids: FixedSizeListArray,
@@ -156,8 +224,14 @@ impl IvfTransformer {
         pq: ProductQuantizer,
         range: Option<Range<u32>>,
     ) -> Self {
-        let mut transforms: Vec<Arc<dyn Transformer>> =
-            vec![Arc::new(super::transform::Flatten::new(vector_column))];
+        let mut transforms: Vec<Arc<dyn Transformer>> = vec![
+            Arc::new(super::transform::Flatten::new(vector_column)),
+            Arc::new(super::transform::ConvertToFloat::new(vector_column)),
+            Arc::new(super::transform::Truncate::new(
+                vector_column,
+                centroids.value_length() as usize,
+            )),
+        ];
 
         let distance_type = if distance_type == MetricType::Cosine {
             transforms.push(Arc::new(super::transform::NormalizeTransformer::new(
@@ -206,8 +280,14 @@ impl IvfTransformer {
         sq: ScalarQuantizer,
         range: Option<Range<u32>>,
     ) -> Self {
-        let mut transforms: Vec<Arc<dyn Transformer>> =
-            vec![Arc::new(super::transform::Flatten::new(vector_column))];
+        let mut transforms: Vec<Arc<dyn Transformer>> = vec![
+            Arc::new(super::transform::Flatten::new(vector_column)),
+            Arc::new(super::transform::ConvertToFloat::new(vector_column)),
+            Arc::new(super::transform::Truncate::new(
+                vector_column,
+                centroids.value_length() as usize,
+            )),
+        ];
 
         let distance_type = if metric_type == MetricType::Cosine {
             transforms.push(Arc::new(super::transform::NormalizeTransformer::new(
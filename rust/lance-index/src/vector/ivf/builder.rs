@@ -51,6 +51,13 @@ pub struct IvfBuildParams {
 
     /// Storage options used to load precomputed partitions.
     pub storage_options: Option<HashMap<String, String>>,
+
+    /// Build the index on the first `truncate_dim` dimensions of the vectors.
+    ///
+    /// This is for Matryoshka embeddings, whose prefix is a good coarse representation
+    /// of the full vector. The search results of such an index are always reranked
+    /// with the full vectors.
+    pub truncate_dim: Option<usize>,
 }
 
 impl Default for IvfBuildParams {
@@ -66,6 +73,7 @@ impl Default for IvfBuildParams {
             shuffle_partition_batches: 1024 * 10,
             shuffle_partition_concurrency: 2,
             storage_options: None,
+            truncate_dim: None,
         }
     }
 }
@@ -8,7 +8,7 @@ use std::sync::Arc;
 
 use arrow::datatypes::{self, ArrowPrimitiveType};
 use arrow_array::{cast::AsArray, Array, FixedSizeListArray, UInt8Array};
-use arrow_array::{ArrayRef, Float32Array, PrimitiveArray};
+use arrow_array::{make_array, ArrayRef, Float32Array, PrimitiveArray};
 use arrow_schema::{DataType, Field};
 use deepsize::DeepSizeOf;
 use distance::build_distance_table_dot;
@@ -24,6 +24,7 @@ use tracing::instrument;
 
 pub mod builder;
 pub mod distance;
+pub mod opq;
 pub mod storage;
 pub mod transform;
 pub(crate) mod utils;
@@ -45,11 +46,19 @@ pub struct ProductQuantizer {
     pub dimension: usize,
     pub codebook: FixedSizeListArray,
     pub distance_type: DistanceType,
+    /// The OPQ rotation, a `dimension x dimension` matrix of f32 that is applied to the
+    /// vectors and the queries before they are quantized.
+    pub rotation: Option<FixedSizeListArray>,
 }
 
 impl DeepSizeOf for ProductQuantizer {
     fn deep_size_of_children(&self, _context: &mut deepsize::Context) -> usize {
         self.codebook.get_array_memory_size()
+            + self
+                .rotation
+                .as_ref()
+                .map(|rotation| rotation.get_array_memory_size())
+                .unwrap_or(0)
             + self.num_sub_vectors.deep_size_of_children(_context)
             + self.num_bits.deep_size_of_children(_context)
             + self.dimension.deep_size_of_children(_context)
@@ -71,9 +80,16 @@ impl ProductQuantizer {
             dimension,
             codebook,
             distance_type,
+            rotation: None,
         }
     }
 
+    /// Set the OPQ rotation of the quantizer, see [opq].
+    pub fn with_rotation(mut self, rotation: Option<FixedSizeListArray>) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
     pub fn from_proto(proto: &pb::Pq, distance_type: DistanceType) -> Result<Self> {
         let distance_type = match distance_type {
             DistanceType::Cosine => DistanceType::L2,
@@ -86,15 +102,42 @@ impl ProductQuantizer {
                 proto.dimension as i32,
             )?,
         };
+        let rotation = proto
+            .rotation_tensor
+            .as_ref()
+            .map(FixedSizeListArray::try_from)
+            .transpose()?;
         Ok(Self {
             num_bits: proto.num_bits,
             num_sub_vectors: proto.num_sub_vectors as usize,
             dimension: proto.dimension as usize,
             codebook,
             distance_type,
+            rotation,
         })
     }
 
+    /// Quantize the vectors that are already rotated.
+    pub(crate) fn encode(&self, fsl: &FixedSizeListArray) -> Result<ArrayRef> {
+        match fsl.value_type() {
+            DataType::Float16 => self.transform::<datatypes::Float16Type>(fsl),
+            DataType::Float32 => self.transform::<datatypes::Float32Type>(fsl),
+            DataType::Float64 => self.transform::<datatypes::Float64Type>(fsl),
+            _ => Err(Error::Index {
+                message: format!("unsupported data type: {}", fsl.value_type()),
+                location: location!(),
+            }),
+        }
+    }
+
+    /// Rotate the query by the OPQ rotation, if any.
+    pub fn rotate_query(&self, query: &dyn Array) -> Result<ArrayRef> {
+        match &self.rotation {
+            Some(rotation) => opq::rotate_array(rotation, query),
+            None => Ok(make_array(query.to_data())),
+        }
+    }
+
     #[instrument(name = "ProductQuantizer::transform", level = "debug", skip_all)]
     fn transform<T: ArrowPrimitiveType>(&self, vectors: &dyn Array) -> Result<ArrayRef>
     where
@@ -192,6 +235,8 @@ impl ProductQuantizer {
         if code.is_empty() {
             return Ok(Float32Array::from(Vec::<f32>::new()));
         }
+        let query = self.rotate_query(query)?;
+        let query = query.as_ref();
 
         match self.distance_type {
             DistanceType::L2 => self.l2_distances(query, code),
@@ -408,7 +453,15 @@ impl Quantization for ProductQuantizer {
             Arc::new(self.codebook.clone()),
         );
 
-        *self = params.build(data, self.distance_type)?;
+        // keep the rotation, only the codebook is retrained
+        let rotation = self.rotation.take();
+        let data = match (&rotation, data.as_fixed_size_list_opt()) {
+            (Some(rotation), Some(fsl)) => Arc::new(opq::rotate_vectors(rotation, fsl)?),
+            _ => make_array(data.to_data()),
+        };
+        *self = params
+            .build(&data, self.distance_type)?
+            .with_rotation(rotation);
         Ok(())
     }
 
@@ -433,14 +486,9 @@ impl Quantization for ProductQuantizer {
             location: location!(),
         })?;
 
-        match fsl.value_type() {
-            DataType::Float16 => self.transform::<datatypes::Float16Type>(vectors),
-            DataType::Float32 => self.transform::<datatypes::Float32Type>(vectors),
-            DataType::Float64 => self.transform::<datatypes::Float64Type>(vectors),
-            _ => Err(Error::Index {
-                message: format!("unsupported data type: {}", fsl.value_type()),
-                location: location!(),
-            }),
+        match &self.rotation {
+            Some(rotation) => self.encode(&opq::rotate_vectors(rotation, fsl)?),
+            None => self.encode(fsl),
         }
     }
 
@@ -467,6 +515,8 @@ impl Quantization for ProductQuantizer {
             codebook: Some(self.codebook.clone()),
             codebook_tensor: Vec::new(),
             transposed: args.map(|args| args.transposed).unwrap_or_default(),
+            rotated: self.rotation.is_some(),
+            rotation: self.rotation.clone(),
         }
     }
 
@@ -482,13 +532,16 @@ impl Quantization for ProductQuantizer {
                 FixedSizeListArray::try_from(&tensor)?
             }
         };
-        Ok(Quantizer::Product(Self::new(
-            metadata.num_sub_vectors,
-            metadata.nbits,
-            metadata.dimension,
-            codebook,
-            distance_type,
-        )))
+        Ok(Quantizer::Product(
+            Self::new(
+                metadata.num_sub_vectors,
+                metadata.nbits,
+                metadata.dimension,
+                codebook,
+                distance_type,
+            )
+            .with_rotation(metadata.rotation.clone()),
+        ))
     }
 
     fn field(&self) -> Field {
@@ -509,12 +562,14 @@ impl TryFrom<&ProductQuantizer> for pb::Pq {
 
     fn try_from(pq: &ProductQuantizer) -> Result<Self> {
         let tensor = pb::Tensor::try_from(&pq.codebook)?;
+        let rotation_tensor = pq.rotation.as_ref().map(pb::Tensor::try_from).transpose()?;
         Ok(Self {
             num_bits: pq.num_bits,
             num_sub_vectors: pq.num_sub_vectors as u32,
             dimension: pq.dimension as u32,
             codebook: vec![],
             codebook_tensor: Some(tensor),
+            rotation_tensor,
         })
     }
 }
@@ -16,12 +16,17 @@ use lance_arrow::FixedSizeListArrayExt;
 use lance_core::{Error, Result};
 use lance_linalg::distance::DistanceType;
 use lance_linalg::distance::{Dot, Normalize, L2};
+use num_traits::Float;
 use snafu::location;
 
+use super::opq::train_opq;
 use super::utils::divide_to_subvectors;
 use super::ProductQuantizer;
 use crate::vector::kmeans::train_kmeans;
 
+/// The default number of iterations to learn the OPQ rotation.
+pub const DEFAULT_OPQ_ITERS: usize = 10;
+
 /// Parameters for building product quantizer.
 #[derive(Debug, Clone)]
 pub struct PQBuildParams {
@@ -43,6 +48,10 @@ pub struct PQBuildParams {
 
     /// Sample rate to train PQ codebook.
     pub sample_rate: usize,
+
+    /// The number of iterations to learn an OPQ rotation together with the codebook,
+    /// see [super::opq]. Default to 0, plain PQ without rotation.
+    pub opq_iters: usize,
 }
 
 impl Default for PQBuildParams {
@@ -54,6 +63,7 @@ impl Default for PQBuildParams {
             kmeans_redos: 1,
             codebook: None,
             sample_rate: 256,
+            opq_iters: 0,
         }
     }
 }
@@ -86,13 +96,23 @@ impl PQBuildParams {
         }
     }
 
+    /// Parameters for OPQ, PQ with a learned rotation.
+    pub fn opq(num_sub_vectors: usize, num_bits: usize) -> Self {
+        Self {
+            num_sub_vectors,
+            num_bits,
+            opq_iters: DEFAULT_OPQ_ITERS,
+            ..Default::default()
+        }
+    }
+
     fn build_from_fsl<T: ArrowNumericType>(
         &self,
         data: &FixedSizeListArray,
         distance_type: DistanceType,
     ) -> Result<ProductQuantizer>
     where
-        T::Native: Dot + L2 + Normalize,
+        T::Native: Float + Dot + L2 + Normalize,
         PrimitiveArray<T>: From<Vec<T::Native>>,
     {
         assert_ne!(
@@ -100,6 +120,9 @@ impl PQBuildParams {
             DistanceType::Cosine,
             "PQ code does not support cosine"
         );
+        if self.opq_iters > 0 {
+            return train_opq::<T>(self, data, distance_type);
+        }
 
         let sub_vectors = divide_to_subvectors::<T>(data, self.num_sub_vectors)?;
         let num_centroids = 2_usize.pow(self.num_bits as u32);
@@ -0,0 +1,354 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Optimized Product Quantization (OPQ)
+//!
+//! PQ quantizes each sub-vector independently, so it loses a lot of information when
+//! the variance of the vectors is concentrated in a few dimensions, or the dimensions
+//! are correlated across sub-vectors.  OPQ learns an orthogonal rotation `R` of the
+//! vector space that minimizes the quantization error, the vectors are rotated by `R`
+//! before they are quantized and the queries are rotated the same way.  The rotation
+//! preserves L2 distances and dot products, so the distances computed from the codes
+//! are still approximations of the distances between the original vectors.
+//!
+//! The rotation and the codebook are trained alternately, as in the non-parametric
+//! OPQ of Ge et al:
+//!
+//! 1. train the codebook on the rotated vectors `X R^T`;
+//! 2. quantize the rotated vectors, and reconstruct them `Y` from their codes;
+//! 3. set `R` to the orthogonal matrix that minimizes `||X R^T - Y||`, which is the
+//!    orthogonal factor of the polar decomposition of `Y^T X` (orthogonal Procrustes).
+
+use std::sync::Arc;
+
+use arrow_array::types::{Float16Type, Float32Type, Float64Type, UInt8Type};
+use arrow_array::{
+    cast::AsArray, Array, ArrayRef, ArrowNumericType, FixedSizeListArray, Float32Array,
+    PrimitiveArray,
+};
+use arrow_schema::DataType;
+use lance_arrow::FixedSizeListArrayExt;
+use lance_core::{Error, Result};
+use lance_linalg::distance::{dot, DistanceType, Dot, Normalize, L2};
+use num_traits::Float;
+use rayon::prelude::*;
+use snafu::location;
+
+use super::builder::PQBuildParams;
+use super::storage::get_centroids;
+use super::ProductQuantizer;
+
+/// The number of kmeans iterations to train the codebook between two updates of the
+/// rotation, the codebook is trained with all the iterations once the rotation is learned.
+const CODEBOOK_ITERS_PER_ROTATION: usize = 10;
+
+/// The max number of iterations to compute the orthogonal factor of a matrix.
+const MAX_ORTHOGONAL_ITERS: usize = 100;
+
+/// Rotate the row-major `vectors`, `x -> R x` for the row-major `dimension x dimension`
+/// matrix `R`.
+pub fn rotate<T: Float + Send + Sync>(rotation: &[f32], dimension: usize, vectors: &[T]) -> Vec<T> {
+    vectors
+        .par_chunks_exact(dimension)
+        .flat_map_iter(|vector| {
+            let vector = vector
+                .iter()
+                .map(|v| v.to_f32().unwrap())
+                .collect::<Vec<_>>();
+            rotation
+                .chunks_exact(dimension)
+                .map(move |row| num_traits::cast::<f32, T>(dot(row, &vector)).unwrap())
+        })
+        .collect()
+}
+
+/// Rotate the flattened vectors in `values` by the `rotation` of a [ProductQuantizer].
+///
+/// It works for both a single query and the values of a FixedSizeList of vectors.
+pub fn rotate_array(rotation: &FixedSizeListArray, values: &dyn Array) -> Result<ArrayRef> {
+    let dimension = rotation.value_length() as usize;
+    let rotation = rotation.values().as_primitive::<Float32Type>().values();
+    match values.data_type() {
+        DataType::Float16 => Ok(Arc::new(PrimitiveArray::<Float16Type>::from(rotate(
+            rotation,
+            dimension,
+            values.as_primitive::<Float16Type>().values(),
+        )))),
+        DataType::Float32 => Ok(Arc::new(PrimitiveArray::<Float32Type>::from(rotate(
+            rotation,
+            dimension,
+            values.as_primitive::<Float32Type>().values(),
+        )))),
+        DataType::Float64 => Ok(Arc::new(PrimitiveArray::<Float64Type>::from(rotate(
+            rotation,
+            dimension,
+            values.as_primitive::<Float64Type>().values(),
+        )))),
+        _ => Err(Error::Index {
+            message: format!("OPQ: unsupported data type: {}", values.data_type()),
+            location: location!(),
+        }),
+    }
+}
+
+/// Rotate a FixedSizeList of vectors by the `rotation` of a [ProductQuantizer].
+pub fn rotate_vectors(
+    rotation: &FixedSizeListArray,
+    vectors: &FixedSizeListArray,
+) -> Result<FixedSizeListArray> {
+    if rotation.value_length() != vectors.value_length() {
+        return Err(Error::Index {
+            message: format!(
+                "OPQ: the rotation is for {} dimensions, but the vectors have {} dimensions",
+                rotation.value_length(),
+                vectors.value_length()
+            ),
+            location: location!(),
+        });
+    }
+    let rotated = rotate_array(rotation, vectors.values().as_ref())?;
+    Ok(FixedSizeListArray::try_new_from_values(
+        rotated,
+        vectors.value_length(),
+    )?)
+}
+
+/// Train a [ProductQuantizer] with an OPQ rotation.
+pub(super) fn train_opq<T: ArrowNumericType>(
+    params: &PQBuildParams,
+    data: &FixedSizeListArray,
+    distance_type: DistanceType,
+) -> Result<ProductQuantizer>
+where
+    T::Native: Float + Dot + L2 + Normalize,
+    PrimitiveArray<T>: From<Vec<T::Native>>,
+{
+    let dimension = data.value_length() as usize;
+    let vectors = data
+        .values()
+        .as_primitive::<T>()
+        .values()
+        .iter()
+        .map(|v| v.to_f32().unwrap())
+        .collect::<Vec<_>>();
+
+    let mut rotation = FixedSizeListArray::try_new_from_values(
+        Float32Array::from(identity(dimension)),
+        dimension as i32,
+    )?;
+    let mut codebook_params = PQBuildParams {
+        max_iters: params.max_iters.min(CODEBOOK_ITERS_PER_ROTATION),
+        opq_iters: 0,
+        ..params.clone()
+    };
+    for _ in 0..params.opq_iters {
+        let rotated = rotate_vectors(&rotation, data)?;
+        let pq = codebook_params.build(&rotated, distance_type)?;
+        let reconstructed = reconstruct::<T>(&pq, &rotated)?;
+        let orthogonal = orthogonal_factor(
+            transpose_mul(&reconstructed, &vectors, dimension),
+            dimension,
+        );
+        rotation = FixedSizeListArray::try_new_from_values(
+            Float32Array::from(orthogonal),
+            dimension as i32,
+        )?;
+        // start from the current codebook, it's close to the one of the new rotation
+        codebook_params.codebook = Some(Arc::new(pq.codebook));
+    }
+
+    let rotated = rotate_vectors(&rotation, data)?;
+    let pq = PQBuildParams {
+        opq_iters: 0,
+        codebook: codebook_params.codebook,
+        ..params.clone()
+    }
+    .build(&rotated, distance_type)?;
+    Ok(pq.with_rotation(Some(rotation)))
+}
+
+/// The (already rotated) vectors decoded from their PQ codes.
+fn reconstruct<T: ArrowNumericType>(
+    pq: &ProductQuantizer,
+    vectors: &FixedSizeListArray,
+) -> Result<Vec<f32>>
+where
+    T::Native: Float,
+{
+    let codes = pq.encode(vectors)?;
+    let codes = codes.as_fixed_size_list();
+    let code_length = codes.value_length() as usize;
+    let codebook = pq.codebook.values().as_primitive::<T>().values();
+    Ok(codes
+        .values()
+        .as_primitive::<UInt8Type>()
+        .values()
+        .chunks_exact(code_length)
+        .flat_map(|code| {
+            get_centroids(
+                codebook,
+                pq.num_bits,
+                pq.num_sub_vectors,
+                pq.dimension,
+                code.iter().copied(),
+            )
+        })
+        .map(|v| v.to_f32().unwrap())
+        .collect())
+}
+
+fn identity(dimension: usize) -> Vec<f32> {
+    let mut matrix = vec![0.0; dimension * dimension];
+    (0..dimension).for_each(|i| matrix[i * dimension + i] = 1.0);
+    matrix
+}
+
+/// `A^T B` for the row-major `n x dimension` matrices `A` and `B`.
+fn transpose_mul(a: &[f32], b: &[f32], dimension: usize) -> Vec<f32> {
+    let mut result = vec![0.0; dimension * dimension];
+    result
+        .par_chunks_exact_mut(dimension)
+        .enumerate()
+        .for_each(|(i, row)| {
+            for (a_row, b_row) in a.chunks_exact(dimension).zip(b.chunks_exact(dimension)) {
+                let a_i = a_row[i];
+                row.iter_mut().zip(b_row).for_each(|(r, b)| *r += a_i * b);
+            }
+        });
+    result
+}
+
+/// `A B` for the row-major `dimension x dimension` matrices `A` and `B`.
+fn mul(a: &[f32], b: &[f32], dimension: usize) -> Vec<f32> {
+    let mut result = vec![0.0; dimension * dimension];
+    result
+        .par_chunks_exact_mut(dimension)
+        .zip(a.par_chunks_exact(dimension))
+        .for_each(|(row, a_row)| {
+            for (a_ik, b_row) in a_row.iter().zip(b.chunks_exact(dimension)) {
+                row.iter_mut().zip(b_row).for_each(|(r, b)| *r += a_ik * b);
+            }
+        });
+    result
+}
+
+/// The orthogonal factor `U V^T` of the polar decomposition of `M = U S V^T`, which is
+/// the orthogonal matrix closest to `M`.
+///
+/// It's computed by the Newton-Schulz iteration `Q <- Q (3I - Q^T Q) / 2`, that converges
+/// to the orthogonal factor if the singular values of `Q` are in `(0, sqrt(3))`.
+fn orthogonal_factor(mut matrix: Vec<f32>, dimension: usize) -> Vec<f32> {
+    let norm = matrix.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm == 0.0 || !norm.is_finite() {
+        return identity(dimension);
+    }
+    // the spectral norm is at most the Frobenius norm, and the singular values
+    // of exactly 0 would stay 0
+    matrix.iter_mut().for_each(|v| *v /= norm);
+    (0..dimension).for_each(|i| matrix[i * dimension + i] += 1e-6);
+
+    let tolerance = 1e-5 * dimension as f32;
+    for _ in 0..MAX_ORTHOGONAL_ITERS {
+        let mut gram = transpose_mul(&matrix, &matrix, dimension);
+        let error = gram
+            .iter()
+            .enumerate()
+            .map(|(idx, v)| {
+                let expected = if idx % (dimension + 1) == 0 { 1.0 } else { 0.0 };
+                (v - expected) * (v - expected)
+            })
+            .sum::<f32>()
+            .sqrt();
+        if error < tolerance {
+            break;
+        }
+        gram.iter_mut().enumerate().for_each(|(idx, v)| {
+            let diagonal = if idx % (dimension + 1) == 0 { 3.0 } else { 0.0 };
+            *v = (diagonal - *v) / 2.0;
+        });
+        matrix = mul(&matrix, &gram, dimension);
+    }
+    matrix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+    use lance_testing::datagen::generate_random_array;
+
+    #[test]
+    fn test_orthogonal_factor() {
+        const DIM: usize = 16;
+        let matrix = generate_random_array(DIM * DIM).values().to_vec();
+        let orthogonal = orthogonal_factor(matrix, DIM);
+        let gram = transpose_mul(&orthogonal, &orthogonal, DIM);
+        gram.iter().zip(identity(DIM)).for_each(|(v, expected)| {
+            assert_relative_eq!(*v, expected, epsilon = 1e-3);
+        });
+    }
+
+    #[test]
+    fn test_opq_reduces_quantization_error() {
+        const DIM: usize = 16;
+        const TOTAL: usize = 2048;
+        // the variance is concentrated in the first sub-vector
+        let values = generate_random_array(DIM * TOTAL)
+            .values()
+            .iter()
+            .enumerate()
+            .map(|(i, v)| if i % DIM < 4 { v * 10.0 } else { *v })
+            .collect::<Vec<_>>();
+        let vectors =
+            FixedSizeListArray::try_new_from_values(Float32Array::from(values.clone()), DIM as i32)
+                .unwrap();
+
+        let error = |pq: &ProductQuantizer| {
+            let rotated = match &pq.rotation {
+                Some(rotation) => rotate_vectors(rotation, &vectors).unwrap(),
+                None => vectors.clone(),
+            };
+            let reconstructed = reconstruct::<Float32Type>(pq, &rotated).unwrap();
+            rotated
+                .values()
+                .as_primitive::<Float32Type>()
+                .values()
+                .iter()
+                .zip(reconstructed)
+                .map(|(v, r)| (v - r) * (v - r))
+                .sum::<f32>()
+        };
+
+        let params = PQBuildParams {
+            num_sub_vectors: 4,
+            num_bits: 4,
+            max_iters: 20,
+            ..Default::default()
+        };
+        let pq = params.build(&vectors, DistanceType::L2).unwrap();
+        assert!(pq.rotation.is_none());
+
+        let params = PQBuildParams {
+            opq_iters: 5,
+            ..params
+        };
+        let opq = params.build(&vectors, DistanceType::L2).unwrap();
+        let rotation = opq.rotation.as_ref().unwrap();
+        assert_eq!(rotation.len(), DIM);
+
+        // the rotation keeps the distances
+        let rotated = rotate_vectors(rotation, &vectors).unwrap();
+        let norm = |fsl: &FixedSizeListArray| {
+            fsl.values()
+                .as_primitive::<Float32Type>()
+                .values()
+                .iter()
+                .map(|v| v * v)
+                .sum::<f32>()
+        };
+        assert_relative_eq!(norm(&rotated), norm(&vectors), max_relative = 1e-3);
+
+        assert!(error(&opq) < error(&pq));
+    }
+}
@@ -31,6 +31,7 @@ use serde::{Deserialize, Serialize};
 use snafu::location;
 
 use super::distance::{build_distance_table_dot, build_distance_table_l2, compute_pq_distance};
+use super::opq::rotate_array;
 use super::ProductQuantizer;
 use crate::frag_reuse::FragReuseIndex;
 use crate::{
@@ -62,6 +63,14 @@ pub struct ProductQuantizationMetadata {
     // deprecated in later version
     pub codebook_tensor: Vec<u8>,
     pub transposed: bool,
+
+    /// Whether there is an OPQ rotation, if so the global buffer is a [pb::Pq]
+    /// with both the codebook and the rotation instead of the codebook tensor.
+    #[serde(default)]
+    pub rotated: bool,
+
+    #[serde(skip)]
+    pub rotation: Option<FixedSizeListArray>,
 }
 
 impl DeepSizeOf for ProductQuantizationMetadata {
@@ -70,6 +79,11 @@ impl DeepSizeOf for ProductQuantizationMetadata {
             .as_ref()
             .map(|codebook| codebook.get_array_memory_size())
             .unwrap_or(0)
+            + self
+                .rotation
+                .as_ref()
+                .map(|rotation| rotation.get_array_memory_size())
+                .unwrap_or(0)
     }
 }
 
@@ -79,6 +93,7 @@ impl PartialEq for ProductQuantizationMetadata {
             && self.nbits == other.nbits
             && self.dimension == other.dimension
             && self.codebook == other.codebook
+            && self.rotation == other.rotation
     }
 }
 
@@ -100,6 +115,13 @@ impl QuantizerMetadata for ProductQuantizationMetadata {
     fn parse_buffer(&mut self, bytes: Bytes) -> Result<()> {
         debug_assert!(!bytes.is_empty());
         debug_assert!(self.codebook.is_none());
+        if self.rotated {
+            let pq = pb::Pq::decode(bytes)?;
+            let pq = ProductQuantizer::from_proto(&pq, DistanceType::L2)?;
+            self.codebook = Some(pq.codebook);
+            self.rotation = pq.rotation;
+            return Ok(());
+        }
         let codebook_tensor: pb::Tensor = pb::Tensor::decode(bytes)?;
         self.codebook = Some(FixedSizeListArray::try_from(&codebook_tensor)?);
         Ok(())
@@ -109,7 +131,18 @@ impl QuantizerMetadata for ProductQuantizationMetadata {
         debug_assert!(self.codebook.is_some());
         let codebook_tensor: pb::Tensor = pb::Tensor::try_from(self.codebook.as_ref().unwrap())?;
         let mut bytes = BytesMut::new();
-        codebook_tensor.encode(&mut bytes)?;
+        match &self.rotation {
+            Some(rotation) => pb::Pq {
+                num_bits: self.nbits,
+                num_sub_vectors: self.num_sub_vectors as u32,
+                dimension: self.dimension as u32,
+                codebook: vec![],
+                codebook_tensor: Some(codebook_tensor),
+                rotation_tensor: Some(pb::Tensor::try_from(rotation)?),
+            }
+            .encode(&mut bytes)?,
+            None => codebook_tensor.encode(&mut bytes)?,
+        }
         Ok(Some(bytes.freeze()))
     }
 
@@ -293,6 +326,8 @@ impl ProductQuantizationStorage {
             codebook: Some(codebook),
             codebook_tensor: Vec::new(), // empty for v1 format
             transposed: true,
+            rotated: false,
+            rotation: None,
         };
         Ok(Self {
             metadata,
@@ -303,6 +338,13 @@ impl ProductQuantizationStorage {
         })
     }
 
+    /// Set the OPQ rotation of the quantizer the codes are from.
+    pub fn with_rotation(mut self, rotation: Option<FixedSizeListArray>) -> Self {
+        self.metadata.rotated = rotation.is_some();
+        self.metadata.rotation = rotation;
+        self
+    }
+
     pub fn batch(&self) -> &RecordBatch {
         &self.batch
     }
@@ -328,9 +370,10 @@ impl ProductQuantizationStorage {
         let dimension = quantizer.dimension;
         let num_sub_vectors = quantizer.num_sub_vectors;
         let metric_type = quantizer.distance_type;
+        let rotation = quantizer.rotation.clone();
         let transform = PQTransformer::new(quantizer, vector_col, PQ_CODE_COLUMN);
         let batch = transform.transform(batch)?;
-        Self::new(
+        Ok(Self::new(
             codebook,
             batch,
             num_bits,
@@ -339,7 +382,8 @@ impl ProductQuantizationStorage {
             metric_type,
             false,
             frag_reuse_index,
-        )
+        )?
+        .with_rotation(rotation))
     }
 
     pub fn codebook(&self) -> &FixedSizeListArray {
@@ -476,7 +520,7 @@ impl QuantizerStorage for ProductQuantizationStorage {
             }
         };
 
-        Self::new(
+        Ok(Self::new(
             codebook,
             batch,
             metadata.nbits,
@@ -485,7 +529,8 @@ impl QuantizerStorage for ProductQuantizationStorage {
             distance_type,
             metadata.transposed,
             frag_reuse_index,
-        )
+        )?
+        .with_rotation(metadata.rotation.clone()))
     }
 
     fn metadata(&self) -> &Self::Metadata {
@@ -582,7 +627,7 @@ impl QuantizerStorage for ProductQuantizationStorage {
         let schema = reader.schema();
         let batch = reader.read_range(range, schema).await?;
 
-        Self::new(
+        Ok(Self::new(
             codebook,
             batch,
             metadata.nbits,
@@ -591,7 +636,8 @@ impl QuantizerStorage for ProductQuantizationStorage {
             distance_type,
             metadata.transposed,
             frag_reuse_index,
-        )
+        )?
+        .with_rotation(metadata.rotation.clone()))
     }
 }
 
@@ -631,6 +677,11 @@ impl VectorStore for ProductQuantizationStorage {
     }
 
     fn dist_calculator(&self, query: ArrayRef) -> Self::DistanceCalculator<'_> {
+        // the codes are of the rotated vectors, so the query must be rotated the same way
+        let query = match &self.metadata.rotation {
+            Some(rotation) => rotate_array(rotation, query.as_ref()).unwrap(),
+            None => query,
+        };
         let codebook = self.metadata.codebook.as_ref().unwrap();
         match codebook.value_type() {
             DataType::Float16 => PQDistCalculator::new(
@@ -981,7 +1032,7 @@ fn get_pq_code(
         .exact_size(num_bytes)
 }
 
-fn get_centroids<T: Clone>(
+pub(super) fn get_centroids<T: Clone>(
     codebook: &[T],
     num_bits: u32,
     num_sub_vectors: usize,
@@ -10,9 +10,9 @@ use std::sync::Arc;
 use arrow::datatypes::UInt64Type;
 use arrow_array::types::{Float16Type, Float32Type, Float64Type};
 use arrow_array::UInt64Array;
-use arrow_array::{cast::AsArray, Array, ArrowPrimitiveType, RecordBatch, UInt32Array};
+use arrow_array::{cast::AsArray, Array, ArrayRef, ArrowPrimitiveType, RecordBatch, UInt32Array};
 use arrow_schema::{DataType, Field, Schema};
-use lance_arrow::RecordBatchExt;
+use lance_arrow::{FixedSizeListArrayExt, RecordBatchExt};
 use num_traits::Float;
 use snafu::location;
 
@@ -20,6 +20,8 @@ use lance_core::{Error, Result, ROW_ID, ROW_ID_FIELD};
 use lance_linalg::kernels::normalize_fsl;
 use tracing::instrument;
 
+use super::utils::truncate_vectors;
+
 /// Transform of a Vector Matrix.
 ///
 ///
@@ -155,6 +157,98 @@ impl Transformer for KeepFiniteVectors {
     }
 }
 
+/// Convert `Int8` vectors to `Float32`.
+///
+/// The centroids and quantizers work with float vectors, other vector types are kept as is.
+#[derive(Debug)]
+pub struct ConvertToFloat {
+    column: String,
+    output_column: Option<String>,
+}
+
+impl ConvertToFloat {
+    pub fn new(column: &str) -> Self {
+        Self {
+            column: column.to_owned(),
+            output_column: None,
+        }
+    }
+
+    /// Convert the vectors into a different column, keeping the original one.
+    pub fn new_with_output(column: &str, output_column: &str) -> Self {
+        Self {
+            column: column.to_owned(),
+            output_column: Some(output_column.to_owned()),
+        }
+    }
+}
+
+impl Transformer for ConvertToFloat {
+    #[instrument(name = "ConvertToFloat::transform", level = "debug", skip_all)]
+    fn transform(&self, batch: &RecordBatch) -> Result<RecordBatch> {
+        let Some(arr) = batch.column_by_name(&self.column) else {
+            return Ok(batch.clone());
+        };
+        let converted: ArrayRef = match arr.data_type() {
+            DataType::FixedSizeList(field, _) if field.data_type() == &DataType::Int8 => {
+                Arc::new(arr.as_fixed_size_list().convert_to_floating_point()?)
+            }
+            _ if self.output_column.is_some() => arr.clone(),
+            _ => return Ok(batch.clone()),
+        };
+        if let Some(output_column) = &self.output_column {
+            let field = Field::new(output_column, converted.data_type().clone(), true);
+            Ok(batch.try_with_column(field, converted)?)
+        } else {
+            Ok(batch.replace_column_schema_by_name(
+                &self.column,
+                converted.data_type().clone(),
+                converted,
+            )?)
+        }
+    }
+}
+
+/// Keep only the first `dim` dimensions of the vectors.
+///
+/// The index built on the truncated vectors is a coarse index, the search results
+/// are expected to be reranked with the full vectors.
+#[derive(Debug)]
+pub struct Truncate {
+    column: String,
+    dim: usize,
+}
+
+impl Truncate {
+    pub fn new(column: &str, dim: usize) -> Self {
+        Self {
+            column: column.to_owned(),
+            dim,
+        }
+    }
+}
+
+impl Transformer for Truncate {
+    #[instrument(name = "Truncate::transform", level = "debug", skip_all)]
+    fn transform(&self, batch: &RecordBatch) -> Result<RecordBatch> {
+        let Some(arr) = batch.column_by_name(&self.column) else {
+            return Ok(batch.clone());
+        };
+        let Some(fsl) = arr.as_fixed_size_list_opt() else {
+            return Ok(batch.clone());
+        };
+        if fsl.value_length() as usize <= self.dim {
+            return Ok(batch.clone());
+        }
+        let truncated = truncate_vectors(fsl, self.dim)?;
+        Ok(batch.replace_column_schema_by_name(
+            &self.column,
+            truncated.data_type().clone(),
+            Arc::new(truncated),
+        )?)
+    }
+}
+
 #[derive(Debug)]
 pub struct DropColumn {
     column: String,
@@ -235,7 +329,7 @@ mod tests {
     use super::*;
 
     use approx::assert_relative_eq;
-    use arrow_array::{FixedSizeListArray, Float16Array, Float32Array, Int32Array};
+    use arrow_array::{FixedSizeListArray, Float16Array, Float32Array, Int32Array, Int8Array};
     use arrow_schema::Schema;
     use half::f16;
     use lance_arrow::*;
@@ -349,4 +443,51 @@ mod tests {
         let dup_drop_result = transformer.transform(&output);
         assert!(dup_drop_result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_convert_to_float() {
+        let data = Int8Array::from_iter_values([1, -1, 127, -128].into_iter());
+        let fsl = FixedSizeListArray::try_new_from_values(data, 2).unwrap();
+        let schema = Schema::new(vec![Field::new(
+            "v",
+            DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Int8, true)), 2),
+            true,
+        )]);
+        let batch = RecordBatch::try_new(schema.into(), vec![Arc::new(fsl)]).unwrap();
+        let transformer = ConvertToFloat::new("v");
+        let output = transformer.transform(&batch).unwrap();
+        assert_eq!(
+            output.schema().field_with_name("v").unwrap().data_type(),
+            &DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), 2)
+        );
+        let act_fsl = output.column_by_name("v").unwrap().as_fixed_size_list();
+        assert_eq!(
+            act_fsl.values().as_primitive::<Float32Type>().values()[..],
+            [1.0, -1.0, 127.0, -128.0]
+        );
+
+        // float vectors are kept as is
+        let same = transformer.transform(&output).unwrap();
+        assert_eq!(same, output);
+    }
+
+    #[tokio::test]
+    async fn test_convert_to_float_with_output() {
+        let data = Int8Array::from_iter_values([1, -1, 127, -128].into_iter());
+        let fsl = FixedSizeListArray::try_new_from_values(data, 2).unwrap();
+        let schema = Schema::new(vec![Field::new(
+            "v",
+            DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Int8, true)), 2),
+            true,
+        )]);
+        let batch = RecordBatch::try_new(schema.into(), vec![Arc::new(fsl)]).unwrap();
+        let transformer = ConvertToFloat::new_with_output("v", "f");
+        let output = transformer.transform(&batch).unwrap();
+        assert_eq!(output.column_by_name("v").unwrap(), batch.column(0));
+        let act_fsl = output.column_by_name("f").unwrap().as_fixed_size_list();
+        assert_eq!(
+            act_fsl.values().as_primitive::<Float32Type>().values()[..],
+            [1.0, -1.0, 127.0, -128.0]
+        );
+    }
 }
@@ -5,7 +5,7 @@ use arrow::{
     array::AsArray,
     datatypes::{Float16Type, Float32Type, Float64Type},
 };
-use arrow_array::{Array, ArrayRef, BooleanArray, FixedSizeListArray};
+use arrow_array::{Array, ArrayRef, BooleanArray, FixedSizeListArray, UInt64Array};
 use arrow_schema::{DataType, Field};
 use lance_arrow::FixedSizeListArrayExt;
 use lance_core::{Error, Result};
@@ -284,6 +284,31 @@ pub fn is_finite(fsl: &FixedSizeListArray) -> BooleanArray {
     BooleanArray::from(is_finite)
 }
 
+/// Keep only the first `dim` dimensions of each vector.
+///
+/// This is used to index Matryoshka embeddings, whose prefix is a good coarse
+/// representation of the full vector. Vectors with no more than `dim` dimensions
+/// are returned as is.
+pub fn truncate_vectors(fsl: &FixedSizeListArray, dim: usize) -> Result<FixedSizeListArray> {
+    let orig_dim = fsl.value_length() as usize;
+    if orig_dim <= dim {
+        return Ok(fsl.clone());
+    }
+    let DataType::FixedSizeList(field, _) = fsl.data_type() else {
+        unreachable!()
+    };
+    let indices = UInt64Array::from_iter_values(
+        (0..fsl.len() as u64).flat_map(|i| (0..dim as u64).map(move |j| i * orig_dim as u64 + j)),
+    );
+    let values = arrow_select::take::take(fsl.values(), &indices, None)?;
+    Ok(FixedSizeListArray::try_new(
+        field.clone(),
+        dim as i32,
+        values,
+        fsl.nulls().cloned(),
+    )?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -317,4 +342,31 @@ mod tests {
         assert_eq!(tensor.shape, vec![4, 5]);
         assert_eq!(tensor.data.len(), 20 * 8);
     }
+
+    #[test]
+    fn test_truncate_vectors() {
+        let fsl = FixedSizeListArray::try_new_from_values(
+            Float32Array::from_iter_values((0..12).map(|v| v as f32)),
+            4,
+        )
+        .unwrap();
+        let truncated = truncate_vectors(&fsl, 2).unwrap();
+        assert_eq!(truncated.len(), 3);
+        assert_eq!(truncated.value_length(), 2);
+        assert_eq!(
+            truncated.values().as_primitive::<Float32Type>().values()[..],
+            [0.0, 1.0, 4.0, 5.0, 8.0, 9.0]
+        );
+
+        // sliced arrays are truncated from the slice offset
+        let truncated = truncate_vectors(&fsl.slice(1, 2), 3).unwrap();
+        assert_eq!(
+            truncated.values().as_primitive::<Float32Type>().values()[..],
+            [4.0, 5.0, 6.0, 8.0, 9.0, 10.0]
+        );
+
+        // no more dimensions than `dim`
+        assert_eq!(truncate_vectors(&fsl, 4).unwrap(), fsl);
+        assert_eq!(truncate_vectors(&fsl, 8).unwrap(), fsl);
+    }
 }
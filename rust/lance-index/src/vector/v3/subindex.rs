@@ -63,6 +63,7 @@ pub trait IvfSubIndex: Send + Sync + Debug + DeepSizeOf {
 pub enum SubIndexType {
     Flat,
     Hnsw,
+    DiskAnn,
 }
 
 impl std::fmt::Display for SubIndexType {
@@ -70,6 +71,7 @@ impl std::fmt::Display for SubIndexType {
         match self {
             Self::Flat => write!(f, "{}", flat::index::FlatIndex::name()),
             Self::Hnsw => write!(f, "{}", hnsw::builder::HNSW::name()),
+            Self::DiskAnn => write!(f, "DISKANN"),
         }
     }
 }
@@ -81,6 +83,7 @@ impl TryFrom<&str> for SubIndexType {
         match value {
             "FLAT" => Ok(Self::Flat),
             "HNSW" => Ok(Self::Hnsw),
+            "DISKANN" => Ok(Self::DiskAnn),
             _ => Err(Error::Index {
                 message: format!("unknown sub index type {}", value),
                 location: location!(),
@@ -104,6 +104,12 @@ impl TryFrom<&str> for DistanceType {
     }
 }
 
+/// MaxSim distance between a multi-vector query and each of the multi-vectors.
+///
+/// For each query vector, the similarity `1 - distance` to its closest vector in the
+/// multi-vector is taken, and the distance is `1 - sum of the similarities`. For cosine
+/// distance, it's `1 - MaxSim` of the late-interaction (ColBERT) score. Null multi-vectors
+/// get a `NaN` distance.
 pub fn multivec_distance(
     query: &dyn Array,
     vectors: &ListArray,
@@ -204,3 +210,52 @@ where
         })
         .sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+    use arrow_array::builder::{FixedSizeListBuilder, Float32Builder, ListBuilder};
+
+    fn multivectors(docs: &[Option<&[[f32; 2]]>]) -> ListArray {
+        let mut builder = ListBuilder::new(FixedSizeListBuilder::new(Float32Builder::new(), 2));
+        for doc in docs {
+            match doc {
+                Some(vectors) => {
+                    for v in vectors.iter() {
+                        builder.values().values().append_slice(v);
+                        builder.values().append(true);
+                    }
+                    builder.append(true);
+                }
+                None => builder.append(false),
+            }
+        }
+        builder.finish()
+    }
+
+    #[test]
+    fn test_multivec_distance() {
+        let query = Float32Array::from(vec![1.0, 0.0, 0.0, 1.0]);
+        let vectors = multivectors(&[
+            Some(&[[1.0, 0.0], [0.0, 1.0]]),
+            Some(&[[1.0, 0.0]]),
+            Some(&[[-1.0, 0.0]]),
+            None,
+        ]);
+
+        // 1 - MaxSim, MaxSim sums up the closest cosine similarity of each query vector
+        let dists = multivec_distance(&query, &vectors, DistanceType::Cosine).unwrap();
+        for (dist, expected) in dists.iter().zip([-1.0, 0.0, 2.0]) {
+            assert_relative_eq!(*dist, expected, epsilon = 1e-6);
+        }
+        assert!(dists[3].is_nan());
+
+        // the dot distance is `1 - dot`, so the similarity is the dot product
+        let dists = multivec_distance(&query, &vectors, DistanceType::Dot).unwrap();
+        for (dist, expected) in dists.iter().zip([-1.0, 0.0, 2.0]) {
+            assert_relative_eq!(*dist, expected, epsilon = 1e-6);
+        }
+    }
+}
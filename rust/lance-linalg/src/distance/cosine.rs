@@ -67,6 +67,8 @@ pub trait Cosine: Dot + Normalize {
 
 impl Cosine for u8 {}
 
+impl Cosine for i8 {}
+
 impl Cosine for bf16 {}
 
 #[cfg(feature = "fp16kernels")]
@@ -320,6 +322,17 @@ pub fn cosine_distance_arrow_batch(
         DataType::Float16 => do_cosine_distance_arrow_batch::<Float16Type>(from.as_primitive(), to),
         DataType::Float32 => do_cosine_distance_arrow_batch::<Float32Type>(from.as_primitive(), to),
         DataType::Float64 => do_cosine_distance_arrow_batch::<Float64Type>(from.as_primitive(), to),
+        DataType::Int8 if to.value_type() == DataType::Int8 => {
+            let dists = cosine_distance_batch(
+                from.as_primitive::<Int8Type>().values(),
+                to.values().as_primitive::<Int8Type>().values(),
+                to.value_length() as usize,
+            );
+            Ok(Arc::new(Float32Array::new(
+                dists.collect(),
+                to.nulls().cloned(),
+            )))
+        }
         DataType::Int8 => do_cosine_distance_arrow_batch::<Float32Type>(
             &from
                 .as_primitive::<Int8Type>()
@@ -7,6 +7,7 @@ use std::iter::Sum;
 use std::ops::AddAssign;
 use std::sync::Arc;
 
+use crate::simd::i8::dot_i8;
 use crate::Error;
 use arrow_array::types::{Float16Type, Float64Type, Int8Type};
 use arrow_array::{cast::AsArray, types::Float32Type, Array, FixedSizeListArray, Float32Array};
@@ -212,6 +213,13 @@ impl Dot for u8 {
     }
 }
 
+impl Dot for i8 {
+    #[inline]
+    fn dot(x: &[Self], y: &[Self]) -> f32 {
+        dot_i8(x, y) as f32
+    }
+}
+
 /// Negative dot product, to present the relative order of dot distance.
 pub fn dot_distance_batch<'a, T: Dot>(
     from: &'a [T],
@@ -278,6 +286,17 @@ pub fn dot_distance_arrow_batch(
         DataType::Float16 => do_dot_distance_arrow_batch::<Float16Type>(from.as_primitive(), to),
         DataType::Float32 => do_dot_distance_arrow_batch::<Float32Type>(from.as_primitive(), to),
         DataType::Float64 => do_dot_distance_arrow_batch::<Float64Type>(from.as_primitive(), to),
+        DataType::Int8 if to.value_type() == DataType::Int8 => {
+            let dists = dot_distance_batch(
+                from.as_primitive::<Int8Type>().values(),
+                to.values().as_primitive::<Int8Type>().values(),
+                dimension,
+            );
+            Ok(Arc::new(Float32Array::new(
+                dists.collect(),
+                to.nulls().cloned(),
+            )))
+        }
         DataType::Int8 => do_dot_distance_arrow_batch::<Float32Type>(
             &from
                 .as_primitive::<Int8Type>()
@@ -8,6 +8,7 @@ use std::iter::Sum;
 use std::ops::AddAssign;
 use std::sync::Arc;
 
+use crate::simd::i8::l2_i8;
 use crate::{Error, Result};
 use arrow_array::{
     cast::AsArray,
@@ -96,6 +97,13 @@ impl L2 for u8 {
     }
 }
 
+impl L2 for i8 {
+    #[inline]
+    fn l2(x: &[Self], y: &[Self]) -> f32 {
+        l2_i8(x, y) as f32
+    }
+}
+
 impl L2 for bf16 {
     #[inline]
     fn l2(x: &[Self], y: &[Self]) -> f32 {
@@ -249,6 +257,17 @@ pub fn l2_distance_arrow_batch(
         DataType::Float16 => do_l2_distance_arrow_batch::<Float16Type>(from.as_primitive(), to),
         DataType::Float32 => do_l2_distance_arrow_batch::<Float32Type>(from.as_primitive(), to),
         DataType::Float64 => do_l2_distance_arrow_batch::<Float64Type>(from.as_primitive(), to),
+        DataType::Int8 if to.value_type() == DataType::Int8 => {
+            let dists = l2_distance_batch(
+                from.as_primitive::<Int8Type>().values(),
+                to.values().as_primitive::<Int8Type>().values(),
+                to.value_length() as usize,
+            );
+            Ok(Arc::new(Float32Array::new(
+                dists.collect(),
+                to.nulls().cloned(),
+            )))
+        }
         DataType::Int8 => do_l2_distance_arrow_batch::<Float32Type>(
             &from
                 .as_primitive::<Int8Type>()
@@ -269,6 +288,7 @@ mod tests {
     use super::*;
 
     use approx::assert_relative_eq;
+    use arrow_array::Int8Array;
     use num_traits::ToPrimitive;
     use proptest::prelude::*;
 
@@ -444,4 +464,26 @@ mod tests {
             (255_u32.pow(2) * 2048) as f32
         );
     }
+
+    #[test]
+    fn test_int8_l2_arrow_batch() {
+        let values = Int8Array::from_iter_values((0..100).map(|v| (v * 7 % 256 - 128) as i8));
+        let mat = FixedSizeListArray::try_new_from_values(values, 10).unwrap();
+        let query = mat.value(3);
+
+        let distances = l2_distance_arrow_batch(query.as_ref(), &mat).unwrap();
+        let expected = l2_distance_arrow_batch(
+            &Float32Array::from_iter_values(
+                query
+                    .as_primitive::<Int8Type>()
+                    .values()
+                    .iter()
+                    .map(|&v| v as f32),
+            ),
+            &mat.convert_to_floating_point().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(distances.as_ref(), expected.as_ref());
+        assert_eq!(distances.value(3), 0.0);
+    }
 }
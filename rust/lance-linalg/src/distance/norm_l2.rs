@@ -10,6 +10,8 @@ use lance_core::utils::cpu::SimdSupport;
 use lance_core::utils::cpu::FP16_SIMD_SUPPORT;
 use num_traits::{AsPrimitive, Float, Num};
 
+use crate::simd::i8::dot_i8;
+
 /// L2 normalization
 pub trait Normalize: Num {
     /// L2 Normalization over a Vector.
@@ -43,6 +45,13 @@ impl Normalize for u8 {
     }
 }
 
+impl Normalize for i8 {
+    #[inline]
+    fn norm_l2(vector: &[Self]) -> f32 {
+        (dot_i8(vector, vector) as f32).sqrt()
+    }
+}
+
 impl Normalize for f16 {
     #[inline]
     fn norm_l2(vector: &[Self]) -> f32 {
@@ -16,6 +16,7 @@ use arrow_array::{
     OffsetSizeTrait, PrimitiveArray, UInt64Array,
 };
 use arrow_schema::{ArrowError, DataType};
+use lance_arrow::FixedSizeListArrayExt;
 use num_traits::{bounds::Bounded, Float, Num};
 
 use crate::{Error, Result};
@@ -128,11 +129,22 @@ where
     Ok(Arc::new(PrimitiveArray::<T>::from_iter_values(normalize(v.values()))) as ArrayRef)
 }
 
+/// L2 normalize a vector.
+///
+/// `Int8` vectors are normalized into `Float32` vectors.
 pub fn normalize_arrow(v: &dyn Array) -> Result<ArrayRef> {
     match v.data_type() {
         DataType::Float16 => do_normalize_arrow::<Float16Type>(v),
         DataType::Float32 => do_normalize_arrow::<Float32Type>(v),
         DataType::Float64 => do_normalize_arrow::<Float64Type>(v),
+        DataType::Int8 => {
+            do_normalize_arrow::<Float32Type>(&PrimitiveArray::<Float32Type>::from_iter_values(
+                v.as_primitive::<Int8Type>()
+                    .values()
+                    .iter()
+                    .map(|&x| x as f32),
+            ))
+        }
         _ => Err(Error::SchemaError(format!(
             "Normalize only supports float array, got: {}",
             v.data_type()
@@ -169,11 +181,14 @@ where
 }
 
 /// L2 normalize a [FixedSizeListArray] (of vectors).
+///
+/// `Int8` vectors are normalized into `Float32` vectors.
 pub fn normalize_fsl(fsl: &FixedSizeListArray) -> Result<FixedSizeListArray> {
     match fsl.value_type() {
         DataType::Float16 => do_normalize_fsl::<Float16Type>(fsl),
         DataType::Float32 => do_normalize_fsl::<Float32Type>(fsl),
         DataType::Float64 => do_normalize_fsl::<Float64Type>(fsl),
+        DataType::Int8 => do_normalize_fsl::<Float32Type>(&fsl.convert_to_floating_point()?),
         _ => Err(ArrowError::SchemaError(format!(
             "Normalize only supports float array, got: {}",
             fsl.value_type()
@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Kernels over `i8` vectors.
+//!
+//! The `i8` values are widened to `i16` before multiplying and the products are
+//! accumulated in `i32`, so the results are exact as long as the dimension is
+//! less than `32768`.
+
+#[cfg(target_arch = "aarch64")]
+use std::arch::aarch64::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+#[cfg(target_arch = "x86_64")]
+use super::{i32::i32x8, SIMD};
+
+const LANES: usize = 16;
+
+/// Dot product of two `i8` vectors.
+#[inline]
+pub fn dot_i8(x: &[i8], y: &[i8]) -> i32 {
+    debug_assert_eq!(x.len(), y.len());
+    let aligned_len = x.len() / LANES * LANES;
+    dot_i8_aligned(&x[..aligned_len], &y[..aligned_len])
+        + dot_i8_scalar(&x[aligned_len..], &y[aligned_len..])
+}
+
+/// Squared L2 distance between two `i8` vectors.
+#[inline]
+pub fn l2_i8(x: &[i8], y: &[i8]) -> i32 {
+    debug_assert_eq!(x.len(), y.len());
+    let aligned_len = x.len() / LANES * LANES;
+    l2_i8_aligned(&x[..aligned_len], &y[..aligned_len])
+        + l2_i8_scalar(&x[aligned_len..], &y[aligned_len..])
+}
+
+#[inline]
+fn dot_i8_scalar(x: &[i8], y: &[i8]) -> i32 {
+    x.iter()
+        .zip(y.iter())
+        .map(|(&a, &b)| a as i32 * b as i32)
+        .sum()
+}
+
+#[inline]
+fn l2_i8_scalar(x: &[i8], y: &[i8]) -> i32 {
+    x.iter()
+        .zip(y.iter())
+        .map(|(&a, &b)| (a as i32 - b as i32).pow(2))
+        .sum()
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+fn dot_i8_aligned(x: &[i8], y: &[i8]) -> i32 {
+    let mut sum = i32x8::zeros();
+    for i in (0..x.len()).step_by(LANES) {
+        unsafe {
+            let a = _mm256_cvtepi8_epi16(_mm_loadu_si128(x.as_ptr().add(i) as *const __m128i));
+            let b = _mm256_cvtepi8_epi16(_mm_loadu_si128(y.as_ptr().add(i) as *const __m128i));
+            sum += i32x8(_mm256_madd_epi16(a, b));
+        }
+    }
+    sum.reduce_sum()
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+fn l2_i8_aligned(x: &[i8], y: &[i8]) -> i32 {
+    let mut sum = i32x8::zeros();
+    for i in (0..x.len()).step_by(LANES) {
+        unsafe {
+            let a = _mm256_cvtepi8_epi16(_mm_loadu_si128(x.as_ptr().add(i) as *const __m128i));
+            let b = _mm256_cvtepi8_epi16(_mm_loadu_si128(y.as_ptr().add(i) as *const __m128i));
+            let diff = _mm256_sub_epi16(a, b);
+            sum += i32x8(_mm256_madd_epi16(diff, diff));
+        }
+    }
+    sum.reduce_sum()
+}
+
+#[cfg(target_arch = "aarch64")]
+#[inline]
+fn dot_i8_aligned(x: &[i8], y: &[i8]) -> i32 {
+    unsafe {
+        let mut sum = vdupq_n_s32(0);
+        for i in (0..x.len()).step_by(LANES) {
+            let a = vld1q_s8(x.as_ptr().add(i));
+            let b = vld1q_s8(y.as_ptr().add(i));
+            sum = vpadalq_s16(sum, vmull_s8(vget_low_s8(a), vget_low_s8(b)));
+            sum = vpadalq_s16(sum, vmull_high_s8(a, b));
+        }
+        vaddvq_s32(sum)
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+#[inline]
+fn l2_i8_aligned(x: &[i8], y: &[i8]) -> i32 {
+    unsafe {
+        let mut sum = vdupq_n_s32(0);
+        for i in (0..x.len()).step_by(LANES) {
+            let a = vld1q_s8(x.as_ptr().add(i));
+            let b = vld1q_s8(y.as_ptr().add(i));
+            let low = vsubl_s8(vget_low_s8(a), vget_low_s8(b));
+            let high = vsubl_high_s8(a, b);
+            sum = vmlal_s16(sum, vget_low_s16(low), vget_low_s16(low));
+            sum = vmlal_high_s16(sum, low, low);
+            sum = vmlal_s16(sum, vget_low_s16(high), vget_low_s16(high));
+            sum = vmlal_high_s16(sum, high, high);
+        }
+        vaddvq_s32(sum)
+    }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[inline]
+fn dot_i8_aligned(x: &[i8], y: &[i8]) -> i32 {
+    dot_i8_scalar(x, y)
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[inline]
+fn l2_i8_aligned(x: &[i8], y: &[i8]) -> i32 {
+    l2_i8_scalar(x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::Rng;
+
+    #[test]
+    fn test_i8_kernels() {
+        let mut rng = rand::thread_rng();
+        for dim in [1, 8, 16, 31, 32, 100, 1024] {
+            let x = (0..dim).map(|_| rng.gen::<i8>()).collect::<Vec<_>>();
+            let y = (0..dim).map(|_| rng.gen::<i8>()).collect::<Vec<_>>();
+            assert_eq!(dot_i8(&x, &y), dot_i8_scalar(&x, &y));
+            assert_eq!(l2_i8(&x, &y), l2_i8_scalar(&x, &y));
+        }
+
+        // the extreme values must not overflow
+        let x = vec![i8::MIN; 64];
+        let y = vec![i8::MAX; 64];
+        assert_eq!(dot_i8(&x, &x), 128 * 128 * 64);
+        assert_eq!(l2_i8(&x, &y), 255 * 255 * 64);
+    }
+}
@@ -18,8 +18,10 @@ pub const FLAG_MOVE_STABLE_ROW_IDS: u64 = 2;
 pub const FLAG_USE_V2_FORMAT_DEPRECATED: u64 = 4;
 /// Table config is present
 pub const FLAG_TABLE_CONFIG: u64 = 8;
+/// Files may be stored under the root of another dataset (shallow clones)
+pub const FLAG_BASE_PATHS: u64 = 16;
 /// The first bit that is unknown as a feature flag
-pub const FLAG_UNKNOWN: u64 = 16;
+pub const FLAG_UNKNOWN: u64 = 32;
 
 /// Set the reader and writer feature flags in the manifest based on the contents of the manifest.
 pub fn apply_feature_flags(manifest: &mut Manifest, enable_stable_row_id: bool) -> Result<()> {
@@ -57,6 +59,13 @@ pub fn apply_feature_flags(manifest: &mut Manifest, enable_stable_row_id: bool)
         manifest.writer_feature_flags |= FLAG_MOVE_STABLE_ROW_IDS;
     }
 
+    // Readers that don't know about base paths would look for the files in the
+    // wrong place
+    if !manifest.base_paths.is_empty() {
+        manifest.reader_feature_flags |= FLAG_BASE_PATHS;
+        manifest.writer_feature_flags |= FLAG_BASE_PATHS;
+    }
+
     // Test whether any table metadata has been set
     if !manifest.config.is_empty() {
         manifest.writer_feature_flags |= FLAG_TABLE_CONFIG;
@@ -87,6 +96,7 @@ mod tests {
         assert!(can_read_dataset(super::FLAG_DELETION_FILES));
         assert!(can_read_dataset(super::FLAG_MOVE_STABLE_ROW_IDS));
         assert!(can_read_dataset(super::FLAG_USE_V2_FORMAT_DEPRECATED));
+        assert!(can_read_dataset(super::FLAG_BASE_PATHS));
         assert!(can_read_dataset(
             super::FLAG_DELETION_FILES
                 | super::FLAG_MOVE_STABLE_ROW_IDS
@@ -102,6 +112,7 @@ mod tests {
         assert!(can_write_dataset(super::FLAG_MOVE_STABLE_ROW_IDS));
         assert!(can_write_dataset(super::FLAG_USE_V2_FORMAT_DEPRECATED));
         assert!(can_write_dataset(super::FLAG_TABLE_CONFIG));
+        assert!(can_write_dataset(super::FLAG_BASE_PATHS));
         assert!(can_write_dataset(
             super::FLAG_DELETION_FILES
                 | super::FLAG_MOVE_STABLE_ROW_IDS
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
+use std::collections::BTreeMap;
 use std::num::NonZero;
 
 use deepsize::DeepSizeOf;
@@ -41,6 +42,12 @@ pub struct DataFile {
 
     /// The size of the file in bytes, if known.
     pub file_size_bytes: CachedFileSize,
+
+    /// The id of the base path the file is stored under, if it belongs to
+    /// another dataset (see [`super::BasePath`]). If `None`, the path is
+    /// relative to this dataset's data directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_id: Option<u32>,
 }
 
 impl DataFile {
@@ -59,6 +66,7 @@ impl DataFile {
             file_major_version,
             file_minor_version,
             file_size_bytes: file_size_bytes.into(),
+            base_id: None,
         }
     }
 
@@ -75,6 +83,7 @@ impl DataFile {
             file_major_version,
             file_minor_version,
             file_size_bytes: Default::default(),
+            base_id: None,
         }
     }
 
@@ -143,6 +152,7 @@ impl From<&DataFile> for pb::DataFile {
             file_major_version: df.file_major_version,
             file_minor_version: df.file_minor_version,
             file_size_bytes: df.file_size_bytes.get().map_or(0, |v| v.get()),
+            base_id: df.base_id,
         }
     }
 }
@@ -158,6 +168,7 @@ impl TryFrom<pb::DataFile> for DataFile {
             file_major_version: proto.file_major_version,
             file_minor_version: proto.file_minor_version,
             file_size_bytes: CachedFileSize::new(proto.file_size_bytes),
+            base_id: proto.base_id,
         })
     }
 }
@@ -186,6 +197,10 @@ pub struct DeletionFile {
     pub file_type: DeletionFileType,
     /// Number of deleted rows in this file. If None, this is unknown.
     pub num_deleted_rows: Option<usize>,
+    /// The id of the base path the file is stored under, if it belongs to
+    /// another dataset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_id: Option<u32>,
 }
 
 impl TryFrom<pb::DeletionFile> for DeletionFile {
@@ -212,6 +227,7 @@ impl TryFrom<pb::DeletionFile> for DeletionFile {
             id: value.id,
             file_type,
             num_deleted_rows,
+            base_id: value.base_id,
         })
     }
 }
@@ -248,6 +264,13 @@ impl TryFrom<pb::data_fragment::RowIdSequence> for RowIdMeta {
     }
 }
 
+/// The partition values shared by every row of a fragment.
+///
+/// Keyed by the field id of the partition column. Values are stored as their
+/// string representation (as produced by casting to `Utf8`), `None` means the
+/// partition value is null.
+pub type PartitionValues = BTreeMap<i32, Option<String>>;
+
 /// Data fragment.
 ///
 /// A fragment is a set of files which represent the different columns of the same rows.
@@ -272,6 +295,11 @@ pub struct Fragment {
     /// unknown. This is only optional for legacy reasons. All new tables should
     /// have this set.
     pub physical_rows: Option<usize>,
+
+    /// The values of the partition columns, if this fragment belongs to a
+    /// single partition of a partitioned dataset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partition_values: Option<PartitionValues>,
 }
 
 impl Fragment {
@@ -282,6 +310,7 @@ impl Fragment {
             deletion_file: None,
             row_id_meta: None,
             physical_rows: None,
+            partition_values: None,
         }
     }
 
@@ -319,6 +348,7 @@ impl Fragment {
             deletion_file: None,
             physical_rows,
             row_id_meta: None,
+            partition_values: None,
         }
     }
 
@@ -348,6 +378,11 @@ impl Fragment {
         self
     }
 
+    pub fn with_partition_values(mut self, partition_values: PartitionValues) -> Self {
+        self.partition_values = Some(partition_values);
+        self
+    }
+
     pub fn add_file(
         &mut self,
         path: impl Into<String>,
@@ -437,6 +472,13 @@ impl TryFrom<pb::DataFragment> for Fragment {
             deletion_file: p.deletion_file.map(DeletionFile::try_from).transpose()?,
             row_id_meta: p.row_id_sequence.map(RowIdMeta::try_from).transpose()?,
             physical_rows,
+            partition_values: p.partition_values.map(|values| {
+                values
+                    .values
+                    .into_iter()
+                    .map(|entry| (entry.field_id, entry.value))
+                    .collect()
+            }),
         })
     }
 }
@@ -453,6 +495,7 @@ impl From<&Fragment> for pb::DataFragment {
                 id: f.id,
                 file_type: file_type.into(),
                 num_deleted_rows: f.num_deleted_rows.unwrap_or_default() as u64,
+                base_id: f.base_id,
             }
         });
 
@@ -467,12 +510,26 @@ impl From<&Fragment> for pb::DataFragment {
             }
         });
 
+        let partition_values = f
+            .partition_values
+            .as_ref()
+            .map(|values| pb::PartitionValues {
+                values: values
+                    .iter()
+                    .map(|(field_id, value)| pb::partition_values::Entry {
+                        field_id: *field_id,
+                        value: value.clone(),
+                    })
+                    .collect(),
+            });
+
         Self {
             id: f.id,
             files: f.files.iter().map(pb::DataFile::from).collect(),
             deletion_file,
             row_id_sequence,
             physical_rows: f.physical_rows.unwrap_or_default() as u64,
+            partition_values,
         }
     }
 }
@@ -523,6 +580,7 @@ mod tests {
             id: 456,
             file_type: DeletionFileType::Array,
             num_deleted_rows: Some(10),
+            base_id: None,
         });
 
         let proto = pb::DataFragment::from(&fragment);
@@ -535,6 +593,26 @@ mod tests {
         assert_eq!(fragment, fragment2);
     }
 
+    #[test]
+    fn test_roundtrip_partition_values() {
+        let fragment = Fragment::new(7).with_partition_values(PartitionValues::from([
+            (0, Some("2024-06-01".to_string())),
+            (3, None),
+        ]));
+
+        let proto = pb::DataFragment::from(&fragment);
+        let fragment2 = Fragment::try_from(proto).unwrap();
+        assert_eq!(fragment, fragment2);
+
+        let json = serde_json::to_string(&fragment).unwrap();
+        let fragment3 = Fragment::from_json(&json).unwrap();
+        assert_eq!(fragment, fragment3);
+
+        // Unpartitioned fragments do not carry any partition values
+        let proto = pb::DataFragment::from(&Fragment::new(8));
+        assert!(proto.partition_values.is_none());
+    }
+
     #[test]
     fn test_to_json() {
         let mut fragment = Fragment::new(123);
@@ -545,6 +623,7 @@ mod tests {
             id: 456,
             file_type: DeletionFileType::Array,
             num_deleted_rows: Some(10),
+            base_id: None,
         });
 
         let json = serde_json::to_string(&fragment).unwrap();
@@ -92,6 +92,10 @@ pub struct Manifest {
 
     /// Blob dataset version
     pub blob_dataset_version: Option<u64>,
+
+    /// Roots of other datasets whose files are referenced by this one, keyed
+    /// by their id.
+    pub base_paths: HashMap<u32, BasePath>,
 }
 
 // We use the most significant bit to indicate that a transaction is detached
@@ -143,6 +147,7 @@ impl Manifest {
             data_storage_format,
             config: HashMap::new(),
             blob_dataset_version,
+            base_paths: HashMap::new(),
         }
     }
 
@@ -176,6 +181,7 @@ impl Manifest {
             data_storage_format: previous.data_storage_format.clone(),
             config: previous.config.clone(),
             blob_dataset_version,
+            base_paths: previous.base_paths.clone(),
         }
     }
 
@@ -369,6 +375,50 @@ impl Manifest {
     }
 }
 
+/// The root of another dataset whose files are referenced by a manifest.
+///
+/// Shallow clones reference the data, deletion and index files of the dataset
+/// they were cloned from. Those files carry the `base_id` of the base path they
+/// are stored under.
+#[derive(Debug, Clone, PartialEq, Eq, DeepSizeOf)]
+pub struct BasePath {
+    pub id: u32,
+    /// Path of the dataset root within the object store.
+    pub path: String,
+}
+
+impl BasePath {
+    pub fn new(id: u32, path: &Path) -> Self {
+        Self {
+            id,
+            path: path.to_string(),
+        }
+    }
+
+    /// The root of the dataset as an object store path.
+    pub fn root(&self) -> Path {
+        Path::from(self.path.as_str())
+    }
+}
+
+impl From<pb::BasePath> for BasePath {
+    fn from(p: pb::BasePath) -> Self {
+        Self {
+            id: p.id,
+            path: p.path,
+        }
+    }
+}
+
+impl From<&BasePath> for pb::BasePath {
+    fn from(base_path: &BasePath) -> Self {
+        Self {
+            id: base_path.id,
+            path: base_path.path.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, DeepSizeOf)]
 pub struct WriterVersion {
     pub library: String,
@@ -569,6 +619,11 @@ impl TryFrom<pb::Manifest> for Manifest {
             } else {
                 Some(p.blob_dataset_version)
             },
+            base_paths: p
+                .base_paths
+                .into_iter()
+                .map(|base_path| (base_path.id, BasePath::from(base_path)))
+                .collect(),
         })
     }
 }
@@ -613,6 +668,7 @@ impl From<&Manifest> for pb::Manifest {
             }),
             config: m.config.clone(),
             blob_dataset_version: m.blob_dataset_version.unwrap_or_default(),
+            base_paths: m.base_paths.values().map(pb::BasePath::from).collect(),
         }
     }
 }
@@ -782,6 +838,7 @@ mod tests {
                 deletion_file: None,
                 row_id_meta: None,
                 physical_rows: None,
+                partition_values: None,
             },
             Fragment {
                 id: 1,
@@ -792,6 +849,7 @@ mod tests {
                 deletion_file: None,
                 row_id_meta: None,
                 physical_rows: None,
+                partition_values: None,
             },
         ];
 
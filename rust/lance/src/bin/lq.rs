@@ -10,7 +10,8 @@ use futures::stream::StreamExt;
 use futures::TryStreamExt;
 use snafu::location;
 
-use lance::dataset::Dataset;
+use lance::dataset::{Dataset, ExportParams, ImportParams, WriteMode, WriteParams};
+use lance::index::vector::eval::{evaluate_vector_index, EvalParams, SearchParams};
 use lance::index::vector::VectorIndexParams;
 use lance::{Error, Result};
 use lance_index::DatasetIndexExt;
@@ -72,12 +73,112 @@ enum Commands {
         /// Distance metric type. Only support 'l2' and 'cosine'.
         #[arg(short = 'm', long, value_name = "DISTANCE")]
         metric_type: Option<String>,
+
+        /// The k of recall@k. Only useful for 'eval'.
+        #[arg(short, default_value_t = 10)]
+        k: usize,
+
+        /// Number of query vectors sampled from the dataset. Only useful for 'eval'.
+        #[arg(long, default_value_t = 100, value_name = "NUM")]
+        num_queries: usize,
+
+        /// URI of a dataset with the query vectors in the same column, used instead of
+        /// the sampled ones. Only useful for 'eval'.
+        #[arg(long, value_name = "URI")]
+        queries: Option<String>,
+
+        /// Comma-separated list of nprobes to evaluate. Only useful for 'eval'.
+        #[arg(long, value_delimiter = ',', value_name = "NUM")]
+        nprobes: Vec<usize>,
+
+        /// Comma-separated list of HNSW ef to evaluate. Only useful for 'eval'.
+        #[arg(long, value_delimiter = ',', value_name = "NUM")]
+        ef: Vec<usize>,
+
+        /// Comma-separated list of refine factors to evaluate. Only useful for 'eval'.
+        #[arg(long, value_delimiter = ',', value_name = "NUM")]
+        refine: Vec<u32>,
+    },
+
+    /// Import a directory of Parquet, CSV or JSON files into a dataset
+    Import {
+        /// The file or directory to import.
+        source: String,
+
+        /// Dataset URI.
+        uri: String,
+
+        /// Format of the source files. Inferred from the file extensions if not set.
+        #[arg(short, long, value_enum)]
+        format: Option<ImportFormat>,
+
+        /// How to write to the dataset.
+        #[arg(long, value_enum, default_value_t = ImportMode::Create)]
+        mode: ImportMode,
+
+        /// CSV files have no header row.
+        #[arg(long)]
+        no_header: bool,
+
+        /// CSV field delimiter.
+        #[arg(long, default_value_t = ',')]
+        delimiter: char,
+
+        /// Maximum number of rows per file.
+        #[arg(long, value_name = "NUM")]
+        max_rows_per_file: Option<usize>,
+
+        /// Number of source files to import at the same time.
+        #[arg(short = 'j', long, value_name = "NUM")]
+        num_threads: Option<usize>,
+    },
+
+    /// Export a dataset version to Parquet or Arrow IPC files
+    Export {
+        /// Dataset URI.
+        uri: String,
+
+        /// The directory to write the files to.
+        dest: String,
+
+        /// Format of the exported files.
+        #[arg(short, long, value_enum, default_value_t = ExportFormat::Parquet)]
+        format: ExportFormat,
+
+        /// The version to export. Defaults to the latest version.
+        #[arg(long, conflicts_with = "tag")]
+        version: Option<u64>,
+
+        /// The tag to export.
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Comma-separated list of columns to export.
+        #[arg(short, long, value_delimiter = ',')]
+        columns: Option<Vec<String>>,
+
+        /// Only export rows matching this SQL filter.
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Maximum number of rows per file.
+        #[arg(long, value_name = "NUM")]
+        max_rows_per_file: Option<usize>,
+
+        /// Maximum number of rows per Parquet row group.
+        #[arg(long, value_name = "NUM")]
+        row_group_size: Option<usize>,
+
+        /// Number of fragments to export at the same time.
+        #[arg(short = 'j', long, value_name = "NUM")]
+        num_threads: Option<usize>,
     },
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum IndexAction {
     Create,
+    Eval,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -85,6 +186,26 @@ enum IndexType {
     IvfPQ,
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum ImportFormat {
+    Parquet,
+    Csv,
+    Json,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum ImportMode {
+    Create,
+    Append,
+    Overwrite,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum ExportFormat {
+    Parquet,
+    Arrow,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
@@ -124,6 +245,12 @@ async fn main() -> Result<()> {
             num_partitions,
             num_sub_vectors,
             metric_type,
+            k,
+            num_queries,
+            queries,
+            nprobes,
+            ef,
+            refine,
         } => {
             let mut dataset = Dataset::open(uri).await.unwrap();
             match action {
@@ -139,11 +266,145 @@ async fn main() -> Result<()> {
                     )
                     .await
                 }
+                IndexAction::Eval => {
+                    eval_index(
+                        &dataset,
+                        column,
+                        *k,
+                        *num_queries,
+                        queries,
+                        nprobes,
+                        ef,
+                        refine,
+                    )
+                    .await
+                }
             }
         }
+        Commands::Import {
+            source,
+            uri,
+            format,
+            mode,
+            no_header,
+            delimiter,
+            max_rows_per_file,
+            num_threads,
+        } => {
+            import(
+                source,
+                uri,
+                format,
+                mode,
+                *no_header,
+                *delimiter,
+                max_rows_per_file,
+                num_threads,
+            )
+            .await
+        }
+        Commands::Export {
+            uri,
+            dest,
+            format,
+            version,
+            tag,
+            columns,
+            filter,
+            max_rows_per_file,
+            row_group_size,
+            num_threads,
+        } => {
+            let mut dataset = Dataset::open(uri).await?;
+            if let Some(version) = version {
+                dataset = dataset.checkout_version(*version).await?;
+            } else if let Some(tag) = tag {
+                dataset = dataset.checkout_version(tag.as_str()).await?;
+            }
+            let mut params = ExportParams {
+                format: match format {
+                    ExportFormat::Parquet => lance::dataset::ExportFormat::Parquet,
+                    ExportFormat::Arrow => lance::dataset::ExportFormat::ArrowIpc,
+                },
+                columns: columns.clone(),
+                filter: filter.clone(),
+                num_threads: *num_threads,
+                ..Default::default()
+            };
+            if let Some(max_rows_per_file) = max_rows_per_file {
+                params.max_rows_per_file = *max_rows_per_file;
+            }
+            if let Some(row_group_size) = row_group_size {
+                params.max_rows_per_group = *row_group_size;
+            }
+
+            let files = dataset.export(dest, Some(params)).await?;
+            println!(
+                "Exported {} rows of version {} to {} files in {}",
+                files.iter().map(|file| file.num_rows).sum::<usize>(),
+                dataset.version().version,
+                files.len(),
+                dest
+            );
+            Ok(())
+        }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+async fn import(
+    source: &str,
+    uri: &str,
+    format: &Option<ImportFormat>,
+    mode: &ImportMode,
+    no_header: bool,
+    delimiter: char,
+    max_rows_per_file: &Option<usize>,
+    num_threads: &Option<usize>,
+) -> Result<()> {
+    if !delimiter.is_ascii() {
+        return Err(Error::invalid_input(
+            format!(
+                "The CSV delimiter must be an ASCII character, got '{}'",
+                delimiter
+            ),
+            location!(),
+        ));
+    }
+    let mut write_params = WriteParams {
+        mode: match mode {
+            ImportMode::Create => WriteMode::Create,
+            ImportMode::Append => WriteMode::Append,
+            ImportMode::Overwrite => WriteMode::Overwrite,
+        },
+        ..Default::default()
+    };
+    if let Some(max_rows_per_file) = max_rows_per_file {
+        write_params.max_rows_per_file = *max_rows_per_file;
+    }
+    let params = ImportParams {
+        format: format.map(|format| match format {
+            ImportFormat::Parquet => lance::dataset::ImportFormat::Parquet,
+            ImportFormat::Csv => lance::dataset::ImportFormat::Csv,
+            ImportFormat::Json => lance::dataset::ImportFormat::Json,
+        }),
+        has_header: !no_header,
+        delimiter: delimiter as u8,
+        num_threads: *num_threads,
+        write_params,
+        ..Default::default()
+    };
+
+    let dataset = Dataset::import(source, uri, Some(params)).await?;
+    println!(
+        "Imported {} rows into {} (version {})",
+        dataset.count_rows(None).await?,
+        uri,
+        dataset.version().version
+    );
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn create_index(
     dataset: &mut Dataset,
@@ -187,3 +448,88 @@ async fn create_index(
         .expect("dataset create index");
     Ok(())
 }
+
+#[allow(clippy::too_many_arguments)]
+async fn eval_index(
+    dataset: &Dataset,
+    column: &Option<String>,
+    k: usize,
+    num_queries: usize,
+    queries: &Option<String>,
+    nprobes: &[usize],
+    ef: &[usize],
+    refine: &[u32],
+) -> Result<()> {
+    let col = column.as_ref().ok_or_else(|| Error::Index {
+        message: "Must specify column".to_string(),
+        location: location!(),
+    })?;
+    let queries = if let Some(uri) = queries {
+        let query_dataset = Dataset::open(uri).await?;
+        let batch = query_dataset
+            .scan()
+            .project(&[col])?
+            .try_into_batch()
+            .await?;
+        Some(batch[col.as_str()].clone())
+    } else {
+        None
+    };
+
+    // Evaluate every combination of the search params.
+    fn or_default<T: Copy>(values: &[T]) -> Vec<Option<T>> {
+        if values.is_empty() {
+            vec![None]
+        } else {
+            values.iter().copied().map(Some).collect()
+        }
+    }
+    let mut sweep = Vec::new();
+    for nprobes in or_default(nprobes) {
+        for ef in or_default(ef) {
+            for refine_factor in or_default(refine) {
+                sweep.push(SearchParams {
+                    nprobes,
+                    ef,
+                    refine_factor,
+                });
+            }
+        }
+    }
+
+    let params = EvalParams {
+        k,
+        num_queries,
+        queries,
+        sweep,
+    };
+    let results = evaluate_vector_index(dataset, col, &params).await?;
+
+    let fmt = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+    println!(
+        "{:>8} {:>8} {:>8} {:>10} {:>10} {:>10} {:>10} {:>10}",
+        "nprobes",
+        "ef",
+        "refine",
+        format!("recall@{}", k),
+        "mean(ms)",
+        "p50(ms)",
+        "p90(ms)",
+        "p99(ms)"
+    );
+    for result in results {
+        let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+        println!(
+            "{:>8} {:>8} {:>8} {:>10.4} {:>10.3} {:>10.3} {:>10.3} {:>10.3}",
+            fmt(result.params.nprobes.map(|v| v.to_string())),
+            fmt(result.params.ef.map(|v| v.to_string())),
+            fmt(result.params.refine_factor.map(|v| v.to_string())),
+            result.recall,
+            ms(result.latency.mean),
+            ms(result.latency.p50),
+            ms(result.latency.p90),
+            ms(result.latency.p99),
+        );
+    }
+    Ok(())
+}
@@ -33,7 +33,7 @@ use lance_io::object_writer::{ObjectWriter, WriteResult};
 use lance_io::traits::WriteExt;
 use lance_io::utils::{read_last_block, read_metadata_offset, read_struct};
 use lance_table::format::{
-    DataStorageFormat, Fragment, Index, Manifest, MAGIC, MAJOR_VERSION, MINOR_VERSION,
+    DataFile, DataStorageFormat, Fragment, Index, Manifest, MAGIC, MAJOR_VERSION, MINOR_VERSION,
 };
 use lance_table::io::commit::{
     migrate_scheme_to_v2, CommitConfig, CommitError, CommitHandler, CommitLock, ManifestLocation,
@@ -56,15 +56,20 @@ use tracing::{info, instrument};
 mod blob;
 pub mod builder;
 pub mod cleanup;
+mod export;
 pub mod fragment;
 mod hash_joiner;
+mod import;
 pub mod index;
+pub mod mem_wal;
 pub mod optimize;
+pub mod partition;
 pub mod progress;
 pub mod refs;
 pub(crate) mod rowids;
 pub mod scanner;
 mod schema_evolution;
+mod shallow_clone;
 pub mod sql;
 pub mod statistics;
 mod take;
@@ -90,13 +95,16 @@ use crate::io::commit::{
 use crate::session::Session;
 use crate::utils::temporal::{timestamp_to_nanos, utc_now, SystemTime};
 use crate::{Error, Result};
-pub use blob::BlobFile;
+pub use blob::{BlobAppender, BlobFile, BlobSink};
+pub use export::{ExportFormat, ExportParams, ExportedFile};
 use hash_joiner::HashJoiner;
+pub use import::{ImportFormat, ImportParams};
 pub use lance_core::ROW_ID;
 use lance_table::feature_flags::{apply_feature_flags, can_read_dataset};
 pub use schema_evolution::{
     BatchInfo, BatchUDF, ColumnAlteration, NewColumnTransform, UDFCheckpointStore,
 };
+pub use shallow_clone::{CLONE_SOURCE_KEY, CLONE_SOURCE_TAG_KEY};
 pub use take::TakeBuilder;
 pub use write::merge_insert::{
     MergeInsertBuilder, MergeInsertJob, MergeStats, UncommittedMergeInsert, WhenMatched,
@@ -145,6 +153,10 @@ pub struct Dataset {
 
     /// File reader options to use when reading data files.
     pub(crate) file_reader_options: Option<FileReaderOptions>,
+
+    /// The object store parameters the dataset was opened with, e.g. to access the
+    /// external blobs with the same storage options.
+    pub(crate) store_params: Option<Box<ObjectStoreParams>>,
 }
 
 impl std::fmt::Debug for Dataset {
@@ -426,6 +438,7 @@ impl Dataset {
             self.session.clone(),
             self.commit_handler.clone(),
             self.file_reader_options.clone(),
+            self.store_params.clone(),
         )
     }
 
@@ -541,6 +554,7 @@ impl Dataset {
         session: Arc<Session>,
         commit_handler: Arc<dyn CommitHandler>,
         file_reader_options: Option<FileReaderOptions>,
+        store_params: Option<Box<ObjectStoreParams>>,
     ) -> Result<Self> {
         let tags = Tags::new(
             object_store.clone(),
@@ -561,6 +575,7 @@ impl Dataset {
             metadata_cache,
             index_cache,
             file_reader_options,
+            store_params,
         })
     }
 
@@ -584,6 +599,60 @@ impl Dataset {
             .await
     }
 
+    /// Import a directory of Parquet, CSV or newline-delimited JSON files.
+    ///
+    /// `source` can be a single file or a directory, which is searched
+    /// recursively. The files are read with DataFusion, their schemas are merged
+    /// and each file is written to its own fragments, several files at a time.
+    /// All of the fragments are committed to `dest` in a single transaction.
+    ///
+    /// See [`ImportParams`] for how to choose the format, the write mode and how
+    /// to resume an import that was interrupted.
+    pub async fn import(
+        source: &str,
+        dest: impl Into<WriteDestination<'_>>,
+        params: Option<ImportParams>,
+    ) -> Result<Self> {
+        import::import(source, dest.into(), params.unwrap_or_default()).await
+    }
+
+    /// Export this version of the dataset to Parquet or Arrow IPC files in the
+    /// directory `dest`.
+    ///
+    /// Each fragment is written to its own files, so the export can be spread
+    /// across several fragments at once. To export an older version or a tag,
+    /// check it out first. bfloat16 columns are exported as float32 and blob
+    /// columns as their bytes, so that the files can be read without Lance.
+    ///
+    /// Returns the files that were written.
+    pub async fn export(
+        &self,
+        dest: &str,
+        params: Option<ExportParams>,
+    ) -> Result<Vec<ExportedFile>> {
+        export::export(self, dest, params.unwrap_or_default()).await
+    }
+
+    /// Create a shallow clone of `version` of this dataset at `target_uri`.
+    ///
+    /// The clone references the data, deletion and index files of the source
+    /// instead of copying them, so it must live in the same object store.
+    /// Later writes to the clone go to `target_uri` and never modify the
+    /// source. The cloned version is tagged in the source (see
+    /// [CLONE_SOURCE_TAG_KEY]) so that `cleanup_old_versions` keeps the
+    /// referenced files.
+    ///
+    /// Returns the clone, checked out at its first version.
+    pub async fn shallow_clone(
+        &self,
+        version: impl Into<refs::Ref>,
+        target_uri: &str,
+        store_params: Option<ObjectStoreParams>,
+    ) -> Result<Self> {
+        let source = self.checkout_version(version).await?;
+        shallow_clone::shallow_clone(&source, target_uri, store_params).await
+    }
+
     /// Append to existing [Dataset] with a stream of [RecordBatch]s
     ///
     /// Returns void result or Returns [Error]
@@ -644,6 +713,7 @@ impl Dataset {
                 self.session.clone(),
                 self.commit_handler.clone(),
                 self.file_reader_options.clone(),
+                self.store_params.clone(),
             )?;
             Ok(Some(Arc::new(blobs_dataset)))
         } else {
@@ -926,6 +996,11 @@ impl Dataset {
         Ok(self.manifest.config.clone())
     }
 
+    /// Get the partition columns of the dataset, empty if it is not partitioned.
+    pub fn partition_columns(&self) -> Result<Vec<String>> {
+        partition::partition_columns(&self.manifest)
+    }
+
     /// Create a Scanner to scan the dataset.
     pub fn scan(&self) -> Scanner {
         Scanner::new(Arc::new(self.clone()))
@@ -1042,6 +1117,19 @@ impl Dataset {
         blob::take_blobs(self, &row_addrs, column.as_ref()).await
     }
 
+    /// Copy the bytes referenced by the external blob column `column` into
+    /// a new blob column `target` stored in the dataset.
+    ///
+    /// The external objects are read once, later reads of `target` don't depend
+    /// on them. Null references stay null.
+    pub async fn materialize_blobs(
+        &mut self,
+        column: impl AsRef<str>,
+        target: impl AsRef<str>,
+    ) -> Result<()> {
+        blob::materialize_blobs(self, column.as_ref(), target.as_ref()).await
+    }
+
     /// Get a stream of batches based on iterator of ranges of row numbers.
     ///
     /// This is an experimental API. It may change at any time.
@@ -1088,6 +1176,50 @@ impl Dataset {
         self.base.child(INDICES_DIR)
     }
 
+    /// The root of the dataset that stores files with the given base path id.
+    ///
+    /// Files without a base path id belong to this dataset. Files of a shallow
+    /// clone may instead belong to the dataset it was cloned from.
+    pub(crate) fn base_dir(&self, base_id: Option<u32>) -> Result<Path> {
+        match base_id {
+            None => Ok(self.base.clone()),
+            Some(base_id) => self
+                .manifest
+                .base_paths
+                .get(&base_id)
+                .map(|base_path| base_path.root())
+                .ok_or_else(|| Error::Internal {
+                    message: format!(
+                        "Base path {} is not defined in version {} of the dataset",
+                        base_id, self.manifest.version
+                    ),
+                    location: location!(),
+                }),
+        }
+    }
+
+    /// The directory containing the given data file.
+    pub fn data_file_dir(&self, data_file: &DataFile) -> Result<Path> {
+        Ok(self.base_dir(data_file.base_id)?.child(DATA_DIR))
+    }
+
+    /// The directory containing the files of the given index.
+    pub(crate) fn index_dir(&self, index: &Index) -> Result<Path> {
+        Ok(self
+            .base_dir(index.base_id)?
+            .child(INDICES_DIR)
+            .child(index.uuid.to_string()))
+    }
+
+    /// The indices directory holding the index with the given uuid.
+    ///
+    /// Indices that are not part of this version yet (e.g. while they are
+    /// being built) are always stored in this dataset's indices directory.
+    pub(crate) async fn indices_dir_for(&self, uuid: &str) -> Result<Path> {
+        let base_id = self.load_index(uuid).await?.and_then(|index| index.base_id);
+        Ok(self.base_dir(base_id)?.child(INDICES_DIR))
+    }
+
     pub fn session(&self) -> Arc<Session> {
         self.session.clone()
     }
@@ -1580,6 +1712,7 @@ pub(crate) fn load_new_transactions(dataset: &Dataset) -> NewTransactionResult<'
                         dataset.session(),
                         dataset.commit_handler.clone(),
                         dataset.file_reader_options.clone(),
+                        dataset.store_params.clone(),
                     )?;
                     let object_store = dataset_version.object_store();
                     let path = dataset_version
@@ -1617,6 +1750,7 @@ pub(crate) fn load_new_transactions(dataset: &Dataset) -> NewTransactionResult<'
                 dataset.session(),
                 dataset.commit_handler.clone(),
                 dataset.file_reader_options.clone(),
+                dataset.store_params.clone(),
             )
         } else {
             // If we didn't get the latest manifest, we can still return the dataset
@@ -1977,8 +2111,14 @@ mod tests {
     use lance_file::v2::writer::FileWriter;
     use lance_file::version::LanceFileVersion;
     use lance_index::scalar::inverted::{
-        query::{BooleanQuery, MatchQuery, Occur, Operator, PhraseQuery},
+        explain::{Explanation, EXPLAIN_COL},
+        highlight::{HighlightParams, HIGHLIGHTS_COL, SNIPPET_COL},
+        query::{
+            BooleanQuery, FtsQuery, MatchQuery, Occur, Operator, PhraseQuery, PrefixQuery,
+            RegexpQuery, WildcardQuery,
+        },
         tokenizer::InvertedIndexParams,
+        SCORE_COL,
     };
     use lance_index::scalar::FullTextSearchQuery;
     use lance_index::{scalar::ScalarIndexParams, vector::DIST_COL, DatasetIndexExt, IndexType};
@@ -4910,6 +5050,545 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_fts_term_pattern_query() {
+        let tempdir = tempfile::tempdir().unwrap();
+
+        let params = InvertedIndexParams::default().stem(false);
+        let text_col = GenericStringArray::<i32>::from(vec![
+            "inter", "internet", "interest", "winter", "land", "lance",
+        ]);
+        let batch = RecordBatch::try_new(
+            arrow_schema::Schema::new(vec![arrow_schema::Field::new(
+                "text",
+                text_col.data_type().to_owned(),
+                false,
+            )])
+            .into(),
+            vec![Arc::new(text_col) as ArrayRef],
+        )
+        .unwrap();
+        let schema = batch.schema();
+        let batches = RecordBatchIterator::new(vec![batch].into_iter().map(Ok), schema);
+        let mut dataset = Dataset::write(batches, tempdir.path().to_str().unwrap(), None)
+            .await
+            .unwrap();
+        dataset
+            .create_index(&["text"], IndexType::Inverted, None, &params, true)
+            .await
+            .unwrap();
+
+        let search = |dataset: Dataset, query: FtsQuery| async move {
+            let results = dataset
+                .scan()
+                .full_text_search(FullTextSearchQuery::new_query(query))
+                .unwrap()
+                .try_into_batch()
+                .await
+                .unwrap();
+            results["text"]
+                .as_string::<i32>()
+                .iter()
+                .map(|s| s.unwrap().to_owned())
+                .collect::<HashSet<_>>()
+        };
+        let set = |texts: &[&str]| texts.iter().map(|s| s.to_string()).collect::<HashSet<_>>();
+
+        // the prefix is lower cased like the indexed tokens
+        let texts = search(dataset.clone(), PrefixQuery::new("INTER".to_owned()).into()).await;
+        assert_eq!(texts, set(&["inter", "internet", "interest"]));
+        // only the first tokens in lexicographic order are expanded
+        let texts = search(
+            dataset.clone(),
+            PrefixQuery::new("inter".to_owned())
+                .with_max_expansions(2)
+                .into(),
+        )
+        .await;
+        assert_eq!(texts, set(&["inter", "interest"]));
+
+        let texts = search(
+            dataset.clone(),
+            WildcardQuery::new("*nte?".to_owned()).into(),
+        )
+        .await;
+        assert_eq!(texts, set(&["inter", "winter"]));
+        let texts = search(
+            dataset.clone(),
+            WildcardQuery::new("int*t".to_owned()).into(),
+        )
+        .await;
+        assert_eq!(texts, set(&["internet", "interest"]));
+
+        let texts = search(
+            dataset.clone(),
+            RegexpQuery::new("lan(d|ce)".to_owned()).into(),
+        )
+        .await;
+        assert_eq!(texts, set(&["land", "lance"]));
+        let texts = search(dataset.clone(), RegexpQuery::new("lan".to_owned()).into()).await;
+        assert!(texts.is_empty());
+
+        // the unindexed data is searched with the pattern too
+        let text_col = GenericStringArray::<i32>::from(vec!["intern", "lancer"]);
+        let batch = RecordBatch::try_new(
+            arrow_schema::Schema::new(vec![arrow_schema::Field::new(
+                "text",
+                text_col.data_type().to_owned(),
+                false,
+            )])
+            .into(),
+            vec![Arc::new(text_col) as ArrayRef],
+        )
+        .unwrap();
+        let schema = batch.schema();
+        let batches = RecordBatchIterator::new(vec![batch].into_iter().map(Ok), schema);
+        let dataset = Dataset::write(
+            batches,
+            tempdir.path().to_str().unwrap(),
+            Some(WriteParams {
+                mode: WriteMode::Append,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        let texts = search(dataset.clone(), PrefixQuery::new("inter".to_owned()).into()).await;
+        assert_eq!(texts, set(&["inter", "internet", "interest", "intern"]));
+        let texts = search(
+            dataset.clone(),
+            RegexpQuery::new("lan(d|ce)".to_owned()).into(),
+        )
+        .await;
+        assert_eq!(texts, set(&["land", "lance"]));
+    }
+
+    #[tokio::test]
+    async fn test_fts_query_string() {
+        let tempdir = tempfile::tempdir().unwrap();
+
+        let params = InvertedIndexParams::default().with_position(true);
+        let title_col =
+            GenericStringArray::<i32>::from(vec!["title hello", "title lance", "title common"]);
+        let content_col = GenericStringArray::<i32>::from(vec![
+            "content world",
+            "content database",
+            "content common",
+        ]);
+        let batch = RecordBatch::try_new(
+            arrow_schema::Schema::new(vec![
+                arrow_schema::Field::new("title", title_col.data_type().to_owned(), false),
+                arrow_schema::Field::new("content", title_col.data_type().to_owned(), false),
+            ])
+            .into(),
+            vec![
+                Arc::new(title_col) as ArrayRef,
+                Arc::new(content_col) as ArrayRef,
+            ],
+        )
+        .unwrap();
+        let schema = batch.schema();
+        let batches = RecordBatchIterator::new(vec![batch].into_iter().map(Ok), schema);
+        let mut dataset = Dataset::write(batches, tempdir.path().to_str().unwrap(), None)
+            .await
+            .unwrap();
+        dataset
+            .create_index(&["title"], IndexType::Inverted, None, &params, true)
+            .await
+            .unwrap();
+        dataset
+            .create_index(&["content"], IndexType::Inverted, None, &params, true)
+            .await
+            .unwrap();
+
+        let search = |query: FullTextSearchQuery| {
+            let dataset = dataset.clone();
+            async move {
+                let results = dataset
+                    .scan()
+                    .full_text_search(query)
+                    .unwrap()
+                    .try_into_batch()
+                    .await
+                    .unwrap();
+                results["title"]
+                    .as_string::<i32>()
+                    .iter()
+                    .map(|s| s.unwrap().to_owned())
+                    .collect::<HashSet<_>>()
+            }
+        };
+        let set = |texts: &[&str]| texts.iter().map(|s| s.to_string()).collect::<HashSet<_>>();
+
+        let query =
+            FullTextSearchQuery::from_query_string("+title:(hello OR lance) -content:database")
+                .unwrap();
+        assert_eq!(search(query).await, set(&["title hello"]));
+
+        // the phrase is nested in a boolean query
+        let query =
+            FullTextSearchQuery::from_query_string("title:\"title lance\" OR content:comm*")
+                .unwrap();
+        assert_eq!(search(query).await, set(&["title lance", "title common"]));
+
+        // the terms without column are searched on all the given columns
+        let query = FullTextSearchQuery::from_query_string("common^2 OR wor*")
+            .unwrap()
+            .with_columns(&["title".to_owned(), "content".to_owned()])
+            .unwrap();
+        assert_eq!(search(query).await, set(&["title hello", "title common"]));
+
+        let err = FullTextSearchQuery::from_query_string("title:(hello").unwrap_err();
+        assert!(err.to_string().contains("expected ')'"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_fts_highlight() {
+        let tempdir = tempfile::tempdir().unwrap();
+
+        let params = InvertedIndexParams::default().with_position(true);
+        let id_col = Int32Array::from(vec![0, 1, 2]);
+        let text_col = GenericStringArray::<i32>::from(vec![
+            "Lance is a columnar format",
+            "a format for lance",
+            "nothing to see here",
+        ]);
+        let batch = RecordBatch::try_new(
+            arrow_schema::Schema::new(vec![
+                arrow_schema::Field::new("id", DataType::Int32, false),
+                arrow_schema::Field::new("text", text_col.data_type().to_owned(), false),
+            ])
+            .into(),
+            vec![Arc::new(id_col) as ArrayRef, Arc::new(text_col) as ArrayRef],
+        )
+        .unwrap();
+        let schema = batch.schema();
+        let batches = RecordBatchIterator::new(vec![batch].into_iter().map(Ok), schema);
+        let mut dataset = Dataset::write(batches, tempdir.path().to_str().unwrap(), None)
+            .await
+            .unwrap();
+        dataset
+            .create_index(&["text"], IndexType::Inverted, None, &params, true)
+            .await
+            .unwrap();
+
+        // the highlighted column doesn't need to be projected
+        let query = FullTextSearchQuery::from_query_string("lance OR \"columnar format\"")
+            .unwrap()
+            .with_highlight(Some(HighlightParams::new()));
+        let results = dataset
+            .scan()
+            .project(&["id"])
+            .unwrap()
+            .full_text_search(query)
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+        let field_names = results
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            field_names,
+            vec!["id", SCORE_COL, HIGHLIGHTS_COL, SNIPPET_COL]
+        );
+        let snippets = results
+            .column_by_name("id")
+            .unwrap()
+            .as_primitive::<Int32Type>()
+            .values()
+            .iter()
+            .zip(results[SNIPPET_COL].as_string::<i32>().iter())
+            .map(|(id, snippet)| (*id, snippet.unwrap().to_owned()))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(
+            snippets,
+            HashMap::from([
+                (
+                    0,
+                    "<em>Lance</em> is a <em>columnar</em> <em>format</em>".to_owned()
+                ),
+                (1, "a format for <em>lance</em>".to_owned()),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fts_explain() {
+        let tempdir = tempfile::tempdir().unwrap();
+
+        let params = InvertedIndexParams::default();
+        let id_col = Int32Array::from(vec![0, 1, 2]);
+        let text_col = GenericStringArray::<i32>::from(vec![
+            "lance is a columnar format",
+            "a format for lance",
+            "nothing to see here",
+        ]);
+        let batch = RecordBatch::try_new(
+            arrow_schema::Schema::new(vec![
+                arrow_schema::Field::new("id", DataType::Int32, false),
+                arrow_schema::Field::new("text", text_col.data_type().to_owned(), false),
+            ])
+            .into(),
+            vec![Arc::new(id_col) as ArrayRef, Arc::new(text_col) as ArrayRef],
+        )
+        .unwrap();
+        let schema = batch.schema();
+        let batches = RecordBatchIterator::new(vec![batch].into_iter().map(Ok), schema);
+        let mut dataset = Dataset::write(batches, tempdir.path().to_str().unwrap(), None)
+            .await
+            .unwrap();
+        dataset
+            .create_index(&["text"], IndexType::Inverted, None, &params, true)
+            .await
+            .unwrap();
+
+        let explain = |results: &RecordBatch| {
+            let ids = results["id"].as_primitive::<Int32Type>().values().to_vec();
+            let scores = results[SCORE_COL]
+                .as_primitive::<Float32Type>()
+                .values()
+                .to_vec();
+            let explanations = results[EXPLAIN_COL]
+                .as_string::<i32>()
+                .iter()
+                .map(|explanation| {
+                    serde_json::from_str::<Explanation>(explanation.unwrap()).unwrap()
+                })
+                .collect::<Vec<_>>();
+            ids.into_iter()
+                .zip(scores.into_iter().zip(explanations))
+                .collect::<HashMap<_, _>>()
+        };
+
+        // the explanation of a single term query is exactly the BM25 score
+        let query = FullTextSearchQuery::new("format".to_owned()).with_explain(true);
+        let results = dataset
+            .scan()
+            .project(&["id"])
+            .unwrap()
+            .full_text_search(query)
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+        let field_names = results
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(field_names, vec!["id", SCORE_COL, EXPLAIN_COL]);
+        let explanations = explain(&results);
+        assert_eq!(explanations.len(), 2);
+        for (score, explanation) in explanations.values() {
+            assert!((score - explanation.value).abs() < 1e-5);
+            // sum of the matched terms -> term weight -> idf and tf
+            let term = &explanation.details[0];
+            assert_eq!(term.details.len(), 2);
+            assert!((term.details[0].value * term.details[1].value - term.value).abs() < 1e-5);
+        }
+        // the shorter document gets the higher term frequency normalization
+        let tf = |id: i32| explanations[&id].1.details[0].details[1].value;
+        assert!(tf(1) > tf(0));
+
+        // the should clause only contributes to the documents matching it
+        let query = FullTextSearchQuery::new_query(
+            BooleanQuery::new([
+                (
+                    Occur::Must,
+                    MatchQuery::new("format".to_owned())
+                        .with_column(Some("text".to_owned()))
+                        .into(),
+                ),
+                (
+                    Occur::Should,
+                    MatchQuery::new("columnar".to_owned())
+                        .with_column(Some("text".to_owned()))
+                        .into(),
+                ),
+            ])
+            .into(),
+        )
+        .with_explain(true);
+        let results = dataset
+            .scan()
+            .project(&["id"])
+            .unwrap()
+            .full_text_search(query)
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+        let explanations = explain(&results);
+        assert_eq!(explanations.len(), 2);
+        assert_eq!(explanations[&0].1.details.len(), 2);
+        assert_eq!(explanations[&1].1.details.len(), 1);
+        assert!(explanations[&0].1.value > explanations[&1].1.value);
+    }
+
+    #[tokio::test]
+    async fn test_fts_synonyms_and_custom_tokenizer() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let uri = tempdir.path().to_str().unwrap();
+
+        let mut session = Session::default();
+        session
+            .register_tokenizer(
+                "comma",
+                tantivy::tokenizer::RegexTokenizer::new("[^,]+").unwrap(),
+            )
+            .unwrap();
+        let session = Arc::new(session);
+
+        let text_col =
+            GenericStringArray::<i32>::from(vec!["new york,car", "paris,automobile", "london,bus"]);
+        let batch = RecordBatch::try_new(
+            arrow_schema::Schema::new(vec![arrow_schema::Field::new(
+                "text",
+                text_col.data_type().to_owned(),
+                false,
+            )])
+            .into(),
+            vec![Arc::new(text_col) as ArrayRef],
+        )
+        .unwrap();
+        let schema = batch.schema();
+        let batches = RecordBatchIterator::new(vec![batch].into_iter().map(Ok), schema);
+        let mut dataset = Dataset::write(
+            batches,
+            uri,
+            Some(WriteParams {
+                session: Some(session.clone()),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        let params = InvertedIndexParams::default()
+            .base_tokenizer("comma".to_owned())
+            .stem(false)
+            .synonyms(vec![vec!["car".to_owned(), "automobile".to_owned()]]);
+        dataset
+            .create_index(&["text"], IndexType::Inverted, None, &params, true)
+            .await
+            .unwrap();
+
+        let search = |dataset: Dataset, query: &str| {
+            let query = FullTextSearchQuery::new(query.to_owned());
+            async move {
+                let results = dataset
+                    .scan()
+                    .full_text_search(query)
+                    .unwrap()
+                    .try_into_batch()
+                    .await?;
+                Result::Ok(
+                    results["text"]
+                        .as_string::<i32>()
+                        .iter()
+                        .map(|s| s.unwrap().to_owned())
+                        .collect::<HashSet<_>>(),
+                )
+            }
+        };
+        let set = |texts: &[&str]| texts.iter().map(|s| s.to_string()).collect::<HashSet<_>>();
+
+        // the query terms are expanded to the synonyms
+        assert_eq!(
+            search(dataset.clone(), "car").await.unwrap(),
+            set(&["new york,car", "paris,automobile"])
+        );
+        // the custom tokenizer keeps the whitespaces
+        assert_eq!(
+            search(dataset.clone(), "New York").await.unwrap(),
+            set(&["new york,car"])
+        );
+
+        // the custom tokenizer must be registered to query the index
+        let dataset = Dataset::open(uri).await.unwrap();
+        let err = search(dataset, "car").await.unwrap_err();
+        assert!(
+            err.to_string().contains("unknown base tokenizer comma"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fts_synonyms_with_and_operator() {
+        let tempdir = tempfile::tempdir().unwrap();
+
+        let text_col = GenericStringArray::<i32>::from(vec![
+            "red car",
+            "red automobile",
+            "blue car",
+            "red bus",
+        ]);
+        let batch = RecordBatch::try_new(
+            arrow_schema::Schema::new(vec![arrow_schema::Field::new(
+                "text",
+                text_col.data_type().to_owned(),
+                false,
+            )])
+            .into(),
+            vec![Arc::new(text_col) as ArrayRef],
+        )
+        .unwrap();
+        let schema = batch.schema();
+        let batches = RecordBatchIterator::new(vec![batch].into_iter().map(Ok), schema);
+        let mut dataset = Dataset::write(batches, tempdir.path().to_str().unwrap(), None)
+            .await
+            .unwrap();
+        // "vehicle" doesn't appear in any document
+        let params = InvertedIndexParams::default()
+            .stem(false)
+            .synonyms(vec![vec![
+                "car".to_owned(),
+                "automobile".to_owned(),
+                "vehicle".to_owned(),
+            ]]);
+        dataset
+            .create_index(&["text"], IndexType::Inverted, None, &params, true)
+            .await
+            .unwrap();
+
+        let search = |query: &str| {
+            let query = FullTextSearchQuery::new_query(
+                MatchQuery::new(query.to_owned())
+                    .with_operator(Operator::And)
+                    .into(),
+            );
+            let dataset = dataset.clone();
+            async move {
+                let results = dataset
+                    .scan()
+                    .full_text_search(query)
+                    .unwrap()
+                    .try_into_batch()
+                    .await
+                    .unwrap();
+                results["text"]
+                    .as_string::<i32>()
+                    .iter()
+                    .map(|s| s.unwrap().to_owned())
+                    .collect::<HashSet<_>>()
+            }
+        };
+        let set = |texts: &[&str]| texts.iter().map(|s| s.to_string()).collect::<HashSet<_>>();
+
+        // any synonym of "car" is enough, but "red" is still required
+        assert_eq!(search("red car").await, set(&["red car", "red automobile"]));
+        assert_eq!(
+            search("vehicle").await,
+            set(&["red car", "red automobile", "blue car"])
+        );
+        assert_eq!(search("blue automobile").await, set(&["blue car"]));
+    }
+
     #[tokio::test]
     async fn test_fts_on_multiple_columns() {
         let tempdir = tempfile::tempdir().unwrap();
@@ -6249,6 +6928,7 @@ mod tests {
             file_major_version: 2,
             file_minor_version: 0,
             file_size_bytes: CachedFileSize::unknown(),
+            base_id: None,
         };
 
         let dataset = Dataset::commit(
@@ -6303,6 +6983,7 @@ mod tests {
             file_major_version: 2,
             file_minor_version: 0,
             file_size_bytes: CachedFileSize::unknown(),
+            base_id: None,
         };
 
         let dataset = Dataset::commit(
@@ -6401,6 +7082,7 @@ mod tests {
             file_major_version: 2,
             file_minor_version: 0,
             file_size_bytes: CachedFileSize::unknown(),
+            base_id: None,
         };
 
         let new_data_file = DataFile {
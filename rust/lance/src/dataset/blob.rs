@@ -5,17 +5,21 @@ use std::{future::Future, ops::DerefMut, sync::Arc};
 
 use arrow::array::AsArray;
 use arrow::datatypes::UInt64Type;
-use arrow_schema::DataType;
+use arrow_array::builder::LargeBinaryBuilder;
+use arrow_array::{Array, ArrayRef, RecordBatch, StringArray, StructArray, UInt64Array};
+use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema};
+use datafusion::error::DataFusionError;
 use datafusion::execution::SendableRecordBatchStream;
-use futures::StreamExt;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use futures::{StreamExt, TryStreamExt};
 use object_store::path::Path;
 use snafu::location;
 use tokio::sync::Mutex;
 
-use super::Dataset;
+use super::{Dataset, NewColumnTransform};
 use crate::io::exec::{ShareableRecordBatchStream, ShareableRecordBatchStreamAdapter};
 use lance_core::{
-    datatypes::{Schema, StorageClass},
+    datatypes::{Schema, StorageClass, BLOB_META_KEY},
     error::CloneableResult,
     utils::{
         address::RowAddress,
@@ -23,8 +27,12 @@ use lance_core::{
     },
     Error, Result,
 };
+use lance_io::object_store::{ObjectStore, ObjectStoreParams};
 use lance_io::traits::Reader;
 
+mod writer;
+pub use writer::{BlobAppender, BlobSink};
+
 /// Current state of the reader.  Held in a mutex for easy sharing
 ///
 /// The u64 is the cursor in the file that the reader is currently at
@@ -37,9 +45,12 @@ enum ReaderState {
 }
 
 /// A file-like object that represents a blob in a dataset
+///
+/// The blob is either stored in a data file of the dataset,
+/// or is a range of an external object referenced by the dataset.
 #[derive(Debug)]
 pub struct BlobFile {
-    dataset: Arc<Dataset>,
+    object_store: Arc<ObjectStore>,
     reader: Arc<Mutex<ReaderState>>,
     data_file: Path,
     position: u64,
@@ -59,10 +70,13 @@ impl BlobFile {
     ) -> Self {
         let frag_id = RowAddress::from(row_addr).fragment_id();
         let frag = dataset.get_fragment(frag_id as usize).unwrap();
-        let data_file = frag.data_file_for_field(field_id).unwrap().path.clone();
-        let data_file = dataset.data_dir().child(data_file);
+        let data_file = frag.data_file_for_field(field_id).unwrap();
+        let data_file = dataset
+            .data_file_dir(data_file)
+            .unwrap()
+            .child(data_file.path.as_str());
         Self {
-            dataset,
+            object_store: dataset.object_store.clone(),
             data_file,
             position,
             size,
@@ -70,6 +84,22 @@ impl BlobFile {
         }
     }
 
+    /// Create a BlobFile of the range of an external object
+    pub(crate) fn new_external(
+        object_store: Arc<ObjectStore>,
+        path: Path,
+        position: u64,
+        size: u64,
+    ) -> Self {
+        Self {
+            object_store,
+            data_file: path,
+            position,
+            size,
+            reader: Arc::new(Mutex::new(ReaderState::Uninitialized(0))),
+        }
+    }
+
     /// Close the blob file, releasing any associated resources
     pub async fn close(&self) -> Result<()> {
         let mut reader = self.reader.lock().await;
@@ -92,7 +122,7 @@ impl BlobFile {
     ) -> Result<T> {
         let mut reader = self.reader.lock().await;
         if let ReaderState::Uninitialized(cursor) = *reader {
-            let opened = self.dataset.object_store.open(&self.data_file).await?;
+            let opened = self.object_store.open(&self.data_file).await?;
             let opened = Arc::<dyn Reader>::from(opened);
             *reader = ReaderState::Open((cursor, opened.clone()));
         }
@@ -189,6 +219,9 @@ pub(super) async fn take_blobs(
     let projection = dataset.schema().project(&[column])?;
     let blob_field = &projection.fields[0];
     let blob_field_id = blob_field.id;
+    if blob_field.is_external_blob() {
+        return take_external_blobs(dataset, row_ids, projection).await;
+    }
     if blob_field.data_type() != DataType::LargeBinary || !projection.fields[0].is_blob() {
         return Err(Error::InvalidInput {
             location: location!(),
@@ -227,6 +260,203 @@ pub(super) async fn take_blobs(
         .collect())
 }
 
+async fn take_external_blobs(
+    dataset: &Arc<Dataset>,
+    row_ids: &[u64],
+    projection: Schema,
+) -> Result<Vec<BlobFile>> {
+    let batch = dataset.take_builder(row_ids, projection)?.execute().await?;
+    let references = ExternalBlobReferences::try_new(batch.column(0))?;
+    let blobs = (0..batch.num_rows())
+        .filter_map(|i| references.get(i))
+        .map(|reference| reference.open(dataset));
+    futures::stream::iter(blobs)
+        .buffered(dataset.object_store.io_parallelism())
+        .try_collect()
+        .await
+}
+
+/// The columns of an external blob reference array
+struct ExternalBlobReferences<'a> {
+    references: &'a StructArray,
+    uris: &'a StringArray,
+    offsets: &'a UInt64Array,
+    lengths: &'a UInt64Array,
+    etags: &'a StringArray,
+}
+
+struct ExternalBlobReference<'a> {
+    uri: &'a str,
+    offset: Option<u64>,
+    length: Option<u64>,
+    etag: Option<&'a str>,
+}
+
+impl<'a> ExternalBlobReferences<'a> {
+    fn try_new(array: &'a ArrayRef) -> Result<Self> {
+        let references = array.as_struct_opt().ok_or_else(|| {
+            Error::invalid_input(
+                format!(
+                    "external blob references must be a struct, but got {}",
+                    array.data_type()
+                ),
+                location!(),
+            )
+        })?;
+        let column = |name: &str| {
+            references.column_by_name(name).ok_or_else(|| {
+                Error::invalid_input(
+                    format!("external blob references must have the field {}", name),
+                    location!(),
+                )
+            })
+        };
+        Ok(Self {
+            references,
+            uris: column("uri")?.as_string::<i32>(),
+            offsets: column("offset")?.as_primitive::<UInt64Type>(),
+            lengths: column("length")?.as_primitive::<UInt64Type>(),
+            etags: column("etag")?.as_string::<i32>(),
+        })
+    }
+
+    // returns None if the reference is null
+    fn get(&self, i: usize) -> Option<ExternalBlobReference<'a>> {
+        if self.references.is_null(i) || self.uris.is_null(i) {
+            return None;
+        }
+        Some(ExternalBlobReference {
+            uri: self.uris.value(i),
+            offset: self.offsets.is_valid(i).then(|| self.offsets.value(i)),
+            length: self.lengths.is_valid(i).then(|| self.lengths.value(i)),
+            etag: self.etags.is_valid(i).then(|| self.etags.value(i)),
+        })
+    }
+}
+
+impl ExternalBlobReference<'_> {
+    /// Resolve the object store of the URI with the registry of the dataset session
+    /// and the store params the dataset was opened with, and check that the object
+    /// exists, is not modified and contains the range
+    async fn open(self, dataset: &Arc<Dataset>) -> Result<BlobFile> {
+        let (object_store, path) = ObjectStore::from_uri_and_params(
+            dataset.session.store_registry(),
+            self.uri,
+            &external_store_params(dataset),
+        )
+        .await?;
+        let meta = object_store.inner.head(&path).await.map_err(|e| match e {
+            object_store::Error::NotFound { .. } => {
+                Error::invalid_input(format!("external blob {} not found", self.uri), location!())
+            }
+            e => e.into(),
+        })?;
+        if let Some(etag) = self.etag {
+            if meta.e_tag.as_deref() != Some(etag) {
+                return Err(Error::invalid_input(
+                    format!(
+                        "external blob {} has been modified, expected etag {} but found {}",
+                        self.uri,
+                        etag,
+                        meta.e_tag.as_deref().unwrap_or("none")
+                    ),
+                    location!(),
+                ));
+            }
+        }
+        let object_size = meta.size as u64;
+        let offset = self.offset.unwrap_or_default();
+        let length = self
+            .length
+            .unwrap_or_else(|| object_size.saturating_sub(offset));
+        if offset
+            .checked_add(length)
+            .is_none_or(|end| end > object_size)
+        {
+            return Err(Error::invalid_input(
+                format!(
+                    "the range {}..{} of external blob {} is out of the object size {}",
+                    offset,
+                    offset as u128 + length as u128,
+                    self.uri,
+                    object_size
+                ),
+                location!(),
+            ));
+        }
+        Ok(BlobFile::new_external(object_store, path, offset, length))
+    }
+}
+
+/// The store params to access the external blobs of the dataset. The explicit object
+/// store of the dataset is not reused since it points at the dataset location.
+fn external_store_params(dataset: &Dataset) -> ObjectStoreParams {
+    match dataset.store_params.as_deref() {
+        #[allow(deprecated)]
+        Some(params) => ObjectStoreParams {
+            object_store: None,
+            ..params.clone()
+        },
+        None => ObjectStoreParams::default(),
+    }
+}
+
+/// Copy the bytes of the external blobs in `column` into a new blob column `target`,
+/// after that the dataset no longer depends on the external objects for the column
+pub(super) async fn materialize_blobs(
+    dataset: &mut Dataset,
+    column: &str,
+    target: &str,
+) -> Result<()> {
+    let field = dataset
+        .schema()
+        .field(column)
+        .ok_or_else(|| Error::invalid_input(format!("column {} not found", column), location!()))?;
+    if !field.is_external_blob() {
+        return Err(Error::invalid_input(
+            format!("the column '{}' is not an external blob column", column),
+            location!(),
+        ));
+    }
+
+    let target_field = ArrowField::new(target, DataType::LargeBinary, true).with_metadata(
+        [(BLOB_META_KEY.to_owned(), "true".to_owned())]
+            .into_iter()
+            .collect(),
+    );
+    let target_schema = Arc::new(ArrowSchema::new(vec![target_field]));
+
+    let source = Arc::new(dataset.clone());
+    let batches = dataset.scan().project(&[column])?.try_into_stream().await?;
+    let output_schema = target_schema.clone();
+    let stream = batches.then(move |batch| {
+        let source = source.clone();
+        let schema = output_schema.clone();
+        async move {
+            let batch = batch?;
+            let references = ExternalBlobReferences::try_new(batch.column(0))?;
+            let mut builder = LargeBinaryBuilder::new();
+            for i in 0..batch.num_rows() {
+                match references.get(i) {
+                    Some(reference) => {
+                        builder.append_value(reference.open(&source).await?.read().await?)
+                    }
+                    None => builder.append_null(),
+                }
+            }
+            let blobs: ArrayRef = Arc::new(builder.finish());
+            Ok(RecordBatch::try_new(schema, vec![blobs])?)
+        }
+    });
+    let stream = RecordBatchStreamAdapter::new(
+        target_schema,
+        stream.map_err(|e: Error| DataFusionError::from(e)),
+    );
+    dataset
+        .add_columns(NewColumnTransform::Stream(Box::pin(stream)), None, None)
+        .await
+}
+
 pub trait BlobStreamExt: Sized {
     /// Splits a stream into a regular portion (the first stream)
     /// and a blob portion (the second stream)
@@ -288,12 +518,17 @@ mod tests {
     use std::sync::Arc;
 
     use arrow::{array::AsArray, datatypes::UInt64Type};
-    use arrow_array::RecordBatch;
+    use arrow_array::{
+        Int32Array, RecordBatch, RecordBatchIterator, StringArray, StructArray, UInt64Array,
+    };
+    use arrow_buffer::NullBuffer;
+    use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema};
     use futures::TryStreamExt;
     use lance_arrow::DataTypeExt;
     use lance_io::stream::RecordBatchStream;
     use tempfile::{tempdir, TempDir};
 
+    use lance_core::datatypes::{external_blob_field, EXTERNAL_BLOB_FIELDS};
     use lance_core::{Error, Result};
     use lance_datagen::{array, BatchCount, RowCount};
     use lance_file::version::LanceFileVersion;
@@ -472,4 +707,130 @@ mod tests {
             assert!(batch.column(0).data_type().is_struct());
         }
     }
+
+    type ExternalBlobReference<'a> = (Option<u64>, Option<u64>, Option<&'a str>);
+
+    async fn write_external_blobs(
+        object_uri: &str,
+        uri: String,
+        references: Vec<Option<ExternalBlobReference<'_>>>,
+    ) -> Dataset {
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int32, false),
+            external_blob_field("blobs", true),
+        ]));
+        let num_rows = references.len();
+        let nulls = NullBuffer::from(references.iter().map(Option::is_some).collect::<Vec<_>>());
+        // null references still need values for the child arrays
+        let (offsets, (lengths, etags)): (Vec<_>, (Vec<_>, Vec<_>)) = references
+            .into_iter()
+            .map(|reference| {
+                let (offset, length, etag) = reference.unwrap_or_default();
+                (offset, (length, etag))
+            })
+            .unzip();
+        let blobs = StructArray::new(
+            EXTERNAL_BLOB_FIELDS.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![object_uri; num_rows])),
+                Arc::new(UInt64Array::from(offsets)),
+                Arc::new(UInt64Array::from(lengths)),
+                Arc::new(StringArray::from(etags)),
+            ],
+            Some(nulls),
+        );
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from_iter_values(0..num_rows as i32)),
+                Arc::new(blobs),
+            ],
+        )
+        .unwrap();
+        let reader = RecordBatchIterator::new(vec![Ok(batch)], schema);
+        Dataset::write(reader, &uri, None).await.unwrap()
+    }
+
+    #[tokio::test]
+    pub async fn test_external_blobs() {
+        let test_dir = tempdir().unwrap();
+        let object_path = test_dir.path().join("object.bin");
+        let bytes = (0..100u8).collect::<Vec<_>>();
+        std::fs::write(&object_path, &bytes).unwrap();
+        let object_uri = object_path.to_str().unwrap();
+
+        let dataset_uri = format!("{}/dataset", test_dir.path().to_str().unwrap());
+        let mut dataset = write_external_blobs(
+            object_uri,
+            dataset_uri,
+            vec![
+                Some((None, None, None)),
+                Some((Some(10), Some(5), None)),
+                None,
+            ],
+        )
+        .await;
+        let blobs = Arc::new(dataset.clone())
+            .take_blobs_by_indices(&[0, 1, 2], "blobs")
+            .await
+            .unwrap();
+        assert_eq!(blobs.len(), 2);
+        assert_eq!(blobs[0].size(), 100);
+        assert_eq!(blobs[0].read().await.unwrap().as_ref(), bytes.as_slice());
+        assert_eq!(blobs[1].size(), 5);
+        assert_eq!(blobs[1].read().await.unwrap().as_ref(), &bytes[10..15]);
+
+        // the referenced range must be in the object, even if its end overflows
+        let out_of_range = Arc::new(
+            write_external_blobs(
+                object_uri,
+                format!("{}/out_of_range", test_dir.path().to_str().unwrap()),
+                vec![
+                    Some((Some(90), Some(20), None)),
+                    Some((Some(10), Some(u64::MAX), None)),
+                ],
+            )
+            .await,
+        );
+        for index in 0..2 {
+            let err = out_of_range
+                .take_blobs_by_indices(&[index], "blobs")
+                .await
+                .unwrap_err();
+            assert!(
+                err.to_string().contains("out of the object size"),
+                "{}",
+                err
+            );
+        }
+
+        // the object must not be modified after it's referenced
+        let modified = write_external_blobs(
+            object_uri,
+            format!("{}/modified", test_dir.path().to_str().unwrap()),
+            vec![Some((None, None, Some("stale-etag")))],
+        )
+        .await;
+        let err = Arc::new(modified)
+            .take_blobs_by_indices(&[0], "blobs")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("has been modified"), "{}", err);
+
+        dataset.materialize_blobs("blobs", "content").await.unwrap();
+        assert!(dataset.schema().field("content").unwrap().is_blob());
+        let content = Arc::new(dataset.clone())
+            .take_blobs_by_indices(&[0, 1, 2], "content")
+            .await
+            .unwrap();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[0].read().await.unwrap().as_ref(), bytes.as_slice());
+        assert_eq!(content[1].read().await.unwrap().as_ref(), &bytes[10..15]);
+
+        let err = dataset
+            .materialize_blobs("id", "id_content")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not an external blob column"));
+    }
 }
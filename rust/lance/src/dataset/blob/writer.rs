@@ -0,0 +1,436 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use arrow_array::{RecordBatch, StructArray, UInt64Array};
+use arrow_buffer::NullBuffer;
+use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema, SchemaRef};
+use arrow_select::concat::concat_batches;
+use lance_arrow::RecordBatchExt;
+use lance_core::datatypes::{Field, StorageClass, BLOB_DESC_FIELDS};
+use lance_core::{Error, Result};
+use lance_file::v2::writer::BlobValueWriter;
+use lance_file::version::LanceFileVersion;
+use lance_table::format::Fragment;
+use snafu::location;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::dataset::transaction::{Operation, Transaction};
+use crate::dataset::write::{open_v2_writer, GenericWriter, V2WriterAdapter};
+use crate::dataset::{CommitBuilder, WriteParams};
+use crate::Dataset;
+
+// the rows are buffered until this many are finished, only the small values
+// are buffered since the blob values are streamed to the file
+const FLUSH_ROWS: usize = 1024;
+
+/// Appends rows to a dataset, streaming the values of the blob columns
+/// into the data files instead of holding them in memory.
+///
+/// Each row is written by streaming its blob values with [`Self::start_blob`] or
+/// [`Self::write_blob`], then finishing it with the values of the other columns
+/// in [`Self::finish_row`]. The rows are committed as one append by [`Self::commit`].
+///
+/// ```ignore
+/// let mut appender = BlobAppender::try_new(dataset).await?;
+/// appender.write_blob("video", &mut video_file).await?;
+/// appender.finish_row(&metadata).await?;
+/// let dataset = appender.commit().await?;
+/// ```
+pub struct BlobAppender {
+    dataset: Arc<Dataset>,
+    storage_version: LanceFileVersion,
+    params: WriteParams,
+    // the schema of the batches written to the files,
+    // the blob columns hold the descriptions of the values
+    file_schema: SchemaRef,
+    blob_fields: Vec<ArrowField>,
+    // the non-blob columns expected by `finish_row`
+    values_schema: SchemaRef,
+    // the descriptions of the blob values of the current row
+    current: Vec<Option<(u64, u64)>>,
+    pending_values: Vec<RecordBatch>,
+    // the descriptions of the pending rows, per blob column
+    pending_descriptions: Vec<Vec<Option<(u64, u64)>>>,
+    writer: Option<V2WriterAdapter>,
+    rows_in_file: usize,
+    fragments: Vec<Fragment>,
+}
+
+impl BlobAppender {
+    pub async fn try_new(dataset: Arc<Dataset>) -> Result<Self> {
+        let storage_version = dataset.manifest.data_storage_format.lance_file_version()?;
+        // only the 2.0 encodings store blob values out of line
+        if storage_version.resolve() != LanceFileVersion::V2_0 {
+            return Err(Error::NotSupported {
+                source: format!(
+                    "streaming blob values requires the 2.0 file format, but the dataset uses {}",
+                    storage_version
+                )
+                .into(),
+                location: location!(),
+            });
+        }
+        if !dataset.partition_columns()?.is_empty() {
+            return Err(Error::NotSupported {
+                source: "streaming blob values is not supported on partitioned datasets".into(),
+                location: location!(),
+            });
+        }
+        let schema = dataset.schema();
+        if schema
+            .fields
+            .iter()
+            .any(|field| field.storage_class() == StorageClass::Blob)
+        {
+            return Err(Error::NotSupported {
+                source: "streaming blob values is not supported with the blob storage class".into(),
+                location: location!(),
+            });
+        }
+        let file_fields = schema
+            .fields
+            .iter()
+            .map(|field| {
+                if Self::is_blob_field(field) {
+                    ArrowField::new(
+                        &field.name,
+                        DataType::Struct(BLOB_DESC_FIELDS.clone()),
+                        field.nullable,
+                    )
+                } else {
+                    ArrowField::from(field)
+                }
+            })
+            .collect::<Vec<_>>();
+        let values_fields = schema
+            .fields
+            .iter()
+            .filter(|field| !Self::is_blob_field(field))
+            .map(ArrowField::from)
+            .collect::<Vec<_>>();
+        let blob_fields = schema
+            .fields
+            .iter()
+            .filter(|field| Self::is_blob_field(field))
+            .map(ArrowField::from)
+            .collect::<Vec<_>>();
+        if blob_fields.is_empty() {
+            return Err(Error::invalid_input(
+                "the dataset has no blob columns",
+                location!(),
+            ));
+        }
+        let num_blob_columns = blob_fields.len();
+
+        Ok(Self {
+            storage_version,
+            params: WriteParams::default(),
+            file_schema: Arc::new(ArrowSchema::new(file_fields)),
+            blob_fields,
+            values_schema: Arc::new(ArrowSchema::new(values_fields)),
+            current: vec![None; num_blob_columns],
+            pending_values: Vec::new(),
+            pending_descriptions: vec![Vec::new(); num_blob_columns],
+            writer: None,
+            rows_in_file: 0,
+            fragments: Vec::new(),
+            dataset,
+        })
+    }
+
+    fn is_blob_field(field: &Field) -> bool {
+        field.is_blob() && field.data_type() == DataType::LargeBinary
+    }
+
+    /// The schema of the values passed to [`Self::finish_row`], all the columns
+    /// of the dataset except the blob columns
+    pub fn values_schema(&self) -> &SchemaRef {
+        &self.values_schema
+    }
+
+    fn blob_index(&self, column: &str) -> Result<usize> {
+        self.blob_fields
+            .iter()
+            .position(|field| field.name() == column)
+            .ok_or_else(|| {
+                Error::invalid_input(
+                    format!("the column '{}' is not a blob column", column),
+                    location!(),
+                )
+            })
+    }
+
+    async fn writer(&mut self) -> Result<&mut V2WriterAdapter> {
+        if self.writer.is_none() {
+            // the files have the dataset schema, the encoder of a blob column
+            // accepts the descriptions of the values already in the file
+            self.writer = Some(
+                open_v2_writer(
+                    &self.dataset.object_store,
+                    self.dataset.schema(),
+                    &self.dataset.base,
+                    self.storage_version,
+                )
+                .await?,
+            );
+        }
+        Ok(self.writer.as_mut().unwrap())
+    }
+
+    /// Starts streaming the value of the blob `column` of the current row
+    ///
+    /// The value is only recorded once the returned [`BlobSink`] is finished,
+    /// writing the column again replaces the value.
+    pub async fn start_blob(&mut self, column: &str) -> Result<BlobSink<'_>> {
+        let index = self.blob_index(column)?;
+        self.writer().await?;
+        let value = self.writer.as_mut().unwrap().writer.start_blob().await?;
+        Ok(BlobSink {
+            value,
+            description: &mut self.current[index],
+        })
+    }
+
+    /// Streams the bytes of `reader` as the value of the blob `column` of the current row
+    pub async fn write_blob(
+        &mut self,
+        column: &str,
+        reader: &mut (impl AsyncRead + Unpin),
+    ) -> Result<()> {
+        let mut sink = self.start_blob(column).await?;
+        tokio::io::copy(reader, &mut sink).await?;
+        sink.finish().await
+    }
+
+    /// Finishes the current row with the `values` of the other columns
+    ///
+    /// `values` must have a single row matching [`Self::values_schema`].
+    /// The blob columns that were not written are null.
+    pub async fn finish_row(&mut self, values: &RecordBatch) -> Result<()> {
+        if values.num_rows() != 1 {
+            return Err(Error::invalid_input(
+                format!("a row must have 1 value, but got {}", values.num_rows()),
+                location!(),
+            ));
+        }
+        let values = values.project_by_schema(&self.values_schema)?;
+        for (field, description) in self.blob_fields.iter().zip(self.current.iter()) {
+            if description.is_none() && !field.is_nullable() {
+                return Err(Error::invalid_input(
+                    format!("the blob column '{}' is not nullable", field.name()),
+                    location!(),
+                ));
+            }
+        }
+        // make sure the row has a file even if all of its blobs are null
+        self.writer().await?;
+
+        self.pending_values.push(values);
+        for (pending, description) in self
+            .pending_descriptions
+            .iter_mut()
+            .zip(self.current.iter_mut())
+        {
+            pending.push(description.take());
+        }
+        self.rows_in_file += 1;
+
+        if self.pending_values.len() >= FLUSH_ROWS {
+            self.flush().await?;
+        }
+        let writer = self.writer.as_mut().unwrap();
+        if self.rows_in_file >= self.params.max_rows_per_file
+            || writer.tell().await? >= self.params.max_bytes_per_file as u64
+        {
+            self.finish_file().await?;
+        }
+        Ok(())
+    }
+
+    // write the pending rows to the current file
+    async fn flush(&mut self) -> Result<()> {
+        if self.pending_values.is_empty() {
+            return Ok(());
+        }
+        let values = concat_batches(&self.values_schema, &self.pending_values)?;
+        self.pending_values.clear();
+        let mut descriptions = self
+            .pending_descriptions
+            .iter_mut()
+            .map(std::mem::take)
+            .map(|descriptions| {
+                let nulls =
+                    NullBuffer::from(descriptions.iter().map(Option::is_some).collect::<Vec<_>>());
+                let (positions, sizes): (Vec<_>, Vec<_>) = descriptions
+                    .into_iter()
+                    .map(|description| description.unwrap_or((1, 0)))
+                    .unzip();
+                Arc::new(StructArray::new(
+                    BLOB_DESC_FIELDS.clone(),
+                    vec![
+                        Arc::new(UInt64Array::from(positions)),
+                        Arc::new(UInt64Array::from(sizes)),
+                    ],
+                    Some(nulls),
+                ))
+            })
+            .collect::<Vec<_>>()
+            .into_iter();
+        let columns = self
+            .file_schema
+            .fields()
+            .iter()
+            .map(|field| match values.column_by_name(field.name()) {
+                Some(column) => column.clone(),
+                None => descriptions.next().unwrap(),
+            })
+            .collect::<Vec<_>>();
+        let batch = RecordBatch::try_new(self.file_schema.clone(), columns)?;
+        self.writer.as_mut().unwrap().write(&[batch]).await
+    }
+
+    async fn finish_file(&mut self) -> Result<()> {
+        self.flush().await?;
+        if let Some(mut writer) = self.writer.take() {
+            let (num_rows, data_file) = writer.finish().await?;
+            let mut fragment = Fragment::new(0);
+            fragment.physical_rows = Some(num_rows as usize);
+            fragment.files.push(data_file);
+            self.fragments.push(fragment);
+            self.rows_in_file = 0;
+        }
+        Ok(())
+    }
+
+    /// Commits the finished rows as an append to the dataset
+    ///
+    /// The blob values of an unfinished row are discarded.
+    pub async fn commit(mut self) -> Result<Dataset> {
+        if self.rows_in_file > 0 {
+            self.finish_file().await?;
+        }
+        if self.fragments.is_empty() {
+            return Ok(self.dataset.as_ref().clone());
+        }
+        let transaction = Transaction::new(
+            self.dataset.manifest.version,
+            Operation::Append {
+                fragments: self.fragments,
+            },
+            None,
+            None,
+        );
+        CommitBuilder::new(self.dataset).execute(transaction).await
+    }
+}
+
+/// Streams the value of a blob column into the data file, see [`BlobAppender::start_blob`]
+pub struct BlobSink<'a> {
+    value: BlobValueWriter<'a>,
+    description: &'a mut Option<(u64, u64)>,
+}
+
+impl BlobSink<'_> {
+    /// Finishes the value, the written bytes become the value of the column
+    pub async fn finish(self) -> Result<()> {
+        *self.description = Some(self.value.finish().await?);
+        Ok(())
+    }
+}
+
+impl AsyncWrite for BlobSink<'_> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.value).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.value).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.value).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow_array::{Int32Array, LargeBinaryArray, RecordBatchIterator};
+    use lance_core::datatypes::BLOB_META_KEY;
+    use tempfile::tempdir;
+    use tokio::io::AsyncWriteExt;
+
+    #[tokio::test]
+    async fn test_blob_appender() {
+        let test_dir = tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int32, false),
+            ArrowField::new("video", DataType::LargeBinary, true).with_metadata(
+                [(BLOB_META_KEY.to_owned(), "true".to_owned())]
+                    .into_iter()
+                    .collect(),
+            ),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![0])),
+                Arc::new(LargeBinaryArray::from(vec![Some(b"small".as_slice())])),
+            ],
+        )
+        .unwrap();
+        let reader = RecordBatchIterator::new(vec![Ok(batch)], schema.clone());
+        let dataset = Arc::new(Dataset::write(reader, test_uri, None).await.unwrap());
+
+        let mut appender = BlobAppender::try_new(dataset).await.unwrap();
+        let values_schema = appender.values_schema().clone();
+        let row = |id: i32| {
+            RecordBatch::try_new(
+                values_schema.clone(),
+                vec![Arc::new(Int32Array::from(vec![id]))],
+            )
+            .unwrap()
+        };
+
+        // streamed in chunks
+        let chunks = (0..4u8).map(|i| vec![i; 1000]).collect::<Vec<_>>();
+        let mut sink = appender.start_blob("video").await.unwrap();
+        for chunk in &chunks {
+            sink.write_all(chunk).await.unwrap();
+        }
+        sink.finish().await.unwrap();
+        appender.finish_row(&row(1)).await.unwrap();
+        // null
+        appender.finish_row(&row(2)).await.unwrap();
+        // from a reader
+        appender
+            .write_blob("video", &mut b"from reader".as_slice())
+            .await
+            .unwrap();
+        appender.finish_row(&row(3)).await.unwrap();
+
+        assert!(appender.start_blob("id").await.is_err());
+        assert!(appender.finish_row(&row(4).slice(0, 0)).await.is_err());
+
+        let dataset = Arc::new(appender.commit().await.unwrap());
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 4);
+        let blobs = dataset
+            .take_blobs_by_indices(&[0, 1, 2, 3], "video")
+            .await
+            .unwrap();
+        assert_eq!(blobs.len(), 3);
+        assert_eq!(blobs[0].read().await.unwrap().as_ref(), b"small");
+        assert_eq!(blobs[1].read().await.unwrap().as_ref(), chunks.concat());
+        assert_eq!(blobs[2].read().await.unwrap().as_ref(), b"from reader");
+    }
+}
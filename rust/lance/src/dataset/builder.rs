@@ -305,6 +305,7 @@ impl DatasetBuilder {
         let manifest = self.manifest.take();
 
         let file_reader_options = self.file_reader_options.clone();
+        let store_params = Box::new(self.options.clone());
         let (object_store, base_path, commit_handler) = self.build_object_store().await?;
 
         if let Some(r) = cloned_ref {
@@ -366,6 +367,7 @@ impl DatasetBuilder {
             session,
             commit_handler,
             file_reader_options,
+            Some(store_params),
         )
     }
 }
@@ -225,8 +225,10 @@ impl<'a> CleanupTask<'a> {
             &mut inspection.verified_files
         };
 
+        // Files with a base id live in another dataset (e.g. the source of a
+        // shallow clone) and are never cleaned up from this one.
         for fragment in manifest.fragments.iter() {
-            for file in fragment.files.iter() {
+            for file in fragment.files.iter().filter(|file| file.base_id.is_none()) {
                 let full_data_path = self.dataset.data_dir().child(file.path.as_str());
                 let relative_data_path = remove_prefix(&full_data_path, &self.dataset.base);
                 referenced_files.data_paths.insert(relative_data_path);
@@ -234,6 +236,7 @@ impl<'a> CleanupTask<'a> {
             let delpath = fragment
                 .deletion_file
                 .as_ref()
+                .filter(|delfile| delfile.base_id.is_none())
                 .map(|delfile| deletion_file_path(&self.dataset.base, fragment.id, delfile));
             if let Some(delpath) = delpath {
                 let relative_path = remove_prefix(&delpath, &self.dataset.base);
@@ -246,7 +249,7 @@ impl<'a> CleanupTask<'a> {
                 .insert(Path::parse("_transactions")?.child(relative_tx_path.as_str()));
         }
 
-        for index in indexes {
+        for index in indexes.iter().filter(|index| index.base_id.is_none()) {
             let uuid_str = index.uuid.to_string();
             referenced_files.index_uuids.insert(uuid_str);
         }
@@ -0,0 +1,712 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Export a dataset version to Parquet or Arrow IPC files.
+//!
+//! Each fragment is scanned (with an optional projection and filter) and
+//! written to its own files, several fragments at a time. Lance-specific
+//! types are converted so other tools can read the files:
+//!
+//! * bfloat16 values (`lance.bfloat16` extension type) are widened to float32.
+//! * Blob columns, which a scan returns as position / size descriptions, are
+//!   exported as the blob bytes themselves (`LargeBinary`).
+//!
+//! Other field metadata is kept as is.
+
+use std::sync::Arc;
+
+use arrow_array::builder::LargeBinaryBuilder;
+use arrow_array::cast::AsArray;
+use arrow_array::types::UInt64Type;
+use arrow_array::{
+    Array, ArrayRef, FixedSizeListArray, Float32Array, LargeListArray, ListArray, RecordBatch,
+    StructArray,
+};
+use arrow_ipc::writer::FileWriter as IpcFileWriter;
+use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema, SchemaRef};
+use datafusion::parquet::arrow::AsyncArrowWriter;
+use datafusion::parquet::basic::Compression;
+use datafusion::parquet::file::properties::WriterProperties;
+use futures::{StreamExt, TryStreamExt};
+use half::bf16;
+use lance_arrow::bfloat16::{is_bfloat16_field, ARROW_EXT_META_KEY, ARROW_EXT_NAME_KEY};
+use lance_core::datatypes::BLOB_META_KEY;
+use lance_core::utils::tokio::get_num_compute_intensive_cpus;
+use lance_core::ROW_ADDR;
+use lance_io::object_store::{ObjectStore, ObjectStoreParams};
+use lance_io::object_writer::ObjectWriter;
+use object_store::path::Path;
+use snafu::location;
+use tokio::io::AsyncWriteExt;
+
+use super::blob::BlobFile;
+use super::fragment::FileFragment;
+use crate::{Dataset, Error, Result};
+
+/// The format of exported files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Parquet,
+    /// The Arrow IPC file format (a.k.a. Feather v2)
+    ArrowIpc,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Parquet => "parquet",
+            Self::ArrowIpc => "arrow",
+        }
+    }
+}
+
+/// Parameters for [`Dataset::export`].
+#[derive(Debug, Clone)]
+pub struct ExportParams {
+    pub format: ExportFormat,
+    /// The columns to export. Defaults to all columns.
+    pub columns: Option<Vec<String>>,
+    /// Only export rows matching this SQL filter.
+    pub filter: Option<String>,
+    /// Max number of rows per file. Defaults to 1 million.
+    pub max_rows_per_file: usize,
+    /// Approximate max number of bytes per file, estimated from the in-memory
+    /// size of the data. Defaults to 1 GiB.
+    pub max_bytes_per_file: usize,
+    /// Max number of rows per Parquet row group. Defaults to 128Ki.
+    ///
+    /// Arrow IPC files are written with the batches produced by the scan.
+    pub max_rows_per_group: usize,
+    /// How many fragments to export at the same time. Defaults to the number
+    /// of compute-intensive CPUs.
+    pub num_threads: Option<usize>,
+    /// Parameters for the object store the files are written to.
+    pub store_params: Option<ObjectStoreParams>,
+}
+
+impl Default for ExportParams {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::default(),
+            columns: None,
+            filter: None,
+            max_rows_per_file: 1024 * 1024,
+            max_bytes_per_file: 1024 * 1024 * 1024,
+            max_rows_per_group: 128 * 1024,
+            num_threads: None,
+            store_params: None,
+        }
+    }
+}
+
+/// A file written by [`Dataset::export`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedFile {
+    /// The name of the file within the export directory.
+    pub file_name: String,
+    /// The fragment the rows were read from.
+    pub fragment_id: u64,
+    pub num_rows: usize,
+}
+
+pub(super) async fn export(
+    dataset: &Dataset,
+    dest: &str,
+    params: ExportParams,
+) -> Result<Vec<ExportedFile>> {
+    if params.max_rows_per_file == 0 || params.max_rows_per_group == 0 {
+        return Err(Error::invalid_input(
+            "max_rows_per_file and max_rows_per_group must be greater than 0",
+            location!(),
+        ));
+    }
+
+    let (object_store, dest_dir) = ObjectStore::from_uri_and_params(
+        dataset.session.store_registry(),
+        dest,
+        &params.store_params.clone().unwrap_or_default(),
+    )
+    .await?;
+
+    let projection = match &params.columns {
+        Some(columns) => dataset.schema().project(columns)?,
+        None => dataset.schema().clone(),
+    };
+    let output_schema = Arc::new(portable_schema(&ArrowSchema::from(&projection)));
+    let blob_field_ids = projection
+        .fields
+        .iter()
+        .map(|field| field.is_blob().then_some(field.id as u32))
+        .collect::<Vec<_>>();
+
+    let dataset = Arc::new(dataset.clone());
+    let num_threads = params
+        .num_threads
+        .unwrap_or_else(get_num_compute_intensive_cpus);
+    let files = futures::stream::iter(dataset.get_fragments())
+        .map(|fragment| {
+            let exporter = FragmentExporter {
+                dataset: dataset.clone(),
+                object_store: object_store.clone(),
+                dest_dir: dest_dir.clone(),
+                params: &params,
+                output_schema: output_schema.clone(),
+                blob_field_ids: &blob_field_ids,
+            };
+            async move { exporter.export(fragment).await }
+        })
+        .buffered(num_threads)
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(files.into_iter().flatten().collect())
+}
+
+struct FragmentExporter<'a> {
+    dataset: Arc<Dataset>,
+    object_store: Arc<ObjectStore>,
+    dest_dir: Path,
+    params: &'a ExportParams,
+    output_schema: SchemaRef,
+    /// For each exported column, the field id if it is a blob column.
+    blob_field_ids: &'a [Option<u32>],
+}
+
+impl FragmentExporter<'_> {
+    async fn export(&self, fragment: FileFragment) -> Result<Vec<ExportedFile>> {
+        let fragment_id = fragment.id() as u64;
+        let has_blobs = self.blob_field_ids.iter().any(Option::is_some);
+
+        let mut scanner = self.dataset.scan();
+        scanner.with_fragments(vec![fragment.metadata().clone()]);
+        if let Some(columns) = &self.params.columns {
+            scanner.project(columns)?;
+        }
+        if let Some(filter) = &self.params.filter {
+            scanner.filter(filter)?;
+        }
+        if has_blobs {
+            scanner.with_row_address();
+        }
+        let mut stream = scanner.try_into_stream().await?;
+
+        let mut files = Vec::new();
+        let mut writer: Option<ExportWriter> = None;
+        while let Some(batch) = stream.try_next().await? {
+            let mut batch = self.to_portable(batch).await?;
+            while batch.num_rows() > 0 {
+                if writer.is_none() {
+                    let file_name = format!(
+                        "{}-{}.{}",
+                        fragment_id,
+                        files.len(),
+                        self.params.format.extension()
+                    );
+                    writer = Some(
+                        ExportWriter::try_new(
+                            &self.object_store,
+                            &self.dest_dir.child(file_name.as_str()),
+                            self.params,
+                            self.output_schema.clone(),
+                        )
+                        .await?,
+                    );
+                    files.push(ExportedFile {
+                        file_name,
+                        fragment_id,
+                        num_rows: 0,
+                    });
+                }
+                let current = writer.as_mut().unwrap();
+
+                let remaining = self.params.max_rows_per_file - current.num_rows;
+                let to_write = batch.slice(0, remaining.min(batch.num_rows()));
+                batch = batch.slice(to_write.num_rows(), batch.num_rows() - to_write.num_rows());
+                current.write(&to_write).await?;
+                files.last_mut().unwrap().num_rows += to_write.num_rows();
+
+                if current.num_rows >= self.params.max_rows_per_file
+                    || current.num_bytes >= self.params.max_bytes_per_file
+                {
+                    writer.take().unwrap().finish().await?;
+                }
+            }
+        }
+        if let Some(writer) = writer {
+            writer.finish().await?;
+        }
+        Ok(files)
+    }
+
+    /// Convert a scanned batch into the exported schema, reading blob bytes
+    /// and converting extension types.
+    async fn to_portable(&self, batch: RecordBatch) -> Result<RecordBatch> {
+        let row_addrs = batch
+            .column_by_name(ROW_ADDR)
+            .map(|row_addrs| row_addrs.as_primitive::<UInt64Type>().clone());
+        let mut columns = Vec::with_capacity(self.output_schema.fields().len());
+        for (field, blob_field_id) in self.output_schema.fields().iter().zip(self.blob_field_ids) {
+            let column = batch
+                .column_by_name(field.name())
+                .ok_or_else(|| Error::Internal {
+                    message: format!("Scan did not return column {}", field.name()),
+                    location: location!(),
+                })?;
+            let column = match blob_field_id {
+                Some(field_id) => {
+                    self.read_blobs(column, *field_id, row_addrs.as_ref().unwrap())
+                        .await?
+                }
+                None => portable_array(column, field.data_type())?,
+            };
+            columns.push(column);
+        }
+        Ok(RecordBatch::try_new(self.output_schema.clone(), columns)?)
+    }
+
+    async fn read_blobs(
+        &self,
+        descriptions: &ArrayRef,
+        field_id: u32,
+        row_addrs: &arrow_array::UInt64Array,
+    ) -> Result<ArrayRef> {
+        let descriptions = descriptions.as_struct();
+        let positions = descriptions.column(0).as_primitive::<UInt64Type>();
+        let sizes = descriptions.column(1).as_primitive::<UInt64Type>();
+        let mut builder = LargeBinaryBuilder::new();
+        for i in 0..descriptions.len() {
+            // Null blobs are described as (position 1, size 0)
+            if descriptions.is_null(i)
+                || positions.is_null(i)
+                || sizes.is_null(i)
+                || (positions.value(i) == 1 && sizes.value(i) == 0)
+            {
+                builder.append_null();
+                continue;
+            }
+            let blob = BlobFile::new(
+                self.dataset.clone(),
+                field_id,
+                row_addrs.value(i),
+                positions.value(i),
+                sizes.value(i),
+            );
+            builder.append_value(blob.read().await?);
+        }
+        Ok(Arc::new(builder.finish()))
+    }
+}
+
+enum ExportFileWriter {
+    Parquet(AsyncArrowWriter<ObjectWriter>),
+    Ipc {
+        // The IPC writer is synchronous, so it writes into a buffer that is
+        // flushed to the object store after every batch.
+        writer: IpcFileWriter<Vec<u8>>,
+        output: ObjectWriter,
+    },
+}
+
+struct ExportWriter {
+    inner: ExportFileWriter,
+    num_rows: usize,
+    num_bytes: usize,
+}
+
+impl ExportWriter {
+    async fn try_new(
+        object_store: &ObjectStore,
+        path: &Path,
+        params: &ExportParams,
+        schema: SchemaRef,
+    ) -> Result<Self> {
+        let output = object_store.create(path).await?;
+        let inner = match params.format {
+            ExportFormat::Parquet => {
+                let props = WriterProperties::builder()
+                    .set_max_row_group_size(params.max_rows_per_group)
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                ExportFileWriter::Parquet(AsyncArrowWriter::try_new(output, schema, Some(props))?)
+            }
+            ExportFormat::ArrowIpc => ExportFileWriter::Ipc {
+                writer: IpcFileWriter::try_new(Vec::new(), &schema)?,
+                output,
+            },
+        };
+        Ok(Self {
+            inner,
+            num_rows: 0,
+            num_bytes: 0,
+        })
+    }
+
+    async fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        match &mut self.inner {
+            ExportFileWriter::Parquet(writer) => writer.write(batch).await?,
+            ExportFileWriter::Ipc { writer, output } => {
+                writer.write(batch)?;
+                let buffer = std::mem::take(writer.get_mut());
+                output.write_all(&buffer).await?;
+            }
+        }
+        self.num_rows += batch.num_rows();
+        self.num_bytes += batch.get_array_memory_size();
+        Ok(())
+    }
+
+    async fn finish(self) -> Result<()> {
+        match self.inner {
+            ExportFileWriter::Parquet(writer) => {
+                writer.close().await?;
+            }
+            ExportFileWriter::Ipc {
+                mut writer,
+                mut output,
+            } => {
+                writer.finish()?;
+                let buffer = std::mem::take(writer.get_mut());
+                output.write_all(&buffer).await?;
+                output.shutdown().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The schema of the exported files.
+fn portable_schema(schema: &ArrowSchema) -> ArrowSchema {
+    ArrowSchema::new_with_metadata(
+        schema
+            .fields()
+            .iter()
+            .map(|field| portable_field(field))
+            .collect::<Vec<_>>(),
+        schema.metadata().clone(),
+    )
+}
+
+fn portable_field(field: &ArrowField) -> ArrowField {
+    let mut metadata = field.metadata().clone();
+    let data_type = if is_bfloat16_field(field) {
+        metadata.remove(ARROW_EXT_NAME_KEY);
+        metadata.remove(ARROW_EXT_META_KEY);
+        DataType::Float32
+    } else if metadata.remove(BLOB_META_KEY).is_some() {
+        DataType::LargeBinary
+    } else {
+        match field.data_type() {
+            DataType::List(item) => DataType::List(Arc::new(portable_field(item))),
+            DataType::LargeList(item) => DataType::LargeList(Arc::new(portable_field(item))),
+            DataType::FixedSizeList(item, size) => {
+                DataType::FixedSizeList(Arc::new(portable_field(item)), *size)
+            }
+            DataType::Struct(fields) => {
+                DataType::Struct(fields.iter().map(|f| portable_field(f)).collect())
+            }
+            other => other.clone(),
+        }
+    };
+    ArrowField::new(field.name(), data_type, field.is_nullable()).with_metadata(metadata)
+}
+
+/// Convert an array to its portable type, as given by [`portable_field`].
+fn portable_array(array: &ArrayRef, data_type: &DataType) -> Result<ArrayRef> {
+    if array.data_type() == data_type {
+        return Ok(array.clone());
+    }
+    let converted: ArrayRef = match (array.data_type(), data_type) {
+        (DataType::FixedSizeBinary(2), DataType::Float32) => {
+            let values = array.as_fixed_size_binary();
+            Arc::new(Float32Array::from_iter((0..values.len()).map(|i| {
+                values.is_valid(i).then(|| {
+                    let bytes = values.value(i);
+                    bf16::from_bits(u16::from_le_bytes([bytes[0], bytes[1]])).to_f32()
+                })
+            })))
+        }
+        (DataType::List(_), DataType::List(item)) => {
+            let list = array.as_list::<i32>();
+            Arc::new(ListArray::try_new(
+                item.clone(),
+                list.offsets().clone(),
+                portable_array(list.values(), item.data_type())?,
+                list.nulls().cloned(),
+            )?)
+        }
+        (DataType::LargeList(_), DataType::LargeList(item)) => {
+            let list = array.as_list::<i64>();
+            Arc::new(LargeListArray::try_new(
+                item.clone(),
+                list.offsets().clone(),
+                portable_array(list.values(), item.data_type())?,
+                list.nulls().cloned(),
+            )?)
+        }
+        (DataType::FixedSizeList(_, _), DataType::FixedSizeList(item, size)) => {
+            let list = array.as_fixed_size_list();
+            Arc::new(FixedSizeListArray::try_new(
+                item.clone(),
+                *size,
+                portable_array(list.values(), item.data_type())?,
+                list.nulls().cloned(),
+            )?)
+        }
+        (DataType::Struct(_), DataType::Struct(fields)) => {
+            let struct_array = array.as_struct();
+            let columns = struct_array
+                .columns()
+                .iter()
+                .zip(fields.iter())
+                .map(|(column, field)| portable_array(column, field.data_type()))
+                .collect::<Result<Vec<_>>>()?;
+            Arc::new(StructArray::try_new(
+                fields.clone(),
+                columns,
+                struct_array.nulls().cloned(),
+            )?)
+        }
+        (from, to) => {
+            return Err(Error::Internal {
+                message: format!("Cannot export {} as {}", from, to),
+                location: location!(),
+            })
+        }
+    };
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use arrow_array::types::{Float32Type, Int64Type};
+    use arrow_array::{FixedSizeBinaryArray, Int64Array, LargeBinaryArray, RecordBatchIterator};
+    use datafusion::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use lance_arrow::bfloat16::BFLOAT16_EXT_NAME;
+    use lance_file::version::LanceFileVersion;
+
+    use crate::dataset::WriteParams;
+
+    fn read_parquet(dir: &std::path::Path, files: &[ExportedFile]) -> Vec<RecordBatch> {
+        files
+            .iter()
+            .flat_map(|file| {
+                let file = std::fs::File::open(dir.join(&file.file_name)).unwrap();
+                ParquetRecordBatchReaderBuilder::try_new(file)
+                    .unwrap()
+                    .build()
+                    .unwrap()
+                    .map(|batch| batch.unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn read_ipc(dir: &std::path::Path, files: &[ExportedFile]) -> Vec<RecordBatch> {
+        files
+            .iter()
+            .flat_map(|file| {
+                let file = std::fs::File::open(dir.join(&file.file_name)).unwrap();
+                arrow_ipc::reader::FileReader::try_new(file, None)
+                    .unwrap()
+                    .map(|batch| batch.unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_export_parquet() {
+        let item = Arc::new(
+            ArrowField::new("item", DataType::FixedSizeBinary(2), true).with_metadata(
+                [
+                    (ARROW_EXT_NAME_KEY.into(), BFLOAT16_EXT_NAME.into()),
+                    (ARROW_EXT_META_KEY.into(), "".into()),
+                ]
+                .into(),
+            ),
+        );
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int64, false)
+                .with_metadata(HashMap::from([("unit".to_string(), "ms".to_string())])),
+            ArrowField::new("vec", DataType::FixedSizeList(item.clone(), 2), true),
+            ArrowField::new("skipped", DataType::Int64, true),
+        ]));
+        let ids = Int64Array::from_iter_values(0..100);
+        let bf16_values = FixedSizeBinaryArray::try_from_iter(
+            (0..200).map(|i| bf16::from_f32(i as f32).to_le_bytes()),
+        )
+        .unwrap();
+        let vectors = FixedSizeListArray::new(item, 2, Arc::new(bf16_values), None);
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(ids.clone()), Arc::new(vectors), Arc::new(ids)],
+        )
+        .unwrap();
+
+        let test_dir = tempfile::tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+        let dataset = Dataset::write(
+            RecordBatchIterator::new(vec![Ok(batch)], schema.clone()),
+            test_uri,
+            Some(WriteParams {
+                max_rows_per_file: 40,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(dataset.get_fragments().len(), 3);
+
+        let export_dir = tempfile::tempdir().unwrap();
+        let files = dataset
+            .export(
+                export_dir.path().to_str().unwrap(),
+                Some(ExportParams {
+                    columns: Some(vec!["id".into(), "vec".into()]),
+                    filter: Some("id >= 10".into()),
+                    max_rows_per_file: 25,
+                    max_rows_per_group: 10,
+                    ..Default::default()
+                }),
+            )
+            .await
+            .unwrap();
+        // Fragments have 30, 40 and 20 matching rows
+        let file_rows = files.iter().map(|f| f.num_rows).collect::<Vec<_>>();
+        assert_eq!(file_rows, vec![25, 5, 25, 15, 20]);
+        assert_eq!(files[0].file_name, "0-0.parquet");
+        assert_eq!(files[1].file_name, "0-1.parquet");
+
+        let file = std::fs::File::open(export_dir.path().join(&files[0].file_name)).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 3);
+
+        let batches = read_parquet(export_dir.path(), &files);
+        let exported_schema = batches[0].schema();
+        assert_eq!(exported_schema.fields().len(), 2);
+        assert_eq!(exported_schema.field(0).metadata()["unit"], "ms");
+        let DataType::FixedSizeList(item, 2) = exported_schema.field(1).data_type() else {
+            panic!("Unexpected type {}", exported_schema.field(1).data_type());
+        };
+        assert_eq!(item.data_type(), &DataType::Float32);
+        assert!(item.metadata().is_empty());
+
+        let batch = arrow_select::concat::concat_batches(&exported_schema, &batches).unwrap();
+        let ids = batch["id"].as_primitive::<Int64Type>();
+        assert_eq!(ids.values().to_vec(), (10..100).collect::<Vec<_>>());
+        let values = batch["vec"].as_fixed_size_list().values().clone();
+        let values = values.as_primitive::<Float32Type>();
+        assert_eq!(
+            values.values().to_vec(),
+            (20..200).map(|i| i as f32).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_ipc_with_blobs() {
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int64, false),
+            ArrowField::new("blob", DataType::LargeBinary, true).with_metadata(HashMap::from([(
+                BLOB_META_KEY.to_string(),
+                "true".to_string(),
+            )])),
+        ]));
+        let blobs = (0..20)
+            .map(|i| (i % 5 != 0).then(|| vec![i as u8; i * 10]))
+            .collect::<Vec<_>>();
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from_iter_values(0..20)),
+                Arc::new(LargeBinaryArray::from_iter(blobs.iter().cloned())),
+            ],
+        )
+        .unwrap();
+
+        let test_dir = tempfile::tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+        let dataset = Dataset::write(
+            RecordBatchIterator::new(vec![Ok(batch)], schema.clone()),
+            test_uri,
+            Some(WriteParams {
+                max_rows_per_file: 8,
+                data_storage_version: Some(LanceFileVersion::Stable),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+
+        let export_dir = tempfile::tempdir().unwrap();
+        let files = dataset
+            .export(
+                export_dir.path().to_str().unwrap(),
+                Some(ExportParams {
+                    format: ExportFormat::ArrowIpc,
+                    num_threads: Some(2),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 3);
+        assert!(files.iter().all(|f| f.file_name.ends_with(".arrow")));
+
+        let batches = read_ipc(export_dir.path(), &files);
+        let exported_schema = batches[0].schema();
+        assert_eq!(exported_schema.field(1).data_type(), &DataType::LargeBinary);
+        assert!(exported_schema.field(1).metadata().is_empty());
+
+        let batch = arrow_select::concat::concat_batches(&exported_schema, &batches).unwrap();
+        assert_eq!(
+            batch["id"].as_primitive::<Int64Type>().values().to_vec(),
+            (0..20).collect::<Vec<_>>()
+        );
+        let exported = batch["blob"]
+            .as_binary::<i64>()
+            .iter()
+            .map(|blob| blob.map(|blob| blob.to_vec()))
+            .collect::<Vec<_>>();
+        assert_eq!(exported, blobs);
+    }
+
+    #[tokio::test]
+    async fn test_export_checked_out_version() {
+        let schema = Arc::new(ArrowSchema::new(vec![ArrowField::new(
+            "id",
+            DataType::Int64,
+            false,
+        )]));
+        let make_batches = |range: std::ops::Range<i64>| {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(Int64Array::from_iter_values(range))],
+            )
+            .unwrap();
+            RecordBatchIterator::new(vec![Ok(batch)], schema.clone())
+        };
+
+        let test_dir = tempfile::tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+        let mut dataset = Dataset::write(make_batches(0..10), test_uri, None)
+            .await
+            .unwrap();
+        dataset.append(make_batches(10..20), None).await.unwrap();
+
+        let first = dataset.checkout_version(1).await.unwrap();
+        let export_dir = tempfile::tempdir().unwrap();
+        let files = first
+            .export(export_dir.path().to_str().unwrap(), None)
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 1);
+
+        let batches = read_parquet(export_dir.path(), &files);
+        let ids = batches[0]["id"].as_primitive::<Int64Type>();
+        assert_eq!(ids.values().to_vec(), (0..10).collect::<Vec<_>>());
+    }
+}
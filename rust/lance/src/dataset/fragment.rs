@@ -901,7 +901,10 @@ impl FileFragment {
         if data_file.is_legacy_file() {
             let max_field_id = data_file.fields.iter().max().unwrap();
             if !schema_per_file.fields.is_empty() {
-                let path = self.dataset.data_dir().child(data_file.path.as_str());
+                let path = self
+                    .dataset
+                    .data_file_dir(data_file)?
+                    .child(data_file.path.as_str());
                 let field_id_offset = Self::get_field_id_offset(data_file);
                 let reader = FileReader::try_new_with_fragment_id(
                     &self.dataset.object_store,
@@ -926,7 +929,10 @@ impl FileFragment {
         } else if schema_per_file.fields.is_empty() {
             Ok(None)
         } else {
-            let path = self.dataset.data_dir().child(data_file.path.as_str());
+            let path = self
+                .dataset
+                .data_file_dir(data_file)?
+                .child(data_file.path.as_str());
             let (store_scheduler, reader_priority) =
                 if let Some(scan_scheduler) = read_config.scan_scheduler.as_ref() {
                     (
@@ -0,0 +1,810 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Import directories of Parquet, CSV or newline-delimited JSON files.
+//!
+//! Source files are read with DataFusion. The schema is inferred across all of
+//! the files and converted into one Lance can store (view types become their
+//! regular counterparts and maps become lists of key / value structs). Field
+//! metadata, such as Parquet field IDs (`PARQUET:field_id`), is kept.
+//!
+//! Each source file is written to its own fragments, several files at a time,
+//! and everything is committed in a single transaction at the end. The
+//! [`WriteFragmentProgress`](super::progress::WriteFragmentProgress) in
+//! [`WriteParams::progress`] is told about every fragment and about every
+//! finished source file, so an interrupted import can be resumed with
+//! [`ImportParams::completed_files`].
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arrow::compute::cast;
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, ArrayRef, FixedSizeListArray, ListArray, RecordBatch, StructArray};
+use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema, SchemaRef};
+use datafusion::error::Result as DFResult;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use datafusion::prelude::{
+    CsvReadOptions, DataFrame, NdJsonReadOptions, ParquetReadOptions, SessionContext,
+};
+use futures::{StreamExt, TryStreamExt};
+use lance_core::utils::tokio::get_num_compute_intensive_cpus;
+use lance_io::object_store::{ObjectStore, ObjectStoreParams, ObjectStoreRegistry};
+use lance_table::format::Fragment;
+use object_store::path::Path;
+use snafu::location;
+use url::Url;
+
+use super::transaction::{Operation, Transaction};
+use super::{CommitBuilder, InsertBuilder, WriteDestination, WriteParams};
+use crate::{Dataset, Error, Result};
+
+/// The URL under which the source object store is registered with DataFusion.
+const SOURCE_STORE_URL: &str = "lance-import://source";
+
+/// The format of the files to import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImportFormat {
+    Parquet,
+    Csv,
+    /// Newline-delimited JSON
+    Json,
+}
+
+impl ImportFormat {
+    /// The file extension used for this format when none is given.
+    pub fn default_extension(&self) -> &'static str {
+        match self {
+            Self::Parquet => "parquet",
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+
+    /// Guess the format from a file extension (without the leading dot).
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "parquet" | "pq" => Some(Self::Parquet),
+            "csv" => Some(Self::Csv),
+            "json" | "jsonl" | "ndjson" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ImportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_extension(s).ok_or_else(|| {
+            Error::invalid_input(
+                format!(
+                    "Unknown import format '{}', expected one of: parquet, csv, json",
+                    s
+                ),
+                location!(),
+            )
+        })
+    }
+}
+
+/// Parameters for [`Dataset::import`].
+#[derive(Debug, Clone)]
+pub struct ImportParams {
+    /// The format of the source files. If not set, it is inferred from the
+    /// extensions of the files found.
+    pub format: Option<ImportFormat>,
+    /// Only files with this extension (without the leading dot) are imported.
+    /// Defaults to the extension of the first file with a recognized format.
+    pub file_extension: Option<String>,
+    /// Whether CSV files start with a header row. Defaults to true.
+    pub has_header: bool,
+    /// The CSV field delimiter. Defaults to `,`.
+    pub delimiter: u8,
+    /// The number of records read from each CSV or JSON file to infer the
+    /// schema. Defaults to 1000.
+    pub schema_infer_max_records: usize,
+    /// How many source files to read and write at the same time. Defaults to
+    /// the number of compute-intensive CPUs.
+    pub num_threads: Option<usize>,
+    /// Source files that were already written by an earlier, interrupted,
+    /// import, along with the fragments written for them. These are keyed by
+    /// the source path reported to
+    /// [`WriteFragmentProgress::complete_source`](super::progress::WriteFragmentProgress::complete_source).
+    ///
+    /// These files are not read again; their fragments are committed along
+    /// with the newly written ones.
+    pub completed_files: HashMap<String, Vec<Fragment>>,
+    /// Parameters used to write the dataset. `mode` decides whether the
+    /// import creates, appends to or overwrites the destination.
+    pub write_params: WriteParams,
+    /// Parameters for the object store holding the source files.
+    pub source_store_params: Option<ObjectStoreParams>,
+}
+
+impl Default for ImportParams {
+    fn default() -> Self {
+        Self {
+            format: None,
+            file_extension: None,
+            has_header: true,
+            delimiter: b',',
+            schema_infer_max_records: 1000,
+            num_threads: None,
+            completed_files: HashMap::new(),
+            write_params: WriteParams::default(),
+            source_store_params: None,
+        }
+    }
+}
+
+/// A source file to import, as a path in the source object store.
+#[derive(Debug, Clone)]
+struct SourceFile {
+    path: Path,
+    url: String,
+}
+
+pub(super) async fn import(
+    source: &str,
+    dest: WriteDestination<'_>,
+    params: ImportParams,
+) -> Result<Dataset> {
+    let registry = params
+        .write_params
+        .session
+        .as_ref()
+        .map(|session| session.store_registry())
+        .unwrap_or_else(|| Arc::new(ObjectStoreRegistry::default()));
+    let (store, base) = ObjectStore::from_uri_and_params(
+        registry,
+        source,
+        &params.source_store_params.clone().unwrap_or_default(),
+    )
+    .await?;
+
+    let (format, files) = list_source_files(&store, &base, &params).await?;
+    if files.is_empty() {
+        return Err(Error::invalid_input(
+            format!("No files to import found at '{}'", source),
+            location!(),
+        ));
+    }
+    log::info!(
+        "Importing {} {:?} files from {} ({} already imported)",
+        files.len(),
+        format,
+        source,
+        files
+            .iter()
+            .filter(|file| params.completed_files.contains_key(file.path.as_ref()))
+            .count()
+    );
+
+    let ctx = SessionContext::new();
+    ctx.register_object_store(&Url::parse(SOURCE_STORE_URL)?, store.inner.clone());
+
+    // Infer a single schema across all of the files.
+    let urls = files
+        .iter()
+        .map(|file| file.url.clone())
+        .collect::<Vec<_>>();
+    let read_schema = read_files(&ctx, format, urls, None, &params)
+        .await?
+        .schema()
+        .as_arrow()
+        .clone();
+    let target_schema = Arc::new(lance_compatible_schema(&read_schema));
+
+    // Write each source file to its own fragments.
+    let num_threads = params
+        .num_threads
+        .unwrap_or_else(get_num_compute_intensive_cpus);
+    let written = futures::stream::iter(files)
+        .map(|file| {
+            let ctx = &ctx;
+            let dest = &dest;
+            let params = &params;
+            let read_schema = &read_schema;
+            let target_schema = target_schema.clone();
+            async move {
+                if let Some(fragments) = params.completed_files.get(file.path.as_ref()) {
+                    return Ok(fragments.clone());
+                }
+                let data = read_files(
+                    ctx,
+                    format,
+                    vec![file.url.clone()],
+                    Some(read_schema),
+                    params,
+                )
+                .await?
+                .execute_stream()
+                .await?;
+                let data = convert_stream(data, target_schema);
+                let transaction = InsertBuilder::new(dest.clone())
+                    .with_params(&params.write_params)
+                    .execute_uncommitted_stream(data)
+                    .await?;
+                if transaction.blobs_op.is_some() {
+                    return Err(Error::NotSupported {
+                        source: "Importing blob columns is not supported".into(),
+                        location: location!(),
+                    });
+                }
+                let fragments = written_fragments(transaction.operation)?;
+                params
+                    .write_params
+                    .progress
+                    .complete_source(file.path.as_ref(), &fragments)
+                    .await?;
+                Ok(fragments)
+            }
+        })
+        .buffered(num_threads)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    // Writing no data gives us the transaction to commit, which we then fill
+    // in with the fragments of every source file.
+    let empty = Box::pin(RecordBatchStreamAdapter::new(
+        target_schema.clone(),
+        futures::stream::empty::<DFResult<RecordBatch>>(),
+    )) as SendableRecordBatchStream;
+    let mut transaction = InsertBuilder::new(dest.clone())
+        .with_params(&params.write_params)
+        .execute_uncommitted_stream(empty)
+        .await?;
+    let new_fragments = written.into_iter().flatten().collect::<Vec<_>>();
+    match &mut transaction.operation {
+        Operation::Overwrite { fragments, .. } | Operation::Append { fragments } => {
+            fragments.extend(new_fragments);
+        }
+        Operation::Update {
+            removed_fragment_ids,
+            new_fragments: fragments,
+            ..
+        } => {
+            // Dynamic partition overwrite: replace every partition that was imported
+            let replaced_partitions = new_fragments
+                .iter()
+                .filter_map(|fragment| fragment.partition_values.clone())
+                .collect::<HashSet<_>>();
+            *removed_fragment_ids = dest
+                .dataset()
+                .map(|dataset| {
+                    dataset
+                        .fragments()
+                        .iter()
+                        .filter(|fragment| {
+                            fragment
+                                .partition_values
+                                .as_ref()
+                                .is_some_and(|values| replaced_partitions.contains(values))
+                        })
+                        .map(|fragment| fragment.id)
+                        .collect()
+                })
+                .unwrap_or_default();
+            fragments.extend(new_fragments);
+        }
+        operation => {
+            return Err(Error::Internal {
+                message: format!("Unexpected operation for import: {}", operation),
+                location: location!(),
+            })
+        }
+    }
+
+    commit(dest, transaction, &params.write_params).await
+}
+
+async fn commit(
+    dest: WriteDestination<'_>,
+    transaction: Transaction,
+    params: &WriteParams,
+) -> Result<Dataset> {
+    let mut builder = CommitBuilder::new(dest)
+        .use_move_stable_row_ids(params.enable_move_stable_row_ids)
+        .with_storage_format(params.storage_version_or_default())
+        .enable_v2_manifest_paths(params.enable_v2_manifest_paths)
+        .with_skip_auto_cleanup(params.skip_auto_cleanup);
+    if let Some(store_params) = params.store_params.as_ref() {
+        builder = builder.with_store_params(store_params.clone());
+    }
+    if let Some(session) = params.session.as_ref() {
+        builder = builder.with_session(session.clone());
+    }
+    if let Some(commit_handler) = params.commit_handler.as_ref() {
+        builder = builder.with_commit_handler(commit_handler.clone());
+    }
+    builder.execute(transaction).await
+}
+
+fn written_fragments(operation: Operation) -> Result<Vec<Fragment>> {
+    match operation {
+        Operation::Overwrite { fragments, .. } | Operation::Append { fragments } => Ok(fragments),
+        Operation::Update { new_fragments, .. } => Ok(new_fragments),
+        operation => Err(Error::Internal {
+            message: format!("Unexpected operation for import: {}", operation),
+            location: location!(),
+        }),
+    }
+}
+
+/// Find the files to import under `base`, sorted by path, and their format.
+async fn list_source_files(
+    store: &ObjectStore,
+    base: &Path,
+    params: &ImportParams,
+) -> Result<(ImportFormat, Vec<SourceFile>)> {
+    let mut paths = store
+        .read_dir_all(base, None)
+        .map_ok(|meta| meta.location)
+        .try_collect::<Vec<_>>()
+        .await?;
+    if paths.is_empty() && store.exists(base).await? {
+        // The source is a single file
+        paths.push(base.clone());
+    }
+    paths.sort();
+
+    let extension = match &params.file_extension {
+        Some(extension) => Some(extension.trim_start_matches('.').to_string()),
+        None => match params.format {
+            Some(format) => Some(format.default_extension().to_string()),
+            None => paths
+                .iter()
+                .filter_map(|path| path.extension())
+                .find(|extension| ImportFormat::from_extension(extension).is_some())
+                .map(str::to_string),
+        },
+    };
+    let Some(extension) = extension else {
+        return Ok((ImportFormat::Parquet, vec![]));
+    };
+    let format = match params.format {
+        Some(format) => format,
+        None => ImportFormat::from_extension(&extension).ok_or_else(|| {
+            Error::invalid_input(
+                format!(
+                    "Cannot infer the import format from the extension '{}'",
+                    extension
+                ),
+                location!(),
+            )
+        })?,
+    };
+
+    let files = paths
+        .into_iter()
+        .filter(|path| path.extension() == Some(extension.as_str()))
+        .map(|path| SourceFile {
+            url: format!("{}/{}", SOURCE_STORE_URL, path),
+            path,
+        })
+        .collect();
+    Ok((format, files))
+}
+
+async fn read_files(
+    ctx: &SessionContext,
+    format: ImportFormat,
+    urls: Vec<String>,
+    schema: Option<&ArrowSchema>,
+    params: &ImportParams,
+) -> Result<DataFrame> {
+    let extension = urls
+        .first()
+        .and_then(|url| url.rsplit_once('.'))
+        .map(|(_, extension)| format!(".{}", extension))
+        .unwrap_or_default();
+    let df = match format {
+        ImportFormat::Parquet => {
+            let mut options = ParquetReadOptions {
+                file_extension: &extension,
+                ..Default::default()
+            };
+            if let Some(schema) = schema {
+                options = options.schema(schema);
+            }
+            ctx.read_parquet(urls, options).await?
+        }
+        ImportFormat::Csv => {
+            let mut options = CsvReadOptions::new()
+                .has_header(params.has_header)
+                .delimiter(params.delimiter)
+                .schema_infer_max_records(params.schema_infer_max_records)
+                .file_extension(&extension);
+            if let Some(schema) = schema {
+                options = options.schema(schema);
+            }
+            ctx.read_csv(urls, options).await?
+        }
+        ImportFormat::Json => {
+            let mut options = NdJsonReadOptions::default()
+                .schema_infer_max_records(params.schema_infer_max_records)
+                .file_extension(&extension);
+            if let Some(schema) = schema {
+                options = options.schema(schema);
+            }
+            ctx.read_json(urls, options).await?
+        }
+    };
+    Ok(df)
+}
+
+/// Convert a schema read by DataFusion into one that Lance can store.
+fn lance_compatible_schema(schema: &ArrowSchema) -> ArrowSchema {
+    ArrowSchema::new_with_metadata(
+        schema
+            .fields()
+            .iter()
+            .map(|field| lance_compatible_field(field))
+            .collect::<Vec<_>>(),
+        schema.metadata().clone(),
+    )
+}
+
+fn lance_compatible_field(field: &ArrowField) -> ArrowField {
+    ArrowField::new(
+        field.name(),
+        lance_compatible_type(field.data_type()),
+        field.is_nullable(),
+    )
+    .with_metadata(field.metadata().clone())
+}
+
+fn lance_compatible_type(data_type: &DataType) -> DataType {
+    match data_type {
+        DataType::Utf8View => DataType::Utf8,
+        DataType::BinaryView => DataType::Binary,
+        DataType::List(item) => DataType::List(Arc::new(lance_compatible_field(item))),
+        DataType::LargeList(item) => DataType::LargeList(Arc::new(lance_compatible_field(item))),
+        DataType::FixedSizeList(item, size) => {
+            DataType::FixedSizeList(Arc::new(lance_compatible_field(item)), *size)
+        }
+        DataType::Struct(fields) => DataType::Struct(
+            fields
+                .iter()
+                .map(|field| lance_compatible_field(field))
+                .collect(),
+        ),
+        // Maps are stored as a list of key / value structs
+        DataType::Map(entries, _) => DataType::List(Arc::new(lance_compatible_field(entries))),
+        other => other.clone(),
+    }
+}
+
+/// Convert an array to the given (Lance compatible) type.
+fn convert_array(array: &ArrayRef, data_type: &DataType) -> Result<ArrayRef> {
+    if array.data_type() == data_type {
+        return Ok(array.clone());
+    }
+    let converted: ArrayRef = match (array.data_type(), data_type) {
+        (DataType::Map(_, _), DataType::List(item)) => {
+            let map = array.as_map();
+            let entries: ArrayRef = Arc::new(map.entries().clone());
+            Arc::new(ListArray::try_new(
+                item.clone(),
+                map.offsets().clone(),
+                convert_array(&entries, item.data_type())?,
+                map.nulls().cloned(),
+            )?)
+        }
+        (DataType::List(_), DataType::List(item)) => {
+            let list = array.as_list::<i32>();
+            Arc::new(ListArray::try_new(
+                item.clone(),
+                list.offsets().clone(),
+                convert_array(list.values(), item.data_type())?,
+                list.nulls().cloned(),
+            )?)
+        }
+        (DataType::LargeList(_), DataType::LargeList(item)) => {
+            let list = array.as_list::<i64>();
+            Arc::new(arrow_array::LargeListArray::try_new(
+                item.clone(),
+                list.offsets().clone(),
+                convert_array(list.values(), item.data_type())?,
+                list.nulls().cloned(),
+            )?)
+        }
+        (DataType::FixedSizeList(_, _), DataType::FixedSizeList(item, size)) => {
+            let list = array.as_fixed_size_list();
+            Arc::new(FixedSizeListArray::try_new(
+                item.clone(),
+                *size,
+                convert_array(list.values(), item.data_type())?,
+                list.nulls().cloned(),
+            )?)
+        }
+        (DataType::Struct(_), DataType::Struct(fields)) => {
+            let struct_array = array.as_struct();
+            let columns = struct_array
+                .columns()
+                .iter()
+                .zip(fields.iter())
+                .map(|(column, field)| convert_array(column, field.data_type()))
+                .collect::<Result<Vec<_>>>()?;
+            Arc::new(StructArray::try_new(
+                fields.clone(),
+                columns,
+                struct_array.nulls().cloned(),
+            )?)
+        }
+        _ => cast(array, data_type)?,
+    };
+    Ok(converted)
+}
+
+fn convert_batch(batch: &RecordBatch, schema: &SchemaRef) -> Result<RecordBatch> {
+    let columns = batch
+        .columns()
+        .iter()
+        .zip(schema.fields())
+        .map(|(column, field)| convert_array(column, field.data_type()))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+fn convert_stream(data: SendableRecordBatchStream, schema: SchemaRef) -> SendableRecordBatchStream {
+    let stream_schema = schema.clone();
+    let stream = data
+        .map(move |batch| -> DFResult<RecordBatch> { Ok(convert_batch(&batch?, &stream_schema)?) });
+    Box::pin(RecordBatchStreamAdapter::new(schema, stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    use arrow_array::builder::{MapBuilder, StringBuilder, StringViewBuilder};
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Int64Type;
+    use arrow_array::Int64Array;
+    use async_trait::async_trait;
+    use datafusion::dataframe::DataFrameWriteOptions;
+
+    use crate::dataset::progress::WriteFragmentProgress;
+    use crate::dataset::WriteMode;
+
+    fn write_text_files(dir: &std::path::Path, files: &[(&str, &str)]) {
+        for (name, contents) in files {
+            std::fs::write(dir.join(name), contents).unwrap();
+        }
+    }
+
+    async fn read_sorted(dataset: &Dataset, column: &str) -> Vec<i64> {
+        let batch = dataset.scan().try_into_batch().await.unwrap();
+        let mut values = batch[column].as_primitive::<Int64Type>().values().to_vec();
+        values.sort();
+        values
+    }
+
+    #[tokio::test]
+    async fn test_import_csv() {
+        let source = tempfile::tempdir().unwrap();
+        write_text_files(
+            source.path(),
+            &[
+                ("a.csv", "id,name\n1,one\n2,two\n"),
+                ("b.csv", "id,name\n3,three\n"),
+                ("notes.txt", "not data"),
+            ],
+        );
+        let dest = tempfile::tempdir().unwrap();
+        let dest = dest.path().to_str().unwrap();
+
+        let dataset = Dataset::import(source.path().to_str().unwrap(), dest, None)
+            .await
+            .unwrap();
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 3);
+        assert_eq!(dataset.get_fragments().len(), 2);
+        assert_eq!(read_sorted(&dataset, "id").await, vec![1, 2, 3]);
+        assert_eq!(
+            dataset.schema().field("name").unwrap().data_type(),
+            DataType::Utf8
+        );
+
+        // Append the same files again
+        let dataset = Dataset::import(
+            source.path().to_str().unwrap(),
+            dest,
+            Some(ImportParams {
+                format: Some(ImportFormat::Csv),
+                write_params: WriteParams {
+                    mode: WriteMode::Append,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_import_json_merges_schemas() {
+        let source = tempfile::tempdir().unwrap();
+        write_text_files(
+            source.path(),
+            &[
+                ("1.jsonl", "{\"id\": 1}\n{\"id\": 2}\n"),
+                ("2.jsonl", "{\"id\": 3, \"tags\": {\"color\": \"red\"}}\n"),
+            ],
+        );
+        let dest = tempfile::tempdir().unwrap();
+        let dest = dest.path().to_str().unwrap();
+
+        let dataset = Dataset::import(source.path().to_str().unwrap(), dest, None)
+            .await
+            .unwrap();
+        assert_eq!(read_sorted(&dataset, "id").await, vec![1, 2, 3]);
+        let tags = dataset.schema().field("tags").unwrap();
+        assert!(matches!(tags.data_type(), DataType::Struct(_)));
+    }
+
+    #[tokio::test]
+    async fn test_import_parquet_nested_and_field_ids() {
+        let source = tempfile::tempdir().unwrap();
+
+        let mut ids = ArrowField::new("id", DataType::Int64, false);
+        ids.set_metadata(HashMap::from([(
+            "PARQUET:field_id".to_string(),
+            "7".to_string(),
+        )]));
+        let mut names = StringViewBuilder::new();
+        let mut attrs = MapBuilder::new(None, StringBuilder::new(), StringBuilder::new());
+        for i in 0..10 {
+            names.append_value(format!("name-{}", i));
+            attrs.keys().append_value("k");
+            attrs.values().append_value(i.to_string());
+            attrs.append(true).unwrap();
+        }
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from_iter_values(0..10)),
+            Arc::new(names.finish()),
+            Arc::new(attrs.finish()),
+        ];
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ids,
+            ArrowField::new("name", DataType::Utf8View, true),
+            ArrowField::new("attrs", columns[2].data_type().clone(), true),
+        ]));
+        let batch = RecordBatch::try_new(schema, columns).unwrap();
+        let ctx = SessionContext::new();
+        for (i, range) in [(0, 0..6), (1, 6..10)] {
+            ctx.read_batch(batch.slice(range.start, range.len()))
+                .unwrap()
+                .write_parquet(
+                    source
+                        .path()
+                        .join(format!("{}.parquet", i))
+                        .to_str()
+                        .unwrap(),
+                    DataFrameWriteOptions::new().with_single_file_output(true),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+
+        #[derive(Debug, Default)]
+        struct RecordSources(Mutex<Vec<(String, usize)>>);
+
+        #[async_trait]
+        impl WriteFragmentProgress for RecordSources {
+            async fn begin(&self, _fragment: &Fragment) -> Result<()> {
+                Ok(())
+            }
+
+            async fn complete(&self, _fragment: &Fragment) -> Result<()> {
+                Ok(())
+            }
+
+            async fn complete_source(&self, source: &str, fragments: &[Fragment]) -> Result<()> {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((source.to_string(), fragments.len()));
+                Ok(())
+            }
+        }
+
+        let progress = Arc::new(RecordSources::default());
+        let dest = tempfile::tempdir().unwrap();
+        let dest = dest.path().to_str().unwrap();
+        let dataset = Dataset::import(
+            source.path().to_str().unwrap(),
+            dest,
+            Some(ImportParams {
+                write_params: WriteParams {
+                    progress: progress.clone(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            read_sorted(&dataset, "id").await,
+            (0..10).collect::<Vec<_>>()
+        );
+        let schema = dataset.schema();
+        assert_eq!(
+            schema.field("id").unwrap().metadata.get("PARQUET:field_id"),
+            Some(&"7".to_string())
+        );
+        assert_eq!(schema.field("name").unwrap().data_type(), DataType::Utf8);
+        assert!(matches!(
+            schema.field("attrs").unwrap().data_type(),
+            DataType::List(_)
+        ));
+
+        let sources = progress.0.lock().unwrap().clone();
+        assert_eq!(sources.len(), 2);
+        assert!(sources[0].0.ends_with("0.parquet"));
+        assert!(sources[1].0.ends_with("1.parquet"));
+    }
+
+    #[tokio::test]
+    async fn test_import_resume() {
+        let source = tempfile::tempdir().unwrap();
+        write_text_files(
+            source.path(),
+            &[("a.csv", "id\n1\n2\n"), ("b.csv", "id\n3\n")],
+        );
+        let dest = tempfile::tempdir().unwrap();
+        let dest = dest.path().to_str().unwrap();
+
+        // Pretend a previous import wrote a.csv, with other data, before it stopped.
+        let previous = InsertBuilder::new(dest)
+            .execute_uncommitted(vec![RecordBatch::try_new(
+                Arc::new(ArrowSchema::new(vec![ArrowField::new(
+                    "id",
+                    DataType::Int64,
+                    true,
+                )])),
+                vec![Arc::new(Int64Array::from(vec![10, 20]))],
+            )
+            .unwrap()])
+            .await
+            .unwrap();
+        let a_path = Path::from_filesystem_path(source.path().join("a.csv")).unwrap();
+
+        let dataset = Dataset::import(
+            source.path().to_str().unwrap(),
+            dest,
+            Some(ImportParams {
+                completed_files: HashMap::from([(
+                    a_path.to_string(),
+                    written_fragments(previous.operation).unwrap(),
+                )]),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(read_sorted(&dataset, "id").await, vec![3, 10, 20]);
+    }
+
+    #[tokio::test]
+    async fn test_import_nothing() {
+        let source = tempfile::tempdir().unwrap();
+        write_text_files(source.path(), &[("notes.txt", "not data")]);
+        let dest = tempfile::tempdir().unwrap();
+        let dest = dest.path().to_str().unwrap();
+        let err = Dataset::import(source.path().to_str().unwrap(), dest, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }), "{}", err);
+    }
+}
@@ -73,8 +73,13 @@ impl IndexRemapper for DatasetIndexRemapper {
     }
 }
 
-pub trait LanceIndexStoreExt {
+pub trait LanceIndexStoreExt: Sized {
+    /// The store for a new index written to the dataset's indices directory.
     fn from_dataset(dataset: &Dataset, uuid: &str) -> Self;
+
+    /// The store for an existing index, which may belong to the dataset a
+    /// shallow clone was made from.
+    fn from_dataset_index(dataset: &Dataset, index: &Index) -> Result<Self>;
 }
 
 impl LanceIndexStoreExt for LanceIndexStore {
@@ -83,4 +88,14 @@ impl LanceIndexStoreExt for LanceIndexStore {
         let cache = dataset.metadata_cache.file_metadata_cache(&index_dir);
         Self::new(dataset.object_store.clone(), index_dir, Arc::new(cache))
     }
+
+    fn from_dataset_index(dataset: &Dataset, index: &Index) -> Result<Self> {
+        let index_dir = dataset.index_dir(index)?;
+        let cache = dataset.metadata_cache.file_metadata_cache(&index_dir);
+        Ok(Self::new(
+            dataset.object_store.clone(),
+            index_dir,
+            Arc::new(cache),
+        ))
+    }
 }
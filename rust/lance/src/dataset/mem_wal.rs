@@ -0,0 +1,583 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Low latency ingestion through the MemWAL.
+//!
+//! A [`MemWalWriter`] owns a region of the MemWAL. Each write is appended to a WAL file
+//! on the object store and to the in-memory [`MemTable`] of the current generation.
+//! The WAL files are recorded in the MemWAL in batches (see [`MemWalWriter::sync`]),
+//! so that a write does not commit a new version of the dataset. The rows in the
+//! MemTables are visible to the [`Scanner`] that is given the MemTables
+//! (see [`Scanner::with_mem_tables`]), they override the rows of the dataset with the same
+//! primary key. Flushing seals the generation and upserts its rows into the dataset with
+//! `merge_insert`, which marks the generation as flushed in the same commit. The sealed
+//! MemTable stays visible until its flush is committed.
+//!
+//! If a writer crashes, the next writer of the region takes over the ownership,
+//! rebuilds the MemTable of the open generation by replaying its WAL entries,
+//! and flushes the generations that were sealed but not flushed.
+
+mod memtable;
+mod wal;
+
+use std::sync::Arc;
+
+use arrow_array::{RecordBatch, RecordBatchIterator};
+use arrow_schema::{Schema as ArrowSchema, SchemaRef};
+use lance_index::mem_wal::{MemWal, State};
+use lance_index::metrics::NoOpMetricsCollector;
+use object_store::path::Path;
+use snafu::location;
+
+pub use memtable::{MemTable, MemTableSnapshot};
+
+use super::scanner::Scanner;
+use super::{Dataset, MergeInsertBuilder, WhenMatched, WhenNotMatched};
+use crate::index::mem_wal::{
+    advance_mem_wal_generation, append_mem_wal_entries, find_latest_mem_wal_generation,
+    mark_mem_wal_as_flushed, update_mem_wal_owner,
+};
+use crate::index::DatasetIndexInternalExt;
+use crate::{Error, Result};
+
+/// The directory under the dataset root for the WAL files
+pub const MEM_WAL_DIR: &str = "_mem_wal";
+
+/// The default number of WAL entries written before they are recorded in the MemWAL
+pub const DEFAULT_MAX_UNSYNCED_WAL_ENTRIES: usize = 32;
+
+/// Writes to a region of the MemWAL of a dataset.
+pub struct MemWalWriter {
+    dataset: Dataset,
+    region: String,
+    owner_id: String,
+    primary_key: Vec<String>,
+    schema: SchemaRef,
+    mem_wal: MemWal,
+    mem_table: Arc<MemTable>,
+    // the WAL entries written but not yet recorded in the MemWAL
+    unsynced_entries: Vec<u64>,
+    max_unsynced_entries: usize,
+    // the generations that were sealed but failed to flush, from the oldest to the newest
+    sealed: Vec<(MemWal, Arc<MemTable>)>,
+}
+
+impl MemWalWriter {
+    /// Open the writer of the region, the rows are deduplicated by the `primary_key` columns.
+    ///
+    /// The writer claims the ownership of the region, so the writes from the previous owner
+    /// fail from now on. The unflushed data of the previous owner is recovered from the WAL.
+    pub async fn open(
+        dataset: Dataset,
+        region: &str,
+        primary_key: Vec<String>,
+        owner_id: &str,
+    ) -> Result<Self> {
+        let mut dataset = dataset;
+        let schema = Arc::new(ArrowSchema::from(dataset.schema()));
+        // fail early if the primary key is invalid
+        MemTable::try_new(schema.clone(), primary_key.clone())?;
+
+        let generations = dataset
+            .open_mem_wal_index(&NoOpMetricsCollector)
+            .await?
+            .and_then(|index| index.mem_wal_map.get(region).cloned())
+            .map(|generations| generations.into_values().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let mut latest_owner_id = None;
+        let mut open_mem_wal = None;
+        for mem_wal in generations {
+            latest_owner_id = Some(mem_wal.owner_id.clone());
+            if mem_wal.state == State::Flushed {
+                continue;
+            }
+            let mem_wal = claim_mem_wal(&mut dataset, mem_wal, owner_id).await?;
+            latest_owner_id = Some(owner_id.to_owned());
+            match mem_wal.state {
+                State::Sealed => {
+                    let mem_table =
+                        replay_mem_wal(&dataset, &mem_wal, &schema, &primary_key).await?;
+                    dataset = flush_mem_table(dataset, &mem_wal, &mem_table, owner_id).await?;
+                }
+                State::Open => open_mem_wal = Some(mem_wal),
+                State::Flushed => unreachable!(),
+            }
+        }
+
+        let (mem_wal, mem_table) = match open_mem_wal {
+            Some(mem_wal) => {
+                let mem_table = replay_mem_wal(&dataset, &mem_wal, &schema, &primary_key).await?;
+                (mem_wal, mem_table)
+            }
+            None => {
+                let mem_wal =
+                    start_generation(&mut dataset, region, latest_owner_id.as_deref(), owner_id)
+                        .await?;
+                let mem_table = MemTable::try_new(schema.clone(), primary_key.clone())?;
+                (mem_wal, mem_table)
+            }
+        };
+
+        Ok(Self {
+            dataset,
+            region: region.to_owned(),
+            owner_id: owner_id.to_owned(),
+            primary_key,
+            schema,
+            mem_wal,
+            mem_table: Arc::new(mem_table),
+            unsynced_entries: Vec::new(),
+            max_unsynced_entries: DEFAULT_MAX_UNSYNCED_WAL_ENTRIES,
+            sealed: Vec::new(),
+        })
+    }
+
+    /// Record the WAL entries in the MemWAL after every `max_unsynced_entries` writes,
+    /// defaults to [`DEFAULT_MAX_UNSYNCED_WAL_ENTRIES`]
+    pub fn with_max_unsynced_entries(mut self, max_unsynced_entries: usize) -> Self {
+        self.max_unsynced_entries = max_unsynced_entries.max(1);
+        self
+    }
+
+    /// The dataset at the version of the last commit of this writer
+    pub fn dataset(&self) -> &Dataset {
+        &self.dataset
+    }
+
+    /// The MemWAL of the current generation
+    pub fn mem_wal(&self) -> &MemWal {
+        &self.mem_wal
+    }
+
+    /// The MemTables with the unflushed rows, ordered from the oldest to the newest
+    pub fn mem_tables(&self) -> Vec<Arc<MemTable>> {
+        self.sealed
+            .iter()
+            .map(|(_, mem_table)| mem_table.clone())
+            .chain(std::iter::once(self.mem_table.clone()))
+            .collect()
+    }
+
+    /// Scan the dataset merged with the unflushed rows
+    pub fn scan(&self) -> Scanner {
+        let mut scanner = self.dataset.scan();
+        scanner.with_mem_tables(self.mem_tables());
+        scanner
+    }
+
+    /// Append the rows to the WAL and the MemTable.
+    ///
+    /// The rows with the same primary key as existing rows replace them. The rows are
+    /// durable once their WAL entry is recorded in the MemWAL, which happens every
+    /// `max_unsynced_entries` writes, on [`Self::sync`] and on [`Self::flush`].
+    pub async fn write(&mut self, batch: RecordBatch) -> Result<()> {
+        if batch.schema().fields() != self.schema.fields() {
+            return Err(Error::invalid_input(
+                format!(
+                    "batch schema {:?} doesn't match the dataset schema {:?}",
+                    batch.schema(),
+                    self.schema
+                ),
+                location!(),
+            ));
+        }
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+
+        let entry_id = match self.unsynced_entries.last() {
+            Some(last_entry_id) => last_entry_id + 1,
+            None => self
+                .mem_wal
+                .wal_entries()
+                .range()
+                .map(|range| range.end() + 1)
+                .unwrap_or_default(),
+        };
+        wal::write_wal_entry(
+            self.dataset.object_store(),
+            &Path::from(self.mem_wal.wal_location.as_str()),
+            entry_id,
+            &self.schema,
+            std::slice::from_ref(&batch),
+        )
+        .await?;
+        self.mem_table.insert(batch)?;
+        self.unsynced_entries.push(entry_id);
+        if self.unsynced_entries.len() >= self.max_unsynced_entries {
+            self.sync().await?;
+        }
+        Ok(())
+    }
+
+    /// Record the written WAL entries in the MemWAL with a single commit,
+    /// making their rows durable.
+    ///
+    /// This fails if another writer took over the ownership of the region.
+    pub async fn sync(&mut self) -> Result<()> {
+        if self.unsynced_entries.is_empty() {
+            return Ok(());
+        }
+        self.mem_wal = append_mem_wal_entries(
+            &mut self.dataset,
+            &self.region,
+            self.mem_wal.id.generation,
+            &self.unsynced_entries,
+            &self.owner_id,
+        )
+        .await?;
+        self.unsynced_entries.clear();
+        Ok(())
+    }
+
+    /// Seal the current generation and flush its rows into the dataset,
+    /// the following writes go to the next generation.
+    ///
+    /// The generations whose flush failed before are flushed first. A sealed generation
+    /// stays visible through [`Self::mem_tables`] until its flush is committed.
+    pub async fn flush(&mut self) -> Result<()> {
+        if !self.mem_table.is_empty() || self.sealed.is_empty() {
+            self.sync().await?;
+            let sealed_mem_wal = self.mem_wal.clone();
+            self.mem_wal = start_generation(
+                &mut self.dataset,
+                &self.region,
+                Some(&self.owner_id),
+                &self.owner_id,
+            )
+            .await?;
+            let sealed_mem_table = std::mem::replace(
+                &mut self.mem_table,
+                Arc::new(MemTable::try_new(
+                    self.schema.clone(),
+                    self.primary_key.clone(),
+                )?),
+            );
+            self.sealed.push((sealed_mem_wal, sealed_mem_table));
+        }
+
+        while let Some((mem_wal, mem_table)) = self.sealed.first() {
+            let dataset = self.dataset.clone();
+            self.dataset = flush_mem_table(dataset, mem_wal, mem_table, &self.owner_id).await?;
+            self.sealed.remove(0);
+        }
+        Ok(())
+    }
+}
+
+fn generation_dir(dataset: &Dataset, region: &str, generation: u64) -> Path {
+    dataset
+        .base
+        .child(MEM_WAL_DIR)
+        .child(region)
+        .child(generation.to_string())
+}
+
+// seal the latest generation if it's open, and open the next one
+async fn start_generation(
+    dataset: &mut Dataset,
+    region: &str,
+    expected_owner_id: Option<&str>,
+    owner_id: &str,
+) -> Result<MemWal> {
+    let generation = find_latest_mem_wal_generation(dataset, region)
+        .await?
+        .map(|mem_wal| mem_wal.id.generation + 1)
+        .unwrap_or_default();
+    let dir = generation_dir(dataset, region, generation);
+    advance_mem_wal_generation(
+        dataset,
+        region,
+        dir.child("mem_table").as_ref(),
+        dir.child("wal").as_ref(),
+        expected_owner_id,
+        owner_id,
+    )
+    .await?;
+    find_latest_mem_wal_generation(dataset, region)
+        .await?
+        .ok_or_else(|| Error::Internal {
+            message: format!("MemWAL region {} has no generation after advancing", region),
+            location: location!(),
+        })
+}
+
+async fn claim_mem_wal(dataset: &mut Dataset, mem_wal: MemWal, owner_id: &str) -> Result<MemWal> {
+    if mem_wal.owner_id == owner_id {
+        return Ok(mem_wal);
+    }
+    update_mem_wal_owner(
+        dataset,
+        &mem_wal.id.region,
+        mem_wal.id.generation,
+        owner_id,
+        None,
+    )
+    .await
+}
+
+// rebuild the MemTable from the WAL entries recorded in the MemWAL
+async fn replay_mem_wal(
+    dataset: &Dataset,
+    mem_wal: &MemWal,
+    schema: &SchemaRef,
+    primary_key: &[String],
+) -> Result<MemTable> {
+    let mem_table = MemTable::try_new(schema.clone(), primary_key.to_vec())?;
+    let wal_location = Path::from(mem_wal.wal_location.as_str());
+    for entry_id in mem_wal.wal_entries().iter() {
+        let batches = wal::read_wal_entry(dataset.object_store(), &wal_location, entry_id).await?;
+        for batch in batches {
+            mem_table.insert(batch)?;
+        }
+    }
+    Ok(mem_table)
+}
+
+// upsert the rows of the sealed MemTable, and mark its MemWAL as flushed in the same commit
+async fn flush_mem_table(
+    dataset: Dataset,
+    mem_wal: &MemWal,
+    mem_table: &MemTable,
+    owner_id: &str,
+) -> Result<Dataset> {
+    let mut dataset = dataset;
+    if mem_table.is_empty() {
+        mark_mem_wal_as_flushed(
+            &mut dataset,
+            &mem_wal.id.region,
+            mem_wal.id.generation,
+            owner_id,
+        )
+        .await?;
+        return Ok(dataset);
+    }
+
+    let snapshot = mem_table.snapshot()?;
+    let mut builder =
+        MergeInsertBuilder::try_new(Arc::new(dataset), mem_table.primary_key().to_vec())?;
+    builder
+        .when_matched(WhenMatched::UpdateAll)
+        .when_not_matched(WhenNotMatched::InsertAll);
+    builder
+        .mark_mem_wal_as_flushed(mem_wal.id.clone(), owner_id)
+        .await?;
+    let reader = RecordBatchIterator::new(
+        snapshot.batches.into_iter().map(Ok),
+        mem_table.schema().clone(),
+    );
+    let (dataset, _) = builder.try_build()?.execute_reader(reader).await?;
+    Ok(Arc::unwrap_or_clone(dataset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow_array::{Int32Array, StringArray};
+    use arrow_schema::{DataType, Field};
+    use futures::TryStreamExt;
+    use lance_index::mem_wal::MEM_WAL_INDEX_NAME;
+    use lance_index::DatasetIndexExt;
+
+    use crate::dataset::WriteParams;
+
+    fn make_batch(schema: &SchemaRef, ids: Vec<i32>, values: Vec<&str>) -> RecordBatch {
+        RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(ids)),
+                Arc::new(StringArray::from(values)),
+            ],
+        )
+        .unwrap()
+    }
+
+    async fn scan_rows(scanner: Scanner) -> Vec<(i32, String)> {
+        let batches = scanner
+            .try_into_stream()
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let mut rows = batches
+            .iter()
+            .flat_map(|batch| {
+                let ids = batch["id"].as_any().downcast_ref::<Int32Array>().unwrap();
+                let values = batch["value"]
+                    .as_any()
+                    .downcast_ref::<StringArray>()
+                    .unwrap();
+                ids.values()
+                    .iter()
+                    .zip(values.iter())
+                    .map(|(id, value)| (*id, value.unwrap().to_owned()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        rows.sort();
+        rows
+    }
+
+    fn rows(rows: &[(i32, &str)]) -> Vec<(i32, String)> {
+        rows.iter()
+            .map(|(id, value)| (*id, value.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_mem_wal_writer() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let uri = tempdir.path().to_str().unwrap();
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("value", DataType::Utf8, true),
+        ]));
+        let batch = make_batch(&schema, vec![1, 2, 3], vec!["a", "b", "c"]);
+        let dataset = Dataset::write(
+            RecordBatchIterator::new(vec![Ok(batch)], schema.clone()),
+            uri,
+            Some(WriteParams::default()),
+        )
+        .await
+        .unwrap();
+
+        let mut writer = MemWalWriter::open(dataset, "GLOBAL", vec!["id".to_owned()], "owner_0")
+            .await
+            .unwrap();
+        let version = writer.dataset().version().version;
+        writer
+            .write(make_batch(&schema, vec![2, 4], vec!["B", "d"]))
+            .await
+            .unwrap();
+        writer
+            .write(make_batch(&schema, vec![4, 5], vec!["D", "e"]))
+            .await
+            .unwrap();
+        // the WAL entries are recorded with a single commit
+        assert!(writer.mem_wal().wal_entries().is_empty());
+        assert_eq!(writer.dataset().version().version, version);
+        writer.sync().await.unwrap();
+        assert_eq!(writer.mem_wal().wal_entries().len(), 2);
+        assert_eq!(writer.dataset().version().version, version + 1);
+
+        // the unflushed rows override the rows of the dataset
+        let expected = rows(&[(1, "a"), (2, "B"), (3, "c"), (4, "D"), (5, "e")]);
+        assert_eq!(scan_rows(writer.scan()).await, expected);
+        assert_eq!(
+            scan_rows(writer.dataset().scan()).await,
+            rows(&[(1, "a"), (2, "b"), (3, "c")])
+        );
+
+        // the filter applies to the unflushed rows as well,
+        // and the overridden row of the dataset is hidden even if it passes the filter
+        let mut scanner = writer.scan();
+        scanner.filter("value >= 'b'").unwrap();
+        assert_eq!(scan_rows(scanner).await, rows(&[(3, "c"), (5, "e")]));
+
+        // crash recovery, a new owner replays the WAL
+        let dataset = writer.dataset().clone();
+        let mut recovered = MemWalWriter::open(dataset, "GLOBAL", vec!["id".to_owned()], "owner_1")
+            .await
+            .unwrap();
+        assert_eq!(recovered.mem_tables()[0].num_rows(), 3);
+        assert_eq!(scan_rows(recovered.scan()).await, expected);
+        // the previous owner can't record its writes anymore
+        writer
+            .write(make_batch(&schema, vec![6], vec!["f"]))
+            .await
+            .unwrap();
+        assert!(writer.sync().await.is_err());
+
+        recovered.flush().await.unwrap();
+        assert!(recovered.mem_tables()[0].is_empty());
+        assert_eq!(scan_rows(recovered.dataset().scan()).await, expected);
+        assert_eq!(scan_rows(recovered.scan()).await, expected);
+
+        let mem_wal_index = recovered
+            .dataset()
+            .open_mem_wal_index(&NoOpMetricsCollector)
+            .await
+            .unwrap()
+            .unwrap();
+        let generations = &mem_wal_index.mem_wal_map["GLOBAL"];
+        assert_eq!(generations[&0].state, State::Flushed);
+        assert_eq!(generations[&1].state, State::Open);
+        assert!(recovered
+            .dataset()
+            .load_indices()
+            .await
+            .unwrap()
+            .iter()
+            .any(|index| index.name == MEM_WAL_INDEX_NAME));
+    }
+
+    #[tokio::test]
+    async fn test_mem_wal_writer_flush_failure() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let uri = tempdir.path().to_str().unwrap();
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("value", DataType::Utf8, true),
+        ]));
+        let batch = make_batch(&schema, vec![1, 2], vec!["a", "b"]);
+        let dataset = Dataset::write(
+            RecordBatchIterator::new(vec![Ok(batch)], schema.clone()),
+            uri,
+            Some(WriteParams::default()),
+        )
+        .await
+        .unwrap();
+
+        let mut writer = MemWalWriter::open(dataset, "GLOBAL", vec!["id".to_owned()], "owner_0")
+            .await
+            .unwrap()
+            .with_max_unsynced_entries(1);
+        writer
+            .write(make_batch(&schema, vec![2, 3], vec!["B", "c"]))
+            .await
+            .unwrap();
+        assert_eq!(writer.mem_wal().wal_entries().len(), 1);
+
+        // the merge_insert of the flush can't read the data files of the dataset
+        let data_dir = tempdir.path().join("data");
+        let moved_data_dir = tempdir.path().join("data_moved");
+        std::fs::rename(&data_dir, &moved_data_dir).unwrap();
+        assert!(writer.flush().await.is_err());
+        std::fs::rename(&moved_data_dir, &data_dir).unwrap();
+
+        // the sealed rows are still visible
+        let mem_tables = writer.mem_tables();
+        assert_eq!(mem_tables.len(), 2);
+        assert_eq!(mem_tables[0].num_rows(), 2);
+        assert!(mem_tables[1].is_empty());
+        let expected = rows(&[(1, "a"), (2, "B"), (3, "c")]);
+        assert_eq!(scan_rows(writer.scan()).await, expected);
+
+        // the rows written after the failure override the sealed rows
+        writer
+            .write(make_batch(&schema, vec![3], vec!["C"]))
+            .await
+            .unwrap();
+        let expected = rows(&[(1, "a"), (2, "B"), (3, "C")]);
+        assert_eq!(scan_rows(writer.scan()).await, expected);
+
+        writer.flush().await.unwrap();
+        assert_eq!(writer.mem_tables().len(), 1);
+        assert!(writer.mem_tables()[0].is_empty());
+        assert_eq!(scan_rows(writer.dataset().scan()).await, expected);
+
+        let mem_wal_index = writer
+            .dataset()
+            .open_mem_wal_index(&NoOpMetricsCollector)
+            .await
+            .unwrap()
+            .unwrap();
+        let generations = &mem_wal_index.mem_wal_map["GLOBAL"];
+        assert_eq!(generations[&0].state, State::Flushed);
+        assert_eq!(generations[&1].state, State::Flushed);
+        assert_eq!(generations[&2].state, State::Open);
+    }
+}
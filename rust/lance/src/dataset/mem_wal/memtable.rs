@@ -0,0 +1,209 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use arrow_array::{BooleanArray, RecordBatch};
+use arrow_row::{OwnedRow, RowConverter, Rows, SortField};
+use arrow_schema::SchemaRef;
+use arrow_select::filter::filter_record_batch;
+use snafu::location;
+
+use crate::{Error, Result};
+
+/// The in-memory table of a MemWAL region.
+///
+/// It holds the rows written to the WAL but not flushed to the dataset yet.
+/// Rows are deduplicated by the primary key, a later write of a key replaces
+/// the earlier one, the same as the `merge_insert` that flushes the table.
+#[derive(Debug)]
+pub struct MemTable {
+    schema: SchemaRef,
+    primary_key: Vec<String>,
+    key_converter: RowConverter,
+    state: RwLock<MemTableState>,
+}
+
+#[derive(Debug, Default)]
+struct MemTableState {
+    batches: Vec<RecordBatch>,
+    // the (batch, row) of the latest version of each key
+    locations: HashMap<OwnedRow, (usize, usize)>,
+}
+
+/// The live rows of the [`MemTable`] at some point
+#[derive(Debug, Clone)]
+pub struct MemTableSnapshot {
+    pub batches: Vec<RecordBatch>,
+    pub keys: HashSet<OwnedRow>,
+}
+
+impl MemTable {
+    pub fn try_new(schema: SchemaRef, primary_key: Vec<String>) -> Result<Self> {
+        if primary_key.is_empty() {
+            return Err(Error::invalid_input(
+                "MemTable requires at least one primary key column",
+                location!(),
+            ));
+        }
+        let sort_fields = primary_key
+            .iter()
+            .map(|column| {
+                let field = schema.field_with_name(column).map_err(|_| {
+                    Error::invalid_input(
+                        format!("primary key column {} not found in the schema", column),
+                        location!(),
+                    )
+                })?;
+                Ok(SortField::new(field.data_type().clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let key_converter = RowConverter::new(sort_fields)?;
+        Ok(Self {
+            schema,
+            primary_key,
+            key_converter,
+            state: RwLock::new(MemTableState::default()),
+        })
+    }
+
+    pub fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    pub fn primary_key(&self) -> &[String] {
+        &self.primary_key
+    }
+
+    /// Number of live rows, i.e. the number of distinct keys
+    pub fn num_rows(&self) -> usize {
+        self.state.read().unwrap().locations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.num_rows() == 0
+    }
+
+    /// Insert the rows, replacing the existing rows with the same keys
+    pub fn insert(&self, batch: RecordBatch) -> Result<()> {
+        if batch.schema().fields() != self.schema.fields() {
+            return Err(Error::invalid_input(
+                format!(
+                    "batch schema {:?} doesn't match the MemTable schema {:?}",
+                    batch.schema(),
+                    self.schema
+                ),
+                location!(),
+            ));
+        }
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+        let keys = self.keys(&batch)?;
+        let mut state = self.state.write().unwrap();
+        let batch_idx = state.batches.len();
+        state.batches.push(batch);
+        for (row_idx, key) in keys.iter().enumerate() {
+            state.locations.insert(key.owned(), (batch_idx, row_idx));
+        }
+        Ok(())
+    }
+
+    /// Converts the primary key columns of the batch into comparable rows
+    pub fn keys(&self, batch: &RecordBatch) -> Result<Rows> {
+        let columns = self
+            .primary_key
+            .iter()
+            .map(|column| {
+                batch.column_by_name(column).cloned().ok_or_else(|| {
+                    Error::invalid_input(
+                        format!("primary key column {} not found in the batch", column),
+                        location!(),
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(self.key_converter.convert_columns(&columns)?)
+    }
+
+    /// The latest version of each row, the older versions of the same keys are dropped
+    pub fn snapshot(&self) -> Result<MemTableSnapshot> {
+        let state = self.state.read().unwrap();
+        let mut masks = state
+            .batches
+            .iter()
+            .map(|batch| vec![false; batch.num_rows()])
+            .collect::<Vec<_>>();
+        for (batch_idx, row_idx) in state.locations.values() {
+            masks[*batch_idx][*row_idx] = true;
+        }
+        let batches = state
+            .batches
+            .iter()
+            .zip(masks)
+            .map(|(batch, mask)| Ok(filter_record_batch(batch, &BooleanArray::from(mask))?))
+            .filter(|batch| !matches!(batch, Ok(batch) if batch.num_rows() == 0))
+            .collect::<Result<Vec<_>>>()?;
+        let keys = state.locations.keys().cloned().collect();
+        Ok(MemTableSnapshot { batches, keys })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow_array::{Int32Array, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+
+    #[test]
+    fn test_mem_table_dedup() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("value", DataType::Utf8, true),
+        ]));
+        let batch = |ids: Vec<i32>, values: Vec<&str>| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int32Array::from(ids)),
+                    Arc::new(StringArray::from(values)),
+                ],
+            )
+            .unwrap()
+        };
+
+        let mem_table = MemTable::try_new(schema.clone(), vec!["id".to_owned()]).unwrap();
+        mem_table
+            .insert(batch(vec![1, 2, 3], vec!["a", "b", "c"]))
+            .unwrap();
+        mem_table.insert(batch(vec![2, 4], vec!["B", "d"])).unwrap();
+        assert_eq!(mem_table.num_rows(), 4);
+
+        let snapshot = mem_table.snapshot().unwrap();
+        assert_eq!(snapshot.keys.len(), 4);
+        let batch = arrow_select::concat::concat_batches(&schema, &snapshot.batches).unwrap();
+        let ids = batch["id"]
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap()
+            .values()
+            .to_vec();
+        let values = batch["value"]
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap()
+            .iter()
+            .map(|v| v.unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 3, 2, 4]);
+        assert_eq!(values, vec!["a", "c", "B", "d"]);
+
+        assert!(MemTable::try_new(schema.clone(), vec!["missing".to_owned()]).is_err());
+        let other_schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)]));
+        let other =
+            RecordBatch::try_new(other_schema, vec![Arc::new(Int32Array::from(vec![1]))]).unwrap();
+        assert!(mem_table.insert(other).is_err());
+    }
+}
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! The WAL files of a MemWAL generation.
+//!
+//! Each entry is an Arrow IPC stream with the batches of one write,
+//! stored at `<wal_location>/<entry_id>.arrow`.
+//! An entry is only durable once its id is appended to the MemWAL,
+//! a file without a recorded id is ignored by replay.
+
+use std::io::Cursor;
+
+use arrow_array::RecordBatch;
+use arrow_ipc::reader::StreamReader;
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::SchemaRef;
+use lance_io::object_store::ObjectStore;
+use object_store::path::Path;
+
+use crate::Result;
+
+pub(super) fn wal_entry_path(wal_location: &Path, entry_id: u64) -> Path {
+    // zero padded so the entries are listed in order
+    wal_location.child(format!("{:020}.arrow", entry_id))
+}
+
+pub(super) async fn write_wal_entry(
+    object_store: &ObjectStore,
+    wal_location: &Path,
+    entry_id: u64,
+    schema: &SchemaRef,
+    batches: &[RecordBatch],
+) -> Result<()> {
+    let mut buffer = Vec::new();
+    {
+        let mut writer = StreamWriter::try_new(&mut buffer, schema)?;
+        for batch in batches {
+            writer.write(batch)?;
+        }
+        writer.finish()?;
+    }
+    object_store
+        .put(&wal_entry_path(wal_location, entry_id), &buffer)
+        .await?;
+    Ok(())
+}
+
+pub(super) async fn read_wal_entry(
+    object_store: &ObjectStore,
+    wal_location: &Path,
+    entry_id: u64,
+) -> Result<Vec<RecordBatch>> {
+    let bytes = object_store
+        .read_one_all(&wal_entry_path(wal_location, entry_id))
+        .await?;
+    let reader = StreamReader::try_new(Cursor::new(bytes), None)?;
+    Ok(reader.collect::<std::result::Result<Vec<_>, _>>()?)
+}
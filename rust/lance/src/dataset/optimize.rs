@@ -93,9 +93,11 @@ use datafusion::physical_plan::SendableRecordBatchStream;
 use futures::{StreamExt, TryStreamExt};
 use lance_core::utils::tokio::get_num_compute_intensive_cpus;
 use lance_core::utils::tracing::{DATASET_COMPACTING_EVENT, TRACE_DATASET_EVENTS};
+use lance_core::{ROW_ADDR, ROW_ID};
 use lance_index::frag_reuse::FragReuseGroup;
 use lance_index::DatasetIndexExt;
 use lance_table::format::{Fragment, RowIdMeta};
+use lance_table::rowids::RowIdSequence;
 use roaring::{RoaringBitmap, RoaringTreemap};
 use serde::{Deserialize, Serialize};
 
@@ -103,14 +105,16 @@ use super::fragment::FileFragment;
 use super::index::DatasetIndexRemapperOptions;
 use super::rowids::load_row_id_sequences;
 use super::transaction::{Operation, RewriteGroup, RewrittenIndex, Transaction};
-use super::utils::make_rowaddr_capture_stream;
+use super::utils::{make_ordered_capture_stream, make_rowaddr_capture_stream};
 use super::{write_fragments_internal, WriteMode, WriteParams};
 use tracing::info;
 
+mod clustering;
 pub mod remapping;
 
 use crate::index::frag_reuse::build_new_frag_reuse_index;
 use crate::io::deletion::read_dataset_deletion_file;
+pub use clustering::CompactionOrdering;
 pub use remapping::{IgnoreRemap, IndexRemapper, IndexRemapperOptions, RemappedIndex};
 
 /// Options to be passed to [compact_files].
@@ -154,6 +158,15 @@ pub struct CompactionOptions {
     /// not be remapped during this compaction operation. Instead, the fragment reuse index
     /// is updated and will be used to perform remapping later.
     pub defer_index_remap: bool,
+    /// How to order rows in the rewritten fragments. Defaults to
+    /// [`CompactionOrdering::Preserve`], which keeps the existing row order.
+    ///
+    /// Sorting or clustering by columns that are commonly filtered on makes
+    /// fragment and page statistics more selective. Indices stay valid: row
+    /// address changes are remapped (or recorded in the fragment reuse index
+    /// when `defer_index_remap` is set) just like any other compaction.
+    #[serde(default)]
+    pub ordering: CompactionOrdering,
 }
 
 impl Default for CompactionOptions {
@@ -168,6 +181,7 @@ impl Default for CompactionOptions {
             max_bytes_per_file: None,
             batch_size: None,
             defer_index_remap: false,
+            ordering: CompactionOrdering::default(),
         }
     }
 }
@@ -211,7 +225,8 @@ impl AddAssign for CompactionMetrics {
 ///  * Removes dropped columns from fragments.
 ///  * Merges fragments that are too small.
 ///
-/// This method tries to preserve the insertion order of rows in the dataset.
+/// This method tries to preserve the insertion order of rows in the dataset,
+/// unless a different [`CompactionOptions::ordering`] is requested.
 ///
 /// If no compaction is needed, this method will not make a new version of the table.
 pub async fn compact_files(
@@ -468,6 +483,8 @@ pub async fn plan_compaction(
     dataset: &Dataset,
     options: &CompactionOptions,
 ) -> Result<CompactionPlan> {
+    options.ordering.validate(dataset)?;
+
     // get_fragments should be returning fragments in sorted order (by id)
     // and fragment ids should be unique
     debug_assert!(
@@ -496,6 +513,11 @@ pub async fn plan_compaction(
             .collect::<Vec<_>>()
     };
 
+    // Rewriting a fragment of a partitioned dataset routes its rows by partition, so
+    // fragments that may span several partitions can not be compacted without
+    // reordering their rows.
+    let is_partitioned = !dataset.partition_columns()?.is_empty();
+
     let mut candidate_bins: Vec<CandidateBin> = Vec::new();
     let mut current_bin: Option<CandidateBin> = None;
     let mut i = 0;
@@ -503,7 +525,9 @@ pub async fn plan_compaction(
     while let Some(res) = fragment_metrics.next().await {
         let (fragment, metrics) = res?;
 
-        let candidacy = if options.materialize_deletions
+        let candidacy = if is_partitioned && fragment.partition_values.is_none() {
+            None
+        } else if options.materialize_deletions
             && metrics.deletion_percentage() > options.materialize_deletions_threshold
         {
             Some(CompactionCandidacy::CompactItself)
@@ -532,15 +556,18 @@ pub async fn plan_compaction(
             }
             (Some(candidacy), Some(bin)) => {
                 // We cannot mix "indexed" and "non-indexed" fragments and so we only consider
-                // the existing bin if it contains the same indices
-                if bin.indices == indices {
+                // the existing bin if it contains the same indices.  Likewise, fragments of
+                // different partitions must not be merged.
+                if bin.indices == indices
+                    && bin.fragments[0].partition_values == fragment.partition_values
+                {
                     // Add to current bin
                     bin.fragments.push(fragment);
                     bin.pos_range.end += 1;
                     bin.candidacy.push(candidacy);
                     bin.row_counts.push(metrics.num_rows());
                 } else {
-                    // Index set is different.  Complete previous bin and start new one
+                    // Index set or partition is different.  Complete previous bin and start new one
                     candidate_bins.push(current_bin.take().unwrap());
                     current_bin = Some(CandidateBin {
                         fragments: vec![fragment],
@@ -597,6 +624,11 @@ pub struct RewriteResult {
     /// in the form of serialized RoaringTreemap
     /// Only set when index remap is deferred after compaction
     pub changed_row_addrs: Option<Vec<u8>>,
+    /// The position in the new fragments that each of the changed row
+    /// addresses (in sorted order) was written to.
+    /// Only set when index remap is deferred and the rows were reordered
+    #[serde(default)]
+    pub new_row_positions: Option<Vec<u32>>,
 }
 
 async fn reserve_fragment_ids(
@@ -653,6 +685,7 @@ async fn rewrite_files(
             original_fragments: task.fragments,
             row_id_map: None,
             changed_row_addrs: None,
+            new_row_positions: None,
         });
     }
 
@@ -688,15 +721,33 @@ async fn rewrite_files(
     scanner
         .with_fragments(fragments.clone())
         .scan_in_order(true);
-    let (row_ids, reader) = if needs_remapping {
-        let row_ids = Arc::new(RwLock::new(RoaringTreemap::new()));
+    let reorder = !options.ordering.is_preserve();
+    if needs_remapping {
         scanner.with_row_address();
-        let data = SendableRecordBatchStream::from(scanner.try_into_stream().await?);
+    } else if reorder {
+        // Stable row ids need to follow their rows into the new order
+        scanner.with_row_id();
+    }
+    let data = SendableRecordBatchStream::from(scanner.try_into_stream().await?);
+    let (row_ids, written_row_ids, reader) = if reorder {
+        log::info!(
+            "Compaction task {}: reordering rows by {:?}",
+            task_id,
+            options.ordering
+        );
+        let data =
+            clustering::reorder_stream(dataset.as_ref(), &fragments, &options.ordering, data)
+                .await?;
+        let written_row_ids = Arc::new(RwLock::new(Vec::with_capacity(num_rows as usize)));
+        let column = if needs_remapping { ROW_ADDR } else { ROW_ID };
+        let data = make_ordered_capture_stream(column, written_row_ids.clone(), data)?;
+        (None, Some(written_row_ids), data)
+    } else if needs_remapping {
+        let row_ids = Arc::new(RwLock::new(RoaringTreemap::new()));
         let data_no_row_ids = make_rowaddr_capture_stream(row_ids.clone(), data)?;
-        (Some(row_ids), data_no_row_ids)
+        (Some(row_ids), None, data_no_row_ids)
     } else {
-        let data = SendableRecordBatchStream::from(scanner.try_into_stream().await?);
-        (None, data)
+        (None, None, data)
     };
 
     let mut rows_read = 0;
@@ -742,11 +793,29 @@ async fn rewrite_files(
 
     log::info!("Compaction task {}: file written", task_id);
 
-    let (row_id_map, changed_row_addrs) = if let Some(row_ids) = row_ids {
-        let row_ids = Arc::try_unwrap(row_ids)
+    let written_row_ids = written_row_ids.map(|written_row_ids| {
+        Arc::try_unwrap(written_row_ids)
             .expect("Row ids lock still owned")
             .into_inner()
-            .expect("Row ids mutex still locked");
+            .expect("Row ids mutex still locked")
+    });
+
+    let (row_id_map, changed_row_addrs, new_row_positions) = if needs_remapping {
+        // When rows were reordered we also need to know where each old row went
+        let (row_ids, new_row_positions) = match (row_ids, written_row_ids) {
+            (_, Some(written_row_addrs)) => {
+                let (row_ids, positions) = remapping::sort_row_addrs(written_row_addrs);
+                (row_ids, Some(positions))
+            }
+            (Some(row_ids), None) => {
+                let row_ids = Arc::try_unwrap(row_ids)
+                    .expect("Row ids lock still owned")
+                    .into_inner()
+                    .expect("Row ids mutex still locked");
+                (row_ids, None)
+            }
+            (None, None) => unreachable!("Row addresses are always captured when remapping"),
+        };
 
         log::info!(
             "Compaction task {}: reserving fragment ids and transposing row ids",
@@ -757,22 +826,38 @@ async fn rewrite_files(
         if options.defer_index_remap {
             let mut changed_row_addrs = Vec::with_capacity(row_ids.serialized_size());
             row_ids.serialize_into(&mut changed_row_addrs)?;
-            (None, Some(changed_row_addrs))
+            (None, Some(changed_row_addrs), new_row_positions)
         } else {
-            let row_id_map = remapping::transpose_row_ids(row_ids, &fragments, &new_fragments);
-            (Some(row_id_map), None)
+            let row_id_map = match new_row_positions {
+                Some(positions) => remapping::transpose_reordered_row_ids(
+                    row_ids,
+                    &positions,
+                    &fragments,
+                    &new_fragments,
+                ),
+                None => remapping::transpose_row_ids(row_ids, &fragments, &new_fragments),
+            };
+            (Some(row_id_map), None, None)
         }
     } else {
-        log::info!("Compaction task {}: rechunking stable row ids", task_id);
-        rechunk_stable_row_ids(dataset.as_ref(), &mut new_fragments, &fragments).await?;
+        if let Some(written_row_ids) = written_row_ids {
+            log::info!(
+                "Compaction task {}: assigning reordered stable row ids",
+                task_id
+            );
+            assign_stable_row_ids(&mut new_fragments, &written_row_ids);
+        } else {
+            log::info!("Compaction task {}: rechunking stable row ids", task_id);
+            rechunk_stable_row_ids(dataset.as_ref(), &mut new_fragments, &fragments).await?;
+        }
 
         if options.defer_index_remap {
             let no_addrs = RoaringTreemap::new();
             let mut serialized_no_addrs = Vec::with_capacity(no_addrs.serialized_size());
             no_addrs.serialize_into(&mut serialized_no_addrs)?;
-            (None, Some(serialized_no_addrs))
+            (None, Some(serialized_no_addrs), None)
         } else {
-            (Some(HashMap::new()), None)
+            (Some(HashMap::new()), None, None)
         }
     };
 
@@ -797,6 +882,7 @@ async fn rewrite_files(
         original_fragments: task.fragments,
         row_id_map,
         changed_row_addrs,
+        new_row_positions,
     })
 }
 
@@ -861,6 +947,28 @@ async fn rechunk_stable_row_ids(
     Ok(())
 }
 
+/// Give the new fragments the stable row ids of their rows, which were written
+/// in the order of `written_row_ids`.
+fn assign_stable_row_ids(new_fragments: &mut [Fragment], written_row_ids: &[u64]) {
+    debug_assert_eq!(
+        written_row_ids.len() as u64,
+        new_fragments
+            .iter()
+            .map(|frag| frag.physical_rows.unwrap() as u64)
+            .sum::<u64>()
+    );
+
+    let mut offset = 0;
+    for fragment in new_fragments.iter_mut() {
+        let num_rows = fragment.physical_rows.unwrap();
+        let sequence = RowIdSequence::from(&written_row_ids[offset..offset + num_rows]);
+        offset += num_rows;
+        // TODO: if large enough, serialize to separate file
+        let serialized = lance_table::rowids::write_row_ids(&sequence);
+        fragment.row_id_meta = Some(RowIdMeta::Inline(serialized));
+    }
+}
+
 /// Commit the results of file compaction.
 ///
 /// It is not required that all tasks are passed to this method. If some failed,
@@ -901,6 +1009,7 @@ pub async fn commit_compaction(
                 changed_row_addrs: task.changed_row_addrs.unwrap(),
                 old_frags: task.original_fragments.iter().map(|f| f.into()).collect(),
                 new_frags: task.new_fragments.iter().map(|f| f.into()).collect(),
+                new_row_positions: task.new_row_positions.unwrap_or_default(),
             });
 
             task.new_fragments.iter().for_each(|frag| {
@@ -1019,6 +1128,7 @@ mod tests {
             deletion_file: None,
             row_id_meta: None,
             physical_rows: Some(0),
+            partition_values: None,
         };
         let single_bin = CandidateBin {
             fragments: vec![fragment.clone()],
@@ -3417,4 +3527,179 @@ mod tests {
             plan
         );
     }
+
+    /// 4,000 rows in 8 fragments where `x` counts down and `y` cycles through
+    /// 0..50, so ordering by either column moves most rows.
+    async fn write_clustering_dataset(enable_move_stable_row_ids: bool) -> Dataset {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("x", DataType::Int64, false),
+            Field::new("y", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from_iter_values((0..4000).rev())),
+                Arc::new(Int64Array::from_iter_values((0..4000).map(|i| i % 50))),
+            ],
+        )
+        .unwrap();
+        Dataset::write(
+            RecordBatchIterator::new(vec![Ok(batch)], schema),
+            &format!("memory://test/clustered-{}", Uuid::new_v4()),
+            Some(WriteParams {
+                max_rows_per_file: 500,
+                enable_move_stable_row_ids,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn scan_clustering_dataset(dataset: &Dataset) -> (Vec<i64>, Vec<i64>, Vec<u64>) {
+        use arrow_array::cast::AsArray;
+        use arrow_array::types::UInt64Type;
+
+        let batch = dataset.scan().with_row_id().try_into_batch().await.unwrap();
+        (
+            batch["x"].as_primitive::<Int64Type>().values().to_vec(),
+            batch["y"].as_primitive::<Int64Type>().values().to_vec(),
+            batch[ROW_ID].as_primitive::<UInt64Type>().values().to_vec(),
+        )
+    }
+
+    async fn check_x_lookup(dataset: &Dataset, x: i64, expected_y: Option<i64>) {
+        let mut scanner = dataset.scan();
+        scanner.filter(&format!("x = {}", x)).unwrap();
+        let plan = scanner.explain_plan(false).await.unwrap();
+        assert!(plan.contains("ScalarIndexQuery"), "{}", plan);
+
+        let batch = scanner.try_into_batch().await.unwrap();
+        let ys = batch["y"]
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .values()
+            .to_vec();
+        assert_eq!(ys, expected_y.into_iter().collect::<Vec<_>>(), "x = {}", x);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_compact_sorted(#[values(false, true)] defer_index_remap: bool) {
+        let mut dataset = write_clustering_dataset(false).await;
+        dataset
+            .create_index(
+                &["x"],
+                IndexType::BTree,
+                Some("x_idx".into()),
+                &ScalarIndexParams::default(),
+                false,
+            )
+            .await
+            .unwrap();
+        // Deleted rows must still be mapped to nothing after reordering
+        dataset.delete("x % 10 = 3").await.unwrap();
+
+        let options = CompactionOptions {
+            target_rows_per_fragment: 10_000,
+            defer_index_remap,
+            ordering: CompactionOrdering::Sort(vec!["x".into()]),
+            ..Default::default()
+        };
+        let metrics = compact_files(&mut dataset, options, None).await.unwrap();
+        assert_eq!(metrics.fragments_removed, 8);
+        assert_eq!(metrics.fragments_added, 1);
+
+        let (xs, ys, _) = scan_clustering_dataset(&dataset).await;
+        assert_eq!(xs.len(), 3600);
+        assert!(xs.windows(2).all(|w| w[0] < w[1]));
+        assert!(xs.iter().zip(&ys).all(|(x, y)| (3999 - x) % 50 == *y));
+
+        if defer_index_remap {
+            let indices = dataset.load_indices().await.unwrap();
+            assert!(indices.iter().any(|idx| idx.name == FRAG_REUSE_INDEX_NAME));
+        }
+        for x in [0, 1, 1234, 3998, 3999] {
+            check_x_lookup(&dataset, x, Some((3999 - x) % 50)).await;
+        }
+        check_x_lookup(&dataset, 1233, None).await;
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_compact_clustered_stable_row_ids(
+        #[values(
+            CompactionOrdering::ZOrder(vec!["x".into(), "y".into()]),
+            CompactionOrdering::Hilbert(vec!["x".into(), "y".into()])
+        )]
+        ordering: CompactionOrdering,
+    ) {
+        let mut dataset = write_clustering_dataset(true).await;
+        dataset
+            .create_index(
+                &["x"],
+                IndexType::BTree,
+                Some("x_idx".into()),
+                &ScalarIndexParams::default(),
+                false,
+            )
+            .await
+            .unwrap();
+        let (xs, ys, row_ids) = scan_clustering_dataset(&dataset).await;
+        let mut rows_before = row_ids
+            .into_iter()
+            .zip(xs.into_iter().zip(ys))
+            .collect::<Vec<_>>();
+        rows_before.sort();
+
+        let options = CompactionOptions {
+            target_rows_per_fragment: 10_000,
+            ordering,
+            ..Default::default()
+        };
+        compact_files(&mut dataset, options, None).await.unwrap();
+        assert_eq!(dataset.get_fragments().len(), 1);
+
+        // Every row keeps its stable row id
+        let (xs, ys, row_ids) = scan_clustering_dataset(&dataset).await;
+        let mut rows_after = row_ids
+            .into_iter()
+            .zip(xs.iter().copied().zip(ys.iter().copied()))
+            .collect::<Vec<_>>();
+        rows_after.sort();
+        assert_eq!(rows_before, rows_after);
+
+        // The first half of the curve covers half of the range of one column
+        let first_half_max_x = xs[..2000].iter().max().unwrap();
+        let first_half_max_y = ys[..2000].iter().max().unwrap();
+        assert!(
+            *first_half_max_x < 2000 || *first_half_max_y < 25,
+            "rows are not clustered: max x {}, max y {}",
+            first_half_max_x,
+            first_half_max_y
+        );
+
+        for x in [0, 1234, 3999] {
+            check_x_lookup(&dataset, x, Some((3999 - x) % 50)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compact_ordering_validation() {
+        let mut dataset = write_clustering_dataset(false).await;
+        for ordering in [
+            CompactionOrdering::Sort(vec![]),
+            CompactionOrdering::ZOrder(vec!["x".into(), "missing".into()]),
+        ] {
+            let options = CompactionOptions {
+                ordering,
+                ..Default::default()
+            };
+            let err = compact_files(&mut dataset, options, None)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::InvalidInput { .. }), "{}", err);
+        }
+    }
 }
@@ -0,0 +1,506 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Row ordering applied while rewriting fragments during compaction.
+//!
+//! By default compaction keeps rows in the order they were written. Sorting or
+//! clustering rows by frequently filtered columns instead makes the min/max
+//! statistics of the rewritten fragments and pages much tighter, so range
+//! filters can skip more data.
+//!
+//! Multi-column clustering maps each row to a point on a space-filling curve
+//! (Z-order or Hilbert). Each column value is first turned into a `u64` whose
+//! unsigned order matches the column's order, then scaled to the range of
+//! values found in the fragments being rewritten so that every column gets
+//! the same number of bits on the curve.
+
+use std::sync::Arc;
+
+use arrow::compute::cast;
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, Int64Type, UInt64Type};
+use arrow_array::{ArrayRef, RecordBatch, UInt64Array};
+use arrow_schema::{DataType, Field as ArrowField, SortOptions};
+use datafusion::error::Result as DFResult;
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::expressions;
+use datafusion::physical_plan::sorts::sort::SortExec;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use datafusion_physical_expr::LexOrdering;
+use futures::{StreamExt, TryStreamExt};
+use lance_core::{Error, Result};
+use lance_datafusion::exec::{execute_plan, LanceExecutionOptions, OneShotExec};
+use lance_table::format::Fragment;
+use serde::{Deserialize, Serialize};
+use snafu::location;
+
+use crate::Dataset;
+
+/// Name of the temporary column holding the curve position of each row.
+const CLUSTER_KEY_COLUMN: &str = "_cluster_key";
+
+/// How rows are ordered when fragments are rewritten by compaction.
+///
+/// Ordering is applied within each compaction task, so only the fragments
+/// that are selected for compaction are reordered.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompactionOrdering {
+    /// Keep rows in the order they are read from the original fragments.
+    #[default]
+    Preserve,
+    /// Sort rows by the given columns, in ascending order with nulls first.
+    Sort(Vec<String>),
+    /// Cluster rows along a Z-order (Morton) curve over the given columns.
+    ZOrder(Vec<String>),
+    /// Cluster rows along a Hilbert curve over the given columns.
+    ///
+    /// This is more expensive to compute than Z-order but keeps neighboring
+    /// rows closer together, which generally gives tighter statistics.
+    Hilbert(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Curve {
+    ZOrder,
+    Hilbert,
+}
+
+impl CompactionOrdering {
+    pub fn is_preserve(&self) -> bool {
+        matches!(self, Self::Preserve)
+    }
+
+    /// The columns rows are ordered by.
+    pub fn columns(&self) -> &[String] {
+        match self {
+            Self::Preserve => &[],
+            Self::Sort(columns) | Self::ZOrder(columns) | Self::Hilbert(columns) => columns,
+        }
+    }
+
+    fn curve(&self) -> Option<Curve> {
+        match self {
+            Self::ZOrder(_) => Some(Curve::ZOrder),
+            Self::Hilbert(_) => Some(Curve::Hilbert),
+            _ => None,
+        }
+    }
+
+    /// Check that the ordering can be applied to the given dataset.
+    pub fn validate(&self, dataset: &Dataset) -> Result<()> {
+        if self.is_preserve() {
+            return Ok(());
+        }
+        let columns = self.columns();
+        if columns.is_empty() {
+            return Err(Error::invalid_input(
+                "Compaction ordering requires at least one column",
+                location!(),
+            ));
+        }
+        if self.curve().is_some() && columns.len() > 64 {
+            return Err(Error::invalid_input(
+                format!(
+                    "Clustering supports at most 64 columns, got {}",
+                    columns.len()
+                ),
+                location!(),
+            ));
+        }
+        for column in columns {
+            let field = dataset
+                .schema()
+                .fields
+                .iter()
+                .find(|f| &f.name == column)
+                .ok_or_else(|| {
+                    Error::invalid_input(
+                        format!(
+                            "Compaction ordering column '{}' is not a top-level column of the dataset",
+                            column
+                        ),
+                        location!(),
+                    )
+                })?;
+            let data_type = field.data_type();
+            let supported = if self.curve().is_some() {
+                is_clusterable(&data_type)
+            } else {
+                data_type.is_primitive() || is_clusterable(&data_type)
+            };
+            if !supported {
+                return Err(Error::invalid_input(
+                    format!(
+                        "Column '{}' has type {} which cannot be used to order rows during compaction",
+                        column, data_type
+                    ),
+                    location!(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_clusterable(data_type: &DataType) -> bool {
+    data_type.is_integer()
+        || data_type.is_floating()
+        || matches!(
+            data_type,
+            DataType::Boolean
+                | DataType::Date32
+                | DataType::Date64
+                | DataType::Timestamp(_, _)
+                | DataType::Utf8
+                | DataType::LargeUtf8
+                | DataType::Binary
+                | DataType::LargeBinary
+        )
+}
+
+/// Map each value of `array` to a `u64` whose unsigned order matches the
+/// order of the values. Strings and binaries are ordered by their first 8 bytes.
+fn order_preserving_keys(array: &ArrayRef) -> Result<Vec<Option<u64>>> {
+    const SIGN_BIT: u64 = 1 << 63;
+    let keys = match array.data_type() {
+        DataType::Boolean => array
+            .as_boolean()
+            .iter()
+            .map(|v| v.map(u64::from))
+            .collect(),
+        DataType::Date32 => {
+            let days = cast(array, &DataType::Int32)?;
+            return order_preserving_keys(&days);
+        }
+        dt if dt.is_signed_integer() || dt.is_temporal() => cast(array, &DataType::Int64)?
+            .as_primitive::<Int64Type>()
+            .iter()
+            .map(|v| v.map(|v| (v as u64) ^ SIGN_BIT))
+            .collect(),
+        dt if dt.is_unsigned_integer() => cast(array, &DataType::UInt64)?
+            .as_primitive::<UInt64Type>()
+            .iter()
+            .collect(),
+        dt if dt.is_floating() => cast(array, &DataType::Float64)?
+            .as_primitive::<Float64Type>()
+            .iter()
+            .map(|v| {
+                v.map(|v| {
+                    let bits = v.to_bits();
+                    if bits & SIGN_BIT != 0 {
+                        !bits
+                    } else {
+                        bits | SIGN_BIT
+                    }
+                })
+            })
+            .collect(),
+        DataType::Utf8 => array
+            .as_string::<i32>()
+            .iter()
+            .map(|v| v.map(|v| prefix_key(v.as_bytes())))
+            .collect(),
+        DataType::LargeUtf8 => array
+            .as_string::<i64>()
+            .iter()
+            .map(|v| v.map(|v| prefix_key(v.as_bytes())))
+            .collect(),
+        DataType::Binary => array
+            .as_binary::<i32>()
+            .iter()
+            .map(|v| v.map(prefix_key))
+            .collect(),
+        DataType::LargeBinary => array
+            .as_binary::<i64>()
+            .iter()
+            .map(|v| v.map(prefix_key))
+            .collect(),
+        dt => {
+            return Err(Error::invalid_input(
+                format!("Cannot cluster rows by a column of type {}", dt),
+                location!(),
+            ))
+        }
+    };
+    Ok(keys)
+}
+
+fn prefix_key(bytes: &[u8]) -> u64 {
+    let mut prefix = [0_u8; 8];
+    let len = bytes.len().min(8);
+    prefix[..len].copy_from_slice(&bytes[..len]);
+    u64::from_be_bytes(prefix)
+}
+
+/// Interleave the bits of `coords`, taking the most significant bit of each
+/// coordinate first.
+fn interleave_bits(coords: &[u64], bits: u32) -> u64 {
+    let mut key = 0_u64;
+    for bit in (0..bits).rev() {
+        for coord in coords {
+            key = (key << 1) | ((coord >> bit) & 1);
+        }
+    }
+    key
+}
+
+/// Convert coordinates into the "transposed" form of their Hilbert index, so
+/// that interleaving the result gives the position along the Hilbert curve.
+///
+/// This is John Skilling's algorithm from "Programming the Hilbert curve"
+/// (AIP Conference Proceedings 707, 2004).
+fn hilbert_transpose(coords: &mut [u64], bits: u32) {
+    let n = coords.len();
+    let m = 1_u64 << (bits - 1);
+
+    // Inverse undo
+    let mut q = m;
+    while q > 1 {
+        let p = q - 1;
+        for i in 0..n {
+            if coords[i] & q != 0 {
+                coords[0] ^= p;
+            } else {
+                let t = (coords[0] ^ coords[i]) & p;
+                coords[0] ^= t;
+                coords[i] ^= t;
+            }
+        }
+        q >>= 1;
+    }
+
+    // Gray encode
+    for i in 1..n {
+        coords[i] ^= coords[i - 1];
+    }
+    let mut t = 0;
+    let mut q = m;
+    while q > 1 {
+        if coords[n - 1] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+    for coord in coords.iter_mut() {
+        *coord ^= t;
+    }
+}
+
+/// Computes the curve position of rows given the range of each column.
+#[derive(Debug, Clone)]
+struct CurveKeys {
+    curve: Curve,
+    columns: Vec<String>,
+    /// The (min, max) order-preserving key of each column, or `None` if the
+    /// column only contains nulls.
+    ranges: Vec<Option<(u64, u64)>>,
+    bits: u32,
+}
+
+impl CurveKeys {
+    /// Scan the clustering columns of the fragments to find their ranges.
+    async fn try_new(
+        dataset: &Dataset,
+        fragments: &[Fragment],
+        columns: &[String],
+        curve: Curve,
+    ) -> Result<Self> {
+        let mut scanner = dataset.scan();
+        scanner
+            .with_fragments(fragments.to_vec())
+            .project(columns)?;
+        let mut stream = scanner.try_into_stream().await?;
+
+        let mut ranges: Vec<Option<(u64, u64)>> = vec![None; columns.len()];
+        while let Some(batch) = stream.try_next().await? {
+            for (range, column) in ranges.iter_mut().zip(batch.columns()) {
+                for key in order_preserving_keys(column)?.into_iter().flatten() {
+                    *range = match *range {
+                        None => Some((key, key)),
+                        Some((min, max)) => Some((min.min(key), max.max(key))),
+                    };
+                }
+            }
+        }
+
+        Ok(Self {
+            curve,
+            columns: columns.to_vec(),
+            ranges,
+            bits: 64 / columns.len() as u32,
+        })
+    }
+
+    /// Scale a key into `[0, 2^bits)` given the range of its column. Nulls
+    /// are placed at the start of the curve.
+    fn normalize(&self, key: Option<u64>, range: Option<(u64, u64)>) -> u64 {
+        match (key, range) {
+            (Some(key), Some((min, max))) if max > min => {
+                let max_coord = (1_u128 << self.bits) - 1;
+                let offset = key.clamp(min, max) - min;
+                (offset as u128 * max_coord / (max - min) as u128) as u64
+            }
+            _ => 0,
+        }
+    }
+
+    fn compute(&self, batch: &RecordBatch) -> Result<UInt64Array> {
+        let columns = self
+            .columns
+            .iter()
+            .map(|name| {
+                let column = batch.column_by_name(name).ok_or_else(|| Error::Internal {
+                    message: format!("Clustering column '{}' missing from scan", name),
+                    location: location!(),
+                })?;
+                order_preserving_keys(column)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut coords = vec![0_u64; columns.len()];
+        let keys = (0..batch.num_rows())
+            .map(|row| {
+                for (i, column) in columns.iter().enumerate() {
+                    coords[i] = self.normalize(column[row], self.ranges[i]);
+                }
+                if self.curve == Curve::Hilbert && self.bits > 1 {
+                    hilbert_transpose(&mut coords, self.bits);
+                }
+                interleave_bits(&coords, self.bits)
+            })
+            .collect::<Vec<_>>();
+        Ok(UInt64Array::from(keys))
+    }
+}
+
+/// Reorder the rows of `data` (read from `fragments`) according to `ordering`.
+///
+/// The sort spills to disk if needed. Any row metadata columns in `data` are
+/// carried through in the new order.
+pub(super) async fn reorder_stream(
+    dataset: &Dataset,
+    fragments: &[Fragment],
+    ordering: &CompactionOrdering,
+    data: SendableRecordBatchStream,
+) -> Result<SendableRecordBatchStream> {
+    let data = match ordering.curve() {
+        Some(curve) => {
+            let keys = CurveKeys::try_new(dataset, fragments, ordering.columns(), curve).await?;
+            let mut fields = data.schema().fields().to_vec();
+            fields.push(Arc::new(ArrowField::new(
+                CLUSTER_KEY_COLUMN,
+                DataType::UInt64,
+                false,
+            )));
+            let schema = Arc::new(arrow_schema::Schema::new(fields));
+            let stream_schema = schema.clone();
+            let stream = data.map(move |batch| -> DFResult<RecordBatch> {
+                let batch = batch?;
+                let key = keys.compute(&batch)?;
+                let mut columns = batch.columns().to_vec();
+                columns.push(Arc::new(key));
+                Ok(RecordBatch::try_new(stream_schema.clone(), columns)?)
+            });
+            Box::pin(RecordBatchStreamAdapter::new(schema, stream)) as SendableRecordBatchStream
+        }
+        None => data,
+    };
+
+    let input = Arc::new(OneShotExec::new(data));
+    let schema = input.schema();
+    let sort_columns = match ordering.curve() {
+        Some(_) => vec![CLUSTER_KEY_COLUMN.to_string()],
+        None => ordering.columns().to_vec(),
+    };
+    let sort_exprs = sort_columns
+        .iter()
+        .map(|column| {
+            Ok(PhysicalSortExpr {
+                expr: expressions::col(column, schema.as_ref())?,
+                options: SortOptions::default(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let plan = Arc::new(SortExec::new(LexOrdering::new(sort_exprs), input));
+    let sorted = execute_plan(
+        plan,
+        LanceExecutionOptions {
+            use_spilling: true,
+            ..Default::default()
+        },
+    )?;
+
+    if ordering.curve().is_none() {
+        return Ok(sorted);
+    }
+    // Drop the curve position column again
+    let schema = sorted.schema();
+    let keep = (0..schema.fields().len() - 1).collect::<Vec<_>>();
+    let output_schema = Arc::new(schema.project(&keep)?);
+    let stream = sorted.map(move |batch| -> DFResult<RecordBatch> { Ok(batch?.project(&keep)?) });
+    Ok(Box::pin(RecordBatchStreamAdapter::new(
+        output_schema,
+        stream,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow_array::{Float32Array, Int32Array, StringArray};
+
+    #[test]
+    fn test_order_preserving_keys() {
+        let ints: ArrayRef = Arc::new(Int32Array::from(vec![Some(-5), None, Some(0), Some(7)]));
+        let keys = order_preserving_keys(&ints).unwrap();
+        assert_eq!(keys[1], None);
+        assert!(keys[0].unwrap() < keys[2].unwrap());
+        assert!(keys[2].unwrap() < keys[3].unwrap());
+
+        let floats: ArrayRef = Arc::new(Float32Array::from(vec![-2.5, -0.5, 0.0, 3.25]));
+        let keys = order_preserving_keys(&floats)
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect::<Vec<_>>();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+
+        let strings: ArrayRef = Arc::new(StringArray::from(vec!["a", "ab", "b", "ba"]));
+        let keys = order_preserving_keys(&strings)
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect::<Vec<_>>();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_z_order_interleaving() {
+        // Bits are taken as x1 y1 x0 y0
+        assert_eq!(interleave_bits(&[0b01, 0b10], 2), 0b0110);
+        assert_eq!(interleave_bits(&[0b11, 0b00], 2), 0b1010);
+    }
+
+    #[test]
+    fn test_hilbert_curve_is_continuous() {
+        // Walking the curve in order should only ever move to an adjacent cell.
+        let bits = 3;
+        let side = 1_u64 << bits;
+        let mut cells = Vec::new();
+        for x in 0..side {
+            for y in 0..side {
+                let mut coords = [x, y];
+                hilbert_transpose(&mut coords, bits);
+                cells.push((interleave_bits(&coords, bits), x, y));
+            }
+        }
+        cells.sort();
+        for (i, window) in cells.windows(2).enumerate() {
+            assert_eq!(window[0].0, i as u64);
+            let dist = window[0].1.abs_diff(window[1].1) + window[0].2.abs_diff(window[1].2);
+            assert_eq!(dist, 1, "{:?}", window);
+        }
+    }
+}
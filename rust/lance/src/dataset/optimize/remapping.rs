@@ -158,6 +158,68 @@ pub fn transpose_row_ids_from_digest(
     row_ids: RoaringTreemap,
     old_fragments: &Vec<FragDigest>,
     new_fragments: &[FragDigest],
+) -> HashMap<u64, Option<u64>> {
+    transpose_row_ids_impl(row_ids, None, old_fragments, new_fragments)
+}
+
+/// Like [transpose_row_ids], but for a rewrite that changed the order of the
+/// rows. The i-th row address in `row_ids` was written as the
+/// `new_row_positions[i]`-th row of the new fragments.
+pub fn transpose_reordered_row_ids(
+    row_ids: RoaringTreemap,
+    new_row_positions: &[u32],
+    old_fragments: &[Fragment],
+    new_fragments: &[Fragment],
+) -> HashMap<u64, Option<u64>> {
+    let old_frag_digests: Vec<FragDigest> = old_fragments.iter().map(|frag| frag.into()).collect();
+    let new_frag_digests: Vec<FragDigest> = new_fragments.iter().map(|frag| frag.into()).collect();
+    transpose_reordered_row_ids_from_digest(
+        row_ids,
+        new_row_positions,
+        &old_frag_digests,
+        &new_frag_digests,
+    )
+}
+
+pub fn transpose_reordered_row_ids_from_digest(
+    row_ids: RoaringTreemap,
+    new_row_positions: &[u32],
+    old_fragments: &Vec<FragDigest>,
+    new_fragments: &[FragDigest],
+) -> HashMap<u64, Option<u64>> {
+    transpose_row_ids_impl(
+        row_ids,
+        Some(new_row_positions),
+        old_fragments,
+        new_fragments,
+    )
+}
+
+/// Given the row addresses of a rewrite in the order the rows were written,
+/// return them as a treemap along with the write position of each address
+/// in the treemap's (sorted) order.
+pub fn sort_row_addrs(written_row_addrs: Vec<u64>) -> (RoaringTreemap, Vec<u32>) {
+    let mut addrs_with_positions = written_row_addrs
+        .into_iter()
+        .enumerate()
+        .map(|(position, addr)| (addr, position as u32))
+        .collect::<Vec<_>>();
+    addrs_with_positions.sort_unstable();
+    let positions = addrs_with_positions
+        .iter()
+        .map(|(_, position)| *position)
+        .collect();
+    let row_addrs =
+        RoaringTreemap::from_sorted_iter(addrs_with_positions.into_iter().map(|(addr, _)| addr))
+            .expect("Row addresses are sorted");
+    (row_addrs, positions)
+}
+
+fn transpose_row_ids_impl(
+    row_ids: RoaringTreemap,
+    new_row_positions: Option<&[u32]>,
+    old_fragments: &Vec<FragDigest>,
+    new_fragments: &[FragDigest],
 ) -> HashMap<u64, Option<u64>> {
     let new_ids = new_fragments.iter().flat_map(|frag| {
         (0..frag.physical_rows as u32).map(|offset| {
@@ -178,7 +240,17 @@ pub fn transpose_row_ids_from_digest(
     // The default hasher is designed to be resistance to DoS attacks, which is
     // more than we need for this use case.
     let mut mapping: HashMap<u64, Option<u64>> = HashMap::with_capacity(expected_size);
-    mapping.extend(row_ids.iter().zip(new_ids));
+    if let Some(new_row_positions) = new_row_positions {
+        let new_ids = new_ids.collect::<Vec<_>>();
+        mapping.extend(
+            row_ids
+                .iter()
+                .zip(new_row_positions)
+                .map(|(old_id, position)| (old_id, new_ids[*position as usize])),
+        );
+    } else {
+        mapping.extend(row_ids.iter().zip(new_ids));
+    }
     MissingIds::new(row_ids.into_iter(), old_fragments).for_each(|id| {
         mapping.insert(id, None);
     });
@@ -271,6 +343,7 @@ async fn remap_index(dataset: &mut Dataset, index_id: &Uuid) -> Result<()> {
                 index_details: curr_index_meta.index_details.clone(),
                 index_version: curr_index_meta.index_version,
                 created_at: curr_index_meta.created_at,
+                base_id: None,
             };
 
             let transaction = Transaction::new(
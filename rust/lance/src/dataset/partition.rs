@@ -0,0 +1,652 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Value-based (Hive-style) partitioning of datasets.
+//!
+//! A dataset may declare a list of partition columns when it is created (or
+//! overwritten). Writers then route rows so that every fragment only contains
+//! rows sharing the same values for those columns, and record those values in
+//! the fragment metadata ([`Fragment::partition_values`]). The scanner uses the
+//! recorded values to skip fragments that cannot match a filter, and
+//! [`super::WriteParams::dynamic_partition_overwrite`] uses them to replace only
+//! the partitions present in the new data.
+//!
+//! Fragments without partition values (e.g. written before partitioning was
+//! declared, or written by older versions of Lance) are always scanned.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::compute::cast;
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, ArrayRef, RecordBatch, StringArray, UInt32Array};
+use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema};
+use arrow_select::take::take_record_batch;
+use datafusion::logical_expr::utils::{conjunction, split_conjunction};
+use datafusion::logical_expr::Expr;
+use lance_core::datatypes::{Field, Schema};
+use lance_core::{Error, Result};
+use lance_datafusion::planner::Planner;
+use lance_table::format::{Fragment, Manifest, PartitionValues};
+use snafu::location;
+
+/// Table config key holding the partition columns of a dataset.
+///
+/// The value is a JSON array of top-level field ids, so that renaming a partition
+/// column does not change the partitioning of the dataset.
+pub const PARTITION_COLUMNS_CONFIG_KEY: &str = "lance.partition.columns";
+
+/// Get the ids of the partition fields declared in the manifest.
+fn partition_field_ids(manifest: &Manifest) -> Result<Vec<i32>> {
+    manifest
+        .config
+        .get(PARTITION_COLUMNS_CONFIG_KEY)
+        .map(|value| {
+            serde_json::from_str::<Vec<i32>>(value).map_err(|e| {
+                Error::invalid_input(
+                    format!("Invalid value for {}: {}", PARTITION_COLUMNS_CONFIG_KEY, e),
+                    location!(),
+                )
+            })
+        })
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+/// Get the partition fields declared in the manifest.
+///
+/// Returns no fields if any of the declared fields is no longer a top-level field
+/// of the schema (e.g. it was dropped), or no longer has a supported type. Such a
+/// dataset is treated as unpartitioned.
+pub(crate) fn partition_fields(manifest: &Manifest) -> Result<Vec<Field>> {
+    let fields = partition_field_ids(manifest)?
+        .into_iter()
+        .map(|id| {
+            manifest
+                .schema
+                .fields
+                .iter()
+                .find(|f| f.id == id && is_supported_partition_type(&f.data_type()))
+                .cloned()
+        })
+        .collect::<Option<Vec<_>>>();
+    Ok(fields.unwrap_or_default())
+}
+
+/// Get the names of the partition columns declared in the manifest.
+pub fn partition_columns(manifest: &Manifest) -> Result<Vec<String>> {
+    Ok(partition_fields(manifest)?
+        .into_iter()
+        .map(|field| field.name)
+        .collect())
+}
+
+/// Encode partition fields as a table config value.
+pub(crate) fn partition_columns_config_value(fields: &[Field]) -> String {
+    serde_json::to_string(&fields.iter().map(|field| field.id).collect::<Vec<_>>())
+        .expect("a list of integers is always serializable")
+}
+
+/// Remove the partition config of a manifest whose partition fields can no longer
+/// be resolved, e.g. because a partition column was dropped.
+///
+/// A dataset that was overwritten without its partition columns is stored with an
+/// empty list of partition fields, which is removed as well.
+pub(crate) fn retain_partition_config(manifest: &mut Manifest) {
+    if manifest.config.contains_key(PARTITION_COLUMNS_CONFIG_KEY)
+        && partition_fields(manifest).is_ok_and(|fields| fields.is_empty())
+    {
+        manifest.delete_config_keys(&[PARTITION_COLUMNS_CONFIG_KEY]);
+    }
+}
+
+/// Look up the partition fields by name in `schema`.
+///
+/// Returns an error if a column does not exist, is nested, or has a type that
+/// cannot be used for partitioning.
+pub(crate) fn resolve_partition_fields(schema: &Schema, columns: &[String]) -> Result<Vec<Field>> {
+    columns
+        .iter()
+        .map(|name| {
+            let field = schema
+                .fields
+                .iter()
+                .find(|f| &f.name == name)
+                .ok_or_else(|| {
+                    Error::invalid_input(
+                        format!(
+                            "Partition column '{}' is not a top-level column of the schema",
+                            name
+                        ),
+                        location!(),
+                    )
+                })?;
+            if !is_supported_partition_type(&field.data_type()) {
+                return Err(Error::invalid_input(
+                    format!(
+                        "Partition column '{}' has unsupported type {}",
+                        name,
+                        field.data_type()
+                    ),
+                    location!(),
+                ));
+            }
+            Ok(field.clone())
+        })
+        .collect()
+}
+
+/// Partition columns must have a lossless, stable string representation.
+fn is_supported_partition_type(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Boolean
+            | DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Utf8
+            | DataType::LargeUtf8
+            | DataType::Date32
+            | DataType::Date64
+    )
+}
+
+/// Split a batch into one batch per distinct combination of partition values.
+///
+/// The relative order of rows within each partition is preserved, and partitions
+/// are returned in the order they are first seen in the batch.
+pub(crate) fn partition_batch(
+    batch: &RecordBatch,
+    fields: &[Field],
+) -> Result<Vec<(PartitionValues, RecordBatch)>> {
+    let columns = fields
+        .iter()
+        .map(|field| {
+            let column = batch.column_by_name(&field.name).ok_or_else(|| {
+                Error::invalid_input(
+                    format!("Partition column '{}' is missing from the data", field.name),
+                    location!(),
+                )
+            })?;
+            Ok(cast(column, &DataType::Utf8)?)
+        })
+        .collect::<Result<Vec<ArrayRef>>>()?;
+    let columns = columns
+        .iter()
+        .map(|column| column.as_string::<i32>())
+        .collect::<Vec<_>>();
+
+    let mut partitions: Vec<(PartitionValues, Vec<u32>)> = Vec::new();
+    let mut positions: HashMap<Vec<Option<&str>>, usize> = HashMap::new();
+    for row in 0..batch.num_rows() {
+        let key = columns
+            .iter()
+            .map(|column| column.is_valid(row).then(|| column.value(row)))
+            .collect::<Vec<_>>();
+        let position = *positions.entry(key).or_insert_with_key(|key| {
+            let values = fields
+                .iter()
+                .zip(key)
+                .map(|(field, value)| (field.id, value.map(str::to_string)))
+                .collect();
+            partitions.push((values, Vec::new()));
+            partitions.len() - 1
+        });
+        partitions[position].1.push(row as u32);
+    }
+
+    if partitions.len() == 1 {
+        let (values, _) = partitions.pop().unwrap();
+        return Ok(vec![(values, batch.clone())]);
+    }
+
+    partitions
+        .into_iter()
+        .map(|(values, indices)| {
+            let indices = UInt32Array::from(indices);
+            Ok((values, take_record_batch(batch, &indices)?))
+        })
+        .collect()
+}
+
+/// Build a batch with one row per fragment holding its partition values.
+fn partition_values_batch(fields: &[Field], fragments: &[&Fragment]) -> Result<RecordBatch> {
+    // A missing value is treated as null
+    let schema = Arc::new(ArrowSchema::new(
+        fields
+            .iter()
+            .map(|field| ArrowField::from(field).with_nullable(true))
+            .collect::<Vec<_>>(),
+    ));
+    let columns = fields
+        .iter()
+        .map(|field| {
+            let values = fragments
+                .iter()
+                .map(|fragment| {
+                    fragment
+                        .partition_values
+                        .as_ref()
+                        .and_then(|values| values.get(&field.id))
+                        .and_then(|value| value.as_deref())
+                })
+                .collect::<StringArray>();
+            Ok(cast(&values, &field.data_type())?)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(RecordBatch::try_new(schema, columns)?)
+}
+
+/// Remove the fragments whose partition values can not satisfy `filter`.
+///
+/// Only the conjuncts of `filter` that reference nothing but partition columns
+/// are considered. Fragments without partition values are always kept.
+pub(crate) fn prune_fragments(
+    filter: &Expr,
+    fields: &[Field],
+    fragments: Vec<Fragment>,
+) -> Result<Vec<Fragment>> {
+    let partition_exprs = split_conjunction(filter)
+        .into_iter()
+        .filter(|expr| {
+            let columns = Planner::column_names_in_expr(expr);
+            !columns.is_empty()
+                && columns
+                    .iter()
+                    .all(|column| fields.iter().any(|field| &field.name == column))
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    let Some(partition_filter) = conjunction(partition_exprs) else {
+        return Ok(fragments);
+    };
+
+    let partitioned = fragments
+        .iter()
+        .filter(|fragment| fragment.partition_values.is_some())
+        .collect::<Vec<_>>();
+    if partitioned.is_empty() {
+        return Ok(fragments);
+    }
+
+    let batch = partition_values_batch(fields, &partitioned)?;
+    let planner = Planner::new(batch.schema());
+    let physical_filter = planner.create_physical_expr(&partition_filter)?;
+    let mask = physical_filter
+        .evaluate(&batch)?
+        .into_array(batch.num_rows())?;
+    let mask = mask.as_boolean_opt().ok_or_else(|| {
+        Error::invalid_input(
+            format!("The filter {} does not return a boolean", partition_filter),
+            location!(),
+        )
+    })?;
+
+    // Every row of a fragment shares the same partition values, so a null or false
+    // result means no row of the fragment can match.
+    let mut matches = mask.iter().map(|matched| matched.unwrap_or(false));
+    Ok(fragments
+        .into_iter()
+        .filter(|fragment| fragment.partition_values.is_none() || matches.next().unwrap())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow_array::types::Int32Type;
+    use arrow_array::{Date32Array, Int32Array, RecordBatchIterator};
+    use lance_datagen::{array, gen, BatchCount, RowCount};
+    use tempfile::tempdir;
+
+    use crate::dataset::{ColumnAlteration, Dataset, WriteMode, WriteParams};
+
+    fn make_batch(days: Vec<i32>, regions: Vec<&str>, values: Vec<i32>) -> RecordBatch {
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("date", DataType::Date32, false),
+            ArrowField::new("region", DataType::Utf8, true),
+            ArrowField::new("value", DataType::Int32, false),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Date32Array::from(days)),
+                Arc::new(StringArray::from(regions)),
+                Arc::new(Int32Array::from(values)),
+            ],
+        )
+        .unwrap()
+    }
+
+    async fn write(uri: &str, batch: RecordBatch, params: WriteParams) -> Dataset {
+        let schema = batch.schema();
+        let reader = RecordBatchIterator::new(vec![Ok(batch)], schema);
+        Dataset::write(reader, uri, Some(params)).await.unwrap()
+    }
+
+    #[test]
+    fn test_partition_batch() {
+        let batch = make_batch(
+            vec![1, 2, 1, 2, 1],
+            vec!["us", "us", "eu", "us", "us"],
+            vec![0, 1, 2, 3, 4],
+        );
+        let schema = Schema::try_from(batch.schema().as_ref()).unwrap();
+        let fields = resolve_partition_fields(&schema, &["date".into(), "region".into()]).unwrap();
+
+        let partitions = partition_batch(&batch, &fields).unwrap();
+        assert_eq!(partitions.len(), 3);
+        let values = partitions
+            .iter()
+            .map(|(_, batch)| batch["value"].as_primitive::<Int32Type>().values().to_vec())
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![vec![0, 4], vec![1, 3], vec![2]]);
+        assert_eq!(
+            partitions[0].0,
+            PartitionValues::from([
+                (fields[0].id, Some("1970-01-02".to_string())),
+                (fields[1].id, Some("us".to_string())),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_unsupported_partition_type() {
+        let schema = ArrowSchema::new(vec![ArrowField::new("x", DataType::Float32, false)]);
+        let schema = Schema::try_from(&schema).unwrap();
+        assert!(resolve_partition_fields(&schema, &["x".into()]).is_err());
+        assert!(resolve_partition_fields(&schema, &["y".into()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_partitioned_write_and_prune() {
+        let test_dir = tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+
+        let batch = make_batch(
+            vec![1, 2, 1, 2, 3],
+            vec!["us", "us", "eu", "us", "us"],
+            vec![0, 1, 2, 3, 4],
+        );
+        let dataset = write(
+            test_uri,
+            batch,
+            WriteParams {
+                partition_columns: Some(vec!["date".into(), "region".into()]),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        assert_eq!(dataset.partition_columns().unwrap(), vec!["date", "region"]);
+        assert_eq!(dataset.get_fragments().len(), 4);
+        assert!(dataset
+            .fragments()
+            .iter()
+            .all(|fragment| fragment.partition_values.is_some()));
+
+        let fields =
+            resolve_partition_fields(dataset.schema(), &dataset.partition_columns().unwrap())
+                .unwrap();
+        let planner = Planner::new(Arc::new(dataset.schema().into()));
+        let filter = planner
+            .parse_filter("date = DATE '1970-01-02' AND value > 0")
+            .unwrap();
+        let pruned =
+            prune_fragments(&filter, &fields, dataset.fragments().as_ref().clone()).unwrap();
+        assert_eq!(pruned.len(), 2);
+
+        let mut scanner = dataset.scan();
+        scanner
+            .filter("date = DATE '1970-01-02' AND value > 0")
+            .unwrap();
+        let batch = scanner.try_into_batch().await.unwrap();
+        let mut values = batch["value"].as_primitive::<Int32Type>().values().to_vec();
+        values.sort();
+        assert_eq!(values, vec![2, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_partition_overwrite() {
+        let test_dir = tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+
+        let batch = make_batch(vec![1, 2, 3], vec!["us", "us", "us"], vec![0, 1, 2]);
+        write(
+            test_uri,
+            batch,
+            WriteParams {
+                partition_columns: Some(vec!["date".into()]),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        // Backfill a single day, the other days must remain untouched
+        let batch = make_batch(vec![2, 2], vec!["eu", "eu"], vec![10, 11]);
+        let dataset = write(
+            test_uri,
+            batch,
+            WriteParams {
+                mode: WriteMode::Overwrite,
+                dynamic_partition_overwrite: true,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 4);
+        let batch = dataset.scan().try_into_batch().await.unwrap();
+        let mut values = batch["value"].as_primitive::<Int32Type>().values().to_vec();
+        values.sort();
+        assert_eq!(values, vec![0, 2, 10, 11]);
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_partition_overwrite_with_unpartitioned_fragments() {
+        let test_dir = tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+        write_partitioned(test_uri).await;
+
+        // Rows written without the region are stored in an unpartitioned fragment
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("date", DataType::Date32, false),
+            ArrowField::new("value", DataType::Int32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Date32Array::from(vec![1])),
+                Arc::new(Int32Array::from(vec![5])),
+            ],
+        )
+        .unwrap();
+        let dataset = write(
+            test_uri,
+            batch,
+            WriteParams {
+                mode: WriteMode::Append,
+                ..Default::default()
+            },
+        )
+        .await;
+        assert!(dataset
+            .fragments()
+            .iter()
+            .any(|fragment| fragment.partition_values.is_none()));
+
+        let batch = make_batch(vec![1], vec!["us"], vec![10]);
+        let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+        let result = Dataset::write(
+            reader,
+            test_uri,
+            Some(WriteParams {
+                mode: WriteMode::Overwrite,
+                dynamic_partition_overwrite: true,
+                ..Default::default()
+            }),
+        )
+        .await;
+        assert!(result.is_err());
+
+        let dataset = Dataset::open(test_uri).await.unwrap();
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 6);
+    }
+
+    async fn filtered_values(dataset: &Dataset, filter: &str) -> Vec<i32> {
+        let mut scanner = dataset.scan();
+        scanner.filter(filter).unwrap();
+        let batch = scanner.try_into_batch().await.unwrap();
+        let mut values = batch["value"].as_primitive::<Int32Type>().values().to_vec();
+        values.sort();
+        values
+    }
+
+    async fn write_partitioned(uri: &str) -> Dataset {
+        let batch = make_batch(
+            vec![1, 2, 1, 2, 3],
+            vec!["us", "us", "eu", "us", "us"],
+            vec![0, 1, 2, 3, 4],
+        );
+        write(
+            uri,
+            batch,
+            WriteParams {
+                partition_columns: Some(vec!["date".into(), "region".into()]),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_rename_partition_column() {
+        let test_dir = tempdir().unwrap();
+        let mut dataset = write_partitioned(test_dir.path().to_str().unwrap()).await;
+
+        dataset
+            .alter_columns(&[ColumnAlteration::new("region".into()).rename("area".into())])
+            .await
+            .unwrap();
+        assert_eq!(dataset.partition_columns().unwrap(), vec!["date", "area"]);
+        assert_eq!(filtered_values(&dataset, "area = 'eu'").await, vec![2]);
+        assert_eq!(
+            filtered_values(&dataset, "date = DATE '1970-01-02'").await,
+            vec![0, 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_drop_partition_column() {
+        let test_dir = tempdir().unwrap();
+        let mut dataset = write_partitioned(test_dir.path().to_str().unwrap()).await;
+
+        dataset.drop_columns(&["region"]).await.unwrap();
+        assert!(dataset.partition_columns().unwrap().is_empty());
+        assert!(!dataset
+            .manifest
+            .config
+            .contains_key(PARTITION_COLUMNS_CONFIG_KEY));
+        assert_eq!(
+            filtered_values(&dataset, "date = DATE '1970-01-02'").await,
+            vec![0, 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_overwrite_partitioned_dataset() {
+        let test_dir = tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+        write_partitioned(test_uri).await;
+
+        // The new data has no partition columns, so the dataset is no longer partitioned
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("region", DataType::Int32, false),
+            ArrowField::new("value", DataType::Int32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2])),
+                Arc::new(Int32Array::from(vec![10, 11])),
+            ],
+        )
+        .unwrap();
+        let dataset = write(
+            test_uri,
+            batch,
+            WriteParams {
+                mode: WriteMode::Overwrite,
+                ..Default::default()
+            },
+        )
+        .await;
+        assert!(dataset.partition_columns().unwrap().is_empty());
+        assert_eq!(filtered_values(&dataset, "region = 2").await, vec![11]);
+
+        // Overwriting an unpartitioned dataset does not re-partition it
+        let batch = make_batch(vec![1, 2], vec!["us", "eu"], vec![20, 21]);
+        let dataset = write(
+            test_uri,
+            batch,
+            WriteParams {
+                mode: WriteMode::Overwrite,
+                ..Default::default()
+            },
+        )
+        .await;
+        assert!(dataset.partition_columns().unwrap().is_empty());
+
+        let batch = make_batch(vec![1, 2], vec!["us", "eu"], vec![30, 31]);
+        let dataset = write(
+            test_uri,
+            batch,
+            WriteParams {
+                mode: WriteMode::Overwrite,
+                partition_columns: Some(vec!["region".into()]),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(dataset.partition_columns().unwrap(), vec!["region"]);
+        assert_eq!(dataset.get_fragments().len(), 2);
+        let batch = make_batch(vec![3], vec!["us"], vec![40]);
+        let dataset = write(
+            test_uri,
+            batch,
+            WriteParams {
+                mode: WriteMode::Overwrite,
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(dataset.partition_columns().unwrap(), vec!["region"]);
+        assert_eq!(filtered_values(&dataset, "region = 'us'").await, vec![40]);
+    }
+
+    #[tokio::test]
+    async fn test_partition_columns_require_existing_columns() {
+        let test_dir = tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+
+        let reader = gen()
+            .col("x", array::step::<Int32Type>())
+            .into_reader_rows(RowCount::from(10), BatchCount::from(1));
+        let result = Dataset::write(
+            reader,
+            test_uri,
+            Some(WriteParams {
+                partition_columns: Some(vec!["y".into()]),
+                ..Default::default()
+            }),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+}
@@ -24,6 +24,15 @@ pub trait WriteFragmentProgress: std::fmt::Debug + Sync + Send {
 
     /// Complete writing a [Fragment].
     async fn complete(&self, fragment: &Fragment) -> Result<()>;
+
+    /// Complete importing a source file with [`crate::Dataset::import`].
+    ///
+    /// `fragments` are all of the (uncommitted) fragments written from `source`.
+    /// Recording them allows an interrupted import to be resumed by passing them
+    /// back in [`crate::dataset::ImportParams::completed_files`].
+    async fn complete_source(&self, _source: &str, _fragments: &[Fragment]) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// By default, Progress tracker is Noop.
@@ -7,6 +7,7 @@ use std::sync::{Arc, LazyLock};
 use std::task::{Context, Poll};
 
 use arrow::array::AsArray;
+use arrow::datatypes::Float64Type;
 use arrow_array::{Array, Float32Array, Int64Array, RecordBatch};
 use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema, SchemaRef, SortOptions};
 use arrow_select::concat::concat_batches;
@@ -50,8 +51,12 @@ use lance_datafusion::exec::{
 use lance_datafusion::projection::ProjectionPlan;
 use lance_file::v2::reader::FileReaderOptions;
 use lance_index::scalar::expression::PlannerIndexExt;
+use lance_index::scalar::inverted::explain::{EXPLAIN_COL, EXPLAIN_FIELD};
+use lance_index::scalar::inverted::highlight::{
+    HighlightParams, HIGHLIGHTS_COL, HIGHLIGHTS_FIELD, SNIPPET_COL, SNIPPET_FIELD,
+};
 use lance_index::scalar::inverted::query::{
-    fill_fts_query_column, FtsQuery, FtsSearchParams, MatchQuery,
+    fill_fts_query_column, FtsQuery, FtsQueryNode, FtsSearchParams, MatchQuery, TermPatternQuery,
 };
 use lance_index::scalar::inverted::SCORE_COL;
 use lance_index::scalar::{FullTextSearchQuery, ScalarIndexType};
@@ -65,15 +70,22 @@ use lance_table::format::{Fragment, Index};
 use roaring::RoaringBitmap;
 use tracing::{info_span, instrument, Span};
 
+use super::mem_wal::MemTable;
+use super::partition;
 use super::Dataset;
 use crate::index::scalar::detect_scalar_index_type;
 use crate::index::vector::utils::{get_vector_dim, get_vector_type};
 use crate::index::DatasetIndexInternalExt;
 use crate::io::exec::filtered_read::{FilteredReadExec, FilteredReadOptions};
-use crate::io::exec::fts::{BoostQueryExec, FlatMatchQueryExec, MatchQueryExec, PhraseQueryExec};
+use crate::io::exec::fts::{
+    BoostQueryExec, FlatMatchQueryExec, FlatTermPatternQueryExec, FtsExplainExec, FtsHighlightExec,
+    MatchQueryExec, PhraseQueryExec, TermPatternQueryExec,
+};
 use crate::io::exec::knn::MultivectorScoringExec;
 use crate::io::exec::scalar_index::{MaterializeIndexExec, ScalarIndexExec};
-use crate::io::exec::{get_physical_optimizer, LanceFilterExec, LanceScanConfig};
+use crate::io::exec::{
+    get_physical_optimizer, LanceFilterExec, LanceScanConfig, MemTableMergeExec,
+};
 use crate::io::exec::{
     knn::new_knn_exec, project, AddRowAddrExec, FilterPlan, KNNVectorDistanceExec,
     LancePushdownScanExec, LanceScanExec, Planner, PreFilterSource, ScanConfig, TakeExec,
@@ -109,6 +121,14 @@ pub static DEFAULT_XTR_OVERFETCH: LazyLock<u32> = LazyLock::new(|| {
         .unwrap_or(10)
 });
 
+/// The default refine factor of the multi-vector search, the `k * factor` candidate
+/// documents are reranked with the exact MaxSim.
+pub static DEFAULT_MULTIVEC_REFINE_FACTOR: LazyLock<u32> = LazyLock::new(|| {
+    std::env::var("LANCE_MULTIVEC_REFINE_FACTOR")
+        .map(|val| val.parse().unwrap())
+        .unwrap_or(4)
+});
+
 // We want to support ~256 concurrent reads to maximize throughput on cloud storage systems
 // Our typical page size is 8MiB (though not all reads are this large yet due to offset buffers, validity buffers, etc.)
 // So we want to support 256 * 8MiB ~= 2GiB of queued reads
@@ -391,6 +411,9 @@ pub struct Scanner {
 
     /// File reader options to use when reading data files.
     file_reader_options: Option<FileReaderOptions>,
+
+    /// The MemTables whose unflushed rows are merged into the scan, from the oldest to the newest
+    mem_tables: Vec<Arc<MemTable>>,
 }
 
 fn escape_column_name(name: &str) -> String {
@@ -428,6 +451,7 @@ impl Scanner {
             scan_stats_callback: None,
             strict_batch_size: false,
             file_reader_options: None,
+            mem_tables: Vec::new(),
         }
     }
 
@@ -735,6 +759,16 @@ impl Scanner {
         self
     }
 
+    /// Merge the unflushed rows of the MemTables into the scan, ordered from the oldest
+    /// to the newest.
+    ///
+    /// The rows of the MemTables replace the rows of the dataset with the same primary key,
+    /// their `_rowid` and `_rowaddr` are null. This is only supported for plain scans.
+    pub fn with_mem_tables(&mut self, mem_tables: Vec<Arc<MemTable>>) -> &mut Self {
+        self.mem_tables = mem_tables;
+        self
+    }
+
     /// Set limit and offset.
     ///
     /// If offset is set, the first offset rows will be skipped. If limit is set,
@@ -764,6 +798,10 @@ impl Scanner {
     /// Find k-nearest neighbor within the vector column.
     /// the query can be a Float16Array, Float32Array, Float64Array, UInt8Array,
     /// or a ListArray/FixedSizeListArray of the above types.
+    ///
+    /// The query always has the full dimension of the column. If the index is built
+    /// on truncated vectors, the prefix of the query is used to search the index and
+    /// the candidates are re-ranked with the full query, see [`Self::refine`].
     pub fn nearest(&mut self, column: &str, q: &dyn Array, k: usize) -> Result<&mut Self> {
         if !self.prefilter {
             // We can allow fragment scan if the input to nearest is a prefilter.
@@ -853,6 +891,26 @@ impl Scanner {
                 q.as_any().downcast_ref::<Float32Array>().unwrap(),
                 FloatType::try_from(&dt)?,
             )?,
+            DataType::Int8 if q.data_type().is_floating() => {
+                // casting would truncate the fractions and turn the values out of range
+                // into nulls, so the query must hold int8 values exactly
+                let values = arrow::compute::cast(&q, &DataType::Float64)?;
+                if values
+                    .as_primitive::<Float64Type>()
+                    .iter()
+                    .flatten()
+                    .any(|v| v.fract() != 0.0 || v < i8::MIN as f64 || v > i8::MAX as f64)
+                {
+                    return Err(Error::invalid_input(
+                        format!(
+                            "Column {} has element type int8 but the query vector has values that are not int8",
+                            column,
+                        ),
+                        location!(),
+                    ));
+                }
+                arrow::compute::cast(&q, &DataType::Int8)?
+            }
             _ => {
                 return Err(Error::invalid_input(
                     format!(
@@ -973,6 +1031,13 @@ impl Scanner {
     ///   the search will read 2x more elements than the requested k before performing
     ///   the re-ranking. Note: even if the factor is 1, the  results will still be
     ///   re-ranked without fetching additional elements.
+    ///
+    /// The multi-vector search is always re-ranked with the exact MaxSim, with a factor of
+    /// [`DEFAULT_MULTIVEC_REFINE_FACTOR`] if not set.
+    ///
+    /// The search on an index built on truncated vectors (see
+    /// [`IvfBuildParams::truncate_dim`](lance_index::vector::ivf::IvfBuildParams::truncate_dim))
+    /// is always re-ranked with the full vectors, with a factor of 1 if not set.
     pub fn refine(&mut self, factor: u32) -> &mut Self {
         if let Some(q) = self.nearest.as_mut() {
             q.refine_factor = Some(factor)
@@ -1072,6 +1137,18 @@ impl Scanner {
         }
     }
 
+    fn highlight_params(&self) -> Option<&HighlightParams> {
+        self.full_text_query
+            .as_ref()
+            .and_then(|query| query.highlight.as_ref())
+    }
+
+    fn explain_score(&self) -> bool {
+        self.full_text_query
+            .as_ref()
+            .is_some_and(|query| query.explain)
+    }
+
     fn add_extra_columns(&self, schema: Schema) -> Result<Schema> {
         let mut extra_columns = vec![];
 
@@ -1083,6 +1160,15 @@ impl Scanner {
             extra_columns.push(ArrowField::new(SCORE_COL, DataType::Float32, true));
         }
 
+        if self.highlight_params().is_some() {
+            extra_columns.push(HIGHLIGHTS_FIELD.clone());
+            extra_columns.push(SNIPPET_FIELD.clone());
+        }
+
+        if self.explain_score() {
+            extra_columns.push(EXPLAIN_FIELD.clone());
+        }
+
         if extra_columns.is_empty() {
             Ok(schema)
         } else {
@@ -1120,6 +1206,20 @@ impl Scanner {
             output_expr.push((score_expr, SCORE_COL.to_string()));
         }
 
+        if self.highlight_params().is_some() {
+            for column in [HIGHLIGHTS_COL, SNIPPET_COL] {
+                if output_expr.iter().all(|(_, name)| name != column) {
+                    let expr = expressions::col(column, current_schema)?;
+                    output_expr.push((expr, column.to_string()));
+                }
+            }
+        }
+
+        if self.explain_score() && output_expr.iter().all(|(_, name)| name != EXPLAIN_COL) {
+            let explain_expr = expressions::col(EXPLAIN_COL, current_schema)?;
+            output_expr.push((explain_expr, EXPLAIN_COL.to_string()));
+        }
+
         if self.projection_plan.physical_projection.with_row_id
             && output_expr.iter().all(|(_, name)| name != ROW_ID)
         {
@@ -1358,6 +1458,15 @@ impl Scanner {
             });
         }
 
+        if !self.mem_tables.is_empty() && (self.nearest.is_some() || self.full_text_query.is_some())
+        {
+            return Err(Error::NotSupported {
+                source: "merging MemTables is not supported with nearest or full text search"
+                    .into(),
+                location: location!(),
+            });
+        }
+
         if self.include_deleted_rows && !self.projection_plan.physical_projection.with_row_id {
             return Err(Error::InvalidInput {
                 source: "include_deleted_rows is set but with_row_id is false".into(),
@@ -1409,8 +1518,8 @@ impl Scanner {
     }
 
     async fn get_scan_range(&self, filter_plan: &FilterPlan) -> Result<Option<Range<u64>>> {
-        if filter_plan.has_any_filter() {
-            // If there is a filter we can't pushdown limit / offset
+        if filter_plan.has_any_filter() || !self.mem_tables.is_empty() {
+            // If there is a filter or rows merged from MemTables we can't pushdown limit / offset
             Ok(None)
         } else {
             match (self.limit, self.offset) {
@@ -1485,6 +1594,8 @@ impl Scanner {
         // Scalar indices are only used when prefiltering
         let use_scalar_index = self.use_scalar_index && (self.prefilter || self.nearest.is_none());
         let mut filter_plan = self.create_filter_plan(use_scalar_index).await?;
+        // the rows merged from the MemTables are filtered by the full filter
+        let mem_table_filter = filter_plan.full_expr.clone();
 
         let mut use_limit_node = true;
         // Stage 1: source (either an (K|A)NN search, full text search or or a (full|indexed) scan)
@@ -1541,6 +1652,24 @@ impl Scanner {
             plan = Arc::new(LanceFilterExec::try_new(refine_expr, plan)?);
         }
 
+        // Stage 2.5: merge the unflushed rows of the MemTables
+        if !self.mem_tables.is_empty() {
+            // The MemTable rows can't be taken by row address later,
+            // so load all the projected columns and the primary keys before merging
+            let primary_key = self.mem_tables.iter().flat_map(|t| t.primary_key());
+            let projection = self
+                .projection_plan
+                .physical_projection
+                .clone()
+                .union_columns(primary_key, OnMissing::Error)?;
+            plan = self.take(plan, projection)?;
+            plan = Arc::new(MemTableMergeExec::try_new(
+                plan,
+                self.mem_tables.clone(),
+                mem_table_filter,
+            )?);
+        }
+
         // Stage 3: sort
         if let Some(ordering) = &self.ordering {
             let ordering_columns = ordering.iter().map(|col| &col.column_name);
@@ -1573,6 +1702,16 @@ impl Scanner {
         // Stage 5: take remaining columns required for projection
         plan = self.take(plan, self.projection_plan.physical_projection.clone())?;
 
+        // Stage 6: highlight the matched terms and explain the scores of the full text search
+        if let Some(query) = &self.full_text_query {
+            if let Some(params) = &query.highlight {
+                plan = self.highlight(plan, query, params).await?;
+            }
+            if query.explain {
+                plan = self.explain_score_plan(plan, query).await?;
+            }
+        }
+
         // Stage 7: final projection
         let output_expr = match self.projection_plan.final_projection_is_empty {
             true => vec![],
@@ -1791,17 +1930,46 @@ impl Scanner {
             None
         };
 
+        let fragments = self.partition_pruned_fragments(filter_plan)?;
+
         self.filtered_read(
             filter_plan,
             projection,
             self.include_deleted_rows,
-            self.fragments.clone().map(Arc::new),
+            fragments,
             scan_range,
             /*is_prefilter= */ false,
         )
         .await
     }
 
+    /// The fragments to scan, skipping those whose partition values can not match the filter
+    fn partition_pruned_fragments(
+        &self,
+        filter_plan: &FilterPlan,
+    ) -> Result<Option<Arc<Vec<Fragment>>>> {
+        let Some(filter) = filter_plan.full_expr.as_ref() else {
+            return Ok(self.fragments.clone().map(Arc::new));
+        };
+        let partition_fields = partition::partition_fields(&self.dataset.manifest)?;
+        if partition_fields.is_empty() {
+            return Ok(self.fragments.clone().map(Arc::new));
+        }
+
+        let fragments = self
+            .fragments
+            .clone()
+            .unwrap_or_else(|| self.dataset.fragments().as_ref().clone());
+        let num_fragments = fragments.len();
+        let fragments = partition::prune_fragments(filter, &partition_fields, fragments)?;
+        log::trace!(
+            "partition pruning kept {} of {} fragments",
+            fragments.len(),
+            num_fragments
+        );
+        Ok(Some(Arc::new(fragments)))
+    }
+
     async fn fts_search_source(
         &self,
         filter_plan: &mut FilterPlan,
@@ -1930,6 +2098,17 @@ impl Scanner {
                 )
                 .await
             }
+            FtsQuery::Prefix(_) | FtsQuery::Wildcard(_) | FtsQuery::Regexp(_) => {
+                let column = query
+                    .columns()
+                    .into_iter()
+                    .next()
+                    .ok_or(Error::invalid_input(
+                        "the column must be specified in the query".to_string(),
+                        location!(),
+                    ))?;
+                self.fragments_covered_by_fts_leaf(&column, accum).await
+            }
             FtsQuery::Boolean(bool_query) => {
                 for query in bool_query.must.iter() {
                     if !self
@@ -1969,17 +2148,10 @@ impl Scanner {
     }
 
     // Create an execution plan to do full text search
-    async fn fts(
-        &self,
-        filter_plan: &FilterPlan,
-        query: &FullTextSearchQuery,
-    ) -> Result<Arc<dyn ExecutionPlan>> {
+    // fills the column of the query if it's not specified
+    async fn resolve_fts_query(&self, query: &FullTextSearchQuery) -> Result<FtsQuery> {
         let columns = query.columns();
-        let mut params = query.params();
-        if params.limit.is_none() {
-            params = params.with_limit(self.limit.map(|l| l as usize));
-        }
-        let query = if columns.is_empty() {
+        if columns.is_empty() {
             // the field is not specified,
             // try to search over all indexed fields
             let string_columns =
@@ -2018,10 +2190,79 @@ impl Scanner {
                 }
             }
 
-            fill_fts_query_column(&query.query, &indexed_columns, false)?
+            fill_fts_query_column(&query.query, &indexed_columns, false)
         } else {
-            query.query.clone()
+            Ok(query.query.clone())
+        }
+    }
+
+    // appends the highlights and snippet columns of the full text search
+    async fn highlight(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        query: &FullTextSearchQuery,
+        params: &HighlightParams,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let fts_query = self.resolve_fts_query(query).await?;
+        let column = match &params.column {
+            Some(column) => column.clone(),
+            None => {
+                let columns = fts_query.columns();
+                if columns.len() != 1 {
+                    return Err(Error::invalid_input(
+                        format!(
+                            "the column to highlight must be specified when the query searches {} columns",
+                            columns.len()
+                        ),
+                        location!(),
+                    ));
+                }
+                columns.into_iter().next().unwrap()
+            }
         };
+        let projection = self
+            .dataset
+            .empty_projection()
+            .union_column(&column, OnMissing::Error)?;
+        let plan = self.take(plan, projection)?;
+        Ok(Arc::new(FtsHighlightExec::try_new(
+            self.dataset.clone(),
+            fts_query,
+            column,
+            params.clone(),
+            plan,
+        )?))
+    }
+
+    // appends the explanations of the scores of the full text search
+    async fn explain_score_plan(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        query: &FullTextSearchQuery,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let fts_query = self.resolve_fts_query(query).await?;
+        let projection = self
+            .dataset
+            .empty_projection()
+            .union_columns(fts_query.columns(), OnMissing::Error)?;
+        let plan = self.take(plan, projection)?;
+        Ok(Arc::new(FtsExplainExec::try_new(
+            self.dataset.clone(),
+            fts_query,
+            plan,
+        )?))
+    }
+
+    async fn fts(
+        &self,
+        filter_plan: &FilterPlan,
+        query: &FullTextSearchQuery,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let mut params = query.params();
+        if params.limit.is_none() {
+            params = params.with_limit(self.limit.map(|l| l as usize));
+        }
+        let query = self.resolve_fts_query(query).await?;
 
         // TODO: Could maybe walk the query here to find all the indices that will be
         // involved in the query to calculate a more accuarate required_fragments than
@@ -2053,9 +2294,38 @@ impl Scanner {
             FtsQuery::Phrase(query) => Arc::new(PhraseQueryExec::new(
                 self.dataset.clone(),
                 query.clone(),
-                params.clone(),
+                // the phrase may be nested in a compound query,
+                // so the slop of the top level query doesn't apply to it
+                params.clone().with_phrase_slop(Some(query.slop)),
                 prefilter_source.clone(),
             )),
+            FtsQuery::Prefix(query) => {
+                self.plan_term_pattern_query(
+                    &query.clone().into(),
+                    params,
+                    filter_plan,
+                    prefilter_source,
+                )
+                .await?
+            }
+            FtsQuery::Wildcard(query) => {
+                self.plan_term_pattern_query(
+                    &query.clone().into(),
+                    params,
+                    filter_plan,
+                    prefilter_source,
+                )
+                .await?
+            }
+            FtsQuery::Regexp(query) => {
+                self.plan_term_pattern_query(
+                    &query.clone().into(),
+                    params,
+                    filter_plan,
+                    prefilter_source,
+                )
+                .await?
+            }
 
             FtsQuery::Boost(query) => {
                 // for boost query, we need to erase the limit so that we can find
@@ -2270,15 +2540,98 @@ impl Scanner {
                 location!(),
             ))?;
 
-        let unindexed_fragments = self.dataset.unindexed_fragments(&index.name).await?;
-        let mut match_plan: Arc<dyn ExecutionPlan> = Arc::new(MatchQueryExec::new(
+        let match_plan: Arc<dyn ExecutionPlan> = Arc::new(MatchQueryExec::new(
             self.dataset.clone(),
             query.clone(),
             params.clone(),
             prefilter_source.clone(),
         ));
+        self.plan_unindexed_fts_leaf(
+            &index,
+            &column,
+            params,
+            filter_plan,
+            match_plan,
+            |scan_node| {
+                Arc::new(FlatMatchQueryExec::new(
+                    self.dataset.clone(),
+                    query.clone(),
+                    params.clone(),
+                    scan_node,
+                ))
+            },
+        )
+        .await
+    }
+
+    async fn plan_term_pattern_query(
+        &self,
+        query: &TermPatternQuery,
+        params: &FtsSearchParams,
+        filter_plan: &FilterPlan,
+        prefilter_source: &PreFilterSource,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let column = query
+            .column
+            .as_ref()
+            .ok_or(Error::invalid_input(
+                "the column must be specified in the query".to_string(),
+                location!(),
+            ))?
+            .clone();
+
+        let index = self
+            .dataset
+            .load_scalar_index(
+                ScalarIndexCriteria::default()
+                    .for_column(&column)
+                    .with_type(ScalarIndexType::Inverted),
+            )
+            .await?
+            .ok_or(Error::invalid_input(
+                format!("Column {} has no inverted index", column),
+                location!(),
+            ))?;
+
+        let pattern_plan: Arc<dyn ExecutionPlan> = Arc::new(TermPatternQueryExec::new(
+            self.dataset.clone(),
+            query.clone(),
+            params.clone(),
+            prefilter_source.clone(),
+        ));
+        self.plan_unindexed_fts_leaf(
+            &index,
+            &column,
+            params,
+            filter_plan,
+            pattern_plan,
+            |scan_node| {
+                Arc::new(FlatTermPatternQueryExec::new(
+                    self.dataset.clone(),
+                    query.clone(),
+                    params.clone(),
+                    scan_node,
+                ))
+            },
+        )
+        .await
+    }
+
+    // search the fragments not covered by the index with the flat plan,
+    // and merge the results with the indexed plan
+    async fn plan_unindexed_fts_leaf(
+        &self,
+        index: &Index,
+        column: &str,
+        params: &FtsSearchParams,
+        filter_plan: &FilterPlan,
+        indexed_plan: Arc<dyn ExecutionPlan>,
+        flat_plan: impl FnOnce(Arc<dyn ExecutionPlan>) -> Arc<dyn ExecutionPlan>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let unindexed_fragments = self.dataset.unindexed_fragments(&index.name).await?;
+        let mut match_plan = indexed_plan;
         if !unindexed_fragments.is_empty() {
-            let mut columns = vec![column.clone()];
+            let mut columns = vec![column.to_string()];
             if let Some(expr) = filter_plan.full_expr.as_ref() {
                 let filter_columns = Planner::column_names_in_expr(expr);
                 columns.extend(filter_columns);
@@ -2299,12 +2652,7 @@ impl Scanner {
                 scan_node = Arc::new(LanceFilterExec::try_new(expr.clone(), scan_node)?);
             }
 
-            let flat_match_plan = Arc::new(FlatMatchQueryExec::new(
-                self.dataset.clone(),
-                query.clone(),
-                params.clone(),
-                scan_node,
-            ));
+            let flat_match_plan = flat_plan(scan_node);
 
             match_plan = Arc::new(UnionExec::new(vec![match_plan, flat_match_plan]));
             match_plan = Arc::new(RepartitionExec::try_new(
@@ -2356,6 +2704,31 @@ impl Scanner {
                 ));
             }
 
+            let idx = self
+                .dataset
+                .open_vector_index(
+                    q.column.as_str(),
+                    &index.uuid.to_string(),
+                    &NoOpMetricsCollector,
+                )
+                .await?;
+            // The index built on truncated vectors only gives the coarse distances,
+            // so the candidates are always reranked with the full vectors.
+            let index_dim = idx.ivf_model().dimension();
+            let mut q = q.clone();
+            if q.refine_factor.is_none() {
+                if matches!(vector_type, DataType::List(_)) {
+                    // The multi-vector candidates are scored with the approximate MaxSim,
+                    // so they are always reranked with the exact MaxSim.
+                    q.refine_factor = Some(*DEFAULT_MULTIVEC_REFINE_FACTOR);
+                } else if index_dim > 0
+                    && index_dim < get_vector_dim(self.dataset.schema(), &q.column)?
+                {
+                    q.refine_factor = Some(1);
+                }
+            }
+            let q = &q;
+
             // Find all deltas with the same index name.
             let deltas = self.dataset.load_indices_by_name(&index.name).await?;
             let ann_node = match vector_type {
@@ -2371,15 +2744,6 @@ impl Scanner {
                     .union_column(&q.column, OnMissing::Error)
                     .unwrap();
                 let knn_node_with_vector = self.take(ann_node, vector_projection)?;
-                // TODO: now we just open an index to get its metric type.
-                let idx = self
-                    .dataset
-                    .open_vector_index(
-                        q.column.as_str(),
-                        &index.uuid.to_string(),
-                        &NoOpMetricsCollector,
-                    )
-                    .await?;
                 let mut q = q.clone();
                 q.metric_type = idx.metric_type();
                 self.flat_knn(knn_node_with_vector, &q)?
@@ -2923,6 +3287,9 @@ impl Scanner {
         // we split the query procedure into two steps:
         // 1. collect the candidates by vector searching on each query vector
         // 2. scoring the candidates
+        // the candidates are reranked with the exact MaxSim later, see `vector_search`.
+        // The index is a regular IVF index over the vectors of the multi-vectors, a row
+        // becomes a candidate if any of its vectors is close to any query vector.
 
         let over_fetch_factor = *DEFAULT_XTR_OVERFETCH;
 
@@ -2931,21 +3298,39 @@ impl Scanner {
             .await?;
         let dim = get_vector_dim(self.dataset.schema(), &q.column)?;
 
+        // the search in each probed partition returns the `k * over_fetch_factor` closest
+        // vectors, so the candidates of a query vector are bounded by the number of the
+        // partitions that may be probed, including the ones of the late search.
+        let mut max_nprobes = 0;
+        for idx in index {
+            let vector_index = self
+                .dataset
+                .open_vector_index(&q.column, &idx.uuid.to_string(), &NoOpMetricsCollector)
+                .await?;
+            let num_partitions = vector_index.ivf_model().num_partitions();
+            max_nprobes += q
+                .maximum_nprobes
+                .unwrap_or(num_partitions)
+                .max(q.minimum_nprobes)
+                .min(num_partitions);
+        }
+        let max_candidates = q.k * over_fetch_factor as usize * max_nprobes.max(1);
+
         let num_queries = q.key.len() / dim;
         let new_queries = (0..num_queries)
             .map(|i| q.key.slice(i * dim, dim))
             .map(|query_vec| {
                 let mut new_query = q.clone();
                 new_query.key = query_vec;
-                // with XTR, we don't need to refine the result with original vectors,
-                // but here we really need to over-fetch the candidates to reach good enough recall.
+                // with XTR, we need to over-fetch the candidates to reach good enough recall.
                 // TODO: improve the recall with WARP, expose this parameter to the users.
                 new_query.refine_factor = Some(over_fetch_factor);
                 new_query
             });
         let mut ann_nodes = Vec::with_capacity(new_queries.len());
         for query in new_queries {
-            // this produces `nprobes * k * over_fetch_factor * num_indices` candidates
+            // the candidates are sorted for the scoring, which takes the distance of the
+            // last one as the estimation of the missed vectors.
             let ann_node = new_knn_exec(
                 self.dataset.clone(),
                 index,
@@ -2961,7 +3346,7 @@ impl Scanner {
             };
             let ann_node = Arc::new(
                 SortExec::new(LexOrdering::new(vec![sort_expr]), ann_node)
-                    .with_fetch(Some(q.k * over_fetch_factor as usize)),
+                    .with_fetch(Some(max_candidates)),
             );
             ann_nodes.push(ann_node as Arc<dyn ExecutionPlan>);
         }
@@ -3336,8 +3721,8 @@ mod test {
     use arrow_array::cast::AsArray;
     use arrow_array::types::{Float32Type, UInt64Type};
     use arrow_array::{
-        ArrayRef, FixedSizeListArray, Float16Array, Int32Array, LargeStringArray, PrimitiveArray,
-        RecordBatchIterator, StringArray, StructArray,
+        ArrayRef, FixedSizeListArray, Float16Array, Int32Array, Int8Array, LargeStringArray,
+        PrimitiveArray, RecordBatchIterator, StringArray, StructArray,
     };
     use arrow_ord::sort::sort_to_indices;
     use arrow_select::take;
@@ -3607,6 +3992,49 @@ mod test {
         assert_eq!(expected_i, actual_i);
     }
 
+    #[tokio::test]
+    async fn test_knn_int8_query() {
+        let schema = Arc::new(ArrowSchema::new(vec![ArrowField::new(
+            "vec",
+            DataType::FixedSizeList(Arc::new(ArrowField::new("item", DataType::Int8, true)), 4),
+            true,
+        )]));
+        let values = Int8Array::from_iter_values((0..40).map(|v| v as i8));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(
+                FixedSizeListArray::try_new_from_values(values, 4).unwrap(),
+            )],
+        )
+        .unwrap();
+        let reader = RecordBatchIterator::new(vec![Ok(batch)], schema);
+        let dataset = Dataset::write(reader, "memory://test", None).await.unwrap();
+
+        let key = Float32Array::from(vec![8.0, 9.0, 10.0, 11.0]);
+        let batch = dataset
+            .scan()
+            .nearest("vec", &key, 1)
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+        assert_eq!(batch[DIST_COL].as_primitive::<Float32Type>().value(0), 0.0);
+
+        // the fractions would be truncated and the values out of range turned into nulls
+        for key in [
+            Float32Array::from(vec![8.5, 9.0, 10.0, 11.0]),
+            Float32Array::from(vec![8.0, 9.0, 10.0, 128.0]),
+            Float32Array::from(vec![8.0, 9.0, f32::NAN, 11.0]),
+        ] {
+            let err = dataset.scan().nearest("vec", &key, 1).unwrap_err();
+            assert!(
+                err.to_string().contains("not int8"),
+                "unexpected error: {}",
+                err
+            );
+        }
+    }
+
     #[rstest]
     #[tokio::test]
     async fn test_can_project_distance() {
@@ -965,6 +965,7 @@ mod test {
                         deletion_file: None,
                         row_id_meta: None,
                         physical_rows: Some(50),
+                        partition_values: None,
                     }))
                 } else {
                     Ok(None)
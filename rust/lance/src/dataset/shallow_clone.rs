@@ -0,0 +1,333 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Shallow clones of a dataset version.
+//!
+//! A shallow clone is a new dataset whose first version references the data,
+//! deletion and index files of the source version instead of copying them.
+//! The source root is recorded as a [`BasePath`] in the clone's manifest and
+//! the referenced files carry its id. Anything written to the clone afterwards
+//! lands in the clone's own directory.
+//!
+//! To keep `cleanup_old_versions` on the source from deleting the referenced
+//! files, the cloned version is tagged in the source with a `clone-<uuid>` tag.
+//! The tag name is stored in the clone's config under [`CLONE_SOURCE_TAG_KEY`]
+//! and can be deleted once the clone is no longer needed.
+
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use lance_index::DatasetIndexExt;
+use lance_io::object_store::{ObjectStore, ObjectStoreParams};
+use lance_table::format::{BasePath, Manifest, RowIdMeta};
+use lance_table::io::commit::{CommitError, CommitHandler, ManifestLocation};
+use lance_table::rowids::write_row_ids;
+use object_store::path::Path;
+use snafu::location;
+use url::Url;
+
+use super::rowids::load_row_id_sequence;
+use super::write::resolve_commit_handler;
+use super::{write_manifest_file, ManifestWriteConfig, BLOB_DIR};
+use crate::{Dataset, Error, Result};
+
+/// Config key of a clone holding the URI of the dataset it was cloned from.
+pub const CLONE_SOURCE_KEY: &str = "lance.clone.source";
+/// Config key of a clone holding the tag that protects the cloned version in
+/// the source dataset.
+pub const CLONE_SOURCE_TAG_KEY: &str = "lance.clone.source_tag";
+
+pub(super) async fn shallow_clone(
+    source: &Dataset,
+    target_uri: &str,
+    store_params: Option<ObjectStoreParams>,
+) -> Result<Dataset> {
+    if store_key(source.uri())? != store_key(target_uri)? {
+        return Err(Error::invalid_input(
+            format!(
+                "A shallow clone must be in the same object store as its source, but {} and {} are not",
+                source.uri(),
+                target_uri
+            ),
+            location!(),
+        ));
+    }
+
+    let (object_store, base) = ObjectStore::from_uri_and_params(
+        source.session.store_registry(),
+        target_uri,
+        &store_params.clone().unwrap_or_default(),
+    )
+    .await?;
+    let commit_handler = resolve_commit_handler(target_uri, None, &store_params).await?;
+
+    let tag = format!("clone-{}", uuid::Uuid::new_v4().simple());
+    let (manifest, manifest_location) = clone_version(
+        source,
+        &object_store,
+        commit_handler.as_ref(),
+        &base,
+        target_uri,
+        &tag,
+    )
+    .await?;
+
+    Dataset::checkout_manifest(
+        object_store,
+        base,
+        target_uri.to_string(),
+        Arc::new(manifest),
+        manifest_location,
+        source.session.clone(),
+        commit_handler,
+        source.file_reader_options.clone(),
+        store_params.map(Box::new),
+    )
+}
+
+/// Write the first version of a clone of `source` at `base`, along with the
+/// clone of its blob dataset if it has one.
+fn clone_version<'a>(
+    source: &'a Dataset,
+    object_store: &'a ObjectStore,
+    commit_handler: &'a dyn CommitHandler,
+    base: &'a Path,
+    target_uri: &'a str,
+    tag: &'a str,
+) -> BoxFuture<'a, Result<(Manifest, ManifestLocation)>> {
+    async move {
+        let mut manifest = source.manifest.as_ref().clone();
+        let base_id = manifest.base_paths.keys().max().map_or(0, |id| id + 1);
+        manifest
+            .base_paths
+            .insert(base_id, BasePath::new(base_id, &source.base));
+
+        let mut fragments = manifest.fragments.as_ref().clone();
+        for fragment in fragments.iter_mut() {
+            for file in fragment.files.iter_mut() {
+                file.base_id = file.base_id.or(Some(base_id));
+            }
+            if let Some(deletion_file) = fragment.deletion_file.as_mut() {
+                deletion_file.base_id = deletion_file.base_id.or(Some(base_id));
+            }
+            // External row id files are relative to the dataset root, so they
+            // are inlined rather than referenced.
+            if matches!(fragment.row_id_meta, Some(RowIdMeta::External(_))) {
+                let sequence = load_row_id_sequence(source, fragment).await?;
+                fragment.row_id_meta = Some(RowIdMeta::Inline(write_row_ids(&sequence)));
+            }
+        }
+        manifest.fragments = Arc::new(fragments);
+
+        let mut indices = source.load_indices().await?.as_ref().clone();
+        for index in indices.iter_mut() {
+            index.base_id = index.base_id.or(Some(base_id));
+        }
+
+        if let Some(blobs) = source.blobs_dataset().await? {
+            let (blob_manifest, _) = clone_version(
+                blobs.as_ref(),
+                object_store,
+                commit_handler,
+                &base.child(BLOB_DIR),
+                &format!("{}/{}", target_uri, BLOB_DIR),
+                tag,
+            )
+            .await?;
+            manifest.blob_dataset_version = Some(blob_manifest.version);
+        }
+
+        manifest.version = 1;
+        manifest.tag = None;
+        manifest.transaction_file = None;
+        manifest.update_config([
+            (CLONE_SOURCE_KEY.to_string(), source.uri().to_string()),
+            (CLONE_SOURCE_TAG_KEY.to_string(), tag.to_string()),
+        ]);
+
+        // Protect the cloned version before anything references it.
+        let mut source_tags = source.tags.clone();
+        source_tags.create(tag, source.version().version).await?;
+
+        let config = ManifestWriteConfig {
+            use_move_stable_row_ids: manifest.uses_move_stable_row_ids(),
+            ..Default::default()
+        };
+        let result = write_manifest_file(
+            object_store,
+            commit_handler,
+            base,
+            &mut manifest,
+            if indices.is_empty() {
+                None
+            } else {
+                Some(indices)
+            },
+            &config,
+            source.manifest_location.naming_scheme,
+        )
+        .await;
+        let manifest_location = match result {
+            Ok(manifest_location) => manifest_location,
+            Err(err) => {
+                source_tags.delete(tag).await?;
+                return Err(match err {
+                    CommitError::CommitConflict => Error::DatasetAlreadyExists {
+                        uri: target_uri.to_string(),
+                        location: location!(),
+                    },
+                    CommitError::OtherError(err) => err,
+                });
+            }
+        };
+        Ok((manifest, manifest_location))
+    }
+    .boxed()
+}
+
+/// Identifies the object store a URI points into: the scheme, plus the bucket
+/// for cloud stores.
+fn store_key(uri: &str) -> Result<String> {
+    let url = match Url::parse(uri) {
+        Ok(url) if url.scheme().len() > 1 => url,
+        // Local paths (including Windows drive letters)
+        _ => return Ok("file://".to_string()),
+    };
+    if ["file", "file-object-store", "memory"].contains(&url.scheme()) {
+        Ok(format!("{}://", url.scheme()))
+    } else {
+        Ok(format!(
+            "{}://{}",
+            url.scheme(),
+            url.host_str().unwrap_or_default()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Int32Type;
+    use arrow_array::{Int32Array, RecordBatch, RecordBatchIterator, RecordBatchReader};
+    use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema};
+    use chrono::TimeDelta;
+    use lance_index::scalar::ScalarIndexParams;
+    use lance_index::IndexType;
+    use lance_table::feature_flags::FLAG_BASE_PATHS;
+
+    use crate::dataset::{WriteMode, WriteParams};
+
+    fn make_batches(values: std::ops::Range<i32>) -> impl RecordBatchReader + Send + 'static {
+        let schema = Arc::new(ArrowSchema::new(vec![ArrowField::new(
+            "x",
+            DataType::Int32,
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from_iter_values(values))],
+        )
+        .unwrap();
+        RecordBatchIterator::new(vec![Ok(batch)], schema)
+    }
+
+    async fn scan_x(dataset: &Dataset) -> Vec<i32> {
+        let batch = dataset.scan().try_into_batch().await.unwrap();
+        let mut values = batch["x"].as_primitive::<Int32Type>().values().to_vec();
+        values.sort();
+        values
+    }
+
+    #[tokio::test]
+    async fn test_shallow_clone() {
+        let test_dir = tempfile::tempdir().unwrap();
+        let source_uri = format!("{}/source", test_dir.path().to_str().unwrap());
+        let clone_uri = format!("{}/clone", test_dir.path().to_str().unwrap());
+
+        let mut source = Dataset::write(make_batches(0..100), &source_uri, None)
+            .await
+            .unwrap();
+        source.delete("x < 10").await.unwrap();
+        source
+            .create_index(
+                &["x"],
+                IndexType::Scalar,
+                None,
+                &ScalarIndexParams::default(),
+                false,
+            )
+            .await
+            .unwrap();
+        let cloned_version = source.version().version;
+        source.append(make_batches(100..110), None).await.unwrap();
+
+        let mut clone = source
+            .shallow_clone(cloned_version, &clone_uri, None)
+            .await
+            .unwrap();
+        assert_eq!(clone.version().version, 1);
+        assert_ne!(clone.manifest.reader_feature_flags & FLAG_BASE_PATHS, 0);
+        assert_eq!(scan_x(&clone).await, (10..100).collect::<Vec<_>>());
+        assert_eq!(clone.count_rows(Some("x = 50".into())).await.unwrap(), 1);
+        assert_eq!(clone.load_indices().await.unwrap().len(), 1);
+
+        // Writes to the clone land in its own directory
+        clone.append(make_batches(200..210), None).await.unwrap();
+        clone.delete("x = 50").await.unwrap();
+        let clone_files = std::fs::read_dir(test_dir.path().join("clone/data"))
+            .unwrap()
+            .count();
+        assert_eq!(clone_files, 1);
+        let source_files = std::fs::read_dir(test_dir.path().join("source/data"))
+            .unwrap()
+            .count();
+        assert_eq!(source_files, 2);
+
+        let clone = Dataset::open(&clone_uri).await.unwrap();
+        let mut expected = (10..100).filter(|x| *x != 50).collect::<Vec<_>>();
+        expected.extend(200..210);
+        assert_eq!(scan_x(&clone).await, expected);
+
+        // The cloned version is protected from cleanup in the source
+        let tag = &clone.manifest.config[CLONE_SOURCE_TAG_KEY];
+        assert_eq!(source.tags.get_version(tag).await.unwrap(), cloned_version);
+        let write_params = WriteParams {
+            mode: WriteMode::Overwrite,
+            ..Default::default()
+        };
+        let source = Dataset::write(make_batches(0..5), &source_uri, Some(write_params))
+            .await
+            .unwrap();
+        source
+            .cleanup_old_versions(TimeDelta::zero(), Some(true), Some(false))
+            .await
+            .unwrap();
+        let clone = Dataset::open(&clone_uri).await.unwrap();
+        assert_eq!(scan_x(&clone).await, expected);
+    }
+
+    #[tokio::test]
+    async fn test_shallow_clone_errors() {
+        let test_dir = tempfile::tempdir().unwrap();
+        let source_uri = format!("{}/source", test_dir.path().to_str().unwrap());
+        let clone_uri = format!("{}/clone", test_dir.path().to_str().unwrap());
+        let source = Dataset::write(make_batches(0..10), &source_uri, None)
+            .await
+            .unwrap();
+
+        let err = source
+            .shallow_clone(1, "memory://clone", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }), "{}", err);
+
+        source.shallow_clone(1, &clone_uri, None).await.unwrap();
+        let err = source.shallow_clone(1, &clone_uri, None).await.unwrap_err();
+        assert!(matches!(err, Error::DatasetAlreadyExists { .. }), "{}", err);
+        // Only the successful clone keeps its tag
+        assert_eq!(source.tags.list().await.unwrap().len(), 1);
+    }
+}
@@ -2,9 +2,11 @@
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
 use crate::datafusion::LanceTableProvider;
+use crate::io::exec::AggregatePushdown;
 use crate::Dataset;
 use arrow_array::{Array, RecordBatch, StringArray};
 use datafusion::dataframe::DataFrame;
+use datafusion::execution::session_state::SessionStateBuilder;
 use datafusion::execution::SendableRecordBatchStream;
 use datafusion::prelude::SessionContext;
 use std::sync::Arc;
@@ -63,7 +65,11 @@ impl SqlQueryBuilder {
     }
 
     pub async fn build(self) -> lance_core::Result<SqlQuery> {
-        let ctx = SessionContext::new();
+        let state = SessionStateBuilder::new()
+            .with_default_features()
+            .with_physical_optimizer_rule(Arc::new(AggregatePushdown))
+            .build();
+        let ctx = SessionContext::new_with_state(state);
         let row_id = self.with_row_id;
         let row_addr = self.with_row_addr;
         ctx.register_table(
@@ -136,7 +142,9 @@ mod tests {
     use all_asserts::assert_true;
     use arrow_array::cast::AsArray;
     use arrow_array::types::{Int32Type, Int64Type, UInt64Type};
-    use lance_datagen::{array, gen};
+    use lance_datagen::{array, gen, ArrayGeneratorExt};
+    use lance_index::scalar::ScalarIndexParams;
+    use lance_index::{DatasetIndexExt, IndexType};
 
     #[tokio::test]
     async fn test_sql_execute() {
@@ -255,4 +263,131 @@ mod tests {
 
         assert!(plan.contains("Aggregate") || plan.contains("SUM"));
     }
+
+    #[tokio::test]
+    async fn test_sql_aggregate_pushdown() {
+        let mut ds = gen()
+            .col("x", array::step::<Int32Type>())
+            .col("y", array::step_custom::<Int32Type>(0, 2))
+            .into_dataset(
+                "memory://test_sql_aggregate_pushdown",
+                FragmentCount::from(10),
+                FragmentRowCount::from(10),
+            )
+            .await
+            .unwrap();
+        ds.create_index(
+            &["x"],
+            IndexType::BTree,
+            None,
+            &ScalarIndexParams::default(),
+            true,
+        )
+        .await
+        .unwrap();
+
+        async fn query(ds: &mut crate::Dataset, sql: &str) -> (String, Vec<i64>) {
+            let plan = ds
+                .sql(sql)
+                .table_name("foo")
+                .build()
+                .await
+                .unwrap()
+                .into_explain_plan(false, false)
+                .await
+                .unwrap();
+            let results = ds
+                .sql(sql)
+                .table_name("foo")
+                .build()
+                .await
+                .unwrap()
+                .into_batch_records()
+                .await
+                .unwrap();
+            let batch = arrow::compute::concat_batches(&results[0].schema(), &results).unwrap();
+            assert_eq!(batch.num_rows(), 1);
+            let values = batch
+                .columns()
+                .iter()
+                .map(|column| {
+                    arrow::compute::cast(column, &arrow_schema::DataType::Int64)
+                        .unwrap()
+                        .as_primitive::<Int64Type>()
+                        .value(0)
+                })
+                .collect();
+            (plan, values)
+        }
+
+        let (plan, values) = query(&mut ds, "SELECT COUNT(*), MIN(x), MAX(x) FROM foo").await;
+        assert!(plan.contains("MetadataAggregate: aggr=[count, min(x), max(x)]"));
+        assert_eq!(values, vec![100, 0, 99]);
+
+        let (plan, values) = query(&mut ds, "SELECT COUNT(*) FROM foo WHERE x >= 30").await;
+        assert!(plan.contains("MetadataAggregate: aggr=[count], query="));
+        assert_eq!(values, vec![70]);
+
+        // MIN/MAX of an unindexed column can't be answered from metadata
+        let (plan, values) = query(&mut ds, "SELECT MIN(y) FROM foo").await;
+        assert!(!plan.contains("MetadataAggregate"));
+        assert_eq!(values, vec![0]);
+
+        // the index still has the deleted values, so the plan falls back to a scan
+        ds.delete("x >= 90").await.unwrap();
+        let (plan, values) = query(&mut ds, "SELECT COUNT(*), MAX(x) FROM foo").await;
+        assert!(plan.contains("MetadataAggregate"));
+        assert_eq!(values, vec![90, 89]);
+    }
+
+    #[tokio::test]
+    async fn test_sql_aggregate_pushdown_with_nulls() {
+        // every 4th value is null, starting from 0
+        let mut ds = gen()
+            .col(
+                "x",
+                array::step::<Int32Type>().with_nulls(&[true, false, false, false]),
+            )
+            .into_dataset(
+                "memory://test_sql_aggregate_pushdown_with_nulls",
+                FragmentCount::from(4),
+                FragmentRowCount::from(25),
+            )
+            .await
+            .unwrap();
+        ds.create_index(
+            &["x"],
+            IndexType::BTree,
+            None,
+            &ScalarIndexParams::default(),
+            true,
+        )
+        .await
+        .unwrap();
+
+        let sql = "SELECT MIN(x), MAX(x) FROM foo";
+        let plan = ds
+            .sql(sql)
+            .table_name("foo")
+            .build()
+            .await
+            .unwrap()
+            .into_explain_plan(false, false)
+            .await
+            .unwrap();
+        assert!(plan.contains("MetadataAggregate"), "{}", plan);
+        let results = ds
+            .sql(sql)
+            .table_name("foo")
+            .build()
+            .await
+            .unwrap()
+            .into_batch_records()
+            .await
+            .unwrap();
+        let batch = arrow::compute::concat_batches(&results[0].schema(), &results).unwrap();
+        // MIN ignores the nulls, even though they are sorted first in the index
+        assert_eq!(batch.column(0).as_primitive::<Int32Type>().value(0), 1);
+        assert_eq!(batch.column(1).as_primitive::<Int32Type>().value(0), 99);
+    }
 }
@@ -50,6 +50,7 @@ use std::{
     sync::Arc,
 };
 
+use super::partition::retain_partition_config;
 use super::ManifestWriteConfig;
 use crate::index::mem_wal::update_mem_wal_index_in_indices_list;
 use crate::utils::temporal::timestamp_to_nanos;
@@ -1560,6 +1561,9 @@ impl Transaction {
             _ => {}
         }
 
+        // Schema evolution may drop a partition column
+        retain_partition_config(&mut manifest);
+
         if let Operation::ReserveFragments { num_fragments } = self.operation {
             manifest.max_fragment_id = Some(manifest.max_fragment_id.unwrap_or(0) + num_fragments);
         }
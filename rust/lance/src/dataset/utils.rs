@@ -73,3 +73,39 @@ pub fn make_rowaddr_capture_stream(
     let stream = RecordBatchStreamAdapter::new(schema, stream);
     Ok(Box::pin(stream))
 }
+
+/// Given a stream that includes the `column` row metadata column (for example
+/// [`ROW_ADDR`] or `_rowid`), return a stream that will capture the values in
+/// the order they arrive and return a stream without that column.
+///
+/// Unlike [`make_rowaddr_capture_stream`] this does not require the values to
+/// be sorted, so it can be used after the rows have been reordered.
+pub fn make_ordered_capture_stream(
+    column: &'static str,
+    values: Arc<RwLock<Vec<u64>>>,
+    target: SendableRecordBatchStream,
+) -> Result<SendableRecordBatchStream> {
+    let schema = target.schema();
+    let (col_idx, _) = schema
+        .column_with_name(column)
+        .unwrap_or_else(|| panic!("Received a batch without {}", column));
+
+    let remaining_cols = (0..schema.fields.len())
+        .filter(|col| *col != col_idx)
+        .collect::<Vec<_>>();
+    let schema = Arc::new(schema.project(&remaining_cols)?);
+
+    let stream = target.map(move |batch| {
+        let batch = batch?;
+        let arr = batch.column(col_idx);
+        let arr = arr
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap_or_else(|| panic!("{} had an unexpected type: {}", column, arr.data_type()));
+        values.write().unwrap().extend(arr.values().iter().copied());
+        Ok(batch.project(&remaining_cols)?)
+    });
+
+    let stream = RecordBatchStreamAdapter::new(schema, stream);
+    Ok(Box::pin(stream))
+}
@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::num::NonZero;
 use std::sync::Arc;
 
@@ -8,9 +10,9 @@ use arrow_array::RecordBatch;
 use chrono::TimeDelta;
 use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
 use datafusion::physical_plan::SendableRecordBatchStream;
-use futures::{Stream, StreamExt, TryStreamExt};
+use futures::{FutureExt, Stream, StreamExt, TryStreamExt};
 use lance_core::datatypes::{
-    NullabilityComparison, OnMissing, OnTypeMismatch, SchemaCompareOptions, StorageClass,
+    Field, NullabilityComparison, OnMissing, OnTypeMismatch, SchemaCompareOptions, StorageClass,
 };
 use lance_core::error::LanceOptionExt;
 use lance_core::utils::tracing::{AUDIT_MODE_CREATE, AUDIT_TYPE_DATA, TRACE_FILE_AUDIT};
@@ -23,7 +25,7 @@ use lance_file::v2::writer::FileWriterOptions;
 use lance_file::version::LanceFileVersion;
 use lance_file::writer::{FileWriter, ManifestProvider};
 use lance_io::object_store::{ObjectStore, ObjectStoreParams, ObjectStoreRegistry};
-use lance_table::format::{DataFile, Fragment};
+use lance_table::format::{DataFile, Fragment, PartitionValues};
 use lance_table::io::commit::{commit_handler_from_url, CommitHandler};
 use lance_table::io::manifest::ManifestDescribing;
 use object_store::path::Path;
@@ -35,6 +37,7 @@ use crate::session::Session;
 use crate::Dataset;
 
 use super::blob::BlobStreamExt;
+use super::partition::{partition_batch, resolve_partition_fields};
 use super::progress::{NoopFragmentWriteProgress, WriteFragmentProgress};
 use super::transaction::Transaction;
 use super::DATA_DIR;
@@ -214,6 +217,23 @@ pub struct WriteParams {
     /// if the writer does not have delete permissions and the clean up would
     /// just try and log a failure anyway. Default is false.
     pub skip_auto_cleanup: bool,
+
+    /// Columns to partition the dataset by.
+    ///
+    /// This is only used when creating or overwriting a dataset. Rows are routed so
+    /// that every fragment only contains rows sharing the same values for these
+    /// columns, which allows filters on them to skip entire fragments. Appends to a
+    /// partitioned dataset always use the partition columns of the dataset.
+    ///
+    /// Partition columns must be top-level boolean, integer, string or date columns.
+    /// Each partition keeps its own file open while writing, so the number of
+    /// distinct partitions in a single write should be kept reasonably small.
+    pub partition_columns: Option<Vec<String>>,
+
+    /// If true, a [`WriteMode::Overwrite`] of a partitioned dataset only replaces
+    /// the partitions that are present in the new data. All other partitions are
+    /// left untouched. Default is false.
+    pub dynamic_partition_overwrite: bool,
 }
 
 impl Default for WriteParams {
@@ -234,6 +254,8 @@ impl Default for WriteParams {
             session: None,
             auto_cleanup: Some(AutoCleanupParams::default()),
             skip_auto_cleanup: false,
+            partition_columns: None,
+            dynamic_partition_overwrite: false,
         }
     }
 }
@@ -344,6 +366,144 @@ pub async fn do_write_fragments(
     Ok(fragments)
 }
 
+/// A data file being written for a single partition.
+struct OpenPartitionFile {
+    writer: Box<dyn GenericWriter>,
+    /// Position of the fragment being written in the output fragments
+    fragment_index: usize,
+    num_rows: usize,
+}
+
+/// Writes the given data to the dataset, routing rows by partition.
+///
+/// Each partition has its own writer, so every fragment returned only contains
+/// rows sharing the same values for `partition_fields`, and records those values
+/// in [`Fragment::partition_values`].
+async fn do_write_partitioned_fragments(
+    object_store: Arc<ObjectStore>,
+    base_dir: &Path,
+    schema: &Schema,
+    mut data: SendableRecordBatchStream,
+    params: WriteParams,
+    storage_version: LanceFileVersion,
+    partition_fields: Vec<Field>,
+) -> Result<Vec<Fragment>> {
+    let writer_generator = WriterGenerator::new(object_store, base_dir, schema, storage_version);
+    let mut open_files: HashMap<PartitionValues, OpenPartitionFile> = HashMap::new();
+    let mut fragments = Vec::new();
+
+    async fn finish_file(
+        open_file: OpenPartitionFile,
+        fragments: &mut [Fragment],
+        params: &WriteParams,
+    ) -> Result<()> {
+        let OpenPartitionFile {
+            mut writer,
+            fragment_index,
+            ..
+        } = open_file;
+        let (num_rows, data_file) = writer.finish().await?;
+        info!(target: TRACE_FILE_AUDIT, mode=AUDIT_MODE_CREATE, r#type=AUDIT_TYPE_DATA, path = &data_file.path);
+        let fragment = &mut fragments[fragment_index];
+        fragment.physical_rows = Some(num_rows as usize);
+        fragment.files.push(data_file);
+        params.progress.complete(fragment).await
+    }
+
+    while let Some(batch) = data.next().await {
+        let batch = batch?;
+        for (values, mut batch) in partition_batch(&batch, &partition_fields)? {
+            while batch.num_rows() > 0 {
+                let open_file = match open_files.entry(values.clone()) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let (writer, fragment) = writer_generator.new_writer().await?;
+                        let fragment = fragment.with_partition_values(values.clone());
+                        params.progress.begin(&fragment).await?;
+                        fragments.push(fragment);
+                        entry.insert(OpenPartitionFile {
+                            writer,
+                            fragment_index: fragments.len() - 1,
+                            num_rows: 0,
+                        })
+                    }
+                };
+
+                // Split the batch on file boundaries
+                let num_rows = batch
+                    .num_rows()
+                    .min(params.max_rows_per_file - open_file.num_rows);
+                let chunk = batch.slice(0, num_rows);
+                batch = batch.slice(num_rows, batch.num_rows() - num_rows);
+
+                open_file.writer.write(&[chunk]).await?;
+                open_file.num_rows += num_rows;
+
+                if open_file.num_rows >= params.max_rows_per_file
+                    || open_file.writer.tell().await? >= params.max_bytes_per_file as u64
+                {
+                    let open_file = open_files.remove(&values).unwrap();
+                    finish_file(open_file, &mut fragments, &params).await?;
+                }
+            }
+        }
+    }
+
+    // Complete the remaining writers, in the order their fragments were created
+    let mut open_files = open_files.into_values().collect::<Vec<_>>();
+    open_files.sort_by_key(|open_file| open_file.fragment_index);
+    for open_file in open_files {
+        finish_file(open_file, &mut fragments, &params).await?;
+    }
+
+    Ok(fragments)
+}
+
+/// Determine the partition fields the written rows should be routed by.
+pub(crate) fn write_partition_fields(
+    dataset: Option<&Dataset>,
+    params: &WriteParams,
+    data_schema: &Schema,
+) -> Result<Vec<Field>> {
+    let Some(dataset) = dataset else {
+        // A brand new dataset may declare its partition columns
+        return params
+            .partition_columns
+            .as_ref()
+            .map(|columns| resolve_partition_fields(data_schema, columns))
+            .transpose()
+            .map(Option::unwrap_or_default);
+    };
+
+    let existing_columns = dataset.partition_columns()?;
+    let columns = match (&params.partition_columns, params.mode) {
+        (Some(columns), WriteMode::Overwrite) => {
+            return resolve_partition_fields(data_schema, columns);
+        }
+        (Some(columns), _) if columns != &existing_columns => {
+            return Err(Error::invalid_input(
+                format!(
+                    "Cannot change the partition columns of an existing dataset from {:?} to {:?} \
+                    unless overwriting it",
+                    existing_columns, columns
+                ),
+                location!(),
+            ));
+        }
+        _ => existing_columns,
+    };
+
+    // Rows written without the partition columns (e.g. when adding new columns)
+    // can not be routed, so they are written as unpartitioned fragments.
+    if columns
+        .iter()
+        .any(|column| data_schema.fields.iter().all(|f| &f.name != column))
+    {
+        return Ok(vec![]);
+    }
+    resolve_partition_fields(data_schema, &columns)
+}
+
 pub struct WrittenFragments {
     /// The fragments written to the dataset (and the schema)
     pub default: (Vec<Fragment>, Schema),
@@ -426,8 +586,23 @@ pub async fn write_fragments_internal(
         OnTypeMismatch::Error,
     )?;
 
+    let partition_fields = write_partition_fields(dataset, &params, &data_schema)?;
+    if !partition_fields.is_empty() && storage_version == LanceFileVersion::Legacy {
+        return Err(Error::NotSupported {
+            source: "Partitioned datasets require Lance file format version 2.0 or later".into(),
+            location: location!(),
+        });
+    }
+
     let (data, blob_data) = data.extract_blob_stream(&data_schema);
 
+    if blob_data.is_some() && !partition_fields.is_empty() {
+        return Err(Error::NotSupported {
+            source: "The blob storage class is not supported on partitioned datasets".into(),
+            location: location!(),
+        });
+    }
+
     // Some params we borrow from the normal write, some we override
     let blob_write_params = WriteParams {
         store_params: params.store_params.clone(),
@@ -449,14 +624,28 @@ pub async fn write_fragments_internal(
     }
 
     let frag_schema = schema.retain_storage_class(StorageClass::Default);
-    let fragments_fut = do_write_fragments(
-        object_store.clone(),
-        base_dir,
-        &frag_schema,
-        data,
-        params,
-        storage_version,
-    );
+    let fragments_fut = if partition_fields.is_empty() {
+        do_write_fragments(
+            object_store.clone(),
+            base_dir,
+            &frag_schema,
+            data,
+            params,
+            storage_version,
+        )
+        .boxed()
+    } else {
+        do_write_partitioned_fragments(
+            object_store.clone(),
+            base_dir,
+            &frag_schema,
+            data,
+            params,
+            storage_version,
+            partition_fields,
+        )
+        .boxed()
+    };
 
     let (default, blob) = if let Some(blob_data) = blob_data {
         let blob_schema = schema.retain_storage_class(StorageClass::Blob);
@@ -515,8 +704,8 @@ impl<M: ManifestProvider + Send + Sync> GenericWriter for (FileWriter<M>, String
     }
 }
 
-struct V2WriterAdapter {
-    writer: v2::writer::FileWriter,
+pub(super) struct V2WriterAdapter {
+    pub(super) writer: v2::writer::FileWriter,
     path: String,
 }
 
@@ -580,24 +769,35 @@ pub async fn open_writer(
             filename,
         ))
     } else {
-        let writer = object_store.create(&full_path).await?;
-        let file_writer = v2::writer::FileWriter::try_new(
-            writer,
-            schema.clone(),
-            FileWriterOptions {
-                format_version: Some(storage_version),
-                ..Default::default()
-            },
-        )?;
-        let writer_adapter = V2WriterAdapter {
-            writer: file_writer,
-            path: filename,
-        };
-        Box::new(writer_adapter) as Box<dyn GenericWriter>
+        Box::new(open_v2_writer(object_store, schema, base_dir, storage_version).await?)
+            as Box<dyn GenericWriter>
     };
     Ok(writer)
 }
 
+pub(super) async fn open_v2_writer(
+    object_store: &ObjectStore,
+    schema: &Schema,
+    base_dir: &Path,
+    storage_version: LanceFileVersion,
+) -> Result<V2WriterAdapter> {
+    let filename = format!("{}.lance", Uuid::new_v4());
+    let full_path = base_dir.child(DATA_DIR).child(filename.as_str());
+    let writer = object_store.create(&full_path).await?;
+    let file_writer = v2::writer::FileWriter::try_new(
+        writer,
+        schema.clone(),
+        FileWriterOptions {
+            format_version: Some(storage_version),
+            ..Default::default()
+        },
+    )?;
+    Ok(V2WriterAdapter {
+        writer: file_writer,
+        path: filename,
+    })
+}
+
 /// Creates new file writers for a given dataset.
 struct WriterGenerator {
     object_store: Arc<ObjectStore>,
@@ -638,7 +838,7 @@ impl WriterGenerator {
 }
 
 // Given input options resolve what the commit handler should be.
-async fn resolve_commit_handler(
+pub(super) async fn resolve_commit_handler(
     uri: &str,
     commit_handler: Option<Arc<dyn CommitHandler>>,
     store_options: &Option<ObjectStoreParams>,
@@ -368,6 +368,7 @@ impl<'a> CommitBuilder<'a> {
                 index_cache,
                 metadata_cache,
                 file_reader_options: None,
+                store_params: self.store_params.clone().map(Box::new),
             }),
         }
     }
@@ -470,10 +471,12 @@ mod tests {
                 file_major_version: 2,
                 file_minor_version: 0,
                 file_size_bytes: CachedFileSize::new(100),
+                base_id: None,
             }],
             deletion_file: None,
             row_id_meta: None,
             physical_rows: Some(10),
+            partition_values: None,
         }
     }
 
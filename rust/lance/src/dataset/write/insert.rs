@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use arrow_array::RecordBatch;
@@ -23,9 +23,10 @@ use object_store::path::Path;
 use snafu::location;
 
 use crate::dataset::builder::DatasetBuilder;
+use crate::dataset::partition::{partition_columns_config_value, PARTITION_COLUMNS_CONFIG_KEY};
 use crate::dataset::transaction::Operation;
 use crate::dataset::transaction::Transaction;
-use crate::dataset::write::write_fragments_internal;
+use crate::dataset::write::{write_fragments_internal, write_partition_fields};
 use crate::dataset::ReadParams;
 use crate::Dataset;
 use crate::{Error, Result};
@@ -246,6 +247,14 @@ impl<'a> InsertBuilder<'a> {
                     }
                     None => None,
                 };
+                let config_upsert_values = match Self::partition_config(context, &schema)? {
+                    Some(partition_config) => {
+                        let mut upsert_values = config_upsert_values.unwrap_or_default();
+                        upsert_values.extend(partition_config);
+                        Some(upsert_values)
+                    }
+                    None => config_upsert_values,
+                };
                 Operation::Overwrite {
                     // Use the full schema, not the written schema
                     schema,
@@ -253,12 +262,43 @@ impl<'a> InsertBuilder<'a> {
                     config_upsert_values,
                 }
             }
-            WriteMode::Overwrite => Operation::Overwrite {
-                // Use the full schema, not the written schema
-                schema,
-                fragments: written_frags.default.0,
-                config_upsert_values: None,
-            },
+            WriteMode::Overwrite => {
+                let config_upsert_values = Self::partition_config(context, &schema)?;
+                Operation::Overwrite {
+                    // Use the full schema, not the written schema
+                    schema,
+                    fragments: written_frags.default.0,
+                    config_upsert_values,
+                }
+            }
+            WriteMode::Append if context.overwrite_partitions => {
+                let new_fragments = written_frags.default.0;
+                let replaced_partitions = new_fragments
+                    .iter()
+                    .filter_map(|fragment| fragment.partition_values.as_ref())
+                    .collect::<HashSet<_>>();
+                let removed_fragment_ids = context
+                    .dest
+                    .dataset()
+                    .expect("partition overwrite requires an existing dataset")
+                    .fragments()
+                    .iter()
+                    .filter(|fragment| {
+                        fragment
+                            .partition_values
+                            .as_ref()
+                            .is_some_and(|values| replaced_partitions.contains(values))
+                    })
+                    .map(|fragment| fragment.id)
+                    .collect();
+                Operation::Update {
+                    removed_fragment_ids,
+                    updated_fragments: vec![],
+                    new_fragments,
+                    fields_modified: vec![],
+                    mem_wal_to_flush: None,
+                }
+            }
             WriteMode::Append => Operation::Append {
                 fragments: written_frags.default.0,
             },
@@ -285,6 +325,29 @@ impl<'a> InsertBuilder<'a> {
         ))
     }
 
+    /// The table config declaring the partition columns of the written `schema`.
+    ///
+    /// Overwriting a partitioned dataset always re-declares its partition columns,
+    /// since the field ids of the new schema may differ. If the new data does not
+    /// have the partition columns, the dataset is no longer partitioned.
+    fn partition_config(
+        context: &WriteContext<'_>,
+        schema: &Schema,
+    ) -> Result<Option<HashMap<String, String>>> {
+        let is_partitioned = match context.dest.dataset() {
+            Some(dataset) => !dataset.partition_columns()?.is_empty(),
+            None => false,
+        };
+        if context.params.partition_columns.is_none() && !is_partitioned {
+            return Ok(None);
+        }
+        let fields = write_partition_fields(context.dest.dataset(), &context.params, schema)?;
+        Ok(Some(HashMap::from([(
+            PARTITION_COLUMNS_CONFIG_KEY.to_string(),
+            partition_columns_config_value(&fields),
+        )])))
+    }
+
     fn validate_write(&self, context: &mut WriteContext, data_schema: &Schema) -> Result<()> {
         // Write mode
         match (&context.params.mode, &context.dest) {
@@ -301,6 +364,51 @@ impl<'a> InsertBuilder<'a> {
             _ => {}
         }
 
+        // Partition overwrite, this is an append that replaces the partitions written to
+        if let (WriteMode::Overwrite, true, WriteDestination::Dataset(dataset)) = (
+            &context.params.mode,
+            context.params.dynamic_partition_overwrite,
+            &context.dest,
+        ) {
+            let partition_columns = dataset.partition_columns()?;
+            if partition_columns.is_empty() {
+                return Err(Error::InvalidInput {
+                    source: "Dynamic partition overwrite requires a partitioned dataset".into(),
+                    location: location!(),
+                });
+            }
+            if let Some(missing) = partition_columns
+                .iter()
+                .find(|column| data_schema.field(column).is_none())
+            {
+                return Err(Error::InvalidInput {
+                    source: format!(
+                        "The partition column {} is required for a dynamic partition overwrite",
+                        missing
+                    )
+                    .into(),
+                    location: location!(),
+                });
+            }
+            // The rows of the unpartitioned fragments can not be matched to the
+            // replaced partitions, so they would be kept next to the new rows
+            if dataset
+                .fragments()
+                .iter()
+                .any(|fragment| fragment.partition_values.is_none())
+            {
+                return Err(Error::InvalidInput {
+                    source: "Dynamic partition overwrite is not supported on a dataset with \
+                        unpartitioned fragments, overwrite the whole dataset instead"
+                        .into(),
+                    location: location!(),
+                });
+            }
+            context.params.mode = WriteMode::Append;
+            context.storage_version = dataset.manifest.data_storage_format.lance_file_version()?;
+            context.overwrite_partitions = true;
+        }
+
         // Validate schema
         if matches!(context.params.mode, WriteMode::Append) {
             if let WriteDestination::Dataset(dataset) = &context.dest {
@@ -456,6 +564,7 @@ impl<'a> InsertBuilder<'a> {
             base_path,
             commit_handler,
             storage_version,
+            overwrite_partitions: false,
         })
     }
 }
@@ -468,6 +577,8 @@ struct WriteContext<'a> {
     base_path: Path,
     commit_handler: Arc<dyn CommitHandler>,
     storage_version: LanceFileVersion,
+    /// True if only the partitions present in the written data are replaced
+    overwrite_partitions: bool,
 }
 
 #[cfg(test)]
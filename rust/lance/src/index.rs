@@ -27,11 +27,14 @@ use lance_index::frag_reuse::{FragReuseIndex, FRAG_REUSE_INDEX_NAME};
 use lance_index::mem_wal::{MemWalIndex, MEM_WAL_INDEX_NAME};
 use lance_index::pb::index::Implementation;
 use lance_index::scalar::expression::{
-    FtsQueryParser, IndexInformationProvider, LabelListQueryParser, MultiQueryParser,
-    SargableQueryParser, ScalarQueryParser, TextQueryParser,
+    json_index_key, FtsQueryParser, IndexInformationProvider, JsonQueryParser,
+    LabelListQueryParser, MultiQueryParser, SargableQueryParser, ScalarQueryParser,
+    TextQueryParser,
 };
+use lance_index::scalar::json::JsonIndexParams;
 use lance_index::scalar::lance_format::LanceIndexStore;
 use lance_index::scalar::{ScalarIndex, ScalarIndexType};
+use lance_index::vector::diskann::DiskAnnIndex;
 use lance_index::vector::flat::index::{FlatBinQuantizer, FlatIndex, FlatQuantizer};
 use lance_index::vector::hnsw::HNSW;
 use lance_index::vector::pq::ProductQuantizer;
@@ -61,8 +64,8 @@ use lance_table::format::{Fragment, SelfDescribingFileReader};
 use lance_table::io::manifest::read_manifest_indexes;
 use roaring::RoaringBitmap;
 use scalar::{
-    build_inverted_index, detect_scalar_index_type, index_matches_criteria, infer_index_type,
-    inverted_index_details, TrainingRequest,
+    build_inverted_index, build_json_index, detect_scalar_index_type, index_matches_criteria,
+    infer_index_type, inverted_index_details, json_index_path, TrainingRequest,
 };
 use serde_json::json;
 use snafu::location;
@@ -72,6 +75,7 @@ use vector::ivf::v2::IVFIndex;
 use vector::utils::get_vector_type;
 
 pub(crate) mod append;
+pub(crate) mod distributed;
 pub mod frag_reuse;
 pub mod mem_wal;
 pub mod prefilter;
@@ -347,7 +351,7 @@ async fn open_index_proto(reader: &dyn Reader) -> Result<pb::Index> {
     Ok(proto)
 }
 
-fn vector_index_details() -> prost_types::Any {
+pub(crate) fn vector_index_details() -> prost_types::Any {
     let details = lance_table::format::pb::VectorIndexDetails::default();
     prost_types::Any::from_msg(&details).unwrap()
 }
@@ -439,6 +443,16 @@ impl DatasetIndexExt for Dataset {
                 build_inverted_index(self, column, &index_id.to_string(), inverted_params).await?;
                 inverted_index_details()
             }
+            (IndexType::Json, _) => {
+                let json_params = params
+                    .as_any()
+                    .downcast_ref::<JsonIndexParams>()
+                    .ok_or_else(|| Error::Index {
+                        message: "Json index type must take a JsonIndexParams".to_string(),
+                        location: location!(),
+                    })?;
+                build_json_index(self, column, &index_id.to_string(), json_params).await?
+            }
             (IndexType::Vector, LANCE_VECTOR_INDEX) => {
                 // Vector index params.
                 let vec_params = params
@@ -513,6 +527,7 @@ impl DatasetIndexExt for Dataset {
             index_details: Some(index_details),
             index_version: index_type.version(),
             created_at: Some(chrono::Utc::now()),
+            base_id: None,
         };
         let transaction = Transaction::new(
             self.manifest.version,
@@ -644,6 +659,7 @@ impl DatasetIndexExt for Dataset {
             index_details: None,
             index_version: 0,
             created_at: Some(chrono::Utc::now()),
+            base_id: None,
         };
 
         let transaction = Transaction::new(
@@ -743,6 +759,7 @@ impl DatasetIndexExt for Dataset {
                 index_details: last_idx.index_details.clone(),
                 index_version: res.new_index_version,
                 created_at: Some(chrono::Utc::now()),
+                base_id: None,
             };
             removed_indices.extend(res.removed_indices.iter().map(|&idx| idx.clone()));
             if deltas.len() > removed_indices.len() {
@@ -1078,7 +1095,7 @@ impl DatasetIndexInternalExt for Dataset {
         // scalar indices, we may start having this file with scalar indices too.  Once that happens
         // we can just read this file and look at the `implementation` or `index_type` fields to
         // determine what kind of index it is.
-        let index_dir = self.indices_dir().child(uuid);
+        let index_dir = self.indices_dir_for(uuid).await?.child(uuid);
         let index_file = index_dir.child(INDEX_FILE_NAME);
         if self.object_store.exists(&index_file).await? {
             let index = self.open_vector_index(column, uuid, metrics).await?;
@@ -1132,7 +1149,8 @@ impl DatasetIndexInternalExt for Dataset {
         }
 
         let frag_reuse_index = self.open_frag_reuse_index(metrics).await?;
-        let index_dir = self.indices_dir().child(uuid);
+        let indices_dir = self.indices_dir_for(uuid).await?;
+        let index_dir = indices_dir.child(uuid);
         let index_file = index_dir.child(INDEX_FILE_NAME);
         let reader: Arc<dyn Reader> = self.object_store.open(&index_file).await?.into();
 
@@ -1224,7 +1242,7 @@ impl DatasetIndexInternalExt for Dataset {
                         DataType::Float16 | DataType::Float32 | DataType::Float64 => {
                             let ivf = IVFIndex::<FlatIndex, FlatQuantizer>::try_new(
                                 self.object_store.clone(),
-                                self.indices_dir(),
+                                indices_dir.clone(),
                                 uuid.to_owned(),
                                 frag_reuse_index,
                                 self.metadata_cache.as_ref(),
@@ -1236,7 +1254,7 @@ impl DatasetIndexInternalExt for Dataset {
                         DataType::UInt8 => {
                             let ivf = IVFIndex::<FlatIndex, FlatBinQuantizer>::try_new(
                                 self.object_store.clone(),
-                                self.indices_dir(),
+                                indices_dir.clone(),
                                 uuid.to_owned(),
                                 frag_reuse_index,
                                 self.metadata_cache.as_ref(),
@@ -1257,7 +1275,7 @@ impl DatasetIndexInternalExt for Dataset {
                     "IVF_PQ" => {
                         let ivf = IVFIndex::<FlatIndex, ProductQuantizer>::try_new(
                             self.object_store.clone(),
-                            self.indices_dir(),
+                            indices_dir.clone(),
                             uuid.to_owned(),
                             frag_reuse_index,
                             self.metadata_cache.as_ref(),
@@ -1270,7 +1288,7 @@ impl DatasetIndexInternalExt for Dataset {
                     "IVF_SQ" => {
                         let ivf = IVFIndex::<FlatIndex, ScalarQuantizer>::try_new(
                             self.object_store.clone(),
-                            self.indices_dir(),
+                            indices_dir.clone(),
                             uuid.to_owned(),
                             frag_reuse_index,
                             self.metadata_cache.as_ref(),
@@ -1281,12 +1299,12 @@ impl DatasetIndexInternalExt for Dataset {
                     }
 
                     "IVF_HNSW_FLAT" => {
-                        let uri = self.indices_dir().child(uuid).child("index.pb");
+                        let uri = index_dir.child("index.pb");
                         let file_metadata_cache =
                             self.session.metadata_cache.file_metadata_cache(&uri);
                         let ivf = IVFIndex::<HNSW, FlatQuantizer>::try_new(
                             self.object_store.clone(),
-                            self.indices_dir(),
+                            indices_dir.clone(),
                             uuid.to_owned(),
                             frag_reuse_index,
                             &file_metadata_cache,
@@ -1299,7 +1317,7 @@ impl DatasetIndexInternalExt for Dataset {
                     "IVF_HNSW_SQ" => {
                         let ivf = IVFIndex::<HNSW, ScalarQuantizer>::try_new(
                             self.object_store.clone(),
-                            self.indices_dir(),
+                            indices_dir.clone(),
                             uuid.to_owned(),
                             frag_reuse_index,
                             self.metadata_cache.as_ref(),
@@ -1312,7 +1330,7 @@ impl DatasetIndexInternalExt for Dataset {
                     "IVF_HNSW_PQ" => {
                         let ivf = IVFIndex::<HNSW, ProductQuantizer>::try_new(
                             self.object_store.clone(),
-                            self.indices_dir(),
+                            indices_dir.clone(),
                             uuid.to_owned(),
                             frag_reuse_index,
                             self.metadata_cache.as_ref(),
@@ -1322,6 +1340,17 @@ impl DatasetIndexInternalExt for Dataset {
                         Ok(Arc::new(ivf) as Arc<dyn VectorIndex>)
                     }
 
+                    "DISKANN" => {
+                        let diskann = DiskAnnIndex::try_new(
+                            self.object_store.clone(),
+                            index_dir.clone(),
+                            frag_reuse_index,
+                            self.metadata_cache.as_ref(),
+                        )
+                        .await?;
+                        Ok(Arc::new(diskann) as Arc<dyn VectorIndex>)
+                    }
+
                     _ => Err(Error::Index {
                         message: format!("Unsupported index type: {}", index_metadata.index_type),
                         location: location!(),
@@ -1443,6 +1472,20 @@ impl DatasetIndexInternalExt for Dataset {
                 location: location!(),
             })?;
 
+            // JSON indices are looked up by the extracted path, not the column
+            if let Some(path) = json_index_path(index)? {
+                let query_parser =
+                    JsonQueryParser::new(index.name.clone(), field.name.clone(), path.clone());
+                indexed_fields.push((
+                    json_index_key(&field.name, &path),
+                    (
+                        DataType::Utf8,
+                        Box::new(query_parser) as Box<dyn ScalarQueryParser>,
+                    ),
+                ));
+                continue;
+            }
+
             let query_parser = match field.data_type() {
                 DataType::List(_) => Box::new(LabelListQueryParser::new(index.name.clone()))
                     as Box<dyn ScalarQueryParser>,
@@ -2308,6 +2351,100 @@ mod tests {
         assert_eq!(index.index_type(), IndexType::Bitmap);
     }
 
+    #[tokio::test]
+    async fn test_create_json_index() {
+        use lance_arrow::json::{json_array_from_strs, json_field};
+
+        fn make_reader(
+            range: std::ops::Range<usize>,
+        ) -> impl arrow_array::RecordBatchReader + Send + 'static {
+            let schema = Arc::new(Schema::new(vec![json_field("payload", true)]));
+            let docs = range
+                .map(|i| format!(r#"{{"user_id": "u{}", "n": {}}}"#, i % 10, i))
+                .collect::<Vec<_>>();
+            let array = json_array_from_strs(docs.iter().map(|doc| Some(doc.as_str()))).unwrap();
+            let batch = RecordBatch::try_new(schema.clone(), vec![array]).unwrap();
+            RecordBatchIterator::new(vec![Ok(batch)], schema)
+        }
+
+        let test_dir = tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+        let mut dataset = Dataset::write(make_reader(0..100), test_uri, None)
+            .await
+            .unwrap();
+        dataset
+            .create_index(
+                &["payload"],
+                IndexType::Json,
+                Some("user_idx".to_string()),
+                &JsonIndexParams::new("user_id"),
+                false,
+            )
+            .await
+            .unwrap();
+        let indices = dataset.load_indices().await.unwrap();
+        let index = dataset
+            .open_generic_index(
+                "payload",
+                &indices[0].uuid.to_string(),
+                &NoOpMetricsCollector,
+            )
+            .await
+            .unwrap();
+        assert_eq!(index.index_type(), IndexType::Json);
+
+        let filter = "payload->>'user_id' = 'u3'";
+        let mut scan = dataset.scan();
+        scan.filter(filter).unwrap();
+        let plan = scan.explain_plan(true).await.unwrap();
+        assert!(plan.contains("ScalarIndexQuery"), "{}", plan);
+        assert_eq!(
+            dataset.count_rows(Some(filter.to_string())).await.unwrap(),
+            10
+        );
+        // Paths other than the indexed one are filtered without the index
+        assert_eq!(
+            dataset
+                .count_rows(Some("payload->>'n' = '42'".to_string()))
+                .await
+                .unwrap(),
+            1
+        );
+
+        // New data is searched both before and after the index is updated
+        dataset.append(make_reader(100..150), None).await.unwrap();
+        assert_eq!(
+            dataset.count_rows(Some(filter.to_string())).await.unwrap(),
+            15
+        );
+        dataset
+            .optimize_indices(&OptimizeOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            dataset.count_rows(Some(filter.to_string())).await.unwrap(),
+            15
+        );
+        assert!(dataset
+            .unindexed_fragments("user_idx")
+            .await
+            .unwrap()
+            .is_empty());
+
+        // A JSON index must be created with JsonIndexParams
+        let err = dataset
+            .create_index(
+                &["payload"],
+                IndexType::Json,
+                Some("bad_idx".to_string()),
+                &ScalarIndexParams::default(),
+                false,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Index { .. }), "{}", err);
+    }
+
     // #[tokio::test]
     #[lance_test_macros::test(tokio::test)]
     async fn test_load_indices() {
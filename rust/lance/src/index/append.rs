@@ -120,7 +120,8 @@ pub async fn merge_indices<'a>(
 
             let mut scanner = dataset.scan();
             let orodering = match index.index_type() {
-                IndexType::Inverted => None,
+                // A JSON index orders by the extracted value itself
+                IndexType::Inverted | IndexType::Json => None,
                 _ => Some(vec![ColumnOrdering::asc_nulls_first(column.name.clone())]),
             };
             scanner
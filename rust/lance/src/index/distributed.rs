@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! The parts shared by the distributed builds of the vector and the scalar indices.
+//!
+//! The workers build partial indices over disjoint fragment sets, each of them in a
+//! directory under the index directory, and the coordinator merges them into the
+//! final index and commits it.  A partial index records the fragments it covers, so
+//! a finished partial index is reused only if it's asked for with the same fragments.
+
+use lance_index::{DatasetIndexExt, IndexType};
+use lance_io::object_store::ObjectStore;
+use lance_table::format::{Fragment, Index as IndexMetadata};
+use object_store::path::Path;
+use roaring::RoaringBitmap;
+use snafu::location;
+use uuid::Uuid;
+
+use crate::dataset::transaction::{Operation, Transaction};
+use crate::dataset::Dataset;
+use crate::{Error, Result};
+
+// the fragments of a partial index, written before building it
+const FRAGMENTS_FILE: &str = "fragments.json";
+
+/// Sort and dedup the fragment ids of a partial index, and look up the fragments.
+pub(crate) fn resolve_fragments(
+    dataset: &Dataset,
+    fragment_ids: &[u32],
+) -> Result<(Vec<u32>, Vec<Fragment>)> {
+    let mut fragment_ids = fragment_ids.to_vec();
+    fragment_ids.sort_unstable();
+    fragment_ids.dedup();
+    if fragment_ids.is_empty() {
+        return Err(Error::invalid_input(
+            "building partial index: no fragments given",
+            location!(),
+        ));
+    }
+    let fragments = fragment_ids
+        .iter()
+        .map(|id| {
+            dataset
+                .get_fragment(*id as usize)
+                .map(|f| f.metadata().clone())
+                .ok_or_else(|| {
+                    Error::invalid_input(format!("fragment {} does not exist", id), location!())
+                })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok((fragment_ids, fragments))
+}
+
+/// Whether the partial index in `dir` is recorded to cover exactly the fragments.
+pub(crate) async fn is_built_over(
+    object_store: &ObjectStore,
+    dir: &Path,
+    fragment_ids: &[u32],
+) -> Result<bool> {
+    let path = dir.child(FRAGMENTS_FILE);
+    if !object_store.exists(&path).await? {
+        return Ok(false);
+    }
+    let recorded: Vec<u32> = serde_json::from_slice(&object_store.read_one_all(&path).await?)?;
+    Ok(recorded == fragment_ids)
+}
+
+/// Prepare the directory of a partial index before building it.
+///
+/// Returns `true` if the partial index is built over the same fragments and can be
+/// reused.  Otherwise the directory is cleared if it holds a partial index over other
+/// fragments, and the fragments are recorded.
+pub(crate) async fn prepare_partial(
+    object_store: &ObjectStore,
+    dir: &Path,
+    fragment_ids: &[u32],
+    is_built: bool,
+) -> Result<bool> {
+    if is_built {
+        if is_built_over(object_store, dir, fragment_ids).await? {
+            log::info!("partial index {} already built, skipping", dir);
+            return Ok(true);
+        }
+        log::warn!(
+            "partial index {} was built over other fragments, rebuilding it over {:?}",
+            dir,
+            fragment_ids
+        );
+        object_store.remove_dir_all(dir.clone()).await?;
+    }
+    object_store
+        .put(
+            &dir.child(FRAGMENTS_FILE),
+            &serde_json::to_vec(fragment_ids)?,
+        )
+        .await?;
+    Ok(false)
+}
+
+/// The fragments covered by the partial indices, they must be disjoint.
+pub(crate) fn union_fragments<'a>(
+    partials: impl IntoIterator<Item = &'a [u32]>,
+) -> Result<RoaringBitmap> {
+    let mut fragment_bitmap = RoaringBitmap::new();
+    for fragment_ids in partials {
+        for id in fragment_ids {
+            if !fragment_bitmap.insert(*id) {
+                return Err(Error::invalid_input(
+                    format!("fragment {} is indexed by more than one partial index", id),
+                    location!(),
+                ));
+            }
+        }
+    }
+    if fragment_bitmap.is_empty() {
+        return Err(Error::invalid_input(
+            "committing distributed index: no partial indices given",
+            location!(),
+        ));
+    }
+    Ok(fragment_bitmap)
+}
+
+/// The merged index of a distributed build, to be committed.
+pub(crate) struct DistributedIndex<'a> {
+    pub uuid: Uuid,
+    pub name: &'a str,
+    pub column: &'a str,
+    pub index_type: IndexType,
+    pub index_details: prost_types::Any,
+}
+
+impl DistributedIndex<'_> {
+    /// Check that the index can be committed to the dataset, before merging it.
+    pub async fn check(&self, dataset: &Dataset) -> Result<()> {
+        if dataset.schema().field(self.column).is_none() {
+            return Err(Error::Index {
+                message: format!("CreateIndex: column '{}' does not exist", self.column),
+                location: location!(),
+            });
+        }
+        let indices = dataset.load_indices().await?;
+        if indices.iter().any(|idx| idx.uuid == self.uuid) {
+            return Err(Error::Index {
+                message: format!("Index {} has been committed already", self.uuid),
+                location: location!(),
+            });
+        }
+        if indices.iter().any(|idx| idx.name == self.name) {
+            return Err(Error::Index {
+                message: format!(
+                    "Index name '{}' already exists, please specify a different name",
+                    self.name
+                ),
+                location: location!(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Commit the index over the fragments.
+    pub async fn commit(
+        self,
+        dataset: &mut Dataset,
+        fragment_bitmap: RoaringBitmap,
+    ) -> Result<IndexMetadata> {
+        let field = dataset
+            .schema()
+            .field(self.column)
+            .ok_or_else(|| Error::Index {
+                message: format!("CreateIndex: column '{}' does not exist", self.column),
+                location: location!(),
+            })?;
+        let new_idx = IndexMetadata {
+            uuid: self.uuid,
+            name: self.name.to_owned(),
+            fields: vec![field.id],
+            dataset_version: dataset.manifest.version,
+            fragment_bitmap: Some(fragment_bitmap),
+            index_details: Some(self.index_details),
+            index_version: self.index_type.version(),
+            created_at: Some(chrono::Utc::now()),
+            base_id: None,
+        };
+        let transaction = Transaction::new(
+            dataset.manifest.version,
+            Operation::CreateIndex {
+                new_indices: vec![new_idx.clone()],
+                removed_indices: vec![],
+            },
+            /*blobs_op= */ None,
+            None,
+        );
+        dataset
+            .apply_commit(transaction, &Default::default(), &Default::default())
+            .await?;
+        Ok(new_idx)
+    }
+}
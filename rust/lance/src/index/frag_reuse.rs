@@ -1,7 +1,9 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
-use crate::dataset::optimize::remapping::transpose_row_ids_from_digest;
+use crate::dataset::optimize::remapping::{
+    transpose_reordered_row_ids_from_digest, transpose_row_ids_from_digest,
+};
 use crate::Dataset;
 use lance_core::Error;
 use lance_index::frag_reuse::{
@@ -50,10 +52,7 @@ pub async fn load_frag_reuse_index_details(
             Ok(Arc::new(FragReuseIndexDetails::try_from(content.clone())?))
         }
         Some(Content::External(external_file)) => {
-            let file_path = dataset
-                .indices_dir()
-                .child(index.uuid.to_string())
-                .child(external_file.path.clone());
+            let file_path = dataset.index_dir(index)?.child(external_file.path.clone());
 
             // the file content will be cached in the index cache later
             // so we do not put it to the file cache
@@ -84,11 +83,16 @@ pub(crate) async fn open_frag_reuse_index(
         for group in version.groups.iter() {
             let cursor = Cursor::new(&group.changed_row_addrs);
             let changed_row_addrs = RoaringTreemap::deserialize_from(cursor).unwrap();
-            let group_row_id_map = transpose_row_ids_from_digest(
-                changed_row_addrs,
-                &group.old_frags,
-                &group.new_frags,
-            );
+            let group_row_id_map = if group.new_row_positions.is_empty() {
+                transpose_row_ids_from_digest(changed_row_addrs, &group.old_frags, &group.new_frags)
+            } else {
+                transpose_reordered_row_ids_from_digest(
+                    changed_row_addrs,
+                    &group.new_row_positions,
+                    &group.old_frags,
+                    &group.new_frags,
+                )
+            };
             row_id_map.extend(group_row_id_map);
         }
         row_id_maps.push(row_id_map);
@@ -176,5 +180,6 @@ pub(crate) async fn build_frag_reuse_index_metadata(
         index_details: Some(prost_types::Any::from_msg(&proto)?),
         index_version: index_meta.map_or(0, |index_meta| index_meta.index_version),
         created_at: Some(chrono::Utc::now()),
+        base_id: None,
     })
 }
@@ -248,6 +248,25 @@ pub async fn append_mem_wal_entry(
     mem_wal_generation: u64,
     entry_id: u64,
     expected_owner_id: &str,
+) -> Result<MemWal> {
+    append_mem_wal_entries(
+        dataset,
+        mem_wal_region,
+        mem_wal_generation,
+        &[entry_id],
+        expected_owner_id,
+    )
+    .await
+}
+
+/// Add new entries to the MemWAL in a single commit.
+/// The entry IDs must be in ascending order.
+pub async fn append_mem_wal_entries(
+    dataset: &mut Dataset,
+    mem_wal_region: &str,
+    mem_wal_generation: u64,
+    entry_ids: &[u64],
+    expected_owner_id: &str,
 ) -> Result<MemWal> {
     let mutate = |mem_wal: &MemWal| -> Result<MemWal> {
         // Can only append to open MemWALs
@@ -255,9 +274,11 @@ pub async fn append_mem_wal_entry(
         mem_wal.check_expected_owner_id(expected_owner_id)?;
 
         let mut updated_mem_wal = mem_wal.clone();
-        let wal_entries = updated_mem_wal.wal_entries();
-        updated_mem_wal.wal_entries =
-            pb::U64Segment::from(wal_entries.with_new_high(entry_id)?).encode_to_vec();
+        let mut wal_entries = updated_mem_wal.wal_entries();
+        for entry_id in entry_ids {
+            wal_entries = wal_entries.with_new_high(*entry_id)?;
+        }
+        updated_mem_wal.wal_entries = pb::U64Segment::from(wal_entries).encode_to_vec();
         Ok(updated_mem_wal)
     };
 
@@ -542,6 +563,7 @@ pub(crate) fn new_mem_wal_index_meta(
         ))?),
         index_version: 0,
         created_at: Some(chrono::Utc::now()),
+        base_id: None,
     })
 }
 
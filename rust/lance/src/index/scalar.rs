@@ -4,6 +4,8 @@
 //! Utilities for integrating scalar indices with datasets
 //!
 
+pub mod distributed;
+
 use std::sync::Arc;
 
 use crate::index::DatasetIndexInternalExt;
@@ -11,11 +13,12 @@ use crate::{
     dataset::{index::LanceIndexStoreExt, scanner::ColumnOrdering},
     Dataset,
 };
-use arrow_schema::DataType;
+use arrow_schema::{DataType, Field as ArrowField};
 use async_trait::async_trait;
 use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
 use datafusion::physical_plan::SendableRecordBatchStream;
 use futures::TryStreamExt;
+use lance_arrow::json::{is_json_field, JsonPath};
 use lance_core::datatypes::Field;
 use lance_core::{Error, Result};
 use lance_datafusion::{chunker::chunk_concat_stream, exec::LanceExecutionOptions};
@@ -34,13 +37,14 @@ use lance_index::{
         btree::{train_btree_index, BTreeIndex, TrainingSource},
         flat::FlatIndexMetadata,
         inverted::{train_inverted_index, InvertedIndex, INVERT_LIST_FILE},
+        json::{train_json_index, JsonIndex, JsonIndexParams},
         label_list::{train_label_list_index, LabelListIndex},
         lance_format::LanceIndexStore,
         ScalarIndex, ScalarIndexParams, ScalarIndexType,
     },
     IndexType,
 };
-use lance_table::format::Index;
+use lance_table::format::{Fragment, Index};
 use log::info;
 use snafu::location;
 use tracing::instrument;
@@ -51,6 +55,8 @@ const TRAINING_UPDATE_FREQ: usize = 1000000;
 pub(crate) struct TrainingRequest {
     dataset: Arc<Dataset>,
     column: String,
+    // only scan these fragments if set
+    fragments: Option<Vec<Fragment>>,
 }
 
 #[async_trait]
@@ -72,7 +78,17 @@ impl TrainingSource for TrainingRequest {
 
 impl TrainingRequest {
     pub fn new(dataset: Arc<Dataset>, column: String) -> Self {
-        Self { dataset, column }
+        Self {
+            dataset,
+            column,
+            fragments: None,
+        }
+    }
+
+    /// Train the index over the given fragments only.
+    pub fn with_fragments(mut self, fragments: Vec<Fragment>) -> Self {
+        self.fragments = Some(fragments);
+        self
     }
 
     async fn scan_chunks(
@@ -80,9 +96,18 @@ impl TrainingRequest {
         chunk_size: u32,
         sort: bool,
     ) -> Result<SendableRecordBatchStream> {
-        let num_rows = self.dataset.count_all_rows().await?;
+        let num_rows = match &self.fragments {
+            Some(fragments) => fragments
+                .iter()
+                .map(|f| f.num_rows().unwrap_or_default())
+                .sum(),
+            None => self.dataset.count_all_rows().await?,
+        };
 
         let mut scan = self.dataset.scan();
+        if let Some(fragments) = self.fragments.clone() {
+            scan.with_fragments(fragments);
+        }
 
         let column_field =
             self.dataset
@@ -175,6 +200,16 @@ fn ngram_index_details() -> prost_types::Any {
     prost_types::Any::from_msg(&details).unwrap()
 }
 
+fn json_index_details(params: &JsonIndexParams) -> prost_types::Any {
+    let details = lance_table::format::pb::JsonIndexDetails {
+        path: JsonPath::parse(&params.path)
+            .map(|path| path.to_string())
+            .unwrap_or_else(|_| params.path.clone()),
+        target_index_type: format!("{:?}", params.target_index_type),
+    };
+    prost_types::Any::from_msg(&details).unwrap()
+}
+
 pub(super) fn inverted_index_details() -> prost_types::Any {
     let details = lance_table::format::pb::InvertedIndexDetails::default();
     prost_types::Any::from_msg(&details).unwrap()
@@ -210,6 +245,12 @@ impl ScalarIndexDetails for lance_table::format::pb::NGramIndexDetails {
     }
 }
 
+impl ScalarIndexDetails for lance_table::format::pb::JsonIndexDetails {
+    fn get_type(&self) -> ScalarIndexType {
+        ScalarIndexType::Json
+    }
+}
+
 fn get_scalar_index_details(
     details: &prost_types::Any,
 ) -> Result<Option<Box<dyn ScalarIndexDetails>>> {
@@ -233,6 +274,10 @@ fn get_scalar_index_details(
         Ok(Some(Box::new(
             details.to_msg::<lance_table::format::pb::NGramIndexDetails>()?,
         )))
+    } else if details.type_url.ends_with("JsonIndexDetails") {
+        Ok(Some(Box::new(
+            details.to_msg::<lance_table::format::pb::JsonIndexDetails>()?,
+        )))
     } else {
         Ok(None)
     }
@@ -258,10 +303,23 @@ pub(super) async fn build_scalar_index(
     uuid: &str,
     params: &ScalarIndexParams,
 ) -> Result<prost_types::Any> {
-    let training_request = Box::new(TrainingRequest {
-        dataset: Arc::new(dataset.clone()),
-        column: column.to_string(),
-    });
+    let training_request = Box::new(TrainingRequest::new(
+        Arc::new(dataset.clone()),
+        column.to_string(),
+    ));
+    let index_store = LanceIndexStore::from_dataset(dataset, uuid);
+    train_scalar_index(dataset, column, training_request, &index_store, params).await
+}
+
+/// Train a Scalar Index from the training request into `index_store` (returns details to
+/// store in the manifest)
+pub(crate) async fn train_scalar_index(
+    dataset: &Dataset,
+    column: &str,
+    training_request: Box<TrainingRequest>,
+    index_store: &LanceIndexStore,
+    params: &ScalarIndexParams,
+) -> Result<prost_types::Any> {
     let field = dataset.schema().field(column).ok_or(Error::InvalidInput {
         source: format!("No column with name {}", column).into(),
         location: location!(),
@@ -295,20 +353,19 @@ pub(super) async fn build_scalar_index(
             location: location!(),
         });
     }
-    let index_store = LanceIndexStore::from_dataset(dataset, uuid);
     match params.force_index_type {
         Some(ScalarIndexType::Bitmap) => {
-            train_bitmap_index(training_request, &index_store).await?;
+            train_bitmap_index(training_request, index_store).await?;
             Ok(bitmap_index_details())
         }
         Some(ScalarIndexType::LabelList) => {
-            train_label_list_index(training_request, &index_store).await?;
+            train_label_list_index(training_request, index_store).await?;
             Ok(label_list_index_details())
         }
         Some(ScalarIndexType::Inverted) => {
             train_inverted_index(
                 training_request,
-                &index_store,
+                index_store,
                 InvertedIndexParams::default(),
             )
             .await?;
@@ -321,15 +378,19 @@ pub(super) async fn build_scalar_index(
                     location: location!(),
                 });
             }
-            train_ngram_index(training_request, &index_store).await?;
+            train_ngram_index(training_request, index_store).await?;
             Ok(ngram_index_details())
         }
+        Some(ScalarIndexType::Json) => Err(Error::InvalidInput {
+            source: "A JSON index must be created with JsonIndexParams".into(),
+            location: location!(),
+        }),
         _ => {
             let flat_index_trainer = FlatIndexMetadata::new(field.data_type());
             train_btree_index(
                 training_request,
                 &flat_index_trainer,
-                &index_store,
+                index_store,
                 DEFAULT_BTREE_BATCH_SIZE as u32,
             )
             .await?;
@@ -346,12 +407,60 @@ pub(super) async fn build_inverted_index(
     uuid: &str,
     params: &InvertedIndexParams,
 ) -> Result<()> {
-    let training_request = Box::new(TrainingRequest {
-        dataset: Arc::new(dataset.clone()),
-        column: column.to_string(),
-    });
+    let training_request = Box::new(TrainingRequest::new(
+        Arc::new(dataset.clone()),
+        column.to_string(),
+    ));
     let index_store = LanceIndexStore::from_dataset(dataset, uuid);
-    train_inverted_index(training_request, &index_store, params.clone()).await
+    let params = params
+        .clone()
+        .with_tokenizer_registry(dataset.session.tokenizer_registry());
+    train_inverted_index(training_request, &index_store, params).await
+}
+
+/// Build a JSON index (returns details to store in the manifest)
+#[instrument(level = "debug", skip_all)]
+pub(super) async fn build_json_index(
+    dataset: &Dataset,
+    column: &str,
+    uuid: &str,
+    params: &JsonIndexParams,
+) -> Result<prost_types::Any> {
+    let field = dataset.schema().field(column).ok_or(Error::InvalidInput {
+        source: format!("No column with name {}", column).into(),
+        location: location!(),
+    })?;
+    if !is_json_field(&ArrowField::from(field))
+        && !matches!(field.data_type(), DataType::Utf8 | DataType::LargeUtf8)
+    {
+        return Err(Error::InvalidInput {
+            source: format!(
+                "A JSON index can only be created on JSON or string columns. Column '{}' has type {}",
+                column,
+                field.data_type()
+            )
+            .into(),
+            location: location!(),
+        });
+    }
+    let training_request = Box::new(TrainingRequest::new(
+        Arc::new(dataset.clone()),
+        column.to_string(),
+    ));
+    let index_store = LanceIndexStore::from_dataset(dataset, uuid);
+    train_json_index(training_request, &index_store, params).await?;
+    Ok(json_index_details(params))
+}
+
+/// The path indexed by a JSON index, or None if `index` is not a JSON index
+pub fn json_index_path(index: &Index) -> Result<Option<JsonPath>> {
+    match &index.index_details {
+        Some(details) if details.type_url.ends_with("JsonIndexDetails") => {
+            let details = details.to_msg::<lance_table::format::pb::JsonIndexDetails>()?;
+            Ok(Some(JsonPath::parse(&details.path)?))
+        }
+        _ => Ok(None),
+    }
 }
 
 pub async fn open_scalar_index(
@@ -361,7 +470,7 @@ pub async fn open_scalar_index(
     metrics: &dyn MetricsCollector,
 ) -> Result<Arc<dyn ScalarIndex>> {
     let uuid_str = index.uuid.to_string();
-    let index_store = Arc::new(LanceIndexStore::from_dataset(dataset, &uuid_str));
+    let index_store = Arc::new(LanceIndexStore::from_dataset_index(dataset, index)?);
     let index_type = detect_scalar_index_type(dataset, index, column).await?;
     let frag_reuse_index = dataset.open_frag_reuse_index(metrics).await?;
 
@@ -380,8 +489,13 @@ pub async fn open_scalar_index(
             Ok(tag_index as Arc<dyn ScalarIndex>)
         }
         ScalarIndexType::Inverted => {
-            let inverted_index =
-                InvertedIndex::load(index_store, frag_reuse_index, index_cache).await?;
+            let inverted_index = InvertedIndex::load_with_tokenizer_registry(
+                index_store,
+                frag_reuse_index,
+                index_cache,
+                Some(dataset.session.tokenizer_registry()),
+            )
+            .await?;
             Ok(inverted_index as Arc<dyn ScalarIndex>)
         }
         ScalarIndexType::NGram => {
@@ -392,15 +506,19 @@ pub async fn open_scalar_index(
             let btree_index = BTreeIndex::load(index_store, frag_reuse_index, index_cache).await?;
             Ok(btree_index as Arc<dyn ScalarIndex>)
         }
+        ScalarIndexType::Json => {
+            let json_index = JsonIndex::load(index_store, frag_reuse_index, index_cache).await?;
+            Ok(json_index as Arc<dyn ScalarIndex>)
+        }
     }
 }
 
 async fn infer_scalar_index_type(
     dataset: &Dataset,
-    index_uuid: &str,
+    index: &Index,
     column: &str,
 ) -> Result<ScalarIndexType> {
-    let index_dir = dataset.indices_dir().child(index_uuid.to_string());
+    let index_dir = dataset.index_dir(index)?;
     let col = dataset.schema().field(column).ok_or(Error::Internal {
         message: format!(
             "Index refers to column {} which does not exist in dataset schema",
@@ -461,7 +579,7 @@ pub async fn detect_scalar_index_type(
         if let Some(index_type) = dataset.index_cache.get_with_key(&type_key).await {
             return Ok(*index_type.as_ref());
         }
-        let index_type = infer_scalar_index_type(dataset, &index.uuid.to_string(), column).await?;
+        let index_type = infer_scalar_index_type(dataset, index, column).await?;
         dataset
             .index_cache
             .insert_with_key(&type_key, Arc::new(index_type))
@@ -521,23 +639,23 @@ pub fn index_matches_criteria(
             }
         }
 
-        // We should not use FTS / NGram indices for exact equality queries
+        // We should not use FTS / NGram / JSON indices for exact equality queries
         // (i.e. merge insert with a join on the indexed column)
         if criteria.supports_exact_equality {
             match expected_type {
-                ScalarIndexType::Inverted | ScalarIndexType::NGram => {
+                ScalarIndexType::Inverted | ScalarIndexType::NGram | ScalarIndexType::Json => {
                     return Ok(false);
                 }
                 _ => {}
             }
         }
 
-        // we allow FTS / NGram indices to co-exist with each other,
+        // we allow FTS / NGram / JSON indices to co-exist with each other,
         // but we don't allow for the other scalar index types
         if has_multiple_indices
             && !matches!(
                 expected_type,
-                ScalarIndexType::Inverted | ScalarIndexType::NGram
+                ScalarIndexType::Inverted | ScalarIndexType::NGram | ScalarIndexType::Json
             )
         {
             return Err(Error::InvalidInput {
@@ -595,6 +713,7 @@ mod tests {
             index_details,
             index_version: 0,
             created_at: None,
+            base_id: None,
         }
     }
 
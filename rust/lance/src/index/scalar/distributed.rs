@@ -0,0 +1,412 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Build a BTree, Bitmap or Inverted index with many workers.
+//!
+//! 1. [`DistributedScalarIndexBuilder::build_segment`] is called by the workers,
+//!    each of them over a disjoint set of fragments, and writes a complete index
+//!    over its fragments, a segment, to `indices/<uuid>/_segment_<fragment id>/`.
+//! 2. [`DistributedScalarIndexBuilder::commit`] merges the segments into the final
+//!    index in `indices/<uuid>/` and commits it, covering exactly the fragments of
+//!    the segments.
+//!
+//! A finished segment or merged index is not built again, so a failed build can be
+//! resumed by running the phases again with the same uuid.  A segment records the
+//! fragments it covers, and it's rebuilt if it's asked for with other fragments.
+
+use std::sync::Arc;
+
+use lance_core::cache::LanceCache;
+use lance_index::scalar::bitmap::{merge_bitmap_indices, BitmapIndex, BITMAP_LOOKUP_NAME};
+use lance_index::scalar::btree::{merge_btree_indices, BTreeIndex, BTREE_LOOKUP_NAME};
+use lance_index::scalar::inverted::{
+    merge_index_segments, train_inverted_index, InvertedIndexParams, METADATA_FILE,
+};
+use lance_index::scalar::lance_format::LanceIndexStore;
+use lance_index::scalar::{IndexStore, ScalarIndex, ScalarIndexParams, ScalarIndexType};
+use lance_index::{IndexParams, IndexType};
+use lance_table::format::Index as IndexMetadata;
+use object_store::path::Path;
+use serde::{Deserialize, Serialize};
+use snafu::location;
+use uuid::Uuid;
+
+use super::{
+    bitmap_index_details, btree_index_details, inverted_index_details, train_scalar_index,
+    TrainingRequest,
+};
+use crate::dataset::Dataset;
+use crate::index::distributed::{
+    is_built_over, prepare_partial, resolve_fragments, union_fragments, DistributedIndex,
+};
+use crate::{Error, Result};
+
+const SEGMENT_DIR_PREFIX: &str = "_segment_";
+
+/// An index segment built by a worker over a set of fragments.
+///
+/// The workers send these back to the coordinator, which passes all of them to
+/// [`DistributedScalarIndexBuilder::commit`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScalarIndexSegment {
+    /// The name of the directory of the segment, under the index directory
+    pub name: String,
+    /// The fragments indexed by the segment
+    pub fragment_ids: Vec<u32>,
+}
+
+/// Build a scalar index over disjoint fragment sets, see the module docs.
+#[derive(Debug, Clone)]
+pub struct DistributedScalarIndexBuilder {
+    dataset: Dataset,
+    column: String,
+    name: String,
+    uuid: Uuid,
+    index_type: ScalarIndexType,
+    inverted_params: Option<InvertedIndexParams>,
+}
+
+impl DistributedScalarIndexBuilder {
+    /// Start a new distributed build, the index gets a new uuid.
+    ///
+    /// `params` must be [`InvertedIndexParams`] for an Inverted index, and
+    /// [`ScalarIndexParams`] otherwise.
+    pub fn try_new(
+        dataset: Dataset,
+        column: &str,
+        name: Option<String>,
+        index_type: IndexType,
+        params: &dyn IndexParams,
+    ) -> Result<Self> {
+        if dataset.schema().field(column).is_none() {
+            return Err(Error::Index {
+                message: format!("CreateIndex: column '{column}' does not exist"),
+                location: location!(),
+            });
+        }
+        let index_type = ScalarIndexType::try_from(index_type)?;
+        let inverted_params = match index_type {
+            ScalarIndexType::BTree | ScalarIndexType::Bitmap => None,
+            ScalarIndexType::Inverted => Some(
+                params
+                    .as_any()
+                    .downcast_ref::<InvertedIndexParams>()
+                    .ok_or_else(|| Error::Index {
+                        message: "Inverted index type must take a InvertedIndexParams"
+                            .to_string(),
+                        location: location!(),
+                    })?
+                    .clone()
+                    .with_tokenizer_registry(dataset.session.tokenizer_registry()),
+            ),
+            _ => {
+                return Err(Error::invalid_input(
+                    format!(
+                        "distributed index build supports only BTree, Bitmap and Inverted indices, but got {:?}",
+                        index_type
+                    ),
+                    location!(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            dataset,
+            name: name.unwrap_or(format!("{column}_idx")),
+            column: column.to_owned(),
+            uuid: Uuid::new_v4(),
+            index_type,
+            inverted_params,
+        })
+    }
+
+    /// Resume the build of the given index uuid, or join it as a worker.
+    pub fn with_uuid(mut self, uuid: Uuid) -> Self {
+        self.uuid = uuid;
+        self
+    }
+
+    /// The uuid of the index being built.
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    fn index_dir(&self) -> Path {
+        self.dataset.indices_dir().child(self.uuid.to_string())
+    }
+
+    fn store(&self, dir: Path) -> LanceIndexStore {
+        LanceIndexStore::new(
+            self.dataset.object_store.clone(),
+            dir,
+            Arc::new(LanceCache::no_cache()),
+        )
+    }
+
+    // the file an index of this type writes last
+    fn last_file(&self) -> &'static str {
+        match self.index_type {
+            ScalarIndexType::Bitmap => BITMAP_LOOKUP_NAME,
+            ScalarIndexType::Inverted => METADATA_FILE,
+            _ => BTREE_LOOKUP_NAME,
+        }
+    }
+
+    async fn is_built(&self, dir: &Path) -> Result<bool> {
+        self.dataset
+            .object_store()
+            .exists(&dir.child(self.last_file()))
+            .await
+    }
+
+    /// Build the index segment over the given fragments.
+    ///
+    /// The fragments must be disjoint from the ones of the other workers.  The
+    /// segment is named after the smallest fragment id, so calling this again with
+    /// the same fragments reuses the finished segment, and a segment of the same
+    /// name over other fragments is rebuilt.
+    pub async fn build_segment(&self, fragment_ids: &[u32]) -> Result<ScalarIndexSegment> {
+        let (fragment_ids, fragments) = resolve_fragments(&self.dataset, fragment_ids)?;
+        let segment = ScalarIndexSegment {
+            name: format!("{}{}", SEGMENT_DIR_PREFIX, fragment_ids[0]),
+            fragment_ids,
+        };
+
+        let segment_dir = self.index_dir().child(segment.name.as_str());
+        let is_built = self.is_built(&segment_dir).await?;
+        if prepare_partial(
+            self.dataset.object_store(),
+            &segment_dir,
+            &segment.fragment_ids,
+            is_built,
+        )
+        .await?
+        {
+            return Ok(segment);
+        }
+
+        let training_request = Box::new(
+            TrainingRequest::new(Arc::new(self.dataset.clone()), self.column.clone())
+                .with_fragments(fragments),
+        );
+        let store = self.store(segment_dir);
+        match &self.inverted_params {
+            Some(params) => {
+                train_inverted_index(training_request, &store, params.clone()).await?;
+            }
+            None => {
+                train_scalar_index(
+                    &self.dataset,
+                    &self.column,
+                    training_request,
+                    &store,
+                    &ScalarIndexParams::new(self.index_type),
+                )
+                .await?;
+            }
+        }
+        Ok(segment)
+    }
+
+    /// Merge the segments into the final index and commit it.
+    ///
+    /// The index covers exactly the fragments of the segments.  The segments are
+    /// removed once the final index is written.
+    pub async fn commit(
+        &self,
+        dataset: &mut Dataset,
+        segments: &[ScalarIndexSegment],
+    ) -> Result<IndexMetadata> {
+        let fragment_bitmap = union_fragments(segments.iter().map(|s| s.fragment_ids.as_slice()))?;
+        let (index_details, index_type) = match self.index_type {
+            ScalarIndexType::Bitmap => (bitmap_index_details(), IndexType::Bitmap),
+            ScalarIndexType::Inverted => (inverted_index_details(), IndexType::Inverted),
+            _ => (btree_index_details(), IndexType::BTree),
+        };
+        let index = DistributedIndex {
+            uuid: self.uuid,
+            name: &self.name,
+            column: &self.column,
+            index_type,
+            index_details,
+        };
+        index.check(dataset).await?;
+
+        let index_dir = self.index_dir();
+        if !self.is_built(&index_dir).await? {
+            self.merge(segments).await?;
+        }
+        for segment in segments {
+            self.dataset
+                .object_store()
+                .remove_dir_all(index_dir.child(segment.name.as_str()))
+                .await?;
+        }
+
+        index.commit(dataset, fragment_bitmap).await
+    }
+
+    async fn merge(&self, segments: &[ScalarIndexSegment]) -> Result<()> {
+        let index_dir = self.index_dir();
+        let mut stores = Vec::with_capacity(segments.len());
+        for segment in segments {
+            let segment_dir = index_dir.child(segment.name.as_str());
+            if !self.is_built(&segment_dir).await?
+                || !is_built_over(
+                    self.dataset.object_store(),
+                    &segment_dir,
+                    &segment.fragment_ids,
+                )
+                .await?
+            {
+                return Err(Error::invalid_input(
+                    format!(
+                        "index segment {} has not been built over its fragments",
+                        segment.name
+                    ),
+                    location!(),
+                ));
+            }
+            stores.push(Arc::new(self.store(segment_dir)) as Arc<dyn IndexStore>);
+        }
+
+        let dest_store = self.store(index_dir);
+        match self.index_type {
+            ScalarIndexType::Bitmap => {
+                let mut indices = Vec::with_capacity(stores.len());
+                for store in stores {
+                    indices.push(BitmapIndex::load(store, None, LanceCache::no_cache()).await?);
+                }
+                merge_bitmap_indices(&indices, &dest_store).await
+            }
+            ScalarIndexType::Inverted => merge_index_segments(&stores, &dest_store).await,
+            _ => {
+                let mut indices = Vec::with_capacity(stores.len());
+                for store in stores {
+                    indices.push(BTreeIndex::load(store, None, LanceCache::no_cache()).await?);
+                }
+                merge_btree_indices(&indices, &dest_store).await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow_array::types::Int32Type;
+    use lance_datagen::{array, gen};
+    use lance_index::scalar::FullTextSearchQuery;
+    use lance_index::DatasetIndexExt;
+    use rstest::rstest;
+    use tempfile::tempdir;
+
+    use crate::utils::test::{DatagenExt, FragmentCount, FragmentRowCount};
+
+    #[rstest]
+    #[case::btree(IndexType::BTree)]
+    #[case::bitmap(IndexType::Bitmap)]
+    #[case::inverted(IndexType::Inverted)]
+    #[tokio::test]
+    async fn test_distributed_build(#[case] index_type: IndexType) {
+        let test_dir = tempdir().unwrap();
+        let mut dataset = gen()
+            .col("id", array::step::<Int32Type>())
+            .col("category", array::cycle::<Int32Type>(vec![1, 2, 3]))
+            .col("text", array::cycle_utf8_literals(&["lance", "datafusion"]))
+            .into_dataset(
+                test_dir.path().to_str().unwrap(),
+                FragmentCount::from(4),
+                FragmentRowCount::from(100),
+            )
+            .await
+            .unwrap();
+        let fragment_ids = dataset
+            .get_fragments()
+            .iter()
+            .map(|f| f.id() as u32)
+            .collect::<Vec<_>>();
+
+        let (column, params): (&str, Box<dyn IndexParams>) = match index_type {
+            IndexType::BTree => ("id", Box::new(ScalarIndexParams::default())),
+            IndexType::Bitmap => ("category", Box::new(ScalarIndexParams::default())),
+            _ => ("text", Box::new(InvertedIndexParams::default())),
+        };
+        let builder = DistributedScalarIndexBuilder::try_new(
+            dataset.clone(),
+            column,
+            None,
+            index_type,
+            params.as_ref(),
+        )
+        .unwrap();
+        let mut segments = Vec::new();
+        for ids in fragment_ids.chunks(2) {
+            segments.push(builder.build_segment(ids).await.unwrap());
+        }
+        // a finished segment is reused
+        assert_eq!(
+            builder.build_segment(&fragment_ids[..2]).await.unwrap(),
+            segments[0]
+        );
+
+        // a segment of the same name over other fragments is rebuilt,
+        // so it can't be merged as the old one
+        let rebuilt = builder.build_segment(&fragment_ids[..1]).await.unwrap();
+        assert_eq!(rebuilt.name, segments[0].name);
+        assert_eq!(rebuilt.fragment_ids, fragment_ids[..1]);
+        assert!(builder.commit(&mut dataset, &segments).await.is_err());
+        assert_eq!(
+            builder.build_segment(&fragment_ids[..2]).await.unwrap(),
+            segments[0]
+        );
+
+        // the segments must be disjoint
+        let mut overlapped = segments.clone();
+        overlapped.push(ScalarIndexSegment {
+            name: "overlapped".to_owned(),
+            fragment_ids: vec![fragment_ids[0]],
+        });
+        assert!(builder.commit(&mut dataset, &overlapped).await.is_err());
+
+        let index = builder.commit(&mut dataset, &segments).await.unwrap();
+        assert_eq!(
+            index.fragment_bitmap.unwrap().iter().collect::<Vec<_>>(),
+            fragment_ids
+        );
+        assert!(!builder
+            .is_built(&builder.index_dir().child(segments[0].name.as_str()))
+            .await
+            .unwrap());
+
+        // the merged index answers the queries over all the segments
+        let mut scan = dataset.scan();
+        match index_type {
+            IndexType::BTree => {
+                scan.filter("id >= 50 AND id < 350").unwrap();
+            }
+            IndexType::Bitmap => {
+                scan.filter("category = 1").unwrap();
+            }
+            _ => {
+                scan.full_text_search(FullTextSearchQuery::new("lance".to_owned()))
+                    .unwrap();
+            }
+        }
+        if index_type != IndexType::Inverted {
+            let plan = scan.explain_plan(false).await.unwrap();
+            assert!(plan.contains("ScalarIndexQuery"), "{}", plan);
+        }
+        let num_rows = scan.try_into_batch().await.unwrap().num_rows();
+        let expected = match index_type {
+            IndexType::BTree => 300,
+            IndexType::Bitmap => 134,
+            _ => 200,
+        };
+        assert_eq!(num_rows, expected);
+
+        // the index can't be committed twice
+        assert!(builder.commit(&mut dataset, &segments).await.is_err());
+    }
+}
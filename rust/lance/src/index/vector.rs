@@ -8,6 +8,9 @@ use std::sync::Arc;
 use std::{any::Any, collections::HashMap};
 
 pub mod builder;
+pub mod diskann;
+pub mod distributed;
+pub mod eval;
 pub mod ivf;
 pub mod pq;
 pub mod utils;
@@ -18,9 +21,11 @@ mod fixture_test;
 use self::{ivf::*, pq::PQIndex};
 use arrow_schema::DataType;
 use builder::IvfIndexBuilder;
+use diskann::build_diskann_index;
 use lance_file::reader::FileReader;
 use lance_index::frag_reuse::FragReuseIndex;
 use lance_index::metrics::NoOpMetricsCollector;
+use lance_index::vector::diskann::DiskAnnBuildParams;
 use lance_index::vector::flat::index::{FlatBinQuantizer, FlatIndex, FlatQuantizer};
 use lance_index::vector::hnsw::HNSW;
 use lance_index::vector::ivf::storage::IvfModel;
@@ -32,7 +37,7 @@ use lance_index::vector::{
         index::{HNSWIndex, HNSWIndexOptions},
     },
     ivf::IvfBuildParams,
-    pq::PQBuildParams,
+    pq::{builder::DEFAULT_OPQ_ITERS, PQBuildParams},
     sq::{builder::SQBuildParams, ScalarQuantizer},
     VectorIndex,
 };
@@ -60,6 +65,7 @@ pub enum StageParams {
     Hnsw(HnswBuildParams),
     PQ(PQBuildParams),
     SQ(SQBuildParams),
+    DiskAnn(DiskAnnBuildParams),
 }
 
 // The version of the index file.
@@ -145,6 +151,25 @@ impl VectorIndexParams {
         }
     }
 
+    /// Create index parameters for `IVF_OPQ` index.
+    ///
+    /// It's an `IVF_PQ` index that learns a rotation of the vectors together with the
+    /// PQ codebook, which improves the recall on the vectors whose variance is unevenly
+    /// spread across the dimensions. The parameters are the same as [Self::ivf_pq].
+    pub fn ivf_opq(
+        num_partitions: usize,
+        num_bits: u8,
+        num_sub_vectors: usize,
+        metric_type: MetricType,
+        max_iterations: usize,
+    ) -> Self {
+        let pq_params = PQBuildParams {
+            max_iters: max_iterations,
+            ..PQBuildParams::opq(num_sub_vectors, num_bits as usize)
+        };
+        Self::with_ivf_pq_params(metric_type, IvfBuildParams::new(num_partitions), pq_params)
+    }
+
     /// Create index parameters with `IVF` and `PQ` parameters, respectively.
     pub fn with_ivf_pq_params(
         metric_type: MetricType,
@@ -205,6 +230,22 @@ impl VectorIndexParams {
         }
     }
 
+    /// Create index parameters with `IVF`, `HNSW` and `PQ` parameters, respectively.
+    /// This is used for `IVF_HNSW_OPQ` index, the PQ learns a rotation of the vectors
+    /// (see [Self::ivf_opq]) with the default number of iterations unless
+    /// `pq.opq_iters` is set.
+    pub fn with_ivf_hnsw_opq_params(
+        metric_type: MetricType,
+        ivf: IvfBuildParams,
+        hnsw: HnswBuildParams,
+        mut pq: PQBuildParams,
+    ) -> Self {
+        if pq.opq_iters == 0 {
+            pq.opq_iters = DEFAULT_OPQ_ITERS;
+        }
+        Self::with_ivf_hnsw_pq_params(metric_type, ivf, hnsw, pq)
+    }
+
     /// Create index parameters with `IVF`, `HNSW` and `SQ` parameters, respectively.
     /// This is used for `IVF_HNSW_SQ` index.
     pub fn with_ivf_hnsw_sq_params(
@@ -224,6 +265,22 @@ impl VectorIndexParams {
             version: IndexFileVersion::V3,
         }
     }
+
+    /// Create index parameters with `DiskANN` and `PQ` parameters, respectively.
+    /// This is used for `DISKANN` index, a Vamana graph on disk that is navigated
+    /// with the PQ codes in memory.
+    pub fn with_diskann_params(
+        metric_type: MetricType,
+        diskann: DiskAnnBuildParams,
+        pq: PQBuildParams,
+    ) -> Self {
+        let stages = vec![StageParams::DiskAnn(diskann), StageParams::PQ(pq)];
+        Self {
+            stages,
+            metric_type,
+            version: IndexFileVersion::V3,
+        }
+    }
 }
 
 impl IndexParams for VectorIndexParams {
@@ -293,6 +350,18 @@ pub(crate) async fn build_vector_index(
         });
     };
 
+    if let [StageParams::DiskAnn(diskann_params), StageParams::PQ(pq_params)] = stages.as_slice() {
+        return build_diskann_index(
+            dataset,
+            column,
+            uuid,
+            params.metric_type,
+            diskann_params,
+            pq_params,
+        )
+        .await;
+    }
+
     let StageParams::Ivf(ivf_params) = &stages[0] else {
         return Err(Error::Index {
             message: format!("Build Vector Index: invalid stages: {:?}", stages),
@@ -316,7 +385,7 @@ pub(crate) async fn build_vector_index(
     let shuffler = IvfShuffler::new(temp_dir_path, ivf_params.num_partitions);
     if is_ivf_flat(stages) {
         match element_type {
-            DataType::Float16 | DataType::Float32 | DataType::Float64 => {
+            DataType::Float16 | DataType::Float32 | DataType::Float64 | DataType::Int8 => {
                 IvfIndexBuilder::<FlatIndex, FlatQuantizer>::new(
                     dataset.clone(),
                     column.to_owned(),
@@ -364,6 +433,12 @@ pub(crate) async fn build_vector_index(
 
         match params.version {
             IndexFileVersion::Legacy => {
+                if ivf_params.truncate_dim.is_some() {
+                    return Err(Error::Index {
+                        message: "Build Vector Index: truncate_dim is not supported by the legacy IVF_PQ index".to_string(),
+                        location: location!(),
+                    });
+                }
                 build_ivf_pq_index(
                     dataset,
                     column,
@@ -634,7 +709,8 @@ pub(crate) async fn open_vector_index_v2(
     let index: Arc<dyn VectorIndex> = match index_metadata.index_type.as_str() {
         "IVF_HNSW_PQ" => {
             let aux_path = dataset
-                .indices_dir()
+                .indices_dir_for(uuid)
+                .await?
                 .child(uuid)
                 .child(INDEX_AUXILIARY_FILE_NAME);
             let aux_reader = dataset.object_store().open(&aux_path).await?;
@@ -664,7 +740,8 @@ pub(crate) async fn open_vector_index_v2(
 
         "IVF_HNSW_SQ" => {
             let aux_path = dataset
-                .indices_dir()
+                .indices_dir_for(uuid)
+                .await?
                 .child(uuid)
                 .child(INDEX_AUXILIARY_FILE_NAME);
             let aux_reader = dataset.object_store().open(&aux_path).await?;
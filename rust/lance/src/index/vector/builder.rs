@@ -27,7 +27,7 @@ use lance_index::vector::quantizer::{
 };
 use lance_index::vector::quantizer::{QuantizerMetadata, QuantizerStorage};
 use lance_index::vector::storage::STORAGE_METADATA_KEY;
-use lance_index::vector::utils::is_finite;
+use lance_index::vector::utils::{is_finite, truncate_vectors};
 use lance_index::vector::v3::shuffler::{EmptyReader, IvfShufflerReader};
 use lance_index::vector::v3::subindex::SubIndexType;
 use lance_index::vector::{ivf::storage::IvfModel, PART_ID_FIELD};
@@ -98,6 +98,11 @@ pub struct IvfIndexBuilder<S: IvfSubIndex, Q: Quantization> {
     // fields for merging indices / remapping
     existing_indices: Vec<Arc<dyn VectorIndex>>,
 
+    // fields for rebalancing the IVF partitions
+    split_threshold: Option<f64>,
+    merge_threshold: Option<f64>,
+    rebalanced: Option<Arc<RebalancedPartitions>>,
+
     frag_reuse_index: Option<Arc<FragReuseIndex>>,
 }
 
@@ -137,6 +142,9 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
             quantizer: None,
             shuffle_reader: None,
             existing_indices: Vec::new(),
+            split_threshold: None,
+            merge_threshold: None,
+            rebalanced: None,
             frag_reuse_index,
         })
     }
@@ -197,6 +205,9 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
             quantizer: Some(ivf_index.quantizer().try_into()?),
             shuffle_reader: None,
             existing_indices: vec![index],
+            split_threshold: None,
+            merge_threshold: None,
+            rebalanced: None,
             frag_reuse_index: None,
         })
     }
@@ -218,10 +229,15 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
             self.shuffle_dataset().await?;
         }
 
-        // step 3. build partitions
+        // step 3. split the oversized partitions and merge the tiny ones
+        if self.split_threshold.is_some() || self.merge_threshold.is_some() {
+            self.rebalance_partitions().await?;
+        }
+
+        // step 4. build partitions
         let build_idx_stream = self.build_partitions().await?;
 
-        // step 4. merge all partitions
+        // step 5. merge all partitions
         self.merge_partitions(build_idx_stream).await?;
 
         Ok(())
@@ -292,6 +308,21 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
         self
     }
 
+    /// Rebalance the IVF partitions before building them.
+    ///
+    /// A partition holding more than `split_threshold` times the average partition size
+    /// is split in two, and a partition holding less than `merge_threshold` times the
+    /// average is merged into its nearest partitions.
+    pub fn rebalance(
+        &mut self,
+        split_threshold: Option<f64>,
+        merge_threshold: Option<f64>,
+    ) -> &mut Self {
+        self.split_threshold = split_threshold;
+        self.merge_threshold = merge_threshold;
+        self
+    }
+
     #[instrument(name = "load_or_build_ivf", level = "debug", skip_all)]
     async fn load_or_build_ivf(&self) -> Result<IvfModel> {
         let Some(dataset) = self.dataset.as_ref() else {
@@ -308,6 +339,9 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
                     // retrain the IVF model with the existing indices
                     let mut ivf_params = IvfBuildParams::new(ivf.num_partitions());
                     ivf_params.retrain = true;
+                    if ivf.dimension() < dim {
+                        ivf_params.truncate_dim = Some(ivf.dimension());
+                    }
 
                     super::build_ivf_model(
                         dataset,
@@ -361,6 +395,36 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
             start.elapsed().as_secs_f32()
         );
 
+        // the quantizers of an index built on truncated vectors encode only the prefix
+        let training_data = match self.ivf.as_ref() {
+            Some(ivf) if ivf.dimension() > 0 => truncate_vectors(&training_data, ivf.dimension())?,
+            _ => training_data,
+        };
+        // the flat storage keeps the vectors as they are, int8 vectors included,
+        // so there is nothing to train on the float vectors.
+        if matches!(Q::quantization_type(), QuantizationType::Flat) {
+            return match &self.quantizer {
+                Some(q) => {
+                    let mut q = q.clone();
+                    q.retrain(&training_data)?;
+                    Ok(q)
+                }
+                None => {
+                    let quantizer_params = self.quantizer_params.as_ref().ok_or(
+                        Error::invalid_input("quantizer build params not set", location!()),
+                    )?;
+                    Q::build(&training_data, DistanceType::L2, quantizer_params)
+                }
+            };
+        }
+        // the other quantizers are trained on the same float vectors they will encode,
+        // which for int8 vectors are converted by the IVF transformer.
+        let training_data = if training_data.value_type() == datatypes::DataType::Int8 {
+            training_data.convert_to_floating_point()?
+        } else {
+            training_data
+        };
+
         // If metric type is cosine, normalize the training data, and after this point,
         // treat the metric type as L2.
         let training_data = if self.distance_type == DistanceType::Cosine {
@@ -617,6 +681,184 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
         Ok(self)
     }
 
+    // split and merge the partitions, only the vectors of the affected partitions
+    // are reassigned, the other partitions are copied unless they have new data
+    #[instrument(name = "rebalance_partitions", level = "debug", skip_all)]
+    async fn rebalance_partitions(&mut self) -> Result<()> {
+        let Some(dataset) = self.dataset.as_ref() else {
+            return Err(Error::invalid_input(
+                "dataset not set before rebalancing partitions",
+                location!(),
+            ));
+        };
+        let Some(ivf) = self.ivf.as_ref() else {
+            return Err(Error::invalid_input(
+                "IVF not set before rebalancing partitions",
+                location!(),
+            ));
+        };
+        let Some(quantizer) = self.quantizer.clone() else {
+            return Err(Error::invalid_input(
+                "quantizer not set before rebalancing partitions",
+                location!(),
+            ));
+        };
+        let Some(reader) = self.shuffle_reader.clone() else {
+            return Err(Error::invalid_input(
+                "shuffle reader not set before rebalancing partitions",
+                location!(),
+            ));
+        };
+        let Some(centroids) = ivf.centroids.as_ref() else {
+            return Err(Error::invalid_input(
+                "IVF centroids not set before rebalancing partitions",
+                location!(),
+            ));
+        };
+
+        let (vector_type, _) = get_vector_type(dataset.schema(), &self.column)?;
+        if matches!(vector_type, datatypes::DataType::List(_)) {
+            log::warn!("rebalancing IVF partitions is not supported for multivector, skipping");
+            return Ok(());
+        }
+
+        let loss = ivf.loss;
+        let num_partitions = ivf.num_partitions();
+        let mut sizes = Vec::with_capacity(num_partitions);
+        for part_id in 0..num_partitions {
+            let mut size = reader.partition_size(part_id)?;
+            for index in self.existing_indices.iter() {
+                size += Self::as_ivf_index(index.as_ref())?.partition_size(part_id);
+            }
+            sizes.push(size);
+        }
+        let plan = RebalancePlan::new(&sizes, self.split_threshold, self.merge_threshold);
+        if plan.is_empty() {
+            return Ok(());
+        }
+        info!(
+            "rebalancing IVF partitions: splitting {} partitions, merging {} partitions",
+            plan.split.len(),
+            plan.merge.len()
+        );
+
+        // load the original vectors of the affected partitions,
+        // the existing codes can't be reused because residuals depend on the centroid
+        let mut vectors = HashMap::with_capacity(plan.split.len() + plan.merge.len());
+        for &part_id in plan.split.iter().chain(plan.merge.iter()) {
+            let (batches, _) =
+                Self::take_partition_batches(part_id, &self.existing_indices, reader.as_ref())
+                    .await?;
+            let row_ids = batches
+                .iter()
+                .flat_map(|batch| {
+                    batch[ROW_ID]
+                        .as_primitive::<UInt64Type>()
+                        .values()
+                        .iter()
+                        .copied()
+                })
+                .collect::<Vec<_>>();
+            let batches = Self::take_vectors(dataset, &self.column, &self.store, &row_ids).await?;
+            vectors.insert(part_id, batches);
+        }
+
+        let mut new_centroids = Vec::with_capacity(num_partitions + plan.split.len());
+        let mut sources = Vec::with_capacity(num_partitions + plan.split.len());
+        let mut appended_centroids = Vec::new();
+        for part_id in 0..num_partitions {
+            if plan.merge.contains(&part_id) {
+                continue;
+            }
+            if !plan.split.contains(&part_id) {
+                new_centroids.push(centroids.value(part_id));
+                sources.push(Some(part_id));
+                continue;
+            }
+
+            let batches = &vectors[&part_id];
+            let part_vectors = if batches.is_empty() {
+                None
+            } else {
+                let batch = arrow::compute::concat_batches(&batches[0].schema(), batches)?;
+                Some(truncate_vectors(
+                    batch[self.column.as_str()].as_fixed_size_list(),
+                    centroids.value_length() as usize,
+                )?)
+            };
+            match part_vectors {
+                Some(part_vectors) if part_vectors.len() >= 2 => {
+                    let (part_vectors, distance_type) =
+                        if self.distance_type == DistanceType::Cosine {
+                            (
+                                lance_linalg::kernels::normalize_fsl(&part_vectors)?,
+                                DistanceType::L2,
+                            )
+                        } else {
+                            (part_vectors, self.distance_type)
+                        };
+                    let local_ivf = super::ivf::train_ivf_model(
+                        None,
+                        &part_vectors,
+                        distance_type,
+                        &IvfBuildParams::new(2),
+                    )
+                    .await?;
+                    let local_centroids = local_ivf.centroids.unwrap();
+                    new_centroids.push(local_centroids.value(0));
+                    appended_centroids.push(local_centroids.value(1));
+                }
+                // too few vectors left after deletions, keep the centroid
+                _ => new_centroids.push(centroids.value(part_id)),
+            }
+            sources.push(None);
+        }
+        sources.resize(sources.len() + appended_centroids.len(), None);
+        new_centroids.extend(appended_centroids);
+
+        let new_centroids = new_centroids.iter().map(|c| c.as_ref()).collect::<Vec<_>>();
+        let new_centroids = FixedSizeListArray::try_new_from_values(
+            arrow::compute::concat(&new_centroids)?,
+            centroids.value_length(),
+        )?;
+
+        // reassign the vectors of the affected partitions to the new centroids
+        let transformer = lance_index::vector::ivf::new_ivf_transformer_with_quantizer(
+            new_centroids.clone(),
+            self.distance_type,
+            &self.column,
+            quantizer.into(),
+            None,
+        )?;
+        let mut reassigned: HashMap<usize, Vec<RecordBatch>> = HashMap::new();
+        for batch in vectors.into_values().flatten() {
+            let batch = transformer.transform(&batch)?;
+            let mut indices: HashMap<u32, Vec<u32>> = HashMap::new();
+            for (row, part_id) in batch[PART_ID_COLUMN]
+                .as_primitive::<datatypes::UInt32Type>()
+                .values()
+                .iter()
+                .enumerate()
+            {
+                indices.entry(*part_id).or_default().push(row as u32);
+            }
+            let batch = batch.drop_column(PART_ID_COLUMN)?;
+            for (part_id, indices) in indices {
+                reassigned
+                    .entry(part_id as usize)
+                    .or_default()
+                    .push(batch.take(&UInt32Array::from(indices))?);
+            }
+        }
+
+        self.ivf = Some(IvfModel::new(new_centroids, loss));
+        self.rebalanced = Some(Arc::new(RebalancedPartitions {
+            sources,
+            reassigned,
+        }));
+        Ok(())
+    }
+
     #[instrument(name = "build_partitions", level = "debug", skip_all)]
     async fn build_partitions(&mut self) -> Result<BuildStream<S, Q>> {
         let Some(ivf) = self.ivf.as_mut() else {
@@ -649,21 +891,52 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
         let distance_type = self.distance_type;
         let column = self.column.clone();
         let frag_reuse_index = self.frag_reuse_index.clone();
+        let rebalanced = self.rebalanced.clone();
         let build_iter = (0..ivf.num_partitions()).map(move |partition| {
             let reader = reader.clone();
             let existing_indices = existing_indices.clone();
+            let rebalanced = rebalanced.clone();
             let distance_type = distance_type;
             let quantizer = quantizer.clone();
             let sub_index_params = sub_index_params.clone();
             let column = column.clone();
             let frag_reuse_index = frag_reuse_index.clone();
             async move {
-                let (batches, loss) = Self::take_partition_batches(
-                    partition,
-                    existing_indices.as_ref(),
-                    reader.as_ref(),
-                )
-                .await?;
+                let source = match rebalanced.as_ref() {
+                    Some(rebalanced) => rebalanced.sources[partition],
+                    None => Some(partition),
+                };
+                // the partitions untouched by rebalancing are copied as they are,
+                // unless they have new data or must be remapped
+                if let (Some(rebalanced), Some(source)) = (rebalanced.as_ref(), source) {
+                    if frag_reuse_index.is_none()
+                        && !rebalanced.reassigned.contains_key(&partition)
+                        && reader.partition_size(source)? == 0
+                    {
+                        if let Some(part) =
+                            Self::copy_partition(source, existing_indices.as_ref()).await?
+                        {
+                            return Ok(Some(part));
+                        }
+                    }
+                }
+                let (mut batches, loss) = match source {
+                    Some(source) => {
+                        Self::take_partition_batches(
+                            source,
+                            existing_indices.as_ref(),
+                            reader.as_ref(),
+                        )
+                        .await?
+                    }
+                    None => (Vec::new(), 0.0),
+                };
+                if let Some(reassigned) = rebalanced
+                    .as_ref()
+                    .and_then(|rebalanced| rebalanced.reassigned.get(&partition))
+                {
+                    batches.extend(reassigned.iter().cloned());
+                }
 
                 let num_rows = batches.iter().map(|b| b.num_rows()).sum::<usize>();
                 if num_rows == 0 {
@@ -703,6 +976,47 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
         Ok((storage, sub_index))
     }
 
+    fn as_ivf_index(index: &dyn VectorIndex) -> Result<&IVFIndex<S, Q>> {
+        index
+            .as_any()
+            .downcast_ref::<IVFIndex<S, Q>>()
+            .ok_or(Error::invalid_input(
+                "existing index is not IVF index",
+                location!(),
+            ))
+    }
+
+    // copy the partition of the only existing index holding it, without rebuilding it,
+    // returns None if the partition is held by no index or more than one index
+    #[instrument(name = "copy_partition", level = "debug", skip_all)]
+    async fn copy_partition(
+        part_id: usize,
+        existing_indices: &[Arc<dyn VectorIndex>],
+    ) -> Result<Option<(Q::Storage, S, f64)>> {
+        let mut holders = Vec::new();
+        for index in existing_indices.iter() {
+            let index = Self::as_ivf_index(index.as_ref())?;
+            if index.partition_size(part_id) > 0 {
+                holders.push(index);
+            }
+        }
+        let [existing_index] = holders.as_slice() else {
+            return Ok(None);
+        };
+        let part = existing_index
+            .load_partition(part_id, false, &NoOpMetricsCollector)
+            .await?;
+        let part = part
+            .as_any()
+            .downcast_ref::<PartitionEntry<S, Q>>()
+            .ok_or(Error::Internal {
+                message: "failed to downcast partition entry".to_string(),
+                location: location!(),
+            })?;
+        let index = S::load(part.index.to_batch()?)?;
+        Ok(Some((part.storage.clone(), index, 0.0)))
+    }
+
     #[instrument(name = "take_partition_batches", level = "debug", skip_all)]
     async fn take_partition_batches(
         part_id: usize,
@@ -912,9 +1226,8 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
 
     // take vectors from the dataset
     // used for reading vectors from existing indices
-    #[allow(dead_code)]
     async fn take_vectors(
-        dataset: &Arc<Dataset>,
+        dataset: &Dataset,
         column: &str,
         store: &ObjectStore,
         row_ids: &[u64],
@@ -937,6 +1250,55 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
     }
 }
 
+/// The partitions to split and to merge when rebalancing the IVF partitions
+#[derive(Debug, Default, PartialEq)]
+struct RebalancePlan {
+    split: Vec<usize>,
+    merge: Vec<usize>,
+}
+
+impl RebalancePlan {
+    fn new(sizes: &[usize], split_threshold: Option<f64>, merge_threshold: Option<f64>) -> Self {
+        let total = sizes.iter().sum::<usize>();
+        if total == 0 {
+            return Self::default();
+        }
+        let avg = total as f64 / sizes.len() as f64;
+
+        let split = match split_threshold {
+            Some(threshold) => (0..sizes.len())
+                .filter(|&i| sizes[i] >= 2 && sizes[i] as f64 > avg * threshold)
+                .collect(),
+            None => Vec::new(),
+        };
+        let mut merge: Vec<usize> = match merge_threshold {
+            Some(threshold) => (0..sizes.len())
+                .filter(|&i| !split.contains(&i) && (sizes[i] as f64) < avg * threshold)
+                .collect(),
+            None => Vec::new(),
+        };
+        // at least one partition must remain to take the merged vectors
+        if merge.len() == sizes.len() {
+            let largest = (0..sizes.len()).max_by_key(|&i| sizes[i]).unwrap();
+            merge.retain(|&i| i != largest);
+        }
+
+        Self { split, merge }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.split.is_empty() && self.merge.is_empty()
+    }
+}
+
+/// The IVF partitions after rebalancing
+struct RebalancedPartitions {
+    // the original partition of each new partition, None if it's newly created by split
+    sources: Vec<Option<usize>>,
+    // the reassigned vectors of each new partition, transformed by the quantizer
+    reassigned: HashMap<usize, Vec<RecordBatch>>,
+}
+
 pub(crate) fn index_type_string(sub_index: SubIndexType, quantizer: QuantizationType) -> String {
     match (sub_index, quantizer) {
         // ignore FLAT sub index,
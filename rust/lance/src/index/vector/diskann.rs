@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Build and optimize the DiskANN index over a dataset.
+
+use std::sync::Arc;
+
+use arrow_array::RecordBatch;
+use arrow_schema::{DataType, Field, Schema};
+use futures::{StreamExt, TryStreamExt};
+use lance_core::{ROW_ID, ROW_ID_FIELD};
+use lance_index::optimize::OptimizeOptions;
+use lance_index::vector::diskann::{
+    DiskAnnBuildParams, DiskAnnBuilder, DiskAnnIndex, DISKANN_VECTOR_COLUMN,
+};
+use lance_index::vector::pq::builder::DEFAULT_OPQ_ITERS;
+use lance_index::vector::pq::{PQBuildParams, ProductQuantizer};
+use lance_index::vector::VectorIndex;
+use lance_io::object_store::ObjectStore;
+use lance_io::stream::RecordBatchStream;
+use lance_linalg::distance::DistanceType;
+use object_store::path::Path;
+use snafu::location;
+use tracing::instrument;
+
+use super::utils::get_vector_type;
+use crate::dataset::Dataset;
+use crate::{Error, Result};
+
+/// Build a DiskANN index over all the vectors of the column.
+#[instrument(level = "debug", skip(dataset))]
+pub(crate) async fn build_diskann_index(
+    dataset: &Dataset,
+    column: &str,
+    uuid: &str,
+    distance_type: DistanceType,
+    params: &DiskAnnBuildParams,
+    pq_params: &PQBuildParams,
+) -> Result<()> {
+    check_vector_type(dataset, column)?;
+
+    let mut scanner = dataset.scan();
+    scanner.with_row_id().project(&[column])?;
+    if dataset.schema().field(column).unwrap().nullable {
+        scanner.filter_expr(datafusion_expr::col(column).is_not_null());
+    }
+
+    DiskAnnBuilder::new(params.clone(), pq_params.clone(), distance_type)
+        .build_stream(
+            scanner.try_into_stream().await?,
+            column,
+            dataset.object_store(),
+            &dataset.indices_dir().child(uuid),
+        )
+        .await
+}
+
+/// Optimize the DiskANN indices.
+///
+/// A graph can't be extended cheaply, so if there is no index to merge, a new delta
+/// index is built over the unindexed vectors with the quantizer of the first index.
+/// Otherwise the vectors of the merged indices are streamed back from their graph
+/// files, and a single graph is built over them and the unindexed vectors.  The
+/// quantizer is retrained if `options.retrain` is set.
+pub(crate) async fn optimize_diskann_indices(
+    unindexed: Option<impl RecordBatchStream + Unpin + 'static>,
+    vector_column: &str,
+    existing_indices: &[Arc<dyn VectorIndex>],
+    indices_to_merge: &[Arc<dyn VectorIndex>],
+    options: &OptimizeOptions,
+    object_store: &ObjectStore,
+    index_dir: Path,
+) -> Result<()> {
+    let first_index = as_diskann(&existing_indices[0])?;
+
+    let mut streams = Vec::with_capacity(indices_to_merge.len() + 1);
+    for index in indices_to_merge {
+        let stream = as_diskann(index)?.to_batch_stream(true).await?;
+        streams.push(stream.map_err(Error::from).boxed());
+    }
+    if let Some(stream) = unindexed {
+        // name the vectors as the merged indices do
+        let vector_column = vector_column.to_string();
+        let stream = stream.map(move |batch| -> Result<RecordBatch> {
+            let batch = batch?;
+            let vectors = batch[vector_column.as_str()].clone();
+            let schema = Arc::new(Schema::new(vec![
+                ROW_ID_FIELD.clone(),
+                Field::new(DISKANN_VECTOR_COLUMN, vectors.data_type().clone(), true),
+            ]));
+            Ok(RecordBatch::try_new(
+                schema,
+                vec![batch[ROW_ID].clone(), vectors],
+            )?)
+        });
+        streams.push(stream.boxed());
+    }
+
+    let quantizer = ProductQuantizer::try_from(first_index.quantizer())?;
+    let pq_params = PQBuildParams {
+        opq_iters: if quantizer.rotation.is_some() {
+            DEFAULT_OPQ_ITERS
+        } else {
+            0
+        },
+        ..PQBuildParams::new(quantizer.num_sub_vectors, quantizer.num_bits as usize)
+    };
+    let mut builder = DiskAnnBuilder::new(
+        first_index.build_params(),
+        pq_params,
+        first_index.metric_type(),
+    );
+    if !options.retrain {
+        builder = builder.with_quantizer(quantizer);
+    }
+    builder
+        .build_stream(
+            futures::stream::iter(streams).flatten(),
+            DISKANN_VECTOR_COLUMN,
+            object_store,
+            &index_dir,
+        )
+        .await
+}
+
+fn as_diskann(index: &Arc<dyn VectorIndex>) -> Result<&DiskAnnIndex> {
+    index
+        .as_any()
+        .downcast_ref::<DiskAnnIndex>()
+        .ok_or(Error::invalid_input(
+            "optimizing DiskANN index: the existing index is not DiskANN",
+            location!(),
+        ))
+}
+
+fn check_vector_type(dataset: &Dataset, column: &str) -> Result<()> {
+    let (vector_type, element_type) = get_vector_type(dataset.schema(), column)?;
+    match (vector_type, element_type) {
+        (
+            DataType::FixedSizeList(..),
+            DataType::Float16 | DataType::Float32 | DataType::Float64,
+        ) => Ok(()),
+        (vector_type, _) => Err(Error::Index {
+            message: format!(
+                "Build Vector Index: DiskANN supports only float vectors, but column {} is {}",
+                column, vector_type
+            ),
+            location: location!(),
+        }),
+    }
+}
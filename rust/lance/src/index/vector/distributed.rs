@@ -0,0 +1,597 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Build an IVF vector index with many workers.
+//!
+//! The build is split into phases, each of them can run on a different machine
+//! and can be retried:
+//!
+//! 1. [`DistributedVectorIndexBuilder::train`] trains the IVF centroids and the
+//!    quantizer once, and writes them as an empty index to the index directory.
+//! 2. [`DistributedVectorIndexBuilder::build_partial`] is called by the workers,
+//!    each of them over a disjoint set of fragments.  A worker loads the trained
+//!    models and writes a partial index over its fragments.
+//! 3. [`DistributedVectorIndexBuilder::commit`] merges the partial indices
+//!    partition by partition into the final index, and commits it.
+//!
+//! All the phases write into `indices/<uuid>/`, so the workers only need to agree
+//! on the index uuid and the params.  A phase whose output already exists is
+//! skipped, so a failed build can be resumed by running the phases again.  A partial
+//! index records the fragments it covers, and it's rebuilt if it's asked for with
+//! other fragments.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use arrow::datatypes;
+use lance_core::cache::LanceCache;
+use lance_core::utils::tokio::get_num_compute_intensive_cpus;
+use lance_index::frag_reuse::FragReuseIndex;
+use lance_index::metrics::NoOpMetricsCollector;
+use lance_index::vector::flat::index::{FlatBinQuantizer, FlatIndex, FlatQuantizer};
+use lance_index::vector::hnsw::HNSW;
+use lance_index::vector::ivf::IvfBuildParams;
+use lance_index::vector::pq::ProductQuantizer;
+use lance_index::vector::quantizer::Quantization;
+use lance_index::vector::sq::ScalarQuantizer;
+use lance_index::vector::v3::shuffler::IvfShuffler;
+use lance_index::vector::v3::subindex::IvfSubIndex;
+use lance_index::vector::VectorIndex;
+use lance_index::{IndexType, INDEX_FILE_NAME};
+use lance_table::format::{Fragment, Index as IndexMetadata};
+use object_store::path::Path;
+use serde::{Deserialize, Serialize};
+use snafu::location;
+use tempfile::tempdir;
+use uuid::Uuid;
+
+use super::builder::IvfIndexBuilder;
+use super::ivf::v2::IVFIndex;
+use super::utils::get_vector_type;
+use super::{
+    is_ivf_flat, is_ivf_hnsw, is_ivf_pq, is_ivf_sq, IndexFileVersion, StageParams,
+    VectorIndexParams,
+};
+use crate::dataset::scanner::DatasetRecordBatchStream;
+use crate::dataset::Dataset;
+use crate::index::distributed::{
+    is_built_over, prepare_partial, resolve_fragments, union_fragments, DistributedIndex,
+};
+use crate::index::{vector_index_details, DatasetIndexInternalExt};
+use crate::{Error, Result};
+
+const TRAINING_DIR: &str = "_training";
+const PARTIAL_DIR_PREFIX: &str = "_partial_";
+
+/// A partial index built by a worker over a set of fragments.
+///
+/// The workers send these back to the coordinator, which passes all of them to
+/// [`DistributedVectorIndexBuilder::commit`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialVectorIndex {
+    /// The name of the directory of the partial index, under the index directory
+    pub name: String,
+    /// The fragments indexed by the partial index
+    pub fragment_ids: Vec<u32>,
+}
+
+enum Phase<'a> {
+    Train,
+    Partial {
+        name: &'a str,
+        fragments: Vec<Fragment>,
+    },
+    Merge {
+        partials: &'a [PartialVectorIndex],
+    },
+}
+
+/// Build an IVF vector index over disjoint fragment sets, see the module docs.
+#[derive(Debug, Clone)]
+pub struct DistributedVectorIndexBuilder {
+    dataset: Dataset,
+    column: String,
+    name: String,
+    uuid: Uuid,
+    params: VectorIndexParams,
+}
+
+impl DistributedVectorIndexBuilder {
+    /// Start a new distributed build, the index gets a new uuid.
+    pub fn try_new(
+        dataset: Dataset,
+        column: &str,
+        name: Option<String>,
+        params: VectorIndexParams,
+    ) -> Result<Self> {
+        if dataset.schema().field(column).is_none() {
+            return Err(Error::Index {
+                message: format!("CreateIndex: column '{column}' does not exist"),
+                location: location!(),
+            });
+        }
+        if !matches!(params.version, IndexFileVersion::V3) {
+            return Err(Error::invalid_input(
+                "distributed vector index build supports only the V3 index file version",
+                location!(),
+            ));
+        }
+        let stages = &params.stages;
+        if !(is_ivf_flat(stages) || is_ivf_pq(stages) || is_ivf_sq(stages) || is_ivf_hnsw(stages)) {
+            return Err(Error::invalid_input(
+                format!(
+                    "distributed vector index build supports only IVF indices, but got stages {:?}",
+                    stages
+                ),
+                location!(),
+            ));
+        }
+
+        Ok(Self {
+            dataset,
+            name: name.unwrap_or(format!("{column}_idx")),
+            column: column.to_owned(),
+            uuid: Uuid::new_v4(),
+            params,
+        })
+    }
+
+    /// Resume the build of the given index uuid, or join it as a worker.
+    pub fn with_uuid(mut self, uuid: Uuid) -> Self {
+        self.uuid = uuid;
+        self
+    }
+
+    /// The uuid of the index being built.
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    fn index_dir(&self) -> Path {
+        self.dataset.indices_dir().child(self.uuid.to_string())
+    }
+
+    /// Train the IVF model and the quantizer over the whole dataset.
+    ///
+    /// This must finish before any worker starts, and it's a no-op if the
+    /// models have been trained already.
+    pub async fn train(&self) -> Result<()> {
+        if self.is_built(TRAINING_DIR).await? {
+            log::info!(
+                "IVF model and quantizer of index {} already trained",
+                self.uuid
+            );
+            return Ok(());
+        }
+        self.run(Phase::Train).await
+    }
+
+    /// Build the partial index over the given fragments.
+    ///
+    /// The fragments must be disjoint from the ones of the other workers.  The
+    /// partial index is named after the smallest fragment id, so calling this
+    /// again with the same fragments reuses the finished partial index, and a
+    /// partial index of the same name over other fragments is rebuilt.
+    pub async fn build_partial(&self, fragment_ids: &[u32]) -> Result<PartialVectorIndex> {
+        let (fragment_ids, fragments) = resolve_fragments(&self.dataset, fragment_ids)?;
+        let partial = PartialVectorIndex {
+            name: format!("{}{}", PARTIAL_DIR_PREFIX, fragment_ids[0]),
+            fragment_ids,
+        };
+
+        if !self.is_built(TRAINING_DIR).await? {
+            return Err(Error::invalid_input(
+                format!(
+                    "index {} must be trained before building partial indices",
+                    self.uuid
+                ),
+                location!(),
+            ));
+        }
+        let partial_dir = self.index_dir().child(partial.name.as_str());
+        let is_built = self.is_built(&partial.name).await?;
+        if prepare_partial(
+            self.dataset.object_store(),
+            &partial_dir,
+            &partial.fragment_ids,
+            is_built,
+        )
+        .await?
+        {
+            return Ok(partial);
+        }
+
+        self.run(Phase::Partial {
+            name: &partial.name,
+            fragments,
+        })
+        .await?;
+        Ok(partial)
+    }
+
+    /// Merge the partial indices into the final index and commit it.
+    ///
+    /// The index covers exactly the fragments of the partial indices.  The
+    /// training and the partial indices are removed once the final index is
+    /// written.
+    pub async fn commit(
+        &self,
+        dataset: &mut Dataset,
+        partials: &[PartialVectorIndex],
+    ) -> Result<IndexMetadata> {
+        let fragment_bitmap = union_fragments(partials.iter().map(|p| p.fragment_ids.as_slice()))?;
+        let index = DistributedIndex {
+            uuid: self.uuid,
+            name: &self.name,
+            column: &self.column,
+            index_type: IndexType::Vector,
+            index_details: vector_index_details(),
+        };
+        index.check(dataset).await?;
+
+        let index_dir = self.index_dir();
+        if !self
+            .dataset
+            .object_store()
+            .exists(&index_dir.child(INDEX_FILE_NAME))
+            .await?
+        {
+            let mut missing = Vec::new();
+            for partial in partials {
+                if !self.is_built(&partial.name).await?
+                    || !is_built_over(
+                        self.dataset.object_store(),
+                        &index_dir.child(partial.name.as_str()),
+                        &partial.fragment_ids,
+                    )
+                    .await?
+                {
+                    missing.push(partial.name.as_str());
+                }
+            }
+            if !missing.is_empty() {
+                return Err(Error::invalid_input(
+                    format!(
+                        "partial indices {:?} have not been built over their fragments",
+                        missing
+                    ),
+                    location!(),
+                ));
+            }
+            self.run(Phase::Merge { partials }).await?;
+        }
+
+        // the scratch directories are not needed by the final index
+        let object_store = self.dataset.object_store();
+        for dir in std::iter::once(TRAINING_DIR).chain(partials.iter().map(|p| p.name.as_str())) {
+            object_store.remove_dir_all(index_dir.child(dir)).await?;
+        }
+
+        index.commit(dataset, fragment_bitmap).await
+    }
+
+    async fn is_built(&self, dir: &str) -> Result<bool> {
+        // the index file is written last, so it exists only if the build finished
+        self.dataset
+            .object_store()
+            .exists(&self.index_dir().child(dir).child(INDEX_FILE_NAME))
+            .await
+    }
+
+    // dispatch the phase by the index type
+    async fn run(&self, phase: Phase<'_>) -> Result<()> {
+        let stages = &self.params.stages;
+        let StageParams::Ivf(ivf_params) = &stages[0] else {
+            return Err(Error::Index {
+                message: format!("Build Vector Index: invalid stages: {:?}", stages),
+                location: location!(),
+            });
+        };
+        let ivf_params = ivf_params.clone();
+        let (_, element_type) = get_vector_type(self.dataset.schema(), &self.column)?;
+
+        if is_ivf_flat(stages) {
+            match element_type {
+                datatypes::DataType::UInt8 => {
+                    self.run_phase::<FlatIndex, FlatBinQuantizer>(phase, ivf_params, (), ())
+                        .await
+                }
+                _ => {
+                    self.run_phase::<FlatIndex, FlatQuantizer>(phase, ivf_params, (), ())
+                        .await
+                }
+            }
+        } else if is_ivf_hnsw(stages) {
+            let StageParams::Hnsw(hnsw_params) = &stages[1] else {
+                unreachable!("checked by is_ivf_hnsw");
+            };
+            let hnsw_params = hnsw_params.clone();
+            match stages.last() {
+                Some(StageParams::PQ(pq_params)) => {
+                    self.run_phase::<HNSW, ProductQuantizer>(
+                        phase,
+                        ivf_params,
+                        pq_params.clone(),
+                        hnsw_params,
+                    )
+                    .await
+                }
+                Some(StageParams::SQ(sq_params)) => {
+                    self.run_phase::<HNSW, ScalarQuantizer>(
+                        phase,
+                        ivf_params,
+                        sq_params.clone(),
+                        hnsw_params,
+                    )
+                    .await
+                }
+                _ => {
+                    self.run_phase::<HNSW, FlatQuantizer>(phase, ivf_params, (), hnsw_params)
+                        .await
+                }
+            }
+        } else if is_ivf_pq(stages) {
+            let Some(StageParams::PQ(pq_params)) = stages.last() else {
+                unreachable!("checked by is_ivf_pq");
+            };
+            self.run_phase::<FlatIndex, ProductQuantizer>(phase, ivf_params, pq_params.clone(), ())
+                .await
+        } else if is_ivf_sq(stages) {
+            let StageParams::SQ(sq_params) = &stages[1] else {
+                unreachable!("checked by is_ivf_sq");
+            };
+            self.run_phase::<FlatIndex, ScalarQuantizer>(phase, ivf_params, sq_params.clone(), ())
+                .await
+        } else {
+            Err(Error::Index {
+                message: format!("Build Vector Index: invalid stages: {:?}", stages),
+                location: location!(),
+            })
+        }
+    }
+
+    async fn run_phase<S: IvfSubIndex + 'static, Q: Quantization + 'static>(
+        &self,
+        phase: Phase<'_>,
+        ivf_params: IvfBuildParams,
+        quantizer_params: Q::BuildParams,
+        sub_index_params: S::BuildParams,
+    ) -> Result<()> {
+        let frag_reuse_index = self
+            .dataset
+            .open_frag_reuse_index(&NoOpMetricsCollector)
+            .await?;
+        let index_dir = self.index_dir();
+        let temp_dir = tempdir()?;
+        let shuffler = IvfShuffler::new(
+            Path::from_filesystem_path(temp_dir.path())?,
+            ivf_params.num_partitions,
+        );
+
+        match phase {
+            Phase::Train => {
+                // an index without any row keeps the trained models for the workers
+                let mut builder = IvfIndexBuilder::<S, Q>::new(
+                    self.dataset.clone(),
+                    self.column.clone(),
+                    index_dir.child(TRAINING_DIR),
+                    self.params.metric_type,
+                    Box::new(shuffler),
+                    Some(ivf_params),
+                    Some(quantizer_params),
+                    sub_index_params,
+                    frag_reuse_index,
+                )?;
+                builder
+                    .shuffle_data(None::<DatasetRecordBatchStream>)
+                    .await?;
+                builder.build().await
+            }
+            Phase::Partial { name, fragments } => {
+                let trained = self
+                    .open_ivf_index::<S, Q>(TRAINING_DIR, frag_reuse_index.clone())
+                    .await?;
+
+                let mut scanner = self.dataset.scan();
+                scanner
+                    .with_fragments(fragments)
+                    .batch_readahead(get_num_compute_intensive_cpus())
+                    .project(&[self.column.as_str()])?
+                    .with_row_id();
+                let (vector_type, _) = get_vector_type(self.dataset.schema(), &self.column)?;
+                if matches!(vector_type, datatypes::DataType::List(_)) {
+                    scanner.batch_size(64);
+                }
+                let stream = scanner.try_into_stream().await?;
+
+                let mut builder = IvfIndexBuilder::<S, Q>::new_incremental(
+                    self.dataset.clone(),
+                    self.column.clone(),
+                    index_dir.child(name),
+                    self.params.metric_type,
+                    Box::new(shuffler),
+                    sub_index_params,
+                    frag_reuse_index,
+                )?;
+                builder
+                    .with_ivf(trained.ivf_model().clone())
+                    .with_quantizer(trained.quantizer().try_into()?);
+                builder.shuffle_data(Some(stream)).await?;
+                builder.build().await
+            }
+            Phase::Merge { partials } => {
+                let trained = self
+                    .open_ivf_index::<S, Q>(TRAINING_DIR, frag_reuse_index.clone())
+                    .await?;
+                let mut existing_indices = Vec::with_capacity(partials.len());
+                let mut names = HashSet::with_capacity(partials.len());
+                for partial in partials {
+                    if names.insert(partial.name.as_str()) {
+                        existing_indices.push(Arc::new(
+                            self.open_ivf_index::<S, Q>(&partial.name, frag_reuse_index.clone())
+                                .await?,
+                        ) as Arc<dyn VectorIndex>);
+                    }
+                }
+
+                // the partitions of the partial indices are concatenated and
+                // the sub indices are rebuilt over them
+                let mut builder = IvfIndexBuilder::<S, Q>::new_incremental(
+                    self.dataset.clone(),
+                    self.column.clone(),
+                    index_dir,
+                    self.params.metric_type,
+                    Box::new(shuffler),
+                    sub_index_params,
+                    frag_reuse_index,
+                )?;
+                builder
+                    .with_ivf(trained.ivf_model().clone())
+                    .with_quantizer(trained.quantizer().try_into()?)
+                    .with_existing_indices(existing_indices);
+                builder
+                    .shuffle_data(None::<DatasetRecordBatchStream>)
+                    .await?;
+                builder.build().await
+            }
+        }
+    }
+
+    async fn open_ivf_index<S: IvfSubIndex + 'static, Q: Quantization + 'static>(
+        &self,
+        dir: &str,
+        frag_reuse_index: Option<Arc<FragReuseIndex>>,
+    ) -> Result<IVFIndex<S, Q>> {
+        IVFIndex::<S, Q>::try_new(
+            self.dataset.object_store.clone(),
+            self.index_dir(),
+            dir.to_owned(),
+            frag_reuse_index,
+            &LanceCache::no_cache(),
+            LanceCache::no_cache(),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Float32Type;
+    use lance_datagen::{array, gen};
+    use lance_index::vector::hnsw::builder::HnswBuildParams;
+    use lance_index::vector::ivf::IvfBuildParams;
+    use lance_index::vector::pq::PQBuildParams;
+    use lance_index::DatasetIndexExt;
+    use lance_linalg::distance::DistanceType;
+    use rstest::rstest;
+
+    use crate::utils::test::{DatagenExt, FragmentCount, FragmentRowCount};
+
+    #[rstest]
+    #[case::ivf_flat(VectorIndexParams::ivf_flat(4, DistanceType::L2))]
+    #[case::ivf_pq(VectorIndexParams::with_ivf_pq_params(
+        DistanceType::L2,
+        IvfBuildParams::new(4),
+        PQBuildParams::new(8, 8),
+    ))]
+    #[case::ivf_hnsw_pq(VectorIndexParams::with_ivf_hnsw_pq_params(
+        DistanceType::L2,
+        IvfBuildParams::new(4),
+        HnswBuildParams::default(),
+        PQBuildParams::new(8, 8),
+    ))]
+    #[tokio::test]
+    async fn test_distributed_build(#[case] params: VectorIndexParams) {
+        let test_dir = tempdir().unwrap();
+        let mut dataset = gen()
+            .col("vec", array::rand_vec::<Float32Type>(32.into()))
+            .into_dataset(
+                test_dir.path().to_str().unwrap(),
+                FragmentCount::from(4),
+                FragmentRowCount::from(500),
+            )
+            .await
+            .unwrap();
+        let fragment_ids = dataset
+            .get_fragments()
+            .iter()
+            .map(|f| f.id() as u32)
+            .collect::<Vec<_>>();
+
+        let builder =
+            DistributedVectorIndexBuilder::try_new(dataset.clone(), "vec", None, params.clone())
+                .unwrap();
+        builder.train().await.unwrap();
+        // training again is a no-op
+        builder.train().await.unwrap();
+
+        // the workers join the build with the uuid
+        let worker = DistributedVectorIndexBuilder::try_new(dataset.clone(), "vec", None, params)
+            .unwrap()
+            .with_uuid(builder.uuid());
+        let mut partials = Vec::new();
+        for ids in fragment_ids.chunks(2) {
+            partials.push(worker.build_partial(ids).await.unwrap());
+        }
+        // a finished partial index is reused
+        assert_eq!(
+            worker.build_partial(&fragment_ids[..2]).await.unwrap(),
+            partials[0]
+        );
+
+        // a partial index of the same name over other fragments is rebuilt,
+        // so it can't be committed as the old one
+        let rebuilt = worker.build_partial(&fragment_ids[..1]).await.unwrap();
+        assert_eq!(rebuilt.name, partials[0].name);
+        assert_eq!(rebuilt.fragment_ids, fragment_ids[..1]);
+        assert!(builder.commit(&mut dataset, &partials[..1]).await.is_err());
+        assert_eq!(
+            worker.build_partial(&fragment_ids[..2]).await.unwrap(),
+            partials[0]
+        );
+
+        // the partial indices must be disjoint
+        let mut overlapped = partials.clone();
+        overlapped.push(PartialVectorIndex {
+            name: "overlapped".to_owned(),
+            fragment_ids: vec![fragment_ids[0]],
+        });
+        assert!(builder.commit(&mut dataset, &overlapped).await.is_err());
+
+        // commit only the first partial index
+        let index = builder.commit(&mut dataset, &partials[..1]).await.unwrap();
+        assert_eq!(
+            index.fragment_bitmap.unwrap().iter().collect::<Vec<_>>(),
+            fragment_ids[..2]
+        );
+        let indices = dataset.load_indices().await.unwrap();
+        assert_eq!(indices.len(), 1);
+        assert_eq!(indices[0].uuid, builder.uuid());
+
+        let stats: serde_json::Value =
+            serde_json::from_str(&dataset.index_statistics("vec_idx").await.unwrap()).unwrap();
+        assert_eq!(stats["num_indexed_rows"], 1000);
+        assert_eq!(stats["num_unindexed_rows"], 1000);
+
+        // the scratch directories are removed
+        assert!(!builder.is_built(TRAINING_DIR).await.unwrap());
+        assert!(!builder.is_built(&partials[0].name).await.unwrap());
+
+        let batch = dataset.scan().try_into_batch().await.unwrap();
+        let query = batch["vec"].as_fixed_size_list().value(0);
+        let results = dataset
+            .scan()
+            .nearest("vec", query.as_ref(), 10)
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+        assert_eq!(results.num_rows(), 10);
+
+        // the index can't be committed twice
+        assert!(builder.commit(&mut dataset, &partials[..1]).await.is_err());
+    }
+}
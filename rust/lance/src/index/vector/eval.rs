@@ -0,0 +1,407 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Evaluate the recall and the latency of a vector index.
+//!
+//! [`evaluate_vector_index`] computes the exact nearest neighbors of a set of
+//! query vectors with a flat search, then runs the ANN search for each point of
+//! a sweep of search params (`nprobes`, `ef` and `refine_factor`), and reports
+//! the recall@k and the latency percentiles of each point.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float32Type, UInt64Type};
+use arrow_array::{Array, ArrayRef};
+use arrow_schema::DataType;
+use futures::TryStreamExt;
+use lance_core::ROW_ID;
+use lance_index::metrics::NoOpMetricsCollector;
+use lance_index::vector::flat::compute_distance;
+use lance_index::vector::DIST_COL;
+use lance_index::DatasetIndexExt;
+use lance_linalg::distance::DistanceType;
+use snafu::location;
+
+use super::utils::get_vector_type;
+use crate::dataset::Dataset;
+use crate::index::DatasetIndexInternalExt;
+use crate::{Error, Result};
+
+/// The search params of one point in the sweep.
+///
+/// The params that are not set fall back to the defaults of the
+/// [`Scanner`](crate::dataset::scanner::Scanner).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchParams {
+    /// The number of IVF partitions to search.
+    pub nprobes: Option<usize>,
+
+    /// The size of the dynamic candidate list of the HNSW search.
+    pub ef: Option<usize>,
+
+    /// The refine factor, see [`Scanner::refine`](crate::dataset::scanner::Scanner::refine).
+    pub refine_factor: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EvalParams {
+    /// The number of nearest neighbors to search, the `k` of recall@k.
+    pub k: usize,
+
+    /// The number of query vectors to sample from the dataset.
+    ///
+    /// Ignored if `queries` is set.
+    pub num_queries: usize,
+
+    /// The query vectors, a `FixedSizeList` array, or a `List` array of
+    /// multi-vectors.  If not set, `num_queries` vectors are sampled from the
+    /// indexed column.
+    pub queries: Option<ArrayRef>,
+
+    /// The search params to evaluate.
+    pub sweep: Vec<SearchParams>,
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        Self {
+            k: 10,
+            num_queries: 100,
+            queries: None,
+            sweep: vec![SearchParams::default()],
+        }
+    }
+}
+
+/// The latency percentiles of the queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyStats {
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencyStats {
+    fn new(mut latencies: Vec<Duration>) -> Self {
+        if latencies.is_empty() {
+            return Self {
+                mean: Duration::ZERO,
+                p50: Duration::ZERO,
+                p90: Duration::ZERO,
+                p99: Duration::ZERO,
+                max: Duration::ZERO,
+            };
+        }
+        latencies.sort();
+        // nearest-rank percentile
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * latencies.len() as f64).ceil() as usize;
+            latencies[rank.clamp(1, latencies.len()) - 1]
+        };
+        Self {
+            mean: latencies.iter().sum::<Duration>() / latencies.len() as u32,
+            p50: percentile(50.0),
+            p90: percentile(90.0),
+            p99: percentile(99.0),
+            max: latencies[latencies.len() - 1],
+        }
+    }
+}
+
+/// The evaluation result of one point in the sweep.
+#[derive(Debug, Clone)]
+pub struct EvalResult {
+    pub params: SearchParams,
+
+    /// The mean recall@k over the queries.
+    pub recall: f32,
+
+    pub latency: LatencyStats,
+}
+
+/// Evaluate the vector index on `column` for each of the search params in
+/// [`EvalParams::sweep`].
+///
+/// The ground truth is computed with a flat search over all the rows, with the
+/// distance type of the index.  The queries are run one by one, after a warm-up
+/// query that is not measured, so the latency is the one of a single query with
+/// a warm index cache.
+pub async fn evaluate_vector_index(
+    dataset: &Dataset,
+    column: &str,
+    params: &EvalParams,
+) -> Result<Vec<EvalResult>> {
+    if params.k == 0 {
+        return Err(Error::invalid_input(
+            "k must be greater than 0".to_string(),
+            location!(),
+        ));
+    }
+    // Sanity check
+    get_vector_type(dataset.schema(), column)?;
+
+    let column_id = dataset.schema().field_id(column)?;
+    let indices = dataset.load_indices().await?;
+    let index = indices
+        .iter()
+        .find(|i| i.fields.contains(&column_id))
+        .ok_or_else(|| Error::Index {
+            message: format!("no vector index found on column {}", column),
+            location: location!(),
+        })?;
+    let distance_type = dataset
+        .open_vector_index(column, &index.uuid.to_string(), &NoOpMetricsCollector)
+        .await?
+        .metric_type();
+
+    let queries = match &params.queries {
+        Some(queries) => queries.clone(),
+        None => {
+            let projection = dataset.schema().project(&[column])?;
+            let batch = dataset.sample(params.num_queries, &projection).await?;
+            batch[column].clone()
+        }
+    };
+    let queries = split_queries(queries.as_ref())?;
+    if queries.is_empty() {
+        return Err(Error::invalid_input(
+            "no valid query vectors to evaluate".to_string(),
+            location!(),
+        ));
+    }
+
+    let ground_truth =
+        compute_ground_truth(dataset, column, &queries, params.k, distance_type).await?;
+
+    // warm up the index cache
+    search(
+        dataset,
+        column,
+        &queries[0],
+        params.k,
+        &SearchParams::default(),
+    )
+    .await?;
+
+    let mut results = Vec::with_capacity(params.sweep.len());
+    for search_params in params.sweep.iter() {
+        let mut latencies = Vec::with_capacity(queries.len());
+        let mut recall = 0.0;
+        for (query, gt) in queries.iter().zip(ground_truth.iter()) {
+            let start = Instant::now();
+            let row_ids = search(dataset, column, query, params.k, search_params).await?;
+            latencies.push(start.elapsed());
+
+            if !gt.is_empty() {
+                let hits = row_ids.iter().filter(|row_id| gt.contains(row_id)).count();
+                recall += hits as f32 / gt.len() as f32;
+            }
+        }
+        results.push(EvalResult {
+            params: search_params.clone(),
+            recall: recall / queries.len() as f32,
+            latency: LatencyStats::new(latencies),
+        });
+    }
+    Ok(results)
+}
+
+/// Split the query array into single queries, skipping the null ones.
+fn split_queries(queries: &dyn Array) -> Result<Vec<ArrayRef>> {
+    match queries.data_type() {
+        DataType::FixedSizeList(_, _) => {
+            let queries = queries.as_fixed_size_list();
+            Ok((0..queries.len())
+                .filter(|i| queries.is_valid(*i))
+                .map(|i| queries.value(i))
+                .collect())
+        }
+        DataType::List(_) => {
+            let queries = queries.as_list::<i32>();
+            Ok((0..queries.len())
+                .filter(|i| queries.is_valid(*i))
+                .map(|i| queries.value(i))
+                .collect())
+        }
+        _ => Err(Error::invalid_input(
+            format!(
+                "queries must be a FixedSizeList or List array, got {}",
+                queries.data_type()
+            ),
+            location!(),
+        )),
+    }
+}
+
+/// Compute the exact k nearest neighbors of all the queries in one pass over the dataset.
+async fn compute_ground_truth(
+    dataset: &Dataset,
+    column: &str,
+    queries: &[ArrayRef],
+    k: usize,
+    distance_type: DistanceType,
+) -> Result<Vec<HashSet<u64>>> {
+    let mut scanner = dataset.scan();
+    scanner.project(&[column])?.with_row_id();
+    let mut stream = scanner.try_into_stream().await?;
+
+    let mut neighbors = vec![Vec::<(f32, u64)>::with_capacity(k * 2); queries.len()];
+    while let Some(batch) = stream.try_next().await? {
+        for (query, neighbors) in queries.iter().zip(neighbors.iter_mut()) {
+            let batch =
+                compute_distance(query.clone(), distance_type, column, batch.clone()).await?;
+            let dists = batch[DIST_COL].as_primitive::<Float32Type>();
+            let row_ids = batch[ROW_ID].as_primitive::<UInt64Type>();
+            neighbors.extend(
+                dists
+                    .iter()
+                    .zip(row_ids.values().iter())
+                    .filter_map(|(dist, row_id)| dist.map(|dist| (dist, *row_id))),
+            );
+            neighbors.sort_by(|a, b| a.0.total_cmp(&b.0));
+            neighbors.truncate(k);
+        }
+    }
+
+    Ok(neighbors
+        .into_iter()
+        .map(|neighbors| neighbors.into_iter().map(|(_, row_id)| row_id).collect())
+        .collect())
+}
+
+async fn search(
+    dataset: &Dataset,
+    column: &str,
+    query: &ArrayRef,
+    k: usize,
+    params: &SearchParams,
+) -> Result<Vec<u64>> {
+    let mut scanner = dataset.scan();
+    scanner
+        .nearest(column, query.as_ref(), k)?
+        .project(&[ROW_ID, DIST_COL])?;
+    if let Some(nprobes) = params.nprobes {
+        scanner.nprobs(nprobes);
+    }
+    if let Some(ef) = params.ef {
+        scanner.ef(ef);
+    }
+    if let Some(refine_factor) = params.refine_factor {
+        scanner.refine(refine_factor);
+    }
+    let batch = scanner.try_into_batch().await?;
+    Ok(batch[ROW_ID].as_primitive::<UInt64Type>().values().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use lance_datagen::{array, gen};
+    use lance_index::IndexType;
+    use rstest::rstest;
+    use tempfile::tempdir;
+
+    use crate::index::vector::VectorIndexParams;
+    use crate::utils::test::{DatagenExt, FragmentCount, FragmentRowCount};
+
+    const NUM_PARTITIONS: usize = 4;
+
+    #[rstest]
+    #[case::sampled(None)]
+    #[case::provided(Some(8))]
+    #[tokio::test]
+    async fn test_evaluate_vector_index(#[case] num_provided: Option<usize>) {
+        let test_dir = tempdir().unwrap();
+        let mut dataset = gen()
+            .col("vec", array::rand_vec::<Float32Type>(32.into()))
+            .into_dataset(
+                test_dir.path().to_str().unwrap(),
+                FragmentCount::from(2),
+                FragmentRowCount::from(500),
+            )
+            .await
+            .unwrap();
+        dataset
+            .create_index(
+                &["vec"],
+                IndexType::Vector,
+                None,
+                &VectorIndexParams::ivf_flat(NUM_PARTITIONS, DistanceType::L2),
+                true,
+            )
+            .await
+            .unwrap();
+
+        let queries = match num_provided {
+            Some(n) => {
+                let vectors = dataset.scan().try_into_batch().await.unwrap()["vec"].clone();
+                Some(vectors.slice(0, n))
+            }
+            None => None,
+        };
+        let params = EvalParams {
+            k: 10,
+            num_queries: 16,
+            queries,
+            sweep: vec![
+                SearchParams {
+                    nprobes: Some(1),
+                    ..Default::default()
+                },
+                SearchParams {
+                    nprobes: Some(NUM_PARTITIONS),
+                    ..Default::default()
+                },
+            ],
+        };
+        let results = evaluate_vector_index(&dataset, "vec", &params)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].params, params.sweep[0]);
+        assert!(results[0].recall <= results[1].recall);
+        // IVF_FLAT is exact when all the partitions are searched
+        assert_eq!(results[1].recall, 1.0);
+        for result in results.iter() {
+            let latency = &result.latency;
+            assert!(latency.p50 <= latency.p90);
+            assert!(latency.p90 <= latency.p99);
+            assert!(latency.p99 <= latency.max);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_without_index() {
+        let test_dir = tempdir().unwrap();
+        let dataset = gen()
+            .col("vec", array::rand_vec::<Float32Type>(8.into()))
+            .into_dataset(
+                test_dir.path().to_str().unwrap(),
+                FragmentCount::from(1),
+                FragmentRowCount::from(100),
+            )
+            .await
+            .unwrap();
+        let err = evaluate_vector_index(&dataset, "vec", &EvalParams::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Index { .. }), "{}", err);
+    }
+
+    #[test]
+    fn test_latency_stats() {
+        let latencies = (1..=100).map(Duration::from_millis).collect::<Vec<_>>();
+        let stats = LatencyStats::new(latencies);
+        assert_eq!(stats.p50, Duration::from_millis(50));
+        assert_eq!(stats.p90, Duration::from_millis(90));
+        assert_eq!(stats.p99, Duration::from_millis(99));
+        assert_eq!(stats.max, Duration::from_millis(100));
+        assert_eq!(stats.mean, Duration::from_micros(50_500));
+    }
+}
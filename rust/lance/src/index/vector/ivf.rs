@@ -5,7 +5,9 @@
 
 use std::{any::Any, collections::HashMap, sync::Arc};
 
-use super::{builder::IvfIndexBuilder, utils::PartitionLoadLock};
+use super::{
+    builder::IvfIndexBuilder, diskann::optimize_diskann_indices, utils::PartitionLoadLock,
+};
 use super::{
     pq::{build_pq_model, PQIndex},
     utils::maybe_sample_training_data,
@@ -50,7 +52,7 @@ use lance_index::vector::flat::index::{FlatBinQuantizer, FlatIndex, FlatQuantize
 use lance_index::vector::ivf::storage::IvfModel;
 use lance_index::vector::pq::storage::transpose;
 use lance_index::vector::quantizer::QuantizationType;
-use lance_index::vector::utils::is_finite;
+use lance_index::vector::utils::{is_finite, truncate_vectors};
 use lance_index::vector::v3::shuffler::IvfShuffler;
 use lance_index::vector::v3::subindex::{IvfSubIndex, SubIndexType};
 use lance_index::{
@@ -290,6 +292,11 @@ pub(crate) async fn optimize_vector_indices(
             "optimizing vector index: retrain is only supported for v3 vector indices, falling back to normal optimization. please re-create the index with lance>=0.25.0 to enable retrain."
         );
     }
+    if options.rebalances_partitions() {
+        warn!(
+            "optimizing vector index: rebalancing partitions is only supported for v3 vector indices, falling back to normal optimization. please re-create the index with lance>=0.25.0 to enable rebalancing."
+        );
+    }
 
     let new_uuid = Uuid::new_v4();
     let object_store = dataset.object_store();
@@ -398,6 +405,25 @@ pub(crate) async fn optimize_vector_indices_v2(
     let indices_to_merge = existing_indices[start_pos..].to_vec();
     let merged_num = indices_to_merge.len();
 
+    // rebalancing changes the IVF model, the delta indices must share it,
+    // so the partitions are rebalanced only if all indices are merged into one
+    let rebalances_partitions = options.rebalances_partitions();
+    if rebalances_partitions && merged_num < existing_indices.len() {
+        warn!(
+            "optimizing vector index: rebalancing partitions requires merging all {} indices, skipping rebalancing",
+            existing_indices.len()
+        );
+    }
+    let (split_partition_threshold, merge_partition_threshold) =
+        if rebalances_partitions && merged_num == existing_indices.len() {
+            (
+                options.split_partition_threshold,
+                options.merge_partition_threshold,
+            )
+        } else {
+            (None, None)
+        };
+
     let (_, element_type) = get_vector_type(dataset.schema(), vector_column)?;
     match index_type {
         // IVF_FLAT
@@ -416,6 +442,7 @@ pub(crate) async fn optimize_vector_indices_v2(
                 .with_quantizer(quantizer.try_into()?)
                 .with_existing_indices(indices_to_merge)
                 .retrain(options.retrain)
+                .rebalance(split_partition_threshold, merge_partition_threshold)
                 .shuffle_data(unindexed)
                 .await?
                 .build()
@@ -434,6 +461,7 @@ pub(crate) async fn optimize_vector_indices_v2(
                 .with_quantizer(quantizer.try_into()?)
                 .with_existing_indices(indices_to_merge)
                 .retrain(options.retrain)
+                .rebalance(split_partition_threshold, merge_partition_threshold)
                 .shuffle_data(unindexed)
                 .await?
                 .build()
@@ -455,6 +483,7 @@ pub(crate) async fn optimize_vector_indices_v2(
             .with_quantizer(quantizer.try_into()?)
             .with_existing_indices(indices_to_merge)
             .retrain(options.retrain)
+            .rebalance(split_partition_threshold, merge_partition_threshold)
             .shuffle_data(unindexed)
             .await?
             .build()
@@ -475,6 +504,7 @@ pub(crate) async fn optimize_vector_indices_v2(
             .with_quantizer(quantizer.try_into()?)
             .with_existing_indices(indices_to_merge)
             .retrain(options.retrain)
+            .rebalance(split_partition_threshold, merge_partition_threshold)
             .shuffle_data(unindexed)
             .await?
             .build()
@@ -498,6 +528,7 @@ pub(crate) async fn optimize_vector_indices_v2(
             .with_quantizer(quantizer.try_into()?)
             .with_existing_indices(indices_to_merge)
             .retrain(options.retrain)
+            .rebalance(split_partition_threshold, merge_partition_threshold)
             .shuffle_data(unindexed)
             .await?
             .build()
@@ -521,6 +552,7 @@ pub(crate) async fn optimize_vector_indices_v2(
             .with_quantizer(quantizer.try_into()?)
             .with_existing_indices(indices_to_merge)
             .retrain(options.retrain)
+            .rebalance(split_partition_threshold, merge_partition_threshold)
             .shuffle_data(unindexed)
             .await?
             .build()
@@ -544,11 +576,25 @@ pub(crate) async fn optimize_vector_indices_v2(
             .with_quantizer(quantizer.try_into()?)
             .with_existing_indices(indices_to_merge)
             .retrain(options.retrain)
+            .rebalance(split_partition_threshold, merge_partition_threshold)
             .shuffle_data(unindexed)
             .await?
             .build()
             .await?;
         }
+        // DISKANN
+        (SubIndexType::DiskAnn, _) => {
+            optimize_diskann_indices(
+                unindexed,
+                vector_column,
+                &existing_indices,
+                &indices_to_merge,
+                options,
+                dataset.object_store(),
+                index_dir,
+            )
+            .await?;
+        }
     }
 
     Ok((new_uuid, merged_num))
@@ -1207,6 +1253,19 @@ pub async fn build_ivf_model(
     metric_type: MetricType,
     params: &IvfBuildParams,
 ) -> Result<IvfModel> {
+    let dim = match params.truncate_dim {
+        Some(truncate_dim) if truncate_dim == 0 || truncate_dim > dim => {
+            return Err(Error::invalid_input(
+                format!(
+                    "truncate_dim must be in range [1, {}], but got {}",
+                    dim, truncate_dim
+                ),
+                location!(),
+            ));
+        }
+        Some(truncate_dim) => truncate_dim,
+        None => dim,
+    };
     let centroids = params.centroids.clone();
     if centroids.is_some() && !params.retrain {
         let centroids = centroids.unwrap();
@@ -1235,6 +1294,7 @@ pub async fn build_ivf_model(
         "Finished loading training data in {:02} seconds",
         start.elapsed().as_secs_f32()
     );
+    let training_data = truncate_vectors(&training_data, dim)?;
 
     // If metric type is cosine, normalize the training data, and after this point,
     // treat the metric type as L2.
@@ -1476,7 +1536,11 @@ pub(crate) async fn remap_index_file(
     transforms: Vec<pb::Transform>,
 ) -> Result<()> {
     let object_store = dataset.object_store();
-    let old_path = dataset.indices_dir().child(old_uuid).child(INDEX_FILE_NAME);
+    let old_path = dataset
+        .indices_dir_for(old_uuid)
+        .await?
+        .child(old_uuid)
+        .child(INDEX_FILE_NAME);
     let new_path = dataset.indices_dir().child(new_uuid).child(INDEX_FILE_NAME);
 
     let reader: Arc<dyn Reader> = object_store.open(&old_path).await?.into();
@@ -1777,7 +1841,7 @@ where
 }
 
 /// Train IVF partitions using kmeans.
-async fn train_ivf_model(
+pub(super) async fn train_ivf_model(
     centroids: Option<Arc<FixedSizeListArray>>,
     data: &FixedSizeListArray,
     distance_type: DistanceType,
@@ -2282,6 +2346,7 @@ mod tests {
             index_details: Some(vector_index_details()),
             index_version: index.index_type().version(),
             created_at: None, // Test index, not setting timestamp
+            base_id: None,
         };
 
         let prefilter = Arc::new(DatasetPreFilter::new(dataset.clone(), &[index_meta], None));
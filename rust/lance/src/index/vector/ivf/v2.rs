@@ -14,9 +14,10 @@ use crate::index::{
     vector::{utils::PartitionLoadLock, VectorIndex},
     PreFilter,
 };
-use arrow::compute::concat_batches;
+use arrow::compute::{cast, concat_batches};
 use arrow_arith::numeric::sub;
 use arrow_array::{RecordBatch, UInt32Array};
+use arrow_schema::DataType;
 use async_trait::async_trait;
 use datafusion::execution::SendableRecordBatchStream;
 use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
@@ -56,6 +57,7 @@ use lance_io::{
     object_store::ObjectStore, scheduler::ScanScheduler, traits::Reader, ReadBatchParams,
 };
 use lance_linalg::distance::DistanceType;
+use lance_linalg::kernels::normalize_arrow;
 use object_store::path::Path;
 use prost::Message;
 use roaring::RoaringBitmap;
@@ -114,6 +116,8 @@ pub struct IVFIndex<S: IvfSubIndex + 'static, Q: Quantization + 'static> {
     reader: FileReader,
     sub_index_metadata: Vec<String>,
     storage: IvfQuantizationStorage<Q>,
+    /// Whether the storage keeps the int8 vectors as they are.
+    int8_storage: bool,
 
     partition_locks: PartitionLoadLock,
 
@@ -215,6 +219,7 @@ impl<S: IvfSubIndex + 'static, Q: Quantization> IVFIndex<S, Q> {
         .await?;
         let storage =
             IvfQuantizationStorage::try_new(storage_reader, frag_reuse_index.clone()).await?;
+        let int8_storage = matches!(storage.quantizer()?, Quantizer::Flat(q) if q.is_int8());
 
         let num_partitions = ivf.num_partitions();
         Ok(Self {
@@ -223,6 +228,7 @@ impl<S: IvfSubIndex + 'static, Q: Quantization> IVFIndex<S, Q> {
             ivf,
             reader: index_reader,
             storage,
+            int8_storage,
             partition_locks: PartitionLoadLock::new(num_partitions),
             sub_index_metadata,
             distance_type,
@@ -308,15 +314,73 @@ impl<S: IvfSubIndex + 'static, Q: Quantization> IVFIndex<S, Q> {
         Ok(part_entry)
     }
 
+    /// The number of vectors in the partition, the IVF model of the sub index
+    /// counts the rows of the sub index instead.
+    pub fn partition_size(&self, partition_id: usize) -> usize {
+        self.storage.partition_size(partition_id)
+    }
+
     pub async fn load_partition_storage(&self, partition_id: usize) -> Result<Q::Storage> {
         self.storage.load_partition(partition_id).await
     }
 
+    /// Convert the query vector into the vectors of the centroids.
+    ///
+    /// The centroids are in float32, and the index built on truncated vectors only
+    /// stores the prefix of the vectors.  The int8 query vectors are not normalized
+    /// before the search, so they are normalized here for cosine.
+    fn index_query(&self, query: &Query) -> Result<Query> {
+        let dim = self.ivf.dimension();
+        let is_int8 = query.key.data_type() == &DataType::Int8;
+        let is_truncated = dim > 0 && query.key.len() > dim;
+        if !is_int8 && !is_truncated {
+            return Ok(query.clone());
+        }
+
+        let mut query = query.clone();
+        if is_int8 {
+            query.key = cast(&query.key, &DataType::Float32)?;
+        }
+        if is_truncated {
+            query.key = query.key.slice(0, dim);
+        }
+        if self.distance_type == DistanceType::Cosine {
+            query.key = normalize_arrow(&query.key)?;
+        }
+        Ok(query)
+    }
+
+    /// Convert the query vector into the vectors stored in the partitions.
+    ///
+    /// The int8 vectors are stored as they are, the cosine distance doesn't need
+    /// them to be normalized.
+    fn storage_query(&self, query: &Query) -> Result<Query> {
+        if !self.int8_storage {
+            return self.index_query(query);
+        }
+        if query.key.data_type() != &DataType::Int8 {
+            return Err(Error::invalid_input(
+                format!(
+                    "the index stores int8 vectors but the query vector is {}",
+                    query.key.data_type()
+                ),
+                location!(),
+            ));
+        }
+        let dim = self.ivf.dimension();
+        let mut query = query.clone();
+        if dim > 0 && query.key.len() > dim {
+            query.key = query.key.slice(0, dim);
+        }
+        Ok(query)
+    }
+
     /// preprocess the query vector given the partition id.
     ///
     /// Internal API with no stability guarantees.
     #[instrument(level = "debug", skip(self))]
     pub fn preprocess_query(&self, partition_id: usize, query: &Query) -> Result<Query> {
+        let query = &self.storage_query(query)?;
         if Q::use_residual(self.distance_type) {
             let partition_centroids =
                 self.ivf
@@ -362,6 +426,7 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> Index for IVFIndex<S,
             (SubIndexType::Hnsw, QuantizationType::Product) => IndexType::IvfHnswPq,
             (SubIndexType::Hnsw, QuantizationType::Scalar) => IndexType::IvfHnswSq,
             (SubIndexType::Hnsw, QuantizationType::Flat) => IndexType::IvfHnswFlat,
+            (SubIndexType::DiskAnn, _) => IndexType::DiskAnn,
         }
     }
 
@@ -456,7 +521,8 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> VectorIndex for IVFInd
 
         let max_nprobes = query.maximum_nprobes.unwrap_or(self.ivf.num_partitions());
 
-        self.ivf.find_partitions(&query.key, max_nprobes, dt)
+        self.ivf
+            .find_partitions(&self.index_query(query)?.key, max_nprobes, dt)
     }
 
     fn total_partitions(&self) -> usize {
@@ -629,7 +695,7 @@ mod tests {
     use std::{ops::Range, sync::Arc};
 
     use all_asserts::{assert_ge, assert_lt};
-    use arrow::datatypes::{Float64Type, UInt64Type, UInt8Type};
+    use arrow::datatypes::{Float64Type, Int8Type, UInt64Type, UInt8Type};
     use arrow::{array::AsArray, datatypes::Float32Type};
     use arrow_array::{
         Array, ArrayRef, ArrowNativeTypeOp, ArrowPrimitiveType, FixedSizeListArray, Float32Array,
@@ -637,9 +703,11 @@ mod tests {
     };
     use arrow_buffer::OffsetBuffer;
     use arrow_schema::{DataType, Field, Schema, SchemaRef};
+    use futures::TryStreamExt;
     use itertools::Itertools;
     use lance_arrow::FixedSizeListArrayExt;
 
+    use crate::dataset::scanner::DEFAULT_XTR_OVERFETCH;
     use crate::index::{vector::is_ivf_hnsw, DatasetIndexInternalExt};
     use crate::utils::test::copy_test_data_to_tmp;
     use crate::{
@@ -658,6 +726,8 @@ mod tests {
         reader::{FileReader, FileReaderOptions},
         writer::FileWriter,
     };
+    use lance_index::vector::diskann::DiskAnnBuildParams;
+    use lance_index::vector::flat::storage::FLAT_COLUMN;
     use lance_index::vector::ivf::IvfBuildParams;
     use lance_index::vector::pq::PQBuildParams;
     use lance_index::vector::quantizer::QuantizerMetadata;
@@ -768,7 +838,7 @@ mod tests {
         let mut fields = vec![Field::new("id", DataType::UInt64, false)];
         let mut arrays: Vec<ArrayRef> = vec![ids];
         let mut fsl = FixedSizeListArray::try_new_from_values(vectors, DIM as i32).unwrap();
-        if fsl.value_type() != DataType::UInt8 {
+        if !matches!(fsl.value_type(), DataType::UInt8 | DataType::Int8) {
             fsl = normalize_fsl(&fsl).unwrap();
         }
         if is_multivector {
@@ -1182,6 +1252,240 @@ mod tests {
         }
     }
 
+    // the row ids of every partition of every index, in the order of the indices
+    async fn get_partition_row_ids(dataset: &Dataset) -> Vec<Vec<HashSet<u64>>> {
+        let indices = dataset.load_indices_by_name("vector_idx").await.unwrap();
+        let mut row_ids = vec![];
+        for idx in indices {
+            let index = dataset
+                .open_vector_index("vector", &idx.uuid.to_string(), &NoOpMetricsCollector)
+                .await
+                .unwrap();
+            let mut partitions = vec![];
+            for part_id in 0..index.ivf_model().num_partitions() {
+                let batches = index
+                    .partition_reader(part_id, false, &NoOpMetricsCollector)
+                    .await
+                    .unwrap()
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .unwrap();
+                partitions.push(
+                    batches
+                        .iter()
+                        .flat_map(|batch| {
+                            batch[ROW_ID]
+                                .as_primitive::<UInt64Type>()
+                                .values()
+                                .iter()
+                                .copied()
+                        })
+                        .collect::<HashSet<_>>(),
+                );
+            }
+            row_ids.push(partitions);
+        }
+        row_ids
+    }
+
+    #[rstest]
+    #[case::ivf_flat(VectorIndexParams::ivf_flat(4, DistanceType::L2))]
+    #[case::ivf_flat_cosine(VectorIndexParams::ivf_flat(4, DistanceType::Cosine))]
+    #[case::ivf_pq(VectorIndexParams::with_ivf_pq_params(
+        DistanceType::L2,
+        IvfBuildParams::new(4),
+        PQBuildParams::default(),
+    ))]
+    #[case::ivf_hnsw_sq(VectorIndexParams::with_ivf_hnsw_sq_params(
+        DistanceType::L2,
+        IvfBuildParams::new(4),
+        HnswBuildParams::default(),
+        SQBuildParams::default(),
+    ))]
+    #[case::ivf_hnsw_sq_cosine(VectorIndexParams::with_ivf_hnsw_sq_params(
+        DistanceType::Cosine,
+        IvfBuildParams::new(4),
+        HnswBuildParams::default(),
+        SQBuildParams::default(),
+    ))]
+    #[tokio::test]
+    async fn test_optimize_rebalance_partitions(#[case] params: VectorIndexParams) {
+        let test_dir = tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+        let (mut dataset, vectors) = generate_test_dataset::<Float32Type>(test_uri, 0.0..1.0).await;
+        let vector_column = "vector";
+        dataset
+            .create_index(&[vector_column], IndexType::Vector, None, &params, true)
+            .await
+            .unwrap();
+
+        // the skewed data is a tight cluster around the first vector,
+        // so it falls into the same partition
+        let num_skewed = NUM_ROWS * 4;
+        let base = vectors.value(0);
+        let noise = generate_random_array_with_range::<Float32Type>(num_skewed * DIM, 0.0..0.01);
+        let skewed = Float32Array::from_iter_values(
+            base.as_primitive::<Float32Type>()
+                .values()
+                .iter()
+                .cycle()
+                .zip(noise.values().iter())
+                .map(|(v, n)| v + n),
+        );
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::UInt64, false),
+            Field::new(
+                "vector",
+                DataType::FixedSizeList(
+                    Arc::new(Field::new("item", DataType::Float32, true)),
+                    DIM as i32,
+                ),
+                true,
+            ),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(UInt64Array::from_iter_values(
+                    NUM_ROWS as u64..(NUM_ROWS + num_skewed) as u64,
+                )),
+                Arc::new(FixedSizeListArray::try_new_from_values(skewed, DIM as i32).unwrap()),
+            ],
+        )
+        .unwrap();
+        let batches = RecordBatchIterator::new(vec![Ok(batch)], schema);
+        dataset.append(batches, None).await.unwrap();
+        dataset
+            .optimize_indices(&OptimizeOptions::append())
+            .await
+            .unwrap();
+        let original = get_partition_row_ids(&dataset).await;
+        assert_eq!(original.len(), 2);
+        let max_partition_size = (0..4)
+            .map(|part_id| original.iter().map(|index| index[part_id].len()).sum())
+            .max()
+            .unwrap();
+
+        // the delta indices must share the IVF model,
+        // so it's not rebalanced without merging all of them
+        dataset
+            .optimize_indices(&OptimizeOptions::rebalance().split_partition_threshold(2.0))
+            .await
+            .unwrap();
+        assert_eq!(get_partition_row_ids(&dataset).await, original);
+
+        dataset
+            .optimize_indices(
+                &OptimizeOptions::rebalance()
+                    .split_partition_threshold(2.0)
+                    .num_indices_to_merge(2),
+            )
+            .await
+            .unwrap();
+        let rebalanced = get_partition_row_ids(&dataset).await;
+        assert_eq!(rebalanced.len(), 1);
+        let partitions = &rebalanced[0];
+        assert_eq!(
+            partitions.iter().map(|part| part.len()).sum::<usize>(),
+            NUM_ROWS * 5
+        );
+        assert!(partitions.len() > 4);
+        assert_lt!(
+            partitions.iter().map(|part| part.len()).max().unwrap(),
+            max_partition_size
+        );
+
+        // the reassigned vectors must still be found
+        let results = dataset
+            .scan()
+            .nearest(vector_column, base.as_primitive::<Float32Type>(), 10)
+            .unwrap()
+            .minimum_nprobes(partitions.len())
+            .try_into_batch()
+            .await
+            .unwrap();
+        assert_eq!(results.num_rows(), 10);
+        assert!(results["id"]
+            .as_primitive::<UInt64Type>()
+            .values()
+            .iter()
+            .all(|&id| id == 0 || id >= NUM_ROWS as u64));
+    }
+
+    #[rstest]
+    #[case::ivf_flat(VectorIndexParams::ivf_flat(16, DistanceType::L2))]
+    #[case::ivf_flat_cosine(VectorIndexParams::ivf_flat(16, DistanceType::Cosine))]
+    #[case::ivf_hnsw_sq_cosine(VectorIndexParams::with_ivf_hnsw_sq_params(
+        DistanceType::Cosine,
+        IvfBuildParams::new(16),
+        HnswBuildParams::default(),
+        SQBuildParams::default(),
+    ))]
+    #[tokio::test]
+    async fn test_optimize_merge_tiny_partitions(#[case] params: VectorIndexParams) {
+        let test_dir = tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+        let (mut dataset, vectors) = generate_test_dataset::<Float32Type>(test_uri, 0.0..1.0).await;
+        let vector_column = "vector";
+        dataset
+            .create_index(&[vector_column], IndexType::Vector, None, &params, true)
+            .await
+            .unwrap();
+
+        let original = get_partition_row_ids(&dataset).await.remove(0);
+        let sizes = original.iter().map(|part| part.len()).collect::<Vec<_>>();
+        let avg = NUM_ROWS as f64 / sizes.len() as f64;
+        let min_size = *sizes.iter().min().unwrap();
+        let max_size = *sizes.iter().max().unwrap();
+        assert_lt!(min_size, max_size);
+        // merge the smallest partitions only
+        let threshold = (min_size as f64 + 0.5) / avg;
+        dataset
+            .optimize_indices(&OptimizeOptions::new().merge_partition_threshold(threshold))
+            .await
+            .unwrap();
+
+        let rebalanced = get_partition_row_ids(&dataset).await;
+        assert_eq!(rebalanced.len(), 1);
+        let partitions = &rebalanced[0];
+        let (merged, kept): (Vec<_>, Vec<_>) =
+            original.iter().partition(|part| part.len() == min_size);
+        assert_eq!(partitions.len(), kept.len());
+        assert_eq!(
+            partitions.iter().map(|part| part.len()).sum::<usize>(),
+            NUM_ROWS
+        );
+        // the remaining partitions keep their vectors and take the merged ones
+        for (part, kept) in partitions.iter().zip(kept) {
+            assert!(part.is_superset(kept));
+        }
+
+        // the vectors of the merged partitions must still be found
+        // the dataset has a single fragment, so the row ids are the row offsets
+        let merged_ids = merged.into_iter().flatten().copied().collect::<Vec<_>>();
+        let mut found = 0;
+        for row_id in merged_ids.iter() {
+            let row = *row_id as usize;
+            let results = dataset
+                .scan()
+                .with_row_id()
+                .nearest(
+                    vector_column,
+                    vectors.value(row).as_primitive::<Float32Type>(),
+                    1,
+                )
+                .unwrap()
+                .minimum_nprobes(partitions.len())
+                .try_into_batch()
+                .await
+                .unwrap();
+            if results[ROW_ID].as_primitive::<UInt64Type>().value(0) == *row_id {
+                found += 1;
+            }
+        }
+        assert_ge!(found as f64, merged_ids.len() as f64 * 0.9);
+    }
+
     async fn test_delete_all_rows(params: VectorIndexParams) {
         match params.metric_type {
             DistanceType::Hamming => {
@@ -1353,6 +1657,89 @@ mod tests {
         test_optimize_strategy(params).await;
     }
 
+    #[rstest]
+    #[case(4, DistanceType::L2, 0.9)]
+    #[case(4, DistanceType::Cosine, 0.9)]
+    #[case(4, DistanceType::Dot, 0.85)]
+    #[tokio::test]
+    async fn test_build_ivf_opq(
+        #[case] nlist: usize,
+        #[case] distance_type: DistanceType,
+        #[case] recall_requirement: f32,
+    ) {
+        let params = VectorIndexParams::ivf_opq(nlist, 8, 16, distance_type, 50);
+        test_index(params.clone(), nlist, recall_requirement, None).await;
+        test_remap(params.clone(), nlist).await;
+        test_optimize_strategy(params.clone()).await;
+
+        // the rotation is also persisted in the legacy index file
+        let mut params = params;
+        params.version(crate::index::vector::IndexFileVersion::Legacy);
+        test_index(params, nlist, recall_requirement, None).await;
+    }
+
+    #[rstest]
+    #[case(DistanceType::L2, 0.9)]
+    #[case(DistanceType::Cosine, 0.9)]
+    #[tokio::test]
+    async fn test_build_diskann(
+        #[case] distance_type: DistanceType,
+        #[case] recall_requirement: f32,
+    ) {
+        let params = VectorIndexParams::with_diskann_params(
+            distance_type,
+            DiskAnnBuildParams::default().max_degree(32),
+            PQBuildParams::new(16, 8),
+        );
+        test_index(params.clone(), 1, recall_requirement, None).await;
+        test_remap(params.clone(), 1).await;
+        test_optimize_diskann(params, recall_requirement).await;
+    }
+
+    async fn test_optimize_diskann(params: VectorIndexParams, recall_requirement: f32) {
+        let test_dir = tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+        let (mut dataset, _) = generate_test_dataset::<Float32Type>(test_uri, 0.0..1.0).await;
+
+        let vector_column = "vector";
+        dataset
+            .create_index(&[vector_column], IndexType::Vector, None, &params, true)
+            .await
+            .unwrap();
+        let new_vectors = append_dataset::<Float32Type>(&mut dataset, NUM_ROWS / 5, 0.0..1.0).await;
+        let query = new_vectors.as_fixed_size_list().value(0);
+
+        // a delta index, then merge the two indices, then retrain the PQ
+        for (options, num_indices) in [
+            (OptimizeOptions::append(), 2),
+            (OptimizeOptions::new().num_indices_to_merge(2), 1),
+            (OptimizeOptions::retrain(), 1),
+        ] {
+            dataset.optimize_indices(&options).await.unwrap();
+            let indices = dataset.load_indices_by_name("vector_idx").await.unwrap();
+            assert_eq!(indices.len(), num_indices);
+
+            let k = 10;
+            let gt = ground_truth(&dataset, vector_column, &query, k, params.metric_type).await;
+            let results = dataset
+                .scan()
+                .nearest(vector_column, query.as_primitive::<Float32Type>(), k)
+                .unwrap()
+                .with_row_id()
+                .try_into_batch()
+                .await
+                .unwrap();
+            let row_ids = results[ROW_ID]
+                .as_primitive::<UInt64Type>()
+                .values()
+                .iter()
+                .copied()
+                .collect::<HashSet<_>>();
+            let recall = row_ids.intersection(&gt).count() as f32 / k as f32;
+            assert_ge!(recall, recall_requirement, "{}", recall);
+        }
+    }
+
     #[rstest]
     #[case(4, DistanceType::L2, 0.85)]
     #[case(4, DistanceType::Cosine, 0.85)]
@@ -1422,6 +1809,213 @@ mod tests {
         test_delete_all_rows(params).await;
     }
 
+    #[rstest]
+    #[case::ivf_flat(VectorIndexParams::ivf_flat(4, DistanceType::L2), 1.0)]
+    #[case::ivf_pq(
+        VectorIndexParams::with_ivf_pq_params(
+            DistanceType::L2,
+            IvfBuildParams::new(4),
+            PQBuildParams::default(),
+        ),
+        0.9
+    )]
+    #[case::ivf_sq(
+        VectorIndexParams::with_ivf_sq_params(
+            DistanceType::Cosine,
+            IvfBuildParams::new(4),
+            SQBuildParams::default(),
+        ),
+        0.85
+    )]
+    #[case::ivf_hnsw_sq(
+        VectorIndexParams::with_ivf_hnsw_sq_params(
+            DistanceType::Dot,
+            IvfBuildParams::new(4),
+            HnswBuildParams::default(),
+            SQBuildParams::default(),
+        ),
+        0.75
+    )]
+    #[tokio::test]
+    async fn test_build_index_int8(
+        #[case] params: VectorIndexParams,
+        #[case] recall_requirement: f32,
+    ) {
+        test_index_impl::<Int8Type>(params, 4, recall_requirement, -64..64, None).await;
+    }
+
+    #[rstest]
+    #[case::ivf_flat(VectorIndexParams::ivf_flat(4, DistanceType::L2), 1.0)]
+    #[case::ivf_flat_cosine(VectorIndexParams::ivf_flat(4, DistanceType::Cosine), 0.99)]
+    #[case::ivf_hnsw_flat(
+        VectorIndexParams::ivf_hnsw(
+            DistanceType::L2,
+            IvfBuildParams::new(4),
+            HnswBuildParams::default(),
+        ),
+        0.9
+    )]
+    #[tokio::test]
+    async fn test_build_index_int8_flat_storage(
+        #[case] params: VectorIndexParams,
+        #[case] recall_requirement: f32,
+    ) {
+        let test_dir = tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+        let (mut dataset, vectors) = generate_test_dataset::<Int8Type>(test_uri, -64..64).await;
+        dataset
+            .create_index(&["vector"], IndexType::Vector, None, &params, true)
+            .await
+            .unwrap();
+
+        // the int8 vectors are stored as they are
+        let indices = dataset.load_indices().await.unwrap();
+        let index = dataset
+            .open_vector_index(
+                "vector",
+                &indices[0].uuid.to_string(),
+                &NoOpMetricsCollector,
+            )
+            .await
+            .unwrap();
+        let mut num_rows = 0;
+        for part_id in 0..index.ivf_model().num_partitions() {
+            let batches = index
+                .partition_reader(part_id, true, &NoOpMetricsCollector)
+                .await
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            for batch in batches {
+                let stored = batch[FLAT_COLUMN].as_fixed_size_list();
+                assert_eq!(stored.value_type(), DataType::Int8);
+                assert_eq!(stored.value_length(), DIM as i32);
+                num_rows += batch.num_rows();
+            }
+        }
+        assert_eq!(num_rows, NUM_ROWS);
+
+        test_recall::<Int8Type>(params, 4, recall_requirement, "vector", &dataset, vectors).await;
+    }
+
+    #[rstest]
+    #[case::ivf_pq(VectorIndexParams::with_ivf_pq_params(
+        DistanceType::L2,
+        IvfBuildParams { truncate_dim: Some(DIM / 2), ..IvfBuildParams::new(4) },
+        PQBuildParams::default(),
+    ))]
+    #[case::ivf_sq(VectorIndexParams::with_ivf_sq_params(
+        DistanceType::Cosine,
+        IvfBuildParams { truncate_dim: Some(DIM / 2), ..IvfBuildParams::new(4) },
+        SQBuildParams::default(),
+    ))]
+    #[case::ivf_hnsw_sq(VectorIndexParams::with_ivf_hnsw_sq_params(
+        DistanceType::Dot,
+        IvfBuildParams { truncate_dim: Some(DIM / 2), ..IvfBuildParams::new(4) },
+        HnswBuildParams::default(),
+        SQBuildParams::default(),
+    ))]
+    #[tokio::test]
+    async fn test_build_index_truncated(#[case] params: VectorIndexParams) {
+        let test_dir = tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+
+        // like Matryoshka embeddings, the prefix dominates the vectors
+        let values = generate_random_array_with_range::<Float32Type>(NUM_ROWS * DIM, 0.0..1.0);
+        let values =
+            Float32Array::from_iter_values(values.values().iter().enumerate().map(|(i, v)| {
+                if i % DIM < DIM / 2 {
+                    *v
+                } else {
+                    v * 0.1
+                }
+            }));
+        let vectors = FixedSizeListArray::try_new_from_values(values, DIM as i32).unwrap();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::UInt64, false),
+            Field::new("vector", vectors.data_type().clone(), true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(UInt64Array::from_iter_values(0..NUM_ROWS as u64)),
+                Arc::new(vectors.clone()),
+            ],
+        )
+        .unwrap();
+        let batches = RecordBatchIterator::new(vec![Ok(batch)], schema);
+        let mut dataset = Dataset::write(batches, test_uri, None).await.unwrap();
+        dataset
+            .create_index(&["vector"], IndexType::Vector, None, &params, true)
+            .await
+            .unwrap();
+
+        let indices = dataset.load_indices().await.unwrap();
+        let index = dataset
+            .open_vector_index(
+                "vector",
+                &indices[0].uuid.to_string(),
+                &NoOpMetricsCollector,
+            )
+            .await
+            .unwrap();
+        assert_eq!(index.ivf_model().dimension(), DIM / 2);
+
+        // the results are reranked with the full vectors even without refine
+        let query = vectors.value(0);
+        let query = query.as_primitive::<Float32Type>();
+        let results = dataset
+            .scan()
+            .nearest("vector", query, 10)
+            .unwrap()
+            .nprobs(4)
+            .try_into_batch()
+            .await
+            .unwrap();
+        assert_eq!(results.num_rows(), 10);
+        let distance_func = params.metric_type.func::<f32>();
+        results["id"]
+            .as_primitive::<UInt64Type>()
+            .values()
+            .iter()
+            .zip(results[DIST_COL].as_primitive::<Float32Type>().values())
+            .for_each(|(id, dist)| {
+                let vector = vectors.value(*id as usize);
+                let expected = distance_func(
+                    query.values(),
+                    vector.as_primitive::<Float32Type>().values(),
+                );
+                assert!(
+                    (dist - expected).abs() < 1e-4,
+                    "id: {}, distance: {}, expected: {}",
+                    id,
+                    dist,
+                    expected
+                );
+            });
+
+        let results = dataset
+            .scan()
+            .nearest("vector", query, 10)
+            .unwrap()
+            .nprobs(4)
+            .refine(10)
+            .with_row_id()
+            .try_into_batch()
+            .await
+            .unwrap();
+        let row_ids = results[ROW_ID]
+            .as_primitive::<UInt64Type>()
+            .values()
+            .iter()
+            .copied()
+            .collect::<HashSet<_>>();
+        let gt = ground_truth(&dataset, "vector", query, 10, params.metric_type).await;
+        let recall = row_ids.intersection(&gt).count() as f32 / 10.0;
+        assert_ge!(recall, 0.9);
+    }
+
     #[rstest]
     #[case(4, DistanceType::L2, 0.9)]
     #[case(4, DistanceType::Cosine, 0.9)]
@@ -1448,6 +2042,29 @@ mod tests {
         test_optimize_strategy(params).await;
     }
 
+    #[rstest]
+    #[case(4, DistanceType::L2, 0.9)]
+    #[case(4, DistanceType::Cosine, 0.9)]
+    #[case(4, DistanceType::Dot, 0.85)]
+    #[tokio::test]
+    async fn test_create_ivf_hnsw_opq(
+        #[case] nlist: usize,
+        #[case] distance_type: DistanceType,
+        #[case] recall_requirement: f32,
+    ) {
+        let ivf_params = IvfBuildParams::new(nlist);
+        let pq_params = PQBuildParams::default();
+        let hnsw_params = HnswBuildParams::default();
+        let params = VectorIndexParams::with_ivf_hnsw_opq_params(
+            distance_type,
+            ivf_params,
+            hnsw_params,
+            pq_params,
+        );
+        test_index(params.clone(), nlist, recall_requirement, None).await;
+        test_optimize_strategy(params).await;
+    }
+
     #[rstest]
     #[case(4, DistanceType::L2, 0.85)]
     #[case(4, DistanceType::Cosine, 0.85)]
@@ -1555,6 +2172,108 @@ mod tests {
             results,
             gt
         );
+
+        // the candidates are reranked with the exact MaxSim
+        let exact_dists = multivec_distance(
+            query.as_fixed_size_list().values(),
+            &vectors,
+            params.metric_type,
+        )
+        .unwrap();
+        for (dist, row_id) in results.iter() {
+            assert!(
+                (dist - exact_dists[*row_id as usize]).abs() < 1e-3,
+                "row {}: distance {} != exact distance {}",
+                row_id,
+                dist,
+                exact_dists[*row_id as usize]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multivec_recall() {
+        let test_dir = tempdir().unwrap();
+        let test_uri = test_dir.path().to_str().unwrap();
+        let (mut dataset, vectors) =
+            generate_multivec_test_dataset::<Float32Type>(test_uri, 0.0..1.0).await;
+        let nlist = 4;
+        let params = VectorIndexParams::ivf_flat(nlist, DistanceType::Cosine);
+        dataset
+            .create_index(&["vector"], IndexType::Vector, None, &params, true)
+            .await
+            .unwrap();
+
+        let k = 10;
+        let num_queries = 10;
+        let mut num_found = 0;
+        for i in 0..num_queries {
+            let query = vectors.value(i);
+            let result = dataset
+                .scan()
+                .nearest("vector", &query, k)
+                .unwrap()
+                .minimum_nprobes(nlist)
+                .with_row_id()
+                .try_into_batch()
+                .await
+                .unwrap();
+            assert_eq!(result.num_rows(), k);
+
+            // the candidates are reranked with the exact MaxSim
+            let exact_dists = multivec_distance(
+                query.as_fixed_size_list().values(),
+                &vectors,
+                DistanceType::Cosine,
+            )
+            .unwrap();
+            let row_ids = result[ROW_ID].as_primitive::<UInt64Type>().values();
+            let dists = result[DIST_COL].as_primitive::<Float32Type>().values();
+            for (dist, row_id) in dists.iter().zip(row_ids.iter()) {
+                assert!(
+                    (dist - exact_dists[*row_id as usize]).abs() < 1e-3,
+                    "row {}: distance {} != exact distance {}",
+                    row_id,
+                    dist,
+                    exact_dists[*row_id as usize]
+                );
+            }
+            // the query is the multi-vector of the row
+            assert_eq!(row_ids[0], i as u64);
+
+            let gt = multivec_ground_truth(&vectors, &query, k, DistanceType::Cosine)
+                .into_iter()
+                .map(|(_, row_id)| row_id)
+                .collect::<HashSet<_>>();
+            num_found += row_ids.iter().filter(|row_id| gt.contains(row_id)).count();
+        }
+
+        let recall = num_found as f32 / (k * num_queries) as f32;
+        assert!(recall >= 0.8, "recall: {}", recall);
+
+        // each query vector keeps at most the `k * over_fetch` closest vectors of each
+        // partition that may be probed
+        let query = vectors.value(0);
+        for (maximum_nprobes, nprobes) in [(None, nlist), (Some(2), 2)] {
+            let mut scanner = dataset.scan();
+            scanner
+                .nearest("vector", &query, k)
+                .unwrap()
+                .minimum_nprobes(1);
+            if let Some(maximum_nprobes) = maximum_nprobes {
+                scanner.maximum_nprobes(maximum_nprobes);
+            }
+            let max_candidates = k * *DEFAULT_XTR_OVERFETCH as usize * nprobes;
+            assert_lt!(max_candidates, vectors.values().len());
+            let plan = scanner.explain_plan(true).await.unwrap();
+            assert_eq!(
+                plan.matches(&format!("SortExec: TopK(fetch={})", max_candidates))
+                    .count(),
+                query.len(),
+                "{}",
+                plan
+            );
+        }
     }
 
     #[rstest]
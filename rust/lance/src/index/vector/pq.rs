@@ -613,7 +613,8 @@ pub(crate) fn build_pq_storage(
         false,
         // TODO: support auto-remap with frag_reuse_index for HNSW
         None,
-    )?;
+    )?
+    .with_rotation(pq.rotation.clone());
 
     Ok(pq_store)
 }
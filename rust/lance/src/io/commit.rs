@@ -427,7 +427,7 @@ pub(crate) async fn migrate_fragments(
                     } else {
                         Either::Right(async {
                             object_store
-                                .size(&dataset.base.child("data").child(file.path.clone()))
+                                .size(&dataset.data_file_dir(file)?.child(file.path.clone()))
                                 .map_ok(|size| {
                                     NonZero::new(size).ok_or_else(|| Error::Internal {
                                         message: format!("File {} has size 0", file.path),
@@ -1399,6 +1399,7 @@ mod tests {
                 deletion_file: None,
                 row_id_meta: None,
                 physical_rows: None,
+                partition_values: None,
             },
             Fragment {
                 id: 1,
@@ -1409,6 +1410,7 @@ mod tests {
                 deletion_file: None,
                 row_id_meta: None,
                 physical_rows: None,
+                partition_values: None,
             },
         ];
 
@@ -1440,6 +1442,7 @@ mod tests {
                 deletion_file: None,
                 row_id_meta: None,
                 physical_rows: None,
+                partition_values: None,
             },
             Fragment {
                 id: 1,
@@ -1450,6 +1453,7 @@ mod tests {
                 deletion_file: None,
                 row_id_meta: None,
                 physical_rows: None,
+                partition_values: None,
             },
         ];
         assert_eq!(manifest.fragments.as_ref(), &expected_fragments);
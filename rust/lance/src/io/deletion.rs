@@ -25,7 +25,7 @@ pub async fn read_dataset_deletion_file(
             read_deletion_file(
                 fragment_id,
                 deletion_file,
-                &dataset.base,
+                &dataset.base_dir(deletion_file.base_id)?,
                 dataset.object_store.as_ref(),
             )
             .await?,
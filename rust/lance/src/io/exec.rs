@@ -5,10 +5,12 @@
 //!
 //! WARNING: Internal API with no stability guarantees.
 
+pub mod aggregate;
 mod filter;
 pub mod filtered_read;
 pub mod fts;
 pub(crate) mod knn;
+mod mem_table;
 mod optimizer;
 mod projection;
 mod pushdown_scan;
@@ -24,7 +26,8 @@ pub use filter::LanceFilterExec;
 pub use knn::{ANNIvfPartitionExec, ANNIvfSubIndexExec, KNNVectorDistanceExec};
 pub use lance_datafusion::planner::Planner;
 pub use lance_index::scalar::expression::FilterPlan;
-pub use optimizer::get_physical_optimizer;
+pub use mem_table::MemTableMergeExec;
+pub use optimizer::{get_physical_optimizer, AggregatePushdown};
 pub use projection::project;
 pub use pushdown_scan::{LancePushdownScanExec, ScanConfig};
 pub use rowids::AddRowAddrExec;
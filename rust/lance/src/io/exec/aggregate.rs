@@ -0,0 +1,318 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::SchemaRef;
+use datafusion::common::Statistics;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::{SendableRecordBatchStream, TaskContext};
+use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
+use datafusion::physical_plan::execution_plan::{Boundedness, EmissionType};
+use datafusion::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricsSet};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, ExecutionPlan, ExecutionPlanProperties, Partitioning,
+    PlanProperties,
+};
+use datafusion::scalar::ScalarValue;
+use datafusion_physical_expr::EquivalenceProperties;
+use futures::{stream, StreamExt, TryStreamExt};
+use lance_index::metrics::NoOpMetricsCollector;
+use lance_index::scalar::btree::BTreeIndex;
+use lance_index::scalar::expression::ScalarIndexExpr;
+use lance_index::{DatasetIndexExt, ScalarIndexCriteria};
+use lance_table::format::Fragment;
+
+use super::scalar_index::count_index_query_rows;
+use crate::dataset::fragment::FileFragment;
+use crate::index::DatasetIndexInternalExt;
+use crate::{Dataset, Result};
+
+/// An aggregate that can be answered from the dataset metadata and indices
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataAggregate {
+    /// The number of rows matching the filter
+    Count,
+    /// The smallest value of a column
+    Min(String),
+    /// The largest value of a column
+    Max(String),
+}
+
+impl std::fmt::Display for MetadataAggregate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Count => write!(f, "count"),
+            Self::Min(column) => write!(f, "min({})", column),
+            Self::Max(column) => write!(f, "max({})", column),
+        }
+    }
+}
+
+/// Computes COUNT/MIN/MAX aggregates without reading any data pages.
+///
+/// COUNT comes from the fragment row counts minus the deletions, or from the
+/// result of an exact scalar index query when there is a filter.  MIN/MAX come
+/// from the page lookup of a BTree index covering all the fragments.
+///
+/// The plan is only known to be answerable when it runs (the index may not cover
+/// the new fragments, the deletions may make the index bounds stale...), so if
+/// any aggregate can't be answered the original aggregate plan is run instead.
+#[derive(Debug)]
+pub struct MetadataAggregateExec {
+    dataset: Arc<Dataset>,
+    aggregates: Vec<MetadataAggregate>,
+    // the fragments that are scanned, all of them if None
+    fragments: Option<Arc<Vec<Fragment>>>,
+    // an exact index query that is the entire filter
+    index_query: Option<ScalarIndexExpr>,
+    fallback: Arc<dyn ExecutionPlan>,
+
+    properties: PlanProperties,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl DisplayAs for MetadataAggregateExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let aggregates = self
+            .aggregates
+            .iter()
+            .map(|aggregate| aggregate.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(f, "MetadataAggregate: aggr=[{}]", aggregates)?;
+                if let Some(index_query) = &self.index_query {
+                    write!(f, ", query={}", index_query)?;
+                }
+                Ok(())
+            }
+            DisplayFormatType::TreeRender => {
+                write!(f, "MetadataAggregate\naggr=[{}]", aggregates)?;
+                if let Some(index_query) = &self.index_query {
+                    write!(f, "\nquery={}", index_query)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl MetadataAggregateExec {
+    /// `fallback` is the aggregate plan being replaced, its schema must have one
+    /// column per aggregate
+    pub fn new(
+        dataset: Arc<Dataset>,
+        aggregates: Vec<MetadataAggregate>,
+        fragments: Option<Arc<Vec<Fragment>>>,
+        index_query: Option<ScalarIndexExpr>,
+        fallback: Arc<dyn ExecutionPlan>,
+    ) -> Self {
+        let fallback = if fallback.output_partitioning().partition_count() > 1 {
+            Arc::new(CoalescePartitionsExec::new(fallback)) as Arc<dyn ExecutionPlan>
+        } else {
+            fallback
+        };
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(fallback.schema()),
+            Partitioning::UnknownPartitioning(1),
+            EmissionType::Final,
+            Boundedness::Bounded,
+        );
+        Self {
+            dataset,
+            aggregates,
+            fragments,
+            index_query,
+            fallback,
+            properties,
+            metrics: ExecutionPlanMetricsSet::new(),
+        }
+    }
+
+    fn fragments(&self) -> Arc<Vec<Fragment>> {
+        self.fragments
+            .clone()
+            .unwrap_or_else(|| self.dataset.fragments().clone())
+    }
+
+    async fn count_rows(&self) -> Result<Option<u64>> {
+        let fragments = self.fragments();
+        if let Some(index_query) = &self.index_query {
+            return count_index_query_rows(self.dataset.clone(), index_query, fragments).await;
+        }
+        let counts = stream::iter(fragments.iter().cloned())
+            .map(|fragment| FileFragment::new(self.dataset.clone(), fragment).count_rows(None))
+            .buffer_unordered(self.dataset.object_store.io_parallelism())
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(Some(counts.into_iter().sum::<usize>() as u64))
+    }
+
+    // the (min, max) of the column from a BTree index, the values are null if the column
+    // has no values
+    async fn min_max(&self, column: &str) -> Result<Option<(ScalarValue, ScalarValue)>> {
+        let fragments = self.fragments();
+        // the index still has the values of the deleted rows
+        if self.index_query.is_some()
+            || fragments
+                .iter()
+                .any(|fragment| fragment.deletion_file.is_some())
+        {
+            return Ok(None);
+        }
+        let Some(data_type) = self
+            .dataset
+            .schema()
+            .field(column)
+            .map(|field| field.data_type())
+        else {
+            return Ok(None);
+        };
+        let Some(index) = self
+            .dataset
+            .load_scalar_index(ScalarIndexCriteria::default().for_column(column))
+            .await?
+        else {
+            return Ok(None);
+        };
+        // the index must have the values of exactly the fragments, the fragments
+        // removed since it was trained may still be in it
+        let Some(fragment_bitmap) = &index.fragment_bitmap else {
+            return Ok(None);
+        };
+        if fragment_bitmap.len() != fragments.len() as u64
+            || fragments
+                .iter()
+                .any(|fragment| !fragment_bitmap.contains(fragment.id as u32))
+        {
+            return Ok(None);
+        }
+        let index = self
+            .dataset
+            .open_scalar_index(column, &index.uuid.to_string(), &NoOpMetricsCollector)
+            .await?;
+        let Some(btree) = index.as_any().downcast_ref::<BTreeIndex>() else {
+            return Ok(None);
+        };
+        match btree.min_max().await? {
+            Some(min_max) => Ok(Some(min_max)),
+            None => {
+                let null = ScalarValue::try_from(&data_type)?;
+                Ok(Some((null.clone(), null)))
+            }
+        }
+    }
+
+    // the aggregate values, or None if any of them can't be answered without a scan
+    async fn try_aggregate(&self) -> Result<Option<RecordBatch>> {
+        let schema = self.schema();
+        let mut count = None;
+        let mut columns: Vec<ArrayRef> = Vec::with_capacity(self.aggregates.len());
+        for (aggregate, field) in self.aggregates.iter().zip(schema.fields()) {
+            let value = match aggregate {
+                MetadataAggregate::Count => {
+                    if count.is_none() {
+                        count = self.count_rows().await?;
+                    }
+                    let Some(count) = count else {
+                        return Ok(None);
+                    };
+                    ScalarValue::UInt64(Some(count))
+                }
+                MetadataAggregate::Min(column) => match self.min_max(column).await? {
+                    Some((min, _)) => min,
+                    None => return Ok(None),
+                },
+                MetadataAggregate::Max(column) => match self.min_max(column).await? {
+                    Some((_, max)) => max,
+                    None => return Ok(None),
+                },
+            };
+            columns.push(value.cast_to(field.data_type())?.to_array_of_size(1)?);
+        }
+        Ok(Some(RecordBatch::try_new(schema, columns)?))
+    }
+}
+
+impl ExecutionPlan for MetadataAggregateExec {
+    fn name(&self) -> &str {
+        "MetadataAggregateExec"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.fallback.schema()
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.fallback]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        mut children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        if children.len() != 1 {
+            return Err(DataFusionError::Internal(
+                "Unexpected number of children".to_string(),
+            ));
+        }
+        Ok(Arc::new(Self::new(
+            self.dataset.clone(),
+            self.aggregates.clone(),
+            self.fragments.clone(),
+            self.index_query.clone(),
+            children.pop().unwrap(),
+        )))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let this = Self::new(
+            self.dataset.clone(),
+            self.aggregates.clone(),
+            self.fragments.clone(),
+            self.index_query.clone(),
+            self.fallback.clone(),
+        );
+        let schema = self.schema();
+        let stream = stream::once(async move {
+            let batches = match this.try_aggregate().await? {
+                Some(batch) => stream::iter(vec![Ok(batch)]).boxed(),
+                None => {
+                    log::debug!("aggregates can't be answered from metadata, running the scan");
+                    this.fallback.execute(partition, context)?.boxed()
+                }
+            };
+            DataFusionResult::Ok(batches)
+        })
+        .try_flatten();
+        Ok(Box::pin(RecordBatchStreamAdapter::new(schema, stream)))
+    }
+
+    fn statistics(&self) -> DataFusionResult<Statistics> {
+        Ok(Statistics::new_unknown(&self.schema()))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn supports_limit_pushdown(&self) -> bool {
+        false
+    }
+}
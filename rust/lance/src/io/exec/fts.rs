@@ -18,27 +18,423 @@ use datafusion_physical_expr::{Distribution, EquivalenceProperties, Partitioning
 use futures::stream::{self};
 use futures::{FutureExt, StreamExt, TryStreamExt};
 use itertools::Itertools;
+use lance_arrow::{RecordBatchExt, SchemaExt};
 use lance_core::{utils::tracing::StreamTracingExt, ROW_ID};
+use lance_index::scalar::inverted::explain::{ScoreExplainer, EXPLAIN_FIELD};
+use lance_index::scalar::inverted::highlight::{
+    HighlightParams, Highlighter, HIGHLIGHTS_FIELD, SNIPPET_FIELD,
+};
 use lance_index::scalar::inverted::query::{
-    collect_tokens, BoostQuery, FtsSearchParams, MatchQuery, PhraseQuery,
+    collect_phrase_tokens, collect_query_tokens, BoostQuery, FtsQuery, FtsQueryNode,
+    FtsSearchParams, MatchQuery, Operator, PhraseQuery, TermPatternQuery,
 };
 use lance_index::scalar::inverted::{
-    flat_bm25_search_stream, InvertedIndex, FTS_SCHEMA, SCORE_COL,
+    flat_bm25_search_stream, flat_pattern_search_stream, InvertedIndex, FTS_SCHEMA, SCORE_COL,
 };
 use lance_index::scalar::ScalarIndexType;
 use lance_index::{prefilter::PreFilter, scalar::inverted::query::BooleanQuery};
 use lance_index::{DatasetIndexExt, ScalarIndexCriteria};
 use tracing::instrument;
 
-use crate::{index::DatasetIndexInternalExt, Dataset};
+use crate::{index::DatasetIndexInternalExt, Dataset};
+
+use super::utils::{build_prefilter, IndexMetrics, InstrumentedRecordBatchStreamAdapter};
+use super::PreFilterSource;
+
+#[derive(Debug)]
+pub struct MatchQueryExec {
+    dataset: Arc<Dataset>,
+    query: MatchQuery,
+    params: FtsSearchParams,
+    prefilter_source: PreFilterSource,
+
+    properties: PlanProperties,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl DisplayAs for MatchQueryExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(f, "MatchQuery: query={}", self.query.terms)
+            }
+            DisplayFormatType::TreeRender => {
+                write!(f, "MatchQuery\nquery={}", self.query.terms)
+            }
+        }
+    }
+}
+
+impl MatchQueryExec {
+    pub fn new(
+        dataset: Arc<Dataset>,
+        query: MatchQuery,
+        params: FtsSearchParams,
+        prefilter_source: PreFilterSource,
+    ) -> Self {
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(FTS_SCHEMA.clone()),
+            Partitioning::RoundRobinBatch(1),
+            EmissionType::Final,
+            Boundedness::Bounded,
+        );
+        Self {
+            dataset,
+            query,
+            params,
+            prefilter_source,
+            properties,
+            metrics: ExecutionPlanMetricsSet::new(),
+        }
+    }
+}
+
+impl ExecutionPlan for MatchQueryExec {
+    fn name(&self) -> &str {
+        "MatchQueryExec"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        match &self.prefilter_source {
+            PreFilterSource::None => vec![],
+            PreFilterSource::FilteredRowIds(src) => vec![&src],
+            PreFilterSource::ScalarIndexQuery(src) => vec![&src],
+        }
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        // Prefilter inputs must be a single partition
+        self.children()
+            .iter()
+            .map(|_| Distribution::SinglePartition)
+            .collect()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        mut children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        let plan = match children.len() {
+            0 => {
+                if !matches!(self.prefilter_source, PreFilterSource::None) {
+                    return Err(DataFusionError::Internal(
+                        "Unexpected prefilter source".to_string(),
+                    ));
+                }
+
+                Self {
+                    dataset: self.dataset.clone(),
+                    query: self.query.clone(),
+                    params: self.params.clone(),
+                    prefilter_source: PreFilterSource::None,
+                    properties: self.properties.clone(),
+                    metrics: ExecutionPlanMetricsSet::new(),
+                }
+            }
+            1 => {
+                let src = children.pop().unwrap();
+                let prefilter_source = match &self.prefilter_source {
+                    PreFilterSource::FilteredRowIds(_) => {
+                        PreFilterSource::FilteredRowIds(src.clone())
+                    }
+                    PreFilterSource::ScalarIndexQuery(_) => {
+                        PreFilterSource::ScalarIndexQuery(src.clone())
+                    }
+                    PreFilterSource::None => {
+                        return Err(DataFusionError::Internal(
+                            "Unexpected prefilter source".to_string(),
+                        ));
+                    }
+                };
+
+                Self {
+                    dataset: self.dataset.clone(),
+                    query: self.query.clone(),
+                    params: self.params.clone(),
+                    prefilter_source,
+                    properties: self.properties.clone(),
+                    metrics: ExecutionPlanMetricsSet::new(),
+                }
+            }
+            _ => {
+                return Err(DataFusionError::Internal(
+                    "Unexpected number of children".to_string(),
+                ));
+            }
+        };
+        Ok(Arc::new(plan))
+    }
+
+    #[instrument(name = "match_query_exec", level = "debug", skip_all)]
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<datafusion::execution::TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let query = self.query.clone();
+        let params = self.params.clone();
+        let ds = self.dataset.clone();
+        let prefilter_source = self.prefilter_source.clone();
+        let metrics = Arc::new(IndexMetrics::new(&self.metrics, partition));
+        let column = query.column.ok_or(DataFusionError::Execution(format!(
+            "column not set for MatchQuery {}",
+            query.terms
+        )))?;
+
+        let stream = stream::once(async move {
+            let index_meta = ds
+                .load_scalar_index(
+                    ScalarIndexCriteria::default()
+                        .for_column(&column)
+                        .with_type(ScalarIndexType::Inverted),
+                )
+                .await?
+                .ok_or(DataFusionError::Execution(format!(
+                    "No Inverted index found for column {}",
+                    column,
+                )))?;
+            let uuid = index_meta.uuid.to_string();
+            let index = ds
+                .open_generic_index(&column, &uuid, metrics.as_ref())
+                .await?;
+
+            let pre_filter = build_prefilter(
+                context.clone(),
+                partition,
+                &prefilter_source,
+                ds,
+                &[index_meta],
+            )?;
+
+            let inverted_idx = index
+                .as_any()
+                .downcast_ref::<InvertedIndex>()
+                .ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "Index for column {} is not an inverted index",
+                        column,
+                    ))
+                })?;
+
+            let is_fuzzy = matches!(query.fuzziness, Some(n) if n != 0);
+            let params = params
+                .with_fuzziness(query.fuzziness)
+                .with_max_expansions(query.max_expansions)
+                .with_prefix_length(query.prefix_length);
+            let mut tokenizer = match is_fuzzy {
+                false => inverted_idx.query_tokenizer(),
+                true => tantivy::tokenizer::TextAnalyzer::from(
+                    tantivy::tokenizer::SimpleTokenizer::default(),
+                ),
+            };
+            // the synonyms share the position of the original token,
+            // so they are matched as alternatives for the `AND` operator
+            let tokens = collect_query_tokens(&query.terms, &mut tokenizer);
+
+            pre_filter.wait_for_ready().await?;
+            let (doc_ids, mut scores) = inverted_idx
+                .bm25_search(
+                    Arc::new(tokens),
+                    params.into(),
+                    query.operator,
+                    pre_filter,
+                    metrics,
+                )
+                .boxed()
+                .await?;
+            scores.iter_mut().for_each(|s| {
+                *s *= query.boost;
+            });
+
+            let batch = RecordBatch::try_new(
+                FTS_SCHEMA.clone(),
+                vec![
+                    Arc::new(UInt64Array::from(doc_ids)),
+                    Arc::new(Float32Array::from(scores)),
+                ],
+            )?;
+            Ok::<_, DataFusionError>(batch)
+        });
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream.stream_in_current_span().boxed(),
+        )))
+    }
+
+    fn statistics(&self) -> DataFusionResult<datafusion::physical_plan::Statistics> {
+        Ok(Statistics::new_unknown(&FTS_SCHEMA))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+}
+
+/// Calculates the FTS score for each row in the input
+#[derive(Debug)]
+pub struct FlatMatchQueryExec {
+    dataset: Arc<Dataset>,
+    query: MatchQuery,
+    params: FtsSearchParams,
+    unindexed_input: Arc<dyn ExecutionPlan>,
+
+    properties: PlanProperties,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl DisplayAs for FlatMatchQueryExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(f, "FlatMatchQuery: query={}", self.query.terms)
+            }
+            DisplayFormatType::TreeRender => {
+                write!(f, "FlatMatchQuery\nquery={}", self.query.terms)
+            }
+        }
+    }
+}
+
+impl FlatMatchQueryExec {
+    pub fn new(
+        dataset: Arc<Dataset>,
+        query: MatchQuery,
+        params: FtsSearchParams,
+        unindexed_input: Arc<dyn ExecutionPlan>,
+    ) -> Self {
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(FTS_SCHEMA.clone()),
+            Partitioning::RoundRobinBatch(1),
+            EmissionType::Incremental,
+            Boundedness::Bounded,
+        );
+        Self {
+            dataset,
+            query,
+            params,
+            unindexed_input,
+            properties,
+            metrics: ExecutionPlanMetricsSet::new(),
+        }
+    }
+}
+
+impl ExecutionPlan for FlatMatchQueryExec {
+    fn name(&self) -> &str {
+        "FlatMatchQueryExec"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.unindexed_input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        mut children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        if children.len() != 1 {
+            return Err(DataFusionError::Internal(
+                "Unexpected number of children".to_string(),
+            ));
+        }
+        let unindexed_input = children.pop().unwrap();
+        Ok(Arc::new(Self {
+            dataset: self.dataset.clone(),
+            query: self.query.clone(),
+            params: self.params.clone(),
+            unindexed_input,
+            properties: self.properties.clone(),
+            metrics: ExecutionPlanMetricsSet::new(),
+        }))
+    }
+
+    #[instrument(name = "flat_match_query_exec", level = "debug", skip_all)]
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<datafusion::execution::TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let query = self.query.clone();
+        let ds = self.dataset.clone();
+        let metrics = Arc::new(IndexMetrics::new(&self.metrics, partition));
+        let unindexed_input = self.unindexed_input.execute(partition, context)?;
+
+        let column = query.column.ok_or(DataFusionError::Execution(format!(
+            "column not set for MatchQuery {}",
+            query.terms
+        )))?;
+
+        let stream = stream::once(async move {
+            let index_meta = ds
+                .load_scalar_index(
+                    ScalarIndexCriteria::default()
+                        .for_column(&column)
+                        .with_type(ScalarIndexType::Inverted),
+                )
+                .await?
+                .ok_or(DataFusionError::Execution(format!(
+                    "No Inverted index found for column {}",
+                    column,
+                )))?;
+            let uuid = index_meta.uuid.to_string();
+            let index = ds
+                .open_generic_index(&column, &uuid, metrics.as_ref())
+                .await?;
+            let inverted_idx = index
+                .as_any()
+                .downcast_ref::<InvertedIndex>()
+                .ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "Index for column {} is not an inverted index",
+                        column,
+                    ))
+                })?;
+            Ok::<_, DataFusionError>(flat_bm25_search_stream(
+                unindexed_input,
+                column,
+                query.terms,
+                inverted_idx,
+            ))
+        })
+        .try_flatten_unordered(None);
+        Ok(Box::pin(InstrumentedRecordBatchStreamAdapter::new(
+            self.schema(),
+            stream.stream_in_current_span().boxed(),
+            partition,
+            &self.metrics,
+        )))
+    }
+
+    fn statistics(&self) -> DataFusionResult<datafusion::physical_plan::Statistics> {
+        Ok(Statistics::new_unknown(&FTS_SCHEMA))
+    }
 
-use super::utils::{build_prefilter, IndexMetrics, InstrumentedRecordBatchStreamAdapter};
-use super::PreFilterSource;
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+}
 
 #[derive(Debug)]
-pub struct MatchQueryExec {
+pub struct TermPatternQueryExec {
     dataset: Arc<Dataset>,
-    query: MatchQuery,
+    query: TermPatternQuery,
     params: FtsSearchParams,
     prefilter_source: PreFilterSource,
 
@@ -46,23 +442,23 @@ pub struct MatchQueryExec {
     metrics: ExecutionPlanMetricsSet,
 }
 
-impl DisplayAs for MatchQueryExec {
+impl DisplayAs for TermPatternQueryExec {
     fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match t {
             DisplayFormatType::Default | DisplayFormatType::Verbose => {
-                write!(f, "MatchQuery: query={}", self.query.terms)
+                write!(f, "TermPatternQuery: pattern={}", self.query.pattern)
             }
             DisplayFormatType::TreeRender => {
-                write!(f, "MatchQuery\nquery={}", self.query.terms)
+                write!(f, "TermPatternQuery\npattern={}", self.query.pattern)
             }
         }
     }
 }
 
-impl MatchQueryExec {
+impl TermPatternQueryExec {
     pub fn new(
         dataset: Arc<Dataset>,
-        query: MatchQuery,
+        query: TermPatternQuery,
         params: FtsSearchParams,
         prefilter_source: PreFilterSource,
     ) -> Self {
@@ -83,9 +479,9 @@ impl MatchQueryExec {
     }
 }
 
-impl ExecutionPlan for MatchQueryExec {
+impl ExecutionPlan for TermPatternQueryExec {
     fn name(&self) -> &str {
-        "MatchQueryExec"
+        "TermPatternQueryExec"
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -163,7 +559,7 @@ impl ExecutionPlan for MatchQueryExec {
         Ok(Arc::new(plan))
     }
 
-    #[instrument(name = "match_query_exec", level = "debug", skip_all)]
+    #[instrument(name = "term_pattern_query_exec", level = "debug", skip_all)]
     fn execute(
         &self,
         partition: usize,
@@ -175,8 +571,8 @@ impl ExecutionPlan for MatchQueryExec {
         let prefilter_source = self.prefilter_source.clone();
         let metrics = Arc::new(IndexMetrics::new(&self.metrics, partition));
         let column = query.column.ok_or(DataFusionError::Execution(format!(
-            "column not set for MatchQuery {}",
-            query.terms
+            "column not set for TermPatternQuery {}",
+            query.pattern
         )))?;
 
         let stream = stream::once(async move {
@@ -214,25 +610,16 @@ impl ExecutionPlan for MatchQueryExec {
                     ))
                 })?;
 
-            let is_fuzzy = matches!(query.fuzziness, Some(n) if n != 0);
-            let params = params
-                .with_fuzziness(query.fuzziness)
-                .with_max_expansions(query.max_expansions)
-                .with_prefix_length(query.prefix_length);
-            let mut tokenizer = match is_fuzzy {
-                false => inverted_idx.tokenizer(),
-                true => tantivy::tokenizer::TextAnalyzer::from(
-                    tantivy::tokenizer::SimpleTokenizer::default(),
-                ),
-            };
-            let tokens = collect_tokens(&query.terms, &mut tokenizer, None);
+            // the expanded tokens are searched as an `OR` match query without fuzziness
+            let tokens = inverted_idx.expand_pattern(&query.pattern, query.max_expansions)?;
+            let params = params.with_fuzziness(Some(0));
 
             pre_filter.wait_for_ready().await?;
             let (doc_ids, mut scores) = inverted_idx
                 .bm25_search(
-                    tokens.into(),
+                    Arc::new(tokens.into()),
                     params.into(),
-                    query.operator,
+                    Operator::Or,
                     pre_filter,
                     metrics,
                 )
@@ -271,11 +658,11 @@ impl ExecutionPlan for MatchQueryExec {
     }
 }
 
-/// Calculates the FTS score for each row in the input
+/// Calculates the FTS score of a term pattern query for each row in the input
 #[derive(Debug)]
-pub struct FlatMatchQueryExec {
+pub struct FlatTermPatternQueryExec {
     dataset: Arc<Dataset>,
-    query: MatchQuery,
+    query: TermPatternQuery,
     params: FtsSearchParams,
     unindexed_input: Arc<dyn ExecutionPlan>,
 
@@ -283,23 +670,23 @@ pub struct FlatMatchQueryExec {
     metrics: ExecutionPlanMetricsSet,
 }
 
-impl DisplayAs for FlatMatchQueryExec {
+impl DisplayAs for FlatTermPatternQueryExec {
     fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match t {
             DisplayFormatType::Default | DisplayFormatType::Verbose => {
-                write!(f, "FlatMatchQuery: query={}", self.query.terms)
+                write!(f, "FlatTermPatternQuery: pattern={}", self.query.pattern)
             }
             DisplayFormatType::TreeRender => {
-                write!(f, "FlatMatchQuery\nquery={}", self.query.terms)
+                write!(f, "FlatTermPatternQuery\npattern={}", self.query.pattern)
             }
         }
     }
 }
 
-impl FlatMatchQueryExec {
+impl FlatTermPatternQueryExec {
     pub fn new(
         dataset: Arc<Dataset>,
-        query: MatchQuery,
+        query: TermPatternQuery,
         params: FtsSearchParams,
         unindexed_input: Arc<dyn ExecutionPlan>,
     ) -> Self {
@@ -320,9 +707,9 @@ impl FlatMatchQueryExec {
     }
 }
 
-impl ExecutionPlan for FlatMatchQueryExec {
+impl ExecutionPlan for FlatTermPatternQueryExec {
     fn name(&self) -> &str {
-        "FlatMatchQueryExec"
+        "FlatTermPatternQueryExec"
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -353,7 +740,7 @@ impl ExecutionPlan for FlatMatchQueryExec {
         }))
     }
 
-    #[instrument(name = "flat_match_query_exec", level = "debug", skip_all)]
+    #[instrument(name = "flat_term_pattern_query_exec", level = "debug", skip_all)]
     fn execute(
         &self,
         partition: usize,
@@ -365,8 +752,8 @@ impl ExecutionPlan for FlatMatchQueryExec {
         let unindexed_input = self.unindexed_input.execute(partition, context)?;
 
         let column = query.column.ok_or(DataFusionError::Execution(format!(
-            "column not set for MatchQuery {}",
-            query.terms
+            "column not set for TermPatternQuery {}",
+            query.pattern
         )))?;
 
         let stream = stream::once(async move {
@@ -394,12 +781,13 @@ impl ExecutionPlan for FlatMatchQueryExec {
                         column,
                     ))
                 })?;
-            Ok::<_, DataFusionError>(flat_bm25_search_stream(
+            Ok::<_, DataFusionError>(flat_pattern_search_stream(
                 unindexed_input,
                 column,
-                query.terms,
+                query.pattern,
+                query.max_expansions,
                 inverted_idx,
-            ))
+            )?)
         })
         .try_flatten_unordered(None);
         Ok(Box::pin(InstrumentedRecordBatchStreamAdapter::new(
@@ -593,13 +981,13 @@ impl ExecutionPlan for PhraseQueryExec {
                     ))
                 })?;
 
-            let mut tokenizer = index.tokenizer();
-            let tokens = collect_tokens(&query.terms, &mut tokenizer, None);
+            let mut tokenizer = index.query_tokenizer();
+            let tokens = collect_phrase_tokens(&query.terms, &mut tokenizer);
 
             pre_filter.wait_for_ready().await?;
-            let (doc_ids, scores) = index
+            let (doc_ids, mut scores) = index
                 .bm25_search(
-                    tokens.into(),
+                    Arc::new(tokens.into()),
                     params.into(),
                     lance_index::scalar::inverted::query::Operator::And,
                     pre_filter,
@@ -607,6 +995,9 @@ impl ExecutionPlan for PhraseQueryExec {
                 )
                 .boxed()
                 .await?;
+            scores.iter_mut().for_each(|s| {
+                *s *= query.boost;
+            });
             let batch = RecordBatch::try_new(
                 FTS_SCHEMA.clone(),
                 vec![
@@ -1027,6 +1418,312 @@ impl ExecutionPlan for BooleanQueryExec {
     }
 }
 
+/// Appends the highlights and snippet columns of the full text search query to the input
+#[derive(Debug)]
+pub struct FtsHighlightExec {
+    dataset: Arc<Dataset>,
+    query: FtsQuery,
+    column: String,
+    params: HighlightParams,
+    input: Arc<dyn ExecutionPlan>,
+
+    properties: PlanProperties,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl DisplayAs for FtsHighlightExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(f, "FtsHighlight: column={}", self.column)
+            }
+            DisplayFormatType::TreeRender => {
+                write!(f, "FtsHighlight\ncolumn={}", self.column)
+            }
+        }
+    }
+}
+
+impl FtsHighlightExec {
+    pub fn try_new(
+        dataset: Arc<Dataset>,
+        query: FtsQuery,
+        column: String,
+        params: HighlightParams,
+        input: Arc<dyn ExecutionPlan>,
+    ) -> DataFusionResult<Self> {
+        let schema = input
+            .schema()
+            .try_with_column(HIGHLIGHTS_FIELD.clone())?
+            .try_with_column(SNIPPET_FIELD.clone())?;
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(Arc::new(schema)),
+            input.properties().partitioning.clone(),
+            EmissionType::Incremental,
+            Boundedness::Bounded,
+        );
+        Ok(Self {
+            dataset,
+            query,
+            column,
+            params,
+            input,
+            properties,
+            metrics: ExecutionPlanMetricsSet::new(),
+        })
+    }
+}
+
+impl ExecutionPlan for FtsHighlightExec {
+    fn name(&self) -> &str {
+        "FtsHighlightExec"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        mut children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        if children.len() != 1 {
+            return Err(DataFusionError::Internal(
+                "Unexpected number of children".to_string(),
+            ));
+        }
+        Ok(Arc::new(Self::try_new(
+            self.dataset.clone(),
+            self.query.clone(),
+            self.column.clone(),
+            self.params.clone(),
+            children.pop().unwrap(),
+        )?))
+    }
+
+    #[instrument(name = "fts_highlight_exec", level = "debug", skip_all)]
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<datafusion::execution::TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let query = self.query.clone();
+        let column = self.column.clone();
+        let params = self.params.clone();
+        let ds = self.dataset.clone();
+        let metrics = Arc::new(IndexMetrics::new(&self.metrics, partition));
+        let input = self.input.execute(partition, context)?;
+
+        let stream = stream::once(async move {
+            let index_meta = ds
+                .load_scalar_index(
+                    ScalarIndexCriteria::default()
+                        .for_column(&column)
+                        .with_type(ScalarIndexType::Inverted),
+                )
+                .await?
+                .ok_or(DataFusionError::Execution(format!(
+                    "No Inverted index found for column {}",
+                    column,
+                )))?;
+            let uuid = index_meta.uuid.to_string();
+            let index = ds
+                .open_generic_index(&column, &uuid, metrics.as_ref())
+                .await?;
+            let inverted_idx = index
+                .as_any()
+                .downcast_ref::<InvertedIndex>()
+                .ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "Index for column {} is not an inverted index",
+                        column,
+                    ))
+                })?;
+            // tokenize the documents in the same way as the index,
+            // so that the highlights are consistent with the search results
+            let mut highlighter =
+                Highlighter::try_new(&query, column, inverted_idx.params(), params)?;
+            Ok::<_, DataFusionError>(input.map(move |batch| {
+                let batch = batch?;
+                Ok::<_, DataFusionError>(highlighter.highlight_batch(batch)?)
+            }))
+        })
+        .try_flatten();
+        Ok(Box::pin(InstrumentedRecordBatchStreamAdapter::new(
+            self.schema(),
+            stream.stream_in_current_span().boxed(),
+            partition,
+            &self.metrics,
+        )))
+    }
+
+    fn statistics(&self) -> DataFusionResult<datafusion::physical_plan::Statistics> {
+        Ok(Statistics::new_unknown(&self.schema()))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+}
+
+/// Appends the explanation of the score of each row for the full text search query to the input
+#[derive(Debug)]
+pub struct FtsExplainExec {
+    dataset: Arc<Dataset>,
+    query: FtsQuery,
+    input: Arc<dyn ExecutionPlan>,
+
+    properties: PlanProperties,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl DisplayAs for FtsExplainExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let columns = self.query.columns().into_iter().sorted().join(",");
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(f, "FtsExplain: columns={}", columns)
+            }
+            DisplayFormatType::TreeRender => {
+                write!(f, "FtsExplain\ncolumns={}", columns)
+            }
+        }
+    }
+}
+
+impl FtsExplainExec {
+    pub fn try_new(
+        dataset: Arc<Dataset>,
+        query: FtsQuery,
+        input: Arc<dyn ExecutionPlan>,
+    ) -> DataFusionResult<Self> {
+        let schema = input.schema().try_with_column(EXPLAIN_FIELD.clone())?;
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(Arc::new(schema)),
+            input.properties().partitioning.clone(),
+            EmissionType::Incremental,
+            Boundedness::Bounded,
+        );
+        Ok(Self {
+            dataset,
+            query,
+            input,
+            properties,
+            metrics: ExecutionPlanMetricsSet::new(),
+        })
+    }
+}
+
+impl ExecutionPlan for FtsExplainExec {
+    fn name(&self) -> &str {
+        "FtsExplainExec"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        mut children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        if children.len() != 1 {
+            return Err(DataFusionError::Internal(
+                "Unexpected number of children".to_string(),
+            ));
+        }
+        Ok(Arc::new(Self::try_new(
+            self.dataset.clone(),
+            self.query.clone(),
+            children.pop().unwrap(),
+        )?))
+    }
+
+    #[instrument(name = "fts_explain_exec", level = "debug", skip_all)]
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<datafusion::execution::TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let query = self.query.clone();
+        let ds = self.dataset.clone();
+        let metrics = Arc::new(IndexMetrics::new(&self.metrics, partition));
+        let input = self.input.execute(partition, context)?;
+
+        let stream = stream::once(async move {
+            let mut indices = Vec::new();
+            for column in query.columns() {
+                let index_meta = ds
+                    .load_scalar_index(
+                        ScalarIndexCriteria::default()
+                            .for_column(&column)
+                            .with_type(ScalarIndexType::Inverted),
+                    )
+                    .await?
+                    .ok_or(DataFusionError::Execution(format!(
+                        "No Inverted index found for column {}",
+                        column,
+                    )))?;
+                let uuid = index_meta.uuid.to_string();
+                let index = ds
+                    .open_generic_index(&column, &uuid, metrics.as_ref())
+                    .await?;
+                if index.as_any().downcast_ref::<InvertedIndex>().is_none() {
+                    return Err(DataFusionError::Execution(format!(
+                        "Index for column {} is not an inverted index",
+                        column,
+                    )));
+                }
+                indices.push((column, index));
+            }
+            Ok::<_, DataFusionError>(input.map(move |batch| {
+                let batch = batch?;
+                // the statistics of BM25 are collected from the same indices as the search,
+                // so that the explanations are consistent with the scores
+                let mut explainer = ScoreExplainer::new(indices.iter().map(|(column, index)| {
+                    let index = index.as_any().downcast_ref::<InvertedIndex>().unwrap();
+                    (column.clone(), index)
+                }));
+                let explanations = explainer.explain_batch(&query, &batch)?;
+                Ok::<_, DataFusionError>(
+                    batch.try_with_column(EXPLAIN_FIELD.clone(), explanations)?,
+                )
+            }))
+        })
+        .try_flatten();
+        Ok(Box::pin(InstrumentedRecordBatchStreamAdapter::new(
+            self.schema(),
+            stream.stream_in_current_span().boxed(),
+            partition,
+            &self.metrics,
+        )))
+    }
+
+    fn statistics(&self) -> DataFusionResult<datafusion::physical_plan::Statistics> {
+        Ok(Statistics::new_unknown(&self.schema()))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use std::sync::Arc;
@@ -14,6 +14,7 @@ use arrow_array::{
 };
 use arrow_array::{Array, Float32Array, UInt32Array, UInt64Array};
 use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use arrow_select::concat::concat_batches;
 use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
 use datafusion::physical_plan::PlanProperties;
 use datafusion::physical_plan::{
@@ -484,7 +485,10 @@ impl ExecutionPlan for ANNIvfPartitionExec {
 
                     let _timer = metrics.baseline_metrics.elapsed_compute().timer();
                     let mut query = query.clone();
-                    if index.metric_type() == DistanceType::Cosine {
+                    // the index normalizes the int8 query vectors itself
+                    if index.metric_type() == DistanceType::Cosine
+                        && query.key.data_type() != &DataType::Int8
+                    {
                         let key = normalize_arrow(&query.key)?;
                         query.key = key;
                     };
@@ -767,7 +771,10 @@ impl ANNIvfSubIndexExec {
                     async move {
                         let _timer = metrics.baseline_metrics.elapsed_compute().timer();
                         let mut query = query.clone();
-                        if index.metric_type() == DistanceType::Cosine {
+                        // the index normalizes the int8 query vectors itself
+                        if index.metric_type() == DistanceType::Cosine
+                            && query.key.data_type() != &DataType::Int8
+                        {
                             let key = normalize_arrow(&query.key)?;
                             query.key = key;
                         };
@@ -826,7 +833,10 @@ impl ANNIvfSubIndexExec {
                 async move {
                     let _timer = metrics.baseline_metrics.elapsed_compute().timer();
                     let mut query = query.clone();
-                    if index.metric_type() == DistanceType::Cosine {
+                    // the index normalizes the int8 query vectors itself
+                    if index.metric_type() == DistanceType::Cosine
+                        && query.key.data_type() != &DataType::Int8
+                    {
                         let key = normalize_arrow(&query.key)?;
                         query.key = key;
                     };
@@ -1135,8 +1145,11 @@ impl ExecutionPlan for MultivectorScoringExec {
         // and max-reduce for each query,
         // records the minimum distance for each query as estimation.
         let mut reduced_inputs = stream::select_all(inputs.into_iter().map(|stream| {
-            stream.map(|batch| {
-                let batch = batch?;
+            stream::once(async move {
+                // the sorted results of a query may come in several batches
+                let schema = stream.schema();
+                let batches = stream.try_collect::<Vec<_>>().await?;
+                let batch = concat_batches(&schema, &batches)?;
                 let row_ids = batch[ROW_ID].as_primitive::<UInt64Type>();
                 let dists = batch[DIST_COL].as_primitive::<Float32Type>();
                 debug_assert_eq!(dists.null_count(), 0);
@@ -1171,6 +1184,7 @@ impl ExecutionPlan for MultivectorScoringExec {
 
                 Ok::<_, DataFusionError>((min_sim, batch))
             })
+            .boxed()
         }));
 
         let k = self.query.k;
@@ -0,0 +1,247 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use arrow::compute::{cast, filter_record_batch};
+use arrow_array::{cast::AsArray, new_null_array, BooleanArray, RecordBatch};
+use arrow_row::OwnedRow;
+use arrow_schema::SchemaRef;
+use datafusion::common::Statistics;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::SendableRecordBatchStream;
+use datafusion::logical_expr::Expr;
+use datafusion::physical_plan::execution_plan::{Boundedness, EmissionType};
+use datafusion::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricsSet};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{DisplayAs, DisplayFormatType, ExecutionPlan, PlanProperties};
+use datafusion_physical_expr::{EquivalenceProperties, PhysicalExpr};
+use futures::{stream, StreamExt, TryStreamExt};
+use lance_datafusion::planner::Planner;
+use snafu::location;
+
+use crate::dataset::mem_wal::{MemTable, MemTableSnapshot};
+use crate::{Error, Result};
+
+/// Merges the unflushed rows of the MemTables into the rows read from the dataset.
+///
+/// The dataset rows whose primary keys are in any MemTable are dropped,
+/// then the live rows of the MemTables that pass the filter are appended,
+/// a newer MemTable overrides the rows of the older ones with the same keys.
+/// The columns missing in the MemTables, like `_rowid`, are null for the appended rows.
+#[derive(Debug)]
+pub struct MemTableMergeExec {
+    input: Arc<dyn ExecutionPlan>,
+    mem_tables: Vec<Arc<MemTable>>,
+    filter: Option<Expr>,
+
+    properties: PlanProperties,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl DisplayAs for MemTableMergeExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let num_rows = self
+            .mem_tables
+            .iter()
+            .map(|mem_table| mem_table.num_rows())
+            .sum::<usize>();
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(
+                    f,
+                    "MemTableMerge: mem_tables={}, rows={}",
+                    self.mem_tables.len(),
+                    num_rows
+                )
+            }
+            DisplayFormatType::TreeRender => {
+                write!(
+                    f,
+                    "MemTableMerge\nmem_tables={}\nrows={}",
+                    self.mem_tables.len(),
+                    num_rows
+                )
+            }
+        }
+    }
+}
+
+impl MemTableMergeExec {
+    /// The input must contain the primary key columns of the MemTables
+    pub fn try_new(
+        input: Arc<dyn ExecutionPlan>,
+        mem_tables: Vec<Arc<MemTable>>,
+        filter: Option<Expr>,
+    ) -> Result<Self> {
+        let schema = input.schema();
+        for mem_table in &mem_tables {
+            for column in mem_table.primary_key() {
+                if schema.column_with_name(column).is_none() {
+                    return Err(Error::invalid_input(
+                        format!("primary key column {} is not in the input", column),
+                        location!(),
+                    ));
+                }
+            }
+        }
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(input.schema()),
+            input.properties().partitioning.clone(),
+            EmissionType::Incremental,
+            Boundedness::Bounded,
+        );
+        Ok(Self {
+            input,
+            mem_tables,
+            filter,
+            properties,
+            metrics: ExecutionPlanMetricsSet::new(),
+        })
+    }
+
+    // the live rows of the MemTables that are not overridden by newer MemTables,
+    // and the keys of all the MemTables
+    fn merged_snapshot(&self) -> Result<(Vec<RecordBatch>, HashSet<OwnedRow>)> {
+        let mut batches = Vec::new();
+        let mut keys = HashSet::new();
+        for mem_table in self.mem_tables.iter().rev() {
+            let MemTableSnapshot {
+                batches: mem_table_batches,
+                keys: mem_table_keys,
+            } = mem_table.snapshot()?;
+            let filter = match &self.filter {
+                Some(filter) => {
+                    Some(Planner::new(mem_table.schema().clone()).create_physical_expr(filter)?)
+                }
+                None => None,
+            };
+            for batch in mem_table_batches {
+                let batch = if keys.is_empty() {
+                    batch
+                } else {
+                    let batch_keys = mem_table.keys(&batch)?;
+                    let mask = batch_keys
+                        .iter()
+                        .map(|key| !keys.contains(&key.owned()))
+                        .collect::<BooleanArray>();
+                    filter_record_batch(&batch, &mask)?
+                };
+                let batch = match &filter {
+                    Some(filter) => apply_filter(filter.as_ref(), &batch)?,
+                    None => batch,
+                };
+                if batch.num_rows() > 0 {
+                    batches.push(batch);
+                }
+            }
+            keys.extend(mem_table_keys);
+        }
+        batches.reverse();
+        Ok((batches, keys))
+    }
+}
+
+fn apply_filter(filter: &dyn PhysicalExpr, batch: &RecordBatch) -> Result<RecordBatch> {
+    let mask = filter.evaluate(batch)?.into_array(batch.num_rows())?;
+    Ok(filter_record_batch(batch, mask.as_boolean())?)
+}
+
+// project the MemTable rows to the output schema, the missing columns are null
+fn project_batch(batch: &RecordBatch, schema: &SchemaRef) -> Result<RecordBatch> {
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|field| match batch.column_by_name(field.name()) {
+            Some(column) => Ok(cast(column, field.data_type())?),
+            None => Ok(new_null_array(field.data_type(), batch.num_rows())),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+impl ExecutionPlan for MemTableMergeExec {
+    fn name(&self) -> &str {
+        "MemTableMergeExec"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        mut children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        if children.len() != 1 {
+            return Err(DataFusionError::Internal(
+                "Unexpected number of children".to_string(),
+            ));
+        }
+        Ok(Arc::new(Self::try_new(
+            children.pop().unwrap(),
+            self.mem_tables.clone(),
+            self.filter.clone(),
+        )?))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<datafusion::execution::TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let schema = self.schema();
+        let (mem_table_batches, keys) = self.merged_snapshot()?;
+        let keys = Arc::new(keys);
+        // every partition drops the overridden rows,
+        // but the MemTable rows are only emitted by the first one
+        let mem_table_batches = match partition {
+            0 => mem_table_batches
+                .iter()
+                .map(|batch| project_batch(batch, &schema))
+                .collect::<Result<Vec<_>>>()?,
+            _ => vec![],
+        };
+
+        let Some(mem_table) = self.mem_tables.first().cloned() else {
+            return self.input.execute(partition, context);
+        };
+        let input = self.input.execute(partition, context)?;
+        let dataset_rows = input.map(move |batch| -> DataFusionResult<RecordBatch> {
+            let batch = batch?;
+            if keys.is_empty() {
+                return Ok(batch);
+            }
+            let batch_keys = mem_table.keys(&batch)?;
+            let mask = batch_keys
+                .iter()
+                .map(|key| !keys.contains(&key.owned()))
+                .collect::<BooleanArray>();
+            Ok(filter_record_batch(&batch, &mask)?)
+        });
+        let stream = dataset_rows
+            .chain(stream::iter(mem_table_batches.into_iter().map(Ok)))
+            .try_filter(|batch| futures::future::ready(batch.num_rows() > 0));
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            schema,
+            stream.boxed(),
+        )))
+    }
+
+    fn statistics(&self) -> DataFusionResult<Statistics> {
+        Ok(Statistics::new_unknown(&self.schema()))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+}
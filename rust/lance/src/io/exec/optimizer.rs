@@ -5,18 +5,33 @@
 
 use std::sync::Arc;
 
-use super::TakeExec;
-use arrow_schema::Schema as ArrowSchema;
+use super::aggregate::{MetadataAggregate, MetadataAggregateExec};
+use super::filtered_read::FilteredReadExec;
+use super::scalar_index::ScalarIndexExec;
+use super::{LanceScanExec, TakeExec};
+use crate::Dataset;
+use arrow_schema::{DataType, Schema as ArrowSchema};
 use datafusion::{
-    common::tree_node::{Transformed, TreeNode},
+    common::tree_node::{Transformed, TreeNode, TreeNodeRecursion},
     config::ConfigOptions,
     error::Result as DFResult,
     physical_optimizer::{optimizer::PhysicalOptimizer, PhysicalOptimizerRule},
     physical_plan::{
-        coalesce_batches::CoalesceBatchesExec, projection::ProjectionExec, ExecutionPlan,
+        aggregates::{AggregateExec, AggregateMode},
+        coalesce_batches::CoalesceBatchesExec,
+        coalesce_partitions::CoalescePartitionsExec,
+        projection::ProjectionExec,
+        repartition::RepartitionExec,
+        ExecutionPlan,
     },
 };
-use datafusion_physical_expr::{expressions::Column, PhysicalExpr};
+use datafusion_physical_expr::{
+    aggregate::AggregateFunctionExpr,
+    expressions::{Column, Literal},
+    PhysicalExpr,
+};
+use lance_index::scalar::expression::ScalarIndexExpr;
+use lance_table::format::Fragment;
 
 /// Rule that eliminates [TakeExec] nodes that are immediately followed by another [TakeExec].
 #[derive(Debug)]
@@ -168,6 +183,223 @@ impl PhysicalOptimizerRule for SimplifyProjection {
     }
 }
 
+/// Rule that answers COUNT/MIN/MAX aggregates without a GROUP BY from the dataset
+/// metadata and indices instead of scanning the data.
+///
+/// The aggregate must read straight from a Lance scan (only projections of columns in
+/// between) and the scan must have no limit/offset.  COUNT(*) and COUNT of non-nullable
+/// columns are answered from the fragment row counts, or from the scalar index when the
+/// whole filter is an indexed query.  MIN/MAX of a column are read from the bounds of a
+/// BTree index on that column, only when there is no filter.  Lance files don't store
+/// column statistics so MIN/MAX of unindexed columns are never pushed down.
+///
+/// The aggregate is replaced by a [MetadataAggregateExec] that keeps the original plan
+/// to fall back on if the metadata can't answer it when it runs (e.g. the index doesn't
+/// cover all the fragments or rows were deleted).
+#[derive(Debug)]
+pub struct AggregatePushdown;
+
+/// The source of an aggregate that can be answered from metadata
+struct MetadataSource {
+    dataset: Arc<Dataset>,
+    fragments: Option<Arc<Vec<Fragment>>>,
+    index_query: Option<ScalarIndexExpr>,
+    schema: Arc<ArrowSchema>,
+}
+
+impl AggregatePushdown {
+    // The aggregate that computes the values from the data, the input of the final
+    // aggregate for two phase aggregations
+    fn data_aggregate(aggregate: &AggregateExec) -> Option<&AggregateExec> {
+        match aggregate.mode() {
+            AggregateMode::Single | AggregateMode::SinglePartitioned => Some(aggregate),
+            AggregateMode::Final | AggregateMode::FinalPartitioned => {
+                let mut input = aggregate.input();
+                while input.as_any().is::<CoalescePartitionsExec>()
+                    || input.as_any().is::<RepartitionExec>()
+                    || input.as_any().is::<CoalesceBatchesExec>()
+                {
+                    input = input.children()[0];
+                }
+                let partial = input.as_any().downcast_ref::<AggregateExec>()?;
+                (*partial.mode() == AggregateMode::Partial).then_some(partial)
+            }
+            AggregateMode::Partial => None,
+        }
+    }
+
+    // Follows the column at `index` in the output of `plan` down to the scan.  The
+    // indices are None for aggregates that don't read a column (e.g. COUNT(*))
+    fn find_source(
+        mut plan: &Arc<dyn ExecutionPlan>,
+        mut indices: Vec<Option<usize>>,
+    ) -> Option<(MetadataSource, Vec<Option<usize>>)> {
+        loop {
+            if let Some(projection) = plan.as_any().downcast_ref::<ProjectionExec>() {
+                for index in indices.iter_mut().flatten() {
+                    let (expr, _) = &projection.expr()[*index];
+                    *index = expr.as_any().downcast_ref::<Column>()?.index();
+                }
+            } else if !plan.as_any().is::<CoalesceBatchesExec>()
+                && !plan.as_any().is::<CoalescePartitionsExec>()
+                && !plan.as_any().is::<RepartitionExec>()
+            {
+                break;
+            }
+            plan = plan.children()[0];
+        }
+
+        if let Some(read) = plan.as_any().downcast_ref::<FilteredReadExec>() {
+            let options = read.options();
+            if options.scan_range_before_filter.is_some()
+                || options.scan_range_after_filter.is_some()
+                || options.with_deleted_rows
+                || options.refine_filter.is_some()
+            {
+                return None;
+            }
+            let index_query = match (&options.full_filter, read.index_input()) {
+                (None, _) => None,
+                (Some(_), Some(index_input)) => Some(
+                    index_input
+                        .as_any()
+                        .downcast_ref::<ScalarIndexExec>()?
+                        .expr()
+                        .clone(),
+                ),
+                (Some(_), None) => return None,
+            };
+            let source = MetadataSource {
+                dataset: read.dataset().clone(),
+                fragments: options.fragments.clone(),
+                index_query,
+                schema: plan.schema(),
+            };
+            Some((source, indices))
+        } else if let Some(scan) = plan.as_any().downcast_ref::<LanceScanExec>() {
+            if scan.range().is_some() || scan.config().with_make_deletions_null {
+                return None;
+            }
+            let source = MetadataSource {
+                dataset: scan.dataset().clone(),
+                fragments: Some(scan.fragments().clone()),
+                index_query: None,
+                schema: plan.schema(),
+            };
+            Some((source, indices))
+        } else {
+            None
+        }
+    }
+
+    // The column read by an aggregate, Some(None) if it doesn't read a column
+    fn aggregate_column(
+        aggregate: &AggregateFunctionExpr,
+        input_schema: &ArrowSchema,
+    ) -> Option<Option<usize>> {
+        if aggregate.is_distinct() {
+            return None;
+        }
+        let [expr] = aggregate.expressions().try_into().ok()?;
+        let name = aggregate.fun().name();
+        if name == "count" && expr.as_any().is::<Literal>() {
+            return Some(None);
+        }
+        let column = expr.as_any().downcast_ref::<Column>()?;
+        let field = input_schema.field(column.index());
+        match name {
+            // only COUNT(*) for nullable columns, the null count is not known
+            "count" if !field.is_nullable() => Some(None),
+            // the index orders NaN differently
+            "min" | "max"
+                if !matches!(
+                    field.data_type(),
+                    DataType::Float16 | DataType::Float32 | DataType::Float64
+                ) =>
+            {
+                Some(Some(column.index()))
+            }
+            _ => None,
+        }
+    }
+
+    fn try_pushdown(plan: &Arc<dyn ExecutionPlan>) -> Option<Arc<dyn ExecutionPlan>> {
+        let aggregate = plan.as_any().downcast_ref::<AggregateExec>()?;
+        if !aggregate.group_expr().is_empty()
+            || aggregate.aggr_expr().is_empty()
+            || aggregate
+                .filter_expr()
+                .iter()
+                .any(|filter| filter.is_some())
+        {
+            return None;
+        }
+        let data_aggregate = Self::data_aggregate(aggregate)?;
+        let input_schema = data_aggregate.input().schema();
+        let columns = data_aggregate
+            .aggr_expr()
+            .iter()
+            .map(|aggregate| Self::aggregate_column(aggregate, &input_schema))
+            .collect::<Option<Vec<_>>>()?;
+        let (source, columns) = Self::find_source(data_aggregate.input(), columns)?;
+
+        let aggregates = data_aggregate
+            .aggr_expr()
+            .iter()
+            .zip(columns)
+            .map(|(aggregate, column)| {
+                let Some(column) = column else {
+                    return Some(MetadataAggregate::Count);
+                };
+                let name = source.schema.field(column).name().clone();
+                // the index bounds include the rows that don't match the filter
+                if source.index_query.is_some() {
+                    return None;
+                }
+                match aggregate.fun().name() {
+                    "min" => Some(MetadataAggregate::Min(name)),
+                    "max" => Some(MetadataAggregate::Max(name)),
+                    _ => None,
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Arc::new(MetadataAggregateExec::new(
+            source.dataset,
+            aggregates,
+            source.fragments,
+            source.index_query,
+            plan.clone(),
+        )))
+    }
+}
+
+impl PhysicalOptimizerRule for AggregatePushdown {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        _config: &ConfigOptions,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        Ok(plan
+            .transform_down(|plan| {
+                if let Some(pushed_down) = Self::try_pushdown(&plan) {
+                    // the original aggregate is kept as the fallback, don't visit it again
+                    return Ok(Transformed::new(pushed_down, true, TreeNodeRecursion::Jump));
+                }
+                Ok(Transformed::no(plan))
+            })?
+            .data)
+    }
+
+    fn name(&self) -> &str {
+        "aggregate_pushdown"
+    }
+
+    fn schema_check(&self) -> bool {
+        true
+    }
+}
+
 pub fn get_physical_optimizer() -> PhysicalOptimizer {
     PhysicalOptimizer::with_rules(vec![
         Arc::new(crate::io::exec::optimizer::CoalesceTake),
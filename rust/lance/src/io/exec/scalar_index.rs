@@ -40,7 +40,7 @@ use lance_datafusion::{
     },
 };
 use lance_index::{
-    metrics::MetricsCollector,
+    metrics::{MetricsCollector, NoOpMetricsCollector},
     scalar::{
         expression::{IndexExprResult, ScalarIndexExpr, ScalarIndexLoader, ScalarIndexSearch},
         SargableQuery, ScalarIndex,
@@ -129,8 +129,12 @@ impl ScalarIndexExec {
         }
     }
 
+    pub fn expr(&self) -> &ScalarIndexExpr {
+        &self.expr
+    }
+
     #[async_recursion]
-    async fn fragments_covered_by_index_query(
+    pub(crate) async fn fragments_covered_by_index_query(
         index_expr: &ScalarIndexExpr,
         dataset: &Dataset,
     ) -> Result<RoaringBitmap> {
@@ -614,6 +618,36 @@ impl MaterializeIndexExec {
     }
 }
 
+/// Counts the rows of the fragments that match the index query, without reading any data
+///
+/// Returns `None` if the index result is not exact or the index does not cover all the fragments.
+pub(crate) async fn count_index_query_rows(
+    dataset: Arc<Dataset>,
+    expr: &ScalarIndexExpr,
+    fragments: Arc<Vec<Fragment>>,
+) -> Result<Option<u64>> {
+    let covered = ScalarIndexExec::fragments_covered_by_index_query(expr, dataset.as_ref()).await?;
+    if fragments
+        .iter()
+        .any(|fragment| !covered.contains(fragment.id as u32))
+    {
+        return Ok(None);
+    }
+    let IndexExprResult::Exact(mask) = expr
+        .evaluate(dataset.as_ref(), &NoOpMetricsCollector)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let fragment_bitmap = RoaringBitmap::from_iter(fragments.iter().map(|frag| frag.id as u32));
+    let mask = match DatasetPreFilter::create_deletion_mask(dataset.clone(), fragment_bitmap) {
+        Some(deletion_mask) => mask & (*deletion_mask.await?).clone(),
+        None => mask,
+    };
+    let row_ids = row_ids_for_mask(mask, &dataset, &fragments).await?;
+    Ok(Some(row_ids.len() as u64))
+}
+
 #[instrument(name = "make_row_ids", skip(mask, dataset, fragments))]
 async fn row_ids_for_mask(
     mask: RowIdMask,
@@ -7,9 +7,11 @@ use std::sync::Arc;
 use deepsize::DeepSizeOf;
 use lance_core::cache::LanceCache;
 use lance_core::{Error, Result};
+use lance_index::scalar::inverted::TokenizerRegistry;
 use lance_index::IndexType;
 use lance_io::object_store::ObjectStoreRegistry;
 use snafu::location;
+use tantivy::tokenizer::Tokenizer;
 
 use crate::dataset::{DEFAULT_INDEX_CACHE_SIZE, DEFAULT_METADATA_CACHE_SIZE};
 use crate::session::caches::GlobalMetadataCache;
@@ -41,6 +43,10 @@ pub struct Session {
 
     pub(crate) index_extensions: HashMap<(IndexType, String), Arc<dyn IndexExtension>>,
 
+    /// Custom tokenizers for the full text search indices,
+    /// they are resolved by name when building and loading the indices.
+    tokenizer_registry: Arc<TokenizerRegistry>,
+
     store_registry: Arc<ObjectStoreRegistry>,
 }
 
@@ -75,6 +81,7 @@ impl std::fmt::Debug for Session {
                 "index_extensions",
                 &self.index_extensions.keys().collect::<Vec<_>>(),
             )
+            .field("tokenizer_registry", &self.tokenizer_registry)
             .finish()
     }
 }
@@ -98,6 +105,7 @@ impl Session {
             index_cache: GlobalIndexCache(LanceCache::with_capacity(index_cache_size)),
             metadata_cache: GlobalMetadataCache(LanceCache::with_capacity(metadata_cache_size)),
             index_extensions: HashMap::new(),
+            tokenizer_registry: Arc::new(TokenizerRegistry::default()),
             store_registry,
         }
     }
@@ -151,6 +159,24 @@ impl Session {
         Ok(())
     }
 
+    /// Register a custom tokenizer for the full text search indices.
+    ///
+    /// The tokenizer can be used as the `base_tokenizer` of `InvertedIndexParams` by its name.
+    /// Only the name is stored in the index, so the tokenizer must be registered in
+    /// the sessions that build, update or query the index.
+    pub fn register_tokenizer<T: Tokenizer>(
+        &mut self,
+        name: impl Into<String>,
+        tokenizer: T,
+    ) -> Result<()> {
+        Arc::make_mut(&mut self.tokenizer_registry).register(name, tokenizer)
+    }
+
+    /// Get the registry of the custom tokenizers.
+    pub fn tokenizer_registry(&self) -> Arc<TokenizerRegistry> {
+        self.tokenizer_registry.clone()
+    }
+
     /// Return the current size of the session in bytes
     pub fn size_bytes(&self) -> u64 {
         // We re-expose deep_size_of here so that users don't
@@ -186,6 +212,7 @@ impl Default for Session {
                 DEFAULT_METADATA_CACHE_SIZE,
             )),
             index_extensions: HashMap::new(),
+            tokenizer_registry: Arc::new(TokenizerRegistry::default()),
             store_registry: Arc::new(ObjectStoreRegistry::default()),
         }
     }